@@ -0,0 +1,45 @@
+// Fills in the SHA-256 of every download listed in the bundled model manifest.
+//
+// Downloads each file once, so it takes a while and a few GB of traffic. Run it
+// with `bun run models:hashes` after adding or changing a model URL, then commit
+// the updated manifest. Hashes already filled in are checked again and reported
+// when they changed.
+
+const MANIFEST = new URL(
+  "../src-tauri/resources/model_manifest.json",
+  import.meta.url,
+);
+
+// A URL, then the sha256 of the same model or variant entry
+const ENTRY = /("url": "([^"]+)",[\s\S]*?"sha256": )(null|"[0-9a-f]*")/g;
+
+async function sha256(url: string): Promise<string> {
+  const response = await fetch(url);
+  if (!response.ok || !response.body) {
+    throw new Error(`${url}: HTTP ${response.status}`);
+  }
+  const hasher = new Bun.CryptoHasher("sha256");
+  for await (const chunk of response.body) {
+    hasher.update(chunk);
+  }
+  return hasher.digest("hex");
+}
+
+const manifest = await Bun.file(MANIFEST).text();
+const hashes = new Map<string, string>();
+for (const [, , url] of manifest.matchAll(ENTRY)) {
+  if (!hashes.has(url)) {
+    console.log(`Hashing ${url}`);
+    hashes.set(url, await sha256(url));
+  }
+}
+
+const updated = manifest.replace(ENTRY, (_, prefix, url, previous) => {
+  const hash = `"${hashes.get(url)}"`;
+  if (previous !== "null" && previous !== hash) {
+    console.warn(`${url} changed: was ${previous}, now ${hash}`);
+  }
+  return prefix + hash;
+});
+await Bun.write(MANIFEST, updated);
+console.log(`Updated ${hashes.size} hashes in ${MANIFEST.pathname}`);
@@ -2,16 +2,22 @@ fn main() {
     #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
     build_apple_intelligence_bridge();
 
-    generate_tray_translations();
+    generate_translations("tray", "TrayStrings", "tray_translations.rs");
+    generate_translations(
+        "notification",
+        "NotificationStrings",
+        "notification_translations.rs",
+    );
 
     tauri_build::build()
 }
 
-/// Generate tray menu translations from frontend locale files.
+/// Generate translations of one section of the frontend locale files, for the tray
+/// menu and native notifications.
 ///
 /// Source of truth: src/i18n/locales/*/translation.json
-/// The English "tray" section defines the struct fields.
-fn generate_tray_translations() {
+/// The English `section` defines the struct fields.
+fn generate_translations(section: &str, struct_name: &str, file_name: &str) {
     use std::collections::BTreeMap;
     use std::fs;
     use std::path::Path;
@@ -38,8 +44,8 @@ fn generate_tray_translations() {
         let content = fs::read_to_string(&json_path).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
 
-        if let Some(tray) = parsed.get("tray").cloned() {
-            translations.insert(lang, tray);
+        if let Some(strings) = parsed.get(section).cloned() {
+            translations.insert(lang, strings);
         }
     }
 
@@ -56,22 +62,24 @@ fn generate_tray_translations() {
     );
 
     // Struct
-    out.push_str("#[derive(Debug, Clone)]\npub struct TrayStrings {\n");
+    out.push_str(&format!(
+        "#[derive(Debug, Clone)]\npub struct {struct_name} {{\n"
+    ));
     for (rust_field, _) in &fields {
         out.push_str(&format!("    pub {rust_field}: String,\n"));
     }
     out.push_str("}\n\n");
 
     // Static map
-    out.push_str(
-        "pub static TRANSLATIONS: Lazy<HashMap<&'static str, TrayStrings>> = Lazy::new(|| {\n",
-    );
+    out.push_str(&format!(
+        "pub static TRANSLATIONS: Lazy<HashMap<&'static str, {struct_name}>> = Lazy::new(|| {{\n"
+    ));
     out.push_str("    let mut m = HashMap::new();\n");
 
-    for (lang, tray) in &translations {
-        out.push_str(&format!("    m.insert(\"{lang}\", TrayStrings {{\n"));
+    for (lang, strings) in &translations {
+        out.push_str(&format!("    m.insert(\"{lang}\", {struct_name} {{\n"));
         for (rust_field, json_key) in &fields {
-            let val = tray.get(json_key).and_then(|v| v.as_str()).unwrap_or("");
+            let val = strings.get(json_key).and_then(|v| v.as_str()).unwrap_or("");
             out.push_str(&format!(
                 "        {rust_field}: \"{}\".to_string(),\n",
                 escape_string(val)
@@ -82,10 +90,10 @@ fn generate_tray_translations() {
 
     out.push_str("    m\n});\n");
 
-    fs::write(Path::new(&out_dir).join("tray_translations.rs"), out).unwrap();
+    fs::write(Path::new(&out_dir).join(file_name), out).unwrap();
 
     println!(
-        "cargo:warning=Generated tray translations: {} languages, {} fields",
+        "cargo:warning=Generated {section} translations: {} languages, {} fields",
         translations.len(),
         fields.len()
     );
@@ -1,22 +1,52 @@
+use crate::app_prompt_rules::ActivePromptRules;
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 use crate::apple_intelligence;
-use crate::audio_feedback::{play_feedback_sound, play_feedback_sound_blocking, SoundType};
+use crate::audio_feedback::{play_feedback_blocking, SoundType};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::is_discardable_transcription;
+use crate::copy_only_session;
+use crate::deferred_paste::{self, DeferredPaste};
+use crate::dictation_metrics;
+use crate::dictation_session;
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent, FileDictationWriteFailed, TranscriptionDiscarded};
+use crate::file_dictation::FileDictation;
+use crate::formatting;
+use crate::helpers::mic_mute;
+use crate::language;
+use crate::llm_client::{self, ChatMessage, LiveToken, RequestPriority};
 use crate::managers::audio::AudioRecordingManager;
-use crate::managers::history::HistoryManager;
-use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, AppSettings, APPLE_INTELLIGENCE_PROVIDER_ID};
+use crate::managers::history::{HistoryManager, NewHistoryEntry};
+use crate::managers::transcription::{Transcription, TranscriptionManager};
+use crate::notification;
+use crate::pipeline::{ActiveOperations, DictationOutcome, PipelineOperation, PipelineStage};
+use crate::prompt_limits;
+use crate::punctuation;
+use crate::review;
+use crate::session_log::SessionLog;
+use crate::settings::{
+    get_settings, AppSettings, CancelBehavior, LLMPrompt, PostProcessProvider,
+    APPLE_INTELLIGENCE_PROVIDER_ID, COPY_ONLY_SESSION_BINDING, COPY_PREVIOUS_TRANSCRIPT_BINDING,
+    COPY_TRANSCRIPT_TWO_BACK_BINDING, DICTATION_SESSION_BINDING, FLUSH_DEFERRED_PASTE_BINDING,
+    UNDO_LAST_PASTE_BINDING,
+};
 use crate::shortcut;
+use crate::transcript_ring::TranscriptRing;
 use crate::tray::{change_tray_icon, TrayIconState};
-use crate::utils::{self, show_recording_overlay, show_transcribing_overlay};
+use crate::undo_paste;
+use crate::utils::{self, show_recording_overlay, show_transcribing_overlay, FrontmostApplication};
+use crate::voice_stop;
 use crate::ManagedToggleState;
 use ferrous_opencc::{config::BuiltinConfig, OpenCC};
-use log::{debug, error};
+use log::{debug, error, info, warn};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tauri::AppHandle;
 use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
 // Shortcut Action Trait
 pub trait ShortcutAction: Send + Sync {
@@ -27,10 +57,48 @@ pub trait ShortcutAction: Send + Sync {
 // Transcribe Action
 struct TranscribeAction;
 
-async fn maybe_post_process_transcription(
+/// The messages a post-processing request sends for `transcription`, with every
+/// prompt token expanded. Fails when the request is over `prompt_limits`.
+pub fn post_process_messages(
     settings: &AppSettings,
+    template: &str,
+    provider: &PostProcessProvider,
     transcription: &str,
+) -> Result<Vec<ChatMessage>, HandyError> {
+    let limits = prompt_limits::clamp(settings.prompt_limits);
+    let output = prompt_limits::limit_variable("output", transcription, &limits)?;
+    // Expanded before ${output} so a transcript that contains the tokens is left alone
+    let language = language::transcript_language(settings, &output);
+    let prompt = formatting::expand_language_tokens(template, language.as_ref());
+    let hardened = settings.prompt_hardening && llm_client::supports_role_separation(provider);
+    let messages = llm_client::build_post_process_messages(&prompt, &output, hardened);
+    prompt_limits::check_request(&messages, &limits)?;
+    Ok(messages)
+}
+
+pub async fn maybe_post_process_transcription(
+    settings: &AppSettings,
+    transcription: &str,
+    priority: RequestPriority,
 ) -> Option<String> {
+    let request = prepare_post_process(settings, transcription)?;
+    run_post_process(settings, request, priority, None).await
+}
+
+/// A post-processing request ready to be sent, see `prepare_post_process`
+pub struct PostProcessRequest {
+    provider: PostProcessProvider,
+    model: String,
+    llm_prompt: LLMPrompt,
+    messages: Vec<ChatMessage>,
+}
+
+/// The request that post-processes `transcription`, None when post-processing is
+/// off or can't run with the current settings
+pub fn prepare_post_process(
+    settings: &AppSettings,
+    transcription: &str,
+) -> Option<PostProcessRequest> {
     if !settings.post_process_enabled {
         return None;
     }
@@ -57,42 +125,55 @@ async fn maybe_post_process_transcription(
         return None;
     }
 
-    let selected_prompt_id = match &settings.post_process_selected_prompt_id {
-        Some(id) => id.clone(),
-        None => {
-            debug!("Post-processing skipped because no prompt is selected");
-            return None;
-        }
-    };
-
-    let prompt = match settings
-        .post_process_prompts
-        .iter()
-        .find(|prompt| prompt.id == selected_prompt_id)
-    {
-        Some(prompt) => prompt.prompt.clone(),
-        None => {
+    let llm_prompt = match settings.resolve_post_process_prompt() {
+        Ok(prompt) => prompt.clone(),
+        Err(misconfiguration) => {
             debug!(
-                "Post-processing skipped because prompt '{}' was not found",
-                selected_prompt_id
+                "Post-processing skipped because of the prompt: {:?}",
+                misconfiguration
             );
             return None;
         }
     };
 
-    if prompt.trim().is_empty() {
-        debug!("Post-processing skipped because the selected prompt is empty");
-        return None;
-    }
+    let messages =
+        match post_process_messages(settings, &llm_prompt.prompt, &provider, transcription) {
+            Ok(messages) => messages,
+            Err(e) => {
+                error!("Post-processing skipped: {}", e);
+                return None;
+            }
+        };
+    Some(PostProcessRequest {
+        provider,
+        model,
+        llm_prompt,
+        messages,
+    })
+}
 
+/// Sends a prepared post-processing request. None when it fails or `cancel` is
+/// cancelled, the transcript is used as it is then.
+pub async fn run_post_process(
+    settings: &AppSettings,
+    request: PostProcessRequest,
+    priority: RequestPriority,
+    cancel: Option<LiveToken>,
+) -> Option<String> {
+    let PostProcessRequest {
+        provider,
+        model,
+        llm_prompt,
+        messages,
+    } = request;
     debug!(
         "Starting LLM post-processing with provider '{}' (model: {})",
         provider.id, model
     );
-
-    // Replace ${output} variable in the prompt with the actual text
-    let processed_prompt = prompt.replace("${output}", transcription);
-    debug!("Processed prompt length: {} chars", processed_prompt.len());
+    debug!(
+        "Processed prompt length: {} chars",
+        messages.iter().map(|m| m.content.len()).sum::<usize>()
+    );
 
     if provider.id == APPLE_INTELLIGENCE_PROVIDER_ID {
         #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
@@ -102,6 +183,12 @@ async fn maybe_post_process_transcription(
                 return None;
             }
 
+            // Without role separation the expanded prompt is the only message
+            let processed_prompt = messages
+                .iter()
+                .map(|m| m.content.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n");
             let token_limit = model.trim().parse::<i32>().unwrap_or(0);
             return match apple_intelligence::process_text(&processed_prompt, token_limit) {
                 Ok(result) => {
@@ -137,16 +224,30 @@ async fn maybe_post_process_transcription(
         .unwrap_or_default();
 
     // Send the chat completion request
-    match crate::llm_client::send_chat_completion(&provider, api_key, &model, processed_prompt)
-        .await
+    match llm_client::send_chat_completion(
+        &provider,
+        api_key,
+        &model,
+        messages,
+        llm_prompt.response_format,
+        llm_prompt.seed,
+        priority,
+        cancel,
+    )
+    .await
     {
-        Ok(Some(content)) => {
+        Ok(Some(completion)) => {
+            if completion.truncated {
+                warn!("LLM response was truncated, pasting the partial output");
+            }
             // Strip invisible Unicode characters that some LLMs (e.g., Qwen) may insert
-            let content = content
+            let content = completion
+                .content
                 .replace('\u{200B}', "") // Zero-Width Space
                 .replace('\u{200C}', "") // Zero-Width Non-Joiner
                 .replace('\u{200D}', "") // Zero-Width Joiner
                 .replace('\u{FEFF}', ""); // Byte Order Mark / Zero-Width No-Break Space
+            let content = formatting::format_json_output(&content, &llm_prompt.json_output);
             debug!(
                 "LLM post-processing succeeded for provider '{}'. Output length: {} chars",
                 provider.id,
@@ -158,6 +259,10 @@ async fn maybe_post_process_transcription(
             error!("LLM API response has no content");
             None
         }
+        Err(e) if e == llm_client::CANCELLED => {
+            debug!("LLM post-processing cancelled");
+            None
+        }
         Err(e) => {
             error!(
                 "LLM post-processing failed for provider '{}': {}. Falling back to original transcription.",
@@ -169,6 +274,208 @@ async fn maybe_post_process_transcription(
     }
 }
 
+/// Pastes the final text into the focused application and shows the completion notification.
+/// Blocks for a moment while the overlay hides so focus can return to the target app.
+pub fn paste_transcript(ah: &AppHandle, final_text: String, operation: Option<PipelineOperation>) {
+    // 1. Hide the overlay first to initiate focus transfer
+    let ah_hide = ah.clone();
+    let _ = ah.run_on_main_thread(move || {
+        utils::hide_recording_overlay(&ah_hide);
+        change_tray_icon(&ah_hide, TrayIconState::Idle);
+    });
+
+    // 2. Wait for the overlay to fade out and focus to return to the target app.
+    // The overlay fade-out animation is ~300ms. We wait 400ms to be safe.
+    std::thread::sleep(std::time::Duration::from_millis(400));
+
+    // 3. Perform the paste operation, in the form the focused app wants
+    let pasted_text = formatting::apply_app_transform(&get_settings(ah), final_text.clone());
+    let ah_paste = ah.clone();
+    let paste_time = Instant::now();
+    ah.run_on_main_thread(move || match utils::paste(pasted_text, ah_paste.clone()) {
+        Ok(report) => {
+            debug!(
+                "Text pasted successfully in {:?} via {:?}",
+                paste_time.elapsed(),
+                report.used
+            );
+            emit_event(&ah_paste, AppEvent::TranscriptPasted(report));
+            if let Some(operation) = &operation {
+                operation.report(&ah_paste, PipelineStage::PasteDone);
+            }
+            end_operation(&ah_paste, &operation, DictationOutcome::Pasted);
+            notification::notify_transcription_complete(&ah_paste, &final_text);
+        }
+        Err(e) => {
+            error!("Failed to paste transcription: {}", e);
+            end_operation(&ah_paste, &operation, DictationOutcome::Failed);
+        }
+    })
+    .unwrap_or_else(|e| {
+        error!("Failed to run paste on main thread: {:?}", e);
+    });
+}
+
+fn report_stage(app: &AppHandle, operation: &Option<PipelineOperation>, stage: PipelineStage) {
+    if let Some(operation) = operation {
+        operation.report(app, stage);
+    }
+}
+
+fn end_operation(
+    app: &AppHandle,
+    operation: &Option<PipelineOperation>,
+    outcome: DictationOutcome,
+) {
+    if let Some(operation) = operation {
+        operation.end(app, outcome);
+    }
+}
+
+/// Reports the outcome of a file dictation write. Returns false when no session
+/// is active and the text should be pasted as usual. A failed write falls back
+/// to the clipboard so the text isn't lost.
+fn handle_file_dictation_write(
+    app: &AppHandle,
+    result: Result<Option<PathBuf>, (PathBuf, String)>,
+    text: &str,
+) -> bool {
+    match result {
+        Ok(None) => false,
+        Ok(Some(path)) => {
+            debug!("Transcription appended to {}", path.display());
+            true
+        }
+        Err((path, err)) => {
+            error!(
+                "Failed to write transcription to {}: {}",
+                path.display(),
+                err
+            );
+            emit_event(
+                app,
+                AppEvent::FileDictationWriteFailed(FileDictationWriteFailed {
+                    path: path.to_string_lossy().to_string(),
+                    error: err,
+                }),
+            );
+            if let Err(e) = app.clipboard().write_text(text) {
+                error!("Failed to copy transcription to clipboard: {}", e);
+            }
+            true
+        }
+    }
+}
+
+/// Drops the transcript of an accidental activation, such as blank output or a lone
+/// "thank you", instead of pasting it. Returns true when it was discarded.
+fn maybe_discard_transcription(
+    app: &AppHandle,
+    hm: &Arc<HistoryManager>,
+    transcription: &str,
+    samples: &[f32],
+) -> bool {
+    let settings = get_settings(app);
+    if !settings.discard_blank_transcriptions
+        || !is_discardable_transcription(transcription, &settings.discarded_phrases)
+    {
+        return false;
+    }
+
+    debug!("Discarding transcription '{}'", transcription);
+    let kept_in_history = settings.keep_discarded_in_history;
+    if kept_in_history {
+        let hm = Arc::clone(hm);
+        let samples = samples.to_vec();
+        let text = transcription.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = hm
+                .save_transcription(
+                    samples,
+                    NewHistoryEntry {
+                        transcription_text: text,
+                        ..Default::default()
+                    },
+                )
+                .await
+            {
+                error!("Failed to save discarded transcription to history: {}", e);
+            }
+        });
+    }
+
+    emit_event(
+        app,
+        AppEvent::TranscriptionDiscarded(TranscriptionDiscarded {
+            text: transcription.to_string(),
+            kept_in_history,
+        }),
+    );
+    true
+}
+
+/// Keeps the audio of a cancelled recording as `cancel_behavior` says. Returns true
+/// while it is being transcribed, the model unloads once that's done.
+pub fn keep_cancelled_recording(app: &AppHandle, samples: Vec<f32>) -> bool {
+    if samples.is_empty() {
+        return false;
+    }
+    let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+    match get_settings(app).cancel_behavior {
+        CancelBehavior::Discard => false,
+        CancelBehavior::SaveDraft => {
+            info!("Keeping the cancelled recording as a draft");
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = hm.save_draft(samples).await {
+                    error!("Failed to save the cancelled recording as a draft: {}", e);
+                }
+            });
+            false
+        }
+        CancelBehavior::TranscribeSilently => {
+            info!("Transcribing the cancelled recording into history");
+            let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+            tauri::async_runtime::spawn(async move {
+                let audio = samples.clone();
+                let transcribed =
+                    tauri::async_runtime::spawn_blocking(move || tm.transcribe(audio)).await;
+                let transcription = match transcribed {
+                    Ok(Ok(transcription)) => transcription,
+                    Ok(Err(e)) => {
+                        error!("Failed to transcribe the cancelled recording: {}", e);
+                        return;
+                    }
+                    Err(e) => {
+                        error!("The cancelled recording's transcription failed: {}", e);
+                        return;
+                    }
+                };
+                if transcription.text.is_empty() {
+                    debug!("The cancelled recording contains no speech");
+                    return;
+                }
+                if let Err(e) = hm
+                    .save_transcription(
+                        samples,
+                        NewHistoryEntry {
+                            transcription_text: transcription.text,
+                            translation_check: transcription.translation_check,
+                            word_corrections: transcription.word_corrections,
+                            speaker_turns: transcription.speaker_turns,
+                            vram_guard: transcription.vram_guard,
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                {
+                    error!("Failed to save the cancelled recording to history: {}", e);
+                }
+            });
+            true
+        }
+    }
+}
+
 async fn maybe_convert_chinese_variant(
     settings: &AppSettings,
     transcription: &str,
@@ -218,23 +525,36 @@ impl ShortcutAction for TranscribeAction {
         let start_time = Instant::now();
         debug!("TranscribeAction::start called for binding: {}", binding_id);
 
-        // Load model in the background
-        let tm = app.state::<Arc<TranscriptionManager>>();
-        tm.initiate_model_load();
-
         let binding_id = binding_id.to_string();
-        change_tray_icon(app, TrayIconState::Recording);
-        show_recording_overlay(app);
+        let settings = get_settings(app);
 
+        // Load the binding's model in the background
+        let tm = app.state::<Arc<TranscriptionManager>>();
+        tm.initiate_model_switch(tm.model_for_binding(&settings, &binding_id));
+        // Resolved once, the whole dictation keeps the feedback it started with
+        let mut feedback = settings.feedback_mode_for(&binding_id);
+        if binding_id == DICTATION_SESSION_BINDING {
+            // The sounds mark the whole session instead, see `dictation_session`
+            feedback = feedback.without_sound();
+        }
         let rm = app.state::<Arc<AudioRecordingManager>>();
+        // A dictation session's next segment goes on with the running recording,
+        // whose mute and watchers are already in place
+        let next_segment = binding_id == DICTATION_SESSION_BINDING
+            && rm.active_binding().as_deref() == Some(DICTATION_SESSION_BINDING);
+        change_tray_icon(app, TrayIconState::Recording);
+        if feedback.shows_overlay() {
+            show_recording_overlay(app);
+        }
 
         // Get the microphone mode to determine audio feedback timing
-        let settings = get_settings(app);
         let is_always_on = settings.always_on_microphone;
         debug!("Microphone mode - always_on: {}", is_always_on);
 
         let mut recording_started = false;
-        if is_always_on {
+        if next_segment {
+            recording_started = true;
+        } else if is_always_on {
             // Always-on mode: Play audio feedback immediately, then apply mute after sound finishes
             debug!("Always-on mode: Playing audio feedback immediately");
             let rm_clone = Arc::clone(&rm);
@@ -242,7 +562,7 @@ impl ShortcutAction for TranscribeAction {
             // The blocking helper exits immediately if audio feedback is disabled,
             // so we can always reuse this thread to ensure mute happens right after playback.
             std::thread::spawn(move || {
-                play_feedback_sound_blocking(&app_clone, SoundType::Start);
+                play_feedback_blocking(&app_clone, feedback, SoundType::Start);
                 rm_clone.apply_mute();
             });
 
@@ -264,7 +584,7 @@ impl ShortcutAction for TranscribeAction {
                     debug!("Handling delayed audio feedback/mute sequence");
                     // Helper handles disabled audio feedback by returning early, so we reuse it
                     // to keep mute sequencing consistent in every mode.
-                    play_feedback_sound_blocking(&app_clone, SoundType::Start);
+                    play_feedback_blocking(&app_clone, feedback, SoundType::Start);
                     rm_clone.apply_mute();
                 });
             } else {
@@ -273,6 +593,24 @@ impl ShortcutAction for TranscribeAction {
         }
 
         if recording_started {
+            app.state::<ActiveOperations>()
+                .begin(app, &binding_id, feedback);
+            if !next_segment {
+                dictation_metrics::start_ticker(app, &binding_id);
+            }
+
+            // Looked up once, the prompt rules and the paste both follow the
+            // application being dictated into
+            let target_app = utils::frontmost_application();
+            app.state::<ActivePromptRules>()
+                .capture(&binding_id, &settings, target_app.as_ref());
+            app.state::<DeferredPaste>()
+                .capture_target(&binding_id, target_app);
+            if !next_segment {
+                voice_stop::watch(app, &binding_id);
+                mic_mute::watch(app, &binding_id, feedback);
+            }
+
             // Dynamically register the cancel shortcut in a separate task to avoid deadlock
             shortcut::register_cancel_shortcut(app);
         }
@@ -294,15 +632,33 @@ impl ShortcutAction for TranscribeAction {
         let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
         let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
         let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+        let app_prompt_rule = app.state::<ActivePromptRules>().take(binding_id);
+        let operation = app.state::<ActiveOperations>().take(binding_id);
+        let focus_target = app.state::<DeferredPaste>().take_target(binding_id);
 
-        change_tray_icon(app, TrayIconState::Transcribing);
-        show_transcribing_overlay(app);
+        // A cancel from here on drops the text, see `ActiveOperations::cancel_current`
+        let live_token = app
+            .state::<ActiveOperations>()
+            .start_processing(app, operation.as_ref());
 
-        // Unmute before playing audio feedback so the stop sound is audible
-        rm.remove_mute();
+        let feedback = operation.as_ref().map_or_else(
+            || get_settings(app).feedback_mode_for(binding_id),
+            PipelineOperation::feedback,
+        );
+        change_tray_icon(app, TrayIconState::Transcribing);
+        if feedback.shows_overlay() {
+            show_transcribing_overlay(app);
+        }
 
-        // Play audio feedback for recording stop
-        play_feedback_sound(app, SoundType::Stop);
+        // A dictation session's segment is split off while the recording goes on,
+        // here rather than in the task so it ends exactly at the cut
+        let segment = dictation_session::is_cutting(app)
+            .then(|| rm.split_recording(binding_id))
+            .flatten();
+        // Unmute now so the stop sound, played once the recording stopped, is audible
+        if segment.is_none() {
+            rm.remove_mute();
+        }
 
         let binding_id = binding_id.to_string(); // Clone binding_id for the async task
 
@@ -314,7 +670,10 @@ impl ShortcutAction for TranscribeAction {
             );
 
             let stop_recording_time = Instant::now();
-            if let Some(samples) = rm.stop_recording(&binding_id) {
+            let recording = segment.or_else(|| rm.stop_recording(&binding_id));
+            // Reported even without samples, it plays the stop feedback
+            report_stage(&ah, &operation, PipelineStage::RecordingStopped);
+            if let Some(samples) = recording {
                 debug!(
                     "Recording stopped and samples retrieved in {:?}, sample count: {}",
                     stop_recording_time.elapsed(),
@@ -323,15 +682,42 @@ impl ShortcutAction for TranscribeAction {
 
                 let transcription_time = Instant::now();
                 let samples_clone = samples.clone(); // Clone for history saving
-                match tm.transcribe(samples) {
-                    Ok(transcription) => {
+                report_stage(&ah, &operation, PipelineStage::InferenceStarted);
+                // The model the press asked for may still be loading, or another
+                // binding's model may have replaced it since
+                let settings = get_settings(&ah);
+                let model_id = tm.model_for_binding(&settings, &binding_id);
+                let transcribed = {
+                    let operations = ah.state::<ActiveOperations>();
+                    let _inference = operations.lock_inference(settings.overlap_transcription);
+                    tm.ensure_model(&model_id)
+                        .and_then(|_| tm.transcribe(samples))
+                };
+                match transcribed {
+                    Ok(Transcription {
+                        text: transcription,
+                        translation_check,
+                        word_corrections,
+                        speaker_turns,
+                        vram_guard,
+                    }) => {
+                        let transcription =
+                            voice_stop::finish_transcript(&ah, &binding_id, transcription);
                         debug!(
                             "Transcription completed in {:?}: '{}'",
                             transcription_time.elapsed(),
                             transcription
                         );
-                        if !transcription.is_empty() {
-                            let settings = get_settings(&ah);
+                        report_stage(&ah, &operation, PipelineStage::InferenceDone);
+                        if maybe_discard_transcription(&ah, &hm, &transcription, &samples_clone) {
+                            utils::hide_recording_overlay(&ah);
+                            change_tray_icon(&ah, TrayIconState::Idle);
+                            end_operation(&ah, &operation, DictationOutcome::Discarded);
+                        } else if !transcription.is_empty() {
+                            let mut settings = get_settings(&ah);
+                            if let Some(rule) = &app_prompt_rule {
+                                rule.apply(&mut settings);
+                            }
                             let mut final_text = transcription.clone();
                             let mut post_processed_text: Option<String> = None;
                             let mut post_process_prompt: Option<String> = None;
@@ -345,27 +731,86 @@ impl ShortcutAction for TranscribeAction {
 
                             // Then apply regular post-processing if enabled
                             // Uses final_text which may already have Chinese conversion applied
-                            if let Some(processed_text) =
-                                maybe_post_process_transcription(&settings, &final_text).await
-                            {
+                            let mut processed = None;
+                            if let Some(request) = prepare_post_process(&settings, &final_text) {
+                                report_stage(&ah, &operation, PipelineStage::PostProcessStarted);
+                                processed = run_post_process(
+                                    &settings,
+                                    request,
+                                    RequestPriority::Live,
+                                    Some(live_token.clone()),
+                                )
+                                .await;
+                                report_stage(&ah, &operation, PipelineStage::PostProcessDone);
+                            }
+                            if let Some(processed_text) = processed {
                                 post_processed_text = Some(processed_text.clone());
                                 final_text = processed_text;
 
                                 // Get the prompt that was used
-                                if let Some(prompt_id) = &settings.post_process_selected_prompt_id {
-                                    if let Some(prompt) = settings
-                                        .post_process_prompts
-                                        .iter()
-                                        .find(|p| &p.id == prompt_id)
-                                    {
-                                        post_process_prompt = Some(prompt.prompt.clone());
-                                    }
+                                if let Ok(prompt) = settings.resolve_post_process_prompt() {
+                                    post_process_prompt = Some(prompt.prompt.clone());
                                 }
                             } else if final_text != transcription {
                                 // Chinese conversion was applied but no LLM post-processing
                                 post_processed_text = Some(final_text.clone());
                             }
 
+                            // Cancelled while transcribing or post-processing, the
+                            // text is stale
+                            if live_token.is_cancelled() {
+                                debug!(
+                                    "Dictation cancelled before its text was ready, dropping it"
+                                );
+                                utils::hide_recording_overlay(&ah);
+                                change_tray_icon(&ah, TrayIconState::Idle);
+                                end_operation(&ah, &operation, DictationOutcome::Cancelled);
+                                finish_dictation(&ah, &rm, binding_id, &live_token);
+                                return;
+                            }
+
+                            // Applied last so post-processing can't undo it
+                            if settings.localize_punctuation {
+                                final_text =
+                                    punctuation::localize_punctuation(&final_text, &settings);
+                            }
+
+                            // Kept even when history is off, for the copy shortcuts
+                            ah.state::<TranscriptRing>().push(&final_text, &settings);
+                            log_session(
+                                &ah,
+                                &settings,
+                                Some(&final_text),
+                                focus_target.as_ref(),
+                                samples_clone.len(),
+                            );
+
+                            // In file dictation mode the text goes to the target file instead of being pasted
+                            let file_write = ah
+                                .state::<FileDictation>()
+                                .write(&final_text, &settings.file_dictation_separator);
+                            let output_file = match &file_write {
+                                Ok(Some(path)) => Some(path.to_string_lossy().to_string()),
+                                _ => None,
+                            };
+                            let written_to_file =
+                                handle_file_dictation_write(&ah, file_write, &final_text);
+
+                            // Only the pasted text is joined, history and the transcript ring
+                            // keep the line breaks. Paste appends the trailing space after it.
+                            let single_line = !written_to_file
+                                && settings.collapse_to_single_line_for(
+                                    &binding_id,
+                                    app_prompt_rule
+                                        .as_ref()
+                                        .and_then(|rule| rule.collapse_to_single_line),
+                                );
+                            let collapsed_to_single_line =
+                                single_line && final_text.contains(['\n', '\r']);
+                            if collapsed_to_single_line {
+                                final_text = formatting::collapse_to_single_line(&final_text);
+                            }
+
                             // Save to history with post-processed text and prompt
                             let hm_clone = Arc::clone(&hm);
                             let transcription_for_history = transcription.clone();
@@ -373,9 +818,18 @@ impl ShortcutAction for TranscribeAction {
                                 if let Err(e) = hm_clone
                                     .save_transcription(
                                         samples_clone,
-                                        transcription_for_history,
-                                        post_processed_text,
-                                        post_process_prompt,
+                                        NewHistoryEntry {
+                                            transcription_text: transcription_for_history,
+                                            post_processed_text,
+                                            post_process_prompt,
+                                            translation_check,
+                                            output_file,
+                                            app_prompt_rule,
+                                            word_corrections,
+                                            speaker_turns,
+                                            vram_guard,
+                                            collapsed_to_single_line,
+                                        },
                                     )
                                     .await
                                 {
@@ -383,54 +837,59 @@ impl ShortcutAction for TranscribeAction {
                                 }
                             });
 
-                            // Paste the final text (either processed or original)
-                            // 1. Hide the overlay first to initiate focus transfer
-                            let ah_hide = ah.clone();
-                            let _ = ah.run_on_main_thread(move || {
-                                utils::hide_recording_overlay(&ah_hide);
-                                change_tray_icon(&ah_hide, TrayIconState::Idle);
-                            });
+                            let review = settings
+                                .bindings
+                                .get(&binding_id)
+                                .and_then(|binding| binding.review_before_paste)
+                                .unwrap_or(settings.review_before_paste);
 
-                            // 2. Wait for the overlay to fade out and focus to return to the target app.
-                            // The overlay fade-out animation is ~300ms. We wait 400ms to be safe.
-                            std::thread::sleep(std::time::Duration::from_millis(400));
-
-                            // 3. Perform the paste operation
-                            let ah_paste = ah.clone();
-                            let paste_time = Instant::now();
-                            ah.run_on_main_thread(move || {
-                                match utils::paste(final_text, ah_paste) {
-                                    Ok(()) => debug!(
-                                        "Text pasted successfully in {:?}",
-                                        paste_time.elapsed()
-                                    ),
-                                    Err(e) => error!("Failed to paste transcription: {}", e),
-                                }
-                            })
-                            .unwrap_or_else(|e| {
-                                error!("Failed to run paste on main thread: {:?}", e);
-                            });
+                            if written_to_file {
+                                utils::hide_recording_overlay(&ah);
+                                change_tray_icon(&ah, TrayIconState::Idle);
+                                end_operation(&ah, &operation, DictationOutcome::WrittenToFile);
+                            } else if review {
+                                utils::hide_recording_overlay(&ah);
+                                change_tray_icon(&ah, TrayIconState::Idle);
+                                review::begin_review(&ah, final_text, focus_target, operation);
+                            } else if deferred_paste::should_defer(
+                                &ah,
+                                &settings,
+                                focus_target.as_ref(),
+                            ) {
+                                change_tray_icon(&ah, TrayIconState::Idle);
+                                deferred_paste::park(&ah, final_text, feedback.shows_overlay());
+                                end_operation(&ah, &operation, DictationOutcome::Deferred);
+                            } else {
+                                paste_transcript(&ah, final_text, operation);
+                            }
                         } else {
                             utils::hide_recording_overlay(&ah);
                             change_tray_icon(&ah, TrayIconState::Idle);
+                            end_operation(&ah, &operation, DictationOutcome::Empty);
                         }
                     }
                     Err(err) => {
                         debug!("Global Shortcut Transcription error: {}", err);
+                        log_session(
+                            &ah,
+                            &get_settings(&ah),
+                            None,
+                            focus_target.as_ref(),
+                            samples_clone.len(),
+                        );
                         utils::hide_recording_overlay(&ah);
                         change_tray_icon(&ah, TrayIconState::Idle);
+                        end_operation(&ah, &operation, DictationOutcome::Failed);
                     }
                 }
             } else {
                 debug!("No samples retrieved from recording stop");
                 utils::hide_recording_overlay(&ah);
                 change_tray_icon(&ah, TrayIconState::Idle);
+                end_operation(&ah, &operation, DictationOutcome::Empty);
             }
 
-            // Clear toggle state now that transcription is complete
-            if let Ok(mut states) = ah.state::<ManagedToggleState>().lock() {
-                states.active_toggles.insert(binding_id, false);
-            }
+            finish_dictation(&ah, &rm, binding_id, &live_token);
         });
 
         debug!(
@@ -440,6 +899,50 @@ impl ShortcutAction for TranscribeAction {
     }
 }
 
+/// Bookkeeping once a dictation left the pipeline, whichever way it did
+fn finish_dictation(
+    app: &AppHandle,
+    rm: &AudioRecordingManager,
+    binding_id: String,
+    live_token: &LiveToken,
+) {
+    let operations = app.state::<ActiveOperations>();
+    operations.finish_processing(app, live_token);
+    if binding_id == DICTATION_SESSION_BINDING {
+        dictation_session::segment_done(app);
+    } else if let Some(feedback) = operations.recording_feedback() {
+        // A recording started meanwhile, and finishing this one put the tray back
+        // to idle
+        change_tray_icon(app, TrayIconState::Recording);
+        if feedback.shows_overlay() {
+            show_recording_overlay(app);
+        }
+    }
+
+    // Clear toggle state now that transcription is complete, unless the
+    // binding is recording again
+    if rm.active_binding().as_deref() != Some(binding_id.as_str()) {
+        if let Ok(mut states) = app.state::<ManagedToggleState>().lock() {
+            states.active_toggles.insert(binding_id, false);
+        }
+    }
+}
+
+/// Adds a dictation to today's session log, `text` is None when transcription failed.
+/// `target` is the app that had focus when the recording started.
+fn log_session(
+    app: &AppHandle,
+    settings: &AppSettings,
+    text: Option<&str>,
+    target: Option<&FrontmostApplication>,
+    samples: usize,
+) {
+    let duration = Duration::from_secs_f64(samples as f64 / WHISPER_SAMPLE_RATE as f64);
+    let target = target.map(|target| target.name.clone());
+    app.state::<SessionLog>()
+        .record(settings, text, target, duration);
+}
+
 // Cancel Action
 struct CancelAction;
 
@@ -453,6 +956,106 @@ impl ShortcutAction for CancelAction {
     }
 }
 
+// Copy Transcript Action
+struct CopyTranscriptAction {
+    /// How far back in the transcript ring, 0 is the most recent transcript
+    index: usize,
+}
+
+impl ShortcutAction for CopyTranscriptAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        if let Err(e) = copy_transcript_ring_entry(app, self.index) {
+            warn!("{}", e);
+        }
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for copy
+    }
+}
+
+struct FlushDeferredPasteAction;
+
+impl ShortcutAction for FlushDeferredPasteAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        if let Err(e) = deferred_paste::flush(app) {
+            debug!("{}", e);
+        }
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for a deferred paste
+    }
+}
+
+struct UndoLastPasteAction;
+
+impl ShortcutAction for UndoLastPasteAction {
+    fn start(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Runs on release, backspaces sent with the shortcut held would be modified
+    }
+
+    fn stop(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = undo_paste::undo(&app) {
+                debug!("{}", e);
+            }
+        });
+    }
+}
+
+struct ToggleDictationSessionAction;
+
+impl ShortcutAction for ToggleDictationSessionAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        let result = if dictation_session::is_active(app) {
+            dictation_session::end(app)
+        } else {
+            dictation_session::start(app)
+        };
+        if let Err(e) = result {
+            warn!("{}", e);
+        }
+    }
+
+    fn stop(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Only reached through the segment being stopped, e.g. from the overlay
+        if dictation_session::is_active(app) {
+            if let Err(e) = dictation_session::end(app) {
+                warn!("{}", e);
+            }
+        }
+    }
+}
+
+struct ToggleCopyOnlySessionAction;
+
+impl ShortcutAction for ToggleCopyOnlySessionAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        copy_only_session::toggle(app);
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for a toggle
+    }
+}
+
+/// Copies an entry of the transcript ring to the OS clipboard
+pub fn copy_transcript_ring_entry(app: &AppHandle, index: usize) -> Result<(), HandyError> {
+    let entry = app.state::<TranscriptRing>().get(index).ok_or_else(|| {
+        HandyError::not_found(format!(
+            "No transcript {} back in the transcript ring",
+            index
+        ))
+    })?;
+    app.clipboard()
+        .write_text(entry.text)
+        .map_err(|e| HandyError::io(format!("Failed to copy transcript: {}", e)))?;
+    debug!("Copied transcript {} back to the clipboard", index);
+    Ok(())
+}
+
 // Test Action
 struct TestAction;
 
@@ -487,9 +1090,118 @@ pub static ACTION_MAP: Lazy<HashMap<String, Arc<dyn ShortcutAction>>> = Lazy::ne
         "cancel".to_string(),
         Arc::new(CancelAction) as Arc<dyn ShortcutAction>,
     );
+    map.insert(
+        COPY_PREVIOUS_TRANSCRIPT_BINDING.to_string(),
+        Arc::new(CopyTranscriptAction { index: 0 }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        COPY_TRANSCRIPT_TWO_BACK_BINDING.to_string(),
+        Arc::new(CopyTranscriptAction { index: 1 }) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        FLUSH_DEFERRED_PASTE_BINDING.to_string(),
+        Arc::new(FlushDeferredPasteAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        UNDO_LAST_PASTE_BINDING.to_string(),
+        Arc::new(UndoLastPasteAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        COPY_ONLY_SESSION_BINDING.to_string(),
+        Arc::new(ToggleCopyOnlySessionAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        DICTATION_SESSION_BINDING.to_string(),
+        Arc::new(ToggleDictationSessionAction) as Arc<dyn ShortcutAction>,
+    );
     map.insert(
         "test".to_string(),
         Arc::new(TestAction) as Arc<dyn ShortcutAction>,
     );
     map
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{get_default_settings, PromptLimits};
+
+    const TEMPLATE: &str = "Fix this ${language_name} (${language}) text: ${output}";
+
+    fn settings(hardening: bool) -> AppSettings {
+        let mut settings = get_default_settings();
+        settings.selected_language = "de".to_string();
+        settings.prompt_hardening = hardening;
+        settings
+    }
+
+    fn provider(settings: &AppSettings) -> PostProcessProvider {
+        settings
+            .post_process_provider("openai")
+            .cloned()
+            .expect("default provider")
+    }
+
+    #[test]
+    fn variables_are_expanded_but_not_inside_the_transcript() {
+        let settings = settings(false);
+        let messages = post_process_messages(
+            &settings,
+            TEMPLATE,
+            &provider(&settings),
+            "hallo ${language}",
+        )
+        .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].role, "user");
+        assert_eq!(
+            messages[0].content,
+            "Fix this German (de) text: hallo ${language}"
+        );
+    }
+
+    #[test]
+    fn hardened_prompts_send_the_transcript_separately() {
+        let settings = settings(true);
+        let messages = post_process_messages(
+            &settings,
+            "${output:upper} in ${language_name}",
+            &provider(&settings),
+            "hallo welt",
+        )
+        .unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "system");
+        assert!(messages[0]
+            .content
+            .starts_with("the transcript provided in the user message in German\n\n"));
+        assert_eq!(messages[1].role, "user");
+        assert_eq!(
+            messages[1].content,
+            "<transcript>\nHALLO WELT\n</transcript>"
+        );
+    }
+
+    #[test]
+    fn long_transcripts_are_cut_or_refused() {
+        let mut settings = settings(false);
+        settings.prompt_limits = PromptLimits {
+            max_variable_chars: 1_000,
+            max_request_chars: 1_100,
+        };
+        let provider = provider(&settings);
+
+        let messages =
+            post_process_messages(&settings, TEMPLATE, &provider, &"a".repeat(1_010)).unwrap();
+        assert!(messages[0].content.ends_with(&format!(
+            "{}\n[10 more characters truncated]",
+            "a".repeat(1_000)
+        )));
+
+        let long_template = format!("{}${{output}}", "x".repeat(200));
+        assert!(
+            post_process_messages(&settings, &long_template, &provider, &"a".repeat(1_000))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Per-application prompt rules. The focused application is checked when recording
+//! starts and the matching rule overrides the selected prompt for that dictation only.
+
+use crate::settings::{AppPromptRule, AppSettings};
+use crate::utils::FrontmostApplication;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A rule that was applied to a dictation, recorded in history
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AppliedPromptRule {
+    pub rule_id: String,
+    pub app_matcher: String,
+    /// Name of the application that had focus
+    pub app_name: String,
+    pub prompt_id: String,
+    pub post_process: Option<bool>,
+    #[serde(default)]
+    pub collapse_to_single_line: Option<bool>,
+}
+
+impl AppliedPromptRule {
+    /// Overrides the prompt selection, and post-processing if the rule forces it
+    pub fn apply(&self, settings: &mut AppSettings) {
+        settings.post_process_selected_prompt_id = Some(self.prompt_id.clone());
+        if let Some(enabled) = self.post_process {
+            settings.post_process_enabled = enabled;
+        }
+    }
+}
+
+/// Returns the first rule matching the application
+pub fn match_rule(
+    rules: &[AppPromptRule],
+    app: &FrontmostApplication,
+) -> Option<AppliedPromptRule> {
+    rules
+        .iter()
+        .find(|rule| app.matches(rule.app_matcher.trim()))
+        .map(|rule| AppliedPromptRule {
+            rule_id: rule.id.clone(),
+            app_matcher: rule.app_matcher.clone(),
+            app_name: app.name.clone(),
+            prompt_id: rule.prompt_id.clone(),
+            post_process: rule.post_process,
+            collapse_to_single_line: rule.collapse_to_single_line,
+        })
+}
+
+/// Rules captured at recording start, keyed by binding id until the recording stops
+#[derive(Default)]
+pub struct ActivePromptRules {
+    active: Mutex<HashMap<String, AppliedPromptRule>>,
+}
+
+impl ActivePromptRules {
+    /// Matches the application focused when a recording started against the
+    /// configured rules
+    pub fn capture(
+        &self,
+        binding_id: &str,
+        settings: &AppSettings,
+        target_app: Option<&FrontmostApplication>,
+    ) {
+        let applied = target_app.and_then(|app| match_rule(&settings.app_prompt_rules, app));
+
+        let mut active = self.active.lock().unwrap();
+        match applied {
+            Some(rule) => {
+                debug!(
+                    "App prompt rule '{}' matched '{}', using prompt '{}'",
+                    rule.rule_id, rule.app_name, rule.prompt_id
+                );
+                active.insert(binding_id.to_string(), rule);
+            }
+            None => {
+                active.remove(binding_id);
+            }
+        }
+    }
+
+    pub fn take(&self, binding_id: &str) -> Option<AppliedPromptRule> {
+        self.active.lock().unwrap().remove(binding_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    fn rule(id: &str, matcher: &str, prompt_id: &str) -> AppPromptRule {
+        AppPromptRule {
+            id: id.to_string(),
+            app_matcher: matcher.to_string(),
+            prompt_id: prompt_id.to_string(),
+            post_process: None,
+            collapse_to_single_line: None,
+        }
+    }
+
+    fn app(name: &str, identifier: Option<&str>) -> FrontmostApplication {
+        FrontmostApplication {
+            name: name.to_string(),
+            identifier: identifier.map(str::to_string),
+            path: None,
+            window_id: None,
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = vec![
+            rule("mail", "com.microsoft.Outlook", "email"),
+            rule("terminal", "Terminal", "code"),
+            rule("fallback", "terminal", "other"),
+        ];
+
+        let outlook = match_rule(&rules, &app("Outlook", Some("com.microsoft.outlook")));
+        assert_eq!(outlook.map(|r| r.prompt_id), Some("email".to_string()));
+
+        let terminal = match_rule(&rules, &app("Terminal", None)).unwrap();
+        assert_eq!(terminal.rule_id, "terminal");
+        assert_eq!(terminal.app_name, "Terminal");
+
+        assert!(match_rule(&rules, &app("Safari", Some("com.apple.Safari"))).is_none());
+    }
+
+    #[test]
+    fn apply_overrides_prompt_and_optionally_post_processing() {
+        let mut settings = get_default_settings();
+        settings.post_process_enabled = false;
+
+        let mut forced = rule("r", "Code", "code");
+        forced.post_process = Some(true);
+        let applied = match_rule(&[forced], &app("Code", None)).unwrap();
+        applied.apply(&mut settings);
+        assert_eq!(
+            settings.post_process_selected_prompt_id.as_deref(),
+            Some("code")
+        );
+        assert!(settings.post_process_enabled);
+
+        let applied = match_rule(&[rule("r", "Code", "plain")], &app("Code", None)).unwrap();
+        applied.apply(&mut settings);
+        assert!(settings.post_process_enabled);
+    }
+}
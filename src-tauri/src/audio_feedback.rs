@@ -1,17 +1,38 @@
-use crate::settings::SoundTheme;
+use crate::audio_toolkit::list_output_devices;
+use crate::events::{emit_event, AppEvent, OutputDeviceChanged};
+use crate::helpers::output_devices;
+use crate::managers::audio::device_environment_fingerprint;
+use crate::paths;
+use crate::pipeline::PipelineStage;
 use crate::settings::{self, AppSettings};
-use cpal::traits::{DeviceTrait, HostTrait};
+use crate::settings::{FeedbackMode, FeedbackTiming, SoundTheme};
 use log::{debug, error, warn};
 use rodio::OutputStreamBuilder;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
 use std::thread;
+use std::time::Duration;
 use tauri::{AppHandle, Manager};
 
+/// Pitch and length of the processing tick
+const TICK: &[(f32, Duration)] = &[(1320.0, Duration::from_millis(30))];
+/// Two falling tones for a recording that couldn't start
+const ERROR_CUE: &[(f32, Duration)] = &[
+    (660.0, Duration::from_millis(120)),
+    (440.0, Duration::from_millis(180)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SoundType {
     Start,
     Stop,
+    /// Short tick when the recording stops but the stop sound waits for a later stage,
+    /// the same for every sound theme
+    Processing,
+    /// Falling tones when a recording is refused, also the same for every theme
+    Error,
 }
 
 fn resolve_sound_path(
@@ -19,40 +40,85 @@ fn resolve_sound_path(
     settings: &AppSettings,
     sound_type: SoundType,
 ) -> Option<PathBuf> {
-    let sound_file = get_sound_path(settings, sound_type);
-    let base_dir = get_sound_base_dir(settings);
-    app.path().resolve(&sound_file, base_dir).ok()
+    let sound_file = get_sound_path(settings, sound_type)?;
+    match settings.sound_theme {
+        SoundTheme::Custom => Some(paths::get(app).data.join(sound_file)),
+        _ => app
+            .path()
+            .resolve(&sound_file, tauri::path::BaseDirectory::Resource)
+            .ok(),
+    }
 }
 
-fn get_sound_path(settings: &AppSettings, sound_type: SoundType) -> String {
-    match (settings.sound_theme, sound_type) {
+/// Pitches and lengths of a synthesized sound, None for the theme's sounds
+fn synthesized_tones(sound_type: SoundType) -> Option<&'static [(f32, Duration)]> {
+    match sound_type {
+        SoundType::Processing => Some(TICK),
+        SoundType::Error => Some(ERROR_CUE),
+        SoundType::Start | SoundType::Stop => None,
+    }
+}
+
+/// The theme's file for `sound_type`, None for the synthesized sounds
+fn get_sound_path(settings: &AppSettings, sound_type: SoundType) -> Option<String> {
+    let path = match (settings.sound_theme, sound_type) {
+        (_, SoundType::Processing | SoundType::Error) => return None,
         (SoundTheme::Custom, SoundType::Start) => "custom_start.wav".to_string(),
         (SoundTheme::Custom, SoundType::Stop) => "custom_stop.wav".to_string(),
         (_, SoundType::Start) => settings.sound_theme.to_start_path(),
         (_, SoundType::Stop) => settings.sound_theme.to_stop_path(),
+    };
+    Some(path)
+}
+
+/// Plays `sound_type` if `feedback` includes sounds, without waiting for it
+fn play_feedback_sound(
+    app: &AppHandle,
+    settings: &AppSettings,
+    feedback: FeedbackMode,
+    sound_type: SoundType,
+) {
+    if !feedback.plays_sound() {
+        return;
+    }
+    if let Some(tones) = synthesized_tones(sound_type) {
+        let app = app.clone();
+        thread::spawn(move || play_synthesized(&app, tones));
+    } else if let Some(path) = resolve_sound_path(app, settings, sound_type) {
+        play_sound_async(app, path);
     }
 }
 
-fn get_sound_base_dir(settings: &AppSettings) -> tauri::path::BaseDirectory {
-    match settings.sound_theme {
-        SoundTheme::Custom => tauri::path::BaseDirectory::AppData,
-        _ => tauri::path::BaseDirectory::Resource,
+/// The sound a dictation reaching `stage` plays under `timing`
+fn stage_sound(timing: FeedbackTiming, stage: PipelineStage) -> Option<SoundType> {
+    match (timing, stage) {
+        (FeedbackTiming::RecordingStop, PipelineStage::RecordingStopped) => Some(SoundType::Stop),
+        (_, PipelineStage::RecordingStopped) => Some(SoundType::Processing),
+        (FeedbackTiming::TranscriptionComplete, PipelineStage::InferenceDone)
+        | (FeedbackTiming::PasteComplete, PipelineStage::PasteDone) => Some(SoundType::Stop),
+        _ => None,
     }
 }
 
-pub fn play_feedback_sound(app: &AppHandle, sound_type: SoundType) {
+/// Plays the feedback for a dictation reaching `stage`, following `feedback_timing`.
+/// Haptics stay with the end of the recording. A dictation that fails or is never
+/// pasted doesn't get to the stage its stop sound waits for.
+pub fn play_stage_feedback(app: &AppHandle, feedback: FeedbackMode, stage: PipelineStage) {
     let settings = settings::get_settings(app);
-    if !settings.audio_feedback {
-        return;
+    if stage == PipelineStage::RecordingStopped {
+        perform_haptic_feedback(app, &settings, feedback);
     }
-    if let Some(path) = resolve_sound_path(app, &settings, sound_type) {
-        play_sound_async(app, path);
+    if let Some(sound_type) = stage_sound(settings.feedback_timing, stage) {
+        play_feedback_sound(app, &settings, feedback, sound_type);
     }
 }
 
-pub fn play_feedback_sound_blocking(app: &AppHandle, sound_type: SoundType) {
+/// Plays the sound, if `feedback` includes sounds, and haptics and returns once the
+/// sound has finished
+pub fn play_feedback_blocking(app: &AppHandle, feedback: FeedbackMode, sound_type: SoundType) {
     let settings = settings::get_settings(app);
-    if !settings.audio_feedback {
+    perform_haptic_feedback(app, &settings, feedback);
+    if !feedback.plays_sound() {
         return;
     }
     if let Some(path) = resolve_sound_path(app, &settings, sound_type) {
@@ -60,9 +126,57 @@ pub fn play_feedback_sound_blocking(app: &AppHandle, sound_type: SoundType) {
     }
 }
 
+/// Plays the error cue, if `feedback` includes sounds, without waiting for it
+pub fn play_error_feedback(app: &AppHandle, feedback: FeedbackMode) {
+    let settings = settings::get_settings(app);
+    play_feedback_sound(app, &settings, feedback, SoundType::Error);
+}
+
+/// A light trackpad tap, on macOS machines with a Force Touch trackpad. A binding
+/// set to no feedback doesn't get one either.
+fn perform_haptic_feedback(app: &AppHandle, settings: &AppSettings, feedback: FeedbackMode) {
+    if !settings.haptic_feedback || feedback == FeedbackMode::None {
+        return;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let result = app.run_on_main_thread(|| {
+            use objc2::runtime::AnyObject;
+            use objc2::{class, msg_send};
+
+            // NSHapticFeedbackPatternGeneric and NSHapticFeedbackPerformanceTimeNow
+            const PATTERN_GENERIC: isize = 0;
+            const PERFORMANCE_TIME_NOW: usize = 1;
+            unsafe {
+                let performer: *mut AnyObject =
+                    msg_send![class!(NSHapticFeedbackManager), defaultPerformer];
+                if let Some(performer) = performer.as_ref() {
+                    let _: () = msg_send![
+                        performer,
+                        performFeedbackPattern: PATTERN_GENERIC,
+                        performanceTime: PERFORMANCE_TIME_NOW
+                    ];
+                }
+            }
+        });
+        if let Err(e) = result {
+            warn!("Failed to perform haptic feedback: {}", e);
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        debug!("Haptic feedback is only available on macOS");
+    }
+}
+
 pub fn play_test_sound(app: &AppHandle, sound_type: SoundType) {
     let settings = settings::get_settings(app);
-    if let Some(path) = resolve_sound_path(app, &settings, sound_type) {
+    if let Some(tones) = synthesized_tones(sound_type) {
+        play_synthesized(app, tones);
+    } else if let Some(path) = resolve_sound_path(app, &settings, sound_type) {
         play_sound_blocking(app, &path);
     }
 }
@@ -85,45 +199,60 @@ fn play_sound_blocking(app: &AppHandle, path: &Path) {
 fn play_sound_at_path(app: &AppHandle, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     let settings = settings::get_settings(app);
     let volume = settings.audio_feedback_volume;
-    let selected_device = settings.selected_output_device.clone();
-    play_audio_file(path, selected_device, volume)
+    let output = app.state::<FeedbackOutput>();
+    let device = output.device(app, &settings)?;
+
+    match play_audio_file(path, device, volume) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            // The device may have vanished since the last check, re-resolve and retry once
+            warn!("Feedback playback failed ({}), re-initializing output", e);
+            reinitialize_output(app);
+            play_audio_file(path, output.device(app, &settings)?, volume)
+        }
+    }
 }
 
-fn play_audio_file(
-    path: &std::path::Path,
-    selected_device: Option<String>,
+fn play_synthesized(app: &AppHandle, tones: &[(f32, Duration)]) {
+    let settings = settings::get_settings(app);
+    let result = app
+        .state::<FeedbackOutput>()
+        .device(app, &settings)
+        .and_then(|device| play_tones(device, tones, settings.audio_feedback_volume));
+    if let Err(e) = result {
+        error!("Failed to play synthesized feedback: {}", e);
+    }
+}
+
+fn play_tones(
+    device: cpal::Device,
+    tones: &[(f32, Duration)],
     volume: f32,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let stream_builder = if let Some(device_name) = selected_device {
-        if device_name == "Default" {
-            debug!("Using default device");
-            OutputStreamBuilder::from_default_device()?
-        } else {
-            let host = crate::audio_toolkit::get_cpal_host();
-            let devices = host.output_devices()?;
-
-            let mut found_device = None;
-            for device in devices {
-                if device.name()? == device_name {
-                    found_device = Some(device);
-                    break;
-                }
-            }
+    use rodio::source::{SineWave, Source};
 
-            match found_device {
-                Some(device) => OutputStreamBuilder::from_device(device)?,
-                None => {
-                    warn!("Device '{}' not found, using default device", device_name);
-                    OutputStreamBuilder::from_default_device()?
-                }
-            }
-        }
-    } else {
-        debug!("Using default device");
-        OutputStreamBuilder::from_default_device()?
-    };
+    let stream_handle = OutputStreamBuilder::from_device(device)?.open_stream()?;
+    let sink = rodio::Sink::connect_new(stream_handle.mixer());
+    for &(frequency, duration) in tones {
+        sink.append(
+            SineWave::new(frequency)
+                .take_duration(duration)
+                .fade_in(Duration::from_millis(5))
+                .amplify(0.5),
+        );
+    }
+    sink.set_volume(volume);
+    sink.sleep_until_end();
 
-    let stream_handle = stream_builder.open_stream()?;
+    Ok(())
+}
+
+fn play_audio_file(
+    path: &std::path::Path,
+    device: cpal::Device,
+    volume: f32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stream_handle = OutputStreamBuilder::from_device(device)?.open_stream()?;
     let mixer = stream_handle.mixer();
 
     let file = File::open(path)?;
@@ -135,3 +264,212 @@ fn play_audio_file(
 
     Ok(())
 }
+
+/// How often connected output devices are checked for changes when the OS
+/// doesn't notify about them
+const OUTPUT_DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time given to a burst of device notifications before the output is re-resolved
+const OUTPUT_DEVICE_SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+struct ResolvedOutput {
+    /// Device requested in the settings, None for the system default
+    requested: Option<String>,
+    name: String,
+    device: cpal::Device,
+    /// True when the requested device is missing and the default is used instead
+    fallback: bool,
+}
+
+/// Output device for feedback sounds. It is resolved once and re-resolved when the
+/// selected device setting changes or devices are connected and disconnected.
+#[derive(Default)]
+pub struct FeedbackOutput {
+    current: Mutex<Option<ResolvedOutput>>,
+}
+
+impl FeedbackOutput {
+    fn device(
+        &self,
+        app: &AppHandle,
+        settings: &AppSettings,
+    ) -> Result<cpal::Device, Box<dyn std::error::Error>> {
+        let requested = requested_output_device(settings);
+        {
+            let current = self.current.lock().unwrap();
+            if let Some(output) = current.as_ref().filter(|o| o.requested == requested) {
+                return Ok(output.device.clone());
+            }
+        }
+        reinitialize_output(app);
+        self.current
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|output| output.device.clone())
+            .ok_or_else(|| "No audio output device available".into())
+    }
+}
+
+/// The output device name from the settings, None when the system default is selected
+fn requested_output_device(settings: &AppSettings) -> Option<String> {
+    settings
+        .selected_output_device
+        .clone()
+        .filter(|name| name != "Default")
+}
+
+/// Index of the device to play on: the requested one when connected, else the default.
+/// `devices` holds each device's name and whether it is the system default.
+fn pick_output_device(requested: Option<&str>, devices: &[(&str, bool)]) -> Option<usize> {
+    requested
+        .and_then(|name| devices.iter().position(|(n, _)| *n == name))
+        .or_else(|| devices.iter().position(|(_, is_default)| *is_default))
+}
+
+/// Resolves the output device from the current settings and emits
+/// `output-device-changed` when a different device ends up being used.
+pub fn reinitialize_output(app: &AppHandle) {
+    let requested = requested_output_device(&settings::get_settings(app));
+    let mut devices = match list_output_devices() {
+        Ok(devices) => devices,
+        Err(e) => {
+            error!("Failed to list output devices: {}", e);
+            return;
+        }
+    };
+
+    let candidates: Vec<(&str, bool)> = devices
+        .iter()
+        .map(|d| (d.name.as_str(), d.is_default))
+        .collect();
+    let Some(index) = pick_output_device(requested.as_deref(), &candidates) else {
+        warn!("No audio output device available for feedback sounds");
+        *app.state::<FeedbackOutput>().current.lock().unwrap() = None;
+        return;
+    };
+    let chosen = devices.swap_remove(index);
+    let fallback = requested.as_deref().is_some_and(|name| name != chosen.name);
+    if fallback {
+        warn!(
+            "Output device '{}' not found, using default device '{}'",
+            requested.as_deref().unwrap_or_default(),
+            chosen.name
+        );
+    }
+
+    let resolved = ResolvedOutput {
+        requested,
+        name: chosen.name,
+        device: chosen.device,
+        fallback,
+    };
+    let changed = OutputDeviceChanged {
+        device: resolved.name.clone(),
+        fallback: resolved.fallback,
+    };
+    let unchanged = {
+        let mut current = app.state::<FeedbackOutput>().current.lock().unwrap();
+        let unchanged = matches!(
+            current.as_ref(),
+            Some(old) if old.name == resolved.name && old.fallback == resolved.fallback
+        );
+        *current = Some(resolved);
+        unchanged
+    };
+
+    if !unchanged {
+        debug!("Feedback sounds now play on '{}'", changed.device);
+        emit_event(app, AppEvent::OutputDeviceChanged(changed));
+    }
+}
+
+/// Fingerprint of the connected output devices and the current default
+fn output_environment() -> Option<String> {
+    let devices = list_output_devices().ok()?;
+    let names: Vec<&str> = devices.iter().map(|d| d.name.as_str()).collect();
+    let default = devices
+        .iter()
+        .find(|d| d.is_default)
+        .map(|d| d.name.as_str());
+    Some(format!(
+        "{}#{}",
+        device_environment_fingerprint(&names),
+        default.unwrap_or_default()
+    ))
+}
+
+/// Watches for output devices being connected or removed (e.g. a Bluetooth headset)
+/// and re-initializes the feedback output when that happens. Follows OS
+/// notifications where available and polls otherwise.
+pub fn start_output_device_watcher(app: &AppHandle) {
+    let app = app.clone();
+    let (tx, rx) = mpsc::channel::<()>();
+    let notified = output_devices::watch(move || {
+        let _ = tx.send(());
+    });
+    thread::spawn(move || {
+        if notified {
+            while rx.recv().is_ok() {
+                // A device change usually arrives as a burst of notifications
+                thread::sleep(OUTPUT_DEVICE_SETTLE_DELAY);
+                while rx.try_recv().is_ok() {}
+                debug!("Output devices changed, re-initializing feedback output");
+                reinitialize_output(&app);
+            }
+            debug!("Output device notifications stopped, polling instead");
+        }
+        let mut last = output_environment();
+        loop {
+            thread::sleep(OUTPUT_DEVICE_POLL_INTERVAL);
+            let environment = output_environment();
+            if environment.is_some() && environment != last {
+                debug!("Output devices changed, re-initializing feedback output");
+                reinitialize_output(&app);
+                last = environment;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_requested_device_or_falls_back_to_default() {
+        let devices = [("Speakers", true), ("AirPods", false)];
+        assert_eq!(pick_output_device(Some("AirPods"), &devices), Some(1));
+        assert_eq!(pick_output_device(Some("Gone"), &devices), Some(0));
+        assert_eq!(pick_output_device(None, &devices), Some(0));
+        assert_eq!(pick_output_device(Some("AirPods"), &[]), None);
+    }
+
+    #[test]
+    fn stop_sound_waits_for_the_configured_stage() {
+        use FeedbackTiming::*;
+        use PipelineStage::*;
+
+        assert_eq!(
+            stage_sound(RecordingStop, RecordingStopped),
+            Some(SoundType::Stop)
+        );
+        assert_eq!(stage_sound(RecordingStop, PasteDone), None);
+
+        assert_eq!(
+            stage_sound(TranscriptionComplete, RecordingStopped),
+            Some(SoundType::Processing)
+        );
+        assert_eq!(
+            stage_sound(TranscriptionComplete, InferenceDone),
+            Some(SoundType::Stop)
+        );
+
+        assert_eq!(
+            stage_sound(PasteComplete, RecordingStopped),
+            Some(SoundType::Processing)
+        );
+        assert_eq!(stage_sound(PasteComplete, InferenceDone), None);
+        assert_eq!(stage_sound(PasteComplete, PasteDone), Some(SoundType::Stop));
+    }
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+/// Gain is evaluated on blocks this long
+const BLOCK_DURATION: Duration = Duration::from_millis(10);
+/// Time constant for raising the gain; slow, so pauses and soft syllables don't pump
+const ATTACK_TIME: Duration = Duration::from_millis(800);
+/// Time constant for lowering the gain; fast, so a sudden loud voice doesn't clip
+const RELEASE_TIME: Duration = Duration::from_millis(60);
+const MIN_GAIN: f32 = 0.1;
+const MAX_GAIN: f32 = 20.0;
+/// Blocks quieter than this are treated as silence and leave the gain unchanged
+const DEFAULT_GATE_RMS: f32 = 0.003;
+
+/// Automatic gain control bringing speech towards a target RMS level.
+///
+/// Runs on the captured signal before the VAD and the level meter, so both see
+/// levels that don't depend on how far the speaker is from the microphone.
+pub struct AutomaticGainControl {
+    target_rms: f32,
+    gate_rms: f32,
+    block_len: usize,
+    attack: f32,
+    release: f32,
+    gain: f32,
+    block: Vec<f32>,
+}
+
+impl AutomaticGainControl {
+    pub fn new(sample_rate: u32, target_rms: f32) -> Self {
+        let block_len = ((sample_rate as u128 * BLOCK_DURATION.as_millis()) / 1000).max(1) as usize;
+        let smoothing =
+            |time: Duration| 1.0 - (-BLOCK_DURATION.as_secs_f32() / time.as_secs_f32()).exp();
+        Self {
+            target_rms,
+            gate_rms: DEFAULT_GATE_RMS,
+            block_len,
+            attack: smoothing(ATTACK_TIME),
+            release: smoothing(RELEASE_TIME),
+            gain: 1.0,
+            block: Vec::with_capacity(block_len),
+        }
+    }
+
+    pub fn target_rms(&self) -> f32 {
+        self.target_rms
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Applies the gain in place. The gain is updated at the end of each block and
+    /// ramped across the samples that completed it, to avoid zipper noise.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let mut start = 0;
+        while start < samples.len() {
+            let take = (self.block_len - self.block.len()).min(samples.len() - start);
+            let chunk = &mut samples[start..start + take];
+            self.block.extend_from_slice(chunk);
+
+            let from = self.gain;
+            if self.block.len() == self.block_len {
+                self.update_gain();
+                self.block.clear();
+            }
+            let to = self.gain;
+            let step = (to - from) / chunk.len() as f32;
+            for (i, sample) in chunk.iter_mut().enumerate() {
+                let gain = from + step * (i + 1) as f32;
+                *sample = (*sample * gain).clamp(-1.0, 1.0);
+            }
+            start += take;
+        }
+    }
+
+    fn update_gain(&mut self) {
+        let rms = (self.block.iter().map(|s| s * s).sum::<f32>() / self.block.len() as f32).sqrt();
+        // Freeze on silence, otherwise the gain would climb until the noise floor is loud
+        if rms < self.gate_rms {
+            return;
+        }
+        let desired = (self.target_rms / rms).clamp(MIN_GAIN, MAX_GAIN);
+        let rate = if desired > self.gain {
+            self.attack
+        } else {
+            self.release
+        };
+        self.gain += (desired - self.gain) * rate;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16_000;
+
+    fn sine(amplitude: f32, seconds: f32) -> Vec<f32> {
+        let len = (SAMPLE_RATE as f32 * seconds) as usize;
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                amplitude * (2.0 * std::f32::consts::PI * 220.0 * t).sin()
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Runs the signal through in 20ms callbacks and returns the RMS of the last 200ms
+    fn settled_rms(agc: &mut AutomaticGainControl, mut signal: Vec<f32>) -> f32 {
+        for chunk in signal.chunks_mut(320) {
+            agc.process(chunk);
+        }
+        rms(&signal[signal.len() - 3200..])
+    }
+
+    #[test]
+    fn quiet_speech_is_raised_to_target_within_bounded_time() {
+        let mut agc = AutomaticGainControl::new(SAMPLE_RATE, 0.1);
+        // An amplitude of 0.02 is an RMS of about 0.014, seven times below the target
+        let level = settled_rms(&mut agc, sine(0.02, 4.0));
+        assert!((level - 0.1).abs() < 0.01, "settled at {}", level);
+    }
+
+    #[test]
+    fn loud_speech_is_lowered_quickly() {
+        let mut agc = AutomaticGainControl::new(SAMPLE_RATE, 0.1);
+        let level = settled_rms(&mut agc, sine(0.8, 0.6));
+        assert!((level - 0.1).abs() < 0.01, "settled at {}", level);
+    }
+
+    #[test]
+    fn gain_is_frozen_on_silence() {
+        let mut agc = AutomaticGainControl::new(SAMPLE_RATE, 0.1);
+        settled_rms(&mut agc, sine(0.02, 4.0));
+        let gain = agc.gain();
+
+        let mut silence = sine(0.001, 2.0);
+        agc.process(&mut silence);
+        assert_eq!(agc.gain(), gain);
+        assert!(rms(&silence) < 0.01);
+    }
+}
@@ -0,0 +1,71 @@
+/// Which input channels make up the mono signal the pipeline works on
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ChannelMix {
+    /// Average of every channel the device delivers
+    #[default]
+    All,
+    /// Average of the given zero-based channels
+    Average(Vec<usize>),
+    /// A single zero-based channel
+    Select(usize),
+}
+
+impl ChannelMix {
+    /// Per-channel weights that turn a frame of `channels` samples into one mono sample.
+    ///
+    /// Channels the device doesn't have are ignored; when none of the requested channels
+    /// exist the mix falls back to averaging all of them rather than capturing silence.
+    pub fn weights(&self, channels: usize) -> Vec<f32> {
+        let mut weights = vec![0.0; channels];
+        let selected: Vec<usize> = match self {
+            ChannelMix::All => (0..channels).collect(),
+            ChannelMix::Average(selected) => {
+                let mut selected: Vec<usize> =
+                    selected.iter().copied().filter(|c| *c < channels).collect();
+                selected.sort_unstable();
+                selected.dedup();
+                selected
+            }
+            ChannelMix::Select(channel) if *channel < channels => vec![*channel],
+            ChannelMix::Select(_) => Vec::new(),
+        };
+
+        if selected.is_empty() {
+            if channels > 0 {
+                log::warn!(
+                    "Channel mix {:?} doesn't match a {}-channel device, averaging all channels",
+                    self,
+                    channels
+                );
+            }
+            return vec![1.0 / channels.max(1) as f32; channels];
+        }
+
+        let weight = 1.0 / selected.len() as f32;
+        for channel in selected {
+            weights[channel] = weight;
+        }
+        weights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_and_average_build_expected_weights() {
+        assert_eq!(ChannelMix::All.weights(2), vec![0.5, 0.5]);
+        assert_eq!(ChannelMix::Select(1).weights(4), vec![0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(
+            ChannelMix::Average(vec![3, 1, 3]).weights(4),
+            vec![0.0, 0.5, 0.0, 0.5]
+        );
+    }
+
+    #[test]
+    fn missing_channels_fall_back_to_all() {
+        assert_eq!(ChannelMix::Select(2).weights(2), vec![0.5, 0.5]);
+        assert_eq!(ChannelMix::Average(vec![5, 1]).weights(2), vec![0.0, 1.0]);
+    }
+}
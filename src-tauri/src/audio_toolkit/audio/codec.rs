@@ -0,0 +1,276 @@
+//! Recordings stored as FLAC or Ogg Opus instead of WAV, to keep the archive small.
+//!
+//! All formats hold 16kHz mono audio as captured. FLAC is lossless; Opus is lossy
+//! but at speech bitrates still transcribes like the original.
+
+use super::utils::{read_wav_file, to_whisper_input, write_wav_file};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use anyhow::{anyhow, bail, Context, Result};
+use log::debug;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Opus frames are 20ms, the usual size for speech
+const OPUS_FRAME_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize / 50;
+const OPUS_BITRATE: i32 = 24_000;
+/// Largest frame Opus produces, 120ms at 48kHz
+const OPUS_MAX_FRAME_SAMPLES: usize = 5_760;
+/// Ogg Opus granule positions always count 48kHz samples
+const OPUS_GRANULE_RATE: u64 = 48_000;
+const OGG_SERIAL: u32 = 0x4861_6e64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    Flac,
+    Opus,
+}
+
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Opus => "opus",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "wav" => Some(AudioFormat::Wav),
+            "flac" => Some(AudioFormat::Flac),
+            "opus" | "ogg" => Some(AudioFormat::Opus),
+            _ => None,
+        }
+    }
+}
+
+/// Writes 16kHz mono samples in the given format. Encoding is CPU bound, so call
+/// this from a blocking task.
+pub fn save_audio_file<P: AsRef<Path>>(
+    path: P,
+    samples: &[f32],
+    format: AudioFormat,
+) -> Result<()> {
+    let path = path.as_ref();
+    match format {
+        AudioFormat::Wav => write_wav_file(path, samples)?,
+        AudioFormat::Flac => std::fs::write(path, encode_flac(samples)?)?,
+        AudioFormat::Opus => write_opus(path, samples)?,
+    }
+    debug!("Saved {:?} recording: {:?}", format, path);
+    Ok(())
+}
+
+/// Reads a recording in any supported format as 16kHz mono samples, ready for
+/// transcription. The format is taken from the file extension.
+pub fn read_audio_file<P: AsRef<Path>>(path: P) -> Result<Vec<f32>> {
+    let path = path.as_ref();
+    match AudioFormat::from_path(path) {
+        Some(AudioFormat::Wav) => read_wav_file(path),
+        Some(AudioFormat::Flac) => read_flac(path),
+        Some(AudioFormat::Opus) => read_opus(path),
+        None => bail!("Unsupported audio file: {}", path.display()),
+    }
+}
+
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+fn encode_flac(samples: &[f32]) -> Result<Vec<u8>> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    let pcm: Vec<i32> = samples.iter().map(|s| to_i16(*s) as i32).collect();
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| anyhow!("Invalid FLAC encoder config: {:?}", e))?;
+    let source =
+        flacenc::source::MemSource::from_samples(&pcm, 1, 16, WHISPER_SAMPLE_RATE as usize);
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| anyhow!("FLAC encoding failed: {:?}", e))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| anyhow!("FLAC encoding failed: {:?}", e))?;
+    Ok(sink.as_slice().to_vec())
+}
+
+fn read_flac(path: &Path) -> Result<Vec<f32>> {
+    let mut reader = claxon::FlacReader::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let info = reader.streaminfo();
+    let scale = (1i64 << (info.bits_per_sample - 1)) as f32;
+    let interleaved: Vec<f32> = reader
+        .samples()
+        .map(|s| s.map(|s| s as f32 / scale))
+        .collect::<Result<_, _>>()?;
+    Ok(to_whisper_input(
+        &interleaved,
+        info.channels as u16,
+        info.sample_rate,
+    ))
+}
+
+/// Identification header of an Ogg Opus stream, RFC 7845 section 5.1
+fn opus_head() -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&0u16.to_le_bytes()); // pre-skip
+    head.extend_from_slice(&WHISPER_SAMPLE_RATE.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+    head
+}
+
+fn opus_tags() -> Vec<u8> {
+    let vendor = concat!("Handy ", env!("CARGO_PKG_VERSION"));
+    let mut tags = Vec::new();
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes()); // no user comments
+    tags
+}
+
+fn write_opus(path: &Path, samples: &[f32]) -> Result<()> {
+    use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+    let mut encoder = opus::Encoder::new(
+        WHISPER_SAMPLE_RATE,
+        opus::Channels::Mono,
+        opus::Application::Voip,
+    )?;
+    encoder.set_bitrate(opus::Bitrate::Bits(OPUS_BITRATE))?;
+
+    let mut writer = PacketWriter::new(BufWriter::new(File::create(path)?));
+    writer.write_packet(opus_head(), OGG_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+    writer.write_packet(opus_tags(), OGG_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+    let granule_scale = OPUS_GRANULE_RATE / WHISPER_SAMPLE_RATE as u64;
+    let frames: Vec<&[f32]> = samples.chunks(OPUS_FRAME_SAMPLES).collect();
+    let mut frame = vec![0.0; OPUS_FRAME_SAMPLES];
+    let mut position = 0u64;
+    for (i, chunk) in frames.iter().enumerate() {
+        // The last frame is padded, its granule position marks where the audio ends
+        frame.fill(0.0);
+        frame[..chunk.len()].copy_from_slice(chunk);
+        let packet = encoder.encode_vec_float(&frame, 4_000)?;
+        position += chunk.len() as u64;
+        let end = if i + 1 == frames.len() {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer.write_packet(packet, OGG_SERIAL, end, position * granule_scale)?;
+    }
+    if frames.is_empty() {
+        writer.write_packet(Vec::new(), OGG_SERIAL, PacketWriteEndInfo::EndStream, 0)?;
+    }
+    writer.into_inner().flush()?;
+    Ok(())
+}
+
+fn read_opus(path: &Path) -> Result<Vec<f32>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut reader = ogg::reading::PacketReader::new(BufReader::new(file));
+
+    let head = reader
+        .read_packet()?
+        .ok_or_else(|| anyhow!("Empty Ogg stream"))?;
+    if head.data.len() < 19 || &head.data[..8] != b"OpusHead" {
+        bail!("{} is not an Ogg Opus file", path.display());
+    }
+    let channels = match head.data[9] {
+        1 => opus::Channels::Mono,
+        2 => opus::Channels::Stereo,
+        n => bail!("Unsupported Opus channel count: {}", n),
+    };
+    let channel_count = head.data[9] as usize;
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+    // The tags header carries nothing needed for decoding
+    reader.read_packet()?;
+
+    // Decoding straight at 16kHz avoids a resampling pass
+    let decode_rate = WHISPER_SAMPLE_RATE;
+    let granule_scale = OPUS_GRANULE_RATE / decode_rate as u64;
+    let mut decoder = opus::Decoder::new(decode_rate, channels)?;
+    let mut buffer = vec![0.0f32; OPUS_MAX_FRAME_SAMPLES * channel_count];
+    let mut interleaved = Vec::new();
+    let mut end_granule = None;
+    while let Some(packet) = reader.read_packet()? {
+        if packet.last_in_stream() {
+            end_granule = Some(packet.absgp_page());
+        }
+        if packet.data.is_empty() {
+            continue;
+        }
+        let decoded = decoder.decode_float(&packet.data, &mut buffer, false)?;
+        interleaved.extend_from_slice(&buffer[..decoded * channel_count]);
+    }
+
+    let skip = (pre_skip / granule_scale) as usize * channel_count;
+    let mut interleaved = interleaved.split_off(skip.min(interleaved.len()));
+    if let Some(end) = end_granule {
+        let frames = (end.saturating_sub(pre_skip) / granule_scale) as usize;
+        interleaved.truncate(frames * channel_count);
+    }
+    Ok(to_whisper_input(
+        &interleaved,
+        channel_count as u16,
+        decode_rate,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.3 * (i as f32 * 2.0 * std::f32::consts::PI * 440.0 / 16_000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn format_follows_extension() {
+        assert_eq!(
+            AudioFormat::from_path(Path::new("handy-1.FLAC")),
+            Some(AudioFormat::Flac)
+        );
+        assert_eq!(
+            AudioFormat::from_path(Path::new("handy-1.opus")),
+            Some(AudioFormat::Opus)
+        );
+        assert_eq!(AudioFormat::from_path(Path::new("handy-1.mp3")), None);
+    }
+
+    #[test]
+    fn recordings_round_trip_in_every_format() {
+        let dir = std::env::temp_dir().join(format!("handy-codec-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Not a whole number of Opus frames, so the end trimming is exercised
+        let samples = tone(16_000 + 123);
+
+        for format in [AudioFormat::Wav, AudioFormat::Flac, AudioFormat::Opus] {
+            let path = dir.join(format!("round-trip.{}", format.extension()));
+            save_audio_file(&path, &samples, format).unwrap();
+            let decoded = read_audio_file(&path).unwrap();
+            assert_eq!(decoded.len(), samples.len(), "{:?}", format);
+            if format != AudioFormat::Opus {
+                let max_error = samples
+                    .iter()
+                    .zip(&decoded)
+                    .map(|(a, b)| (a - b).abs())
+                    .fold(0.0, f32::max);
+                assert!(max_error < 1e-3, "{:?} error {}", format, max_error);
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
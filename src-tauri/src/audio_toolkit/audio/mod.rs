@@ -1,12 +1,18 @@
 // Re-export all audio components
+mod agc;
+mod channel_mix;
+mod codec;
 mod device;
 mod recorder;
 mod resampler;
 mod utils;
 mod visualizer;
 
+pub use agc::AutomaticGainControl;
+pub use channel_mix::ChannelMix;
+pub use codec::{read_audio_file, save_audio_file, AudioFormat};
 pub use device::{list_input_devices, list_output_devices, CpalDeviceInfo};
-pub use recorder::AudioRecorder;
+pub use recorder::{AudioRecorder, DeviceRecovery, FrameTap};
 pub use resampler::FrameResampler;
-pub use utils::save_wav_file;
+pub use utils::{read_wav_file, save_wav_file};
 pub use visualizer::AudioVisualiser;
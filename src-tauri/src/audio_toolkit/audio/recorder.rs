@@ -1,7 +1,11 @@
 use std::{
+    collections::VecDeque,
     io::Error,
-    sync::{mpsc, Arc, Mutex},
-    time::Duration,
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 use cpal::{
@@ -10,7 +14,7 @@ use cpal::{
 };
 
 use crate::audio_toolkit::{
-    audio::{AudioVisualiser, FrameResampler},
+    audio::{AudioVisualiser, AutomaticGainControl, ChannelMix, FrameResampler},
     constants,
     vad::{self, VadFrame},
     VoiceActivityDetector,
@@ -19,15 +23,63 @@ use crate::audio_toolkit::{
 enum Cmd {
     Start,
     Stop(mpsc::Sender<Vec<f32>>),
+    /// Copy of the last samples recorded so far, the recording goes on
+    Tail(usize, mpsc::Sender<Vec<f32>>),
+    /// Takes the samples recorded so far, the recording goes on
+    Split(mpsc::Sender<Vec<f32>>),
     Shutdown,
 }
 
+/// What the capture stream hands to the consumer thread
+enum StreamMsg {
+    Samples(Vec<f32>),
+    /// The stream opened as `generation` reported an error and may have stopped
+    Error {
+        generation: u32,
+        message: String,
+    },
+}
+
+/// Outcome of a device error while the stream is open
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceRecovery {
+    /// Capture continues, on the default input instead of the chosen one when `fallback`
+    Recovered { fallback: bool },
+    /// No input could be reopened; the recording keeps what was captured before
+    Lost,
+}
+
+/// A stream reopened after an error
+struct Reopened {
+    generation: u32,
+    sample_rate: u32,
+    fallback: bool,
+}
+
+/// How long the failed device gets to come back before falling back to the default
+const RECOVERY_WINDOW: Duration = Duration::from_secs(2);
+const RECOVERY_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+/// Silence inserted where a recovered stream picks up, so the transcription sees
+/// a pause rather than two sentences spliced together
+const RECOVERY_GAP: Duration = Duration::from_millis(300);
+/// Commands are also checked this often when no audio arrives
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct AudioRecorder {
     device: Option<Device>,
     cmd_tx: Option<mpsc::Sender<Cmd>>,
     worker_handle: Option<std::thread::JoinHandle<()>>,
     vad: Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
     level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    recovery_cb: Option<Arc<dyn Fn(DeviceRecovery) + Send + Sync + 'static>>,
+    frame_tap: Arc<Mutex<Option<FrameTap>>>,
+    /// When the VAD last let speech through in the current recording
+    last_speech: Arc<Mutex<Option<Instant>>>,
+    /// Number of 16 kHz samples kept from before `start` and prepended to the recording
+    pre_roll_samples: Arc<AtomicUsize>,
+    /// AGC target RMS as f32 bits, 0 when AGC is off
+    agc_target: Arc<AtomicU32>,
+    channel_mix: ChannelMix,
 }
 
 impl AudioRecorder {
@@ -38,9 +90,64 @@ impl AudioRecorder {
             worker_handle: None,
             vad: None,
             level_cb: None,
+            recovery_cb: None,
+            frame_tap: Arc::new(Mutex::new(None)),
+            last_speech: Arc::new(Mutex::new(None)),
+            pre_roll_samples: Arc::new(AtomicUsize::new(0)),
+            agc_target: Arc::new(AtomicU32::new(0)),
+            channel_mix: ChannelMix::default(),
         })
     }
 
+    /// Number of channels the device is captured with, see `set_channel_mix`
+    pub fn input_channels(device: &Device) -> Option<u16> {
+        Self::get_preferred_config(device)
+            .ok()
+            .map(|config| config.channels())
+    }
+
+    /// Sample rate the device is captured at, before resampling to 16 kHz
+    pub fn input_sample_rate(device: &Device) -> Option<u32> {
+        Self::get_preferred_config(device)
+            .ok()
+            .map(|config| config.sample_rate().0)
+    }
+
+    /// Chooses the input channels that are mixed down to mono.
+    /// Takes effect the next time the stream is opened.
+    pub fn set_channel_mix(&mut self, mix: ChannelMix) {
+        self.channel_mix = mix;
+    }
+
+    /// Keeps this much audio from before `start` while the stream is open.
+    /// Takes effect immediately, also on an already open stream.
+    pub fn set_pre_roll(&self, duration: Duration) {
+        let samples =
+            (duration.as_millis() as usize) * constants::WHISPER_SAMPLE_RATE as usize / 1000;
+        self.pre_roll_samples.store(samples, Ordering::Relaxed);
+    }
+
+    /// Enables automatic gain control towards `target_rms`, or disables it with None.
+    /// Takes effect immediately, also on an already open stream.
+    pub fn set_agc(&self, target_rms: Option<f32>) {
+        let bits = target_rms.filter(|t| *t > 0.0).map_or(0, f32::to_bits);
+        self.agc_target.store(bits, Ordering::Relaxed);
+    }
+
+    /// Hands every 16 kHz frame captured between recordings to `tap`, or stops with
+    /// None. Called on the capture thread, so it must return quickly; frames are
+    /// only lent to it and never kept by the recorder.
+    /// Takes effect immediately, also on an already open stream.
+    pub fn set_frame_tap(&self, tap: Option<FrameTap>) {
+        *self.frame_tap.lock().unwrap() = tap;
+    }
+
+    /// Time since the VAD last heard speech in the current recording, None when
+    /// it hasn't heard any since `start`
+    pub fn since_speech(&self) -> Option<Duration> {
+        self.last_speech.lock().unwrap().map(|at| at.elapsed())
+    }
+
     pub fn with_vad(mut self, vad: Box<dyn VoiceActivityDetector>) -> Self {
         self.vad = Some(Arc::new(Mutex::new(vad)));
         self
@@ -54,12 +161,22 @@ impl AudioRecorder {
         self
     }
 
+    /// Called from the capture thread after the stream failed and was reopened or given
+    /// up on. Must not wait on the recorder, which is blocked until it returns.
+    pub fn with_recovery_callback<F>(mut self, cb: F) -> Self
+    where
+        F: Fn(DeviceRecovery) + Send + Sync + 'static,
+    {
+        self.recovery_cb = Some(Arc::new(cb));
+        self
+    }
+
     pub fn open(&mut self, device: Option<Device>) -> Result<(), Box<dyn std::error::Error>> {
         if self.worker_handle.is_some() {
             return Ok(()); // already open
         }
 
-        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
+        let (sample_tx, sample_rx) = mpsc::channel::<StreamMsg>();
         let (cmd_tx, cmd_rx) = mpsc::channel::<Cmd>();
 
         let host = crate::audio_toolkit::get_cpal_host();
@@ -72,52 +189,55 @@ impl AudioRecorder {
 
         let thread_device = device.clone();
         let vad = self.vad.clone();
-        // Move the optional level callback into the worker thread
+        // Move the optional callbacks into the worker thread
         let level_cb = self.level_cb.clone();
+        let recovery_cb = self.recovery_cb.clone();
+        let frame_tap = self.frame_tap.clone();
+        let last_speech = self.last_speech.clone();
+        let pre_roll_samples = self.pre_roll_samples.clone();
+        let agc_target = self.agc_target.clone();
+        let channel_mix = self.channel_mix.clone();
 
         let worker = std::thread::spawn(move || {
-            let config = AudioRecorder::get_preferred_config(&thread_device)
-                .expect("failed to fetch preferred config");
-
-            let sample_rate = config.sample_rate().0;
-            let channels = config.channels() as usize;
+            let (stream, sample_rate) =
+                match open_stream(&thread_device, &channel_mix, sample_tx.clone(), 0) {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        log::error!("Failed to open input stream: {}", e);
+                        return;
+                    }
+                };
+
+            // Owns the live stream, so it stays open while the consumer runs
+            let mut stream = Some(stream);
+            let mut generation = 0;
+            let reopen = move || {
+                // Release the failed stream before opening the device again
+                stream = None;
+                generation += 1;
+                let (new_stream, sample_rate, fallback) =
+                    reopen_stream(&thread_device, &channel_mix, &sample_tx, generation)?;
+                stream = Some(new_stream);
+                Ok(Reopened {
+                    generation,
+                    sample_rate,
+                    fallback,
+                })
+            };
 
-            log::info!(
-                "Using device: {:?}\nSample rate: {}\nChannels: {}\nFormat: {:?}",
-                thread_device.name(),
+            run_consumer(
                 sample_rate,
-                channels,
-                config.sample_format()
+                vad,
+                sample_rx,
+                cmd_rx,
+                level_cb,
+                recovery_cb,
+                frame_tap,
+                last_speech,
+                pre_roll_samples,
+                agc_target,
+                reopen,
             );
-
-            let stream = match config.sample_format() {
-                cpal::SampleFormat::U8 => {
-                    AudioRecorder::build_stream::<u8>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I8 => {
-                    AudioRecorder::build_stream::<i8>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I16 => {
-                    AudioRecorder::build_stream::<i16>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I32 => {
-                    AudioRecorder::build_stream::<i32>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::F32 => {
-                    AudioRecorder::build_stream::<f32>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                _ => panic!("unsupported sample format"),
-            };
-
-            stream.play().expect("failed to start stream");
-
-            // keep the stream alive while we process samples
-            run_consumer(sample_rate, vad, sample_rx, cmd_rx, level_cb);
             // stream is dropped here, after run_consumer returns
         });
 
@@ -128,6 +248,14 @@ impl AudioRecorder {
         Ok(())
     }
 
+    /// Whether the capture worker is still running. It exits on its own when the
+    /// device disappears or the stream fails to build.
+    pub fn is_alive(&self) -> bool {
+        self.worker_handle
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
     pub fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(tx) = &self.cmd_tx {
             tx.send(Cmd::Start)?;
@@ -135,6 +263,25 @@ impl AudioRecorder {
         Ok(())
     }
 
+    /// The last `samples` of the current recording, empty when not recording
+    pub fn tail(&self, samples: usize) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+        let (resp_tx, resp_rx) = mpsc::channel();
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Tail(samples, resp_tx))?;
+        }
+        Ok(resp_rx.recv()?)
+    }
+
+    /// The samples recorded since the start or the last split, without stopping.
+    /// The stream and the VAD carry on, so no audio is lost between the parts.
+    pub fn split(&self) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+        let (resp_tx, resp_rx) = mpsc::channel();
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Split(resp_tx))?;
+        }
+        Ok(resp_rx.recv()?)
+    }
+
     pub fn stop(&self) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
         let (resp_tx, resp_rx) = mpsc::channel();
         if let Some(tx) = &self.cmd_tx {
@@ -157,14 +304,17 @@ impl AudioRecorder {
     fn build_stream<T>(
         device: &cpal::Device,
         config: &cpal::SupportedStreamConfig,
-        sample_tx: mpsc::Sender<Vec<f32>>,
-        channels: usize,
+        sample_tx: mpsc::Sender<StreamMsg>,
+        weights: Vec<f32>,
+        generation: u32,
     ) -> Result<cpal::Stream, cpal::BuildStreamError>
     where
         T: Sample + SizedSample + Send + 'static,
         f32: cpal::FromSample<T>,
     {
         let mut output_buffer = Vec::new();
+        let channels = weights.len();
+        let error_tx = sample_tx.clone();
 
         let stream_cb = move |data: &[T], _: &cpal::InputCallbackInfo| {
             output_buffer.clear();
@@ -180,14 +330,17 @@ impl AudioRecorder {
                 for frame in data.chunks_exact(channels) {
                     let mono_sample = frame
                         .iter()
-                        .map(|&sample| sample.to_sample::<f32>())
-                        .sum::<f32>()
-                        / channels as f32;
+                        .zip(&weights)
+                        .map(|(&sample, weight)| sample.to_sample::<f32>() * weight)
+                        .sum::<f32>();
                     output_buffer.push(mono_sample);
                 }
             }
 
-            if sample_tx.send(output_buffer.clone()).is_err() {
+            if sample_tx
+                .send(StreamMsg::Samples(output_buffer.clone()))
+                .is_err()
+            {
                 log::error!("Failed to send samples");
             }
         };
@@ -195,7 +348,13 @@ impl AudioRecorder {
         device.build_input_stream(
             &config.clone().into(),
             stream_cb,
-            |err| log::error!("Stream error: {}", err),
+            move |err| {
+                log::error!("Stream error: {}", err);
+                let _ = error_tx.send(StreamMsg::Error {
+                    generation,
+                    message: err.to_string(),
+                });
+            },
             None,
         )
     }
@@ -239,95 +398,584 @@ impl AudioRecorder {
     }
 }
 
-fn run_consumer(
-    in_sample_rate: u32,
-    vad: Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
-    sample_rx: mpsc::Receiver<Vec<f32>>,
-    cmd_rx: mpsc::Receiver<Cmd>,
-    level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
-) {
-    let mut frame_resampler = FrameResampler::new(
-        in_sample_rate as usize,
-        constants::WHISPER_SAMPLE_RATE as usize,
-        Duration::from_millis(30),
+/// Opens and starts a capture stream, returning it with its sample rate
+fn open_stream(
+    device: &Device,
+    channel_mix: &ChannelMix,
+    sample_tx: mpsc::Sender<StreamMsg>,
+    generation: u32,
+) -> Result<(cpal::Stream, u32), String> {
+    let config = AudioRecorder::get_preferred_config(device)
+        .map_err(|e| format!("failed to fetch preferred config: {}", e))?;
+
+    let sample_rate = config.sample_rate().0;
+    let channels = config.channels() as usize;
+    // Built before resampling, which only ever sees the mono signal
+    let weights = channel_mix.weights(channels);
+
+    log::info!(
+        "Using device: {:?}\nSample rate: {}\nChannels: {} (mix {:?})\nFormat: {:?}",
+        device.name(),
+        sample_rate,
+        channels,
+        channel_mix,
+        config.sample_format()
     );
 
-    let mut processed_samples = Vec::<f32>::new();
-    let mut recording = false;
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::U8 => {
+            AudioRecorder::build_stream::<u8>(device, &config, sample_tx, weights, generation)
+        }
+        cpal::SampleFormat::I8 => {
+            AudioRecorder::build_stream::<i8>(device, &config, sample_tx, weights, generation)
+        }
+        cpal::SampleFormat::I16 => {
+            AudioRecorder::build_stream::<i16>(device, &config, sample_tx, weights, generation)
+        }
+        cpal::SampleFormat::I32 => {
+            AudioRecorder::build_stream::<i32>(device, &config, sample_tx, weights, generation)
+        }
+        cpal::SampleFormat::F32 => {
+            AudioRecorder::build_stream::<f32>(device, &config, sample_tx, weights, generation)
+        }
+        format => return Err(format!("unsupported sample format {:?}", format)),
+    }
+    .map_err(|e| format!("failed to build stream: {}", e))?;
+
+    stream
+        .play()
+        .map_err(|e| format!("failed to start stream: {}", e))?;
+    Ok((stream, sample_rate))
+}
+
+/// Reopens `device` after a stream error, retrying while it comes back, then falls
+/// back to the default input. Returns the stream, its rate and whether it's the fallback.
+fn reopen_stream(
+    device: &Device,
+    channel_mix: &ChannelMix,
+    sample_tx: &mpsc::Sender<StreamMsg>,
+    generation: u32,
+) -> Result<(cpal::Stream, u32, bool), String> {
+    let deadline = Instant::now() + RECOVERY_WINDOW;
+    loop {
+        match open_stream(device, channel_mix, sample_tx.clone(), generation) {
+            Ok((stream, sample_rate)) => return Ok((stream, sample_rate, false)),
+            Err(e) => log::warn!("Reopening input device failed: {}", e),
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        std::thread::sleep(RECOVERY_RETRY_INTERVAL);
+    }
+
+    let fallback = crate::audio_toolkit::get_cpal_host()
+        .default_input_device()
+        .ok_or_else(|| "no input device available".to_string())?;
+    // The default device's channels don't match the configured mix
+    let (stream, sample_rate) =
+        open_stream(&fallback, &ChannelMix::All, sample_tx.clone(), generation)?;
+    Ok((stream, sample_rate, true))
+}
 
-    // ---------- spectrum visualisation setup ---------------------------- //
-    const BUCKETS: usize = 16;
-    const WINDOW_SIZE: usize = 512;
-    let mut visualizer = AudioVisualiser::new(
+type Vad = Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>;
+type LevelCallback = Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>;
+type RecoveryCallback = Arc<dyn Fn(DeviceRecovery) + Send + Sync + 'static>;
+/// Receives the 16 kHz frames captured between recordings, see `set_frame_tap`
+pub type FrameTap = Arc<dyn Fn(&[f32]) + Send + Sync + 'static>;
+
+// ---------- spectrum visualisation setup ---------------------------- //
+const BUCKETS: usize = 16;
+const WINDOW_SIZE: usize = 512;
+
+fn new_visualizer(in_sample_rate: u32) -> AudioVisualiser {
+    AudioVisualiser::new(
         in_sample_rate,
         WINDOW_SIZE,
         BUCKETS,
         400.0,  // vocal_min_hz
         4000.0, // vocal_max_hz
-    );
+    )
+}
 
-    fn handle_frame(
-        samples: &[f32],
-        recording: bool,
-        vad: &Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
-        out_buf: &mut Vec<f32>,
-    ) {
-        if !recording {
-            return;
+fn new_resampler(in_sample_rate: u32) -> FrameResampler {
+    FrameResampler::new(
+        in_sample_rate as usize,
+        constants::WHISPER_SAMPLE_RATE as usize,
+        Duration::from_millis(30),
+    )
+}
+
+/// State of the consumer thread, which turns stream audio into the recording
+struct Capture {
+    in_sample_rate: u32,
+    /// Stream whose errors are acted on; older streams' errors are stale
+    generation: u32,
+    vad: Option<Vad>,
+    level_cb: Option<LevelCallback>,
+    recovery_cb: Option<RecoveryCallback>,
+    frame_tap: Arc<Mutex<Option<FrameTap>>>,
+    last_speech: Arc<Mutex<Option<Instant>>>,
+    pre_roll_samples: Arc<AtomicUsize>,
+    agc_target: Arc<AtomicU32>,
+    frame_resampler: FrameResampler,
+    visualizer: AudioVisualiser,
+    processed_samples: Vec<f32>,
+    recording: bool,
+    pre_roll: PreRollBuffer,
+    agc: Option<AutomaticGainControl>,
+    /// The stream failed for good during the recording
+    lost: bool,
+}
+
+impl Capture {
+    fn new(
+        in_sample_rate: u32,
+        vad: Option<Vad>,
+        level_cb: Option<LevelCallback>,
+        recovery_cb: Option<RecoveryCallback>,
+        frame_tap: Arc<Mutex<Option<FrameTap>>>,
+        last_speech: Arc<Mutex<Option<Instant>>>,
+        pre_roll_samples: Arc<AtomicUsize>,
+        agc_target: Arc<AtomicU32>,
+    ) -> Self {
+        Self {
+            in_sample_rate,
+            generation: 0,
+            vad,
+            level_cb,
+            recovery_cb,
+            frame_tap,
+            last_speech,
+            pre_roll_samples,
+            agc_target,
+            frame_resampler: new_resampler(in_sample_rate),
+            visualizer: new_visualizer(in_sample_rate),
+            processed_samples: Vec::new(),
+            recording: false,
+            pre_roll: PreRollBuffer::default(),
+            agc: None,
+            lost: false,
         }
+    }
 
-        if let Some(vad_arc) = vad {
-            let mut det = vad_arc.lock().unwrap();
-            match det.push_frame(samples).unwrap_or(VadFrame::Speech(samples)) {
-                VadFrame::Speech(buf) => out_buf.extend_from_slice(buf),
-                VadFrame::Noise => {}
+    /// Handles a stream message. Returns false when the stream is gone and the
+    /// consumer should exit.
+    fn handle(
+        &mut self,
+        msg: StreamMsg,
+        reopen: &mut impl FnMut() -> Result<Reopened, String>,
+    ) -> bool {
+        match msg {
+            StreamMsg::Samples(raw) => {
+                self.process(raw);
+                true
+            }
+            StreamMsg::Error {
+                generation,
+                message,
+            } => {
+                if generation != self.generation || self.lost {
+                    return true;
+                }
+                log::warn!("Input stream failed, reopening: {}", message);
+                self.recover(reopen)
             }
-        } else {
-            out_buf.extend_from_slice(samples);
         }
     }
 
-    loop {
-        let raw = match sample_rx.recv() {
-            Ok(s) => s,
-            Err(_) => break, // stream closed
-        };
+    fn process(&mut self, mut raw: Vec<f32>) {
+        // ---------- gain control ----------------------------------------- //
+        // Ahead of the meter and the VAD so both see the levelled signal
+        match self.agc_target.load(Ordering::Relaxed) {
+            0 => self.agc = None,
+            bits => {
+                let target = f32::from_bits(bits);
+                if self.agc.as_ref().map(|a| a.target_rms()) != Some(target) {
+                    self.agc = Some(AutomaticGainControl::new(self.in_sample_rate, target));
+                }
+            }
+        }
+        if let Some(agc) = self.agc.as_mut() {
+            agc.process(&mut raw);
+        }
 
         // ---------- spectrum processing ---------------------------------- //
-        if let Some(buckets) = visualizer.feed(&raw) {
-            if let Some(cb) = &level_cb {
-                cb(buckets);
+        // Levels are only reported while recording; a warm stream stays silent
+        if self.recording {
+            if let Some(buckets) = self.visualizer.feed(&raw) {
+                if let Some(cb) = &self.level_cb {
+                    cb(buckets);
+                }
             }
         }
 
         // ---------- existing pipeline ------------------------------------ //
-        frame_resampler.push(&raw, &mut |frame: &[f32]| {
-            handle_frame(frame, recording, &vad, &mut processed_samples)
+        let pre_roll_capacity = self.pre_roll_samples.load(Ordering::Relaxed);
+        let recording = self.recording;
+        let tap = self.frame_tap.lock().unwrap().clone();
+        let vad = &self.vad;
+        let last_speech = &self.last_speech;
+        let pre_roll = &mut self.pre_roll;
+        let processed_samples = &mut self.processed_samples;
+        self.frame_resampler.push(&raw, &mut |frame: &[f32]| {
+            if !recording {
+                pre_roll.push(frame, pre_roll_capacity);
+                if let Some(tap) = &tap {
+                    tap(frame);
+                }
+            }
+            if handle_frame(frame, recording, vad, processed_samples) {
+                *last_speech.lock().unwrap() = Some(Instant::now());
+            }
+        });
+    }
+
+    fn start(&mut self) {
+        self.processed_samples.clear();
+        // Audio from just before the key press; it skips the VAD, which has
+        // no speech context yet and would cut the leading word
+        self.pre_roll.drain_into(&mut self.processed_samples);
+        self.recording = true;
+        *self.last_speech.lock().unwrap() = None;
+        self.visualizer.reset(); // Reset visualization buffer
+        if let Some(v) = &self.vad {
+            v.lock().unwrap().reset();
+        }
+    }
+
+    fn tail(&self, samples: usize) -> Vec<f32> {
+        if !self.recording {
+            return Vec::new();
+        }
+        let start = self.processed_samples.len().saturating_sub(samples);
+        self.processed_samples[start..].to_vec()
+    }
+
+    /// Speech is timed again from the split, so a pause ends each part anew
+    fn split(&mut self) -> Vec<f32> {
+        if !self.recording {
+            return Vec::new();
+        }
+        *self.last_speech.lock().unwrap() = None;
+        std::mem::take(&mut self.processed_samples)
+    }
+
+    fn finish(&mut self) -> Vec<f32> {
+        self.recording = false;
+        self.flush_resampler();
+        std::mem::take(&mut self.processed_samples)
+    }
+
+    fn flush_resampler(&mut self) {
+        let vad = &self.vad;
+        let processed_samples = &mut self.processed_samples;
+        self.frame_resampler.finish(&mut |frame: &[f32]| {
+            // we still want to process the last few frames
+            handle_frame(frame, true, vad, processed_samples);
         });
+    }
+
+    /// Reopens the stream after an error. Returns false when nothing could be
+    /// reopened and no recording needs the consumer anymore.
+    fn recover(&mut self, reopen: &mut impl FnMut() -> Result<Reopened, String>) -> bool {
+        match reopen() {
+            Ok(reopened) => {
+                log::info!(
+                    "Input stream reopened at {} Hz{}",
+                    reopened.sample_rate,
+                    if reopened.fallback {
+                        " on the default device"
+                    } else {
+                        ""
+                    }
+                );
+                self.generation = reopened.generation;
+                self.bridge_gap(reopened.sample_rate);
+                if self.recording {
+                    if let Some(cb) = &self.recovery_cb {
+                        cb(DeviceRecovery::Recovered {
+                            fallback: reopened.fallback,
+                        });
+                    }
+                }
+                true
+            }
+            Err(e) => {
+                log::error!("Input device lost: {}", e);
+                if !self.recording {
+                    return false;
+                }
+                // Keep what was recorded until the caller stops the recording
+                self.lost = true;
+                if let Some(cb) = &self.recovery_cb {
+                    cb(DeviceRecovery::Lost);
+                }
+                true
+            }
+        }
+    }
+
+    /// Joins the audio before and after a reopened stream with a short silence
+    fn bridge_gap(&mut self, sample_rate: u32) {
+        if self.recording {
+            self.flush_resampler();
+            let gap =
+                RECOVERY_GAP.as_millis() as usize * constants::WHISPER_SAMPLE_RATE as usize / 1000;
+            self.processed_samples
+                .extend(std::iter::repeat(0.0).take(gap));
+        }
+        // The reopened device may run at another rate
+        self.in_sample_rate = sample_rate;
+        self.frame_resampler = new_resampler(sample_rate);
+        self.visualizer = new_visualizer(sample_rate);
+        self.agc = None;
+    }
+}
+
+/// Adds the frame to the recording if the VAD lets it through, returning whether it did
+fn handle_frame(
+    samples: &[f32],
+    recording: bool,
+    vad: &Option<Vad>,
+    out_buf: &mut Vec<f32>,
+) -> bool {
+    if !recording {
+        return false;
+    }
+
+    if let Some(vad_arc) = vad {
+        let mut det = vad_arc.lock().unwrap();
+        match det.push_frame(samples).unwrap_or(VadFrame::Speech(samples)) {
+            VadFrame::Speech(buf) => {
+                out_buf.extend_from_slice(buf);
+                true
+            }
+            VadFrame::Noise => false,
+        }
+    } else {
+        out_buf.extend_from_slice(samples);
+        true
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_consumer(
+    in_sample_rate: u32,
+    vad: Option<Vad>,
+    sample_rx: mpsc::Receiver<StreamMsg>,
+    cmd_rx: mpsc::Receiver<Cmd>,
+    level_cb: Option<LevelCallback>,
+    recovery_cb: Option<RecoveryCallback>,
+    frame_tap: Arc<Mutex<Option<FrameTap>>>,
+    last_speech: Arc<Mutex<Option<Instant>>>,
+    pre_roll_samples: Arc<AtomicUsize>,
+    agc_target: Arc<AtomicU32>,
+    mut reopen: impl FnMut() -> Result<Reopened, String>,
+) {
+    let mut capture = Capture::new(
+        in_sample_rate,
+        vad,
+        level_cb,
+        recovery_cb,
+        frame_tap,
+        last_speech,
+        pre_roll_samples,
+        agc_target,
+    );
+
+    loop {
+        // Polled, so commands are still answered once a failed stream goes quiet
+        match sample_rx.recv_timeout(COMMAND_POLL_INTERVAL) {
+            Ok(msg) => {
+                if !capture.handle(msg, &mut reopen) {
+                    return;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break, // stream closed
+        }
 
         // non-blocking check for a command
         while let Ok(cmd) = cmd_rx.try_recv() {
             match cmd {
-                Cmd::Start => {
-                    processed_samples.clear();
-                    recording = true;
-                    visualizer.reset(); // Reset visualization buffer
-                    if let Some(v) = &vad {
-                        v.lock().unwrap().reset();
+                Cmd::Start => capture.start(),
+                Cmd::Stop(reply_tx) => {
+                    // Audio that arrived before the stop belongs to the recording
+                    while let Ok(msg) = sample_rx.try_recv() {
+                        if !capture.handle(msg, &mut reopen) {
+                            break;
+                        }
+                    }
+                    let _ = reply_tx.send(capture.finish());
+                    if capture.lost {
+                        return;
                     }
                 }
-                Cmd::Stop(reply_tx) => {
-                    recording = false;
-
-                    frame_resampler.finish(&mut |frame: &[f32]| {
-                        // we still want to process the last few frames
-                        handle_frame(frame, true, &vad, &mut processed_samples)
-                    });
-
-                    let _ = reply_tx.send(std::mem::take(&mut processed_samples));
+                Cmd::Tail(samples, reply_tx) => {
+                    let _ = reply_tx.send(capture.tail(samples));
+                }
+                Cmd::Split(reply_tx) => {
+                    while let Ok(msg) = sample_rx.try_recv() {
+                        if !capture.handle(msg, &mut reopen) {
+                            break;
+                        }
+                    }
+                    let _ = reply_tx.send(capture.split());
                 }
                 Cmd::Shutdown => return,
             }
         }
     }
 }
+
+/// Rolling window of the most recent samples captured while not recording
+#[derive(Default)]
+struct PreRollBuffer {
+    samples: VecDeque<f32>,
+}
+
+impl PreRollBuffer {
+    fn push(&mut self, frame: &[f32], capacity: usize) {
+        if capacity == 0 {
+            self.samples.clear();
+            return;
+        }
+        self.samples.extend(frame);
+        let excess = self.samples.len().saturating_sub(capacity);
+        self.samples.drain(..excess);
+    }
+
+    fn drain_into(&mut self, out: &mut Vec<f32>) {
+        out.extend(self.samples.drain(..));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pre_roll_keeps_only_the_latest_samples() {
+        let mut buffer = PreRollBuffer::default();
+        buffer.push(&[1.0, 2.0, 3.0], 4);
+        buffer.push(&[4.0, 5.0], 4);
+
+        let mut out = vec![0.0];
+        buffer.drain_into(&mut out);
+        assert_eq!(out, vec![0.0, 2.0, 3.0, 4.0, 5.0]);
+
+        buffer.drain_into(&mut out);
+        assert_eq!(out.len(), 5);
+    }
+
+    #[test]
+    fn pre_roll_disabled_holds_nothing() {
+        let mut buffer = PreRollBuffer::default();
+        buffer.push(&[1.0, 2.0], 4);
+        buffer.push(&[3.0], 0);
+
+        let mut out = Vec::new();
+        buffer.drain_into(&mut out);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn recording_survives_a_reopened_stream() {
+        let (sample_tx, sample_rx) = mpsc::channel();
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (reply_tx, reply_rx) = mpsc::channel();
+
+        // Whole 30ms frames, so flushing the resampler adds no padding
+        let before = vec![0.5; 960];
+        let after = vec![0.25; 480];
+        cmd_tx.send(Cmd::Start).unwrap();
+        sample_tx.send(StreamMsg::Samples(Vec::new())).unwrap();
+        sample_tx.send(StreamMsg::Samples(before.clone())).unwrap();
+        sample_tx
+            .send(StreamMsg::Error {
+                generation: 0,
+                message: "device unplugged".into(),
+            })
+            .unwrap();
+        sample_tx.send(StreamMsg::Samples(after.clone())).unwrap();
+        cmd_tx.send(Cmd::Stop(reply_tx)).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+
+        let recovered = Arc::new(Mutex::new(Vec::new()));
+        let events = recovered.clone();
+        let mut reopens = 0;
+        run_consumer(
+            constants::WHISPER_SAMPLE_RATE,
+            None,
+            sample_rx,
+            cmd_rx,
+            None,
+            Some(Arc::new(move |r| events.lock().unwrap().push(r))),
+            Arc::new(Mutex::new(None)),
+            Arc::new(Mutex::new(None)),
+            Arc::new(AtomicUsize::new(0)),
+            Arc::new(AtomicU32::new(0)),
+            || {
+                reopens += 1;
+                Ok(Reopened {
+                    generation: reopens,
+                    sample_rate: constants::WHISPER_SAMPLE_RATE,
+                    fallback: false,
+                })
+            },
+        );
+
+        let samples = reply_rx.recv().unwrap();
+        let gap = RECOVERY_GAP.as_millis() as usize * 16;
+        assert_eq!(samples.len(), before.len() + gap + after.len());
+        assert_eq!(&samples[..before.len()], &before[..]);
+        assert!(samples[before.len()..before.len() + gap]
+            .iter()
+            .all(|s| *s == 0.0));
+        assert_eq!(&samples[before.len() + gap..], &after[..]);
+        assert_eq!(
+            *recovered.lock().unwrap(),
+            vec![DeviceRecovery::Recovered { fallback: false }]
+        );
+    }
+
+    #[test]
+    fn split_parts_add_up_to_the_recording() {
+        let (sample_tx, sample_rx) = mpsc::channel();
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let consumer = std::thread::spawn(move || {
+            run_consumer(
+                constants::WHISPER_SAMPLE_RATE,
+                None,
+                sample_rx,
+                cmd_rx,
+                None,
+                None,
+                Arc::new(Mutex::new(None)),
+                Arc::new(Mutex::new(None)),
+                Arc::new(AtomicUsize::new(0)),
+                Arc::new(AtomicU32::new(0)),
+                || Err("no device".to_string()),
+            )
+        });
+
+        // Whole 30ms frames, so flushing the resampler adds no padding
+        let first = vec![0.5; 960];
+        let second = vec![0.25; 480];
+        cmd_tx.send(Cmd::Start).unwrap();
+        // Commands are handled after each stream message, so Start comes first
+        sample_tx.send(StreamMsg::Samples(Vec::new())).unwrap();
+        sample_tx.send(StreamMsg::Samples(first.clone())).unwrap();
+        let (reply_tx, reply_rx) = mpsc::channel();
+        cmd_tx.send(Cmd::Split(reply_tx)).unwrap();
+        assert_eq!(reply_rx.recv().unwrap(), first);
+
+        sample_tx.send(StreamMsg::Samples(second.clone())).unwrap();
+        let (reply_tx, reply_rx) = mpsc::channel();
+        cmd_tx.send(Cmd::Stop(reply_tx)).unwrap();
+        assert_eq!(reply_rx.recv().unwrap(), second);
+
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        consumer.join().unwrap();
+    }
+}
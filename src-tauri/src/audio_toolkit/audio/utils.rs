@@ -1,10 +1,19 @@
+use super::FrameResampler;
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
 use anyhow::Result;
-use hound::{WavSpec, WavWriter};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
 use log::debug;
 use std::path::Path;
+use std::time::Duration;
 
 /// Save audio samples as a WAV file
 pub async fn save_wav_file<P: AsRef<Path>>(file_path: P, samples: &[f32]) -> Result<()> {
+    write_wav_file(file_path.as_ref(), samples)?;
+    debug!("Saved WAV file: {:?}", file_path.as_ref());
+    Ok(())
+}
+
+pub(super) fn write_wav_file(file_path: &Path, samples: &[f32]) -> Result<()> {
     let spec = WavSpec {
         channels: 1,
         sample_rate: 16000,
@@ -12,7 +21,7 @@ pub async fn save_wav_file<P: AsRef<Path>>(file_path: P, samples: &[f32]) -> Res
         sample_format: hound::SampleFormat::Int,
     };
 
-    let mut writer = WavWriter::create(file_path.as_ref(), spec)?;
+    let mut writer = WavWriter::create(file_path, spec)?;
 
     // Convert f32 samples to i16 for WAV
     for sample in samples {
@@ -21,6 +30,55 @@ pub async fn save_wav_file<P: AsRef<Path>>(file_path: P, samples: &[f32]) -> Res
     }
 
     writer.finalize()?;
-    debug!("Saved WAV file: {:?}", file_path.as_ref());
     Ok(())
 }
+
+/// Read a WAV file as 16kHz mono samples, ready for transcription
+pub fn read_wav_file<P: AsRef<Path>>(file_path: P) -> Result<Vec<f32>> {
+    let mut reader = WavReader::open(file_path.as_ref())?;
+    let spec = reader.spec();
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    let samples = to_whisper_input(&interleaved, spec.channels, spec.sample_rate);
+    debug!(
+        "Read WAV file {:?}: {} Hz, {} channel(s), {} samples",
+        file_path.as_ref(),
+        spec.sample_rate,
+        spec.channels,
+        samples.len()
+    );
+    Ok(samples)
+}
+
+/// Downmixes interleaved samples to mono and resamples them to 16kHz
+pub(super) fn to_whisper_input(interleaved: &[f32], channels: u16, sample_rate: u32) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    let mono: Vec<f32> = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+
+    if sample_rate == WHISPER_SAMPLE_RATE {
+        return mono;
+    }
+
+    let mut resampler = FrameResampler::new(
+        sample_rate as usize,
+        WHISPER_SAMPLE_RATE as usize,
+        Duration::from_millis(30),
+    );
+    let mut samples = Vec::with_capacity(mono.len());
+    resampler.push(&mono, |frame| samples.extend_from_slice(frame));
+    resampler.finish(|frame| samples.extend_from_slice(frame));
+    samples
+}
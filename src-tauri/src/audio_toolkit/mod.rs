@@ -5,8 +5,13 @@ pub mod utils;
 pub mod vad;
 
 pub use audio::{
-    list_input_devices, list_output_devices, save_wav_file, AudioRecorder, CpalDeviceInfo,
+    list_input_devices, list_output_devices, read_audio_file, read_wav_file, save_audio_file,
+    save_wav_file, AudioFormat, AudioRecorder, ChannelMix, CpalDeviceInfo, DeviceRecovery,
+    FrameTap,
+};
+pub use text::{
+    apply_custom_words, apply_custom_words_with_changes, collapse_repeated_phrases,
+    filter_transcription_output, is_discardable_transcription,
 };
-pub use text::{apply_custom_words, filter_transcription_output};
 pub use utils::get_cpal_host;
 pub use vad::{SileroVad, VoiceActivityDetector};
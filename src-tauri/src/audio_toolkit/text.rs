@@ -1,12 +1,89 @@
 use natural::phonetics::soundex;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use strsim::levenshtein;
+use strsim::{levenshtein, normalized_damerau_levenshtein};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Scores how far a transcribed word is from a custom word, both lowercase, from
+/// 0.0 (the same) to 1.0 (nothing alike). The closest custom word scoring under
+/// the correction threshold replaces the transcribed one.
+pub trait WordMatcher {
+    fn distance(&self, word: &str, custom_word: &str) -> f64;
+}
+
+/// Edit distance, with words that also sound alike scored much closer. Works well
+/// for English and for misheard names and jargon.
+pub struct Levenshtein;
+
+/// Like `Levenshtein`, but two swapped neighbouring letters count as a single
+/// edit. Suits models that garble letter order, such as "Stopify" for "Spotify".
+pub struct DamerauLevenshtein;
+
+/// Jaro similarity, which counts shared letters in roughly the same order rather
+/// than exact edits. Forgiving of a differing accent or ending, so it suits short
+/// words and inflected or accented languages; raise the threshold carefully.
+pub struct Jaro;
+
+/// Only words that sound alike, ranked by edit distance. Suits phonetic mistakes
+/// like "Smyth" for "Smith" while leaving words that merely look alike untouched.
+/// Soundex is built for English names, so it helps little in other languages.
+pub struct Phonetic;
+
+/// Factor applied to the distance of words with the same Soundex code
+const PHONETIC_BOOST: f64 = 0.3;
+
+/// Levenshtein distance relative to the longer word
+fn normalized_levenshtein(word: &str, custom_word: &str) -> f64 {
+    let max_len = word.len().max(custom_word.len()) as f64;
+    if max_len > 0.0 {
+        levenshtein(word, custom_word) as f64 / max_len
+    } else {
+        1.0
+    }
+}
+
+fn boost_phonetic_match(word: &str, custom_word: &str, distance: f64) -> f64 {
+    if soundex(word, custom_word) {
+        distance * PHONETIC_BOOST
+    } else {
+        distance
+    }
+}
+
+impl WordMatcher for Levenshtein {
+    fn distance(&self, word: &str, custom_word: &str) -> f64 {
+        let distance = normalized_levenshtein(word, custom_word);
+        boost_phonetic_match(word, custom_word, distance)
+    }
+}
+
+impl WordMatcher for DamerauLevenshtein {
+    fn distance(&self, word: &str, custom_word: &str) -> f64 {
+        let distance = 1.0 - normalized_damerau_levenshtein(word, custom_word);
+        boost_phonetic_match(word, custom_word, distance)
+    }
+}
+
+impl WordMatcher for Jaro {
+    fn distance(&self, word: &str, custom_word: &str) -> f64 {
+        1.0 - strsim::jaro(word, custom_word)
+    }
+}
+
+impl WordMatcher for Phonetic {
+    fn distance(&self, word: &str, custom_word: &str) -> f64 {
+        if soundex(word, custom_word) {
+            normalized_levenshtein(word, custom_word) * PHONETIC_BOOST
+        } else {
+            1.0
+        }
+    }
+}
 
 /// Applies custom word corrections to transcribed text using fuzzy matching
 ///
 /// This function corrects words in the input text by finding the best matches
-/// from a list of custom words using a combination of:
+/// from a list of custom words using `Levenshtein`, a combination of:
 /// - Levenshtein distance for string similarity
 /// - Soundex phonetic matching for pronunciation similarity
 ///
@@ -18,8 +95,20 @@ use strsim::levenshtein;
 /// # Returns
 /// The corrected text with custom words applied
 pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -> String {
+    apply_custom_words_with_changes(text, custom_words, threshold, &Levenshtein).0
+}
+
+/// Like `apply_custom_words` with the given matcher, also returning each word that
+/// was replaced as `(original, replacement)`, in text order. Matches that leave a
+/// word unchanged aren't listed.
+pub fn apply_custom_words_with_changes(
+    text: &str,
+    custom_words: &[String],
+    threshold: f64,
+    matcher: &dyn WordMatcher,
+) -> (String, Vec<(String, String)>) {
     if custom_words.is_empty() {
-        return text.to_string();
+        return (text.to_string(), Vec::new());
     }
 
     // Pre-compute lowercase versions to avoid repeated allocations
@@ -27,6 +116,7 @@ pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -
 
     let words: Vec<&str> = text.split_whitespace().collect();
     let mut corrected_words = Vec::new();
+    let mut changes = Vec::new();
 
     for word in words {
         let cleaned_word = word
@@ -54,24 +144,7 @@ pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -
                 continue;
             }
 
-            // Calculate Levenshtein distance (normalized by length)
-            let levenshtein_dist = levenshtein(&cleaned_word, custom_word_lower);
-            let max_len = cleaned_word.len().max(custom_word_lower.len()) as f64;
-            let levenshtein_score = if max_len > 0.0 {
-                levenshtein_dist as f64 / max_len
-            } else {
-                1.0
-            };
-
-            // Calculate phonetic similarity using Soundex
-            let phonetic_match = soundex(&cleaned_word, custom_word_lower);
-
-            // Combine scores: favor phonetic matches, but also consider string similarity
-            let combined_score = if phonetic_match {
-                levenshtein_score * 0.3 // Give significant boost to phonetic matches
-            } else {
-                levenshtein_score
-            };
+            let combined_score = matcher.distance(&cleaned_word, custom_word_lower);
 
             // Accept if the score is good enough (configurable threshold)
             if combined_score < threshold && combined_score < best_score {
@@ -86,13 +159,17 @@ pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -
 
             // Preserve punctuation from original word
             let (prefix, suffix) = extract_punctuation(word);
-            corrected_words.push(format!("{}{}{}", prefix, corrected, suffix));
+            let corrected = format!("{}{}{}", prefix, corrected, suffix);
+            if corrected != word {
+                changes.push((word.to_string(), corrected.clone()));
+            }
+            corrected_words.push(corrected);
         } else {
             corrected_words.push(word.to_string());
         }
     }
 
-    corrected_words.join(" ")
+    (corrected_words.join(" "), changes)
 }
 
 /// Preserves the case pattern of the original word when applying a replacement
@@ -110,28 +187,26 @@ fn preserve_case_pattern(original: &str, replacement: &str) -> String {
     }
 }
 
-/// Extracts punctuation prefix and suffix from a word
+/// Extracts punctuation prefix and suffix from a word. Works on whole graphemes, so
+/// a letter keeps its combining marks and multi-byte punctuation is never split.
 fn extract_punctuation(word: &str) -> (&str, &str) {
-    let prefix_end = word.chars().take_while(|c| !c.is_alphabetic()).count();
-    let suffix_start = word
-        .char_indices()
+    let is_punctuation = |grapheme: &&str| !grapheme.chars().any(char::is_alphabetic);
+    let prefix_len: usize = word
+        .graphemes(true)
+        .take_while(is_punctuation)
+        .map(str::len)
+        .sum();
+    if prefix_len == word.len() {
+        return (word, "");
+    }
+    let suffix_len: usize = word
+        .graphemes(true)
         .rev()
-        .take_while(|(_, c)| !c.is_alphabetic())
-        .count();
-
-    let prefix = if prefix_end > 0 {
-        &word[..prefix_end]
-    } else {
-        ""
-    };
+        .take_while(is_punctuation)
+        .map(str::len)
+        .sum();
 
-    let suffix = if suffix_start > 0 {
-        &word[word.len() - suffix_start..]
-    } else {
-        ""
-    };
-
-    (prefix, suffix)
+    (&word[..prefix_len], &word[word.len() - suffix_len..])
 }
 
 /// Filler words to remove from transcriptions
@@ -158,7 +233,7 @@ fn collapse_stutters(text: &str) -> String {
         let word_lower = word.to_lowercase();
 
         // Only process 1-2 letter words
-        if word_lower.len() <= 2 && word_lower.chars().all(|c| c.is_alphabetic()) {
+        if word_lower.chars().count() <= 2 && word_lower.chars().all(|c| c.is_alphabetic()) {
             // Count consecutive repetitions (case-insensitive)
             let mut count = 1;
             while i + count < words.len() && words[i + count].to_lowercase() == word_lower {
@@ -182,6 +257,97 @@ fn collapse_stutters(text: &str) -> String {
     result.join(" ")
 }
 
+/// Longest phrase, in words, checked for hallucinated repetition
+const MAX_REPEATED_PHRASE_WORDS: usize = 8;
+
+/// Lowercases a word and drops its punctuation, for comparing repeated phrases
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Collapses phrases repeated back to back more than `max_repeats` times to a single
+/// instance. Whisper loops like this on silence or music ("thank you thank you thank
+/// you ..."). Single words need one repetition more, since emphasis like "no no no"
+/// is common in real speech.
+///
+/// # Arguments
+/// * `text` - The transcription text to clean up
+/// * `max_repeats` - How many back-to-back copies of a phrase are kept as spoken
+///
+/// # Returns
+/// The text with runaway repetitions collapsed
+pub fn collapse_repeated_phrases(text: &str, max_repeats: usize) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let normalized: Vec<String> = words.iter().map(|w| normalize_word(w)).collect();
+
+    let mut result: Vec<&str> = Vec::with_capacity(words.len());
+    let mut i = 0;
+
+    while i < words.len() {
+        // Phrase length and repeat count of the repetition covering the most words
+        let mut best: Option<(usize, usize)> = None;
+
+        for n in 1..=MAX_REPEATED_PHRASE_WORDS.min((words.len() - i) / 2) {
+            let phrase = &normalized[i..i + n];
+            if phrase.iter().all(|w| w.is_empty()) {
+                continue;
+            }
+
+            let mut repeats = 1;
+            while i + (repeats + 1) * n <= words.len()
+                && normalized[i + repeats * n..i + (repeats + 1) * n] == *phrase
+            {
+                repeats += 1;
+            }
+
+            let limit = if n == 1 { max_repeats + 1 } else { max_repeats };
+            if repeats <= limit {
+                continue;
+            }
+            match best {
+                Some((best_n, best_repeats)) if best_n * best_repeats >= n * repeats => {}
+                _ => best = Some((n, repeats)),
+            }
+        }
+
+        match best {
+            Some((n, repeats)) => {
+                result.extend_from_slice(&words[i..i + n]);
+                i += n * repeats;
+            }
+            None => {
+                result.push(words[i]);
+                i += 1;
+            }
+        }
+    }
+
+    result.join(" ")
+}
+
+/// Whether a transcript is blank or consists only of a known hallucination phrase,
+/// as produced by accidental activations ("you", "thank you").
+/// Case, punctuation and surrounding whitespace are ignored.
+pub fn is_discardable_transcription(text: &str, phrases: &[String]) -> bool {
+    let normalize = |s: &str| {
+        s.split_whitespace()
+            .map(normalize_word)
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let normalized = normalize(text);
+    normalized.is_empty()
+        || phrases
+            .iter()
+            .map(|p| normalize(p))
+            .any(|p| !p.is_empty() && p == normalized)
+}
+
 /// Pre-compiled filler word patterns (built lazily)
 static FILLER_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
     FILLER_WORDS
@@ -243,6 +409,37 @@ mod tests {
         assert_eq!(result, "hello world");
     }
 
+    #[test]
+    fn test_apply_custom_words_reports_changes() {
+        let custom_words = vec!["Handy".to_string(), "Tauri".to_string()];
+        let (text, changes) = apply_custom_words_with_changes(
+            "Handy runs on tory.",
+            &custom_words,
+            0.5,
+            &Levenshtein,
+        );
+        assert_eq!(text, "Handy runs on tauri.");
+        assert_eq!(changes, vec![("tory.".to_string(), "tauri.".to_string())]);
+    }
+
+    #[test]
+    fn test_correction_algorithms_differ() {
+        let custom_words = vec!["Spotify".to_string(), "Smith".to_string()];
+        let correct = |text: &str, matcher: &dyn WordMatcher| {
+            apply_custom_words_with_changes(text, &custom_words, 0.18, matcher).0
+        };
+
+        // Swapped letters are a single edit only for Damerau-Levenshtein
+        assert_eq!(correct("stopify", &Levenshtein), "stopify");
+        assert_eq!(correct("stopify", &DamerauLevenshtein), "spotify");
+        assert_eq!(correct("stopify", &Jaro), "spotify");
+
+        // Phonetic only replaces words that sound alike
+        assert_eq!(correct("spotity", &Levenshtein), "spotify");
+        assert_eq!(correct("spotity", &Phonetic), "spotity");
+        assert_eq!(correct("smyth", &Phonetic), "smith");
+    }
+
     #[test]
     fn test_preserve_case_pattern() {
         assert_eq!(preserve_case_pattern("HELLO", "world"), "WORLD");
@@ -321,6 +518,17 @@ mod tests {
         assert_eq!(result, "w wh why");
     }
 
+    #[test]
+    fn test_filter_mixed_script_text() {
+        // A stuttered Hebrew "no", English, numbers and pointed "shalom"
+        let text = "um \u{5dc}\u{5d0} \u{5dc}\u{5d0} \u{5dc}\u{5d0} iOS 17 uh 2.0 \u{5e9}\u{5b8}\u{5c1}\u{5dc}\u{5d5}\u{5b9}\u{5dd}.";
+        let result = filter_transcription_output(text);
+        assert_eq!(
+            result,
+            "\u{5dc}\u{5d0} iOS 17 2.0 \u{5e9}\u{5b8}\u{5c1}\u{5dc}\u{5d5}\u{5b9}\u{5dd}."
+        );
+    }
+
     #[test]
     fn test_filter_stutter_short_words() {
         let text = "I I I I think so so so so";
@@ -335,10 +543,62 @@ mod tests {
         assert_eq!(result, "No");
     }
 
+    #[test]
+    fn test_collapse_repeated_phrases() {
+        let text = "Thank you. Thank you. Thank you. Thank you.";
+        assert_eq!(collapse_repeated_phrases(text, 2), "Thank you.");
+
+        let text = "see you later see you later see you later bye";
+        assert_eq!(collapse_repeated_phrases(text, 2), "see you later bye");
+    }
+
+    #[test]
+    fn test_collapse_repeated_phrases_keeps_legitimate_repetition() {
+        let text = "that was very very good, thank you thank you";
+        assert_eq!(collapse_repeated_phrases(text, 2), text);
+
+        let text = "no no no I said";
+        assert_eq!(collapse_repeated_phrases(text, 2), text);
+
+        let text = "go go go go go";
+        assert_eq!(collapse_repeated_phrases(text, 2), "go");
+    }
+
+    #[test]
+    fn test_is_discardable_transcription() {
+        let phrases = vec!["you".to_string(), "Thank you.".to_string()];
+        assert!(is_discardable_transcription("", &phrases));
+        assert!(is_discardable_transcription(" ... ", &phrases));
+        assert!(is_discardable_transcription("You.", &phrases));
+        assert!(is_discardable_transcription("thank  you!", &phrases));
+        assert!(!is_discardable_transcription("thank you all", &phrases));
+        assert!(!is_discardable_transcription("you", &[]));
+    }
+
     #[test]
     fn test_filter_stutter_preserves_two_repetitions() {
         let text = "no no is fine";
         let result = filter_transcription_output(text);
         assert_eq!(result, "no no is fine");
     }
+
+    #[test]
+    fn test_custom_words_in_mixed_direction_text() {
+        let custom_words = vec!["Kubernetes".to_string()];
+        let text = "\u{5d4}\u{5ea}\u{5e7}\u{5e0}\u{5ea}\u{5d9} kubernets, \u{5d2}\u{5e8}\u{5e1}\u{5d4} 1.29.";
+        let result = apply_custom_words(text, &custom_words, 0.5);
+        assert_eq!(
+            result,
+            "\u{5d4}\u{5ea}\u{5e7}\u{5e0}\u{5ea}\u{5d9} Kubernetes, \u{5d2}\u{5e8}\u{5e1}\u{5d4} 1.29."
+        );
+
+        // Pointed Hebrew keeps its marks, the quotes around it are punctuation
+        assert_eq!(
+            extract_punctuation(
+                "\u{201d}\u{5e9}\u{5b8}\u{5c1}\u{5dc}\u{5d5}\u{5b9}\u{5dd}!\u{201c}"
+            ),
+            ("\u{201d}", "!\u{201c}")
+        );
+        assert_eq!(extract_punctuation("\u{5be}"), ("\u{5be}", ""));
+    }
 }
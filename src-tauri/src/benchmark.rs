@@ -0,0 +1,149 @@
+//! Measures how fast a model runs on this machine and how much memory it takes,
+//! to help choose between model sizes.
+//!
+//! The model is loaded into an engine of its own and transcribes one recording, so
+//! the model used for dictation stays loaded and dictating keeps working meanwhile.
+//! Memory is how far the process's resident memory grew over the load and the run,
+//! so it is only a rough figure: allocations elsewhere in the app at the same time
+//! count too.
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::read_audio_file;
+use crate::compare::{self, RecordingSource, TranscriptionComparison};
+use crate::error::HandyError;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::history::HistoryManager;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::{TranscriptionManager, TranscriptionOptions};
+use crate::settings::get_settings;
+use log::info;
+use serde::Serialize;
+use specta::Type;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct ModelBenchmark {
+    pub model_id: String,
+    /// Length of the benchmark recording
+    pub audio_ms: u32,
+    pub load_ms: u32,
+    /// Time from handing the audio to the model to getting the transcript
+    pub latency_ms: u32,
+    /// Latency over recording length, below 1 is faster than real time
+    pub real_time_factor: f64,
+    /// Growth of resident memory while loading and running the model, None when
+    /// the platform doesn't report it
+    pub memory_mb: Option<u64>,
+}
+
+fn real_time_factor(latency: Duration, audio: Duration) -> f64 {
+    if audio.is_zero() {
+        return 0.0;
+    }
+    latency.as_secs_f64() / audio.as_secs_f64()
+}
+
+fn resident_bytes() -> Option<usize> {
+    memory_stats::memory_stats().map(|stats| stats.physical_mem)
+}
+
+/// Benchmarks a downloaded model on a recording, by default the latest one in
+/// history
+pub async fn benchmark(
+    app: &AppHandle,
+    model_id: String,
+    source: Option<RecordingSource>,
+) -> Result<ModelBenchmark, HandyError> {
+    let downloaded = app
+        .state::<Arc<ModelManager>>()
+        .get_model_info(&model_id)
+        .is_some_and(|m| m.is_downloaded);
+    if !downloaded {
+        return Err(HandyError::not_found(format!(
+            "Model not downloaded: {}",
+            model_id
+        )));
+    }
+    if app.state::<Arc<AudioRecordingManager>>().is_recording() {
+        return Err(HandyError::invalid_input(
+            "Models can't be benchmarked while recording".to_string(),
+        ));
+    }
+
+    let source = match source {
+        Some(source) => source,
+        None => {
+            let latest = app.state::<Arc<HistoryManager>>().get_latest_entry()?;
+            let entry = latest.ok_or_else(|| {
+                HandyError::not_found("There is no recording in history to benchmark with")
+            })?;
+            RecordingSource::HistoryEntry(entry.id)
+        }
+    };
+    let path = compare::resolve_source(app, source).await?;
+    let audio = read_audio_file(&path)
+        .map_err(|e| HandyError::io(format!("Failed to read {}: {}", path.display(), e)))?;
+    if audio.is_empty() {
+        return Err(HandyError::invalid_input(format!(
+            "{} contains no audio",
+            path.display()
+        )));
+    }
+
+    // A comparison running at the same time would skew the timings
+    let guard = app.state::<TranscriptionComparison>();
+    guard.begin()?;
+    let app_clone = app.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || run(&app_clone, &model_id, audio)).await;
+    guard.finish();
+    result.map_err(|e| HandyError::internal(e.to_string()))?
+}
+
+fn run(app: &AppHandle, model_id: &str, audio: Vec<f32>) -> Result<ModelBenchmark, HandyError> {
+    let tm = app.state::<Arc<TranscriptionManager>>();
+    let options = TranscriptionOptions::from_settings(&get_settings(app));
+    let audio_duration = Duration::from_secs_f64(audio.len() as f64 / WHISPER_SAMPLE_RATE as f64);
+
+    let before = resident_bytes();
+    let started = Instant::now();
+    let mut engine = tm.load_standalone(model_id)?;
+    let load = started.elapsed();
+    let loaded = resident_bytes();
+
+    let started = Instant::now();
+    engine.transcribe(&audio, &options)?;
+    let latency = started.elapsed();
+    let peak = loaded.max(resident_bytes());
+
+    let memory_mb = before
+        .zip(peak)
+        .map(|(before, peak)| (peak.saturating_sub(before) / (1024 * 1024)) as u64);
+    let benchmark = ModelBenchmark {
+        model_id: model_id.to_string(),
+        audio_ms: audio_duration.as_millis() as u32,
+        load_ms: load.as_millis() as u32,
+        latency_ms: latency.as_millis() as u32,
+        real_time_factor: real_time_factor(latency, audio_duration),
+        memory_mb,
+    };
+    info!("Benchmarked {}: {:?}", benchmark.model_id, benchmark);
+    Ok(benchmark)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real_time_factor_compares_latency_to_audio_length() {
+        let rtf = real_time_factor(Duration::from_millis(500), Duration::from_secs(2));
+        assert!((rtf - 0.25).abs() < f64::EPSILON);
+        assert_eq!(
+            real_time_factor(Duration::from_secs(1), Duration::ZERO),
+            0.0
+        );
+    }
+}
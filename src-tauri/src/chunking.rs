@@ -0,0 +1,188 @@
+//! Splits long recordings into chunks transcribed one after another.
+//!
+//! Models degrade and slow down on audio far beyond what they were trained on. A
+//! recording longer than `chunk_seconds` is cut at the quietest point near each
+//! chunk boundary, with a short overlap so a word on the cut isn't lost. The
+//! overlap can show up in both transcripts, so `stitch` lines up the words each
+//! transcript has within the overlap and keeps them once.
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use std::ops::Range;
+use std::time::Duration;
+
+/// Audio shared by neighbouring chunks
+const OVERLAP: Duration = Duration::from_millis(500);
+/// How far before the nominal boundary a quieter cut point is searched for
+const SEARCH_WINDOW: Duration = Duration::from_secs(5);
+/// Energy is compared over windows this long
+const ENERGY_WINDOW: Duration = Duration::from_millis(20);
+/// Shortest configurable chunk, shorter ones cut through too many sentences
+pub const MIN_CHUNK_SECONDS: u32 = 10;
+
+fn samples(duration: Duration) -> usize {
+    (duration.as_millis() as usize) * WHISPER_SAMPLE_RATE as usize / 1000
+}
+
+/// Ranges of 16kHz `audio` to transcribe separately, a single range when it
+/// fits in one chunk or chunking is off
+pub fn chunk_ranges(audio: &[f32], chunk_seconds: u32) -> Vec<Range<usize>> {
+    let chunk = chunk_seconds as usize * WHISPER_SAMPLE_RATE as usize;
+    let half_overlap = samples(OVERLAP) / 2;
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while chunk > 0 && audio.len() - start > chunk {
+        let end = start + chunk;
+        let search_start = end
+            .saturating_sub(samples(SEARCH_WINDOW))
+            .max(start + chunk / 2);
+        let cut = quietest_point(&audio[search_start..end]) + search_start;
+        ranges.push(start..(cut + half_overlap).min(audio.len()));
+        start = cut - half_overlap.min(cut - start);
+    }
+    ranges.push(start..audio.len());
+    ranges
+}
+
+/// Offset of the middle of the lowest energy window
+fn quietest_point(audio: &[f32]) -> usize {
+    let window = samples(ENERGY_WINDOW);
+    audio
+        .chunks(window)
+        .enumerate()
+        .map(|(i, w)| (i, w.iter().map(|s| s * s).sum::<f32>() / w.len() as f32))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(audio.len() / 2, |(i, _)| i * window + window / 2)
+}
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Words at either end of a transcript spoken within `span` samples, estimated
+/// from its speaking rate, plus one for a word the cut went through
+fn words_within(words: usize, span: usize, total: usize) -> usize {
+    let estimate = if total == 0 {
+        words
+    } else {
+        (words * span).div_ceil(total)
+    };
+    (estimate + 1).min(words)
+}
+
+/// Longest run of words shared by `a` and `b`, as where the run ends in each
+fn longest_common_run(a: &[String], b: &[String]) -> Option<(usize, usize)> {
+    let mut best = (0, 0, 0);
+    for i in 0..a.len() {
+        for j in 0..b.len() {
+            let len = a[i..]
+                .iter()
+                .zip(&b[j..])
+                .take_while(|(x, y)| !x.is_empty() && x == y)
+                .count();
+            if len > best.0 {
+                best = (len, i + len, j + len);
+            }
+        }
+    }
+    (best.0 > 0).then_some((best.1, best.2))
+}
+
+/// Joins chunk transcripts, given with the length of their chunk in samples. Only
+/// the words falling within the overlap are compared, so a phrase repeated
+/// elsewhere is kept, and where they match, the words around the match that the
+/// cut garbled are dropped too.
+pub fn stitch(chunks: &[(String, usize)]) -> String {
+    let overlap = samples(OVERLAP);
+    let mut words: Vec<&str> = Vec::new();
+    let mut previous: Option<(usize, usize)> = None;
+    for (text, len) in chunks {
+        let next: Vec<&str> = text.split_whitespace().collect();
+        let mut skip = 0;
+        if let Some((previous_words, previous_len)) = previous {
+            let tail = words_within(previous_words, overlap, previous_len).min(words.len());
+            let head = words_within(next.len(), overlap, *len);
+            let tail_start = words.len() - tail;
+            let normalized =
+                |words: &[&str]| -> Vec<String> { words.iter().map(|w| normalize(w)).collect() };
+            if let Some((tail_end, head_end)) = longest_common_run(
+                &normalized(&words[tail_start..]),
+                &normalized(&next[..head]),
+            ) {
+                words.truncate(tail_start + tail_end);
+                skip = head_end;
+            }
+        }
+        words.extend_from_slice(&next[skip..]);
+        previous = Some((next.len(), *len));
+    }
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_audio_is_cut_at_silence() {
+        let second = WHISPER_SAMPLE_RATE as usize;
+        let mut audio = vec![0.5; 25 * second];
+        // A pause at 8s, within the search window of the first 10s chunk
+        audio[8 * second..8 * second + second / 10].fill(0.0);
+
+        let ranges = chunk_ranges(&audio, 10);
+        assert_eq!(ranges.first().unwrap().start, 0);
+        assert_eq!(ranges.last().unwrap().end, audio.len());
+        let first_cut = ranges[0].end - samples(OVERLAP) / 2;
+        assert!((8 * second..8 * second + second / 10).contains(&first_cut));
+        for pair in ranges.windows(2) {
+            assert!(pair[1].start < pair[0].end, "chunks must overlap");
+        }
+
+        for chunk_seconds in [0, 30] {
+            let whole = chunk_ranges(&audio, chunk_seconds);
+            assert_eq!(whole.len(), 1);
+            assert_eq!(whole[0], 0..audio.len());
+        }
+    }
+
+    fn chunk(text: &str, seconds: usize) -> (String, usize) {
+        (text.to_string(), seconds * WHISPER_SAMPLE_RATE as usize)
+    }
+
+    #[test]
+    fn stitching_drops_words_repeated_across_a_join() {
+        let chunks = [
+            chunk("We met on Tuesday to", 2),
+            chunk("to discuss the budget.", 2),
+            chunk("Budget, approved.", 1),
+        ];
+        assert_eq!(
+            stitch(&chunks),
+            "We met on Tuesday to discuss the budget. approved."
+        );
+        assert_eq!(stitch(&[chunk("one", 1), chunk("two", 1)]), "one two");
+    }
+
+    #[test]
+    fn stitching_only_compares_words_within_the_overlap() {
+        // Said again well after the join, not transcribed twice
+        let chunks = [
+            chunk("one two three four five six seven eight nine ten", 10),
+            chunk("six seven eight nine ten eleven", 10),
+        ];
+        assert_eq!(
+            stitch(&chunks),
+            "one two three four five six seven eight nine ten six seven eight nine ten eleven"
+        );
+
+        // A word the cut went through is dropped along with the repeat
+        let chunks = [
+            chunk("we agreed to ship it fr", 2),
+            chunk("it friday morning", 2),
+        ];
+        assert_eq!(stitch(&chunks), "we agreed to ship it friday morning");
+    }
+}
@@ -1,7 +1,13 @@
-use crate::input::{self, EnigoState};
+use crate::copy_only_session::CopyOnlySession;
+use crate::events::{emit_event, AppEvent, PasteReport, TranscriptTruncated};
+use crate::formatting;
+use crate::input::{self, AccessibilityInsert, EnigoState};
+use crate::notification;
 use crate::settings::{get_settings, ClipboardHandling, PasteMethod};
+use crate::undo_paste::LastPaste;
 use enigo::Enigo;
 use log::{info, warn};
+use std::time::Instant;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
@@ -435,9 +441,43 @@ fn paste_direct(enigo: &mut Enigo, text: &str) -> Result<(), String> {
     input::paste_text_direct(enigo, text)
 }
 
-pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
+pub fn paste(text: String, app_handle: AppHandle) -> Result<PasteReport, String> {
+    let start = Instant::now();
     let settings = get_settings(&app_handle);
-    let paste_method = settings.paste_method;
+    // A copy-only session overrides both settings until it is turned off
+    let (paste_method, clipboard_handling) =
+        app_handle.state::<CopyOnlySession>().output(&settings);
+
+    // Enforce the configured maximum length; history already holds the full text
+    let text = match settings.max_transcript_chars {
+        Some(max_chars) => match settings.truncation_strategy.apply(&text, max_chars) {
+            Some(truncated) => {
+                let original_chars = text.chars().count();
+                info!(
+                    "Transcript truncated from {} to {} chars before paste",
+                    original_chars, max_chars
+                );
+                emit_event(
+                    &app_handle,
+                    AppEvent::TranscriptTruncated(TranscriptTruncated {
+                        original_chars,
+                        max_chars,
+                    }),
+                );
+                truncated
+            }
+            None => text,
+        },
+        None => text,
+    };
+
+    // Isolate left-to-right runs in right-to-left text, after truncation so the
+    // isolates always come in pairs
+    let text = if settings.bidi_isolation {
+        formatting::isolate_ltr_runs(&text)
+    } else {
+        text
+    };
 
     // Append trailing space if setting is enabled
     let text = if settings.append_trailing_space {
@@ -458,6 +498,8 @@ pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
         .map_err(|e| format!("Failed to lock Enigo: {}", e))?;
 
     // Perform the paste operation
+    let mut used = paste_method;
+    let mut fallback_reason = None;
     match paste_method {
         PasteMethod::None => {
             info!("PasteMethod::None selected - skipping paste action");
@@ -468,15 +510,41 @@ pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
         PasteMethod::CtrlV | PasteMethod::CtrlShiftV | PasteMethod::ShiftInsert => {
             paste_via_clipboard(&mut enigo, &text, &app_handle, &paste_method)?
         }
+        PasteMethod::Accessibility => match input::insert_text_accessibility(&text) {
+            AccessibilityInsert::Inserted => {}
+            AccessibilityInsert::SecureField => {
+                // Never fall back to keystrokes here, that would type into the password field
+                notification::notify_secure_field_refused(&app_handle);
+                return Err("Refusing to insert transcript into a secure text field".into());
+            }
+            AccessibilityInsert::Unsupported(reason) => {
+                info!(
+                    "Accessibility insertion unavailable ({}), falling back to clipboard paste",
+                    reason
+                );
+                used = PasteMethod::CtrlV;
+                fallback_reason = Some(reason);
+                paste_via_clipboard(&mut enigo, &text, &app_handle, &PasteMethod::CtrlV)?
+            }
+        },
+    }
+
+    if paste_method != PasteMethod::None {
+        app_handle.state::<LastPaste>().record(&text);
     }
 
     // After pasting, optionally copy to clipboard based on settings
-    if settings.clipboard_handling == ClipboardHandling::CopyToClipboard {
+    if clipboard_handling == ClipboardHandling::CopyToClipboard {
         let clipboard = app_handle.clipboard();
         clipboard
             .write_text(&text)
             .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
     }
 
-    Ok(())
+    Ok(PasteReport {
+        requested: paste_method,
+        used,
+        fallback_reason,
+        elapsed_ms: start.elapsed().as_millis() as u32,
+    })
 }
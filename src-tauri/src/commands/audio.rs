@@ -1,7 +1,17 @@
 use crate::audio_feedback;
 use crate::audio_toolkit::audio::{list_input_devices, list_output_devices};
-use crate::managers::audio::{AudioRecordingManager, MicrophoneMode};
-use crate::settings::{get_settings, write_settings};
+use crate::audio_toolkit::AudioRecorder;
+use crate::error::HandyError;
+use crate::helpers::mic_mute;
+use crate::managers::audio::{
+    current_device_environment, default_input_name_for_role, AudioRecordingManager, CaptureState,
+    MicrophoneMode, AGC_TARGET_RMS_RANGE, MAX_PRE_ROLL_MS,
+};
+use crate::mic_check::{self, MicAnalysis};
+use crate::settings::{
+    get_settings, write_settings, ChannelMixMode, MicChannelConfig, WindowsDeviceRole,
+};
+use crate::wake_word::{self, WakeWordModel, WakeWordTest};
 use log::warn;
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -15,12 +25,10 @@ pub struct CustomSounds {
 }
 
 fn custom_sound_exists(app: &AppHandle, sound_type: &str) -> bool {
-    app.path()
-        .resolve(
-            format!("custom_{}.wav", sound_type),
-            tauri::path::BaseDirectory::AppData,
-        )
-        .map_or(false, |path| path.exists())
+    crate::paths::get(app)
+        .data
+        .join(format!("custom_{}.wav", sound_type))
+        .exists()
 }
 
 #[tauri::command]
@@ -41,7 +49,7 @@ pub struct AudioDevice {
 
 #[tauri::command]
 #[specta::specta]
-pub fn update_microphone_mode(app: AppHandle, always_on: bool) -> Result<(), String> {
+pub fn update_microphone_mode(app: AppHandle, always_on: bool) -> Result<(), HandyError> {
     // Update settings
     let mut settings = get_settings(&app);
     settings.always_on_microphone = always_on;
@@ -56,21 +64,71 @@ pub fn update_microphone_mode(app: AppHandle, always_on: bool) -> Result<(), Str
     };
 
     rm.update_mode(new_mode)
-        .map_err(|e| format!("Failed to update microphone mode: {}", e))
+        .map_err(|e| HandyError::io(format!("Failed to update microphone mode: {}", e)))
 }
 
+/// Sets how much audio from before the shortcut press is kept (always-on microphone only)
 #[tauri::command]
 #[specta::specta]
-pub fn get_microphone_mode(app: AppHandle) -> Result<bool, String> {
+pub fn change_pre_roll_setting(app: AppHandle, pre_roll_ms: u32) -> Result<(), HandyError> {
+    if pre_roll_ms > MAX_PRE_ROLL_MS {
+        return Err(HandyError::invalid_input(format!(
+            "Pre-roll can be at most {} ms, got {}",
+            MAX_PRE_ROLL_MS, pre_roll_ms
+        )));
+    }
+
+    let mut settings = get_settings(&app);
+    settings.pre_roll_ms = pre_roll_ms;
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>().update_pre_roll();
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_agc_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = get_settings(&app);
+    settings.agc_enabled = enabled;
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>().update_agc();
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_agc_target_rms_setting(app: AppHandle, target_rms: f32) -> Result<(), HandyError> {
+    if !AGC_TARGET_RMS_RANGE.contains(&target_rms) {
+        return Err(HandyError::invalid_input(format!(
+            "AGC target must be between {} and {}, got {}",
+            AGC_TARGET_RMS_RANGE.start(),
+            AGC_TARGET_RMS_RANGE.end(),
+            target_rms
+        )));
+    }
+
+    let mut settings = get_settings(&app);
+    settings.agc_target_rms = target_rms;
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>().update_agc();
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_microphone_mode(app: AppHandle) -> Result<bool, HandyError> {
     let settings = get_settings(&app);
     Ok(settings.always_on_microphone)
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_available_microphones() -> Result<Vec<AudioDevice>, String> {
-    let devices =
-        list_input_devices().map_err(|e| format!("Failed to list audio devices: {}", e))?;
+pub fn get_available_microphones() -> Result<Vec<AudioDevice>, HandyError> {
+    let devices = list_input_devices()
+        .map_err(|e| HandyError::io(format!("Failed to list audio devices: {}", e)))?;
 
     let mut result = vec![AudioDevice {
         index: "default".to_string(),
@@ -87,10 +145,106 @@ pub fn get_available_microphones() -> Result<Vec<AudioDevice>, String> {
     Ok(result)
 }
 
+/// An input device with its channel layout, for configuring multi-channel interfaces
+#[derive(Serialize, Debug, Clone, Type)]
+pub struct DetailedAudioDevice {
+    pub index: String,
+    pub name: String,
+    /// Whether this is the system default input, on Windows the console default
+    pub is_default: bool,
+    /// Whether this is Windows' default input for calls, always false elsewhere
+    pub is_communications_default: bool,
+    /// Channels the device is captured with, None if its configuration can't be read
+    pub channels: Option<u32>,
+    /// Configured channels, None when all channels are averaged
+    pub channel_config: Option<MicChannelConfig>,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_available_microphones_detailed(
+    app: AppHandle,
+) -> Result<Vec<DetailedAudioDevice>, HandyError> {
+    let settings = get_settings(&app);
+    let devices = list_input_devices()
+        .map_err(|e| HandyError::io(format!("Failed to list audio devices: {}", e)))?;
+    let communications_default = default_input_name_for_role(WindowsDeviceRole::Communications);
+
+    Ok(devices
+        .into_iter()
+        .map(|d| DetailedAudioDevice {
+            channels: AudioRecorder::input_channels(&d.device).map(u32::from),
+            channel_config: settings.mic_channel_map.get(&d.name).cloned(),
+            is_communications_default: communications_default.as_ref() == Some(&d.name),
+            index: d.index,
+            name: d.name,
+            is_default: d.is_default,
+        })
+        .collect())
+}
+
+/// Chooses which input channels of a device are captured. Channels are numbered from
+/// one; an empty list goes back to averaging all channels.
+#[tauri::command]
+#[specta::specta]
+pub fn change_mic_channels(
+    app: AppHandle,
+    device: String,
+    channels: Vec<u32>,
+    mode: Option<ChannelMixMode>,
+) -> Result<(), HandyError> {
+    let mode = mode.unwrap_or_default();
+    if channels.contains(&0) {
+        return Err(HandyError::invalid_input(
+            "Channels are numbered from 1".to_string(),
+        ));
+    }
+    if mode == ChannelMixMode::Select && channels.len() > 1 {
+        return Err(HandyError::invalid_input(
+            "Select a single channel, or average several".to_string(),
+        ));
+    }
+    // The device may be unplugged, in which case the channels can't be checked yet
+    let available = list_input_devices()
+        .ok()
+        .and_then(|devices| devices.into_iter().find(|d| d.name == device))
+        .and_then(|d| AudioRecorder::input_channels(&d.device));
+    if let Some(available) = available {
+        if let Some(channel) = channels.iter().find(|c| **c > u32::from(available)) {
+            return Err(HandyError::invalid_input(format!(
+                "'{}' has {} channels, there is no channel {}",
+                device, available, channel
+            )));
+        }
+    }
+
+    let mut settings = get_settings(&app);
+    if channels.is_empty() {
+        settings.mic_channel_map.remove(&device);
+    } else {
+        settings
+            .mic_channel_map
+            .insert(device, MicChannelConfig { channels, mode });
+    }
+    write_settings(&app, settings);
+
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    rm.update_selected_device()
+        .map_err(|e| HandyError::io(format!("Failed to reopen the microphone: {}", e)))?;
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
-pub fn set_selected_microphone(app: AppHandle, device_name: String) -> Result<(), String> {
+pub fn set_selected_microphone(app: AppHandle, device_name: String) -> Result<(), HandyError> {
     let mut settings = get_settings(&app);
+    if settings.remember_mic_per_environment {
+        if let Some(environment) = current_device_environment() {
+            settings
+                .environment_microphones
+                .insert(environment, device_name.clone());
+        }
+    }
     settings.selected_microphone = if device_name == "default" {
         None
     } else {
@@ -101,14 +255,31 @@ pub fn set_selected_microphone(app: AppHandle, device_name: String) -> Result<()
     // Update the audio manager to use the new device
     let rm = app.state::<Arc<AudioRecordingManager>>();
     rm.update_selected_device()
-        .map_err(|e| format!("Failed to update selected device: {}", e))?;
+        .map_err(|e| HandyError::io(format!("Failed to update selected device: {}", e)))?;
 
     Ok(())
 }
 
+/// Chooses which Windows default input is used while no microphone is selected.
+/// Other platforms have a single default and ignore it.
 #[tauri::command]
 #[specta::specta]
-pub fn get_selected_microphone(app: AppHandle) -> Result<String, String> {
+pub fn set_windows_default_device_role(
+    app: AppHandle,
+    role: WindowsDeviceRole,
+) -> Result<(), HandyError> {
+    let mut settings = get_settings(&app);
+    settings.windows_default_device_role = role;
+    write_settings(&app, settings);
+
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    rm.update_selected_device()
+        .map_err(|e| HandyError::io(format!("Failed to update selected device: {}", e)))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_selected_microphone(app: AppHandle) -> Result<String, HandyError> {
     let settings = get_settings(&app);
     Ok(settings
         .selected_microphone
@@ -117,9 +288,9 @@ pub fn get_selected_microphone(app: AppHandle) -> Result<String, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_available_output_devices() -> Result<Vec<AudioDevice>, String> {
-    let devices =
-        list_output_devices().map_err(|e| format!("Failed to list output devices: {}", e))?;
+pub fn get_available_output_devices() -> Result<Vec<AudioDevice>, HandyError> {
+    let devices = list_output_devices()
+        .map_err(|e| HandyError::io(format!("Failed to list output devices: {}", e)))?;
 
     let mut result = vec![AudioDevice {
         index: "default".to_string(),
@@ -138,7 +309,7 @@ pub fn get_available_output_devices() -> Result<Vec<AudioDevice>, String> {
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_selected_output_device(app: AppHandle, device_name: String) -> Result<(), String> {
+pub fn set_selected_output_device(app: AppHandle, device_name: String) -> Result<(), HandyError> {
     let mut settings = get_settings(&app);
     settings.selected_output_device = if device_name == "default" {
         None
@@ -146,12 +317,13 @@ pub fn set_selected_output_device(app: AppHandle, device_name: String) -> Result
         Some(device_name)
     };
     write_settings(&app, settings);
+    audio_feedback::reinitialize_output(&app);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_selected_output_device(app: AppHandle) -> Result<String, String> {
+pub fn get_selected_output_device(app: AppHandle) -> Result<String, HandyError> {
     let settings = get_settings(&app);
     Ok(settings
         .selected_output_device
@@ -164,6 +336,8 @@ pub async fn play_test_sound(app: AppHandle, sound_type: String) {
     let sound = match sound_type.as_str() {
         "start" => audio_feedback::SoundType::Start,
         "stop" => audio_feedback::SoundType::Stop,
+        "processing" => audio_feedback::SoundType::Processing,
+        "error" => audio_feedback::SoundType::Error,
         _ => {
             warn!("Unknown sound type: {}", sound_type);
             return;
@@ -174,7 +348,7 @@ pub async fn play_test_sound(app: AppHandle, sound_type: String) {
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_clamshell_microphone(app: AppHandle, device_name: String) -> Result<(), String> {
+pub fn set_clamshell_microphone(app: AppHandle, device_name: String) -> Result<(), HandyError> {
     let mut settings = get_settings(&app);
     settings.clamshell_microphone = if device_name == "default" {
         None
@@ -187,13 +361,90 @@ pub fn set_clamshell_microphone(app: AppHandle, device_name: String) -> Result<(
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_clamshell_microphone(app: AppHandle) -> Result<String, String> {
+pub fn get_clamshell_microphone(app: AppHandle) -> Result<String, HandyError> {
     let settings = get_settings(&app);
     Ok(settings
         .clamshell_microphone
         .unwrap_or_else(|| "default".to_string()))
 }
 
+/// Whether the microphone stream is warm and how fast the last recording started
+#[tauri::command]
+#[specta::specta]
+pub fn get_capture_state(app: AppHandle) -> CaptureState {
+    app.state::<Arc<AudioRecordingManager>>().capture_state()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_wake_word_models() -> Vec<WakeWordModel> {
+    wake_word::models()
+}
+
+/// Configures the wake word, starting or stopping the detector to match
+#[tauri::command]
+#[specta::specta]
+pub fn change_wake_word_setting(
+    app: AppHandle,
+    enabled: bool,
+    model: String,
+    sensitivity: f32,
+    cooldown_ms: u32,
+) -> Result<(), HandyError> {
+    if !wake_word::is_known_model(&model) {
+        return Err(HandyError::invalid_input(format!(
+            "Unknown wake word model '{}'",
+            model
+        )));
+    }
+    if !(0.0..=1.0).contains(&sensitivity) {
+        return Err(HandyError::invalid_input(
+            "Wake word sensitivity must be between 0 and 1",
+        ));
+    }
+    let mut settings = get_settings(&app);
+    settings.wake_word_enabled = enabled;
+    settings.wake_word_model = model;
+    settings.wake_word_sensitivity = sensitivity;
+    settings.wake_word_cooldown_ms = cooldown_ms;
+    write_settings(&app, settings);
+    wake_word::restart(&app);
+    Ok(())
+}
+
+/// Listens for the wake phrase for a few seconds and reports what was heard,
+/// without recording anything
+#[tauri::command]
+#[specta::specta]
+pub async fn test_wake_word(app: AppHandle) -> Result<WakeWordTest, HandyError> {
+    wake_word::test(&app).await.map_err(HandyError::internal)
+}
+
+/// Sentence the user reads aloud during `analyze_microphone`
+#[tauri::command]
+#[specta::specta]
+pub fn get_mic_check_sentence() -> String {
+    mic_check::CHECK_SENTENCE.to_string()
+}
+
+/// Records the user reading the check sentence for a few seconds and rates the
+/// microphone, see `mic_check`. Uses the selected microphone when `device` is None.
+#[tauri::command]
+#[specta::specta]
+pub async fn analyze_microphone(
+    app: AppHandle,
+    device: Option<String>,
+) -> Result<MicAnalysis, HandyError> {
+    mic_check::analyze(&app, device).await
+}
+
+/// Unmutes the microphone recordings use, where the platform lets apps do that
+#[tauri::command]
+#[specta::specta]
+pub fn unmute_microphone(app: AppHandle) -> Result<(), HandyError> {
+    mic_mute::unmute(&app)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn is_recording(app: AppHandle) -> bool {
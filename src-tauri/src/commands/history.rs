@@ -1,17 +1,107 @@
-use crate::managers::history::{HistoryEntry, HistoryManager};
+use crate::actions::copy_transcript_ring_entry;
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::read_audio_file;
+use crate::error::HandyError;
+use crate::events::RecordingConversionProgress;
+use crate::managers::history::{audio_format, HistoryEntry, HistoryManager, SpeakingRateStats};
+use crate::managers::transcription::TranscriptionManager;
+use crate::session_log::{SessionLog, SessionLogEntry};
+use crate::settings::RecordingFormat;
+use crate::transcript_ring::{TranscriptRing, TranscriptRingEntry};
+use crate::word_diff::WordDiff;
 use std::sync::Arc;
-use tauri::{AppHandle, State};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
 
 #[tauri::command]
 #[specta::specta]
 pub async fn get_history_entries(
     _app: AppHandle,
     history_manager: State<'_, Arc<HistoryManager>>,
-) -> Result<Vec<HistoryEntry>, String> {
+) -> Result<Vec<HistoryEntry>, HandyError> {
     history_manager
         .get_history_entries()
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| HandyError::io(e.to_string()))
+}
+
+/// Average words per minute of past dictations, over the last `days` days or all
+/// time. Today's totals come from the session log, so they are complete even when
+/// history keeps only a few entries.
+#[tauri::command]
+#[specta::specta]
+pub fn get_speaking_rate_stats(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    session_log: State<'_, SessionLog>,
+    days: Option<u32>,
+) -> Result<SpeakingRateStats, HandyError> {
+    let mut stats = history_manager
+        .speaking_rate_stats(days)
+        .map_err(|e| HandyError::io(e.to_string()))?;
+    stats.today = Some(session_log.summary());
+    Ok(stats)
+}
+
+/// Today's dictations, oldest first. Kept apart from history and cleared at midnight.
+#[tauri::command]
+#[specta::specta]
+pub fn get_session_log(session_log: State<'_, SessionLog>) -> Vec<SessionLogEntry> {
+    session_log.entries()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn clear_session_log(session_log: State<'_, SessionLog>) {
+    session_log.clear();
+}
+
+/// Word diff between the raw and post-processed transcript of a history entry,
+/// None when it wasn't post-processed
+#[tauri::command]
+#[specta::specta]
+pub async fn get_history_diff(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+) -> Result<Option<WordDiff>, HandyError> {
+    let entry = history_manager
+        .get_entry_by_id(id)
+        .await
+        .map_err(|e| HandyError::io(e.to_string()))?;
+    if entry.is_none() {
+        return Err(HandyError::not_found(format!(
+            "History entry {} not found",
+            id
+        )));
+    }
+    history_manager
+        .get_post_process_diff(id)
+        .map_err(|e| HandyError::io(e.to_string()))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn search_history(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    query: String,
+) -> Result<Vec<HistoryEntry>, HandyError> {
+    history_manager
+        .search_entries(&query)
+        .await
+        .map_err(|e| HandyError::io(e.to_string()))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rebuild_history_index(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+) -> Result<usize, HandyError> {
+    let history_manager = Arc::clone(&history_manager);
+    tauri::async_runtime::spawn_blocking(move || history_manager.rebuild_search_index())
+        .await
+        .map_err(|e| HandyError::internal(e.to_string()))?
+        .map_err(|e| HandyError::io(e.to_string()))
 }
 
 #[tauri::command]
@@ -20,11 +110,47 @@ pub async fn toggle_history_entry_saved(
     _app: AppHandle,
     history_manager: State<'_, Arc<HistoryManager>>,
     id: i64,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     history_manager
         .toggle_saved_status(id)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| HandyError::io(e.to_string()))
+}
+
+/// Transcribes an entry's recording again with the current model and settings,
+/// replacing its transcript. Draft entries get their text this way.
+#[tauri::command]
+#[specta::specta]
+pub async fn retranscribe_history_entry(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+) -> Result<HistoryEntry, HandyError> {
+    let entry = history_manager
+        .get_entry_by_id(id)
+        .await
+        .map_err(|e| HandyError::io(e.to_string()))?
+        .ok_or_else(|| HandyError::not_found(format!("No history entry with id {}", id)))?;
+    let path = history_manager.get_audio_file_path(&entry.file_name);
+    let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+    let (transcription, duration) = tauri::async_runtime::spawn_blocking(move || {
+        let audio = read_audio_file(&path)
+            .map_err(|e| HandyError::io(format!("Failed to read {}: {}", path.display(), e)))?;
+        let duration = Duration::from_secs_f64(audio.len() as f64 / WHISPER_SAMPLE_RATE as f64);
+        tm.initiate_model_load();
+        Ok::<_, HandyError>((tm.transcribe(audio)?, duration))
+    })
+    .await
+    .map_err(|e| HandyError::internal(e.to_string()))??;
+
+    history_manager
+        .update_transcription(id, &transcription, duration)
+        .map_err(|e| HandyError::io(e.to_string()))?;
+    history_manager
+        .get_entry_by_id(id)
+        .await
+        .map_err(|e| HandyError::io(e.to_string()))?
+        .ok_or_else(|| HandyError::not_found(format!("No history entry with id {}", id)))
 }
 
 #[tauri::command]
@@ -33,10 +159,10 @@ pub async fn get_audio_file_path(
     _app: AppHandle,
     history_manager: State<'_, Arc<HistoryManager>>,
     file_name: String,
-) -> Result<String, String> {
+) -> Result<String, HandyError> {
     let path = history_manager.get_audio_file_path(&file_name);
     path.to_str()
-        .ok_or_else(|| "Invalid file path".to_string())
+        .ok_or_else(|| HandyError::internal("Invalid file path".to_string()))
         .map(|s| s.to_string())
 }
 
@@ -46,11 +172,11 @@ pub async fn delete_history_entry(
     _app: AppHandle,
     history_manager: State<'_, Arc<HistoryManager>>,
     id: i64,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     history_manager
         .delete_entry(id)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| HandyError::io(e.to_string()))
 }
 
 #[tauri::command]
@@ -59,14 +185,14 @@ pub async fn update_history_limit(
     app: AppHandle,
     history_manager: State<'_, Arc<HistoryManager>>,
     limit: usize,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     let mut settings = crate::settings::get_settings(&app);
     settings.history_limit = limit;
     crate::settings::write_settings(&app, settings);
 
     history_manager
         .cleanup_old_entries()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| HandyError::io(e.to_string()))?;
 
     Ok(())
 }
@@ -77,7 +203,7 @@ pub async fn update_recording_retention_period(
     app: AppHandle,
     history_manager: State<'_, Arc<HistoryManager>>,
     period: String,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     use crate::settings::RecordingRetentionPeriod;
 
     let retention_period = match period.as_str() {
@@ -86,7 +212,12 @@ pub async fn update_recording_retention_period(
         "days3" => RecordingRetentionPeriod::Days3,
         "weeks2" => RecordingRetentionPeriod::Weeks2,
         "months3" => RecordingRetentionPeriod::Months3,
-        _ => return Err(format!("Invalid retention period: {}", period)),
+        _ => {
+            return Err(HandyError::invalid_input(format!(
+                "Invalid retention period: {}",
+                period
+            )))
+        }
     };
 
     let mut settings = crate::settings::get_settings(&app);
@@ -95,7 +226,55 @@ pub async fn update_recording_retention_period(
 
     history_manager
         .cleanup_old_entries()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| HandyError::io(e.to_string()))?;
 
     Ok(())
 }
+
+/// Sets the format new recordings are stored in. Existing recordings keep theirs,
+/// see `convert_existing_recordings`.
+#[tauri::command]
+#[specta::specta]
+pub fn change_recording_format_setting(
+    app: AppHandle,
+    format: RecordingFormat,
+) -> Result<(), HandyError> {
+    let mut settings = crate::settings::get_settings(&app);
+    settings.recording_format = format;
+    crate::settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Re-encodes stored recordings in `format`, emitting `recording-conversion-progress`
+#[tauri::command]
+#[specta::specta]
+pub async fn convert_existing_recordings(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    format: RecordingFormat,
+) -> Result<RecordingConversionProgress, HandyError> {
+    let history_manager = Arc::clone(&history_manager);
+    tauri::async_runtime::spawn_blocking(move || {
+        history_manager.convert_recordings(audio_format(format))
+    })
+    .await
+    .map_err(|e| HandyError::internal(e.to_string()))?
+    .map_err(|e| HandyError::io(e.to_string()))
+}
+
+/// Recent transcripts kept in memory, newest first. Works even when history is disabled.
+#[tauri::command]
+#[specta::specta]
+pub fn get_transcript_clipboard(
+    _app: AppHandle,
+    transcript_ring: State<TranscriptRing>,
+) -> Result<Vec<TranscriptRingEntry>, HandyError> {
+    Ok(transcript_ring.entries())
+}
+
+/// Copies a transcript from the ring to the OS clipboard, 0 being the newest
+#[tauri::command]
+#[specta::specta]
+pub fn copy_transcript_clipboard_entry(app: AppHandle, index: u32) -> Result<(), HandyError> {
+    copy_transcript_ring_entry(&app, index as usize)
+}
@@ -3,54 +3,90 @@ pub mod history;
 pub mod models;
 pub mod transcription;
 
-use crate::settings::{get_settings, write_settings, AppSettings, LogLevel};
+use crate::error::HandyError;
+use crate::helpers::log_tail;
+use crate::llm_client::{self, ChatMessage, LlmQueueStatus};
+use crate::paths::{self, AppPaths};
+use crate::prompt_lint::PromptLintIssue;
+use crate::self_test::SelfTestReport;
+use crate::settings::{
+    get_settings, write_settings, AppSettings, EffectiveSettings, LogLevel,
+    PostProcessMisconfiguration, PromptLimits,
+};
 use crate::utils::cancel_current_operation;
+use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_opener::OpenerExt;
 
+/// Entries returned by `get_recent_logs` when no count is given
+const DEFAULT_RECENT_LOG_LINES: u32 = 200;
+const MAX_RECENT_LOG_LINES: u32 = 2000;
+
+/// Smallest log file size accepted before rotating
+const MIN_LOG_FILE_SIZE_KB: u32 = 10;
+
 #[tauri::command]
 #[specta::specta]
 pub fn cancel_operation(app: AppHandle) {
     cancel_current_operation(&app);
 }
 
+/// Stops the current recording and transcribes it, used by the interactive overlay
 #[tauri::command]
 #[specta::specta]
-pub fn get_app_dir_path(app: AppHandle) -> Result<String, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+pub fn stop_recording(app: AppHandle) -> Result<(), HandyError> {
+    Ok(crate::utils::stop_current_recording(&app)?)
+}
 
-    Ok(app_data_dir.to_string_lossy().to_string())
+/// Every directory the app keeps files in
+#[tauri::command]
+#[specta::specta]
+pub fn get_app_paths(app: AppHandle) -> AppPaths {
+    paths::get(&app).clone()
 }
 
+/// The data directory, superseded by `get_app_paths`
 #[tauri::command]
 #[specta::specta]
-pub fn get_app_settings(app: AppHandle) -> Result<AppSettings, String> {
+pub fn get_app_dir_path(app: AppHandle) -> Result<String, HandyError> {
+    Ok(paths::get(&app).data.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_app_settings(app: AppHandle) -> Result<AppSettings, HandyError> {
     Ok(get_settings(&app))
 }
 
+/// Settings with all defaults merged in, exactly as the app uses them. Unlike
+/// `get_app_settings` this also writes the merge back to the settings file.
 #[tauri::command]
 #[specta::specta]
-pub fn get_default_settings() -> Result<AppSettings, String> {
+pub fn get_effective_settings(app: AppHandle) -> Result<EffectiveSettings, HandyError> {
+    Ok(crate::settings::get_effective_settings(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_default_settings() -> Result<AppSettings, HandyError> {
     Ok(crate::settings::get_default_settings())
 }
 
+fn log_dir(app: &AppHandle) -> Result<PathBuf, HandyError> {
+    Ok(paths::get(app).logs.clone())
+}
+
 #[tauri::command]
 #[specta::specta]
-pub fn get_log_dir_path(app: AppHandle) -> Result<String, String> {
-    let log_dir = app
-        .path()
-        .app_log_dir()
-        .map_err(|e| format!("Failed to get log directory: {}", e))?;
+pub fn get_log_dir_path(app: AppHandle) -> Result<String, HandyError> {
+    let log_dir = log_dir(&app)?;
 
     Ok(log_dir.to_string_lossy().to_string())
 }
 
 #[specta::specta]
 #[tauri::command]
-pub fn set_log_level(app: AppHandle, level: LogLevel) -> Result<(), String> {
+pub fn set_log_level(app: AppHandle, level: LogLevel) -> Result<(), HandyError> {
     let tauri_log_level: tauri_plugin_log::LogLevel = level.into();
     let log_level: log::Level = tauri_log_level.into();
     // Update the file log level atomic so the filter picks up the new level
@@ -66,52 +102,143 @@ pub fn set_log_level(app: AppHandle, level: LogLevel) -> Result<(), String> {
     Ok(())
 }
 
+/// Configures log rotation. Takes effect the next time the app starts.
+#[specta::specta]
+#[tauri::command]
+pub fn change_log_rotation_setting(
+    app: AppHandle,
+    max_file_size_kb: u32,
+    max_files: u32,
+) -> Result<(), HandyError> {
+    if max_file_size_kb < MIN_LOG_FILE_SIZE_KB {
+        return Err(HandyError::invalid_input(format!(
+            "Log files must be at least {} KB",
+            MIN_LOG_FILE_SIZE_KB
+        )));
+    }
+
+    let mut settings = get_settings(&app);
+    settings.log_max_file_size_kb = max_file_size_kb;
+    settings.log_max_files = max_files;
+    write_settings(&app, settings);
+
+    Ok(())
+}
+
+/// Deletes rotated log files and empties the active one
 #[specta::specta]
 #[tauri::command]
-pub fn open_recordings_folder(app: AppHandle) -> Result<(), String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+pub fn clear_logs(app: AppHandle) -> Result<(), HandyError> {
+    let log_dir = log_dir(&app)?;
+    let active_log = format!("{}.log", crate::LOG_FILE_NAME);
+
+    let entries = std::fs::read_dir(&log_dir)
+        .map_err(|e| HandyError::io(format!("Failed to read log directory: {}", e)))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("log") {
+            continue;
+        }
+        let result = if entry.file_name() == active_log.as_str() {
+            // The logger keeps the active file open, so it is truncated rather than removed
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_len(0))
+        } else {
+            std::fs::remove_file(&path)
+        };
+        result.map_err(|e| HandyError::io(format!("Failed to clear {}: {}", path.display(), e)))?;
+    }
 
-    let recordings_dir = app_data_dir.join("recordings");
+    log::info!("Log files cleared");
+    Ok(())
+}
 
-    let path = recordings_dir.to_string_lossy().as_ref().to_string();
+/// Returns the most recent entries of the active log file, oldest first.
+/// `since` is an RFC 3339 timestamp; `min_level` keeps that level and more severe ones.
+#[specta::specta]
+#[tauri::command]
+pub fn get_recent_logs(
+    app: AppHandle,
+    lines: Option<u32>,
+    since: Option<String>,
+    min_level: Option<LogLevel>,
+) -> Result<Vec<String>, HandyError> {
+    let limit = lines.unwrap_or(DEFAULT_RECENT_LOG_LINES);
+    if limit == 0 || limit > MAX_RECENT_LOG_LINES {
+        return Err(HandyError::invalid_input(format!(
+            "Line count must be between 1 and {}",
+            MAX_RECENT_LOG_LINES
+        )));
+    }
+    // Log timestamps are written in UTC
+    let since = since
+        .map(|since| {
+            chrono::DateTime::parse_from_rfc3339(&since)
+                .map(|time| time.naive_utc())
+                .map_err(|e| {
+                    HandyError::invalid_input(format!("Invalid timestamp '{}': {}", since, e))
+                })
+        })
+        .transpose()?;
+    let min_level = min_level.map(|level| {
+        let level: tauri_plugin_log::LogLevel = level.into();
+        log::Level::from(level)
+    });
+
+    let path = log_dir(&app)?.join(format!("{}.log", crate::LOG_FILE_NAME));
+    let contents = match std::fs::read(&path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(HandyError::io(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+
+    Ok(log_tail::recent_entries(
+        &contents,
+        limit as usize,
+        since,
+        min_level,
+    ))
+}
+
+#[specta::specta]
+#[tauri::command]
+pub fn open_recordings_folder(app: AppHandle) -> Result<(), HandyError> {
+    let path = paths::get(&app).recordings.to_string_lossy().to_string();
     app.opener()
         .open_path(path, None::<String>)
-        .map_err(|e| format!("Failed to open recordings folder: {}", e))?;
+        .map_err(|e| HandyError::io(format!("Failed to open recordings folder: {}", e)))?;
 
     Ok(())
 }
 
 #[specta::specta]
 #[tauri::command]
-pub fn open_log_dir(app: AppHandle) -> Result<(), String> {
-    let log_dir = app
-        .path()
-        .app_log_dir()
-        .map_err(|e| format!("Failed to get log directory: {}", e))?;
+pub fn open_log_dir(app: AppHandle) -> Result<(), HandyError> {
+    let log_dir = log_dir(&app)?;
 
     let path = log_dir.to_string_lossy().as_ref().to_string();
     app.opener()
         .open_path(path, None::<String>)
-        .map_err(|e| format!("Failed to open log directory: {}", e))?;
+        .map_err(|e| HandyError::io(format!("Failed to open log directory: {}", e)))?;
 
     Ok(())
 }
 
 #[specta::specta]
 #[tauri::command]
-pub fn open_app_data_dir(app: AppHandle) -> Result<(), String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-
-    let path = app_data_dir.to_string_lossy().as_ref().to_string();
+pub fn open_app_data_dir(app: AppHandle) -> Result<(), HandyError> {
+    let path = paths::get(&app).data.to_string_lossy().to_string();
     app.opener()
         .open_path(path, None::<String>)
-        .map_err(|e| format!("Failed to open app data directory: {}", e))?;
+        .map_err(|e| HandyError::io(format!("Failed to open app data directory: {}", e)))?;
 
     Ok(())
 }
@@ -135,7 +262,7 @@ pub fn check_apple_intelligence_available() -> bool {
 /// On macOS, this will return an error if accessibility permissions are not granted.
 #[specta::specta]
 #[tauri::command]
-pub fn initialize_enigo(app: AppHandle) -> Result<(), String> {
+pub fn initialize_enigo(app: AppHandle) -> Result<(), HandyError> {
     use crate::input::EnigoState;
 
     // Check if already initialized
@@ -160,28 +287,105 @@ pub fn initialize_enigo(app: AppHandle) -> Result<(), String> {
             } else {
                 log::warn!("Failed to initialize Enigo: {}", e);
             }
-            Err(format!("Failed to initialize input system: {}", e))
+            Err(HandyError::permission(format!(
+                "Failed to initialize input system: {}",
+                e
+            )))
         }
     }
 }
 
+/// Checks the model, microphone, input simulation, shortcuts and post-processing
+/// provider, returning a pass/fail report with a remediation hint per failure.
+#[specta::specta]
+#[tauri::command]
+pub async fn run_self_test(app: AppHandle) -> Result<SelfTestReport, HandyError> {
+    Ok(crate::self_test::run(&app).await)
+}
+
+/// Returns what is missing when post-processing is enabled but can't run.
+#[specta::specta]
+#[tauri::command]
+pub fn get_post_process_misconfiguration(app: AppHandle) -> Option<PostProcessMisconfiguration> {
+    get_settings(&app).post_process_misconfiguration()
+}
+
+/// Offline checks of a prompt against the active provider configuration.
+#[specta::specta]
+#[tauri::command]
+pub fn lint_prompt(app: AppHandle, prompt: String) -> Vec<PromptLintIssue> {
+    crate::prompt_lint::lint_prompt(&get_settings(&app), &prompt)
+}
+
+/// The messages post-processing `input` with a saved prompt would send to the
+/// active provider, with every token expanded. Nothing is sent.
+#[specta::specta]
+#[tauri::command]
+pub fn render_prompt(
+    app: AppHandle,
+    prompt_id: String,
+    input: String,
+) -> Result<Vec<ChatMessage>, HandyError> {
+    let settings = get_settings(&app);
+    let prompt = settings
+        .post_process_prompts
+        .iter()
+        .find(|prompt| prompt.id == prompt_id)
+        .ok_or_else(|| HandyError::not_found(format!("Prompt '{}' not found", prompt_id)))?;
+    let provider = settings
+        .active_post_process_provider()
+        .ok_or_else(|| HandyError::config("No post-processing provider is selected"))?;
+    crate::actions::post_process_messages(&settings, &prompt.prompt, provider, &input)
+}
+
+/// Sets the size caps for post-processing requests, kept within bounds
+#[specta::specta]
+#[tauri::command]
+pub fn change_prompt_limits_setting(
+    app: AppHandle,
+    limits: PromptLimits,
+) -> Result<PromptLimits, HandyError> {
+    let mut settings = get_settings(&app);
+    settings.prompt_limits = crate::prompt_limits::clamp(limits);
+    let limits = settings.prompt_limits;
+    write_settings(&app, settings);
+    Ok(limits)
+}
+
+/// Queue depth and rate-limit state for every configured post-processing provider.
+#[specta::specta]
+#[tauri::command]
+pub fn get_llm_queue_status(app: AppHandle) -> Result<Vec<LlmQueueStatus>, HandyError> {
+    let settings = get_settings(&app);
+    Ok(settings
+        .post_process_providers
+        .iter()
+        .filter(|provider| provider.id != crate::settings::APPLE_INTELLIGENCE_PROVIDER_ID)
+        .map(|provider| llm_client::queue_status(&provider.id))
+        .collect())
+}
+
 /// Test post-processing on arbitrary input text.
 /// Returns the processed text if successful.
 #[specta::specta]
 #[tauri::command]
-pub async fn test_post_process(app: AppHandle, input_text: String) -> Result<String, String> {
-    use crate::settings::{APPLE_INTELLIGENCE_PROVIDER_ID};
+pub async fn test_post_process(app: AppHandle, input_text: String) -> Result<String, HandyError> {
+    use crate::settings::APPLE_INTELLIGENCE_PROVIDER_ID;
 
     let settings = get_settings(&app);
 
     if !settings.post_process_enabled {
-        return Err("Post-processing is disabled. Please enable it first.".to_string());
+        return Err(HandyError::config(
+            "Post-processing is disabled. Please enable it first.".to_string(),
+        ));
     }
 
     let provider = settings
         .active_post_process_provider()
         .cloned()
-        .ok_or_else(|| "No post-processing provider is selected.".to_string())?;
+        .ok_or_else(|| {
+            HandyError::config("No post-processing provider is selected.".to_string())
+        })?;
 
     let model = settings
         .post_process_models
@@ -190,48 +394,52 @@ pub async fn test_post_process(app: AppHandle, input_text: String) -> Result<Str
         .unwrap_or_default();
 
     if model.trim().is_empty() {
-        return Err(format!(
+        return Err(HandyError::config(format!(
             "No model configured for provider '{}'.",
             provider.label
-        ));
+        )));
     }
 
-    let selected_prompt_id = settings
-        .post_process_selected_prompt_id
-        .clone()
-        .ok_or_else(|| "No prompt is selected.".to_string())?;
-
     let prompt = settings
-        .post_process_prompts
-        .iter()
-        .find(|p| p.id == selected_prompt_id)
-        .ok_or_else(|| format!("Selected prompt '{}' not found.", selected_prompt_id))?;
-
-    if prompt.prompt.trim().is_empty() {
-        return Err("The selected prompt is empty.".to_string());
-    }
-
-    // Replace ${output} variable in the prompt with the input text
-    let processed_prompt = prompt.prompt.replace("${output}", &input_text);
+        .resolve_post_process_prompt()
+        .map_err(|misconfiguration| match misconfiguration {
+            PostProcessMisconfiguration::PromptNotFound { prompt_id } => {
+                HandyError::not_found(format!("Selected prompt '{}' not found.", prompt_id))
+            }
+            PostProcessMisconfiguration::EmptyPrompt { .. } => {
+                HandyError::config("The selected prompt is empty.".to_string())
+            }
+            _ => HandyError::config("No prompt is available.".to_string()),
+        })?;
+    let messages =
+        crate::actions::post_process_messages(&settings, &prompt.prompt, &provider, &input_text)?;
 
     // Handle Apple Intelligence separately
     if provider.id == APPLE_INTELLIGENCE_PROVIDER_ID {
         #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
         {
             if !crate::apple_intelligence::check_apple_intelligence_availability() {
-                return Err("Apple Intelligence is not available on this device.".to_string());
+                return Err(HandyError::config(
+                    "Apple Intelligence is not available on this device.".to_string(),
+                ));
             }
 
+            // Apple Intelligence takes a single prompt, so ${output} is expanded inline
+            let processed_prompt = messages
+                .iter()
+                .map(|m| m.content.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n");
             let token_limit = model.trim().parse::<i32>().unwrap_or(0);
             return crate::apple_intelligence::process_text(&processed_prompt, token_limit)
-                .map_err(|e| format!("Apple Intelligence error: {}", e));
+                .map_err(|e| HandyError::internal(format!("Apple Intelligence error: {}", e)));
         }
 
         #[cfg(not(all(target_os = "macos", target_arch = "aarch64")))]
         {
-            return Err(
+            return Err(HandyError::config(
                 "Apple Intelligence is only available on Apple silicon Macs.".to_string(),
-            );
+            ));
         }
     }
 
@@ -242,19 +450,34 @@ pub async fn test_post_process(app: AppHandle, input_text: String) -> Result<Str
         .unwrap_or_default();
 
     // Send the chat completion request
-    match crate::llm_client::send_chat_completion(&provider, api_key, &model, processed_prompt)
-        .await
+    match llm_client::send_chat_completion(
+        &provider,
+        api_key,
+        &model,
+        messages,
+        prompt.response_format,
+        prompt.seed,
+        llm_client::RequestPriority::Live,
+        None,
+    )
+    .await
     {
-        Ok(Some(content)) => {
+        Ok(Some(completion)) => {
             // Strip invisible Unicode characters that some LLMs may insert
-            let content = content
+            let content = completion
+                .content
                 .replace('\u{200B}', "") // Zero-Width Space
                 .replace('\u{200C}', "") // Zero-Width Non-Joiner
                 .replace('\u{200D}', "") // Zero-Width Joiner
                 .replace('\u{FEFF}', ""); // Byte Order Mark
-            Ok(content)
+            Ok(crate::formatting::format_json_output(
+                &content,
+                &prompt.json_output,
+            ))
         }
-        Ok(None) => Err("LLM returned an empty response.".to_string()),
-        Err(e) => Err(format!("LLM request failed: {}", e)),
+        Ok(None) => Err(HandyError::internal(
+            "LLM returned an empty response.".to_string(),
+        )),
+        Err(e) => Err(HandyError::network(format!("LLM request failed: {}", e))),
     }
 }
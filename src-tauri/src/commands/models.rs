@@ -1,23 +1,50 @@
-use crate::managers::model::{ModelInfo, ModelManager};
-use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, write_settings};
+use crate::benchmark::{self, ModelBenchmark};
+use crate::compare::RecordingSource;
+use crate::error::HandyError;
+use crate::managers::model::{ModelInfo, ModelManager, ModelSummary, QuantizationVariant};
+use crate::managers::transcription::{ModelState, TranscriptionManager};
+use crate::model_manifest;
+use crate::power_profile::{self, ActivePowerProfile, PowerSource};
+use crate::settings::{get_settings, write_settings, PowerProfiles};
+use crate::system_memory::{self, ModelSwitch, SystemMemory};
+use log::warn;
+use serde::Serialize;
+use specta::Type;
 use std::sync::Arc;
 use tauri::{AppHandle, State};
 
+/// The model catalog. A stale fetched catalog is refreshed in the background and
+/// shows up on a later call.
 #[tauri::command]
 #[specta::specta]
 pub async fn get_available_models(
+    app: AppHandle,
     model_manager: State<'_, Arc<ModelManager>>,
-) -> Result<Vec<ModelInfo>, String> {
+) -> Result<Vec<ModelInfo>, HandyError> {
+    model_manifest::refresh_in_background(&app, false);
     Ok(model_manager.get_available_models())
 }
 
+/// Every model in the catalog with what sets it apart, smallest first. Sizes of
+/// downloaded models are measured on disk.
+#[tauri::command]
+#[specta::specta]
+pub fn list_available_models(model_manager: State<'_, Arc<ModelManager>>) -> Vec<ModelSummary> {
+    let mut models: Vec<ModelSummary> = model_manager
+        .get_available_models()
+        .iter()
+        .map(|model| ModelSummary::new(model, model_manager.size_on_disk(&model.id)))
+        .collect();
+    models.sort_by(|a, b| a.param_count.cmp(&b.param_count).then(a.id.cmp(&b.id)));
+    models
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_model_info(
     model_manager: State<'_, Arc<ModelManager>>,
     model_id: String,
-) -> Result<Option<ModelInfo>, String> {
+) -> Result<Option<ModelInfo>, HandyError> {
     Ok(model_manager.get_model_info(&model_id))
 }
 
@@ -26,11 +53,11 @@ pub async fn get_model_info(
 pub async fn download_model(
     model_manager: State<'_, Arc<ModelManager>>,
     model_id: String,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     model_manager
         .download_model(&model_id)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| HandyError::network(e.to_string()))
 }
 
 #[tauri::command]
@@ -38,12 +65,57 @@ pub async fn download_model(
 pub async fn delete_model(
     model_manager: State<'_, Arc<ModelManager>>,
     model_id: String,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     model_manager
         .delete_model(&model_id)
-        .map_err(|e| e.to_string())
+        .map_err(|e| HandyError::io(e.to_string()))
+}
+
+/// Quantizations a model can be downloaded in, empty when it comes in only one
+#[tauri::command]
+#[specta::specta]
+pub fn list_model_variants(
+    model_manager: State<'_, Arc<ModelManager>>,
+    model_id: String,
+) -> Result<Vec<QuantizationVariant>, HandyError> {
+    model_manager
+        .get_model_info(&model_id)
+        .map(|model| model.variants)
+        .ok_or_else(|| HandyError::not_found(format!("Model not found: {}", model_id)))
+}
+
+/// Chooses the quantization to download and load for a model, None for its
+/// default. Until it's downloaded, another downloaded quantization is loaded.
+#[tauri::command]
+#[specta::specta]
+pub fn set_model_quantization(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    model_id: String,
+    quantization: Option<String>,
+) -> Result<(), HandyError> {
+    if model_manager.get_model_info(&model_id).is_none() {
+        return Err(HandyError::not_found(format!(
+            "Model not found: {}",
+            model_id
+        )));
+    }
+    model_manager
+        .set_quantization(&model_id, quantization.as_deref())
+        .map_err(|e| HandyError::invalid_input(e.to_string()))?;
+
+    let mut settings = get_settings(&app_handle);
+    match quantization {
+        Some(quantization) => settings.model_quantization.insert(model_id, quantization),
+        None => settings.model_quantization.remove(&model_id),
+    };
+    write_settings(&app_handle, settings);
+    Ok(())
 }
 
+/// Switches to a model. If it may not fit in the free memory, nothing changes and
+/// the warning is returned for confirmation; call again with
+/// `ignore_memory_warning` to switch anyway.
 #[tauri::command]
 #[specta::specta]
 pub async fn set_active_model(
@@ -51,14 +123,26 @@ pub async fn set_active_model(
     model_manager: State<'_, Arc<ModelManager>>,
     transcription_manager: State<'_, Arc<TranscriptionManager>>,
     model_id: String,
-) -> Result<(), String> {
+    ignore_memory_warning: Option<bool>,
+) -> Result<ModelSwitch, HandyError> {
     // Check if model exists and is available
     let model_info = model_manager
         .get_model_info(&model_id)
         .ok_or_else(|| format!("Model not found: {}", model_id))?;
 
     if !model_info.is_downloaded {
-        return Err(format!("Model not downloaded: {}", model_id));
+        return Err(HandyError::not_found(format!(
+            "Model not downloaded: {}",
+            model_id
+        )));
+    }
+
+    if let Some(warning) = system_memory::check(&model_info, system_memory::system_memory()) {
+        if !ignore_memory_warning.unwrap_or(false) {
+            warn!("Not switching yet: {}", warning.message);
+            return Ok(ModelSwitch::NeedsConfirmation(warning));
+        }
+        warn!("Switching despite the warning: {}", warning.message);
     }
 
     // Load the model in the transcription manager
@@ -71,12 +155,114 @@ pub async fn set_active_model(
     settings.selected_model = model_id.clone();
     write_settings(&app_handle, settings);
 
+    Ok(ModelSwitch::Switched)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_system_memory() -> SystemMemory {
+    system_memory::system_memory()
+}
+
+/// Reads a model into memory ahead of use. Without an id the selected model is
+/// loaded; with one, that model is loaded and becomes the selected model.
+#[tauri::command]
+#[specta::specta]
+pub async fn load_model(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    model_id: Option<String>,
+) -> Result<(), HandyError> {
+    let mut settings = get_settings(&app_handle);
+    let model_id = model_id.unwrap_or_else(|| settings.selected_model.clone());
+    if model_id.is_empty() {
+        return Err(HandyError::config("No model is selected"));
+    }
+    let model_info = model_manager
+        .get_model_info(&model_id)
+        .ok_or_else(|| HandyError::not_found(format!("Model not found: {}", model_id)))?;
+    if !model_info.is_downloaded {
+        return Err(HandyError::not_found(format!(
+            "Model not downloaded: {}",
+            model_id
+        )));
+    }
+
+    if transcription_manager.get_current_model().as_deref() == Some(model_id.as_str()) {
+        transcription_manager.mark_activity();
+    } else {
+        let tm = Arc::clone(&transcription_manager);
+        let id = model_id.clone();
+        tauri::async_runtime::spawn_blocking(move || tm.load_model(&id))
+            .await
+            .map_err(|e| HandyError::internal(e.to_string()))?
+            .map_err(|e| HandyError::internal(e.to_string()))?;
+    }
+
+    if settings.selected_model != model_id {
+        settings.selected_model = model_id;
+        write_settings(&app_handle, settings);
+    }
     Ok(())
 }
 
+/// Frees the loaded model; the next dictation loads it again
+#[tauri::command]
+#[specta::specta]
+pub fn unload_model(
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<(), HandyError> {
+    transcription_manager
+        .unload_model()
+        .map_err(|e| HandyError::internal(format!("Failed to unload model: {}", e)))
+}
+
 #[tauri::command]
 #[specta::specta]
-pub async fn get_current_model(app_handle: AppHandle) -> Result<String, String> {
+pub fn get_model_state(
+    app_handle: AppHandle,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<ModelStatus, HandyError> {
+    Ok(ModelStatus {
+        state: transcription_manager.model_state(),
+        power_profile: power_profile::active(&app_handle),
+    })
+}
+
+/// `ModelState` along with the power profile that picks the model
+#[derive(Serialize, Debug, Clone, Type)]
+pub struct ModelStatus {
+    #[serde(flatten)]
+    pub state: ModelState,
+    pub power_profile: ActivePowerProfile,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_power_profiles_setting(
+    app_handle: AppHandle,
+    profiles: PowerProfiles,
+) -> Result<(), HandyError> {
+    let mut settings = get_settings(&app_handle);
+    settings.power_profiles = profiles;
+    write_settings(&app_handle, settings);
+    power_profile::apply(&app_handle);
+    Ok(())
+}
+
+/// Uses the battery or plugged-in profile regardless of the power source until
+/// the next plug or unplug. None goes back to the detected source.
+#[tauri::command]
+#[specta::specta]
+pub fn set_power_profile(app_handle: AppHandle, source: Option<PowerSource>) -> ActivePowerProfile {
+    power_profile::set_manual(&app_handle, source);
+    power_profile::active(&app_handle)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_current_model(app_handle: AppHandle) -> Result<String, HandyError> {
     let settings = get_settings(&app_handle);
     Ok(settings.selected_model)
 }
@@ -85,7 +271,7 @@ pub async fn get_current_model(app_handle: AppHandle) -> Result<String, String>
 #[specta::specta]
 pub async fn get_transcription_model_status(
     transcription_manager: State<'_, Arc<TranscriptionManager>>,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, HandyError> {
     Ok(transcription_manager.get_current_model())
 }
 
@@ -93,7 +279,7 @@ pub async fn get_transcription_model_status(
 #[specta::specta]
 pub async fn is_model_loading(
     transcription_manager: State<'_, Arc<TranscriptionManager>>,
-) -> Result<bool, String> {
+) -> Result<bool, HandyError> {
     // Check if transcription manager has a loaded model
     let current_model = transcription_manager.get_current_model();
     Ok(current_model.is_none())
@@ -103,7 +289,7 @@ pub async fn is_model_loading(
 #[specta::specta]
 pub async fn has_any_models_available(
     model_manager: State<'_, Arc<ModelManager>>,
-) -> Result<bool, String> {
+) -> Result<bool, HandyError> {
     let models = model_manager.get_available_models();
     Ok(models.iter().any(|m| m.is_downloaded))
 }
@@ -112,7 +298,7 @@ pub async fn has_any_models_available(
 #[specta::specta]
 pub async fn has_any_models_or_downloads(
     model_manager: State<'_, Arc<ModelManager>>,
-) -> Result<bool, String> {
+) -> Result<bool, HandyError> {
     let models = model_manager.get_available_models();
     // Return true if any models are downloaded OR if any downloads are in progress
     Ok(models.iter().any(|m| m.is_downloaded))
@@ -123,15 +309,27 @@ pub async fn has_any_models_or_downloads(
 pub async fn cancel_download(
     model_manager: State<'_, Arc<ModelManager>>,
     model_id: String,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     model_manager
         .cancel_download(&model_id)
-        .map_err(|e| e.to_string())
+        .map_err(|e| HandyError::network(e.to_string()))
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_recommended_first_model() -> Result<String, String> {
+pub async fn get_recommended_first_model() -> Result<String, HandyError> {
     // Recommend Parakeet V3 model for first-time users - fastest and most accurate
     Ok("parakeet-tdt-0.6b-v3".to_string())
 }
+
+/// Loads a model and transcribes a recording with it, by default the latest one
+/// in history, reporting speed and memory use on this machine
+#[tauri::command]
+#[specta::specta]
+pub async fn benchmark_model(
+    app: AppHandle,
+    model_id: String,
+    recording_source: Option<RecordingSource>,
+) -> Result<ModelBenchmark, HandyError> {
+    benchmark::benchmark(&app, model_id, recording_source).await
+}
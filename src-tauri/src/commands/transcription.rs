@@ -1,7 +1,22 @@
+use crate::compare::{
+    self, CompareConfig, CompareResult, RecordingSource, TranscriptionComparison,
+};
+use crate::copy_only_session;
+use crate::custom_words::{self, ImportReport, WordListFormat};
+use crate::deferred_paste::{self, DeferredPaste};
+use crate::dictation_session;
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent};
+use crate::file_dictation::FileDictation;
 use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, write_settings, ModelUnloadTimeout};
+use crate::review::{self, PendingReviews, TranscriptReview};
+use crate::settings::{
+    get_settings, write_settings, CancelBehavior, ModelUnloadTimeout, VramGuardBehavior,
+};
+use crate::undo_paste;
 use serde::Serialize;
 use specta::Type;
+use std::path::PathBuf;
 use tauri::{AppHandle, State};
 
 #[derive(Serialize, Type)]
@@ -18,11 +33,27 @@ pub fn set_model_unload_timeout(app: AppHandle, timeout: ModelUnloadTimeout) {
     write_settings(&app, settings);
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn set_vram_guard_behavior(app: AppHandle, behavior: VramGuardBehavior) {
+    let mut settings = get_settings(&app);
+    settings.vram_guard_behavior = behavior;
+    write_settings(&app, settings);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_cancel_behavior(app: AppHandle, behavior: CancelBehavior) {
+    let mut settings = get_settings(&app);
+    settings.cancel_behavior = behavior;
+    write_settings(&app, settings);
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn get_model_load_status(
     transcription_manager: State<TranscriptionManager>,
-) -> Result<ModelLoadStatus, String> {
+) -> Result<ModelLoadStatus, HandyError> {
     Ok(ModelLoadStatus {
         is_loaded: transcription_manager.is_model_loaded(),
         current_model: transcription_manager.get_current_model(),
@@ -33,8 +64,209 @@ pub fn get_model_load_status(
 #[specta::specta]
 pub fn unload_model_manually(
     transcription_manager: State<TranscriptionManager>,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     transcription_manager
         .unload_model()
-        .map_err(|e| format!("Failed to unload model: {}", e))
+        .map_err(|e| HandyError::internal(format!("Failed to unload model: {}", e)))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn start_file_dictation(
+    app: AppHandle,
+    file_dictation: State<FileDictation>,
+    path: String,
+    append: bool,
+) -> Result<(), HandyError> {
+    if path.trim().is_empty() {
+        return Err(HandyError::invalid_input(
+            "A file path is required".to_string(),
+        ));
+    }
+    file_dictation.start(PathBuf::from(&path), append)?;
+    emit_event(&app, AppEvent::FileDictationChanged(Some(path)));
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn stop_file_dictation(
+    app: AppHandle,
+    file_dictation: State<FileDictation>,
+) -> Result<Option<String>, HandyError> {
+    let path = file_dictation
+        .stop()
+        .map(|path| path.to_string_lossy().to_string());
+    emit_event(&app, AppEvent::FileDictationChanged(None));
+    Ok(path)
+}
+
+/// Pastes a transcript held for review, replacing it with `edited_text` when given
+#[tauri::command]
+#[specta::specta]
+pub fn confirm_transcript(
+    app: AppHandle,
+    id: u32,
+    edited_text: Option<String>,
+) -> Result<(), HandyError> {
+    review::confirm(&app, id, edited_text)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn discard_transcript(app: AppHandle, id: u32) -> Result<(), HandyError> {
+    review::discard(&app, id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_pending_transcript_reviews(
+    reviews: State<PendingReviews>,
+) -> Result<Vec<TranscriptReview>, HandyError> {
+    Ok(reviews.list())
+}
+
+/// Parks the next transcript instead of pasting it, see `flush_deferred_paste`
+#[tauri::command]
+#[specta::specta]
+pub fn defer_paste(deferred: State<DeferredPaste>) -> Result<(), HandyError> {
+    deferred.arm();
+    Ok(())
+}
+
+/// Pastes the deferred transcript into the currently focused field
+#[tauri::command]
+#[specta::specta]
+pub fn flush_deferred_paste(app: AppHandle) -> Result<(), HandyError> {
+    deferred_paste::flush(&app)
+}
+
+/// Deletes the text of the most recent paste, see `undo_paste`
+#[tauri::command]
+#[specta::specta]
+pub async fn undo_last_transcription(app: AppHandle) -> Result<(), HandyError> {
+    // Waits for held modifiers, off the main thread
+    tauri::async_runtime::spawn_blocking(move || undo_paste::undo(&app))
+        .await
+        .map_err(|e| HandyError::internal(format!("Undo task failed: {}", e)))?
+}
+
+/// Whether transcripts are currently copied instead of pasted, see `copy_only_session`
+#[tauri::command]
+#[specta::specta]
+pub fn get_copy_only_session(app: AppHandle) -> bool {
+    copy_only_session::is_active(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_copy_only_session(app: AppHandle, active: bool) {
+    copy_only_session::set(&app, active);
+}
+
+/// Starts a continuous dictation session, see `dictation_session`
+#[tauri::command]
+#[specta::specta]
+pub fn start_dictation_session(app: AppHandle) -> Result<(), HandyError> {
+    dictation_session::start(&app)
+}
+
+/// Ends the dictation session once its last segment is transcribed
+#[tauri::command]
+#[specta::specta]
+pub fn end_dictation_session(app: AppHandle) -> Result<(), HandyError> {
+    dictation_session::end(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_dictation_session(app: AppHandle) -> bool {
+    dictation_session::is_active(&app)
+}
+
+/// Transcribes one recording with each configuration in turn, reporting every run
+/// through `compare-progress`
+#[tauri::command]
+#[specta::specta]
+pub async fn compare_transcriptions(
+    app: AppHandle,
+    recording_source: RecordingSource,
+    configs: Vec<CompareConfig>,
+) -> Result<Vec<CompareResult>, HandyError> {
+    compare::compare(&app, recording_source, configs).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_transcription_comparison(
+    comparison: State<TranscriptionComparison>,
+) -> Result<(), HandyError> {
+    comparison.cancel();
+    Ok(())
+}
+
+/// Merges a word list into `custom_words`, or the list of `language`, see
+/// `custom_words`. Without a path, text replacements are read from the system's
+/// preferences (macOS only). With `dry_run` nothing is saved and the report lists
+/// what would be added.
+#[tauri::command]
+#[specta::specta]
+pub fn import_custom_words(
+    app: AppHandle,
+    path: Option<String>,
+    format: WordListFormat,
+    dry_run: bool,
+    language: Option<String>,
+) -> Result<ImportReport, HandyError> {
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None if format == WordListFormat::MacosTextReplacements => {
+            system_text_replacements_path(&app)?
+        }
+        None => {
+            return Err(HandyError::invalid_input(
+                "A file path is required".to_string(),
+            ))
+        }
+    };
+    let words = custom_words::read(&path, format)?;
+    let mut settings = get_settings(&app);
+    let list = custom_words::list_mut(&mut settings, language.as_deref())?;
+    let report = custom_words::merge(list, words, dry_run);
+    if !dry_run && !report.added.is_empty() {
+        write_settings(&app, settings);
+    }
+    Ok(report)
+}
+
+/// Writes `custom_words`, or the list of `language`, to `path`
+#[tauri::command]
+#[specta::specta]
+pub fn export_custom_words(
+    app: AppHandle,
+    path: String,
+    format: WordListFormat,
+    language: Option<String>,
+) -> Result<(), HandyError> {
+    let mut settings = get_settings(&app);
+    let words = custom_words::list_mut(&mut settings, language.as_deref())?;
+    custom_words::write(&PathBuf::from(path), words, format)
+}
+
+#[cfg(target_os = "macos")]
+fn system_text_replacements_path(app: &AppHandle) -> Result<PathBuf, HandyError> {
+    use tauri::Manager;
+
+    app.path()
+        .home_dir()
+        .map(|home| home.join("Library/Preferences/.GlobalPreferences.plist"))
+        .map_err(|e| HandyError::io(format!("Failed to find the home directory: {}", e)))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn system_text_replacements_path(_app: &AppHandle) -> Result<PathBuf, HandyError> {
+    Err(HandyError::invalid_input(
+        "System text replacements are only available on macOS, pass an exported file instead"
+            .to_string(),
+    ))
 }
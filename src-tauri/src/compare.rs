@@ -0,0 +1,314 @@
+//! Side-by-side transcription of one recording with several model and language
+//! configurations, to pick the best setup for a voice or accent.
+
+use crate::audio_toolkit::read_audio_file;
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent};
+use crate::managers::history::HistoryManager;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::{TranscriptionManager, TranscriptionOptions};
+use crate::settings::{get_settings, CorrectionAlgorithm, DecodingParams};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+/// Each configuration loads a model, so keep comparisons to a handful of runs
+pub const MAX_COMPARE_CONFIGS: usize = 6;
+
+/// Audio to transcribe in a comparison
+#[derive(Deserialize, Debug, Clone, Type)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum RecordingSource {
+    HistoryEntry(i64),
+    /// Path to a WAV file
+    File(String),
+}
+
+/// One run of a comparison. Fields left out use the current settings.
+#[derive(Deserialize, Debug, Clone, Type)]
+pub struct CompareConfig {
+    pub model_id: String,
+    pub language: Option<String>,
+    pub translate_to_english: Option<bool>,
+    pub custom_words: Option<Vec<String>>,
+    pub word_correction_threshold: Option<f64>,
+    pub correction_algorithm: Option<CorrectionAlgorithm>,
+    /// Whisper decoding parameters to try without saving them
+    pub decoding: Option<DecodingParams>,
+}
+
+impl CompareConfig {
+    fn options(&self, base: &TranscriptionOptions) -> TranscriptionOptions {
+        let mut options = base.clone();
+        if let Some(language) = &self.language {
+            options.language = language.clone();
+        }
+        if let Some(translate) = self.translate_to_english {
+            options.translate_to_english = translate;
+        }
+        if let Some(words) = &self.custom_words {
+            // Exactly these words, for whatever language
+            options.custom_words = words.clone();
+            options.custom_words_by_language.clear();
+        }
+        if let Some(threshold) = self.word_correction_threshold {
+            options.word_correction_threshold = threshold;
+        }
+        if let Some(algorithm) = self.correction_algorithm {
+            options.correction_algorithm = algorithm;
+        }
+        if let Some(decoding) = self.decoding {
+            options.decoding = decoding.clamped();
+        }
+        options
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct CompareResult {
+    pub model_id: String,
+    pub text: String,
+    /// Why the run failed, `text` is empty in that case
+    pub error: Option<String>,
+    /// Time spent loading the model, 0 when it was already loaded
+    pub load_ms: u32,
+    pub inference_ms: u32,
+}
+
+/// Payload of the `compare-progress` event, sent after every run
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct CompareProgress {
+    pub completed: u32,
+    pub total: u32,
+    pub result: CompareResult,
+}
+
+/// Guards against overlapping comparisons and carries cancellation requests. Model
+/// benchmarks swap models as well and take the same guard.
+#[derive(Default)]
+pub struct TranscriptionComparison {
+    running: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl TranscriptionComparison {
+    pub fn begin(&self) -> Result<(), HandyError> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err(HandyError::invalid_input(
+                "A transcription comparison or model benchmark is already running".to_string(),
+            ));
+        }
+        self.cancelled.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn finish(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Stops the comparison after the run in progress
+    pub fn cancel(&self) {
+        if self.running.load(Ordering::SeqCst) {
+            self.cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+fn validate_configs(configs: &[CompareConfig]) -> Result<(), HandyError> {
+    if configs.is_empty() {
+        return Err(HandyError::invalid_input(
+            "At least one configuration is required".to_string(),
+        ));
+    }
+    if configs.len() > MAX_COMPARE_CONFIGS {
+        return Err(HandyError::invalid_input(format!(
+            "At most {} configurations can be compared at once",
+            MAX_COMPARE_CONFIGS
+        )));
+    }
+    Ok(())
+}
+
+pub async fn resolve_source(
+    app: &AppHandle,
+    source: RecordingSource,
+) -> Result<PathBuf, HandyError> {
+    match source {
+        RecordingSource::HistoryEntry(id) => {
+            let history = app.state::<Arc<HistoryManager>>();
+            let entry = history
+                .get_entry_by_id(id)
+                .await?
+                .ok_or_else(|| HandyError::not_found(format!("No history entry with id {}", id)))?;
+            Ok(history.get_audio_file_path(&entry.file_name))
+        }
+        RecordingSource::File(path) => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Transcribes a recording once per configuration, one model in memory at a time.
+/// The model that was loaded before is restored afterwards.
+pub async fn compare(
+    app: &AppHandle,
+    source: RecordingSource,
+    configs: Vec<CompareConfig>,
+) -> Result<Vec<CompareResult>, HandyError> {
+    validate_configs(&configs)?;
+    let model_manager = app.state::<Arc<ModelManager>>();
+    for config in &configs {
+        let downloaded = model_manager
+            .get_model_info(&config.model_id)
+            .is_some_and(|m| m.is_downloaded);
+        if !downloaded {
+            return Err(HandyError::not_found(format!(
+                "Model not downloaded: {}",
+                config.model_id
+            )));
+        }
+    }
+
+    let path = resolve_source(app, source).await?;
+    let audio = read_audio_file(&path)
+        .map_err(|e| HandyError::io(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    let comparison = app.state::<TranscriptionComparison>();
+    comparison.begin()?;
+    let app_clone = app.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || run_configs(&app_clone, audio, configs)).await;
+    comparison.finish();
+    result.map_err(|e| HandyError::internal(e.to_string()))
+}
+
+fn run_configs(
+    app: &AppHandle,
+    audio: Vec<f32>,
+    configs: Vec<CompareConfig>,
+) -> Vec<CompareResult> {
+    let tm = app.state::<Arc<TranscriptionManager>>();
+    let comparison = app.state::<TranscriptionComparison>();
+    let previous_model = tm.get_current_model();
+    let base = TranscriptionOptions::from_settings(&get_settings(app));
+    let total = configs.len() as u32;
+    let mut results = Vec::with_capacity(configs.len());
+
+    for config in configs {
+        if comparison.is_cancelled() {
+            info!(
+                "Transcription comparison cancelled after {} run(s)",
+                results.len()
+            );
+            break;
+        }
+        let result = run_config(&tm, audio.clone(), &config, &base);
+        results.push(result.clone());
+        emit_event(
+            app,
+            AppEvent::CompareProgress(CompareProgress {
+                completed: results.len() as u32,
+                total,
+                result,
+            }),
+        );
+    }
+
+    restore_model(&tm, previous_model);
+    results
+}
+
+/// Loads the model that was in use before models were swapped, or unloads
+pub fn restore_model(tm: &TranscriptionManager, previous_model: Option<String>) {
+    if tm.get_current_model() != previous_model {
+        let restored = match &previous_model {
+            Some(model_id) => tm.load_model(model_id),
+            None => tm.unload_model(),
+        };
+        if let Err(e) = restored {
+            warn!("Failed to restore the model after swapping models: {}", e);
+        }
+    }
+}
+
+fn run_config(
+    tm: &TranscriptionManager,
+    audio: Vec<f32>,
+    config: &CompareConfig,
+    base: &TranscriptionOptions,
+) -> CompareResult {
+    let mut result = CompareResult {
+        model_id: config.model_id.clone(),
+        text: String::new(),
+        error: None,
+        load_ms: 0,
+        inference_ms: 0,
+    };
+
+    if tm.get_current_model().as_deref() != Some(config.model_id.as_str()) {
+        // Free the current model first so only one is held in memory
+        let started = Instant::now();
+        let loaded = tm
+            .unload_model()
+            .and_then(|_| tm.load_model(&config.model_id));
+        result.load_ms = started.elapsed().as_millis() as u32;
+        if let Err(e) = loaded {
+            result.error = Some(format!("Failed to load model: {}", e));
+            return result;
+        }
+    }
+
+    let started = Instant::now();
+    match tm.transcribe_with(audio, &config.options(base)) {
+        Ok(transcription) => result.text = transcription.text,
+        Err(e) => result.error = Some(e.to_string()),
+    }
+    result.inference_ms = started.elapsed().as_millis() as u32;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(model_id: &str) -> CompareConfig {
+        CompareConfig {
+            model_id: model_id.to_string(),
+            language: None,
+            translate_to_english: None,
+            custom_words: None,
+            word_correction_threshold: None,
+            correction_algorithm: None,
+            decoding: None,
+        }
+    }
+
+    #[test]
+    fn config_count_is_capped() {
+        assert!(validate_configs(&[]).is_err());
+        assert!(validate_configs(&vec![config("small"); MAX_COMPARE_CONFIGS]).is_ok());
+        assert!(validate_configs(&vec![config("small"); MAX_COMPARE_CONFIGS + 1]).is_err());
+    }
+
+    #[test]
+    fn only_one_comparison_runs_at_a_time() {
+        let comparison = TranscriptionComparison::default();
+        comparison.cancel();
+        assert!(comparison.begin().is_ok());
+        assert!(!comparison.is_cancelled());
+        assert!(comparison.begin().is_err());
+
+        comparison.cancel();
+        assert!(comparison.is_cancelled());
+        comparison.finish();
+        assert!(comparison.begin().is_ok());
+        assert!(!comparison.is_cancelled());
+    }
+}
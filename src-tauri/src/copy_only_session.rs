@@ -0,0 +1,85 @@
+//! Copy-only session: while on, transcripts are copied to the clipboard instead
+//! of pasted, whatever the paste method and clipboard handling settings say.
+//!
+//! Meant for a stretch of dictating into something that doesn't take pastes well,
+//! like a remote desktop or a password prompt. The session is toggled from a
+//! shortcut or the tray, lives only in memory and is off again after a restart;
+//! the settings themselves are never changed.
+
+use crate::events::{emit_event, AppEvent};
+use crate::settings::{AppSettings, ClipboardHandling, PasteMethod};
+use crate::tray;
+use log::info;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager};
+
+#[derive(Default)]
+pub struct CopyOnlySession(AtomicBool);
+
+impl CopyOnlySession {
+    pub fn is_active(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Paste method and clipboard handling to use for the next paste
+    pub fn output(&self, settings: &AppSettings) -> (PasteMethod, ClipboardHandling) {
+        if self.is_active() {
+            (PasteMethod::None, ClipboardHandling::CopyToClipboard)
+        } else {
+            (settings.paste_method, settings.clipboard_handling)
+        }
+    }
+}
+
+pub fn is_active(app: &AppHandle) -> bool {
+    app.state::<CopyOnlySession>().is_active()
+}
+
+/// Turns the session on or off and tells the overlay and tray
+pub fn set(app: &AppHandle, active: bool) {
+    if app
+        .state::<CopyOnlySession>()
+        .0
+        .swap(active, Ordering::SeqCst)
+        == active
+    {
+        return;
+    }
+    info!(
+        "Copy-only session {}",
+        if active { "started" } else { "ended" }
+    );
+    emit_event(app, AppEvent::CopyOnlySessionChanged(active));
+    tray::refresh_tray_menu(app);
+}
+
+/// Flips the session, returning whether it is now on
+pub fn toggle(app: &AppHandle) -> bool {
+    let active = !is_active(app);
+    set(app, active);
+    active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    #[test]
+    fn active_session_copies_instead_of_pasting() {
+        let mut settings = get_default_settings();
+        settings.paste_method = PasteMethod::CtrlV;
+        settings.clipboard_handling = ClipboardHandling::DontModify;
+
+        let session = CopyOnlySession::default();
+        assert_eq!(
+            session.output(&settings),
+            (PasteMethod::CtrlV, ClipboardHandling::DontModify)
+        );
+        session.0.store(true, Ordering::SeqCst);
+        assert_eq!(
+            session.output(&settings),
+            (PasteMethod::None, ClipboardHandling::CopyToClipboard)
+        );
+    }
+}
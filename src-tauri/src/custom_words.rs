@@ -0,0 +1,347 @@
+//! Importing and exporting the `custom_words` list, and picking the words that
+//! correct a transcript.
+//!
+//! `custom_words` applies to every language, `custom_words_by_language` adds lists
+//! used only for transcripts in one language. A language's word replaces a global
+//! one that differs only in case, so `GitHub` for English can override `github`.
+//! Lists saved before there were languages are the global list as they are.
+//!
+//! Words come from plain text (one per line), CSV (the first column) or macOS text
+//! replacements, whose phrases are the words. Imports merge into the list, skipping
+//! words already in it regardless of case.
+//!
+//! Text replacements are read from a plist: the system's global preferences, or a
+//! file of replacements dragged out of the Text Replacements settings.
+
+use crate::error::HandyError;
+use crate::settings::AppSettings;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Correction compares every transcribed word with every custom word, so beyond
+/// this many dictation slows down and more words get corrected by mistake
+pub const RECOMMENDED_MAX_CUSTOM_WORDS: usize = 1_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum WordListFormat {
+    /// One word per line, lines starting with `#` are skipped
+    Text,
+    /// The first column of each row, a `word` or `term` header is skipped
+    Csv,
+    /// A plist of macOS text replacements, import only
+    MacosTextReplacements,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct ImportReport {
+    /// Words new to the list, in file order
+    pub added: Vec<String>,
+    /// Words already in the list or repeated in the file
+    pub duplicates: u32,
+    /// Nothing was saved, `added` is what the import would add
+    pub dry_run: bool,
+    /// Size of the list after the import
+    pub total: u32,
+    /// Set when the list grows past `RECOMMENDED_MAX_CUSTOM_WORDS`. No words are
+    /// dropped, every one is still used for correction.
+    pub warning: Option<String>,
+}
+
+/// The words correcting a transcript in `language`: the language's own, then the
+/// global ones they don't replace. A regional code such as `zh-Hans` also gets
+/// the words of its base language.
+pub fn words_for_language(
+    global: &[String],
+    by_language: &HashMap<String, Vec<String>>,
+    language: Option<&str>,
+) -> Vec<String> {
+    let mut codes = Vec::new();
+    if let Some(code) = language {
+        codes.push(code);
+        if let Some((base, _)) = code.split_once('-') {
+            codes.push(base);
+        }
+    }
+    let language_words = codes
+        .into_iter()
+        .filter_map(|code| by_language.get(code))
+        .flatten();
+
+    let mut seen = HashSet::new();
+    language_words
+        .chain(global)
+        .filter(|word| seen.insert(word.to_lowercase()))
+        .cloned()
+        .collect()
+}
+
+/// The list of `language`, or the global list without one. A language list that
+/// doesn't exist yet is created empty.
+pub fn list_mut<'a>(
+    settings: &'a mut AppSettings,
+    language: Option<&str>,
+) -> Result<&'a mut Vec<String>, HandyError> {
+    let Some(language) = language else {
+        return Ok(&mut settings.custom_words);
+    };
+    let language = language.trim();
+    if language.is_empty() || language == "auto" {
+        return Err(HandyError::invalid_input(format!(
+            "'{}' isn't a language custom words can be kept for",
+            language
+        )));
+    }
+    Ok(settings
+        .custom_words_by_language
+        .entry(language.to_string())
+        .or_default())
+}
+
+/// Reads the words of a list in `format`
+pub fn read(path: &Path, format: WordListFormat) -> Result<Vec<String>, HandyError> {
+    match format {
+        WordListFormat::Text => Ok(parse_text(&read_to_string(path)?)),
+        WordListFormat::Csv => Ok(parse_csv(&read_to_string(path)?)),
+        WordListFormat::MacosTextReplacements => {
+            let value = plist::Value::from_file(path).map_err(|e| {
+                HandyError::invalid_input(format!(
+                    "Failed to read text replacements from {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            Ok(text_replacement_phrases(&value))
+        }
+    }
+}
+
+/// Writes `words` in `format`, replacing the file
+pub fn write(path: &Path, words: &[String], format: WordListFormat) -> Result<(), HandyError> {
+    let contents = match format {
+        WordListFormat::Text => words.iter().map(|word| format!("{}\n", word)).collect(),
+        WordListFormat::Csv => std::iter::once("word\n".to_string())
+            .chain(words.iter().map(|word| format!("{}\n", csv_field(word))))
+            .collect::<String>(),
+        WordListFormat::MacosTextReplacements => {
+            return Err(HandyError::invalid_input(
+                "Custom words can't be exported as text replacements".to_string(),
+            ))
+        }
+    };
+    std::fs::write(path, contents).map_err(|e| {
+        HandyError::io(format!(
+            "Failed to write custom words to {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Merges `imported` into `existing`, which receives the new words unless `dry_run`
+pub fn merge(existing: &mut Vec<String>, imported: Vec<String>, dry_run: bool) -> ImportReport {
+    let mut seen: HashSet<String> = existing.iter().map(|w| w.to_lowercase()).collect();
+    let mut added = Vec::new();
+    let mut duplicates = 0;
+    for word in imported {
+        if seen.insert(word.to_lowercase()) {
+            added.push(word);
+        } else {
+            duplicates += 1;
+        }
+    }
+
+    let total = existing.len() + added.len();
+    if !dry_run {
+        existing.extend(added.iter().cloned());
+    }
+    ImportReport {
+        added,
+        duplicates,
+        dry_run,
+        total: total as u32,
+        warning: (total > RECOMMENDED_MAX_CUSTOM_WORDS).then(|| {
+            format!(
+                "{} custom words is more than the recommended {}. All of them are still \
+                 used, but correction compares each transcribed word with every one, which \
+                 slows dictation and corrects more words by mistake.",
+                total, RECOMMENDED_MAX_CUSTOM_WORDS
+            )
+        }),
+    }
+}
+
+fn read_to_string(path: &Path) -> Result<String, HandyError> {
+    std::fs::read_to_string(path).map_err(|e| {
+        HandyError::io(format!(
+            "Failed to read custom words from {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn parse_text(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_csv(contents: &str) -> Vec<String> {
+    let mut words: Vec<String> = csv_first_fields(contents)
+        .into_iter()
+        .map(|field| field.trim().to_string())
+        .filter(|field| !field.is_empty())
+        .collect();
+    if words
+        .first()
+        .is_some_and(|first| ["word", "words", "term"].contains(&first.to_lowercase().as_str()))
+    {
+        words.remove(0);
+    }
+    words
+}
+
+/// First field of every row, with quoted fields unquoted. Quoted fields may hold
+/// commas, line breaks and `""` for a quote.
+fn csv_first_fields(contents: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut first_column = true;
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                if first_column {
+                    field.push('"');
+                }
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => first_column = false,
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                first_column = true;
+            }
+            '\r' if !in_quotes => {}
+            _ if first_column => field.push(c),
+            _ => {}
+        }
+    }
+    if !field.is_empty() {
+        fields.push(field);
+    }
+    fields
+}
+
+fn csv_field(word: &str) -> String {
+    if word.contains([',', '"', '\n']) {
+        format!("\"{}\"", word.replace('"', "\"\""))
+    } else {
+        word.to_string()
+    }
+}
+
+/// Phrases of the text replacements in `value`: either global preferences with
+/// `NSUserDictionaryReplacementItems`, or an exported array of replacements
+fn text_replacement_phrases(value: &plist::Value) -> Vec<String> {
+    let items = value
+        .as_dictionary()
+        .and_then(|dict| dict.get("NSUserDictionaryReplacementItems"))
+        .unwrap_or(value);
+    let Some(items) = items.as_array() else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(plist::Value::as_dictionary)
+        // Preferences call the phrase `with`, exported files `phrase`
+        .filter_map(|item| item.get("with").or_else(|| item.get("phrase")))
+        .filter_map(plist::Value::as_string)
+        .map(str::trim)
+        .filter(|phrase| !phrase.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_are_parsed_and_merged_without_duplicates() {
+        assert_eq!(
+            parse_text("Kubernetes\n\n# comment\n  gRPC  \n"),
+            vec!["Kubernetes", "gRPC"]
+        );
+        assert_eq!(
+            parse_csv("Word,Notes\r\nPostgreSQL,db\n\"Smith, Jr.\",\"a\nb\"\n\"say \"\"hi\"\"\"\n"),
+            vec!["PostgreSQL", "Smith, Jr.", "say \"hi\""]
+        );
+
+        let mut words = vec!["Kubernetes".to_string()];
+        let imported = vec![
+            "kubernetes".to_string(),
+            "gRPC".to_string(),
+            "GRPC".to_string(),
+        ];
+        let report = merge(&mut words, imported.clone(), true);
+        assert_eq!(report.added, vec!["gRPC"]);
+        assert_eq!(report.duplicates, 2);
+        assert_eq!(report.total, 2);
+        assert_eq!(words.len(), 1);
+
+        merge(&mut words, imported, false);
+        assert_eq!(words, vec!["Kubernetes", "gRPC"]);
+    }
+
+    #[test]
+    fn language_words_come_first_and_replace_global_ones() {
+        let global = vec!["github".to_string(), "Kubernetes".to_string()];
+        let by_language = HashMap::from([
+            ("en".to_string(), vec!["GitHub".to_string()]),
+            ("fr".to_string(), vec!["Québec".to_string()]),
+            ("zh".to_string(), vec!["微信".to_string()]),
+        ]);
+        assert_eq!(
+            words_for_language(&global, &by_language, Some("en")),
+            vec!["GitHub", "Kubernetes"]
+        );
+        assert_eq!(
+            words_for_language(&global, &by_language, Some("zh-Hans")),
+            vec!["微信", "github", "Kubernetes"]
+        );
+        assert_eq!(
+            words_for_language(&global, &by_language, None),
+            vec!["github", "Kubernetes"]
+        );
+    }
+
+    #[test]
+    fn text_replacement_phrases_are_read_from_either_plist() {
+        let item = |key: &str, phrase: &str| {
+            let mut dict = plist::Dictionary::new();
+            dict.insert("replace".to_string(), "k8".into());
+            dict.insert(key.to_string(), phrase.into());
+            plist::Value::Dictionary(dict)
+        };
+        let mut preferences = plist::Dictionary::new();
+        preferences.insert(
+            "NSUserDictionaryReplacementItems".to_string(),
+            plist::Value::Array(vec![item("with", "Kubernetes")]),
+        );
+        assert_eq!(
+            text_replacement_phrases(&plist::Value::Dictionary(preferences)),
+            vec!["Kubernetes"]
+        );
+
+        let exported = plist::Value::Array(vec![item("phrase", "gRPC")]);
+        assert_eq!(text_replacement_phrases(&exported), vec!["gRPC"]);
+    }
+}
@@ -0,0 +1,167 @@
+//! Deferred paste: a transcript is parked instead of pasted, with a chip in the
+//! overlay, and pasted into whatever has focus once the user flushes it.
+//!
+//! Transcripts are parked when `defer_paste` was requested for the next dictation,
+//! or, with `defer_paste_on_focus_change`, when the focused application changed
+//! while transcribing. Only one transcript is pending at a time and it is not
+//! persisted across restarts.
+
+use crate::actions::paste_transcript;
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent};
+use crate::overlay;
+use crate::settings::AppSettings;
+use crate::utils::{frontmost_application, FrontmostApplication};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+#[derive(Default)]
+pub struct DeferredPaste {
+    /// Application focused when each recording started, keyed by binding id
+    targets: Mutex<HashMap<String, FrontmostApplication>>,
+    /// Parks the next transcript regardless of focus
+    armed: AtomicBool,
+    pending: Mutex<Option<String>>,
+}
+
+impl DeferredPaste {
+    /// Remembers the focused application of a recording that is starting
+    pub fn capture_target(&self, binding_id: &str, target_app: Option<FrontmostApplication>) {
+        let mut targets = self.targets.lock().unwrap();
+        match target_app {
+            Some(app) => {
+                targets.insert(binding_id.to_string(), app);
+            }
+            None => {
+                targets.remove(binding_id);
+            }
+        }
+    }
+
+    /// Hands over the focused application captured for a recording once it stops
+    pub fn take_target(&self, binding_id: &str) -> Option<FrontmostApplication> {
+        self.targets.lock().unwrap().remove(binding_id)
+    }
+
+    pub fn arm(&self) {
+        self.armed.store(true, Ordering::SeqCst);
+    }
+
+    /// Parks a transcript, returning the one it replaced
+    fn park(&self, text: String) -> Option<String> {
+        self.pending.lock().unwrap().replace(text)
+    }
+
+    fn take(&self) -> Option<String> {
+        self.pending.lock().unwrap().take()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.lock().unwrap().is_some()
+    }
+}
+
+/// True when focus moved from `target` to another application
+fn focus_changed(target: &FrontmostApplication, current: &FrontmostApplication) -> bool {
+    !current.matches(target.identifier.as_deref().unwrap_or(&target.name))
+}
+
+/// Decides whether a finished transcript should be parked instead of pasted.
+/// Consumes a pending `defer_paste` request.
+pub fn should_defer(
+    app: &AppHandle,
+    settings: &AppSettings,
+    target: Option<&FrontmostApplication>,
+) -> bool {
+    let state = app.state::<DeferredPaste>();
+    if state.armed.swap(false, Ordering::SeqCst) {
+        return true;
+    }
+    let Some(target) = target.filter(|_| settings.defer_paste_on_focus_change) else {
+        return false;
+    };
+    match frontmost_application() {
+        Some(current) if focus_changed(target, &current) => {
+            info!(
+                "Focus moved from '{}' to '{}', deferring the paste",
+                target.name, current.name
+            );
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Parks a transcript and shows the pending chip in the overlay, unless the
+/// dictation's binding has the overlay off
+pub fn park(app: &AppHandle, text: String, show_overlay: bool) {
+    if let Some(replaced) = app.state::<DeferredPaste>().park(text.clone()) {
+        debug!("Deferred transcript replaced by a newer one");
+        emit_event(app, AppEvent::DeferredPasteReplaced(replaced));
+    }
+    emit_event(app, AppEvent::DeferredPasteChanged(Some(text)));
+    if !show_overlay {
+        return;
+    }
+    let app_clone = app.clone();
+    let _ = app.run_on_main_thread(move || overlay::show_deferred_overlay(&app_clone));
+}
+
+/// Pastes the parked transcript into whatever has focus now
+pub fn flush(app: &AppHandle) -> Result<(), HandyError> {
+    let text = app
+        .state::<DeferredPaste>()
+        .take()
+        .ok_or_else(|| HandyError::not_found("No deferred transcript to paste".to_string()))?;
+    emit_event(app, AppEvent::DeferredPasteChanged(None));
+    let app = app.clone();
+    std::thread::spawn(move || paste_transcript(&app, text, None));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn application(name: &str, identifier: Option<&str>) -> FrontmostApplication {
+        FrontmostApplication {
+            name: name.to_string(),
+            identifier: identifier.map(str::to_string),
+            path: None,
+            window_id: None,
+        }
+    }
+
+    #[test]
+    fn newer_transcript_replaces_pending_one() {
+        let deferred = DeferredPaste::default();
+        assert_eq!(deferred.park("first".to_string()), None);
+        assert_eq!(
+            deferred.park("second".to_string()),
+            Some("first".to_string())
+        );
+        assert!(deferred.is_pending());
+        assert_eq!(deferred.take(), Some("second".to_string()));
+        assert!(!deferred.is_pending());
+    }
+
+    #[test]
+    fn focus_change_compares_identifiers() {
+        let editor = application("Code", Some("com.microsoft.VSCode"));
+        assert!(!focus_changed(
+            &editor,
+            &application("Visual Studio Code", Some("com.microsoft.vscode"))
+        ));
+        assert!(focus_changed(
+            &editor,
+            &application("Safari", Some("com.apple.Safari"))
+        ));
+        assert!(!focus_changed(
+            &application("firefox", None),
+            &application("Firefox", None)
+        ));
+    }
+}
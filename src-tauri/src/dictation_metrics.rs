@@ -0,0 +1,72 @@
+//! Speaking pace of the current dictation and of past ones.
+//!
+//! Transcription only runs once a recording stops, so while recording only the
+//! elapsed time is reported. Word count and words per minute are computed from the
+//! final transcript and stored on the history entry.
+
+use crate::events::{emit_event, AppEvent, DictationMetrics};
+use crate::managers::audio::AudioRecordingManager;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const METRICS_INTERVAL: Duration = Duration::from_secs(1);
+/// Rates of shorter recordings swing wildly on a single word
+const MIN_RATE_DURATION: Duration = Duration::from_secs(2);
+
+/// Words in a transcript, ignoring stray punctuation
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+/// Speaking rate of a transcript recorded over `duration`
+pub fn words_per_minute(text: &str, duration: Duration) -> Option<f64> {
+    if duration < MIN_RATE_DURATION {
+        return None;
+    }
+    let words = word_count(text);
+    if words == 0 {
+        return None;
+    }
+    Some(words as f64 * 60.0 / duration.as_secs_f64())
+}
+
+/// Emits `dictation-metrics` once a second while the binding's recording runs
+pub fn start_ticker(app: &AppHandle, binding_id: &str) {
+    let app = app.clone();
+    let binding_id = binding_id.to_string();
+    let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
+    let started = Instant::now();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(METRICS_INTERVAL);
+        if rm.active_binding().as_deref() != Some(binding_id.as_str()) {
+            break;
+        }
+        emit_event(
+            &app,
+            AppEvent::DictationMetrics(DictationMetrics {
+                elapsed_ms: started.elapsed().as_millis() as u32,
+                word_count: None,
+                words_per_minute: None,
+            }),
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_counts_words_over_recording_time() {
+        assert_eq!(word_count("Hello, world — this is Handy."), 5);
+        assert_eq!(
+            words_per_minute("one two three four five", Duration::from_secs(3)),
+            Some(100.0)
+        );
+        assert_eq!(words_per_minute("hello", Duration::from_secs(1)), None);
+        assert_eq!(words_per_minute("  ", Duration::from_secs(10)), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Continuous dictation: a session keeps recording, and every pause in speech ends
+//! a segment that is transcribed and pasted while the next one records.
+//!
+//! One recording runs on one stream for the whole session. A cut splits off the
+//! audio since the previous cut and the recording goes on, so nothing said at a
+//! cut is lost. Segments go through the regular transcription pipeline under the
+//! `toggle_dictation_session` binding, so that binding's feedback and review
+//! overrides apply to them. The start and stop sounds mark the session rather than
+//! every segment. A segment is only cut once the previous one went through the
+//! pipeline, which keeps pastes in order; when transcription falls behind,
+//! segments just get longer.
+
+use crate::actions::ACTION_MAP;
+use crate::audio_feedback::{play_feedback_blocking, SoundType};
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent};
+use crate::managers::audio::AudioRecordingManager;
+use crate::pipeline::ActiveOperations;
+use crate::settings::{get_settings, DICTATION_SESSION_BINDING};
+use crate::tray::{change_tray_icon, TrayIconState};
+use log::{debug, info, warn};
+use serde::Serialize;
+use specta::Type;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Longest wait for the last segment to release the recorder
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+/// Action that records and transcribes the segments
+const TRANSCRIBE_ACTION: &str = "transcribe";
+const SHORTCUT: &str = "dictation_session";
+
+/// Payload of `dictation-segment`, sent when a segment is cut
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+pub struct DictationSegment {
+    /// Counts the session's segments from 1
+    pub segment: u32,
+    /// The segment's pipeline operation, to match it with `paste-done`
+    pub operation_id: Option<u32>,
+}
+
+#[derive(Default)]
+pub struct DictationSession {
+    active: AtomicBool,
+    segments: AtomicU32,
+    /// A cut segment is still going through the pipeline
+    segment_in_flight: AtomicBool,
+    /// A segment is being cut, so stopping it splits the recording instead
+    cutting: AtomicBool,
+    /// Held while cutting a segment or ending the session
+    transition: Mutex<()>,
+}
+
+impl DictationSession {
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+}
+
+pub fn is_active(app: &AppHandle) -> bool {
+    app.try_state::<DictationSession>()
+        .is_some_and(|session| session.is_active())
+}
+
+/// Whether the session's binding is stopped to cut a segment, rather than to end
+/// the recording
+pub fn is_cutting(app: &AppHandle) -> bool {
+    app.try_state::<DictationSession>()
+        .is_some_and(|session| session.cutting.load(Ordering::SeqCst))
+}
+
+pub fn start(app: &AppHandle) -> Result<(), HandyError> {
+    let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
+    if rm.is_recording() {
+        return Err(HandyError::invalid_input(
+            "A recording is already in progress".to_string(),
+        ));
+    }
+    let session = app.state::<DictationSession>();
+    if session.active.swap(true, Ordering::SeqCst) {
+        return Err(HandyError::invalid_input(
+            "A dictation session is already running".to_string(),
+        ));
+    }
+    session.segments.store(0, Ordering::SeqCst);
+    session.segment_in_flight.store(false, Ordering::SeqCst);
+    info!("Dictation session started");
+    emit_event(app, AppEvent::DictationSessionChanged(true));
+
+    let app = app.clone();
+    thread::spawn(move || {
+        // Played before recording, muting while recording would silence it
+        let feedback = get_settings(&app).feedback_mode_for(DICTATION_SESSION_BINDING);
+        play_feedback_blocking(&app, feedback, SoundType::Start);
+        start_segment(&app);
+        monitor(&app, &rm);
+    });
+    Ok(())
+}
+
+/// Ends the session, the segment being recorded is still transcribed and pasted
+pub fn end(app: &AppHandle) -> Result<(), HandyError> {
+    let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
+    let session = app.state::<DictationSession>();
+    let _transition = session.transition.lock().unwrap();
+    if !session.active.swap(false, Ordering::SeqCst) {
+        return Err(HandyError::not_found(
+            "No dictation session is running".to_string(),
+        ));
+    }
+    if is_recording_segment(&rm) {
+        hand_off_segment(app, &session);
+        let stopped = Instant::now();
+        while rm.is_recording() && stopped.elapsed() < STOP_TIMEOUT {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+    ended(app);
+    Ok(())
+}
+
+/// Ends the session without a sound, the segment being recorded was cancelled
+pub fn cancel(app: &AppHandle) {
+    if app
+        .state::<DictationSession>()
+        .active
+        .swap(false, Ordering::SeqCst)
+    {
+        info!("Dictation session cancelled");
+        emit_event(app, AppEvent::DictationSessionChanged(false));
+    }
+}
+
+/// Called when a segment went through the pipeline, so the next one may be cut
+pub fn segment_done(app: &AppHandle) {
+    let session = app.state::<DictationSession>();
+    session.segment_in_flight.store(false, Ordering::SeqCst);
+    // Finishing the segment put the tray back to idle
+    if session.is_active() && is_recording_segment(&app.state::<Arc<AudioRecordingManager>>()) {
+        change_tray_icon(app, TrayIconState::Recording);
+    }
+}
+
+fn ended(app: &AppHandle) {
+    info!("Dictation session ended");
+    emit_event(app, AppEvent::DictationSessionChanged(false));
+    let app = app.clone();
+    thread::spawn(move || {
+        let feedback = get_settings(&app).feedback_mode_for(DICTATION_SESSION_BINDING);
+        play_feedback_blocking(&app, feedback, SoundType::Stop);
+    });
+}
+
+fn monitor(app: &AppHandle, rm: &AudioRecordingManager) {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let session = app.state::<DictationSession>();
+        let _transition = session.transition.lock().unwrap();
+        if !session.is_active() {
+            return;
+        }
+        if !is_recording_segment(rm) {
+            // The segment couldn't start, or was stopped some other way
+            if session.active.swap(false, Ordering::SeqCst) {
+                ended(app);
+            }
+            return;
+        }
+        let pause = Duration::from_millis(get_settings(app).dictation_session_pause_ms.into());
+        let in_flight = session.segment_in_flight.load(Ordering::SeqCst);
+        if should_cut(rm.since_speech(), pause, in_flight) {
+            debug!("Pause in the dictation session, cutting a segment");
+            session.segment_in_flight.store(true, Ordering::SeqCst);
+            session.cutting.store(true, Ordering::SeqCst);
+            hand_off_segment(app, &session);
+            session.cutting.store(false, Ordering::SeqCst);
+            start_segment(app);
+        }
+    }
+}
+
+/// A segment ends after `pause` without speech, once the previous one is done
+fn should_cut(since_speech: Option<Duration>, pause: Duration, segment_in_flight: bool) -> bool {
+    !segment_in_flight && since_speech.is_some_and(|silence| silence >= pause)
+}
+
+fn is_recording_segment(rm: &AudioRecordingManager) -> bool {
+    rm.active_binding().as_deref() == Some(DICTATION_SESSION_BINDING)
+}
+
+fn start_segment(app: &AppHandle) {
+    match ACTION_MAP.get(TRANSCRIBE_ACTION) {
+        Some(action) => action.start(app, DICTATION_SESSION_BINDING, SHORTCUT),
+        None => warn!("No transcribe action for the dictation session"),
+    }
+}
+
+/// Hands the segment to the pipeline, splitting it off the recording while cutting
+fn hand_off_segment(app: &AppHandle, session: &DictationSession) {
+    let segment = session.segments.fetch_add(1, Ordering::SeqCst) + 1;
+    let operation_id = app
+        .state::<ActiveOperations>()
+        .id(DICTATION_SESSION_BINDING);
+    if let Some(action) = ACTION_MAP.get(TRANSCRIBE_ACTION) {
+        action.stop(app, DICTATION_SESSION_BINDING, SHORTCUT);
+    }
+    emit_event(
+        app,
+        AppEvent::DictationSegment(DictationSegment {
+            segment,
+            operation_id,
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_are_cut_after_a_pause_once_the_previous_is_done() {
+        let pause = Duration::from_millis(1_200);
+        assert!(should_cut(Some(Duration::from_millis(1_500)), pause, false));
+        assert!(!should_cut(Some(Duration::from_millis(500)), pause, false));
+        // Nothing said yet
+        assert!(!should_cut(None, pause, false));
+        assert!(!should_cut(Some(Duration::from_millis(1_500)), pause, true));
+    }
+}
@@ -0,0 +1,152 @@
+//! Logging before the log plugin is set up.
+//!
+//! The plugin needs the settings for its rotation, so it is registered during setup,
+//! after paths and settings were already read (and possibly migrated). This logger
+//! is installed first; it buffers what is logged until then and hands it to the
+//! plugin's logger once that exists.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use once_cell::sync::{Lazy, OnceCell};
+use std::sync::Mutex;
+
+/// Most messages kept before the plugin is ready, older ones are dropped
+const MAX_BUFFERED: usize = 1000;
+
+struct BufferedRecord {
+    level: log::Level,
+    target: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    message: String,
+}
+
+#[derive(Default)]
+struct EarlyLogger {
+    plugin: OnceCell<Box<dyn Log>>,
+    buffered: Mutex<Vec<BufferedRecord>>,
+}
+
+static LOGGER: Lazy<EarlyLogger> = Lazy::new(EarlyLogger::default);
+
+impl Log for EarlyLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.plugin
+            .get()
+            .is_none_or(|plugin| plugin.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(plugin) = self.plugin.get() {
+            plugin.log(record);
+            return;
+        }
+        let mut buffered = self.buffered.lock().unwrap();
+        // The plugin may have been attached while waiting for the lock
+        if let Some(plugin) = self.plugin.get() {
+            plugin.log(record);
+            return;
+        }
+        if buffered.len() >= MAX_BUFFERED {
+            buffered.remove(0);
+        }
+        buffered.push(BufferedRecord {
+            level: record.level(),
+            target: record.target().to_string(),
+            module_path: record.module_path().map(str::to_string),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            message: record.args().to_string(),
+        });
+    }
+
+    fn flush(&self) {
+        if let Some(plugin) = self.plugin.get() {
+            plugin.flush();
+        }
+    }
+}
+
+/// Installs the buffering logger, before anything is logged
+pub fn install() {
+    if log::set_logger(&*LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Trace);
+    }
+}
+
+/// Hands logging over to the plugin's logger, replaying what was buffered
+pub fn attach(max_level: LevelFilter, plugin: Box<dyn Log>) {
+    LOGGER.attach(plugin);
+    log::set_max_level(max_level);
+}
+
+impl EarlyLogger {
+    fn attach(&self, plugin: Box<dyn Log>) {
+        // Held until the plugin is set so nothing is logged out of order
+        let mut buffered = self.buffered.lock().unwrap();
+        for record in buffered.drain(..) {
+            plugin.log(
+                &Record::builder()
+                    .level(record.level)
+                    .target(&record.target)
+                    .module_path(record.module_path.as_deref())
+                    .file(record.file.as_deref())
+                    .line(record.line)
+                    .args(format_args!("{}", record.message))
+                    .build(),
+            );
+        }
+        let _ = self.plugin.set(plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", record.level(), record.args()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn log(logger: &EarlyLogger, level: log::Level, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .target("handy")
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn buffered_messages_are_replayed_in_order_then_forwarded() {
+        let logger = EarlyLogger::default();
+        log(&logger, log::Level::Info, "first");
+        log(&logger, log::Level::Warn, "second");
+
+        let capture = Capture::default();
+        logger.attach(Box::new(capture.clone()));
+        log(&logger, log::Level::Debug, "third");
+
+        assert_eq!(
+            *capture.0.lock().unwrap(),
+            vec!["INFO first", "WARN second", "DEBUG third"]
+        );
+        assert!(logger.buffered.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,277 @@
+//! Error type returned by commands.
+//!
+//! Each error has a category the frontend can branch on (e.g. prompt for the
+//! accessibility permission vs. point at the API key field). It serializes as
+//! `{ "code": "<category>", "message": "<details>", "recoverable": bool,
+//! "action_hint": "<action>" | null }`; the message is for logs and as a fallback,
+//! the code is what the UI localizes and the hint picks the button it offers.
+
+use serde::Serialize;
+use specta::Type;
+use std::fmt;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+#[serde(tag = "code", content = "message", rename_all = "snake_case")]
+pub enum HandyErrorKind {
+    /// An argument or setting value was rejected
+    InvalidInput(String),
+    /// A model, binding, prompt, history entry or similar doesn't exist
+    NotFound(String),
+    /// Missing or inconsistent configuration, such as no provider or model selected
+    Config(String),
+    /// A remote service rejected the credentials
+    Auth(String),
+    /// A request to a remote service failed
+    Network(String),
+    /// An OS permission such as accessibility or microphone access is missing
+    Permission(String),
+    /// Reading or writing files, the database or audio devices failed
+    Io(String),
+    /// Anything without a more specific category
+    Internal(String),
+}
+
+/// What the UI can offer the user to resolve an error
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorActionHint {
+    OpenSettings,
+    GrantPermission,
+    CheckNetwork,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct HandyError {
+    #[serde(flatten)]
+    pub kind: HandyErrorKind,
+    /// Whether retrying can succeed without the user changing anything
+    pub recoverable: bool,
+    pub action_hint: Option<ErrorActionHint>,
+}
+
+impl HandyError {
+    /// Builds an error with the usual recoverability and hint of its category
+    pub fn new(kind: HandyErrorKind) -> Self {
+        let (recoverable, action_hint) = match kind {
+            HandyErrorKind::Config(_) | HandyErrorKind::Auth(_) => {
+                (false, Some(ErrorActionHint::OpenSettings))
+            }
+            HandyErrorKind::Network(_) => (true, Some(ErrorActionHint::CheckNetwork)),
+            HandyErrorKind::Permission(_) => (false, Some(ErrorActionHint::GrantPermission)),
+            HandyErrorKind::Io(_) => (true, None),
+            HandyErrorKind::InvalidInput(_)
+            | HandyErrorKind::NotFound(_)
+            | HandyErrorKind::Internal(_) => (false, None),
+        };
+        Self {
+            kind,
+            recoverable,
+            action_hint,
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(HandyErrorKind::InvalidInput(message.into()))
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(HandyErrorKind::NotFound(message.into()))
+    }
+
+    pub fn config(message: impl Into<String>) -> Self {
+        Self::new(HandyErrorKind::Config(message.into()))
+    }
+
+    pub fn auth(message: impl Into<String>) -> Self {
+        Self::new(HandyErrorKind::Auth(message.into()))
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::new(HandyErrorKind::Network(message.into()))
+    }
+
+    pub fn permission(message: impl Into<String>) -> Self {
+        Self::new(HandyErrorKind::Permission(message.into()))
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::new(HandyErrorKind::Io(message.into()))
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(HandyErrorKind::Internal(message.into()))
+    }
+
+    /// Overrides the category's default hint
+    pub fn with_action_hint(mut self, action_hint: Option<ErrorActionHint>) -> Self {
+        self.action_hint = action_hint;
+        self
+    }
+
+    /// Stable identifier of the category, matching the serialized `code`
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            HandyErrorKind::InvalidInput(_) => "invalid_input",
+            HandyErrorKind::NotFound(_) => "not_found",
+            HandyErrorKind::Config(_) => "config",
+            HandyErrorKind::Auth(_) => "auth",
+            HandyErrorKind::Network(_) => "network",
+            HandyErrorKind::Permission(_) => "permission",
+            HandyErrorKind::Io(_) => "io",
+            HandyErrorKind::Internal(_) => "internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match &self.kind {
+            HandyErrorKind::InvalidInput(message)
+            | HandyErrorKind::NotFound(message)
+            | HandyErrorKind::Config(message)
+            | HandyErrorKind::Auth(message)
+            | HandyErrorKind::Network(message)
+            | HandyErrorKind::Permission(message)
+            | HandyErrorKind::Io(message)
+            | HandyErrorKind::Internal(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for HandyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message())
+    }
+}
+
+impl std::error::Error for HandyError {}
+
+/// Plain string errors from helpers that don't categorize their failures
+impl From<String> for HandyError {
+    fn from(message: String) -> Self {
+        HandyError::internal(message)
+    }
+}
+
+impl From<&str> for HandyError {
+    fn from(message: &str) -> Self {
+        HandyError::internal(message)
+    }
+}
+
+/// Keeps the category of a request or file error wrapped in an `anyhow` chain
+impl From<anyhow::Error> for HandyError {
+    fn from(error: anyhow::Error) -> Self {
+        if let Some(request_error) = error.downcast_ref::<reqwest::Error>() {
+            return request_error_kind(request_error, error.to_string());
+        }
+        if error.downcast_ref::<std::io::Error>().is_some() {
+            return HandyError::io(error.to_string());
+        }
+        HandyError::internal(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for HandyError {
+    fn from(error: std::io::Error) -> Self {
+        HandyError::io(error.to_string())
+    }
+}
+
+impl From<reqwest::Error> for HandyError {
+    fn from(error: reqwest::Error) -> Self {
+        let message = error.without_url().to_string();
+        request_error_kind(&error, message)
+    }
+}
+
+impl From<tauri_plugin_store::Error> for HandyError {
+    fn from(error: tauri_plugin_store::Error) -> Self {
+        HandyError::io(format!("Settings store error: {}", error))
+    }
+}
+
+/// Rejected credentials are an auth problem, anything else about a request is network
+fn request_error_kind(error: &reqwest::Error, message: String) -> HandyError {
+    match error.status() {
+        Some(status)
+            if status == reqwest::StatusCode::UNAUTHORIZED
+                || status == reqwest::StatusCode::FORBIDDEN =>
+        {
+            HandyError::auth(message)
+        }
+        _ => HandyError::network(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_message_and_hints() {
+        let error = HandyError::auth("invalid API key");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "auth",
+                "message": "invalid API key",
+                "recoverable": false,
+                "action_hint": "open_settings"
+            })
+        );
+        assert_eq!(error.to_string(), "auth: invalid API key");
+
+        let error = HandyError::internal("boom");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "internal",
+                "message": "boom",
+                "recoverable": false,
+                "action_hint": null
+            })
+        );
+    }
+
+    #[test]
+    fn code_matches_serialized_tag() {
+        let errors = [
+            HandyError::invalid_input(""),
+            HandyError::not_found(""),
+            HandyError::config(""),
+            HandyError::auth(""),
+            HandyError::network(""),
+            HandyError::permission(""),
+            HandyError::io(""),
+            HandyError::internal(""),
+        ];
+        for error in errors {
+            let value = serde_json::to_value(&error).unwrap();
+            assert_eq!(value["code"], error.code());
+        }
+    }
+
+    #[test]
+    fn categories_carry_default_hints() {
+        let error = HandyError::network("timed out");
+        assert!(error.recoverable);
+        assert_eq!(error.action_hint, Some(ErrorActionHint::CheckNetwork));
+        assert_eq!(
+            HandyError::permission("").action_hint,
+            Some(ErrorActionHint::GrantPermission)
+        );
+        assert_eq!(
+            HandyError::config("").with_action_hint(None).action_hint,
+            None
+        );
+    }
+
+    #[test]
+    fn uncategorized_errors_are_internal() {
+        let error: HandyError = "boom".into();
+        assert_eq!(error, HandyError::internal("boom"));
+        let error: HandyError = std::io::Error::other("disk full").into();
+        assert_eq!(error.code(), "io");
+        let error: HandyError = anyhow::Error::from(std::io::Error::other("disk full")).into();
+        assert_eq!(error.code(), "io");
+    }
+}
@@ -0,0 +1,584 @@
+//! Typed catalog of every event the backend emits to the frontend.
+//!
+//! Each `AppEvent` variant serializes as `{ "type": "<event-name>", "payload": ... }`.
+//! The `type` tag doubles as the Tauri event name, so `emit_event` can never send a
+//! payload on the wrong channel. The enum is exported through specta, which gives the
+//! frontend a discriminated union it can use to type its `listen` calls.
+
+use log::error;
+use serde::Serialize;
+use specta::Type;
+use tauri::{Emitter, Runtime};
+
+use crate::compare::{CompareProgress, CompareResult};
+use crate::dictation_session::DictationSegment;
+use crate::managers::model::DownloadProgress;
+use crate::managers::transcription::ModelStateEvent;
+use crate::pipeline::{DictationEnded, DictationsInFlight, PipelineProgress};
+use crate::power_profile::ActivePowerProfile;
+use crate::review::{ReviewOutcome, TranscriptReview, TranscriptReviewEnded};
+use crate::settings::PasteMethod;
+use crate::settings::PostProcessMisconfiguration;
+use crate::shortcut::handy_keys::FrontendKeyEvent;
+use crate::translation_guard::TranslationCheck;
+
+/// Payload for the generic `settings-changed` event
+#[derive(Serialize, Debug, Clone, Type)]
+pub struct SettingChanged {
+    pub setting: String,
+    pub value: bool,
+}
+
+/// Which state the recording overlay should display
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayState {
+    Recording,
+    Transcribing,
+    /// A deferred transcript is waiting to be pasted
+    Deferred,
+}
+
+#[derive(Serialize, Debug, Clone, Type)]
+pub struct ModelExtractionFailed {
+    pub model_id: String,
+    pub error: String,
+}
+
+/// Sent when a transcript was shortened before pasting; history keeps the full text
+#[derive(Serialize, Debug, Clone, Type)]
+pub struct TranscriptTruncated {
+    pub original_chars: usize,
+    pub max_chars: usize,
+}
+
+/// Sent when a transcription couldn't be written in file dictation mode.
+/// The text is copied to the clipboard instead.
+#[derive(Serialize, Debug, Clone, Type)]
+pub struct FileDictationWriteFailed {
+    pub path: String,
+    pub error: String,
+}
+
+/// Output device that feedback sounds now play on
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct OutputDeviceChanged {
+    pub device: String,
+    /// True when the selected device is unavailable and the default is used
+    pub fallback: bool,
+}
+
+/// The input stream failed during a recording and was reopened
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct RecordingDeviceRecovered {
+    /// True when the chosen microphone didn't come back and the default is used
+    pub fallback: bool,
+}
+
+/// The OS muted the microphone, so a recording was stopped as it started or cut short
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct MicrophoneMuted {
+    /// The muted input, None for the system default
+    pub device: Option<String>,
+    /// True when the mute came mid-recording and the recording was stopped there
+    pub during_recording: bool,
+}
+
+/// A failed inference is being retried
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct TranscriptionRetrying {
+    /// Starts at 1 for the first retry
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub error: String,
+}
+
+/// Sent instead of pasting when a transcript was blank or a known hallucination
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct TranscriptionDiscarded {
+    pub text: String,
+    /// Whether the recording was saved to history anyway
+    pub kept_in_history: bool,
+}
+
+/// How a transcript was delivered to the focused application
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct PasteReport {
+    pub requested: PasteMethod,
+    pub used: PasteMethod,
+    /// Why the requested method wasn't used, when it fell back
+    pub fallback_reason: Option<String>,
+    pub elapsed_ms: u32,
+}
+
+/// A watch folder couldn't be watched or one of its files failed to transcribe
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct WatchFolderError {
+    pub folder_id: String,
+    /// File that failed, None when watching the folder itself failed
+    pub path: Option<String>,
+    pub error: String,
+}
+
+/// The wake phrase was heard, see `wake_word`
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct WakeWordDetection {
+    pub model: String,
+    pub score: f32,
+    /// Heard during `test_wake_word`, so no recording was started
+    pub test: bool,
+}
+
+/// Progress of a history search index rebuild
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+pub struct HistoryIndexProgress {
+    pub indexed: usize,
+    pub total: usize,
+}
+
+/// Pace of the dictation being recorded, sent once a second. Word count and rate
+/// stay empty until interim transcripts are available.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Type)]
+pub struct DictationMetrics {
+    pub elapsed_ms: u32,
+    pub word_count: Option<u32>,
+    pub words_per_minute: Option<f64>,
+}
+
+/// A binding pressed or released while shortcut test mode is on
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct ShortcutTestFired {
+    pub binding_id: String,
+    pub shortcut: String,
+    pub pressed: bool,
+}
+
+/// Progress of re-encoding stored recordings, see `convert_existing_recordings`
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+pub struct RecordingConversionProgress {
+    pub converted: usize,
+    /// Recordings left as they were because they couldn't be read or re-encoded
+    pub failed: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Type)]
+#[serde(tag = "type", content = "payload", rename_all = "kebab-case")]
+pub enum AppEvent {
+    SettingsChanged(SettingChanged),
+    CheckForUpdates,
+    MicLevel(Vec<f32>),
+    ShowOverlay(OverlayState),
+    HideOverlay,
+    HistoryUpdated,
+    HistoryIndexProgress(HistoryIndexProgress),
+    RecordingConversionProgress(RecordingConversionProgress),
+    DictationMetrics(DictationMetrics),
+    ShortcutTestFired(ShortcutTestFired),
+    ShortcutTestModeChanged(bool),
+    /// Identifier of the keyboard layout now in use
+    KeyboardLayoutChanged(String),
+    ModelStateChanged(ModelStateEvent),
+    ModelDownloadProgress(DownloadProgress),
+    ModelDownloadComplete(String),
+    ModelExtractionStarted(String),
+    ModelExtractionCompleted(String),
+    ModelExtractionFailed(ModelExtractionFailed),
+    HandyKeysEvent(FrontendKeyEvent),
+    TranscriptTruncated(TranscriptTruncated),
+    TranslationSuspect(TranslationCheck),
+    PostProcessMisconfigured(PostProcessMisconfiguration),
+    /// Target file of file dictation, None when the session ended
+    FileDictationChanged(Option<String>),
+    FileDictationWriteFailed(FileDictationWriteFailed),
+    /// A transcript is waiting in the review window
+    TranscriptReview(TranscriptReview),
+    TranscriptReviewEnded(TranscriptReviewEnded),
+    OutputDeviceChanged(OutputDeviceChanged),
+    RecordingDeviceRecovered(RecordingDeviceRecovered),
+    /// No input could be reopened; the recording is stopped with what was captured
+    RecordingDeviceLost,
+    MicrophoneMuted(MicrophoneMuted),
+    TranscriptionDiscarded(TranscriptionDiscarded),
+    TranscriptionRetrying(TranscriptionRetrying),
+    WatchFolderError(WatchFolderError),
+    TranscriptPasted(PasteReport),
+    // Dictation lifecycle, see `pipeline`
+    RecordingStarted(PipelineProgress),
+    RecordingStopped(PipelineProgress),
+    InferenceStarted(PipelineProgress),
+    InferenceDone(PipelineProgress),
+    PostProcessStarted(PipelineProgress),
+    PostProcessDone(PipelineProgress),
+    PasteDone(PipelineProgress),
+    DictationEnded(DictationEnded),
+    DictationsInFlight(DictationsInFlight),
+    CompareProgress(CompareProgress),
+    /// The deferred transcript, None once it was pasted
+    DeferredPasteChanged(Option<String>),
+    /// A deferred transcript was dropped for a newer one
+    DeferredPasteReplaced(String),
+    /// Whether a copy-only session is on, see `copy_only_session`
+    CopyOnlySessionChanged(bool),
+    WakeWordDetected(WakeWordDetection),
+    PowerProfileChanged(ActivePowerProfile),
+    /// Whether a dictation session is running, see `dictation_session`
+    DictationSessionChanged(bool),
+    DictationSegment(DictationSegment),
+    /// Top-level settings keys that changed when the store file was edited outside
+    /// the app, see `settings_watcher`
+    SettingsReloaded(Vec<String>),
+}
+
+impl AppEvent {
+    /// Convenience constructor for the common boolean `settings-changed` event
+    pub fn setting_changed(setting: &str, value: bool) -> Self {
+        AppEvent::SettingsChanged(SettingChanged {
+            setting: setting.to_string(),
+            value,
+        })
+    }
+
+    /// Split the event into its Tauri event name and payload
+    fn into_parts(self) -> Result<(String, serde_json::Value), String> {
+        let value = serde_json::to_value(&self).map_err(|e| e.to_string())?;
+        let name = value
+            .get("type")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| "AppEvent serialized without a type tag".to_string())?
+            .to_string();
+        let payload = value
+            .get("payload")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        Ok((name, payload))
+    }
+}
+
+/// Emit an event on the channel named by its `type` tag.
+/// Works with anything that can emit: the app handle or a specific window.
+pub fn emit_event<R: Runtime, E: Emitter<R>>(emitter: &E, event: AppEvent) {
+    match event.into_parts() {
+        Ok((name, payload)) => {
+            if let Err(e) = emitter.emit(&name, payload) {
+                error!("Failed to emit {} event: {}", name, e);
+            }
+        }
+        Err(e) => error!("Failed to serialize event: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::DictationOutcome;
+
+    fn all_variants() -> Vec<(AppEvent, &'static str)> {
+        vec![
+            (
+                AppEvent::setting_changed("debug_mode", true),
+                "settings-changed",
+            ),
+            (AppEvent::CheckForUpdates, "check-for-updates"),
+            (AppEvent::MicLevel(vec![0.1, 0.2]), "mic-level"),
+            (
+                AppEvent::ShowOverlay(OverlayState::Recording),
+                "show-overlay",
+            ),
+            (AppEvent::HideOverlay, "hide-overlay"),
+            (AppEvent::HistoryUpdated, "history-updated"),
+            (
+                AppEvent::HistoryIndexProgress(HistoryIndexProgress {
+                    indexed: 10,
+                    total: 20,
+                }),
+                "history-index-progress",
+            ),
+            (
+                AppEvent::RecordingConversionProgress(RecordingConversionProgress {
+                    converted: 3,
+                    failed: 1,
+                    total: 10,
+                }),
+                "recording-conversion-progress",
+            ),
+            (
+                AppEvent::DictationMetrics(DictationMetrics {
+                    elapsed_ms: 3000,
+                    word_count: None,
+                    words_per_minute: None,
+                }),
+                "dictation-metrics",
+            ),
+            (
+                AppEvent::ShortcutTestFired(ShortcutTestFired {
+                    binding_id: "transcribe".to_string(),
+                    shortcut: "ctrl+space".to_string(),
+                    pressed: true,
+                }),
+                "shortcut-test-fired",
+            ),
+            (
+                AppEvent::ShortcutTestModeChanged(false),
+                "shortcut-test-mode-changed",
+            ),
+            (
+                AppEvent::KeyboardLayoutChanged("com.apple.keylayout.German".to_string()),
+                "keyboard-layout-changed",
+            ),
+            (
+                AppEvent::ModelStateChanged(ModelStateEvent {
+                    event_type: "unloaded".to_string(),
+                    model_id: None,
+                    model_name: None,
+                    error: None,
+                }),
+                "model-state-changed",
+            ),
+            (
+                AppEvent::ModelDownloadProgress(DownloadProgress {
+                    model_id: "small".to_string(),
+                    downloaded: 1,
+                    total: 2,
+                    percentage: 50.0,
+                }),
+                "model-download-progress",
+            ),
+            (
+                AppEvent::ModelDownloadComplete("small".to_string()),
+                "model-download-complete",
+            ),
+            (
+                AppEvent::ModelExtractionStarted("small".to_string()),
+                "model-extraction-started",
+            ),
+            (
+                AppEvent::ModelExtractionCompleted("small".to_string()),
+                "model-extraction-completed",
+            ),
+            (
+                AppEvent::ModelExtractionFailed(ModelExtractionFailed {
+                    model_id: "small".to_string(),
+                    error: "boom".to_string(),
+                }),
+                "model-extraction-failed",
+            ),
+            (
+                AppEvent::HandyKeysEvent(FrontendKeyEvent {
+                    modifiers: vec!["ctrl".to_string()],
+                    key: Some("space".to_string()),
+                    is_key_down: true,
+                    hotkey_string: "ctrl+space".to_string(),
+                }),
+                "handy-keys-event",
+            ),
+            (
+                AppEvent::TranscriptTruncated(TranscriptTruncated {
+                    original_chars: 500,
+                    max_chars: 100,
+                }),
+                "transcript-truncated",
+            ),
+            (
+                AppEvent::TranslationSuspect(TranslationCheck {
+                    detected_language: Some("deu".to_string()),
+                    confidence: 0.9,
+                    suspect: true,
+                    retried: false,
+                }),
+                "translation-suspect",
+            ),
+            (
+                AppEvent::PostProcessMisconfigured(PostProcessMisconfiguration::NoPromptSelected),
+                "post-process-misconfigured",
+            ),
+            (
+                AppEvent::FileDictationChanged(Some("/tmp/notes.md".to_string())),
+                "file-dictation-changed",
+            ),
+            (
+                AppEvent::FileDictationWriteFailed(FileDictationWriteFailed {
+                    path: "/tmp/notes.md".to_string(),
+                    error: "disk full".to_string(),
+                }),
+                "file-dictation-write-failed",
+            ),
+            (
+                AppEvent::TranscriptReview(TranscriptReview {
+                    id: 1,
+                    text: "hello".to_string(),
+                    timeout_secs: 60,
+                }),
+                "transcript-review",
+            ),
+            (
+                AppEvent::TranscriptReviewEnded(TranscriptReviewEnded {
+                    id: 1,
+                    outcome: ReviewOutcome::TimedOut,
+                }),
+                "transcript-review-ended",
+            ),
+            (
+                AppEvent::OutputDeviceChanged(OutputDeviceChanged {
+                    device: "Headphones".to_string(),
+                    fallback: false,
+                }),
+                "output-device-changed",
+            ),
+            (
+                AppEvent::RecordingDeviceRecovered(RecordingDeviceRecovered { fallback: true }),
+                "recording-device-recovered",
+            ),
+            (AppEvent::RecordingDeviceLost, "recording-device-lost"),
+            (
+                AppEvent::MicrophoneMuted(MicrophoneMuted {
+                    device: None,
+                    during_recording: false,
+                }),
+                "microphone-muted",
+            ),
+            (
+                AppEvent::TranscriptionDiscarded(TranscriptionDiscarded {
+                    text: "thank you".to_string(),
+                    kept_in_history: false,
+                }),
+                "transcription-discarded",
+            ),
+            (
+                AppEvent::TranscriptionRetrying(TranscriptionRetrying {
+                    attempt: 1,
+                    max_retries: 2,
+                    error: "decoder error".to_string(),
+                }),
+                "transcription-retrying",
+            ),
+            (
+                AppEvent::WatchFolderError(WatchFolderError {
+                    folder_id: "folder_1".to_string(),
+                    path: None,
+                    error: "No such directory".to_string(),
+                }),
+                "watch-folder-error",
+            ),
+            (
+                AppEvent::TranscriptPasted(PasteReport {
+                    requested: PasteMethod::Accessibility,
+                    used: PasteMethod::CtrlV,
+                    fallback_reason: Some("no focused element".to_string()),
+                    elapsed_ms: 120,
+                }),
+                "transcript-pasted",
+            ),
+            (AppEvent::RecordingStarted(progress()), "recording-started"),
+            (AppEvent::RecordingStopped(progress()), "recording-stopped"),
+            (AppEvent::InferenceStarted(progress()), "inference-started"),
+            (AppEvent::InferenceDone(progress()), "inference-done"),
+            (
+                AppEvent::PostProcessStarted(progress()),
+                "post-process-started",
+            ),
+            (AppEvent::PostProcessDone(progress()), "post-process-done"),
+            (AppEvent::PasteDone(progress()), "paste-done"),
+            (
+                AppEvent::DictationEnded(DictationEnded {
+                    progress: progress(),
+                    outcome: DictationOutcome::Discarded,
+                }),
+                "dictation-ended",
+            ),
+            (
+                AppEvent::CompareProgress(CompareProgress {
+                    completed: 1,
+                    total: 2,
+                    result: CompareResult {
+                        model_id: "small".to_string(),
+                        text: "hello".to_string(),
+                        error: None,
+                        load_ms: 800,
+                        inference_ms: 400,
+                    },
+                }),
+                "compare-progress",
+            ),
+            (
+                AppEvent::DeferredPasteChanged(Some("hello".to_string())),
+                "deferred-paste-changed",
+            ),
+            (
+                AppEvent::DeferredPasteReplaced("hello".to_string()),
+                "deferred-paste-replaced",
+            ),
+            (
+                AppEvent::CopyOnlySessionChanged(true),
+                "copy-only-session-changed",
+            ),
+            (
+                AppEvent::WakeWordDetected(WakeWordDetection {
+                    model: "hey_jarvis".to_string(),
+                    score: 0.9,
+                    test: true,
+                }),
+                "wake-word-detected",
+            ),
+            (
+                AppEvent::PowerProfileChanged(ActivePowerProfile {
+                    source: crate::power_profile::PowerSource::Battery,
+                    manual: false,
+                }),
+                "power-profile-changed",
+            ),
+            (
+                AppEvent::DictationSessionChanged(true),
+                "dictation-session-changed",
+            ),
+            (
+                AppEvent::DictationSegment(DictationSegment {
+                    segment: 2,
+                    operation_id: Some(5),
+                }),
+                "dictation-segment",
+            ),
+            (
+                AppEvent::DictationsInFlight(DictationsInFlight {
+                    recording: 1,
+                    processing: 1,
+                }),
+                "dictations-in-flight",
+            ),
+            (
+                AppEvent::SettingsReloaded(vec!["overlay_position".to_string()]),
+                "settings-reloaded",
+            ),
+        ]
+    }
+
+    fn progress() -> PipelineProgress {
+        PipelineProgress {
+            operation_id: 1,
+            binding_id: "transcribe".to_string(),
+            elapsed_ms: 1500,
+            stage_ms: 300,
+        }
+    }
+
+    #[test]
+    fn every_variant_has_stable_type_tag() {
+        for (event, expected) in all_variants() {
+            let value = serde_json::to_value(&event).expect("serialize event");
+            assert_eq!(value["type"], expected, "unexpected tag for {:?}", event);
+        }
+    }
+
+    #[test]
+    fn into_parts_uses_tag_as_event_name() {
+        let (name, payload) = AppEvent::ShowOverlay(OverlayState::Transcribing)
+            .into_parts()
+            .expect("split event");
+        assert_eq!(name, "show-overlay");
+        assert_eq!(payload, serde_json::json!("transcribing"));
+
+        let (name, payload) = AppEvent::HistoryUpdated.into_parts().expect("split event");
+        assert_eq!(name, "history-updated");
+        assert!(payload.is_null());
+    }
+}
@@ -0,0 +1,136 @@
+//! Transcribe-to-file mode: while a target file is set, transcriptions are
+//! appended to it instead of being pasted.
+
+use chrono::Local;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const TIMESTAMP_TOKEN: &str = "${timestamp}";
+
+/// Target file of the current file dictation session, if any.
+/// The lock is held while writing so overlapping dictations append in order.
+#[derive(Default)]
+pub struct FileDictation {
+    target: Mutex<Option<PathBuf>>,
+}
+
+impl FileDictation {
+    /// Starts a session, creating the file if needed. Without `append` the file is emptied.
+    pub fn start(&self, path: PathBuf, append: bool) -> Result<(), String> {
+        let mut target = self.target.lock().unwrap();
+        OpenOptions::new()
+            .create(true)
+            .append(append)
+            .write(true)
+            .truncate(!append)
+            .open(&path)
+            .map_err(|e| format!("Failed to open '{}': {}", path.display(), e))?;
+        *target = Some(path);
+        Ok(())
+    }
+
+    /// Ends the session and returns the file that was being written
+    pub fn stop(&self) -> Option<PathBuf> {
+        self.target.lock().unwrap().take()
+    }
+
+    pub fn active_path(&self) -> Option<PathBuf> {
+        self.target.lock().unwrap().clone()
+    }
+
+    /// Appends a transcription to the target file. Returns Ok(None) when no session is active.
+    pub fn write(
+        &self,
+        text: &str,
+        separator_template: &str,
+    ) -> Result<Option<PathBuf>, (PathBuf, String)> {
+        let target = self.target.lock().unwrap();
+        let Some(path) = target.as_ref() else {
+            return Ok(None);
+        };
+        append_entry(path, text, &render_separator(separator_template))
+            .map(|_| Some(path.clone()))
+            .map_err(|e| (path.clone(), e.to_string()))
+    }
+}
+
+/// Appends a transcription to a file outside of a session, with the same separator
+pub fn append_to(path: &Path, text: &str, separator_template: &str) -> std::io::Result<()> {
+    append_entry(path, text, &render_separator(separator_template))
+}
+
+/// Expands `${timestamp}` in the separator with the local time
+fn render_separator(template: &str) -> String {
+    if !template.contains(TIMESTAMP_TOKEN) {
+        return template.to_string();
+    }
+    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    template.replace(TIMESTAMP_TOKEN, &now)
+}
+
+/// Appends `text` to the file, preceded by the separator unless the file is empty
+fn append_entry(path: &Path, text: &str, separator: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() > 0 {
+        file.write_all(separator.as_bytes())?;
+    }
+    file.write_all(text.as_bytes())?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "handy-file-dictation-{}-{}.txt",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn writes_separator_only_between_entries() {
+        let path = temp_path("separator");
+        let dictation = FileDictation::default();
+        dictation.start(path.clone(), false).unwrap();
+
+        dictation.write("first", "\n---\n").unwrap();
+        dictation.write("second", "\n---\n").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "first\n---\nsecond"
+        );
+
+        assert_eq!(dictation.stop(), Some(path.clone()));
+        assert_eq!(dictation.write("ignored", "\n").unwrap(), None);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn start_without_append_truncates() {
+        let path = temp_path("truncate");
+        std::fs::write(&path, "old notes").unwrap();
+        let dictation = FileDictation::default();
+
+        dictation.start(path.clone(), true).unwrap();
+        dictation.write("more", " ").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old notes more");
+
+        dictation.start(path.clone(), false).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn separator_expands_timestamp() {
+        let rendered = render_separator("\n[${timestamp}]\n");
+        assert!(!rendered.contains(TIMESTAMP_TOKEN));
+        assert!(rendered.starts_with("\n[") && rendered.ends_with("]\n"));
+    }
+}
@@ -0,0 +1,508 @@
+use crate::language::TranscriptLanguage;
+use crate::settings::{AppSettings, JsonOutput, OutputTransform};
+use crate::utils::{frontmost_application, FrontmostApplication};
+use log::{debug, warn};
+use unicode_segmentation::UnicodeSegmentation;
+
+const OUTPUT_TOKEN_PREFIX: &str = "${output";
+const LANGUAGE_TOKEN: &str = "${language}";
+const LANGUAGE_NAME_TOKEN: &str = "${language_name}";
+/// Transforms understood in `${output:<transform>}` tokens
+pub const OUTPUT_TRANSFORMS: &[&str] = &["lower", "upper", "trim"];
+
+/// Left-to-right and right-to-left isolates, see Unicode's bidi algorithm (UAX #9)
+const LRI: char = '\u{2066}';
+const PDI: char = '\u{2069}';
+
+/// Splits text into lowercase alphanumeric words, dropping punctuation. Works on
+/// graphemes, so a letter keeps its combining marks.
+fn words(text: &str) -> Vec<String> {
+    let in_word = |grapheme: &&str| {
+        grapheme
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || c == '\'')
+    };
+    text.graphemes(true)
+        .collect::<Vec<_>>()
+        .split(|grapheme| !in_word(grapheme))
+        .map(|word| word.concat().replace('\'', "").to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Applies an output transform to transcribed text
+pub fn apply_transform(text: &str, transform: OutputTransform) -> String {
+    match transform {
+        OutputTransform::AsIs => text.to_string(),
+        OutputTransform::Lowercase => text.to_lowercase(),
+        OutputTransform::SnakeCase => words(text).join("_"),
+        OutputTransform::KebabCase => words(text).join("-"),
+    }
+}
+
+/// Joins the lines of `text` with single spaces, for targets like REPLs and chat
+/// boxes where a line break sends the text. Blank lines and trailing newlines go.
+pub fn collapse_to_single_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Scripts written right to left: Hebrew, Arabic, Syriac, Thaana, N'Ko and others,
+/// with their presentation forms
+fn is_rtl(c: char) -> bool {
+    matches!(c, '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}')
+        || matches!(c, '\u{10800}'..='\u{10FFF}' | '\u{1E800}'..='\u{1EFFF}')
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Ltr,
+    Rtl,
+    /// Digits, spaces, punctuation and marks, which take the direction around them
+    Neutral,
+}
+
+/// Direction of a grapheme, going by its first letter
+fn direction(grapheme: &str) -> Direction {
+    match grapheme.chars().find(|c| c.is_alphabetic()) {
+        Some(c) if is_rtl(c) => Direction::Rtl,
+        Some(_) => Direction::Ltr,
+        None => Direction::Neutral,
+    }
+}
+
+/// Wraps every left-to-right run of a right-to-left text in directional isolates,
+/// so apps lay out e.g. "iOS 17" inside Hebrew as one unit instead of moving its
+/// parts around. A run starts at a left-to-right letter and ends at the last letter
+/// or digit before the next right-to-left letter; the spaces and punctuation
+/// around it stay right-to-left. Each line is judged by its first letter, lines
+/// starting left to right are left alone. The text stays in logical order and is
+/// only cut between graphemes.
+pub fn isolate_ltr_runs(text: &str) -> String {
+    text.split('\n')
+        .map(isolate_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn isolate_line(line: &str) -> String {
+    let graphemes: Vec<&str> = line.graphemes(true).collect();
+    let first_strong = graphemes
+        .iter()
+        .map(|g| direction(g))
+        .find(|d| *d != Direction::Neutral);
+    if first_strong != Some(Direction::Rtl) {
+        return line.to_string();
+    }
+
+    let mut result = String::with_capacity(line.len() + 16);
+    let mut i = 0;
+    while i < graphemes.len() {
+        if direction(graphemes[i]) != Direction::Ltr {
+            result.push_str(graphemes[i]);
+            i += 1;
+            continue;
+        }
+        let end = graphemes[i..]
+            .iter()
+            .position(|g| direction(g) == Direction::Rtl)
+            .map_or(graphemes.len(), |offset| i + offset);
+        // Trailing spaces and punctuation belong to the surrounding text
+        let run_end = (i..end)
+            .rev()
+            .find(|&j| {
+                direction(graphemes[j]) == Direction::Ltr
+                    || graphemes[j].chars().any(|c| c.is_numeric())
+            })
+            .map_or(end, |j| j + 1);
+        result.push(LRI);
+        result.extend(&graphemes[i..run_end]);
+        result.push(PDI);
+        result.extend(&graphemes[run_end..end]);
+        i = end;
+    }
+    result
+}
+
+/// Looks up the transform configured for the given application.
+/// Entries match the app's identifier or its name, case-insensitively.
+pub fn transform_for_app(
+    settings: &AppSettings,
+    app: Option<&FrontmostApplication>,
+) -> OutputTransform {
+    let Some(app) = app else {
+        return OutputTransform::AsIs;
+    };
+
+    settings
+        .app_output_transforms
+        .iter()
+        .find(|(key, _)| app.matches(key))
+        .map(|(_, transform)| *transform)
+        .unwrap_or_default()
+}
+
+/// Applies the output transform configured for the focused application. Looking
+/// the app up can spawn a process, so this must not run on the main thread.
+pub fn apply_app_transform(settings: &AppSettings, text: String) -> String {
+    // Skip the frontmost app lookup entirely when no transforms are configured
+    if settings.app_output_transforms.is_empty() {
+        return text;
+    }
+    match transform_for_app(settings, frontmost_application().as_ref()) {
+        OutputTransform::AsIs => text,
+        transform => {
+            debug!("Applying output transform {:?}", transform);
+            apply_transform(&text, transform)
+        }
+    }
+}
+
+/// Applies a named prompt transform such as `lower` to the transcript
+fn transform_output(output: &str, name: &str) -> String {
+    match name {
+        "lower" => output.to_lowercase(),
+        "upper" => output.to_uppercase(),
+        "trim" => output.trim().to_string(),
+        other => {
+            warn!(
+                "Unknown prompt transform '{}', using the plain output",
+                other
+            );
+            output.to_string()
+        }
+    }
+}
+
+/// Expands `${output}` and `${output:<transform>}` tokens in a prompt template.
+/// Supported transforms are `lower`, `upper` and `trim`.
+pub fn expand_prompt_template(template: &str, output: &str) -> String {
+    replace_output_tokens(template, |transform| match transform {
+        Some(name) => transform_output(output, name),
+        None => output.to_string(),
+    })
+}
+
+/// Expands `${language}` with the transcript's language code and `${language_name}`
+/// with its English name. An unknown language expands to `auto` and an empty name.
+/// Run before the output tokens so a transcript containing the tokens stays as is.
+pub fn expand_language_tokens(template: &str, language: Option<&TranscriptLanguage>) -> String {
+    let (code, name) = language.map_or(("auto", ""), |language| {
+        (language.code.as_str(), language.name.as_str())
+    });
+    template
+        .replace(LANGUAGE_TOKEN, code)
+        .replace(LANGUAGE_NAME_TOKEN, name)
+}
+
+/// Replaces each output token with a fixed reference and returns the transcript
+/// with the first token's transform applied, for sending it as separate content.
+pub fn split_prompt_template(template: &str, output: &str, reference: &str) -> (String, String) {
+    let mut first_transform: Option<Option<String>> = None;
+    let instructions = replace_output_tokens(template, |transform| {
+        first_transform.get_or_insert_with(|| transform.map(str::to_string));
+        reference.to_string()
+    });
+    let data = match first_transform.flatten() {
+        Some(name) => transform_output(output, &name),
+        None => output.to_string(),
+    };
+    (instructions, data)
+}
+
+/// Transform of every output token in the template, None for a plain `${output}`
+pub fn output_token_transforms(template: &str) -> Vec<Option<String>> {
+    let mut transforms = Vec::new();
+    replace_output_tokens(template, |transform| {
+        transforms.push(transform.map(str::to_string));
+        String::new()
+    });
+    transforms
+}
+
+/// Calls `replacement` with the transform name (if any) of every output token
+fn replace_output_tokens(
+    template: &str,
+    mut replacement: impl FnMut(Option<&str>) -> String,
+) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(OUTPUT_TOKEN_PREFIX) {
+        result.push_str(&rest[..start]);
+        let after_prefix = &rest[start + OUTPUT_TOKEN_PREFIX.len()..];
+
+        if let Some(after_brace) = after_prefix.strip_prefix('}') {
+            result.push_str(&replacement(None));
+            rest = after_brace;
+        } else if let Some(transform) = after_prefix
+            .strip_prefix(':')
+            .and_then(|spec| spec.split_once('}'))
+        {
+            let (name, after_token) = transform;
+            result.push_str(&replacement(Some(name.trim())));
+            rest = after_token;
+        } else {
+            // Not a token we understand, keep it literally
+            result.push_str(OUTPUT_TOKEN_PREFIX);
+            rest = after_prefix;
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Pretty-prints a JSON response or pulls out one of its top-level fields.
+/// Responses that aren't JSON, or lack the field, are returned unchanged.
+pub fn format_json_output(content: &str, mode: &JsonOutput) -> String {
+    if *mode == JsonOutput::Raw {
+        return content.to_string();
+    }
+    let value: serde_json::Value = match serde_json::from_str(content.trim()) {
+        Ok(value) => value,
+        Err(_) => {
+            warn!("Expected a JSON response but got plain text, pasting it unchanged");
+            return content.to_string();
+        }
+    };
+
+    match mode {
+        JsonOutput::Raw => content.to_string(),
+        JsonOutput::Pretty => {
+            serde_json::to_string_pretty(&value).unwrap_or_else(|_| content.to_string())
+        }
+        JsonOutput::Field(field) => match value.get(field) {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(other) => {
+                serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string())
+            }
+            None => {
+                warn!(
+                    "JSON response has no '{}' field, pasting it unchanged",
+                    field
+                );
+                content.to_string()
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_output_extracts_field_or_pretty_prints() {
+        let response = r#"{"summary":"Ship it","items":["a","b"]}"#;
+        assert_eq!(
+            format_json_output(response, &JsonOutput::Field("summary".to_string())),
+            "Ship it"
+        );
+        assert_eq!(
+            format_json_output(response, &JsonOutput::Field("items".to_string())),
+            "[\n  \"a\",\n  \"b\"\n]"
+        );
+        assert!(format_json_output(response, &JsonOutput::Pretty).contains("\n  \"summary\""));
+        assert_eq!(format_json_output(response, &JsonOutput::Raw), response);
+    }
+
+    #[test]
+    fn json_output_leaves_non_json_unchanged() {
+        let text = "- first\n- second";
+        assert_eq!(format_json_output(text, &JsonOutput::Pretty), text);
+        assert_eq!(
+            format_json_output("{\"a\":1}", &JsonOutput::Field("missing".to_string())),
+            "{\"a\":1}"
+        );
+    }
+
+    #[test]
+    fn split_prompt_template_moves_output_out_of_instructions() {
+        let (instructions, data) =
+            split_prompt_template("Fix: ${output:upper}\nAgain: ${output}", "hi", "[T]");
+        assert_eq!(instructions, "Fix: [T]\nAgain: [T]");
+        assert_eq!(data, "HI");
+    }
+
+    #[test]
+    fn prompt_template_expands_plain_output() {
+        assert_eq!(
+            expand_prompt_template("Fix: ${output}\nAgain: ${output}", "Hi"),
+            "Fix: Hi\nAgain: Hi"
+        );
+    }
+
+    #[test]
+    fn prompt_template_applies_transforms() {
+        assert_eq!(
+            expand_prompt_template(
+                "${output:lower}|${output:upper}|${output:trim}",
+                " Hi There "
+            ),
+            " hi there | HI THERE |Hi There"
+        );
+    }
+
+    #[test]
+    fn prompt_template_unknown_transform_uses_plain_output() {
+        assert_eq!(expand_prompt_template("[${output:reverse}]", "Hi"), "[Hi]");
+    }
+
+    #[test]
+    fn language_tokens_expand_to_auto_when_unknown() {
+        let german = TranscriptLanguage {
+            code: "de".to_string(),
+            name: "German".to_string(),
+        };
+        let template = "Respond in ${language_name} (${language}): ${output}";
+        assert_eq!(
+            expand_language_tokens(template, Some(&german)),
+            "Respond in German (de): ${output}"
+        );
+        assert_eq!(
+            expand_language_tokens(template, None),
+            "Respond in  (auto): ${output}"
+        );
+    }
+
+    #[test]
+    fn prompt_template_keeps_unrelated_text() {
+        assert_eq!(
+            expand_prompt_template("${outputs} ${other} ${output:lower", "Hi"),
+            "${outputs} ${other} ${output:lower"
+        );
+    }
+
+    #[test]
+    fn as_is_leaves_text_untouched() {
+        assert_eq!(
+            apply_transform("Hello, World!", OutputTransform::AsIs),
+            "Hello, World!"
+        );
+    }
+
+    #[test]
+    fn lowercase_keeps_punctuation() {
+        assert_eq!(
+            apply_transform("Hello, World!", OutputTransform::Lowercase),
+            "hello, world!"
+        );
+    }
+
+    #[test]
+    fn snake_case_strips_punctuation() {
+        assert_eq!(
+            apply_transform("User account ID.", OutputTransform::SnakeCase),
+            "user_account_id"
+        );
+        assert_eq!(
+            apply_transform("don't retry", OutputTransform::SnakeCase),
+            "dont_retry"
+        );
+    }
+
+    #[test]
+    fn kebab_case_joins_with_dashes() {
+        assert_eq!(
+            apply_transform("  Feature flag   name ", OutputTransform::KebabCase),
+            "feature-flag-name"
+        );
+    }
+
+    #[test]
+    fn transforms_keep_mixed_script_text_exact() {
+        // "Shalom" with vowel points, then English, a version and a domain
+        let text = "\u{5e9}\u{5b8}\u{5c1}\u{5dc}\u{5d5}\u{5b9}\u{5dd} World, iOS 17.2 \u{2014} example.com!";
+        assert_eq!(
+            apply_transform(text, OutputTransform::Lowercase),
+            "\u{5e9}\u{5b8}\u{5c1}\u{5dc}\u{5d5}\u{5b9}\u{5dd} world, ios 17.2 \u{2014} example.com!"
+        );
+        assert_eq!(
+            apply_transform(text, OutputTransform::SnakeCase),
+            "\u{5e9}\u{5b8}\u{5c1}\u{5dc}\u{5d5}\u{5b9}\u{5dd}_world_ios_17_2_example_com"
+        );
+        assert_eq!(
+            apply_transform(text, OutputTransform::KebabCase),
+            "\u{5e9}\u{5b8}\u{5c1}\u{5dc}\u{5d5}\u{5b9}\u{5dd}-world-ios-17-2-example-com"
+        );
+    }
+
+    #[test]
+    fn transform_matches_identifier_or_name() {
+        let mut settings = crate::settings::get_default_settings();
+        settings.app_output_transforms.insert(
+            "com.microsoft.VSCode".to_string(),
+            OutputTransform::SnakeCase,
+        );
+        settings
+            .app_output_transforms
+            .insert("terminal".to_string(), OutputTransform::KebabCase);
+
+        let vscode = FrontmostApplication {
+            name: "Code".to_string(),
+            identifier: Some("com.microsoft.vscode".to_string()),
+            path: None,
+            window_id: None,
+        };
+        let terminal = FrontmostApplication {
+            name: "Terminal".to_string(),
+            identifier: None,
+            path: None,
+            window_id: None,
+        };
+        let mail = FrontmostApplication {
+            name: "Mail".to_string(),
+            identifier: Some("com.apple.mail".to_string()),
+            path: None,
+            window_id: None,
+        };
+
+        assert_eq!(
+            transform_for_app(&settings, Some(&vscode)),
+            OutputTransform::SnakeCase
+        );
+        assert_eq!(
+            transform_for_app(&settings, Some(&terminal)),
+            OutputTransform::KebabCase
+        );
+        assert_eq!(
+            transform_for_app(&settings, Some(&mail)),
+            OutputTransform::AsIs
+        );
+        assert_eq!(transform_for_app(&settings, None), OutputTransform::AsIs);
+    }
+
+    #[test]
+    fn lines_collapse_to_one() {
+        assert_eq!(
+            collapse_to_single_line("First line \r\n\n  second line\nthird\n\n"),
+            "First line second line third"
+        );
+        assert_eq!(collapse_to_single_line("one line"), "one line");
+        assert_eq!(collapse_to_single_line("\n"), "");
+    }
+
+    #[test]
+    fn ltr_runs_in_rtl_text_are_isolated() {
+        // "I installed iOS 17 on the iPhone."
+        let text = "\u{5d4}\u{5ea}\u{5e7}\u{5e0}\u{5ea}\u{5d9} iOS 17 \u{5e2}\u{5dc} iPhone.";
+        assert_eq!(
+            isolate_ltr_runs(text),
+            "\u{5d4}\u{5ea}\u{5e7}\u{5e0}\u{5ea}\u{5d9} \u{2066}iOS 17\u{2069} \u{5e2}\u{5dc} \u{2066}iPhone\u{2069}."
+        );
+        // Numbers on their own already follow the surrounding text
+        let text = "\u{5d2}\u{5e8}\u{5e1}\u{5d4} 1.29";
+        assert_eq!(isolate_ltr_runs(text), text);
+        // Lines starting left to right are left alone
+        let text = "\u{5e9}\u{5dc}\u{5d5}\u{5dd} git\nUse \u{5e2}\u{5d1}\u{5e8}\u{5d9}\u{5ea} here";
+        assert_eq!(
+            isolate_ltr_runs(text),
+            "\u{5e9}\u{5dc}\u{5d5}\u{5dd} \u{2066}git\u{2069}\nUse \u{5e2}\u{5d1}\u{5e8}\u{5d9}\u{5ea} here"
+        );
+    }
+}
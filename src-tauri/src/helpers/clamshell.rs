@@ -1,3 +1,15 @@
+//! Lid (clamshell) and laptop detection, used to switch to `clamshell_microphone`
+//! while a laptop's lid is closed.
+//!
+//! - macOS reads `AppleClamshellState` from the IORegistry.
+//! - Windows has no lid state to query. The lid counts as closed when the laptop's
+//!   built-in screen is off while an external display is on, which is also the
+//!   case when the built-in screen was turned off by hand.
+//! - Linux reads the ACPI lid button.
+//!
+//! Elsewhere the lid is never closed and `clamshell_microphone` is ignored.
+
+use crate::error::HandyError;
 #[cfg(target_os = "macos")]
 use std::process::Command;
 
@@ -32,12 +44,12 @@ pub fn is_clamshell() -> Result<bool, String> {
 #[cfg(target_os = "macos")]
 #[tauri::command]
 #[specta::specta]
-pub fn is_laptop() -> Result<bool, String> {
+pub fn is_laptop() -> Result<bool, HandyError> {
     let output = Command::new("pmset")
         .arg("-g")
         .arg("batt")
         .output()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| HandyError::io(e.to_string()))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
 
@@ -45,19 +57,146 @@ pub fn is_laptop() -> Result<bool, String> {
     Ok(stdout.contains("InternalBattery"))
 }
 
-/// Stub implementation for non-macOS platforms
-/// Always returns false since clamshell mode is macOS-specific
-#[cfg(not(target_os = "macos"))]
+/// Checks if the laptop's lid is closed, going by which displays are active
+///
+/// Desktops only have external displays, so this requires a battery too.
+#[cfg(target_os = "windows")]
+pub fn is_clamshell() -> Result<bool, String> {
+    use windows::Win32::Devices::Display::{
+        GetDisplayConfigBufferSizes, QueryDisplayConfig, DISPLAYCONFIG_MODE_INFO,
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EMBEDDED,
+        DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL, DISPLAYCONFIG_OUTPUT_TECHNOLOGY_UDI_EMBEDDED,
+        DISPLAYCONFIG_PATH_INFO, QDC_ONLY_ACTIVE_PATHS,
+    };
+
+    if !is_laptop().unwrap_or(false) {
+        return Ok(false);
+    }
+
+    let mut path_count = 0;
+    let mut mode_count = 0;
+    unsafe { GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count) }
+        .ok()
+        .map_err(|e| format!("Failed to size the display configuration: {}", e))?;
+    let mut paths = vec![DISPLAYCONFIG_PATH_INFO::default(); path_count as usize];
+    let mut modes = vec![DISPLAYCONFIG_MODE_INFO::default(); mode_count as usize];
+    unsafe {
+        QueryDisplayConfig(
+            QDC_ONLY_ACTIVE_PATHS,
+            &mut path_count,
+            paths.as_mut_ptr(),
+            &mut mode_count,
+            modes.as_mut_ptr(),
+            None,
+        )
+    }
+    .ok()
+    .map_err(|e| format!("Failed to query the display configuration: {}", e))?;
+    paths.truncate(path_count as usize);
+
+    let internal: Vec<bool> = paths
+        .iter()
+        .map(|path| {
+            let technology = path.targetInfo.outputTechnology;
+            technology == DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL
+                || technology == DISPLAYCONFIG_OUTPUT_TECHNOLOGY_DISPLAYPORT_EMBEDDED
+                || technology == DISPLAYCONFIG_OUTPUT_TECHNOLOGY_UDI_EMBEDDED
+        })
+        .collect();
+    Ok(only_external_displays(&internal))
+}
+
+/// Checks for a system battery, which laptops have and desktops typically don't
+#[cfg(target_os = "windows")]
+#[tauri::command]
+#[specta::specta]
+pub fn is_laptop() -> Result<bool, HandyError> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // 128 means no system battery, 255 that its status is unknown
+    const NO_SYSTEM_BATTERY: u8 = 128;
+    const UNKNOWN_STATUS: u8 = 255;
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }
+        .map_err(|e| HandyError::io(format!("Failed to read the power status: {}", e)))?;
+    Ok(status.BatteryFlag != UNKNOWN_STATUS && status.BatteryFlag & NO_SYSTEM_BATTERY == 0)
+}
+
+/// True when displays are on, but none of them is the laptop's own.
+/// `internal` says for each active display whether it is built in.
+#[cfg(any(target_os = "windows", test))]
+fn only_external_displays(internal: &[bool]) -> bool {
+    !internal.is_empty() && !internal.contains(&true)
+}
+
+/// Checks the ACPI lid button, false on machines without one
+#[cfg(target_os = "linux")]
+pub fn is_clamshell() -> Result<bool, String> {
+    let Ok(lids) = std::fs::read_dir("/proc/acpi/button/lid") else {
+        return Ok(false);
+    };
+    Ok(lids.flatten().any(|lid| {
+        std::fs::read_to_string(lid.path().join("state"))
+            .is_ok_and(|state| lid_state_closed(&state))
+    }))
+}
+
+/// Checks for a system battery, which laptops have and desktops typically don't
+#[cfg(target_os = "linux")]
+#[tauri::command]
+#[specta::specta]
+pub fn is_laptop() -> Result<bool, HandyError> {
+    // Peripheral batteries, like a mouse's, report a scope of Device
+    Ok(PowerSupply::all()
+        .iter()
+        .any(|supply| supply.read("type") == "Battery" && supply.read("scope") != "Device"))
+}
+
+/// An entry of `/sys/class/power_supply`: a battery, charger or USB port
+#[cfg(target_os = "linux")]
+pub(crate) struct PowerSupply(std::path::PathBuf);
+
+#[cfg(target_os = "linux")]
+impl PowerSupply {
+    pub(crate) fn all() -> Vec<Self> {
+        std::fs::read_dir("/sys/class/power_supply")
+            .map(|supplies| {
+                supplies
+                    .flatten()
+                    .map(|supply| Self(supply.path()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// An attribute such as `type` or `online`, empty when missing
+    pub(crate) fn read(&self, name: &str) -> String {
+        std::fs::read_to_string(self.0.join(name))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Parses `/proc/acpi/button/lid/*/state`, e.g. `state:      closed`
+#[cfg(any(target_os = "linux", test))]
+fn lid_state_closed(state: &str) -> bool {
+    state.split_whitespace().last() == Some("closed")
+}
+
+/// Stub implementation for other platforms
+/// Always returns false since the lid state isn't known there
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 pub fn is_clamshell() -> Result<bool, String> {
     Ok(false)
 }
 
-/// Stub implementation for non-macOS platforms
-/// Always returns false since laptop detection is macOS-specific
-#[cfg(not(target_os = "macos"))]
+/// Stub implementation for other platforms
+/// Always returns false since laptop detection isn't available there
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 #[tauri::command]
 #[specta::specta]
-pub fn is_laptop() -> Result<bool, String> {
+pub fn is_laptop() -> Result<bool, HandyError> {
     Ok(false)
 }
 
@@ -84,4 +223,16 @@ mod tests {
             println!("Is laptop: {}", is_laptop);
         }
     }
+
+    #[test]
+    fn lid_is_closed_by_acpi_state_or_displays() {
+        assert!(lid_state_closed("state:      closed\n"));
+        assert!(!lid_state_closed("state:      open\n"));
+        assert!(!lid_state_closed(""));
+
+        assert!(only_external_displays(&[false, false]));
+        assert!(!only_external_displays(&[true, false]));
+        // Everything off, e.g. asleep
+        assert!(!only_external_displays(&[]));
+    }
 }
@@ -0,0 +1,125 @@
+//! Reads back the most recent entries of the log file for in-app diagnostics.
+//!
+//! Lines are written by tauri-plugin-log as `[date][time][target][LEVEL] message`,
+//! with timestamps in UTC. Lines without that header continue the previous entry.
+
+use chrono::NaiveDateTime;
+
+/// One log entry, possibly spanning several lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub timestamp: Option<NaiveDateTime>,
+    pub level: Option<log::Level>,
+    pub text: String,
+}
+
+/// Timestamp and level from the header of a log line
+fn parse_header(line: &str) -> Option<(NaiveDateTime, Option<log::Level>)> {
+    let mut fields = Vec::new();
+    let mut rest = line;
+    while let Some(field) = rest.strip_prefix('[') {
+        let end = field.find(']')?;
+        fields.push(&field[..end]);
+        rest = &field[end + 1..];
+    }
+    let [date, time, tags @ ..] = fields.as_slice() else {
+        return None;
+    };
+    let timestamp =
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S").ok()?;
+    let level = tags.iter().find_map(|tag| tag.parse::<log::Level>().ok());
+    Some((timestamp, level))
+}
+
+pub fn parse_entries(contents: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for line in contents.lines() {
+        match parse_header(line) {
+            Some((timestamp, level)) => entries.push(LogEntry {
+                timestamp: Some(timestamp),
+                level,
+                text: line.to_string(),
+            }),
+            None => match entries.last_mut() {
+                Some(entry) => {
+                    entry.text.push('\n');
+                    entry.text.push_str(line);
+                }
+                // Continuation of an entry from before the file was rotated
+                None => entries.push(LogEntry {
+                    timestamp: None,
+                    level: None,
+                    text: line.to_string(),
+                }),
+            },
+        }
+    }
+    entries
+}
+
+/// The last `limit` entries at `min_level` or more severe, written at or after `since`
+pub fn recent_entries(
+    contents: &str,
+    limit: usize,
+    since: Option<NaiveDateTime>,
+    min_level: Option<log::Level>,
+) -> Vec<String> {
+    let mut entries: Vec<String> = parse_entries(contents)
+        .into_iter()
+        .rev()
+        .filter(|entry| match (since, entry.timestamp) {
+            (Some(since), Some(timestamp)) => timestamp >= since,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .filter(|entry| match (min_level, entry.level) {
+            (Some(min_level), Some(level)) => level <= min_level,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .take(limit)
+        .map(|entry| entry.text)
+        .collect();
+    entries.reverse();
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+[2024-05-01][10:00:00][handy_app_lib::actions][DEBUG] Starting transcription
+[2024-05-01][10:00:01][handy_app_lib::actions][ERROR] Failed to paste
+caused by: clipboard unavailable
+[2024-05-01][10:00:02][handy_app_lib::managers::model][INFO] Model loaded
+";
+
+    fn at(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("2024-05-01 {}", time), "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn continuation_lines_join_their_entry() {
+        let entries = parse_entries(LOG);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].level, Some(log::Level::Error));
+        assert_eq!(entries[1].timestamp, Some(at("10:00:01")));
+        assert!(entries[1]
+            .text
+            .ends_with("\ncaused by: clipboard unavailable"));
+    }
+
+    #[test]
+    fn filters_by_count_time_and_level() {
+        let last = recent_entries(LOG, 1, None, None);
+        assert_eq!(last.len(), 1);
+        assert!(last[0].ends_with("Model loaded"));
+
+        assert_eq!(recent_entries(LOG, 10, Some(at("10:00:01")), None).len(), 2);
+
+        let errors_and_info = recent_entries(LOG, 10, None, Some(log::Level::Info));
+        assert_eq!(errors_and_info.len(), 2);
+        assert!(errors_and_info[0].contains("Failed to paste"));
+    }
+}
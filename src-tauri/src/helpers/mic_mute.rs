@@ -0,0 +1,457 @@
+//! Whether the OS has muted the microphone, so a recording isn't made of silence.
+//!
+//! The check covers the capture endpoint's own mute on Windows, the input device's
+//! mute on macOS, and the default source on Linux, where a selected microphone
+//! other than the default can't be checked. A platform that can't tell counts as
+//! unmuted. It runs on the recording's watcher thread, never on the start path,
+//! since on Linux it runs a mixer command.
+
+use crate::audio_feedback::play_error_feedback;
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent, MicrophoneMuted};
+use crate::managers::audio::AudioRecordingManager;
+use crate::settings::{get_settings, FeedbackMode, WindowsDeviceRole};
+use crate::utils;
+use log::{debug, info, warn};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often a recording checks that the microphone wasn't muted since it started.
+/// Less often on Linux, where every check runs a mixer command.
+#[cfg(not(target_os = "linux"))]
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+#[cfg(target_os = "linux")]
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Whether the input named `device_name`, or the default one, is muted. None when
+/// the platform doesn't say. `role` picks Windows' default input.
+pub fn input_muted(device_name: Option<&str>, role: WindowsDeviceRole) -> Option<bool> {
+    #[cfg(target_os = "windows")]
+    {
+        with_capture_volume(device_name, role, |volume| unsafe {
+            volume.GetMute().map(|muted| muted.as_bool())
+        })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    let _ = role;
+
+    #[cfg(target_os = "linux")]
+    {
+        // The mixers only tell about the default source
+        if !linux_mixer::is_default_source(device_name) {
+            return None;
+        }
+        linux_mixer::input_muted()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        macos_capture::input_muted(device_name)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        let _ = device_name;
+        None
+    }
+}
+
+/// Clears the input's mute, returning whether it was applied
+pub fn unmute_input(device_name: Option<&str>, role: WindowsDeviceRole) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        with_capture_volume(device_name, role, |volume| unsafe {
+            volume.SetMute(false, std::ptr::null())
+        })
+        .is_some()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    let _ = role;
+
+    #[cfg(target_os = "linux")]
+    {
+        linux_mixer::is_default_source(device_name) && linux_mixer::unmute_input()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        macos_capture::unmute_input(device_name)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        let _ = device_name;
+        false
+    }
+}
+
+fn report(app: &AppHandle, device: Option<String>, during_recording: bool) {
+    emit_event(
+        app,
+        AppEvent::MicrophoneMuted(MicrophoneMuted {
+            device,
+            during_recording,
+        }),
+    );
+}
+
+/// Stops the recording of `binding_id` with the error cue and a `microphone-muted`
+/// event if the microphone is muted when it starts or gets muted during it, keeping
+/// what was said before
+pub fn watch(app: &AppHandle, binding_id: &str, feedback: FeedbackMode) {
+    let app = app.clone();
+    let binding_id = binding_id.to_string();
+    thread::spawn(move || {
+        let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
+        let device = rm.effective_microphone_name();
+        let role = get_settings(&app).windows_default_device_role;
+        let mut during_recording = false;
+        loop {
+            if rm.active_binding().as_deref() != Some(binding_id.as_str()) {
+                return;
+            }
+            if input_muted(device.as_deref(), role) == Some(true) {
+                break;
+            }
+            during_recording = true;
+            thread::sleep(POLL_INTERVAL);
+        }
+        if during_recording {
+            info!("The microphone was muted, stopping the recording");
+        } else {
+            info!(
+                "The microphone '{}' is muted, stopping the recording",
+                device.as_deref().unwrap_or("default")
+            );
+        }
+        report(&app, device, during_recording);
+        if let Err(e) = utils::stop_current_recording(&app) {
+            warn!("Failed to stop the recording on a muted microphone: {}", e);
+        }
+        play_error_feedback(&app, feedback);
+    });
+}
+
+/// Unmutes the microphone recordings use
+pub fn unmute(app: &AppHandle) -> Result<(), HandyError> {
+    let device = app
+        .state::<Arc<AudioRecordingManager>>()
+        .effective_microphone_name();
+    let role = get_settings(app).windows_default_device_role;
+    if input_muted(device.as_deref(), role) == Some(false) {
+        return Ok(());
+    }
+    if !unmute_input(device.as_deref(), role) {
+        return Err(HandyError::permission(
+            "The microphone can't be unmuted from Handy, unmute it in the system settings",
+        ));
+    }
+    debug!(
+        "Unmuted the microphone '{}'",
+        device.as_deref().unwrap_or("default")
+    );
+    Ok(())
+}
+
+/// Runs `f` against the volume control of the capture endpoint named
+/// `device_name`, or the default one for `role`
+#[cfg(target_os = "windows")]
+fn with_capture_volume<T>(
+    device_name: Option<&str>,
+    role: WindowsDeviceRole,
+    f: impl FnOnce(
+        &windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume,
+    ) -> windows::core::Result<T>,
+) -> Option<T> {
+    use super::windows_audio;
+
+    let device = windows_audio::capture_endpoint(device_name, role)?;
+    windows_audio::with_volume(&device, f)
+}
+
+/// PipeWire (wpctl), PulseAudio (pactl) and ALSA (amixer), tried in that order
+/// like the output volume in `volume`
+#[cfg(any(target_os = "linux", test))]
+mod linux_mixer {
+    /// Whether the recording goes through the default source, the only one the
+    /// mixers are asked about. cpal's ALSA names for it are the sound servers'.
+    pub fn is_default_source(device_name: Option<&str>) -> bool {
+        matches!(device_name, None | Some("default" | "pipewire" | "pulse"))
+    }
+
+    /// Parses `wpctl get-volume` output such as `Volume: 0.40 [MUTED]`
+    pub fn parse_wpctl_muted(output: &str) -> Option<bool> {
+        let output = output.trim();
+        output
+            .starts_with("Volume:")
+            .then(|| output.contains("[MUTED]"))
+    }
+
+    /// Parses `pactl get-source-mute` output such as `Mute: yes`
+    pub fn parse_pactl_muted(output: &str) -> Option<bool> {
+        let value = output.trim().strip_prefix("Mute:")?.trim();
+        Some(value == "yes")
+    }
+
+    /// Parses `amixer get Capture` output, muted when no channel's switch is `[on]`
+    pub fn parse_amixer_muted(output: &str) -> Option<bool> {
+        let switches: Vec<&str> = output
+            .lines()
+            .filter(|line| line.contains("[on]") || line.contains("[off]"))
+            .collect();
+        if switches.is_empty() {
+            return None;
+        }
+        Some(!switches.iter().any(|line| line.contains("[on]")))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn run(program: &str, args: &[&str]) -> Option<String> {
+        let output = std::process::Command::new(program)
+            .args(args)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn input_muted() -> Option<bool> {
+        if let Some(output) = run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SOURCE@"]) {
+            return parse_wpctl_muted(&output);
+        }
+        if let Some(output) = run("pactl", &["get-source-mute", "@DEFAULT_SOURCE@"]) {
+            return parse_pactl_muted(&output);
+        }
+        run("amixer", &["get", "Capture"]).and_then(|output| parse_amixer_muted(&output))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn unmute_input() -> bool {
+        run("wpctl", &["set-mute", "@DEFAULT_AUDIO_SOURCE@", "0"]).is_some()
+            || run("pactl", &["set-source-mute", "@DEFAULT_SOURCE@", "0"]).is_some()
+            || run("amixer", &["set", "Capture", "cap"]).is_some()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos_capture {
+    use std::ffi::c_void;
+
+    type AudioObjectId = u32;
+    type OsStatus = i32;
+    type CfStringRef = *const c_void;
+
+    #[repr(C)]
+    struct AudioObjectPropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    const AUDIO_OBJECT_SYSTEM_OBJECT: AudioObjectId = 1;
+    const PROPERTY_DEFAULT_INPUT_DEVICE: u32 = u32::from_be_bytes(*b"dIn ");
+    const PROPERTY_DEVICES: u32 = u32::from_be_bytes(*b"dev#");
+    const PROPERTY_NAME: u32 = u32::from_be_bytes(*b"lnam");
+    const PROPERTY_MUTE: u32 = u32::from_be_bytes(*b"mute");
+    const SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+    const SCOPE_INPUT: u32 = u32::from_be_bytes(*b"inpt");
+    const ELEMENT_MAIN: u32 = 0;
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioObjectGetPropertyDataSize(
+            object: AudioObjectId,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+        ) -> OsStatus;
+        fn AudioObjectGetPropertyData(
+            object: AudioObjectId,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> OsStatus;
+        fn AudioObjectSetPropertyData(
+            object: AudioObjectId,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: u32,
+            data: *const c_void,
+        ) -> OsStatus;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringGetCString(
+            string: CfStringRef,
+            buffer: *mut std::ffi::c_char,
+            buffer_size: isize,
+            encoding: u32,
+        ) -> u8;
+        fn CFRelease(object: *const c_void);
+    }
+
+    fn address(selector: u32, scope: u32) -> AudioObjectPropertyAddress {
+        AudioObjectPropertyAddress {
+            selector,
+            scope,
+            element: ELEMENT_MAIN,
+        }
+    }
+
+    fn get<T: Default>(object: AudioObjectId, address: &AudioObjectPropertyAddress) -> Option<T> {
+        let mut value = T::default();
+        let mut size = std::mem::size_of::<T>() as u32;
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                object,
+                address,
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut value as *mut T as *mut c_void,
+            )
+        };
+        (status == 0).then_some(value)
+    }
+
+    fn device_name(device: AudioObjectId) -> Option<String> {
+        let name: usize = get(device, &address(PROPERTY_NAME, SCOPE_GLOBAL))?;
+        let name = name as CfStringRef;
+        if name.is_null() {
+            return None;
+        }
+        let mut buffer = [0 as std::ffi::c_char; 256];
+        let converted = unsafe {
+            let converted = CFStringGetCString(
+                name,
+                buffer.as_mut_ptr(),
+                buffer.len() as isize,
+                CF_STRING_ENCODING_UTF8,
+            );
+            CFRelease(name);
+            converted
+        };
+        if converted == 0 {
+            return None;
+        }
+        let name = unsafe { std::ffi::CStr::from_ptr(buffer.as_ptr()) };
+        Some(name.to_string_lossy().into_owned())
+    }
+
+    /// The device named `device_name`, or the default input
+    fn input_device(device_name: Option<&str>) -> Option<AudioObjectId> {
+        let named = device_name.and_then(|name| {
+            let devices_address = address(PROPERTY_DEVICES, SCOPE_GLOBAL);
+            let mut size = 0u32;
+            let status = unsafe {
+                AudioObjectGetPropertyDataSize(
+                    AUDIO_OBJECT_SYSTEM_OBJECT,
+                    &devices_address,
+                    0,
+                    std::ptr::null(),
+                    &mut size,
+                )
+            };
+            if status != 0 {
+                return None;
+            }
+            let mut devices =
+                vec![0 as AudioObjectId; size as usize / std::mem::size_of::<AudioObjectId>()];
+            let status = unsafe {
+                AudioObjectGetPropertyData(
+                    AUDIO_OBJECT_SYSTEM_OBJECT,
+                    &devices_address,
+                    0,
+                    std::ptr::null(),
+                    &mut size,
+                    devices.as_mut_ptr() as *mut c_void,
+                )
+            };
+            if status != 0 {
+                return None;
+            }
+            devices
+                .into_iter()
+                .find(|&device| device_name(device).as_deref() == Some(name))
+        });
+        named.or_else(|| {
+            get::<AudioObjectId>(
+                AUDIO_OBJECT_SYSTEM_OBJECT,
+                &address(PROPERTY_DEFAULT_INPUT_DEVICE, SCOPE_GLOBAL),
+            )
+            .filter(|&device| device != 0)
+        })
+    }
+
+    // AVAudioApplication's input mute is left alone, it only mutes this app's own
+    // input and says nothing about the device
+    pub fn input_muted(device_name: Option<&str>) -> Option<bool> {
+        let device = input_device(device_name)?;
+        get::<u32>(device, &address(PROPERTY_MUTE, SCOPE_INPUT)).map(|muted| muted != 0)
+    }
+
+    pub fn unmute_input(device_name: Option<&str>) -> bool {
+        let Some(device) = input_device(device_name) else {
+            return false;
+        };
+        let unmuted: u32 = 0;
+        let status = unsafe {
+            AudioObjectSetPropertyData(
+                device,
+                &address(PROPERTY_MUTE, SCOPE_INPUT),
+                0,
+                std::ptr::null(),
+                std::mem::size_of::<u32>() as u32,
+                &unmuted as *const u32 as *const c_void,
+            )
+        };
+        status == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::linux_mixer::*;
+
+    #[test]
+    fn parses_mixer_mute_state() {
+        assert_eq!(parse_wpctl_muted("Volume: 0.40 [MUTED]\n"), Some(true));
+        assert_eq!(parse_wpctl_muted("Volume: 0.40\n"), Some(false));
+        assert_eq!(parse_pactl_muted("Mute: yes\n"), Some(true));
+        assert_eq!(parse_pactl_muted("Mute: no\n"), Some(false));
+        assert_eq!(
+            parse_amixer_muted(
+                "Simple mixer control 'Capture',0\n  Front Left: Capture 40 [63%] [12.00dB] [off]\n  Front Right: Capture 40 [63%] [12.00dB] [off]\n"
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            parse_amixer_muted(
+                "Simple mixer control 'Capture',0\n  Front Left: Capture 40 [63%] [12.00dB] [on]\n  Front Right: Capture 40 [63%] [12.00dB] [off]\n"
+            ),
+            Some(false)
+        );
+        assert_eq!(parse_wpctl_muted("garbage"), None);
+        assert_eq!(parse_amixer_muted("garbage"), None);
+    }
+
+    #[test]
+    fn only_the_default_source_is_checked() {
+        assert!(is_default_source(None));
+        assert!(is_default_source(Some("pipewire")));
+        assert!(!is_default_source(Some("hw:CARD=USB,DEV=0")));
+    }
+}
@@ -1 +1,7 @@
 pub mod clamshell;
+pub mod log_tail;
+pub mod mic_mute;
+pub mod output_devices;
+pub mod volume;
+#[cfg(target_os = "windows")]
+pub mod windows_audio;
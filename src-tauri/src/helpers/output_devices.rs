@@ -0,0 +1,259 @@
+//! Notifications for audio output devices being connected or removed, or the
+//! default output changing.
+//!
+//! - macOS registers CoreAudio property listeners on the system object.
+//! - Windows registers an `IMMNotificationClient` with the endpoint enumerator.
+//! - Linux follows `pactl subscribe`, which PipeWire answers too.
+//!
+//! Where none of these are available callers fall back to polling.
+
+use log::{debug, warn};
+
+/// Calls `on_change` from a background thread whenever output devices change.
+/// Returns false when the platform offers no notifications. On Linux the callback
+/// is dropped if the sound server goes away, which callers can detect through
+/// whatever the callback owns, e.g. a channel sender.
+pub fn watch(on_change: impl Fn() + Send + Sync + 'static) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        macos_core_audio::watch(Box::new(on_change))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows_endpoints::watch(Box::new(on_change))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        pulse_events::watch(Box::new(on_change))
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = on_change;
+        debug!("No output device notifications on this platform");
+        false
+    }
+}
+
+type Callback = Box<dyn Fn() + Send + Sync>;
+
+#[cfg(target_os = "macos")]
+mod macos_core_audio {
+    use super::Callback;
+    use log::warn;
+    use std::ffi::c_void;
+
+    type AudioObjectId = u32;
+    type OsStatus = i32;
+
+    #[repr(C)]
+    struct AudioObjectPropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    type PropertyListener = unsafe extern "C" fn(
+        object: AudioObjectId,
+        address_count: u32,
+        addresses: *const AudioObjectPropertyAddress,
+        client_data: *mut c_void,
+    ) -> OsStatus;
+
+    const AUDIO_OBJECT_SYSTEM_OBJECT: AudioObjectId = 1;
+    const PROPERTY_DEVICES: u32 = u32::from_be_bytes(*b"dev#");
+    const PROPERTY_DEFAULT_OUTPUT_DEVICE: u32 = u32::from_be_bytes(*b"dOut");
+    const SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+    const ELEMENT_MAIN: u32 = 0;
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioObjectAddPropertyListener(
+            object: AudioObjectId,
+            address: *const AudioObjectPropertyAddress,
+            listener: PropertyListener,
+            client_data: *mut c_void,
+        ) -> OsStatus;
+    }
+
+    /// Runs on CoreAudio's notification thread
+    unsafe extern "C" fn on_property_changed(
+        _object: AudioObjectId,
+        _address_count: u32,
+        _addresses: *const AudioObjectPropertyAddress,
+        client_data: *mut c_void,
+    ) -> OsStatus {
+        let on_change = &*(client_data as *const Callback);
+        on_change();
+        0
+    }
+
+    pub fn watch(on_change: Callback) -> bool {
+        // Never freed, the listeners stay registered for the life of the app
+        let client_data = Box::into_raw(Box::new(on_change)) as *mut c_void;
+        for selector in [PROPERTY_DEVICES, PROPERTY_DEFAULT_OUTPUT_DEVICE] {
+            let address = AudioObjectPropertyAddress {
+                selector,
+                scope: SCOPE_GLOBAL,
+                element: ELEMENT_MAIN,
+            };
+            let status = unsafe {
+                AudioObjectAddPropertyListener(
+                    AUDIO_OBJECT_SYSTEM_OBJECT,
+                    &address,
+                    on_property_changed,
+                    client_data,
+                )
+            };
+            if status != 0 {
+                warn!("Failed to listen for output device changes: {}", status);
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_endpoints {
+    use super::Callback;
+    use log::warn;
+    use windows::core::{implement, Result, PCWSTR};
+    use windows::Win32::{
+        Foundation::PROPERTYKEY,
+        Media::Audio::{
+            eConsole, eRender, EDataFlow, ERole, IMMDeviceEnumerator, IMMNotificationClient,
+            IMMNotificationClient_Impl, DEVICE_STATE,
+        },
+    };
+
+    #[implement(IMMNotificationClient)]
+    struct OutputNotifications {
+        on_change: Callback,
+    }
+
+    impl IMMNotificationClient_Impl for OutputNotifications_Impl {
+        fn OnDeviceStateChanged(&self, _device_id: &PCWSTR, _state: DEVICE_STATE) -> Result<()> {
+            (self.on_change)();
+            Ok(())
+        }
+
+        fn OnDeviceAdded(&self, _device_id: &PCWSTR) -> Result<()> {
+            (self.on_change)();
+            Ok(())
+        }
+
+        fn OnDeviceRemoved(&self, _device_id: &PCWSTR) -> Result<()> {
+            (self.on_change)();
+            Ok(())
+        }
+
+        fn OnDefaultDeviceChanged(
+            &self,
+            flow: EDataFlow,
+            role: ERole,
+            _device_id: &PCWSTR,
+        ) -> Result<()> {
+            if flow == eRender && role == eConsole {
+                (self.on_change)();
+            }
+            Ok(())
+        }
+
+        fn OnPropertyValueChanged(&self, _device_id: &PCWSTR, _key: &PROPERTYKEY) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    pub fn watch(on_change: Callback) -> bool {
+        let devices: IMMDeviceEnumerator = match crate::helpers::windows_audio::enumerator() {
+            Ok(devices) => devices,
+            Err(e) => {
+                warn!("Failed to create the audio endpoint enumerator: {}", e);
+                return false;
+            }
+        };
+        unsafe {
+            let client: IMMNotificationClient = OutputNotifications { on_change }.into();
+            if let Err(e) = devices.RegisterEndpointNotificationCallback(&client) {
+                warn!("Failed to listen for output device changes: {}", e);
+                return false;
+            }
+            // Both stay alive for the life of the app so the callback stays registered
+            std::mem::forget(client);
+            std::mem::forget(devices);
+        }
+        true
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+mod pulse_events {
+    /// Whether a `pactl subscribe` line reports a sink being added or removed, or
+    /// the server's default sink changing. Sink `change` events fire on every
+    /// volume change and are ignored.
+    pub fn is_output_event(line: &str) -> bool {
+        let Some(event) = line.trim().strip_prefix("Event '") else {
+            return false;
+        };
+        let Some((kind, target)) = event.split_once("' on ") else {
+            return false;
+        };
+        let facility = target.split_whitespace().next().unwrap_or_default();
+        match facility {
+            "sink" => kind == "new" || kind == "remove",
+            "server" => kind == "change",
+            _ => false,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn watch(on_change: super::Callback) -> bool {
+        use super::{debug, warn};
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let mut child = match Command::new("pactl")
+            .arg("subscribe")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                debug!("pactl is not available for output device events: {}", e);
+                return false;
+            }
+        };
+        let Some(stdout) = child.stdout.take() else {
+            return false;
+        };
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if is_output_event(&line) {
+                    on_change();
+                }
+            }
+            let _ = child.wait();
+            warn!("pactl subscribe exited, no longer notified of output device changes");
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pulse_events::is_output_event;
+
+    #[test]
+    fn pactl_sink_additions_and_default_changes_count() {
+        assert!(is_output_event("Event 'new' on sink #57"));
+        assert!(is_output_event("Event 'remove' on sink #57"));
+        assert!(is_output_event("Event 'change' on server #4"));
+
+        // Volume changes and other facilities don't
+        assert!(!is_output_event("Event 'change' on sink #57"));
+        assert!(!is_output_event("Event 'new' on source #12"));
+        assert!(!is_output_event("Event 'new' on sink-input #301"));
+        assert!(!is_output_event("garbage"));
+    }
+}
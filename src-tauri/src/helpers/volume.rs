@@ -0,0 +1,304 @@
+//! System output volume, used to duck other audio while recording.
+//!
+//! Volumes are scalars from 0.0 to 1.0 on the default output device.
+
+/// Volume to duck to, as `level_percent` of the current volume
+pub fn ducked_volume(current: f32, level_percent: u32) -> f32 {
+    (current * level_percent.min(100) as f32 / 100.0).clamp(0.0, 1.0)
+}
+
+/// Current volume of the default output device, if the platform mixer is reachable
+pub fn output_volume() -> Option<f32> {
+    #[cfg(target_os = "windows")]
+    {
+        with_output_volume(|volume| unsafe { volume.GetMasterVolumeLevelScalar() })
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux_mixer::output_volume()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        macos_core_audio::output_volume()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// Sets the volume of the default output device, returning whether it was applied
+pub fn set_output_volume(volume: f32) -> bool {
+    let volume = volume.clamp(0.0, 1.0);
+
+    #[cfg(target_os = "windows")]
+    {
+        with_output_volume(|endpoint| unsafe {
+            endpoint.SetMasterVolumeLevelScalar(volume, std::ptr::null())
+        })
+        .is_some()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux_mixer::set_output_volume(volume)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        macos_core_audio::set_output_volume(volume)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        let _ = volume;
+        false
+    }
+}
+
+/// Runs `f` against the default render endpoint's volume control
+#[cfg(target_os = "windows")]
+pub fn with_output_volume<T>(
+    f: impl FnOnce(
+        &windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume,
+    ) -> windows::core::Result<T>,
+) -> Option<T> {
+    use super::windows_audio;
+    use windows::Win32::Media::Audio::{eMultimedia, eRender};
+
+    let device = windows_audio::default_endpoint(eRender, eMultimedia)?;
+    windows_audio::with_volume(&device, f)
+}
+
+/// PipeWire (wpctl), PulseAudio (pactl) and ALSA (amixer), tried in that order
+/// like `set_mute`. Reads and writes go through the same first working backend.
+#[cfg(any(target_os = "linux", test))]
+mod linux_mixer {
+    /// PulseAudio's 100% volume
+    const PA_VOLUME_NORM: f32 = 65536.0;
+
+    /// Parses `wpctl get-volume` output such as `Volume: 0.40 [MUTED]`
+    pub fn parse_wpctl_volume(output: &str) -> Option<f32> {
+        output
+            .trim()
+            .strip_prefix("Volume:")?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
+
+    /// Parses the first channel of `pactl get-sink-volume` output such as
+    /// `Volume: front-left: 26214 /  40% / -23.88 dB,   front-right: ...`
+    pub fn parse_pactl_volume(output: &str) -> Option<f32> {
+        let channels = output.trim().strip_prefix("Volume:")?;
+        let (_, first) = channels.split_once(':')?;
+        let raw: u32 = first.split('/').next()?.trim().parse().ok()?;
+        Some(raw as f32 / PA_VOLUME_NORM)
+    }
+
+    /// Parses `amixer get Master` output, which reports each channel as `[40%]`
+    pub fn parse_amixer_volume(output: &str) -> Option<f32> {
+        let start = output.find('[')? + 1;
+        let end = start + output[start..].find("%]")?;
+        let percent: u32 = output[start..end].parse().ok()?;
+        Some(percent as f32 / 100.0)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn run(program: &str, args: &[&str]) -> Option<String> {
+        let output = std::process::Command::new(program)
+            .args(args)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn output_volume() -> Option<f32> {
+        if let Some(output) = run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"]) {
+            return parse_wpctl_volume(&output);
+        }
+        if let Some(output) = run("pactl", &["get-sink-volume", "@DEFAULT_SINK@"]) {
+            return parse_pactl_volume(&output);
+        }
+        run("amixer", &["get", "Master"]).and_then(|output| parse_amixer_volume(&output))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_output_volume(volume: f32) -> bool {
+        let wpctl_volume = format!("{:.4}", volume);
+        if run(
+            "wpctl",
+            &["set-volume", "@DEFAULT_AUDIO_SINK@", &wpctl_volume],
+        )
+        .is_some()
+        {
+            return true;
+        }
+        // Raw volume rather than a percentage so restoring is exact
+        let raw = ((volume * PA_VOLUME_NORM).round() as u32).to_string();
+        if run("pactl", &["set-sink-volume", "@DEFAULT_SINK@", &raw]).is_some() {
+            return true;
+        }
+        let percent = format!("{}%", (volume * 100.0).round() as u32);
+        run("amixer", &["set", "Master", &percent]).is_some()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos_core_audio {
+    use std::ffi::c_void;
+
+    type AudioObjectId = u32;
+    type OsStatus = i32;
+
+    #[repr(C)]
+    struct AudioObjectPropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    const AUDIO_OBJECT_SYSTEM_OBJECT: AudioObjectId = 1;
+    const PROPERTY_DEFAULT_OUTPUT_DEVICE: u32 = u32::from_be_bytes(*b"dOut");
+    const PROPERTY_VIRTUAL_MAIN_VOLUME: u32 = u32::from_be_bytes(*b"vmvc");
+    const SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+    const SCOPE_OUTPUT: u32 = u32::from_be_bytes(*b"outp");
+    const ELEMENT_MAIN: u32 = 0;
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioObjectGetPropertyData(
+            object: AudioObjectId,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> OsStatus;
+    }
+
+    // The virtual main volume spans all channels, like the menu bar slider
+    #[link(name = "AudioToolbox", kind = "framework")]
+    extern "C" {
+        fn AudioHardwareServiceGetPropertyData(
+            object: AudioObjectId,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> OsStatus;
+        fn AudioHardwareServiceSetPropertyData(
+            object: AudioObjectId,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: u32,
+            data: *const c_void,
+        ) -> OsStatus;
+    }
+
+    fn default_output_device() -> Option<AudioObjectId> {
+        let address = AudioObjectPropertyAddress {
+            selector: PROPERTY_DEFAULT_OUTPUT_DEVICE,
+            scope: SCOPE_GLOBAL,
+            element: ELEMENT_MAIN,
+        };
+        let mut device: AudioObjectId = 0;
+        let mut size = std::mem::size_of::<AudioObjectId>() as u32;
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                AUDIO_OBJECT_SYSTEM_OBJECT,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut device as *mut AudioObjectId as *mut c_void,
+            )
+        };
+        (status == 0 && device != 0).then_some(device)
+    }
+
+    fn main_volume_address() -> AudioObjectPropertyAddress {
+        AudioObjectPropertyAddress {
+            selector: PROPERTY_VIRTUAL_MAIN_VOLUME,
+            scope: SCOPE_OUTPUT,
+            element: ELEMENT_MAIN,
+        }
+    }
+
+    pub fn output_volume() -> Option<f32> {
+        let device = default_output_device()?;
+        let mut volume: f32 = 0.0;
+        let mut size = std::mem::size_of::<f32>() as u32;
+        let status = unsafe {
+            AudioHardwareServiceGetPropertyData(
+                device,
+                &main_volume_address(),
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut volume as *mut f32 as *mut c_void,
+            )
+        };
+        (status == 0).then_some(volume)
+    }
+
+    pub fn set_output_volume(volume: f32) -> bool {
+        let Some(device) = default_output_device() else {
+            return false;
+        };
+        let status = unsafe {
+            AudioHardwareServiceSetPropertyData(
+                device,
+                &main_volume_address(),
+                0,
+                std::ptr::null(),
+                std::mem::size_of::<f32>() as u32,
+                &volume as *const f32 as *const c_void,
+            )
+        };
+        status == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::linux_mixer::*;
+    use super::*;
+
+    #[test]
+    fn ducks_relative_to_current_volume() {
+        assert_eq!(ducked_volume(0.5, 20), 0.1);
+        assert_eq!(ducked_volume(0.8, 100), 0.8);
+        assert_eq!(ducked_volume(0.8, 250), 0.8);
+        assert_eq!(ducked_volume(0.8, 0), 0.0);
+    }
+
+    #[test]
+    fn parses_mixer_output() {
+        assert_eq!(parse_wpctl_volume("Volume: 0.40\n"), Some(0.40));
+        assert_eq!(parse_wpctl_volume("Volume: 0.25 [MUTED]\n"), Some(0.25));
+        assert_eq!(
+            parse_pactl_volume(
+                "Volume: front-left: 32768 /  50% / -18.06 dB,   front-right: 32768 /  50% / -18.06 dB\n        balance 0.00\n"
+            ),
+            Some(0.5)
+        );
+        assert_eq!(
+            parse_amixer_volume(
+                "Simple mixer control 'Master',0\n  Mono: Playback 26 [40%] [-34.00dB] [on]\n"
+            ),
+            Some(0.4)
+        );
+        assert_eq!(parse_wpctl_volume("garbage"), None);
+    }
+}
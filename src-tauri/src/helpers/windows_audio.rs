@@ -0,0 +1,74 @@
+//! Windows audio endpoints through WASAPI's device enumerator, shared by the
+//! volume, mute and device helpers.
+
+use crate::settings::WindowsDeviceRole;
+use windows::Win32::{
+    Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
+    Media::Audio::{
+        eCapture, eCommunications, eConsole, EDataFlow, ERole, Endpoints::IAudioEndpointVolume,
+        IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator, DEVICE_STATE_ACTIVE,
+    },
+    System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED, STGM_READ},
+};
+
+impl From<WindowsDeviceRole> for ERole {
+    fn from(role: WindowsDeviceRole) -> Self {
+        match role {
+            WindowsDeviceRole::Console => eConsole,
+            WindowsDeviceRole::Communications => eCommunications,
+        }
+    }
+}
+
+/// The endpoint enumerator, with COM initialized on the calling thread
+pub fn enumerator() -> windows::core::Result<IMMDeviceEnumerator> {
+    unsafe {
+        // If already initialized (e.g., by Tauri), this does nothing
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+    }
+}
+
+/// Windows' default endpoint for `flow` in `role`
+pub fn default_endpoint(flow: EDataFlow, role: ERole) -> Option<IMMDevice> {
+    unsafe { enumerator().ok()?.GetDefaultAudioEndpoint(flow, role).ok() }
+}
+
+/// The friendly name, which is also what cpal reports as the device name
+pub fn friendly_name(device: &IMMDevice) -> Option<String> {
+    unsafe {
+        let properties = device.OpenPropertyStore(STGM_READ).ok()?;
+        let name = properties.GetValue(&PKEY_Device_FriendlyName).ok()?;
+        Some(name.to_string()).filter(|name| !name.is_empty())
+    }
+}
+
+/// The active capture endpoint named `device_name`, or the default input for
+/// `role`, which is the one recordings use without a selected microphone
+pub fn capture_endpoint(device_name: Option<&str>, role: WindowsDeviceRole) -> Option<IMMDevice> {
+    let devices = enumerator().ok()?;
+    unsafe {
+        let named = device_name.and_then(|name| {
+            let endpoints = devices
+                .EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE)
+                .ok()?;
+            let count = endpoints.GetCount().ok()?;
+            (0..count)
+                .filter_map(|i| endpoints.Item(i).ok())
+                .find(|device| friendly_name(device).as_deref() == Some(name))
+        });
+        match named {
+            Some(device) => Some(device),
+            None => devices.GetDefaultAudioEndpoint(eCapture, role.into()).ok(),
+        }
+    }
+}
+
+/// Runs `f` against the endpoint's volume control
+pub fn with_volume<T>(
+    device: &IMMDevice,
+    f: impl FnOnce(&IAudioEndpointVolume) -> windows::core::Result<T>,
+) -> Option<T> {
+    let volume = unsafe { device.Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None) }.ok()?;
+    f(&volume).ok()
+}
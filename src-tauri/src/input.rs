@@ -112,6 +112,49 @@ pub fn send_paste_shift_insert(enigo: &mut Enigo) -> Result<(), String> {
     Ok(())
 }
 
+/// Deletes `count` characters before the caret, one backspace each.
+pub fn send_backspaces(enigo: &mut Enigo, count: usize) -> Result<(), String> {
+    for _ in 0..count {
+        enigo
+            .key(Key::Backspace, enigo::Direction::Click)
+            .map_err(|e| format!("Failed to click Backspace key: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Whether Shift, Control, Alt/Option, Command/Windows or Fn is physically held
+/// down, None where the platform can't tell.
+pub fn modifier_keys_held() -> Option<bool> {
+    #[cfg(target_os = "macos")]
+    {
+        #[link(name = "CoreGraphics", kind = "framework")]
+        extern "C" {
+            fn CGEventSourceFlagsState(state_id: i32) -> u64;
+        }
+        const HID_SYSTEM_STATE: i32 = 1;
+        // Shift, Control, Option, Command and Fn, which turns Backspace into Delete
+        const MODIFIER_FLAGS: u64 =
+            0x0002_0000 | 0x0004_0000 | 0x0008_0000 | 0x0010_0000 | 0x0080_0000;
+        let flags = unsafe { CGEventSourceFlagsState(HID_SYSTEM_STATE) };
+        Some(flags & MODIFIER_FLAGS != 0)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::Input::KeyboardAndMouse::{
+            GetAsyncKeyState, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+        };
+        // The high bit, which makes the state negative, is set while the key is down
+        let held = [VK_SHIFT, VK_CONTROL, VK_MENU, VK_LWIN, VK_RWIN]
+            .iter()
+            .any(|key| unsafe { GetAsyncKeyState(key.0 as i32) } < 0);
+        Some(held)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
 /// Pastes text directly using the enigo text method.
 /// This tries to use system input methods if possible, otherwise simulates keystrokes one by one.
 pub fn paste_text_direct(enigo: &mut Enigo, text: &str) -> Result<(), String> {
@@ -121,3 +164,234 @@ pub fn paste_text_direct(enigo: &mut Enigo, text: &str) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Outcome of inserting text through the platform accessibility API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessibilityInsert {
+    Inserted,
+    /// The focused element is a password field; nothing was inserted
+    SecureField,
+    /// The focused element can't take direct insertion, with the reason why
+    Unsupported(String),
+}
+
+/// Inserts text into the focused field through the accessibility API (AXUIElement on
+/// macOS, UI Automation on Windows) instead of simulating keys, so keyboard layouts
+/// and concurrent typing can't garble it. Password fields are detected and refused.
+pub fn insert_text_accessibility(text: &str) -> AccessibilityInsert {
+    #[cfg(target_os = "macos")]
+    {
+        macos_ax::insert_text(text)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows_uia::insert_text(text)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = text;
+        AccessibilityInsert::Unsupported("not available on this platform".to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos_ax {
+    use super::AccessibilityInsert;
+    use std::ffi::{c_char, c_void, CStr};
+
+    type CFTypeRef = *const c_void;
+    type AXError = i32;
+
+    const AX_ERROR_SUCCESS: AXError = 0;
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXUIElementCreateSystemWide() -> CFTypeRef;
+        fn AXUIElementCopyAttributeValue(
+            element: CFTypeRef,
+            attribute: CFTypeRef,
+            value: *mut CFTypeRef,
+        ) -> AXError;
+        fn AXUIElementIsAttributeSettable(
+            element: CFTypeRef,
+            attribute: CFTypeRef,
+            settable: *mut u8,
+        ) -> AXError;
+        fn AXUIElementSetAttributeValue(
+            element: CFTypeRef,
+            attribute: CFTypeRef,
+            value: CFTypeRef,
+        ) -> AXError;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithBytes(
+            alloc: CFTypeRef,
+            bytes: *const u8,
+            num_bytes: isize,
+            encoding: u32,
+            is_external_representation: u8,
+        ) -> CFTypeRef;
+        fn CFStringGetCString(
+            string: CFTypeRef,
+            buffer: *mut c_char,
+            buffer_size: isize,
+            encoding: u32,
+        ) -> u8;
+        fn CFGetTypeID(cf: CFTypeRef) -> usize;
+        fn CFStringGetTypeID() -> usize;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    /// Owned Core Foundation reference, released on drop
+    struct Cf(CFTypeRef);
+
+    impl Cf {
+        fn string(s: &str) -> Option<Self> {
+            let string = unsafe {
+                CFStringCreateWithBytes(
+                    std::ptr::null(),
+                    s.as_ptr(),
+                    s.len() as isize,
+                    CF_STRING_ENCODING_UTF8,
+                    0,
+                )
+            };
+            (!string.is_null()).then_some(Cf(string))
+        }
+
+        /// Reads a short CFString value such as a role name
+        fn as_string(&self) -> Option<String> {
+            if unsafe { CFGetTypeID(self.0) != CFStringGetTypeID() } {
+                return None;
+            }
+            let mut buffer = [0 as c_char; 128];
+            let ok = unsafe {
+                CFStringGetCString(
+                    self.0,
+                    buffer.as_mut_ptr(),
+                    buffer.len() as isize,
+                    CF_STRING_ENCODING_UTF8,
+                )
+            };
+            if ok == 0 {
+                return None;
+            }
+            let value = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+            Some(value.to_string_lossy().into_owned())
+        }
+    }
+
+    impl Drop for Cf {
+        fn drop(&mut self) {
+            unsafe { CFRelease(self.0) }
+        }
+    }
+
+    fn attribute(element: &Cf, name: &str) -> Option<Cf> {
+        let name = Cf::string(name)?;
+        let mut value: CFTypeRef = std::ptr::null();
+        let err = unsafe { AXUIElementCopyAttributeValue(element.0, name.0, &mut value) };
+        (err == AX_ERROR_SUCCESS && !value.is_null()).then_some(Cf(value))
+    }
+
+    pub fn insert_text(text: &str) -> AccessibilityInsert {
+        let system = Cf(unsafe { AXUIElementCreateSystemWide() });
+        let Some(focused) = attribute(&system, "AXFocusedUIElement") else {
+            return AccessibilityInsert::Unsupported("no focused element".to_string());
+        };
+
+        let role = attribute(&focused, "AXRole").and_then(|r| r.as_string());
+        let subrole = attribute(&focused, "AXSubrole").and_then(|r| r.as_string());
+        if role.as_deref() == Some("AXSecureTextField")
+            || subrole.as_deref() == Some("AXSecureTextField")
+        {
+            return AccessibilityInsert::SecureField;
+        }
+        let role = role.unwrap_or_else(|| "focused element".to_string());
+
+        // Setting the selected text replaces the selection, or inserts at the caret
+        let (Some(selected_text), Some(value)) = (Cf::string("AXSelectedText"), Cf::string(text))
+        else {
+            return AccessibilityInsert::Unsupported("failed to create CFString".to_string());
+        };
+        let mut settable = 0u8;
+        let err =
+            unsafe { AXUIElementIsAttributeSettable(focused.0, selected_text.0, &mut settable) };
+        if err != AX_ERROR_SUCCESS || settable == 0 {
+            return AccessibilityInsert::Unsupported(format!(
+                "{} does not accept text insertion",
+                role
+            ));
+        }
+
+        let err = unsafe { AXUIElementSetAttributeValue(focused.0, selected_text.0, value.0) };
+        if err == AX_ERROR_SUCCESS {
+            AccessibilityInsert::Inserted
+        } else {
+            AccessibilityInsert::Unsupported(format!(
+                "{} rejected the text (AXError {})",
+                role, err
+            ))
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_uia {
+    use super::AccessibilityInsert;
+    use windows::core::BSTR;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+    };
+    use windows::Win32::UI::Accessibility::{
+        CUIAutomation, IUIAutomation, IUIAutomationValuePattern, UIA_ValuePatternId,
+    };
+
+    pub fn insert_text(text: &str) -> AccessibilityInsert {
+        match try_insert_text(text) {
+            Ok(result) => result,
+            Err(e) => AccessibilityInsert::Unsupported(format!("UI Automation error: {}", e)),
+        }
+    }
+
+    fn try_insert_text(text: &str) -> windows::core::Result<AccessibilityInsert> {
+        unsafe {
+            // The webview already initialized COM on this thread; if so this does nothing
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+            let automation: IUIAutomation =
+                CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER)?;
+            let element = automation.GetFocusedElement()?;
+            if element.CurrentIsPassword()?.as_bool() {
+                return Ok(AccessibilityInsert::SecureField);
+            }
+
+            // TextPattern is read-only, so ValuePattern is the only way to write text
+            let Ok(pattern) =
+                element.GetCurrentPatternAs::<IUIAutomationValuePattern>(UIA_ValuePatternId)
+            else {
+                return Ok(AccessibilityInsert::Unsupported(
+                    "focused element has no value pattern".to_string(),
+                ));
+            };
+            if pattern.CurrentIsReadOnly()?.as_bool() {
+                return Ok(AccessibilityInsert::Unsupported(
+                    "focused element is read-only".to_string(),
+                ));
+            }
+            // ValuePattern replaces the whole value, which only equals inserting at
+            // the caret when the field is empty
+            if !pattern.CurrentValue()?.is_empty() {
+                return Ok(AccessibilityInsert::Unsupported(
+                    "UI Automation can't insert at the caret of a non-empty field".to_string(),
+                ));
+            }
+
+            pattern.SetValue(&BSTR::from(text))?;
+            Ok(AccessibilityInsert::Inserted)
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Language of a transcript, for the `${language}` and `${language_name}` prompt
+//! tokens.
+//!
+//! The transcription engines don't report the language they recognized, so with
+//! `auto` it is detected from the transcript text. Short or mixed transcripts can't
+//! be detected reliably; the language is unknown then.
+
+use crate::settings::AppSettings;
+use whatlang::Lang;
+
+/// Below this detector confidence the language is treated as unknown
+const MIN_DETECTION_CONFIDENCE: f64 = 0.5;
+
+/// ISO 639-1 codes as used by `selected_language`, with their English names
+const LANGUAGES: &[(&str, Lang, &str)] = &[
+    ("af", Lang::Afr, "Afrikaans"),
+    ("am", Lang::Amh, "Amharic"),
+    ("ar", Lang::Ara, "Arabic"),
+    ("az", Lang::Aze, "Azerbaijani"),
+    ("be", Lang::Bel, "Belarusian"),
+    ("bg", Lang::Bul, "Bulgarian"),
+    ("bn", Lang::Ben, "Bengali"),
+    ("ca", Lang::Cat, "Catalan"),
+    ("cs", Lang::Ces, "Czech"),
+    ("da", Lang::Dan, "Danish"),
+    ("de", Lang::Deu, "German"),
+    ("el", Lang::Ell, "Greek"),
+    ("en", Lang::Eng, "English"),
+    ("eo", Lang::Epo, "Esperanto"),
+    ("es", Lang::Spa, "Spanish"),
+    ("et", Lang::Est, "Estonian"),
+    ("fa", Lang::Pes, "Persian"),
+    ("fi", Lang::Fin, "Finnish"),
+    ("fr", Lang::Fra, "French"),
+    ("gu", Lang::Guj, "Gujarati"),
+    ("he", Lang::Heb, "Hebrew"),
+    ("hi", Lang::Hin, "Hindi"),
+    ("hr", Lang::Hrv, "Croatian"),
+    ("hu", Lang::Hun, "Hungarian"),
+    ("hy", Lang::Hye, "Armenian"),
+    ("id", Lang::Ind, "Indonesian"),
+    ("it", Lang::Ita, "Italian"),
+    ("ja", Lang::Jpn, "Japanese"),
+    ("jw", Lang::Jav, "Javanese"),
+    ("ka", Lang::Kat, "Georgian"),
+    ("km", Lang::Khm, "Khmer"),
+    ("kn", Lang::Kan, "Kannada"),
+    ("ko", Lang::Kor, "Korean"),
+    ("la", Lang::Lat, "Latin"),
+    ("lt", Lang::Lit, "Lithuanian"),
+    ("lv", Lang::Lav, "Latvian"),
+    ("mk", Lang::Mkd, "Macedonian"),
+    ("ml", Lang::Mal, "Malayalam"),
+    ("mr", Lang::Mar, "Marathi"),
+    ("my", Lang::Mya, "Burmese"),
+    ("ne", Lang::Nep, "Nepali"),
+    ("nl", Lang::Nld, "Dutch"),
+    ("no", Lang::Nob, "Norwegian"),
+    ("or", Lang::Ori, "Odia"),
+    ("pa", Lang::Pan, "Punjabi"),
+    ("pl", Lang::Pol, "Polish"),
+    ("pt", Lang::Por, "Portuguese"),
+    ("ro", Lang::Ron, "Romanian"),
+    ("ru", Lang::Rus, "Russian"),
+    ("si", Lang::Sin, "Sinhala"),
+    ("sk", Lang::Slk, "Slovak"),
+    ("sl", Lang::Slv, "Slovenian"),
+    ("sn", Lang::Sna, "Shona"),
+    ("sr", Lang::Srp, "Serbian"),
+    ("sv", Lang::Swe, "Swedish"),
+    ("ta", Lang::Tam, "Tamil"),
+    ("te", Lang::Tel, "Telugu"),
+    ("th", Lang::Tha, "Thai"),
+    ("tk", Lang::Tuk, "Turkmen"),
+    ("tl", Lang::Tgl, "Tagalog"),
+    ("tr", Lang::Tur, "Turkish"),
+    ("uk", Lang::Ukr, "Ukrainian"),
+    ("ur", Lang::Urd, "Urdu"),
+    ("uz", Lang::Uzb, "Uzbek"),
+    ("vi", Lang::Vie, "Vietnamese"),
+    ("yi", Lang::Yid, "Yiddish"),
+    ("zh", Lang::Cmn, "Chinese"),
+    ("zu", Lang::Zul, "Zulu"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptLanguage {
+    /// As in `selected_language`, e.g. `de` or `zh-Hans`
+    pub code: String,
+    /// English name, the code itself for languages without a known name
+    pub name: String,
+}
+
+impl TranscriptLanguage {
+    fn from_code(code: &str) -> Self {
+        // zh-Hans and zh-Hant are both Chinese
+        let base = code.split('-').next().unwrap_or(code);
+        let name = LANGUAGES
+            .iter()
+            .find(|(c, _, _)| *c == base)
+            .map_or(code, |(_, _, name)| name);
+        Self {
+            code: code.to_string(),
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Language of `text`: the selected one, English when translating, otherwise
+/// detected from the text. None when detection isn't confident.
+pub fn transcript_language(settings: &AppSettings, text: &str) -> Option<TranscriptLanguage> {
+    if settings.translate_to_english {
+        return Some(TranscriptLanguage::from_code("en"));
+    }
+    if settings.selected_language != "auto" {
+        return Some(TranscriptLanguage::from_code(&settings.selected_language));
+    }
+    detect_language(text)
+}
+
+/// Language detected from `text`, None when detection isn't confident
+pub fn detect_language(text: &str) -> Option<TranscriptLanguage> {
+    detect_language_with_confidence(text, MIN_DETECTION_CONFIDENCE)
+}
+
+/// Language detected from `text`, None below `min_confidence`
+pub fn detect_language_with_confidence(
+    text: &str,
+    min_confidence: f64,
+) -> Option<TranscriptLanguage> {
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() || info.confidence() < min_confidence {
+        return None;
+    }
+    LANGUAGES
+        .iter()
+        .find(|(_, lang, _)| *lang == info.lang())
+        .map(|(code, _, _)| TranscriptLanguage::from_code(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    #[test]
+    fn language_comes_from_settings_or_the_text() {
+        let mut settings = get_default_settings();
+        settings.selected_language = "zh-Hans".to_string();
+        let language = transcript_language(&settings, "").unwrap();
+        assert_eq!(language.code, "zh-Hans");
+        assert_eq!(language.name, "Chinese");
+
+        settings.selected_language = "auto".to_string();
+        let german = "Das ist ein ziemlich langer deutscher Satz, der eindeutig erkannt wird.";
+        let language = transcript_language(&settings, german).unwrap();
+        assert_eq!(language.code, "de");
+        assert_eq!(language.name, "German");
+        assert_eq!(transcript_language(&settings, "ok"), None);
+
+        settings.translate_to_english = true;
+        assert_eq!(transcript_language(&settings, german).unwrap().code, "en");
+
+        assert_eq!(TranscriptLanguage::from_code("haw").name, "haw");
+    }
+}
@@ -1,26 +1,67 @@
 mod actions;
+mod app_prompt_rules;
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 mod apple_intelligence;
 mod audio_feedback;
 pub mod audio_toolkit;
+mod benchmark;
+mod chunking;
 mod clipboard;
 mod commands;
+mod compare;
+mod copy_only_session;
+mod custom_words;
+mod deferred_paste;
+mod dictation_metrics;
+mod dictation_session;
+mod early_log;
+mod error;
+mod events;
+mod file_dictation;
+mod formatting;
 mod helpers;
 mod input;
+mod language;
 mod llm_client;
 mod managers;
+mod mic_check;
+mod model_manifest;
+mod notification;
 mod overlay;
+mod paths;
+mod pipeline;
+mod power_profile;
+mod prompt_limits;
+mod prompt_lint;
+mod punctuation;
+mod review;
+mod self_test;
+mod session_log;
 mod settings;
+mod settings_watcher;
 mod shortcut;
 mod signal_handle;
+mod speaker_turns;
+mod system_memory;
+mod transcript_ring;
+mod translation_guard;
 mod tray;
 mod tray_i18n;
+mod undo_paste;
 mod utils;
+mod voice_stop;
+mod vram_guard;
+mod wake_word;
+mod watch_folders;
+mod whisper_decoder;
+mod word_diff;
 #[cfg(debug_assertions)]
 use specta_typescript::{BigIntExportBehavior, Typescript};
 use tauri_specta::{collect_commands, Builder};
 
 use env_filter::Builder as EnvFilterBuilder;
+use error::HandyError;
+use events::{emit_event, AppEvent};
 use managers::audio::AudioRecordingManager;
 use managers::history::HistoryManager;
 use managers::model::ModelManager;
@@ -30,12 +71,12 @@ use signal_hook::consts::SIGUSR2;
 #[cfg(unix)]
 use signal_hook::iterator::Signals;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::image::Image;
 
 use tauri::tray::TrayIconBuilder;
-use tauri::Emitter;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
 use tauri_plugin_log::{Builder as LogBuilder, RotationStrategy, Target, TargetKind};
@@ -46,6 +87,9 @@ use crate::settings::get_settings;
 // We use u8 to store the log::LevelFilter as a number
 pub static FILE_LOG_LEVEL: AtomicU8 = AtomicU8::new(log::LevelFilter::Debug as u8);
 
+/// Name of the active log file, without the `.log` extension
+pub const LOG_FILE_NAME: &str = "handy";
+
 fn level_filter_from_u8(value: u8) -> log::LevelFilter {
     match value {
         0 => log::LevelFilter::Off,
@@ -80,6 +124,49 @@ fn build_console_filter() -> env_filter::Filter {
     builder.build()
 }
 
+/// Console and file logging. File logs rotate per the user's settings. The logger
+/// is returned separately so it can take over from `early_log`.
+fn log_plugin(
+    app: &AppHandle,
+    settings: &settings::AppSettings,
+    log_dir: PathBuf,
+    console_filter: env_filter::Filter,
+) -> Result<
+    (
+        tauri::plugin::TauriPlugin<tauri::Wry>,
+        log::LevelFilter,
+        Box<dyn log::Log>,
+    ),
+    tauri_plugin_log::Error,
+> {
+    let rotation = match settings.log_max_files {
+        0 => RotationStrategy::KeepAll,
+        1 => RotationStrategy::KeepOne,
+        files => RotationStrategy::KeepSome(files as usize),
+    };
+
+    LogBuilder::new()
+        .level(log::LevelFilter::Trace) // Set to most verbose level globally
+        .max_file_size(settings.log_max_file_size_kb as u128 * 1000)
+        .rotation_strategy(rotation)
+        .clear_targets()
+        .targets([
+            // Console output respects RUST_LOG environment variable
+            Target::new(TargetKind::Stdout)
+                .filter(move |metadata| console_filter.enabled(metadata)),
+            // File logs respect the user's settings (stored in FILE_LOG_LEVEL atomic)
+            Target::new(TargetKind::Folder {
+                path: log_dir,
+                file_name: Some(LOG_FILE_NAME.into()),
+            })
+            .filter(|metadata| {
+                let file_level = FILE_LOG_LEVEL.load(Ordering::Relaxed);
+                metadata.level() <= level_filter_from_u8(file_level)
+            }),
+        ])
+        .split(app)
+}
+
 #[derive(Default)]
 struct ShortcutToggleStates {
     // Map: shortcut_binding_id -> is_active
@@ -134,10 +221,34 @@ fn initialize_core_logic(app_handle: &AppHandle) {
     app_handle.manage(model_manager.clone());
     app_handle.manage(transcription_manager.clone());
     app_handle.manage(history_manager.clone());
+    app_handle.manage(transcript_ring::TranscriptRing::load(
+        app_handle,
+        &settings::get_settings(app_handle),
+    ));
+    app_handle.manage(session_log::SessionLog::load(
+        app_handle,
+        &settings::get_settings(app_handle),
+    ));
 
     // Initialize the shortcuts
     shortcut::init_shortcuts(app_handle);
 
+    // Follow output device changes for feedback sounds
+    audio_feedback::start_output_device_watcher(app_handle);
+
+    // Transcribe recordings dropped into watch folders
+    app_handle.manage(watch_folders::WatchFolders::new(app_handle));
+    watch_folders::restart(app_handle);
+
+    // Listen for the wake phrase if it's enabled
+    wake_word::restart(app_handle);
+
+    // Follow the power source for battery and plugged-in model settings
+    power_profile::start(app_handle);
+
+    // Pick up a newer model catalog if one is configured
+    model_manifest::refresh_in_background(app_handle, false);
+
     #[cfg(unix)]
     let signals = Signals::new(&[SIGUSR2]).unwrap();
     // Set up SIGUSR2 signal handler for toggling transcription
@@ -178,7 +289,7 @@ fn initialize_core_logic(app_handle: &AppHandle) {
                 let settings = settings::get_settings(app);
                 if settings.update_checks_enabled {
                     show_main_window(app);
-                    let _ = app.emit("check-for-updates", ());
+                    emit_event(app, AppEvent::CheckForUpdates);
                 }
             }
             "copy_last_transcript" => {
@@ -190,9 +301,21 @@ fn initialize_core_logic(app_handle: &AppHandle) {
                 // Use centralized cancellation that handles all operations
                 cancel_current_operation(app);
             }
+            "load_model" => {
+                tray::load_model_from_menu(app);
+            }
+            "unload_model" => {
+                tray::unload_model_from_menu(app);
+            }
+            "copy_only_session" => {
+                copy_only_session::toggle(app);
+            }
             "quit" => {
                 app.exit(0);
             }
+            id if id.starts_with(tray::PROMPT_MENU_ID_PREFIX) => {
+                tray::select_prompt_from_menu(app, id);
+            }
             _ => {}
         })
         .build(app_handle)
@@ -201,11 +324,20 @@ fn initialize_core_logic(app_handle: &AppHandle) {
 
     // Initialize tray menu with idle state
     utils::update_tray_menu(app_handle, &utils::TrayIconState::Idle, None);
+    tray::follow_model_state(app_handle);
+
+    // Apply edits made to the settings file while the app is running
+    settings_watcher::restart(app_handle);
 
     // Get the autostart manager and configure based on user setting
     let autostart_manager = app_handle.autolaunch();
     let settings = settings::get_settings(&app_handle);
 
+    // Surface post-processing problems now rather than at the next dictation
+    if let Some(misconfiguration) = settings.post_process_misconfiguration() {
+        settings::report_post_process_misconfiguration(app_handle, misconfiguration);
+    }
+
     if settings.autostart_enabled {
         // Enable autostart if user has opted in
         let _ = autostart_manager.enable();
@@ -220,18 +352,20 @@ fn initialize_core_logic(app_handle: &AppHandle) {
 
 #[tauri::command]
 #[specta::specta]
-fn trigger_update_check(app: AppHandle) -> Result<(), String> {
+fn trigger_update_check(app: AppHandle) -> Result<(), HandyError> {
     let settings = settings::get_settings(&app);
     if !settings.update_checks_enabled {
         return Ok(());
     }
-    app.emit("check-for-updates", ())
-        .map_err(|e| e.to_string())?;
+    emit_event(&app, AppEvent::CheckForUpdates);
     Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Buffer anything logged before the log plugin is set up
+    early_log::install();
+
     // Parse console logging directives from RUST_LOG, falling back to info-level logging
     // when the variable is unset
     let console_filter = build_console_filter();
@@ -240,70 +374,167 @@ pub fn run() {
         shortcut::change_binding,
         shortcut::reset_binding,
         shortcut::change_ptt_setting,
+        shortcut::change_suppress_keys_during_ptt_setting,
         shortcut::change_audio_feedback_setting,
         shortcut::change_audio_feedback_volume_setting,
+        shortcut::change_feedback_timing_setting,
+        shortcut::change_dictation_session_pause_setting,
+        shortcut::change_overlap_transcription_setting,
+        shortcut::change_voice_stop_setting,
+        shortcut::change_feedback_mode_setting,
+        shortcut::change_binding_feedback_mode_setting,
+        shortcut::change_binding_match_mode,
+        shortcut::get_binding_display_label,
+        shortcut::change_haptic_feedback_setting,
+        shortcut::shortcut_test_mode,
         shortcut::change_sound_theme_setting,
         shortcut::change_start_hidden_setting,
         shortcut::change_autostart_setting,
         shortcut::change_translate_to_english_setting,
+        shortcut::change_translation_guard_setting,
         shortcut::change_selected_language_setting,
+        shortcut::change_localize_punctuation_setting,
         shortcut::change_overlay_position_setting,
+        shortcut::change_overlay_interactive_setting,
         shortcut::change_debug_mode_setting,
         shortcut::change_word_correction_threshold_setting,
+        shortcut::change_correction_algorithm_setting,
+        shortcut::change_beam_size_setting,
+        shortcut::change_decoding_params,
+        shortcut::reset_decoding_params,
+        shortcut::change_anti_hallucination_setting,
+        shortcut::change_hallucination_max_repeats_setting,
+        shortcut::change_chunk_seconds_setting,
+        shortcut::change_transcription_retry_count_setting,
+        shortcut::change_speaker_change_hints_setting,
+        shortcut::change_discard_blank_transcriptions_setting,
+        shortcut::update_discarded_phrases,
         shortcut::change_paste_method_setting,
         shortcut::change_clipboard_handling_setting,
+        shortcut::set_app_output_transform,
+        shortcut::change_max_transcript_chars_setting,
+        shortcut::change_truncation_strategy_setting,
         shortcut::change_post_process_enabled_setting,
+        shortcut::change_offline_mode_setting,
+        shortcut::change_llm_user_agent_setting,
         shortcut::change_experimental_enabled_setting,
+        shortcut::change_model_manifest_url_setting,
         shortcut::change_post_process_base_url_setting,
+        shortcut::change_post_process_auth_style_setting,
+        shortcut::change_post_process_extra_headers_setting,
+        shortcut::change_post_process_extra_query_params_setting,
+        shortcut::change_post_process_openai_scope_setting,
+        shortcut::change_post_process_chat_path_setting,
+        shortcut::change_post_process_api_version_setting,
         shortcut::change_post_process_api_key_setting,
+        shortcut::change_post_process_rate_limit_setting,
         shortcut::change_post_process_model_setting,
         shortcut::set_post_process_provider,
         shortcut::fetch_post_process_models,
         shortcut::add_post_process_prompt,
         shortcut::update_post_process_prompt,
+        shortcut::change_post_process_prompt_output_setting,
+        shortcut::add_app_prompt_rule,
+        shortcut::add_app_prompt_rule_for_current_app,
+        shortcut::update_app_prompt_rule,
+        shortcut::delete_app_prompt_rule,
+        shortcut::reorder_app_prompt_rules,
+        shortcut::add_watch_folder,
+        shortcut::update_watch_folder,
+        shortcut::delete_watch_folder,
+        shortcut::duplicate_post_process_prompt,
+        shortcut::reset_prompt_to_default,
         shortcut::delete_post_process_prompt,
         shortcut::set_post_process_selected_prompt,
+        shortcut::change_post_process_prompt_short_label,
+        shortcut::change_post_process_prompt_seed,
+        shortcut::change_post_process_default_prompt_setting,
         shortcut::update_custom_words,
+        shortcut::update_language_custom_words,
         shortcut::suspend_binding,
         shortcut::resume_binding,
         shortcut::change_mute_while_recording_setting,
+        shortcut::change_duck_audio_while_recording_setting,
+        shortcut::change_duck_level_setting,
+        shortcut::change_remember_mic_per_environment_setting,
         shortcut::change_append_trailing_space_setting,
+        shortcut::change_bidi_isolation_setting,
+        shortcut::change_notify_on_complete_setting,
+        shortcut::change_prompt_hardening_setting,
+        shortcut::change_transcript_ring_setting,
+        shortcut::change_session_log_setting,
+        shortcut::change_review_before_paste_setting,
+        shortcut::change_defer_paste_on_focus_change_setting,
+        shortcut::change_binding_review_setting,
+        shortcut::change_collapse_to_single_line_setting,
+        shortcut::change_binding_single_line_setting,
+        shortcut::change_binding_model_setting,
+        shortcut::change_review_timeout_setting,
         shortcut::change_app_language_setting,
         shortcut::change_update_checks_setting,
+        shortcut::change_watch_settings_file_setting,
         shortcut::change_keyboard_implementation_setting,
         shortcut::get_keyboard_implementation,
         shortcut::handy_keys::start_handy_keys_recording,
         shortcut::handy_keys::stop_handy_keys_recording,
         trigger_update_check,
         commands::cancel_operation,
+        commands::stop_recording,
         commands::get_app_dir_path,
+        commands::get_app_paths,
         commands::get_app_settings,
+        commands::get_effective_settings,
         commands::get_default_settings,
         commands::get_log_dir_path,
         commands::set_log_level,
+        commands::change_log_rotation_setting,
+        commands::clear_logs,
+        commands::get_recent_logs,
         commands::open_recordings_folder,
         commands::open_log_dir,
         commands::open_app_data_dir,
         commands::check_apple_intelligence_available,
         commands::initialize_enigo,
         commands::test_post_process,
+        commands::get_llm_queue_status,
+        commands::get_post_process_misconfiguration,
+        commands::run_self_test,
+        commands::lint_prompt,
+        commands::render_prompt,
+        commands::change_prompt_limits_setting,
         commands::models::get_available_models,
+        commands::models::list_available_models,
         commands::models::get_model_info,
+        commands::models::load_model,
+        commands::models::unload_model,
+        commands::models::get_model_state,
+        commands::models::change_power_profiles_setting,
+        commands::models::set_power_profile,
         commands::models::download_model,
         commands::models::delete_model,
+        commands::models::list_model_variants,
+        commands::models::set_model_quantization,
         commands::models::cancel_download,
         commands::models::set_active_model,
+        commands::models::get_system_memory,
         commands::models::get_current_model,
         commands::models::get_transcription_model_status,
         commands::models::is_model_loading,
         commands::models::has_any_models_available,
         commands::models::has_any_models_or_downloads,
         commands::models::get_recommended_first_model,
+        commands::models::benchmark_model,
         commands::audio::update_microphone_mode,
+        commands::audio::change_pre_roll_setting,
+        commands::audio::change_agc_enabled_setting,
+        commands::audio::change_agc_target_rms_setting,
         commands::audio::get_microphone_mode,
         commands::audio::get_available_microphones,
+        commands::audio::get_available_microphones_detailed,
+        commands::audio::change_mic_channels,
         commands::audio::set_selected_microphone,
         commands::audio::get_selected_microphone,
+        commands::audio::set_windows_default_device_role,
         commands::audio::get_available_output_devices,
         commands::audio::set_selected_output_device,
         commands::audio::get_selected_output_device,
@@ -312,17 +543,56 @@ pub fn run() {
         commands::audio::set_clamshell_microphone,
         commands::audio::get_clamshell_microphone,
         commands::audio::is_recording,
+        commands::audio::get_capture_state,
+        commands::audio::get_wake_word_models,
+        commands::audio::change_wake_word_setting,
+        commands::audio::test_wake_word,
+        commands::audio::get_mic_check_sentence,
+        commands::audio::analyze_microphone,
+        commands::audio::unmute_microphone,
         commands::transcription::set_model_unload_timeout,
+        commands::transcription::set_vram_guard_behavior,
+        commands::transcription::set_cancel_behavior,
         commands::transcription::get_model_load_status,
         commands::transcription::unload_model_manually,
+        commands::transcription::start_file_dictation,
+        commands::transcription::stop_file_dictation,
+        commands::transcription::confirm_transcript,
+        commands::transcription::discard_transcript,
+        commands::transcription::get_pending_transcript_reviews,
+        commands::transcription::compare_transcriptions,
+        commands::transcription::cancel_transcription_comparison,
+        commands::transcription::import_custom_words,
+        commands::transcription::export_custom_words,
+        commands::transcription::defer_paste,
+        commands::transcription::flush_deferred_paste,
+        commands::transcription::undo_last_transcription,
+        commands::transcription::get_copy_only_session,
+        commands::transcription::set_copy_only_session,
+        commands::transcription::start_dictation_session,
+        commands::transcription::end_dictation_session,
+        commands::transcription::get_dictation_session,
         commands::history::get_history_entries,
+        commands::history::search_history,
+        commands::history::rebuild_history_index,
+        commands::history::get_transcript_clipboard,
+        commands::history::copy_transcript_clipboard_entry,
         commands::history::toggle_history_entry_saved,
+        commands::history::retranscribe_history_entry,
         commands::history::get_audio_file_path,
         commands::history::delete_history_entry,
         commands::history::update_history_limit,
         commands::history::update_recording_retention_period,
+        commands::history::change_recording_format_setting,
+        commands::history::convert_existing_recordings,
+        commands::history::get_speaking_rate_stats,
+        commands::history::get_session_log,
+        commands::history::clear_session_log,
+        commands::history::get_history_diff,
         helpers::clamshell::is_laptop,
-    ]);
+        utils::get_frontmost_application,
+    ])
+    .typ::<AppEvent>();
 
     #[cfg(debug_assertions)] // <- Only export on non-release builds
     specta_builder
@@ -332,29 +602,7 @@ pub fn run() {
         )
         .expect("Failed to export typescript bindings");
 
-    let builder = tauri::Builder::default().plugin(
-        LogBuilder::new()
-            .level(log::LevelFilter::Trace) // Set to most verbose level globally
-            .max_file_size(500_000)
-            .rotation_strategy(RotationStrategy::KeepOne)
-            .clear_targets()
-            .targets([
-                // Console output respects RUST_LOG environment variable
-                Target::new(TargetKind::Stdout).filter({
-                    let console_filter = console_filter.clone();
-                    move |metadata| console_filter.enabled(metadata)
-                }),
-                // File logs respect the user's settings (stored in FILE_LOG_LEVEL atomic)
-                Target::new(TargetKind::LogDir {
-                    file_name: Some("handy".into()),
-                })
-                .filter(|metadata| {
-                    let file_level = FILE_LOG_LEVEL.load(Ordering::Relaxed);
-                    metadata.level() <= level_filter_from_u8(file_level)
-                }),
-            ])
-            .build(),
-    );
+    let builder = tauri::Builder::default();
 
     #[cfg(target_os = "macos")]
     {
@@ -371,6 +619,7 @@ pub fn run() {
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_macos_permissions::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_autostart::init(
@@ -378,12 +627,42 @@ pub fn run() {
             Some(vec![]),
         ))
         .manage(Mutex::new(ShortcutToggleStates::default()))
+        .manage(file_dictation::FileDictation::default())
+        .manage(review::PendingReviews::default())
+        .manage(app_prompt_rules::ActivePromptRules::default())
+        .manage(pipeline::ActiveOperations::default())
+        .manage(compare::TranscriptionComparison::default())
+        .manage(deferred_paste::DeferredPaste::default())
+        .manage(undo_paste::LastPaste::default())
+        .manage(copy_only_session::CopyOnlySession::default())
+        .manage(wake_word::WakeWord::default())
+        .manage(power_profile::PowerState::default())
+        .manage(dictation_session::DictationSession::default())
+        .manage(voice_stop::VoiceStop::default())
+        .manage(audio_feedback::FeedbackOutput::default())
         .setup(move |app| {
+            let app_paths = paths::get(app.handle());
+            let settings_migration = paths::migrate_settings(app.handle());
             let settings = get_settings(&app.handle());
+            // Registered here rather than on the builder so rotation can follow the settings
+            let (plugin, max_level, logger) = log_plugin(
+                app.handle(),
+                &settings,
+                app_paths.logs.clone(),
+                console_filter,
+            )?;
+            app.handle().plugin(plugin)?;
+            early_log::attach(max_level, logger);
             let tauri_log_level: tauri_plugin_log::LogLevel = settings.log_level.into();
             let file_log_level: log::Level = tauri_log_level.into();
             // Store the file log level in the atomic for the filter to use
             FILE_LOG_LEVEL.store(file_log_level.to_level_filter() as u8, Ordering::Relaxed);
+            llm_client::set_offline_mode(settings.offline_mode);
+            llm_client::set_user_agent(settings.llm_user_agent.clone());
+            if app_paths.portable {
+                log::info!("Running in portable mode from {}", app_paths.data.display());
+            }
+            paths::migrate(app.handle(), settings_migration);
             let app_handle = app.handle().clone();
 
             initialize_core_logic(&app_handle);
@@ -402,6 +681,9 @@ pub fn run() {
             tauri::WindowEvent::CloseRequested { api, .. } => {
                 api.prevent_close();
                 let _res = window.hide();
+                if window.label() == "main" {
+                    shortcut::test_mode::set_enabled(window.app_handle(), false);
+                }
                 #[cfg(target_os = "macos")]
                 {
                     let res = window
@@ -420,6 +702,18 @@ pub fn run() {
             _ => {}
         })
         .invoke_handler(specta_builder.invoke_handler())
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                // Post-processing still in flight would otherwise paste after quitting
+                if let Some(operations) = app.try_state::<pipeline::ActiveOperations>() {
+                    operations.cancel_all();
+                }
+                // The volume ducking replaced is only kept in memory
+                if let Some(rm) = app.try_state::<Arc<AudioRecordingManager>>() {
+                    rm.remove_mute();
+                }
+            }
+        });
 }
@@ -1,12 +1,107 @@
-use crate::audio_toolkit::{list_input_devices, vad::SmoothedVad, AudioRecorder, SileroVad};
-use crate::helpers::clamshell;
-use crate::settings::{get_settings, AppSettings};
+use crate::audio_toolkit::{
+    list_input_devices, vad::SmoothedVad, AudioRecorder, ChannelMix, CpalDeviceInfo,
+    DeviceRecovery, FrameTap, SileroVad,
+};
+use crate::events::{emit_event, AppEvent, RecordingDeviceRecovered};
+use crate::helpers::{clamshell, volume};
+use crate::settings::{
+    get_settings, AppSettings, ChannelMixMode, MicChannelConfig, WindowsDeviceRole,
+};
 use crate::utils;
-use log::{debug, error, info};
+use cpal::traits::{DeviceTrait, HostTrait};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use specta::Type;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tauri::Manager;
 
+/// Longest pre-roll buffer that can be configured
+pub const MAX_PRE_ROLL_MS: u32 = 2_000;
+/// Accepted range of the AGC target RMS
+pub const AGC_TARGET_RMS_RANGE: std::ops::RangeInclusive<f32> = 0.01..=0.5;
+
+fn agc_target(settings: &AppSettings) -> Option<f32> {
+    settings.agc_enabled.then_some(settings.agc_target_rms)
+}
+
+/// Downmix for a microphone's channel configuration, see `AppSettings::mic_channel_map`
+pub fn channel_mix(config: Option<&MicChannelConfig>) -> ChannelMix {
+    let Some(config) = config else {
+        return ChannelMix::All;
+    };
+    // Settings count channels from one, the recorder from zero
+    let channels: Vec<usize> = config
+        .channels
+        .iter()
+        .filter_map(|c| c.checked_sub(1))
+        .map(|c| c as usize)
+        .collect();
+    match (config.mode, channels.first()) {
+        (_, None) => ChannelMix::All,
+        (ChannelMixMode::Select, Some(first)) => ChannelMix::Select(*first),
+        (ChannelMixMode::Average, Some(_)) => ChannelMix::Average(channels),
+    }
+}
+
+/// Name of Windows' default input for `role`. cpal only opens the console default,
+/// so the communications default has to be looked up through WASAPI and opened by
+/// name. None where the lookup fails and on other platforms, which have one default.
+pub fn default_input_name_for_role(role: WindowsDeviceRole) -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        use crate::helpers::windows_audio;
+        use windows::Win32::Media::Audio::eCapture;
+
+        let device = windows_audio::default_endpoint(eCapture, role.into())?;
+        windows_audio::friendly_name(&device)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = role;
+        None
+    }
+}
+
+/// Builds a stable fingerprint for the set of currently available input devices.
+/// Order and duplicates are ignored so the same dock always maps to the same key.
+pub fn device_environment_fingerprint<S: AsRef<str>>(names: &[S]) -> String {
+    let mut names: Vec<&str> = names.iter().map(|n| n.as_ref()).collect();
+    names.sort_unstable();
+    names.dedup();
+    names.join("|")
+}
+
+/// Fingerprint of the input devices currently connected to the machine
+pub fn current_device_environment() -> Option<String> {
+    match list_input_devices() {
+        Ok(devices) => {
+            let names: Vec<String> = devices.into_iter().map(|d| d.name).collect();
+            Some(device_environment_fingerprint(&names))
+        }
+        Err(e) => {
+            debug!("Failed to list devices for environment fingerprint: {}", e);
+            None
+        }
+    }
+}
+
+fn remembered_environment_microphone(
+    settings: &AppSettings,
+    devices: &[CpalDeviceInfo],
+) -> Option<String> {
+    if !settings.remember_mic_per_environment {
+        return None;
+    }
+
+    let names: Vec<&str> = devices.iter().map(|d| d.name.as_str()).collect();
+    let fingerprint = device_environment_fingerprint(&names);
+    let remembered = settings.environment_microphones.get(&fingerprint)?;
+    debug!("Using remembered microphone for this device environment: {remembered}");
+    Some(remembered.clone())
+}
+
 fn set_mute(mute: bool) {
     // Expected behavior:
     // - Windows: works on most systems using standard audio drivers.
@@ -17,38 +112,9 @@ fn set_mute(mute: bool) {
 
     #[cfg(target_os = "windows")]
     {
-        unsafe {
-            use windows::Win32::{
-                Media::Audio::{
-                    eMultimedia, eRender, Endpoints::IAudioEndpointVolume, IMMDeviceEnumerator,
-                    MMDeviceEnumerator,
-                },
-                System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED},
-            };
-
-            macro_rules! unwrap_or_return {
-                ($expr:expr) => {
-                    match $expr {
-                        Ok(val) => val,
-                        Err(_) => return,
-                    }
-                };
-            }
-
-            // Initialize the COM library for this thread.
-            // If already initialized (e.g., by another library like Tauri), this does nothing.
-            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
-
-            let all_devices: IMMDeviceEnumerator =
-                unwrap_or_return!(CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL));
-            let default_device =
-                unwrap_or_return!(all_devices.GetDefaultAudioEndpoint(eRender, eMultimedia));
-            let volume_interface = unwrap_or_return!(
-                default_device.Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None)
-            );
-
-            let _ = volume_interface.SetMute(mute, std::ptr::null());
-        }
+        crate::helpers::volume::with_output_volume(|volume| unsafe {
+            volume.SetMute(mute, std::ptr::null())
+        });
     }
 
     #[cfg(target_os = "linux")]
@@ -112,6 +178,26 @@ pub enum MicrophoneMode {
     OnDemand,
 }
 
+/// Diagnostics for the capture pipeline, see `get_capture_state`
+#[derive(Serialize, Debug, Clone, Type)]
+pub struct CaptureState {
+    pub always_on: bool,
+    /// Whether the input stream is currently running
+    pub stream_open: bool,
+    pub recording: bool,
+    pub pre_roll_ms: u32,
+    /// Whether the last recording started on an already running stream
+    pub last_start_warm: Option<bool>,
+    /// Time from the start request until samples were being kept, for the last recording
+    pub last_start_latency_ms: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CaptureStart {
+    warm: bool,
+    latency: Duration,
+}
+
 /* ──────────────────────────────────────────────────────────────── */
 
 fn create_audio_recorder(
@@ -132,6 +218,32 @@ fn create_audio_recorder(
             move |levels| {
                 utils::emit_levels(&app_handle, &levels);
             }
+        })
+        .with_recovery_callback({
+            let app_handle = app_handle.clone();
+            move |recovery| match recovery {
+                DeviceRecovery::Recovered { fallback } => {
+                    warn!("Recording continues after an input device error");
+                    emit_event(
+                        &app_handle,
+                        AppEvent::RecordingDeviceRecovered(RecordingDeviceRecovered { fallback }),
+                    );
+                }
+                DeviceRecovery::Lost => {
+                    error!("Input device lost, stopping the recording");
+                    emit_event(&app_handle, AppEvent::RecordingDeviceLost);
+                    // Stopping waits on the recorder, which is blocked in this callback
+                    let app_handle = app_handle.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = utils::stop_current_recording(&app_handle) {
+                            warn!(
+                                "Failed to stop the recording after losing the device: {}",
+                                e
+                            );
+                        }
+                    });
+                }
+            }
         });
 
     Ok(recorder)
@@ -149,6 +261,11 @@ pub struct AudioRecordingManager {
     is_open: Arc<Mutex<bool>>,
     is_recording: Arc<Mutex<bool>>,
     did_mute: Arc<Mutex<bool>>,
+    /// Output volume replaced by ducking, restored when recording ends
+    ducked_from: Arc<Mutex<Option<f32>>>,
+    last_start: Arc<Mutex<Option<CaptureStart>>>,
+    /// Listener for the audio between recordings, which keeps the stream open
+    frame_tap: Arc<Mutex<Option<FrameTap>>>,
 }
 
 impl AudioRecordingManager {
@@ -171,6 +288,9 @@ impl AudioRecordingManager {
             is_open: Arc::new(Mutex::new(false)),
             is_recording: Arc::new(Mutex::new(false)),
             did_mute: Arc::new(Mutex::new(false)),
+            ducked_from: Arc::new(Mutex::new(None)),
+            last_start: Arc::new(Mutex::new(None)),
+            frame_tap: Arc::new(Mutex::new(None)),
         };
 
         // Always-on?  Open immediately.
@@ -191,28 +311,45 @@ impl AudioRecordingManager {
             false
         };
 
+        let devices = match list_input_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                debug!("Failed to list devices, using default: {}", e);
+                return None;
+            }
+        };
+
         let device_name = if use_clamshell_mic {
-            settings.clamshell_microphone.as_ref().unwrap()
+            settings.clamshell_microphone.clone().unwrap()
+        } else if let Some(remembered) = remembered_environment_microphone(settings, &devices) {
+            // "default" is stored explicitly so an environment can opt back into the system mic
+            if remembered == "default" {
+                return None;
+            }
+            remembered
+        } else if let Some(selected) = settings.selected_microphone.clone() {
+            selected
         } else {
-            settings.selected_microphone.as_ref()?
+            // Without a name the recorder opens the console default
+            default_input_name_for_role(settings.windows_default_device_role)?
         };
 
         // Find the device by name
-        match list_input_devices() {
-            Ok(devices) => devices
-                .into_iter()
-                .find(|d| d.name == *device_name)
-                .map(|d| d.device),
-            Err(e) => {
-                debug!("Failed to list devices, using default: {}", e);
-                None
-            }
-        }
+        devices
+            .into_iter()
+            .find(|d| d.name == device_name)
+            .map(|d| d.device)
+    }
+
+    /// Name of the microphone a recording would use now, None for the system default
+    pub fn effective_microphone_name(&self) -> Option<String> {
+        let settings = get_settings(&self.app_handle);
+        self.get_effective_microphone_device(&settings)?.name().ok()
     }
 
     /* ---------- microphone life-cycle -------------------------------------- */
 
-    /// Applies mute if mute_while_recording is enabled and stream is open
+    /// Applies mute or ducking, per settings, if the stream is open
     pub fn apply_mute(&self) {
         let settings = get_settings(&self.app_handle);
         let mut did_mute_guard = self.did_mute.lock().unwrap();
@@ -222,9 +359,15 @@ impl AudioRecordingManager {
             *did_mute_guard = true;
             debug!("Mute applied");
         }
+        drop(did_mute_guard);
+
+        // Runs after the start sound, by which time the recording may already be over
+        if settings.duck_audio_while_recording && *self.is_recording.lock().unwrap() {
+            self.apply_duck(settings.duck_level);
+        }
     }
 
-    /// Removes mute if it was applied
+    /// Removes mute and ducking if they were applied
     pub fn remove_mute(&self) {
         let mut did_mute_guard = self.did_mute.lock().unwrap();
         if *did_mute_guard {
@@ -232,6 +375,34 @@ impl AudioRecordingManager {
             *did_mute_guard = false;
             debug!("Mute removed");
         }
+        drop(did_mute_guard);
+        self.remove_duck();
+    }
+
+    fn apply_duck(&self, level_percent: u32) {
+        let mut ducked_from = self.ducked_from.lock().unwrap();
+        if ducked_from.is_some() {
+            return;
+        }
+        let Some(current) = volume::output_volume() else {
+            debug!("Output volume unavailable, not ducking");
+            return;
+        };
+        if volume::set_output_volume(volume::ducked_volume(current, level_percent)) {
+            *ducked_from = Some(current);
+            debug!("Output ducked from {current} to {level_percent}%");
+        }
+    }
+
+    fn remove_duck(&self) {
+        let Some(previous) = self.ducked_from.lock().unwrap().take() else {
+            return;
+        };
+        if volume::set_output_volume(previous) {
+            debug!("Output volume restored to {previous}");
+        } else {
+            warn!("Failed to restore output volume to {previous}");
+        }
     }
 
     pub fn start_microphone_stream(&self) -> Result<(), anyhow::Error> {
@@ -268,7 +439,23 @@ impl AudioRecordingManager {
         let settings = get_settings(&self.app_handle);
         let selected_device = self.get_effective_microphone_device(&settings);
 
+        let device_name = match &selected_device {
+            Some(device) => device.name().ok(),
+            None => crate::audio_toolkit::get_cpal_host()
+                .default_input_device()
+                .and_then(|device| device.name().ok()),
+        };
+        let mix = channel_mix(
+            device_name
+                .as_ref()
+                .and_then(|name| settings.mic_channel_map.get(name)),
+        );
+
         if let Some(rec) = recorder_opt.as_mut() {
+            rec.set_pre_roll(self.pre_roll_duration(&settings));
+            rec.set_agc(agc_target(&settings));
+            rec.set_frame_tap(self.frame_tap.lock().unwrap().clone());
+            rec.set_channel_mix(mix);
             rec.open(selected_device)
                 .map_err(|e| anyhow::anyhow!("Failed to open recorder: {}", e))?;
         }
@@ -292,6 +479,8 @@ impl AudioRecordingManager {
             set_mute(false);
         }
         *did_mute_guard = false;
+        drop(did_mute_guard);
+        self.remove_duck();
 
         if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
             // If still recording, stop first.
@@ -306,27 +495,102 @@ impl AudioRecordingManager {
         debug!("Microphone stream stopped");
     }
 
+    /// Whether the stream is open and its capture worker has not died since
+    fn stream_is_live(&self) -> bool {
+        *self.is_open.lock().unwrap()
+            && self
+                .recorder
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|rec| rec.is_alive())
+    }
+
+    /// Whether the stream stays open between recordings
+    fn keeps_stream_open(&self) -> bool {
+        matches!(*self.mode.lock().unwrap(), MicrophoneMode::AlwaysOn)
+            || self.frame_tap.lock().unwrap().is_some()
+    }
+
+    /// Hands the audio between recordings to `tap`, keeping the stream open for it,
+    /// or stops with None. The audio is only passed on, never stored.
+    pub fn set_frame_tap(&self, tap: Option<FrameTap>) -> Result<(), anyhow::Error> {
+        let listening = tap.is_some();
+        *self.frame_tap.lock().unwrap() = tap.clone();
+        if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
+            rec.set_frame_tap(tap);
+        }
+        if listening {
+            self.start_microphone_stream()
+        } else {
+            if !self.keeps_stream_open() && !self.is_recording() {
+                self.stop_microphone_stream();
+            }
+            Ok(())
+        }
+    }
+
+    /// Time since the VAD last heard speech in the current recording, None when
+    /// it hasn't heard any yet or nothing is recording
+    pub fn since_speech(&self) -> Option<Duration> {
+        if !self.is_recording() {
+            return None;
+        }
+        self.recorder
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|rec| rec.since_speech())
+    }
+
+    /// The pre-roll only has audio to keep when the stream runs between recordings
+    fn pre_roll_duration(&self, settings: &AppSettings) -> Duration {
+        let always_on = matches!(*self.mode.lock().unwrap(), MicrophoneMode::AlwaysOn);
+        if !always_on {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(settings.pre_roll_ms.min(MAX_PRE_ROLL_MS) as u64)
+    }
+
+    /// Re-applies the pre-roll setting to the open recorder
+    pub fn update_pre_roll(&self) {
+        let settings = get_settings(&self.app_handle);
+        let duration = self.pre_roll_duration(&settings);
+        if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
+            rec.set_pre_roll(duration);
+        }
+    }
+
+    /// Re-applies the AGC settings to the open recorder
+    pub fn update_agc(&self) {
+        let settings = get_settings(&self.app_handle);
+        if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
+            rec.set_agc(agc_target(&settings));
+        }
+    }
+
     /* ---------- mode switching --------------------------------------------- */
 
     pub fn update_mode(&self, new_mode: MicrophoneMode) -> Result<(), anyhow::Error> {
-        let mode_guard = self.mode.lock().unwrap();
-        let cur_mode = mode_guard.clone();
+        // Switch the mode first so the stream is opened with the new mode's pre-roll,
+        // and so an active recording closes the stream when it stops
+        let cur_mode = std::mem::replace(&mut *self.mode.lock().unwrap(), new_mode.clone());
 
         match (cur_mode, &new_mode) {
             (MicrophoneMode::AlwaysOn, MicrophoneMode::OnDemand) => {
-                if matches!(*self.state.lock().unwrap(), RecordingState::Idle) {
-                    drop(mode_guard);
+                if !self.keeps_stream_open()
+                    && matches!(*self.state.lock().unwrap(), RecordingState::Idle)
+                {
                     self.stop_microphone_stream();
                 }
             }
             (MicrophoneMode::OnDemand, MicrophoneMode::AlwaysOn) => {
-                drop(mode_guard);
                 self.start_microphone_stream()?;
             }
             _ => {}
         }
 
-        *self.mode.lock().unwrap() = new_mode;
+        self.update_pre_roll();
         Ok(())
     }
 
@@ -336,8 +600,16 @@ impl AudioRecordingManager {
         let mut state = self.state.lock().unwrap();
 
         if let RecordingState::Idle = *state {
-            // Ensure microphone is open in on-demand mode
-            if matches!(*self.mode.lock().unwrap(), MicrophoneMode::OnDemand) {
+            let requested_at = Instant::now();
+            let warm = self.stream_is_live();
+
+            if !warm {
+                // An always-on stream should already be running; it only gets here
+                // if opening failed earlier or the device went away
+                if self.keeps_stream_open() {
+                    warn!("Always-on microphone stream is not running, reopening it");
+                    self.stop_microphone_stream();
+                }
                 if let Err(e) = self.start_microphone_stream() {
                     error!("Failed to open microphone stream: {e}");
                     return false;
@@ -346,6 +618,13 @@ impl AudioRecordingManager {
 
             if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
                 if rec.start().is_ok() {
+                    let latency = requested_at.elapsed();
+                    debug!(
+                        "Capture started in {:?} ({} stream)",
+                        latency,
+                        if warm { "warm" } else { "cold" }
+                    );
+                    *self.last_start.lock().unwrap() = Some(CaptureStart { warm, latency });
                     *self.is_recording.lock().unwrap() = true;
                     *state = RecordingState::Recording {
                         binding_id: binding_id.to_string(),
@@ -396,7 +675,7 @@ impl AudioRecordingManager {
                 *self.is_recording.lock().unwrap() = false;
 
                 // In on-demand mode turn the mic off again
-                if matches!(*self.mode.lock().unwrap(), MicrophoneMode::OnDemand) {
+                if !self.keeps_stream_open() {
                     self.stop_microphone_stream();
                 }
 
@@ -414,6 +693,66 @@ impl AudioRecordingManager {
             _ => None,
         }
     }
+
+    /// The audio `binding_id` recorded since it started or was last split, while
+    /// it goes on recording on the same stream. Short parts are padded like
+    /// `stop_recording`'s.
+    pub fn split_recording(&self, binding_id: &str) -> Option<Vec<f32>> {
+        if self.active_binding().as_deref() != Some(binding_id) {
+            return None;
+        }
+        let recorder = self.recorder.lock().unwrap();
+        let mut samples = match recorder.as_ref()?.split() {
+            Ok(samples) => samples,
+            Err(e) => {
+                error!("split() failed: {e}");
+                Vec::new()
+            }
+        };
+        if samples.len() < WHISPER_SAMPLE_RATE && !samples.is_empty() {
+            samples.resize(WHISPER_SAMPLE_RATE * 5 / 4, 0.0);
+        }
+        Some(samples)
+    }
+
+    /// A copy of the last `duration` of the current recording, None when nothing
+    /// is recording
+    pub fn recording_tail(&self, duration: Duration) -> Option<Vec<f32>> {
+        if !self.is_recording() {
+            return None;
+        }
+        let samples = (duration.as_secs_f64() * WHISPER_SAMPLE_RATE as f64) as usize;
+        let recorder = self.recorder.lock().unwrap();
+        match recorder.as_ref()?.tail(samples) {
+            Ok(tail) => Some(tail),
+            Err(e) => {
+                error!("tail() failed: {e}");
+                None
+            }
+        }
+    }
+
+    /// Binding that started the current recording, if one is in progress
+    pub fn active_binding(&self) -> Option<String> {
+        match &*self.state.lock().unwrap() {
+            RecordingState::Recording { binding_id } => Some(binding_id.clone()),
+            RecordingState::Idle => None,
+        }
+    }
+
+    pub fn capture_state(&self) -> CaptureState {
+        let settings = get_settings(&self.app_handle);
+        let last_start = *self.last_start.lock().unwrap();
+        CaptureState {
+            always_on: matches!(*self.mode.lock().unwrap(), MicrophoneMode::AlwaysOn),
+            stream_open: self.stream_is_live(),
+            recording: self.is_recording(),
+            pre_roll_ms: self.pre_roll_duration(&settings).as_millis() as u32,
+            last_start_warm: last_start.map(|s| s.warm),
+            last_start_latency_ms: last_start.map(|s| s.latency.as_millis() as u32),
+        }
+    }
+
     pub fn is_recording(&self) -> bool {
         matches!(
             *self.state.lock().unwrap(),
@@ -421,24 +760,71 @@ impl AudioRecordingManager {
         )
     }
 
-    /// Cancel any ongoing recording without returning audio samples
-    pub fn cancel_recording(&self) {
+    /// Cancel any ongoing recording, returning what was recorded so far. Unlike
+    /// `stop_recording` short recordings aren't padded.
+    pub fn cancel_recording(&self) -> Option<Vec<f32>> {
         let mut state = self.state.lock().unwrap();
 
         if let RecordingState::Recording { .. } = *state {
             *state = RecordingState::Idle;
             drop(state);
 
-            if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
-                let _ = rec.stop(); // Discard the result
-            }
+            let samples = match self.recorder.lock().unwrap().as_ref().map(|rec| rec.stop()) {
+                Some(Ok(samples)) => samples,
+                Some(Err(e)) => {
+                    error!("stop() failed: {e}");
+                    Vec::new()
+                }
+                None => Vec::new(),
+            };
 
             *self.is_recording.lock().unwrap() = false;
+            self.remove_mute();
 
             // In on-demand mode turn the mic off again
-            if matches!(*self.mode.lock().unwrap(), MicrophoneMode::OnDemand) {
+            if !self.keeps_stream_open() {
                 self.stop_microphone_stream();
             }
+            Some(samples)
+        } else {
+            None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_ignores_order_and_duplicates() {
+        let docked = device_environment_fingerprint(&["USB Mic", "MacBook Mic", "USB Mic"]);
+        let reordered = device_environment_fingerprint(&["MacBook Mic", "USB Mic"]);
+        assert_eq!(docked, reordered);
+    }
+
+    #[test]
+    fn fingerprint_changes_with_device_set() {
+        let docked = device_environment_fingerprint(&["USB Mic", "MacBook Mic"]);
+        let undocked = device_environment_fingerprint(&["MacBook Mic"]);
+        assert_ne!(docked, undocked);
+    }
+
+    #[test]
+    fn channel_config_converts_to_zero_based_mix() {
+        let config = |channels: Vec<u32>, mode| MicChannelConfig { channels, mode };
+        assert_eq!(channel_mix(None), ChannelMix::All);
+        assert_eq!(
+            channel_mix(Some(&config(vec![2, 3], ChannelMixMode::Select))),
+            ChannelMix::Select(1)
+        );
+        assert_eq!(
+            channel_mix(Some(&config(vec![1, 2], ChannelMixMode::Average))),
+            ChannelMix::Average(vec![0, 1])
+        );
+        assert_eq!(
+            channel_mix(Some(&config(vec![0], ChannelMixMode::Average))),
+            ChannelMix::All
+        );
+    }
+}
@@ -1,15 +1,66 @@
 use anyhow::Result;
 use chrono::{DateTime, Local, Utc};
-use log::{debug, error, info};
-use rusqlite::{params, Connection, OptionalExtension};
+use log::{debug, error, info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use rusqlite_migration::{Migrations, M};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use std::fs;
-use std::path::PathBuf;
-use tauri::{AppHandle, Emitter, Manager};
-
-use crate::audio_toolkit::save_wav_file;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::app_prompt_rules::AppliedPromptRule;
+use crate::audio_toolkit::{read_audio_file, save_audio_file, AudioFormat};
+use crate::dictation_metrics;
+use crate::events::{emit_event, AppEvent, HistoryIndexProgress, RecordingConversionProgress};
+use crate::managers::transcription::{Transcription, WordCorrections};
+use crate::session_log::SessionSummary;
+use crate::settings::RecordingFormat;
+use crate::speaker_turns::SpeakerTurn;
+use crate::translation_guard::TranslationCheck;
+use crate::vram_guard::VramGuardDecision;
+use crate::word_diff::{diff_words, WordDiff};
+
+/// Full-text index over history, kept in sync with transcription_history by triggers.
+/// Index writes happen inside the history save, which already runs off the paste path.
+const SEARCH_INDEX_SCHEMA: &str = "
+    CREATE VIRTUAL TABLE IF NOT EXISTS transcription_history_fts USING fts5(
+        title,
+        transcription_text,
+        post_processed_text,
+        content='transcription_history',
+        content_rowid='id',
+        tokenize='unicode61 remove_diacritics 2'
+    );
+    CREATE TRIGGER IF NOT EXISTS transcription_history_fts_insert
+    AFTER INSERT ON transcription_history BEGIN
+        INSERT INTO transcription_history_fts(rowid, title, transcription_text, post_processed_text)
+        VALUES (new.id, new.title, new.transcription_text, new.post_processed_text);
+    END;
+    CREATE TRIGGER IF NOT EXISTS transcription_history_fts_delete
+    AFTER DELETE ON transcription_history BEGIN
+        INSERT INTO transcription_history_fts(transcription_history_fts, rowid, title, transcription_text, post_processed_text)
+        VALUES ('delete', old.id, old.title, old.transcription_text, old.post_processed_text);
+    END;
+    CREATE TRIGGER IF NOT EXISTS transcription_history_fts_update
+    AFTER UPDATE OF title, transcription_text, post_processed_text ON transcription_history BEGIN
+        INSERT INTO transcription_history_fts(transcription_history_fts, rowid, title, transcription_text, post_processed_text)
+        VALUES ('delete', old.id, old.title, old.transcription_text, old.post_processed_text);
+        INSERT INTO transcription_history_fts(rowid, title, transcription_text, post_processed_text)
+        VALUES (new.id, new.title, new.transcription_text, new.post_processed_text);
+    END;
+";
+
+const DROP_SEARCH_INDEX: &str = "
+    DROP TRIGGER IF EXISTS transcription_history_fts_insert;
+    DROP TRIGGER IF EXISTS transcription_history_fts_delete;
+    DROP TRIGGER IF EXISTS transcription_history_fts_update;
+    DROP TABLE IF EXISTS transcription_history_fts;
+";
+
+/// Number of entries indexed per transaction while rebuilding
+const INDEX_REBUILD_BATCH: i64 = 1000;
 
 /// Database migrations for transcription history.
 /// Each migration is applied in order. The library tracks which migrations
@@ -31,8 +82,48 @@ static MIGRATIONS: &[M] = &[
     ),
     M::up("ALTER TABLE transcription_history ADD COLUMN post_processed_text TEXT;"),
     M::up("ALTER TABLE transcription_history ADD COLUMN post_process_prompt TEXT;"),
+    M::up(SEARCH_INDEX_SCHEMA),
+    M::up("INSERT INTO transcription_history_fts(transcription_history_fts) VALUES('rebuild');"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN translation_check TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN output_file TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN app_prompt_rule TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN words_per_minute REAL;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN post_process_diff TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN word_corrections TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN speaker_turns TEXT;"),
+    M::up("ALTER TABLE transcription_history ADD COLUMN vram_guard TEXT;"),
+    M::up(
+        "ALTER TABLE transcription_history ADD COLUMN collapsed_to_single_line BOOLEAN NOT NULL DEFAULT 0;",
+    ),
+    M::up("ALTER TABLE transcription_history ADD COLUMN draft BOOLEAN NOT NULL DEFAULT 0;"),
 ];
 
+pub fn audio_format(format: RecordingFormat) -> AudioFormat {
+    match format {
+        RecordingFormat::Wav => AudioFormat::Wav,
+        RecordingFormat::Flac => AudioFormat::Flac,
+        RecordingFormat::Opus => AudioFormat::Opus,
+    }
+}
+
+const HISTORY_COLUMNS: &str = "id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, translation_check, output_file, app_prompt_rule, words_per_minute, word_corrections, speaker_turns, vram_guard, collapsed_to_single_line, draft";
+
+/// A dictation to add to history with `save_transcription`
+#[derive(Default)]
+pub struct NewHistoryEntry {
+    pub transcription_text: String,
+    pub post_processed_text: Option<String>,
+    pub post_process_prompt: Option<String>,
+    pub translation_check: Option<TranslationCheck>,
+    /// File the text was written to in file dictation mode instead of being pasted
+    pub output_file: Option<String>,
+    pub app_prompt_rule: Option<AppliedPromptRule>,
+    pub word_corrections: Option<WordCorrections>,
+    pub speaker_turns: Option<Vec<SpeakerTurn>>,
+    pub vram_guard: Option<VramGuardDecision>,
+    pub collapsed_to_single_line: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 pub struct HistoryEntry {
     pub id: i64,
@@ -43,6 +134,46 @@ pub struct HistoryEntry {
     pub transcription_text: String,
     pub post_processed_text: Option<String>,
     pub post_process_prompt: Option<String>,
+    pub translation_check: Option<TranslationCheck>,
+    /// File the text was written to in file dictation mode instead of being pasted
+    pub output_file: Option<String>,
+    /// App prompt rule that selected the prompt for this dictation
+    pub app_prompt_rule: Option<AppliedPromptRule>,
+    /// Speaking rate of the raw transcript, None for very short recordings
+    pub words_per_minute: Option<f64>,
+    /// Custom-word corrections applied to the raw transcript, None when no custom
+    /// words were configured or the entry predates recording them
+    pub word_corrections: Option<WordCorrections>,
+    /// Parts of the recording separated by the speaker change marker in the
+    /// transcript, None when speaker change hints were off
+    pub speaker_turns: Option<Vec<SpeakerTurn>>,
+    /// Set when the selected model didn't fit in GPU memory and a smaller one
+    /// transcribed this entry
+    pub vram_guard: Option<VramGuardDecision>,
+    /// The text was pasted as a single line, `transcription_text` keeps its line breaks
+    pub collapsed_to_single_line: bool,
+    /// A cancelled recording kept without transcribing, see `CancelBehavior`.
+    /// `transcription_text` stays empty until the entry is retranscribed.
+    pub draft: bool,
+}
+
+/// Average speaking rate over past dictations, see `get_speaking_rate_stats`
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct SpeakingRateStats {
+    pub average_wpm: Option<f64>,
+    pub dictations: u32,
+    /// Oldest day first, in local time
+    pub daily: Vec<DailySpeakingRate>,
+    /// Today's dictations from the session log, None when not read
+    pub today: Option<SessionSummary>,
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct DailySpeakingRate {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub average_wpm: f64,
+    pub dictations: u32,
 }
 
 pub struct HistoryManager {
@@ -53,10 +184,9 @@ pub struct HistoryManager {
 
 impl HistoryManager {
     pub fn new(app_handle: &AppHandle) -> Result<Self> {
-        // Create recordings directory in app data dir
-        let app_data_dir = app_handle.path().app_data_dir()?;
-        let recordings_dir = app_data_dir.join("recordings");
-        let db_path = app_data_dir.join("history.db");
+        let paths = crate::paths::get(app_handle);
+        let recordings_dir = paths.recordings.clone();
+        let db_path = paths.data.join("history.db");
 
         // Ensure recordings directory exists
         if !recordings_dir.exists() {
@@ -73,6 +203,9 @@ impl HistoryManager {
         // Initialize database and run migrations synchronously
         manager.init_database()?;
 
+        // Verifying the search index can take a moment on large histories
+        manager.spawn_search_index_check();
+
         Ok(manager)
     }
 
@@ -173,43 +306,328 @@ impl HistoryManager {
     }
 
     fn get_connection(&self) -> Result<Connection> {
-        Ok(Connection::open(&self.db_path)?)
+        let conn = Connection::open(&self.db_path)?;
+        // An index rebuild may briefly hold the write lock
+        conn.busy_timeout(Duration::from_secs(5))?;
+        Ok(conn)
+    }
+
+    fn map_entry(row: &Row) -> rusqlite::Result<HistoryEntry> {
+        Ok(HistoryEntry {
+            id: row.get("id")?,
+            file_name: row.get("file_name")?,
+            timestamp: row.get("timestamp")?,
+            saved: row.get("saved")?,
+            title: row.get("title")?,
+            transcription_text: row.get("transcription_text")?,
+            post_processed_text: row.get("post_processed_text")?,
+            post_process_prompt: row.get("post_process_prompt")?,
+            translation_check: row
+                .get::<_, Option<String>>("translation_check")?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            output_file: row.get("output_file")?,
+            app_prompt_rule: row
+                .get::<_, Option<String>>("app_prompt_rule")?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            words_per_minute: row.get("words_per_minute")?,
+            word_corrections: row
+                .get::<_, Option<String>>("word_corrections")?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            speaker_turns: row
+                .get::<_, Option<String>>("speaker_turns")?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            vram_guard: row
+                .get::<_, Option<String>>("vram_guard")?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            collapsed_to_single_line: row.get("collapsed_to_single_line")?,
+            draft: row.get("draft")?,
+        })
+    }
+
+    /* ---------- search ------------------------------------------------------ */
+
+    /// Search history entries, newest first. An empty query returns every entry.
+    pub async fn search_entries(&self, query: &str) -> Result<Vec<HistoryEntry>> {
+        let conn = self.get_connection()?;
+        Self::search_entries_with_conn(&conn, query)
+    }
+
+    fn search_entries_with_conn(conn: &Connection, query: &str) -> Result<Vec<HistoryEntry>> {
+        let Some(fts_query) = build_fts_query(query) else {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM transcription_history ORDER BY timestamp DESC",
+                HISTORY_COLUMNS
+            ))?;
+            let entries = stmt
+                .query_map([], Self::map_entry)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            return Ok(entries);
+        };
+
+        match Self::search_index(conn, &fts_query) {
+            Ok(entries) => Ok(entries),
+            Err(e) => {
+                warn!(
+                    "History search index unavailable, falling back to scan: {}",
+                    e
+                );
+                Self::search_scan(conn, query.trim())
+            }
+        }
+    }
+
+    fn search_index_sql() -> String {
+        let columns = HISTORY_COLUMNS
+            .split(", ")
+            .map(|column| format!("h.{}", column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "SELECT {} FROM transcription_history_fts f
+             JOIN transcription_history h ON h.id = f.rowid
+             WHERE transcription_history_fts MATCH ?1
+             ORDER BY h.timestamp DESC",
+            columns
+        )
+    }
+
+    fn search_index(conn: &Connection, fts_query: &str) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let mut stmt = conn.prepare(&Self::search_index_sql())?;
+        let entries = stmt
+            .query_map([fts_query], Self::map_entry)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    fn search_scan_sql() -> String {
+        format!(
+            "SELECT {} FROM transcription_history
+             WHERE transcription_text LIKE ?1 OR post_processed_text LIKE ?1 OR title LIKE ?1
+             ORDER BY timestamp DESC",
+            HISTORY_COLUMNS
+        )
+    }
+
+    fn search_scan(conn: &Connection, query: &str) -> Result<Vec<HistoryEntry>> {
+        let pattern = format!("%{}%", query);
+        let mut stmt = conn.prepare(&Self::search_scan_sql())?;
+        let entries = stmt
+            .query_map([pattern], Self::map_entry)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    /// Whether the search index exists and matches the history table
+    fn search_index_healthy(conn: &Connection) -> bool {
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='transcription_history_fts'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        exists
+            && conn
+                .execute(
+                    "INSERT INTO transcription_history_fts(transcription_history_fts, rank) VALUES('integrity-check', 1)",
+                    [],
+                )
+                .is_ok()
+    }
+
+    fn spawn_search_index_check(&self) {
+        let db_path = self.db_path.clone();
+        let app_handle = self.app_handle.clone();
+
+        std::thread::spawn(move || {
+            let result = Connection::open(&db_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|mut conn| {
+                    if Self::search_index_healthy(&conn) {
+                        return Ok(());
+                    }
+                    warn!("History search index is missing or corrupt, rebuilding");
+                    Self::rebuild_search_index_with_conn(&mut conn, |progress| {
+                        emit_event(&app_handle, AppEvent::HistoryIndexProgress(progress));
+                    })
+                    .map(|_| ())
+                });
+
+            if let Err(e) = result {
+                error!("Failed to verify history search index: {}", e);
+            }
+        });
+    }
+
+    /// Drop and recreate the search index, reporting progress after each batch.
+    /// Returns the number of indexed entries.
+    pub fn rebuild_search_index(&self) -> Result<usize> {
+        let mut conn = self.get_connection()?;
+        Self::rebuild_search_index_with_conn(&mut conn, |progress| {
+            emit_event(&self.app_handle, AppEvent::HistoryIndexProgress(progress));
+        })
+    }
+
+    fn rebuild_search_index_with_conn(
+        conn: &mut Connection,
+        mut on_progress: impl FnMut(HistoryIndexProgress),
+    ) -> Result<usize> {
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(DROP_SEARCH_INDEX)?;
+        conn.execute_batch(SEARCH_INDEX_SCHEMA)?;
+
+        let total: i64 =
+            conn.query_row("SELECT COUNT(*) FROM transcription_history", [], |row| {
+                row.get(0)
+            })?;
+        let total = total as usize;
+        on_progress(HistoryIndexProgress { indexed: 0, total });
+
+        let mut indexed = 0usize;
+        let mut last_id = 0i64;
+        loop {
+            let tx = conn.transaction()?;
+            let batch_end: Option<i64> = tx.query_row(
+                "SELECT MAX(id) FROM (SELECT id FROM transcription_history WHERE id > ?1 ORDER BY id LIMIT ?2)",
+                params![last_id, INDEX_REBUILD_BATCH],
+                |row| row.get(0),
+            )?;
+            let Some(batch_end) = batch_end else {
+                break;
+            };
+
+            let inserted = tx.execute(
+                "INSERT INTO transcription_history_fts(rowid, title, transcription_text, post_processed_text)
+                 SELECT id, title, transcription_text, post_processed_text
+                 FROM transcription_history WHERE id > ?1 AND id <= ?2",
+                params![last_id, batch_end],
+            )?;
+            tx.commit()?;
+
+            indexed += inserted;
+            last_id = batch_end;
+            on_progress(HistoryIndexProgress { indexed, total });
+        }
+
+        info!("Rebuilt history search index with {} entries", indexed);
+        Ok(indexed)
     }
 
-    /// Save a transcription to history (both database and WAV file)
+    /// Save a transcription to history (both database and recording file)
     pub async fn save_transcription(
         &self,
         audio_samples: Vec<f32>,
-        transcription_text: String,
-        post_processed_text: Option<String>,
-        post_process_prompt: Option<String>,
+        entry: NewHistoryEntry,
     ) -> Result<()> {
         let timestamp = Utc::now().timestamp();
-        let file_name = format!("handy-{}.wav", timestamp);
+        let duration = Duration::from_secs_f64(
+            audio_samples.len() as f64
+                / crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE as f64,
+        );
+        let words_per_minute =
+            dictation_metrics::words_per_minute(&entry.transcription_text, duration);
+        let file_name = self.save_recording(audio_samples, timestamp).await?;
         let title = self.format_timestamp_title(timestamp);
 
-        // Save WAV file
-        let file_path = self.recordings_dir.join(&file_name);
-        save_wav_file(file_path, &audio_samples).await?;
-
         // Save to database
+        self.save_to_database(file_name, timestamp, title, words_per_minute, entry, false)?;
+
+        // Clean up old entries
+        self.cleanup_old_entries()?;
+
+        // Emit history updated event
+        emit_event(&self.app_handle, AppEvent::HistoryUpdated);
+
+        Ok(())
+    }
+
+    /// Saves a cancelled recording as a draft entry, without a transcript
+    pub async fn save_draft(&self, audio_samples: Vec<f32>) -> Result<()> {
+        let timestamp = Utc::now().timestamp();
+        let file_name = self.save_recording(audio_samples, timestamp).await?;
+        let title = self.format_timestamp_title(timestamp);
         self.save_to_database(
             file_name,
             timestamp,
             title,
-            transcription_text,
-            post_processed_text,
-            post_process_prompt,
+            None,
+            NewHistoryEntry::default(),
+            true,
         )?;
-
-        // Clean up old entries
+        // Drafts count towards the retention limits like any other recording
         self.cleanup_old_entries()?;
+        emit_event(&self.app_handle, AppEvent::HistoryUpdated);
+        Ok(())
+    }
 
-        // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
-            error!("Failed to emit history-updated event: {}", e);
-        }
+    /// Writes the audio file of a new entry, returning its file name
+    async fn save_recording(&self, audio_samples: Vec<f32>, timestamp: i64) -> Result<String> {
+        let format = audio_format(crate::settings::get_settings(&self.app_handle).recording_format);
+        let file_name = format!("handy-{}.{}", timestamp, format.extension());
+
+        // Encoding FLAC or Opus takes a while, keep it off the async runtime
+        let file_path = self.recordings_dir.join(&file_name);
+        tauri::async_runtime::spawn_blocking(move || {
+            save_audio_file(file_path, &audio_samples, format)
+        })
+        .await??;
+        Ok(file_name)
+    }
 
+    /// Replaces an entry's transcript with a new transcription of its recording,
+    /// which is `duration` long. The post-processed text no longer matches, so it
+    /// is cleared, and a draft stops being one.
+    pub fn update_transcription(
+        &self,
+        id: i64,
+        transcription: &Transcription,
+        duration: Duration,
+    ) -> Result<()> {
+        let conn = self.get_connection()?;
+        Self::update_transcription_with_conn(&conn, id, transcription, duration)?;
+        emit_event(&self.app_handle, AppEvent::HistoryUpdated);
+        Ok(())
+    }
+
+    fn update_transcription_with_conn(
+        conn: &Connection,
+        id: i64,
+        transcription: &Transcription,
+        duration: Duration,
+    ) -> Result<()> {
+        let translation_check = transcription
+            .translation_check
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let word_corrections = transcription
+            .word_corrections
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let speaker_turns = transcription
+            .speaker_turns
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let vram_guard = transcription
+            .vram_guard
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        conn.execute(
+            "UPDATE transcription_history SET transcription_text = ?2, words_per_minute = ?3, translation_check = ?4, word_corrections = ?5, speaker_turns = ?6, vram_guard = ?7, post_processed_text = NULL, post_process_prompt = NULL, post_process_diff = NULL, draft = 0 WHERE id = ?1",
+            params![
+                id,
+                transcription.text,
+                dictation_metrics::words_per_minute(&transcription.text, duration),
+                translation_check,
+                word_corrections,
+                speaker_turns,
+                vram_guard
+            ],
+        )?;
         Ok(())
     }
 
@@ -218,14 +636,45 @@ impl HistoryManager {
         file_name: String,
         timestamp: i64,
         title: String,
-        transcription_text: String,
-        post_processed_text: Option<String>,
-        post_process_prompt: Option<String>,
+        words_per_minute: Option<f64>,
+        entry: NewHistoryEntry,
+        draft: bool,
     ) -> Result<()> {
+        let NewHistoryEntry {
+            transcription_text,
+            post_processed_text,
+            post_process_prompt,
+            translation_check,
+            output_file,
+            app_prompt_rule,
+            word_corrections,
+            speaker_turns,
+            vram_guard,
+            collapsed_to_single_line,
+        } = entry;
+        let translation_check = translation_check
+            .map(|check| serde_json::to_string(&check))
+            .transpose()?;
+        let app_prompt_rule = app_prompt_rule
+            .map(|rule| serde_json::to_string(&rule))
+            .transpose()?;
+        let word_corrections = word_corrections
+            .map(|corrections| serde_json::to_string(&corrections))
+            .transpose()?;
+        let speaker_turns = speaker_turns
+            .map(|turns| serde_json::to_string(&turns))
+            .transpose()?;
+        let vram_guard = vram_guard
+            .map(|decision| serde_json::to_string(&decision))
+            .transpose()?;
+        let post_process_diff = post_processed_text
+            .as_deref()
+            .map(|processed| serde_json::to_string(&diff_words(&transcription_text, processed)))
+            .transpose()?;
         let conn = self.get_connection()?;
         conn.execute(
-            "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![file_name, timestamp, false, title, transcription_text, post_processed_text, post_process_prompt],
+            "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, translation_check, output_file, app_prompt_rule, words_per_minute, post_process_diff, word_corrections, speaker_turns, vram_guard, collapsed_to_single_line, draft) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+            params![file_name, timestamp, false, title, transcription_text, post_processed_text, post_process_prompt, translation_check, output_file, app_prompt_rule, words_per_minute, post_process_diff, word_corrections, speaker_turns, vram_guard, collapsed_to_single_line, draft],
         )?;
 
         debug!("Saved transcription to database");
@@ -354,22 +803,12 @@ impl HistoryManager {
 
     pub async fn get_history_entries(&self) -> Result<Vec<HistoryEntry>> {
         let conn = self.get_connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt FROM transcription_history ORDER BY timestamp DESC"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history ORDER BY timestamp DESC",
+            HISTORY_COLUMNS
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
-            Ok(HistoryEntry {
-                id: row.get("id")?,
-                file_name: row.get("file_name")?,
-                timestamp: row.get("timestamp")?,
-                saved: row.get("saved")?,
-                title: row.get("title")?,
-                transcription_text: row.get("transcription_text")?,
-                post_processed_text: row.get("post_processed_text")?,
-                post_process_prompt: row.get("post_process_prompt")?,
-            })
-        })?;
+        let rows = stmt.query_map([], Self::map_entry)?;
 
         let mut entries = Vec::new();
         for row in rows {
@@ -385,27 +824,12 @@ impl HistoryManager {
     }
 
     fn get_latest_entry_with_conn(conn: &Connection) -> Result<Option<HistoryEntry>> {
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt
-             FROM transcription_history
-             ORDER BY timestamp DESC
-             LIMIT 1",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history ORDER BY timestamp DESC LIMIT 1",
+            HISTORY_COLUMNS
+        ))?;
 
-        let entry = stmt
-            .query_row([], |row| {
-                Ok(HistoryEntry {
-                    id: row.get("id")?,
-                    file_name: row.get("file_name")?,
-                    timestamp: row.get("timestamp")?,
-                    saved: row.get("saved")?,
-                    title: row.get("title")?,
-                    transcription_text: row.get("transcription_text")?,
-                    post_processed_text: row.get("post_processed_text")?,
-                    post_process_prompt: row.get("post_process_prompt")?,
-                })
-            })
-            .optional()?;
+        let entry = stmt.query_row([], Self::map_entry).optional()?;
 
         Ok(entry)
     }
@@ -430,42 +854,169 @@ impl HistoryManager {
         debug!("Toggled saved status for entry {}: {}", id, new_saved);
 
         // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
-            error!("Failed to emit history-updated event: {}", e);
+        emit_event(&self.app_handle, AppEvent::HistoryUpdated);
+
+        Ok(())
+    }
+
+    /// Re-encodes every stored recording that isn't in `format` yet, reporting progress
+    /// after each file. Recordings that can't be converted are kept as they are.
+    pub fn convert_recordings(&self, format: AudioFormat) -> Result<RecordingConversionProgress> {
+        let conn = self.get_connection()?;
+        let pending: Vec<(i64, String)> = conn
+            .prepare("SELECT id, file_name FROM transcription_history")?
+            .query_map([], |row| Ok((row.get("id")?, row.get("file_name")?)))?
+            .collect::<rusqlite::Result<Vec<(i64, String)>>>()?
+            .into_iter()
+            .filter(|(_, file_name)| AudioFormat::from_path(Path::new(file_name)) != Some(format))
+            .collect();
+
+        let mut progress = RecordingConversionProgress {
+            converted: 0,
+            failed: 0,
+            total: pending.len(),
+        };
+        emit_event(
+            &self.app_handle,
+            AppEvent::RecordingConversionProgress(progress),
+        );
+
+        for (id, file_name) in pending {
+            match self.convert_recording(&conn, id, &file_name, format) {
+                Ok(()) => progress.converted += 1,
+                Err(e) => {
+                    warn!("Failed to convert recording {}: {}", file_name, e);
+                    progress.failed += 1;
+                }
+            }
+            emit_event(
+                &self.app_handle,
+                AppEvent::RecordingConversionProgress(progress),
+            );
         }
 
+        info!(
+            "Converted {} recordings to {:?}, {} failed",
+            progress.converted, format, progress.failed
+        );
+        if progress.converted > 0 {
+            emit_event(&self.app_handle, AppEvent::HistoryUpdated);
+        }
+        Ok(progress)
+    }
+
+    fn convert_recording(
+        &self,
+        conn: &Connection,
+        id: i64,
+        file_name: &str,
+        format: AudioFormat,
+    ) -> Result<()> {
+        let source = self.recordings_dir.join(file_name);
+        let samples = read_audio_file(&source)?;
+
+        let new_name = Path::new(file_name)
+            .with_extension(format.extension())
+            .to_string_lossy()
+            .to_string();
+        let target = self.recordings_dir.join(&new_name);
+        save_audio_file(&target, &samples, format)?;
+
+        // Only drop the original once the entry points at its replacement
+        if let Err(e) = conn.execute(
+            "UPDATE transcription_history SET file_name = ?1 WHERE id = ?2",
+            params![new_name, id],
+        ) {
+            let _ = fs::remove_file(&target);
+            return Err(e.into());
+        }
+        if let Err(e) = fs::remove_file(&source) {
+            warn!("Failed to remove converted recording {}: {}", file_name, e);
+        }
         Ok(())
     }
 
+    /// Average speaking rate overall and per day, over the last `days` days if given
+    pub fn speaking_rate_stats(&self, days: Option<u32>) -> Result<SpeakingRateStats> {
+        let since = days.map_or(0, |days| {
+            Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60
+        });
+        let conn = self.get_connection()?;
+        Self::speaking_rate_stats_with_conn(&conn, since)
+    }
+
+    fn speaking_rate_stats_with_conn(conn: &Connection, since: i64) -> Result<SpeakingRateStats> {
+        let (average_wpm, dictations) = conn.query_row(
+            "SELECT AVG(words_per_minute), COUNT(*) FROM transcription_history
+             WHERE words_per_minute IS NOT NULL AND draft = 0 AND timestamp >= ?1",
+            params![since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT date(timestamp, 'unixepoch', 'localtime') AS day, AVG(words_per_minute), COUNT(*)
+             FROM transcription_history
+             WHERE words_per_minute IS NOT NULL AND draft = 0 AND timestamp >= ?1
+             GROUP BY day ORDER BY day",
+        )?;
+        let daily = stmt
+            .query_map(params![since], |row| {
+                Ok(DailySpeakingRate {
+                    date: row.get(0)?,
+                    average_wpm: row.get(1)?,
+                    dictations: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(SpeakingRateStats {
+            average_wpm,
+            dictations,
+            daily,
+            today: None,
+        })
+    }
+
     pub fn get_audio_file_path(&self, file_name: &str) -> PathBuf {
         self.recordings_dir.join(file_name)
     }
 
     pub async fn get_entry_by_id(&self, id: i64) -> Result<Option<HistoryEntry>> {
         let conn = self.get_connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt
-             FROM transcription_history WHERE id = ?1",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history WHERE id = ?1",
+            HISTORY_COLUMNS
+        ))?;
 
-        let entry = stmt
-            .query_row([id], |row| {
-                Ok(HistoryEntry {
-                    id: row.get("id")?,
-                    file_name: row.get("file_name")?,
-                    timestamp: row.get("timestamp")?,
-                    saved: row.get("saved")?,
-                    title: row.get("title")?,
-                    transcription_text: row.get("transcription_text")?,
-                    post_processed_text: row.get("post_processed_text")?,
-                    post_process_prompt: row.get("post_process_prompt")?,
-                })
-            })
-            .optional()?;
+        let entry = stmt.query_row([id], Self::map_entry).optional()?;
 
         Ok(entry)
     }
 
+    /// Word diff between the raw and post-processed transcript of an entry. None when
+    /// the entry doesn't exist or wasn't post-processed.
+    pub fn get_post_process_diff(&self, id: i64) -> Result<Option<WordDiff>> {
+        let conn = self.get_connection()?;
+        Self::post_process_diff_with_conn(&conn, id)
+    }
+
+    fn post_process_diff_with_conn(conn: &Connection, id: i64) -> Result<Option<WordDiff>> {
+        let row: Option<(String, Option<String>, Option<String>)> = conn
+            .query_row(
+                "SELECT transcription_text, post_processed_text, post_process_diff
+                 FROM transcription_history WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let Some((raw, Some(processed), stored)) = row else {
+            return Ok(None);
+        };
+        // Entries from before diffs were stored get theirs computed on request
+        let stored = stored.and_then(|json| serde_json::from_str(&json).ok());
+        Ok(Some(stored.unwrap_or_else(|| diff_words(&raw, &processed))))
+    }
+
     pub async fn delete_entry(&self, id: i64) -> Result<()> {
         let conn = self.get_connection()?;
 
@@ -490,9 +1041,7 @@ impl HistoryManager {
         debug!("Deleted history entry with id: {}", id);
 
         // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
-            error!("Failed to emit history-updated event: {}", e);
-        }
+        emit_event(&self.app_handle, AppEvent::HistoryUpdated);
 
         Ok(())
     }
@@ -508,10 +1057,25 @@ impl HistoryManager {
     }
 }
 
+/// Turn free-form user input into an FTS5 query: every word must match as a prefix.
+/// Words are quoted so FTS5 operators and punctuation in the input are taken literally.
+fn build_fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rusqlite::{params, Connection};
+    use rusqlite::{params, Connection, StatementStatus};
 
     fn setup_conn() -> Connection {
         let conn = Connection::open_in_memory().expect("open in-memory db");
@@ -524,7 +1088,17 @@ mod tests {
                 title TEXT NOT NULL,
                 transcription_text TEXT NOT NULL,
                 post_processed_text TEXT,
-                post_process_prompt TEXT
+                post_process_prompt TEXT,
+                translation_check TEXT,
+                output_file TEXT,
+                app_prompt_rule TEXT,
+                words_per_minute REAL,
+                post_process_diff TEXT,
+                word_corrections TEXT,
+                speaker_turns TEXT,
+                vram_guard TEXT,
+                collapsed_to_single_line BOOLEAN NOT NULL DEFAULT 0,
+                draft BOOLEAN NOT NULL DEFAULT 0
             );",
         )
         .expect("create transcription_history table");
@@ -548,6 +1122,106 @@ mod tests {
         .expect("insert history entry");
     }
 
+    #[test]
+    fn speaking_rate_stats_average_rated_entries() {
+        let conn = setup_conn();
+        insert_entry(&conn, 100, "unrated", None);
+        for (timestamp, wpm) in [(200, 120.0), (300, 160.0), (400, 200.0)] {
+            insert_entry(&conn, timestamp, "rated", None);
+            conn.execute(
+                "UPDATE transcription_history SET words_per_minute = ?1 WHERE timestamp = ?2",
+                params![wpm, timestamp],
+            )
+            .expect("set rate");
+        }
+
+        let stats = HistoryManager::speaking_rate_stats_with_conn(&conn, 0).expect("stats");
+        assert_eq!(stats.dictations, 3);
+        assert_eq!(stats.average_wpm, Some(160.0));
+        assert_eq!(stats.daily.iter().map(|d| d.dictations).sum::<u32>(), 3);
+
+        let recent = HistoryManager::speaking_rate_stats_with_conn(&conn, 300).expect("stats");
+        assert_eq!(recent.dictations, 2);
+        assert_eq!(recent.average_wpm, Some(180.0));
+    }
+
+    #[test]
+    fn post_process_diff_is_computed_for_older_entries() {
+        let conn = setup_conn();
+        insert_entry(&conn, 100, "raw only", None);
+        insert_entry(&conn, 200, "so um hello", Some("So, hello."));
+
+        assert_eq!(
+            HistoryManager::post_process_diff_with_conn(&conn, 1).expect("diff"),
+            None
+        );
+        let diff = HistoryManager::post_process_diff_with_conn(&conn, 2)
+            .expect("diff")
+            .expect("post-processed entry has a diff");
+        assert_eq!(diff, diff_words("so um hello", "So, hello."));
+        assert!(!diff.skipped);
+    }
+
+    #[test]
+    fn drafts_count_in_stats_once_retranscribed() {
+        let conn = setup_conn();
+        insert_entry(&conn, 100, "", None);
+        conn.execute(
+            "UPDATE transcription_history SET draft = 1, words_per_minute = 90.0",
+            [],
+        )
+        .expect("mark draft");
+        let stats = HistoryManager::speaking_rate_stats_with_conn(&conn, 0).expect("stats");
+        assert_eq!(stats.dictations, 0);
+
+        let transcription = Transcription {
+            text: "one two three four five six".to_string(),
+            translation_check: None,
+            word_corrections: None,
+            speaker_turns: None,
+            vram_guard: None,
+        };
+        HistoryManager::update_transcription_with_conn(
+            &conn,
+            1,
+            &transcription,
+            Duration::from_secs(3),
+        )
+        .expect("update");
+        let entry = HistoryManager::get_latest_entry_with_conn(&conn)
+            .expect("fetch")
+            .expect("entry");
+        assert!(!entry.draft);
+        assert_eq!(entry.transcription_text, "one two three four five six");
+        let stats = HistoryManager::speaking_rate_stats_with_conn(&conn, 0).expect("stats");
+        assert_eq!(stats.dictations, 1);
+        assert_eq!(stats.average_wpm, Some(120.0));
+    }
+
+    #[test]
+    fn word_corrections_are_read_back_with_the_entry() {
+        let conn = setup_conn();
+        insert_entry(&conn, 100, "before corrections were recorded", None);
+        insert_entry(&conn, 200, "ship it to Tauri", None);
+        let corrections = WordCorrections {
+            threshold: 0.18,
+            algorithm: Default::default(),
+            applied: vec![crate::managers::transcription::WordCorrection {
+                from: "tory".to_string(),
+                to: "Tauri".to_string(),
+            }],
+        };
+        conn.execute(
+            "UPDATE transcription_history SET word_corrections = ?1 WHERE timestamp = 200",
+            params![serde_json::to_string(&corrections).unwrap()],
+        )
+        .expect("set corrections");
+
+        let entries = HistoryManager::search_entries_with_conn(&conn, "").expect("entries");
+        assert_eq!(entries[0].word_corrections, Some(corrections));
+        assert_eq!(entries[1].word_corrections, None);
+    }
+
     #[test]
     fn get_latest_entry_returns_none_when_empty() {
         let conn = setup_conn();
@@ -569,4 +1243,159 @@ mod tests {
         assert_eq!(entry.transcription_text, "second");
         assert_eq!(entry.post_processed_text.as_deref(), Some("processed"));
     }
+
+    fn migrated_conn() -> Connection {
+        let mut conn = Connection::open_in_memory().expect("open in-memory db");
+        Migrations::new(MIGRATIONS.to_vec())
+            .to_latest(&mut conn)
+            .expect("run migrations");
+        conn
+    }
+
+    #[test]
+    fn build_fts_query_quotes_terms() {
+        assert_eq!(build_fts_query("   "), None);
+        assert_eq!(
+            build_fts_query("hello wor"),
+            Some("\"hello\"* \"wor\"*".to_string())
+        );
+        assert_eq!(
+            build_fts_query("say \"hi\" OR"),
+            Some("\"say\"* \"\"\"hi\"\"\"* \"OR\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn search_index_tracks_inserts_updates_and_deletes() {
+        let conn = migrated_conn();
+        insert_entry(&conn, 100, "buy oat milk", None);
+        insert_entry(&conn, 200, "call the dentist", Some("Call the dentist."));
+
+        let found = HistoryManager::search_entries_with_conn(&conn, "dent").expect("search");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].timestamp, 200);
+
+        conn.execute(
+            "UPDATE transcription_history SET transcription_text = 'buy almond milk' WHERE timestamp = 100",
+            [],
+        )
+        .expect("update entry");
+        assert!(HistoryManager::search_entries_with_conn(&conn, "oat")
+            .expect("search")
+            .is_empty());
+        assert_eq!(
+            HistoryManager::search_entries_with_conn(&conn, "almond")
+                .expect("search")
+                .len(),
+            1
+        );
+
+        conn.execute(
+            "DELETE FROM transcription_history WHERE timestamp = 200",
+            [],
+        )
+        .expect("delete entry");
+        assert!(HistoryManager::search_entries_with_conn(&conn, "dentist")
+            .expect("search")
+            .is_empty());
+        assert!(HistoryManager::search_index_healthy(&conn));
+    }
+
+    #[test]
+    fn empty_query_returns_all_entries_newest_first() {
+        let conn = migrated_conn();
+        insert_entry(&conn, 100, "first", None);
+        insert_entry(&conn, 200, "second", None);
+
+        let entries = HistoryManager::search_entries_with_conn(&conn, "").expect("search");
+        let timestamps: Vec<i64> = entries.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![200, 100]);
+    }
+
+    #[test]
+    fn rebuild_restores_dropped_index() {
+        let mut conn = migrated_conn();
+        insert_entry(&conn, 100, "quarterly report draft", None);
+        conn.execute_batch(DROP_SEARCH_INDEX).expect("drop index");
+        assert!(!HistoryManager::search_index_healthy(&conn));
+
+        // Search still works without the index by scanning
+        assert_eq!(
+            HistoryManager::search_entries_with_conn(&conn, "quarterly")
+                .expect("search")
+                .len(),
+            1
+        );
+
+        let mut progress = Vec::new();
+        let indexed =
+            HistoryManager::rebuild_search_index_with_conn(&mut conn, |p| progress.push(p))
+                .expect("rebuild index");
+        assert_eq!(indexed, 1);
+        assert_eq!(
+            progress.last(),
+            Some(&HistoryIndexProgress {
+                indexed: 1,
+                total: 1
+            })
+        );
+        assert!(HistoryManager::search_index_healthy(&conn));
+        assert_eq!(
+            HistoryManager::search_entries_with_conn(&conn, "report")
+                .expect("search")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn indexed_search_is_fast_on_large_history() {
+        const WORDS: &[&str] = &[
+            "meeting", "notes", "project", "deadline", "review", "budget", "email", "call",
+            "schedule", "draft", "update", "client", "follow", "up", "tomorrow", "today",
+        ];
+
+        let mut conn = migrated_conn();
+        let tx = conn.transaction().expect("begin transaction");
+        for i in 0..50_000i64 {
+            let mut text: Vec<&str> = (0..12)
+                .map(|j| WORDS[((i * 7 + j * 13) as usize) % WORDS.len()])
+                .collect();
+            if i % 100 == 0 {
+                text.push("kumquat");
+            }
+            insert_entry(&tx, i, &text.join(" "), None);
+        }
+        tx.commit().expect("commit corpus");
+
+        let found = HistoryManager::search_entries_with_conn(&conn, "kumquat").expect("search");
+        assert_eq!(found.len(), 500);
+
+        // Measured in SQLite VM steps rather than wall time, so a slow machine
+        // can't fail the test
+        let (indexed, indexed_steps) = count_steps(
+            &conn,
+            &HistoryManager::search_index_sql(),
+            &build_fts_query("kumquat").unwrap(),
+        );
+        let (scanned, scan_steps) =
+            count_steps(&conn, &HistoryManager::search_scan_sql(), "%kumquat%");
+        assert_eq!((indexed, scanned), (500, 500));
+        assert!(
+            indexed_steps * 20 < scan_steps,
+            "index took {} steps, scan {}",
+            indexed_steps,
+            scan_steps
+        );
+    }
+
+    /// Runs a search statement, returning its row count and the VM steps it took
+    fn count_steps(conn: &Connection, sql: &str, param: &str) -> (usize, i32) {
+        let mut stmt = conn.prepare(sql).expect("prepare search");
+        let rows = stmt
+            .query_map([param], HistoryManager::map_entry)
+            .expect("run search")
+            .count();
+        (rows, stmt.get_status(StatementStatus::VmStep))
+    }
 }
@@ -1,3 +1,5 @@
+use crate::events::{emit_event, AppEvent, ModelExtractionFailed};
+use crate::model_manifest::{self, ModelManifest};
 use crate::settings::{get_settings, write_settings};
 use anyhow::Result;
 use flate2::read::GzDecoder;
@@ -9,10 +11,20 @@ use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tar::Archive;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
+
+/// Rough transcription quality, for comparing models at a glance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityTier {
+    Basic,
+    Good,
+    Great,
+    Best,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub enum EngineType {
@@ -36,6 +48,77 @@ pub struct ModelInfo {
     pub engine_type: EngineType,
     pub accuracy_score: f32, // 0.0 to 1.0, higher is more accurate
     pub speed_score: f32,    // 0.0 to 1.0, higher is faster
+    /// Quantizations the weights come in, empty when there is only one file
+    pub variants: Vec<QuantizationVariant>,
+    /// Variant that `filename`, `url` and `size_mb` refer to, see `model_quantization`
+    pub quantization: Option<String>,
+    /// Parameters in millions
+    pub parameters_m: u32,
+    /// ISO 639-1 codes of the languages the model transcribes
+    pub languages: Vec<String>,
+    pub quality_tier: QualityTier,
+    pub recommended_ram_mb: u32,
+    /// None for models that run on the CPU
+    pub recommended_vram_mb: Option<u32>,
+    /// Checksum of `filename`'s download, None when the manifest doesn't list one
+    pub sha256: Option<String>,
+}
+
+/// What sets models apart, for comparing them, see `list_available_models`
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+pub struct ModelSummary {
+    pub id: String,
+    pub display_name: String,
+    /// Taken on disk once downloaded, otherwise the size of the download
+    pub size_bytes: u64,
+    pub param_count: u64,
+    pub multilingual: bool,
+    /// ISO 639-1 codes of the languages the model transcribes
+    pub languages: Vec<String>,
+    pub downloaded: bool,
+}
+
+impl ModelSummary {
+    /// `on_disk` is the size of the downloaded files, when known
+    pub fn new(model: &ModelInfo, on_disk: Option<u64>) -> Self {
+        Self {
+            id: model.id.clone(),
+            display_name: model.name.clone(),
+            size_bytes: on_disk.unwrap_or(model.size_mb * 1024 * 1024),
+            param_count: u64::from(model.parameters_m) * 1_000_000,
+            multilingual: model.languages.len() > 1,
+            languages: model.languages.clone(),
+            downloaded: model.is_downloaded,
+        }
+    }
+}
+
+/// One quantization of a model's weights, stored in a file of its own. Smaller
+/// quantizations load faster and need less memory, at some cost in accuracy.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct QuantizationVariant {
+    /// Quantization name, like "q5_0" or "f16"
+    pub id: String,
+    pub filename: String,
+    pub url: Option<String>,
+    pub size_mb: u64,
+    pub sha256: Option<String>,
+    pub is_downloaded: bool,
+}
+
+/// Points the model's file at the `preferred` variant, or at the first one when
+/// there is no preference or the model has no such variant
+fn select_variant(model: &mut ModelInfo, preferred: Option<&str>) {
+    let chosen = preferred
+        .and_then(|id| model.variants.iter().find(|v| v.id == id))
+        .or(model.variants.first());
+    if let Some(variant) = chosen {
+        model.filename = variant.filename.clone();
+        model.url = variant.url.clone();
+        model.size_mb = variant.size_mb;
+        model.sha256 = variant.sha256.clone();
+        model.quantization = Some(variant.id.clone());
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -54,154 +137,22 @@ pub struct ModelManager {
 
 impl ModelManager {
     pub fn new(app_handle: &AppHandle) -> Result<Self> {
-        // Create models directory in app data
-        let models_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
-            .join("models");
+        let models_dir = crate::paths::get(app_handle).models.clone();
 
         if !models_dir.exists() {
             fs::create_dir_all(&models_dir)?;
         }
 
-        let mut available_models = HashMap::new();
-
-        // TODO this should be read from a JSON file or something..
-        available_models.insert(
-            "small".to_string(),
-            ModelInfo {
-                id: "small".to_string(),
-                name: "Whisper Small".to_string(),
-                description: "Fast and fairly accurate.".to_string(),
-                filename: "ggml-small.bin".to_string(),
-                url: Some("https://blob.handy.computer/ggml-small.bin".to_string()),
-                size_mb: 487,
-                is_downloaded: false,
-                is_downloading: false,
-                partial_size: 0,
-                is_directory: false,
-                engine_type: EngineType::Whisper,
-                accuracy_score: 0.60,
-                speed_score: 0.85,
-            },
-        );
-
-        // Add downloadable models
-        available_models.insert(
-            "medium".to_string(),
-            ModelInfo {
-                id: "medium".to_string(),
-                name: "Whisper Medium".to_string(),
-                description: "Good accuracy, medium speed".to_string(),
-                filename: "whisper-medium-q4_1.bin".to_string(),
-                url: Some("https://blob.handy.computer/whisper-medium-q4_1.bin".to_string()),
-                size_mb: 492, // Approximate size
-                is_downloaded: false,
-                is_downloading: false,
-                partial_size: 0,
-                is_directory: false,
-                engine_type: EngineType::Whisper,
-                accuracy_score: 0.75,
-                speed_score: 0.60,
-            },
-        );
-
-        available_models.insert(
-            "turbo".to_string(),
-            ModelInfo {
-                id: "turbo".to_string(),
-                name: "Whisper Turbo".to_string(),
-                description: "Balanced accuracy and speed.".to_string(),
-                filename: "ggml-large-v3-turbo.bin".to_string(),
-                url: Some("https://blob.handy.computer/ggml-large-v3-turbo.bin".to_string()),
-                size_mb: 1600, // Approximate size
-                is_downloaded: false,
-                is_downloading: false,
-                partial_size: 0,
-                is_directory: false,
-                engine_type: EngineType::Whisper,
-                accuracy_score: 0.80,
-                speed_score: 0.40,
-            },
-        );
-
-        available_models.insert(
-            "large".to_string(),
-            ModelInfo {
-                id: "large".to_string(),
-                name: "Whisper Large".to_string(),
-                description: "Good accuracy, but slow.".to_string(),
-                filename: "ggml-large-v3-q5_0.bin".to_string(),
-                url: Some("https://blob.handy.computer/ggml-large-v3-q5_0.bin".to_string()),
-                size_mb: 1100, // Approximate size
-                is_downloaded: false,
-                is_downloading: false,
-                partial_size: 0,
-                is_directory: false,
-                engine_type: EngineType::Whisper,
-                accuracy_score: 0.85,
-                speed_score: 0.30,
-            },
-        );
-
-        // Add NVIDIA Parakeet models (directory-based)
-        available_models.insert(
-            "parakeet-tdt-0.6b-v2".to_string(),
-            ModelInfo {
-                id: "parakeet-tdt-0.6b-v2".to_string(),
-                name: "Parakeet V2".to_string(),
-                description: "English only. The best model for English speakers.".to_string(),
-                filename: "parakeet-tdt-0.6b-v2-int8".to_string(), // Directory name
-                url: Some("https://blob.handy.computer/parakeet-v2-int8.tar.gz".to_string()),
-                size_mb: 473, // Approximate size for int8 quantized model
-                is_downloaded: false,
-                is_downloading: false,
-                partial_size: 0,
-                is_directory: true,
-                engine_type: EngineType::Parakeet,
-                accuracy_score: 0.85,
-                speed_score: 0.85,
-            },
-        );
+        let mut available_models: HashMap<String, ModelInfo> = model_manifest::load(app_handle)
+            .models
+            .into_iter()
+            .map(|model| (model.id.clone(), model.into_info()))
+            .collect();
 
-        available_models.insert(
-            "parakeet-tdt-0.6b-v3".to_string(),
-            ModelInfo {
-                id: "parakeet-tdt-0.6b-v3".to_string(),
-                name: "Parakeet V3".to_string(),
-                description: "Fast and accurate".to_string(),
-                filename: "parakeet-tdt-0.6b-v3-int8".to_string(), // Directory name
-                url: Some("https://blob.handy.computer/parakeet-v3-int8.tar.gz".to_string()),
-                size_mb: 478, // Approximate size for int8 quantized model
-                is_downloaded: false,
-                is_downloading: false,
-                partial_size: 0,
-                is_directory: true,
-                engine_type: EngineType::Parakeet,
-                accuracy_score: 0.80,
-                speed_score: 0.85,
-            },
-        );
-
-        available_models.insert(
-            "moonshine-base".to_string(),
-            ModelInfo {
-                id: "moonshine-base".to_string(),
-                name: "Moonshine Base".to_string(),
-                description: "Very fast, English only. Handles accents well.".to_string(),
-                filename: "moonshine-base".to_string(),
-                url: Some("https://blob.handy.computer/moonshine-base.tar.gz".to_string()),
-                size_mb: 58,
-                is_downloaded: false,
-                is_downloading: false,
-                partial_size: 0,
-                is_directory: true,
-                engine_type: EngineType::Moonshine,
-                accuracy_score: 0.70,
-                speed_score: 0.90,
-            },
-        );
+        let preferences = get_settings(app_handle).model_quantization;
+        for model in available_models.values_mut() {
+            select_variant(model, preferences.get(&model.id).map(String::as_str));
+        }
 
         let manager = Self {
             app_handle: app_handle.clone(),
@@ -262,47 +213,57 @@ impl ModelManager {
         let mut models = self.available_models.lock().unwrap();
 
         for model in models.values_mut() {
-            if model.is_directory {
-                // For directory-based models, check if the directory exists
-                let model_path = self.models_dir.join(&model.filename);
-                let partial_path = self.models_dir.join(format!("{}.partial", &model.filename));
-                let extracting_path = self
-                    .models_dir
-                    .join(format!("{}.extracting", &model.filename));
-
-                // Clean up any leftover .extracting directories from interrupted extractions
-                if extracting_path.exists() {
-                    warn!("Cleaning up interrupted extraction for model: {}", model.id);
-                    let _ = fs::remove_dir_all(&extracting_path);
-                }
+            self.check_files(model);
+        }
 
-                model.is_downloaded = model_path.exists() && model_path.is_dir();
-                model.is_downloading = false;
+        Ok(())
+    }
 
-                // Get partial file size if it exists (for the .tar.gz being downloaded)
-                if partial_path.exists() {
-                    model.partial_size = partial_path.metadata().map(|m| m.len()).unwrap_or(0);
-                } else {
-                    model.partial_size = 0;
-                }
+    /// Refreshes which of the model's files are on disk
+    fn check_files(&self, model: &mut ModelInfo) {
+        if model.is_directory {
+            // For directory-based models, check if the directory exists
+            let model_path = self.models_dir.join(&model.filename);
+            let partial_path = self.models_dir.join(format!("{}.partial", &model.filename));
+            let extracting_path = self
+                .models_dir
+                .join(format!("{}.extracting", &model.filename));
+
+            // Clean up any leftover .extracting directories from interrupted extractions
+            if extracting_path.exists() {
+                warn!("Cleaning up interrupted extraction for model: {}", model.id);
+                let _ = fs::remove_dir_all(&extracting_path);
+            }
+
+            model.is_downloaded = model_path.exists() && model_path.is_dir();
+            model.is_downloading = false;
+
+            // Get partial file size if it exists (for the .tar.gz being downloaded)
+            if partial_path.exists() {
+                model.partial_size = partial_path.metadata().map(|m| m.len()).unwrap_or(0);
             } else {
-                // For file-based models (existing logic)
-                let model_path = self.models_dir.join(&model.filename);
-                let partial_path = self.models_dir.join(format!("{}.partial", &model.filename));
+                model.partial_size = 0;
+            }
+        } else {
+            // For file-based models (existing logic)
+            let model_path = self.models_dir.join(&model.filename);
+            let partial_path = self.models_dir.join(format!("{}.partial", &model.filename));
 
-                model.is_downloaded = model_path.exists();
-                model.is_downloading = false;
+            for variant in &mut model.variants {
+                variant.is_downloaded = self.models_dir.join(&variant.filename).exists();
+            }
+            // Another quantization stands in for the preferred one, see `get_model_path`
+            model.is_downloaded =
+                model_path.exists() || model.variants.iter().any(|v| v.is_downloaded);
+            model.is_downloading = false;
 
-                // Get partial file size if it exists
-                if partial_path.exists() {
-                    model.partial_size = partial_path.metadata().map(|m| m.len()).unwrap_or(0);
-                } else {
-                    model.partial_size = 0;
-                }
+            // Get partial file size if it exists
+            if partial_path.exists() {
+                model.partial_size = partial_path.metadata().map(|m| m.len()).unwrap_or(0);
+            } else {
+                model.partial_size = 0;
             }
         }
-
-        Ok(())
     }
 
     fn auto_select_model_if_needed(&self) -> Result<()> {
@@ -452,9 +413,10 @@ impl ModelManager {
                 0.0
             },
         };
-        let _ = self
-            .app_handle
-            .emit("model-download-progress", &initial_progress);
+        emit_event(
+            &self.app_handle,
+            AppEvent::ModelDownloadProgress(initial_progress),
+        );
 
         // Download with progress
         while let Some(chunk) = stream.next().await {
@@ -486,7 +448,7 @@ impl ModelManager {
                 percentage,
             };
 
-            let _ = self.app_handle.emit("model-download-progress", &progress);
+            emit_event(&self.app_handle, AppEvent::ModelDownloadProgress(progress));
         }
 
         file.flush()?;
@@ -512,10 +474,43 @@ impl ModelManager {
             }
         }
 
+        // Verify against the manifest, which catches corrupt resumed downloads too
+        if let Some(expected) = model_info.sha256.clone() {
+            let path = partial_path.clone();
+            let actual =
+                tauri::async_runtime::spawn_blocking(move || model_manifest::file_sha256(&path))
+                    .await??;
+            if !model_manifest::checksum_matches(&expected, &actual) {
+                let _ = fs::remove_file(&partial_path);
+                {
+                    let mut models = self.available_models.lock().unwrap();
+                    if let Some(model) = models.get_mut(model_id) {
+                        model.is_downloading = false;
+                        model.partial_size = 0;
+                    }
+                }
+                return Err(anyhow::anyhow!(
+                    "Download of {} is corrupt: expected checksum {}, got {}",
+                    model_id,
+                    expected,
+                    actual
+                ));
+            }
+            debug!("Checksum of {} verified", model_id);
+        } else {
+            warn!(
+                "No checksum listed for {}, the download isn't verified",
+                model_id
+            );
+        }
+
         // Handle directory-based models (extract tar.gz) vs file-based models
         if model_info.is_directory {
             // Emit extraction started event
-            let _ = self.app_handle.emit("model-extraction-started", model_id);
+            emit_event(
+                &self.app_handle,
+                AppEvent::ModelExtractionStarted(model_id.to_string()),
+            );
             info!("Extracting archive for directory-based model: {}", model_id);
 
             // Use a temporary extraction directory to ensure atomic operations
@@ -542,11 +537,11 @@ impl ModelManager {
                 let error_msg = format!("Failed to extract archive: {}", e);
                 // Clean up failed extraction
                 let _ = fs::remove_dir_all(&temp_extract_dir);
-                let _ = self.app_handle.emit(
-                    "model-extraction-failed",
-                    &serde_json::json!({
-                        "model_id": model_id,
-                        "error": error_msg
+                emit_event(
+                    &self.app_handle,
+                    AppEvent::ModelExtractionFailed(ModelExtractionFailed {
+                        model_id: model_id.to_string(),
+                        error: error_msg.clone(),
                     }),
                 );
                 anyhow::anyhow!(error_msg)
@@ -577,7 +572,10 @@ impl ModelManager {
 
             info!("Successfully extracted archive for model: {}", model_id);
             // Emit extraction completed event
-            let _ = self.app_handle.emit("model-extraction-completed", model_id);
+            emit_event(
+                &self.app_handle,
+                AppEvent::ModelExtractionCompleted(model_id.to_string()),
+            );
 
             // Remove the downloaded tar.gz file
             let _ = fs::remove_file(&partial_path);
@@ -593,11 +591,21 @@ impl ModelManager {
                 model.is_downloading = false;
                 model.is_downloaded = true;
                 model.partial_size = 0;
+                if let Some(variant) = model
+                    .variants
+                    .iter_mut()
+                    .find(|v| v.filename == model_info.filename)
+                {
+                    variant.is_downloaded = true;
+                }
             }
         }
 
         // Emit completion event
-        let _ = self.app_handle.emit("model-download-complete", model_id);
+        emit_event(
+            &self.app_handle,
+            AppEvent::ModelDownloadComplete(model_id.to_string()),
+        );
 
         info!(
             "Successfully downloaded model {} to {:?}",
@@ -638,10 +646,18 @@ impl ModelManager {
                 deleted_something = true;
             }
         } else {
-            // Delete complete model file if it exists
-            if model_path.exists() {
-                info!("Deleting model file at: {:?}", model_path);
-                fs::remove_file(&model_path)?;
+            // Delete every downloaded quantization, not only the preferred one
+            let mut paths: Vec<PathBuf> = model_info
+                .variants
+                .iter()
+                .map(|v| self.models_dir.join(&v.filename))
+                .collect();
+            if paths.is_empty() {
+                paths.push(model_path.clone());
+            }
+            for path in paths.iter().filter(|path| path.exists()) {
+                info!("Deleting model file at: {:?}", path);
+                fs::remove_file(path)?;
                 info!("Model file deleted successfully");
                 deleted_something = true;
             }
@@ -666,6 +682,63 @@ impl ModelManager {
         Ok(())
     }
 
+    /// Directory for models that support the app rather than transcribe, such as
+    /// wake word detection. Kept apart from the catalog's models.
+    fn auxiliary_models_dir(&self, kind: &str) -> Result<PathBuf> {
+        let dir = self.models_dir.join(kind);
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Path of a file from the manifest's auxiliary list, downloading it first if
+    /// it's missing. The download is checked against the listed SHA-256 and
+    /// deleted if it doesn't match.
+    pub async fn ensure_auxiliary(&self, kind: &str, filename: &str) -> Result<PathBuf> {
+        let path = self.auxiliary_models_dir(kind)?.join(filename);
+        if path.exists() {
+            return Ok(path);
+        }
+        let file = model_manifest::load(&self.app_handle)
+            .auxiliary
+            .into_iter()
+            .find(|f| f.kind == kind && f.filename == filename)
+            .ok_or_else(|| anyhow::anyhow!("No download listed for {}/{}", kind, filename))?;
+
+        info!("Downloading {}", file.url);
+        let response = reqwest::get(&file.url).await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to download {}: HTTP {}",
+                filename,
+                response.status()
+            ));
+        }
+        let body = response.bytes().await?;
+        // Written aside first so an interrupted download isn't taken for the file
+        let partial_path = path.with_extension("partial");
+        fs::write(&partial_path, &body)?;
+
+        if let Some(expected) = &file.sha256 {
+            let actual = model_manifest::file_sha256(&partial_path)?;
+            if !model_manifest::checksum_matches(expected, &actual) {
+                let _ = fs::remove_file(&partial_path);
+                return Err(anyhow::anyhow!(
+                    "Download of {} is corrupt: expected checksum {}, got {}",
+                    filename,
+                    expected,
+                    actual
+                ));
+            }
+        } else {
+            warn!(
+                "No checksum listed for {}, the download isn't verified",
+                filename
+            );
+        }
+        fs::rename(&partial_path, &path)?;
+        Ok(path)
+    }
+
     pub fn get_model_path(&self, model_id: &str) -> Result<PathBuf> {
         let model_info = self
             .get_model_info(model_id)
@@ -700,8 +773,20 @@ impl ModelManager {
             }
         } else {
             // For file-based models (existing logic)
+            let fallback = model_info
+                .variants
+                .iter()
+                .find(|v| v.is_downloaded && v.filename != model_info.filename);
             if model_path.exists() && !partial_path.exists() {
                 Ok(model_path)
+            } else if let Some(fallback) = fallback {
+                warn!(
+                    "Quantization {} of model {} isn't downloaded, loading {} instead",
+                    model_info.quantization.as_deref().unwrap_or_default(),
+                    model_id,
+                    fallback.id
+                );
+                Ok(self.models_dir.join(&fallback.filename))
             } else {
                 Err(anyhow::anyhow!(
                     "Complete model file not found: {}",
@@ -711,6 +796,74 @@ impl ModelManager {
         }
     }
 
+    /// Bytes the model's downloaded files take, None when it isn't downloaded
+    pub fn size_on_disk(&self, model_id: &str) -> Option<u64> {
+        let path = self.get_model_path(model_id).ok()?;
+        Some(disk_usage(&path))
+    }
+
+    /// Chooses the quantization of a model to download and load, None for its
+    /// default. A loaded model keeps running until it is loaded again.
+    pub fn set_quantization(&self, model_id: &str, quantization: Option<&str>) -> Result<()> {
+        let mut models = self.available_models.lock().unwrap();
+        let model = models
+            .get_mut(model_id)
+            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
+        if let Some(id) = quantization {
+            if !model.variants.iter().any(|v| v.id == id) {
+                return Err(anyhow::anyhow!(
+                    "Model {} has no quantization {}",
+                    model_id,
+                    id
+                ));
+            }
+        }
+        if model.is_downloading {
+            return Err(anyhow::anyhow!(
+                "Model is currently downloading: {}",
+                model_id
+            ));
+        }
+        select_variant(model, quantization);
+        self.check_files(model);
+        Ok(())
+    }
+
+    /// Updates the catalog from a newer manifest. Models being downloaded keep
+    /// their entry, and so do downloaded models whose file the new entry wouldn't
+    /// find, so a catalog update never makes a model unusable.
+    pub fn apply_manifest(&self, manifest: ModelManifest) {
+        let preferences = get_settings(&self.app_handle).model_quantization;
+        let mut models = self.available_models.lock().unwrap();
+        for entry in manifest.models {
+            if models.get(&entry.id).is_some_and(|m| m.is_downloading) {
+                continue;
+            }
+            let mut model = entry.into_info();
+            select_variant(&mut model, preferences.get(&model.id).map(String::as_str));
+            self.check_files(&mut model);
+            if models.get(&model.id).is_some_and(|m| m.is_downloaded) && !model.is_downloaded {
+                warn!(
+                    "Keeping the catalog entry of {}, the new one doesn't match its files",
+                    model.id
+                );
+                continue;
+            }
+            models.insert(model.id.clone(), model);
+        }
+    }
+
+    /// Applies the quantizations chosen in the settings, after they were edited
+    /// outside the app. Models being downloaded keep theirs.
+    pub fn apply_quantization_preferences(&self) {
+        let preferences = get_settings(&self.app_handle).model_quantization;
+        let mut models = self.available_models.lock().unwrap();
+        for model in models.values_mut().filter(|m| !m.is_downloading) {
+            select_variant(model, preferences.get(&model.id).map(String::as_str));
+            self.check_files(model);
+        }
+    }
+
     pub fn cancel_download(&self, model_id: &str) -> Result<()> {
         debug!("ModelManager: cancel_download called for: {}", model_id);
 
@@ -741,3 +894,109 @@ impl ModelManager {
         Ok(())
     }
 }
+
+/// Size of a file, or of everything in a directory
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = fs::metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| disk_usage(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_manifest::ManifestModel;
+
+    fn whisper_model() -> ModelInfo {
+        let entry = r#"{
+            "id": "turbo",
+            "name": "Whisper Turbo",
+            "description": "Balanced accuracy and speed.",
+            "engine_type": "Whisper",
+            "filename": "ggml-large-v3-turbo.bin",
+            "url": "https://blob.handy.computer/ggml-large-v3-turbo.bin",
+            "is_directory": false,
+            "size_mb": 1600,
+            "parameters_m": 809,
+            "languages": ["en", "de"],
+            "quality_tier": "great",
+            "recommended_ram_mb": 4096,
+            "recommended_vram_mb": 2560,
+            "accuracy_score": 0.8,
+            "speed_score": 0.4,
+            "variants": [
+                {
+                    "id": "f16",
+                    "filename": "ggml-large-v3-turbo.bin",
+                    "url": "https://blob.handy.computer/ggml-large-v3-turbo.bin",
+                    "size_mb": 1600
+                },
+                {
+                    "id": "q5_0",
+                    "filename": "ggml-large-v3-turbo-q5_0.bin",
+                    "url": "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q5_0.bin",
+                    "size_mb": 574,
+                    "sha256": "abc123"
+                }
+            ]
+        }"#;
+        serde_json::from_str::<ManifestModel>(entry)
+            .expect("valid manifest entry")
+            .into_info()
+    }
+
+    #[test]
+    fn preferred_variant_sets_the_model_file() {
+        let mut model = whisper_model();
+        select_variant(&mut model, None);
+        assert_eq!(model.quantization.as_deref(), Some("f16"));
+        assert_eq!(model.filename, "ggml-large-v3-turbo.bin");
+
+        select_variant(&mut model, Some("q5_0"));
+        assert_eq!(model.quantization.as_deref(), Some("q5_0"));
+        assert_eq!(model.size_mb, 574);
+        assert_eq!(model.sha256.as_deref(), Some("abc123"));
+        assert_eq!(
+            model.url.as_deref(),
+            Some("https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q5_0.bin")
+        );
+
+        // A preference for a variant that doesn't exist falls back to the default
+        select_variant(&mut model, Some("q2_k"));
+        assert_eq!(model.quantization.as_deref(), Some("f16"));
+
+        let mut single_file = whisper_model();
+        single_file.variants.clear();
+        select_variant(&mut single_file, Some("q5_0"));
+        assert_eq!(single_file.quantization, None);
+    }
+
+    #[test]
+    fn summary_prefers_the_size_on_disk() {
+        let mut model = whisper_model();
+        let summary = ModelSummary::new(&model, None);
+        assert_eq!(summary.display_name, "Whisper Turbo");
+        assert_eq!(summary.size_bytes, 1600 * 1024 * 1024);
+        assert_eq!(summary.param_count, 809_000_000);
+        assert!(summary.multilingual);
+        assert!(!summary.downloaded);
+
+        model.is_downloaded = true;
+        model.languages = vec!["en".to_string()];
+        let summary = ModelSummary::new(&model, Some(1_234));
+        assert_eq!(summary.size_bytes, 1_234);
+        assert!(!summary.multilingual);
+        assert!(summary.downloaded);
+    }
+}
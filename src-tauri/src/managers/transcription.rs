@@ -1,26 +1,50 @@
-use crate::audio_toolkit::{apply_custom_words, filter_transcription_output};
+use crate::audio_toolkit::text::{DamerauLevenshtein, Jaro, Levenshtein, Phonetic, WordMatcher};
+use crate::audio_toolkit::{
+    apply_custom_words_with_changes, collapse_repeated_phrases, filter_transcription_output,
+};
+use crate::chunking::{chunk_ranges, stitch};
+use crate::custom_words;
+use crate::events::{emit_event, AppEvent, TranscriptionRetrying};
+use crate::language;
 use crate::managers::model::{EngineType, ModelManager};
-use crate::settings::{get_settings, ModelUnloadTimeout};
+use crate::power_profile;
+use crate::settings::{
+    get_settings, AppSettings, ComputeBackend, CorrectionAlgorithm, DecodingParams,
+    ModelUnloadTimeout, TranslationGuard, VramGuardBehavior,
+};
+use crate::speaker_turns::{detect_turns, SpeakerTurn};
+use crate::translation_guard::{check_english, TranslationCheck};
+use crate::vram_guard::{self, Outcome, VramGuardDecision};
+use crate::whisper_decoder::WhisperDecoder;
 use anyhow::Result;
 use log::{debug, error, info, warn};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 use std::time::{Duration, SystemTime};
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 use transcribe_rs::{
     engines::{
         moonshine::{ModelVariant, MoonshineEngine, MoonshineModelParams},
         parakeet::{
             ParakeetEngine, ParakeetInferenceParams, ParakeetModelParams, TimestampGranularity,
         },
-        whisper::{WhisperEngine, WhisperInferenceParams},
     },
     TranscriptionEngine,
 };
+use whisper_rs::WhisperError;
 
-#[derive(Clone, Debug, Serialize)]
+/// Most retries of a failed inference that can be configured
+pub const MAX_TRANSCRIPTION_RETRIES: u32 = 5;
+/// Wait before a retry, multiplied by the attempt number
+const RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Debug, Serialize, Type)]
 pub struct ModelStateEvent {
     pub event_type: String,
     pub model_id: Option<String>,
@@ -28,23 +52,195 @@ pub struct ModelStateEvent {
     pub error: Option<String>,
 }
 
+/// Whether a transcription model is in memory, as reported to the frontend
+#[derive(Clone, Debug, PartialEq, Serialize, Type)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ModelState {
+    Unloaded,
+    Loading {
+        model_id: String,
+    },
+    Loaded {
+        model_id: String,
+        /// Rough memory use, models take about their download size once loaded
+        memory_estimate_mb: u64,
+        /// Seconds since the last transcription or load
+        idle_secs: u32,
+    },
+}
+
+/// A word that custom-word correction replaced, with surrounding punctuation
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Type)]
+pub struct WordCorrection {
+    pub from: String,
+    pub to: String,
+}
+
+/// Custom-word corrections applied to a transcript
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Type)]
+pub struct WordCorrections {
+    /// `word_correction_threshold` the words were matched with
+    pub threshold: f64,
+    #[serde(default)]
+    pub algorithm: CorrectionAlgorithm,
+    /// In text order, empty when no word was close enough to a custom word
+    pub applied: Vec<WordCorrection>,
+}
+
+/// Final transcription text along with the translation guard's verdict, if it ran
+#[derive(Clone, Debug)]
+pub struct Transcription {
+    pub text: String,
+    pub translation_check: Option<TranslationCheck>,
+    /// None when no custom words are configured
+    pub word_corrections: Option<WordCorrections>,
+    /// Turns separated by the speaker change marker, None when hints are off
+    pub speaker_turns: Option<Vec<SpeakerTurn>>,
+    /// Set when the selected model didn't fit in GPU memory and another one ran
+    pub vram_guard: Option<VramGuardDecision>,
+}
+
+/// Settings that shape a transcription. Normally read from the current settings,
+/// comparisons override them per run.
+#[derive(Clone, Debug)]
+pub struct TranscriptionOptions {
+    pub language: String,
+    pub translate_to_english: bool,
+    pub translation_guard: TranslationGuard,
+    pub custom_words: Vec<String>,
+    pub custom_words_by_language: HashMap<String, Vec<String>>,
+    pub word_correction_threshold: f64,
+    pub correction_algorithm: CorrectionAlgorithm,
+    pub anti_hallucination: bool,
+    pub hallucination_max_repeats: u32,
+    pub decoding: DecodingParams,
+    /// See `AppSettings::chunk_seconds`
+    pub chunk_seconds: u32,
+    pub retry_count: u32,
+    pub speaker_change_hints: bool,
+    pub speaker_change_marker: String,
+    /// Leave the model loaded even when it's set to unload right after use
+    pub keep_loaded: bool,
+}
+
+impl TranscriptionOptions {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            language: settings.selected_language.clone(),
+            translate_to_english: settings.translate_to_english,
+            translation_guard: settings.translation_guard,
+            custom_words: settings.custom_words.clone(),
+            custom_words_by_language: settings.custom_words_by_language.clone(),
+            word_correction_threshold: settings.word_correction_threshold,
+            correction_algorithm: settings.correction_algorithm,
+            anti_hallucination: settings.anti_hallucination,
+            hallucination_max_repeats: settings.hallucination_max_repeats,
+            decoding: settings.advanced_decoding,
+            chunk_seconds: settings.chunk_seconds,
+            retry_count: settings
+                .transcription_retry_count
+                .min(MAX_TRANSCRIPTION_RETRIES),
+            speaker_change_hints: settings.speaker_change_hints,
+            speaker_change_marker: settings.speaker_change_marker.clone(),
+            keep_loaded: false,
+        }
+    }
+
+    /// Whether any custom words may correct the transcript
+    fn has_custom_words(&self) -> bool {
+        !self.custom_words.is_empty()
+            || self
+                .custom_words_by_language
+                .values()
+                .any(|words| !words.is_empty())
+    }
+
+    /// Custom words correcting `text`, see `custom_words`. With `auto` the language
+    /// is detected from the text, only the global words apply when that's unsure.
+    fn custom_words_for(&self, text: &str) -> Vec<String> {
+        if self.custom_words_by_language.is_empty() {
+            return self.custom_words.clone();
+        }
+        let language = if self.translate_to_english {
+            Some("en".to_string())
+        } else if self.language != "auto" {
+            Some(self.language.clone())
+        } else {
+            language::detect_language(text).map(|language| language.code)
+        };
+        custom_words::words_for_language(
+            &self.custom_words,
+            &self.custom_words_by_language,
+            language.as_deref(),
+        )
+    }
+}
+
 enum LoadedEngine {
-    Whisper(WhisperEngine),
+    Whisper(WhisperDecoder),
     Parakeet(ParakeetEngine),
     Moonshine(MoonshineEngine),
 }
 
+/// A model loaded outside the manager, see `TranscriptionManager::load_standalone`.
+/// Dropping it frees the model.
+pub struct StandaloneEngine(LoadedEngine);
+
+impl StandaloneEngine {
+    /// Runs the raw engine over the audio in chunks, without retries or clean up
+    pub fn transcribe(&mut self, audio: &[f32], options: &TranscriptionOptions) -> Result<String> {
+        let mut texts = Vec::new();
+        for range in chunk_ranges(audio, options.chunk_seconds) {
+            let len = range.len();
+            let (text, _) = run_engine(&mut self.0, audio[range].to_vec(), options)?;
+            texts.push((text, len));
+        }
+        Ok(stitch(&texts))
+    }
+}
+
+/// How `load_model` loads a model after `check_vram`
+struct VramCheck {
+    /// The replacement loaded instead, if any
+    decision: Option<VramGuardDecision>,
+    use_gpu: bool,
+}
+
+impl Default for VramCheck {
+    fn default() -> Self {
+        Self {
+            decision: None,
+            use_gpu: true,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct TranscriptionManager {
     engine: Arc<Mutex<Option<LoadedEngine>>>,
     model_manager: Arc<ModelManager>,
     app_handle: AppHandle,
     current_model_id: Arc<Mutex<Option<String>>>,
+    /// Model being read from disk, if any
+    loading_model_id: Arc<Mutex<Option<String>>>,
+    /// Why the loaded model differs from the one asked for, if it does
+    vram_guard: Arc<Mutex<Option<VramGuardDecision>>>,
+    /// The loaded model runs on the CPU because it didn't fit in GPU memory
+    loaded_on_cpu: Arc<AtomicBool>,
     last_activity: Arc<AtomicU64>,
     shutdown_signal: Arc<AtomicBool>,
     watcher_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
     is_loading: Arc<Mutex<bool>>,
     loading_condvar: Arc<Condvar>,
+    /// Model asked for by `initiate_model_switch` during a load, loaded next
+    queued_switch: Arc<Mutex<Option<String>>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
 }
 
 impl TranscriptionManager {
@@ -54,16 +250,15 @@ impl TranscriptionManager {
             model_manager,
             app_handle: app_handle.clone(),
             current_model_id: Arc::new(Mutex::new(None)),
-            last_activity: Arc::new(AtomicU64::new(
-                SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64,
-            )),
+            loading_model_id: Arc::new(Mutex::new(None)),
+            vram_guard: Arc::new(Mutex::new(None)),
+            loaded_on_cpu: Arc::new(AtomicBool::new(false)),
+            last_activity: Arc::new(AtomicU64::new(now_ms())),
             shutdown_signal: Arc::new(AtomicBool::new(false)),
             watcher_handle: Arc::new(Mutex::new(None)),
             is_loading: Arc::new(Mutex::new(false)),
             loading_condvar: Arc::new(Condvar::new()),
+            queued_switch: Arc::new(Mutex::new(None)),
         };
 
         // Start the idle watcher
@@ -81,35 +276,32 @@ impl TranscriptionManager {
                     }
 
                     let settings = get_settings(&app_handle_cloned);
-                    let timeout_seconds = settings.model_unload_timeout.to_seconds();
+                    let unload_timeout =
+                        power_profile::unload_timeout(&app_handle_cloned, &settings);
+                    let timeout_seconds = unload_timeout.to_seconds();
 
                     if let Some(limit_seconds) = timeout_seconds {
                         // Skip polling-based unloading for immediate timeout since it's handled directly in transcribe()
-                        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately {
+                        if unload_timeout == ModelUnloadTimeout::Immediately {
                             continue;
                         }
 
                         let last = manager_cloned.last_activity.load(Ordering::Relaxed);
-                        let now_ms = SystemTime::now()
-                            .duration_since(SystemTime::UNIX_EPOCH)
-                            .unwrap()
-                            .as_millis() as u64;
-
-                        if now_ms.saturating_sub(last) > limit_seconds * 1000 {
+                        if now_ms().saturating_sub(last) > limit_seconds * 1000 {
                             // idle -> unload
                             if manager_cloned.is_model_loaded() {
                                 let unload_start = std::time::Instant::now();
                                 debug!("Starting to unload model due to inactivity");
 
                                 if let Ok(()) = manager_cloned.unload_model() {
-                                    let _ = app_handle_cloned.emit(
-                                        "model-state-changed",
-                                        ModelStateEvent {
+                                    emit_event(
+                                        &app_handle_cloned,
+                                        AppEvent::ModelStateChanged(ModelStateEvent {
                                             event_type: "unloaded".to_string(),
                                             model_id: None,
                                             model_name: None,
                                             error: None,
-                                        },
+                                        }),
                                     );
                                     let unload_duration = unload_start.elapsed();
                                     debug!(
@@ -142,7 +334,7 @@ impl TranscriptionManager {
             let mut engine = self.engine.lock().unwrap();
             if let Some(ref mut loaded_engine) = *engine {
                 match loaded_engine {
-                    LoadedEngine::Whisper(ref mut e) => e.unload_model(),
+                    LoadedEngine::Whisper(_) => {}
                     LoadedEngine::Parakeet(ref mut e) => e.unload_model(),
                     LoadedEngine::Moonshine(ref mut e) => e.unload_model(),
                 }
@@ -153,16 +345,18 @@ impl TranscriptionManager {
             let mut current_model = self.current_model_id.lock().unwrap();
             *current_model = None;
         }
+        *self.vram_guard.lock().unwrap() = None;
+        self.loaded_on_cpu.store(false, Ordering::Relaxed);
 
         // Emit unloaded event
-        let _ = self.app_handle.emit(
-            "model-state-changed",
-            ModelStateEvent {
+        emit_event(
+            &self.app_handle,
+            AppEvent::ModelStateChanged(ModelStateEvent {
                 event_type: "unloaded".to_string(),
                 model_id: None,
                 model_name: None,
                 error: None,
-            },
+            }),
         );
 
         let unload_duration = unload_start.elapsed();
@@ -173,10 +367,35 @@ impl TranscriptionManager {
         Ok(())
     }
 
+    /// Restarts the idle timeout, as if the model had just been used
+    pub fn mark_activity(&self) {
+        self.last_activity.store(now_ms(), Ordering::Relaxed);
+    }
+
+    pub fn model_state(&self) -> ModelState {
+        if let Some(model_id) = self.loading_model_id.lock().unwrap().clone() {
+            return ModelState::Loading { model_id };
+        }
+        let Some(model_id) = self.get_current_model() else {
+            return ModelState::Unloaded;
+        };
+        let memory_estimate_mb = self
+            .model_manager
+            .get_model_info(&model_id)
+            .map_or(0, |info| info.size_mb);
+        let idle_ms = now_ms().saturating_sub(self.last_activity.load(Ordering::Relaxed));
+        ModelState::Loaded {
+            model_id,
+            memory_estimate_mb,
+            idle_secs: (idle_ms / 1000) as u32,
+        }
+    }
+
     /// Unloads the model immediately if the setting is enabled and the model is loaded
     pub fn maybe_unload_immediately(&self, context: &str) {
         let settings = get_settings(&self.app_handle);
-        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately
+        if power_profile::unload_timeout(&self.app_handle, &settings)
+            == ModelUnloadTimeout::Immediately
             && self.is_model_loaded()
         {
             info!("Immediately unloading model after {}", context);
@@ -187,18 +406,138 @@ impl TranscriptionManager {
     }
 
     pub fn load_model(&self, model_id: &str) -> Result<()> {
+        let settings = get_settings(&self.app_handle);
+        // The CPU needs no GPU memory to check
+        let VramCheck { decision, use_gpu } =
+            if power_profile::compute_backend(&self.app_handle, &settings) == ComputeBackend::Cpu {
+                VramCheck {
+                    decision: None,
+                    use_gpu: false,
+                }
+            } else {
+                self.check_vram(model_id)?
+            };
+        let model_id = decision
+            .as_ref()
+            .map_or(model_id, |d| d.loaded_model.as_str());
+        // The GPU can't hold both, and the check counted the old one's memory as free
+        if self.loaded_vram_mb() > 0 {
+            self.unload_model()?;
+        }
+        *self.loading_model_id.lock().unwrap() = Some(model_id.to_string());
+        let result = self.load_engine(model_id, use_gpu);
+        *self.loading_model_id.lock().unwrap() = None;
+        if result.is_ok() {
+            *self.vram_guard.lock().unwrap() = decision;
+            // Only Whisper has a GPU to stay off
+            let whisper = self
+                .model_manager
+                .get_model_info(model_id)
+                .is_some_and(|info| matches!(info.engine_type, EngineType::Whisper));
+            self.loaded_on_cpu
+                .store(whisper && !use_gpu, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Checks that a Whisper model fits in the free GPU memory, see `vram_guard`.
+    /// Returns the replacement to load or the CPU to load it on if the settings ask
+    /// for one.
+    fn check_vram(&self, model_id: &str) -> Result<VramCheck> {
+        let behavior = get_settings(&self.app_handle).vram_guard_behavior;
+        // An unknown model is reported by `load_engine`
+        let Some(requested) = self.model_manager.get_model_info(model_id) else {
+            return Ok(VramCheck::default());
+        };
+        let required_mb = vram_guard::required_mb(&requested);
+        if behavior == VramGuardBehavior::Off || required_mb == 0 {
+            return Ok(VramCheck::default());
+        }
+        let Some(free_mb) = vram_guard::available_mb() else {
+            debug!("Free GPU memory unknown, loading {} unchecked", model_id);
+            return Ok(VramCheck::default());
+        };
+        // The loaded model is freed before the new one loads
+        let available_mb = free_mb + self.loaded_vram_mb();
+
+        let models = self.model_manager.get_available_models();
+        match vram_guard::decide(&requested, &models, available_mb, behavior) {
+            Outcome::Load => {
+                info!(
+                    "{} needs about {} MB of GPU memory, {} MB free",
+                    model_id, required_mb, available_mb
+                );
+                Ok(VramCheck::default())
+            }
+            Outcome::LoadOnCpu => {
+                warn!(
+                    "{} needs about {} MB of GPU memory but only {} MB is free, loading it on the CPU",
+                    model_id, required_mb, available_mb
+                );
+                Ok(VramCheck {
+                    decision: None,
+                    use_gpu: false,
+                })
+            }
+            Outcome::Substitute(loaded_model) => {
+                warn!(
+                    "{} needs about {} MB of GPU memory but only {} MB is free, loading {} instead",
+                    model_id, required_mb, available_mb, loaded_model
+                );
+                Ok(VramCheck {
+                    decision: Some(VramGuardDecision {
+                        requested_model: model_id.to_string(),
+                        loaded_model,
+                        required_mb,
+                        available_mb,
+                    }),
+                    use_gpu: true,
+                })
+            }
+            Outcome::Refuse(reason) => {
+                warn!("Not loading {}: {}", model_id, reason);
+                emit_event(
+                    &self.app_handle,
+                    AppEvent::ModelStateChanged(ModelStateEvent {
+                        event_type: "loading_failed".to_string(),
+                        model_id: Some(model_id.to_string()),
+                        model_name: Some(requested.name),
+                        error: Some(reason.clone()),
+                    }),
+                );
+                Err(anyhow::anyhow!(reason))
+            }
+        }
+    }
+
+    /// Whether the loaded Whisper model runs on the CPU
+    pub fn loaded_on_cpu(&self) -> bool {
+        self.loaded_on_cpu.load(Ordering::Relaxed)
+    }
+
+    /// Estimated GPU memory the loaded model holds, see `vram_guard::required_mb`
+    fn loaded_vram_mb(&self) -> u64 {
+        if self.loaded_on_cpu() {
+            return 0;
+        }
+        self.get_current_model()
+            .and_then(|id| self.model_manager.get_model_info(&id))
+            .map_or(0, |info| vram_guard::required_mb(&info))
+    }
+
+    fn load_engine(&self, model_id: &str, use_gpu: bool) -> Result<()> {
         let load_start = std::time::Instant::now();
         debug!("Starting to load model: {}", model_id);
 
         // Emit loading started event
-        let _ = self.app_handle.emit(
-            "model-state-changed",
-            ModelStateEvent {
+        emit_event(
+            &self.app_handle,
+            AppEvent::ModelStateChanged(ModelStateEvent {
                 event_type: "loading_started".to_string(),
                 model_id: Some(model_id.to_string()),
                 model_name: None,
                 error: None,
-            },
+            }),
         );
 
         let model_info = self
@@ -208,83 +547,35 @@ impl TranscriptionManager {
 
         if !model_info.is_downloaded {
             let error_msg = "Model not downloaded";
-            let _ = self.app_handle.emit(
-                "model-state-changed",
-                ModelStateEvent {
+            emit_event(
+                &self.app_handle,
+                AppEvent::ModelStateChanged(ModelStateEvent {
                     event_type: "loading_failed".to_string(),
                     model_id: Some(model_id.to_string()),
                     model_name: Some(model_info.name.clone()),
                     error: Some(error_msg.to_string()),
-                },
+                }),
             );
             return Err(anyhow::anyhow!(error_msg));
         }
 
         let model_path = self.model_manager.get_model_path(model_id)?;
+        let loaded_engine = open_engine(model_id, &model_info.engine_type, &model_path, use_gpu)
+            .map_err(|e| {
+                emit_event(
+                    &self.app_handle,
+                    AppEvent::ModelStateChanged(ModelStateEvent {
+                        event_type: "loading_failed".to_string(),
+                        model_id: Some(model_id.to_string()),
+                        model_name: Some(model_info.name.clone()),
+                        error: Some(e.to_string()),
+                    }),
+                );
+                e
+            })?;
 
-        // Create appropriate engine based on model type
-        let loaded_engine = match model_info.engine_type {
-            EngineType::Whisper => {
-                let mut engine = WhisperEngine::new();
-                engine.load_model(&model_path).map_err(|e| {
-                    let error_msg = format!("Failed to load whisper model {}: {}", model_id, e);
-                    let _ = self.app_handle.emit(
-                        "model-state-changed",
-                        ModelStateEvent {
-                            event_type: "loading_failed".to_string(),
-                            model_id: Some(model_id.to_string()),
-                            model_name: Some(model_info.name.clone()),
-                            error: Some(error_msg.clone()),
-                        },
-                    );
-                    anyhow::anyhow!(error_msg)
-                })?;
-                LoadedEngine::Whisper(engine)
-            }
-            EngineType::Parakeet => {
-                let mut engine = ParakeetEngine::new();
-                engine
-                    .load_model_with_params(&model_path, ParakeetModelParams::int8())
-                    .map_err(|e| {
-                        let error_msg =
-                            format!("Failed to load parakeet model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
-                            "model-state-changed",
-                            ModelStateEvent {
-                                event_type: "loading_failed".to_string(),
-                                model_id: Some(model_id.to_string()),
-                                model_name: Some(model_info.name.clone()),
-                                error: Some(error_msg.clone()),
-                            },
-                        );
-                        anyhow::anyhow!(error_msg)
-                    })?;
-                LoadedEngine::Parakeet(engine)
-            }
-            EngineType::Moonshine => {
-                let mut engine = MoonshineEngine::new();
-                engine
-                    .load_model_with_params(
-                        &model_path,
-                        MoonshineModelParams::variant(ModelVariant::Base),
-                    )
-                    .map_err(|e| {
-                        let error_msg =
-                            format!("Failed to load moonshine model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
-                            "model-state-changed",
-                            ModelStateEvent {
-                                event_type: "loading_failed".to_string(),
-                                model_id: Some(model_id.to_string()),
-                                model_name: Some(model_info.name.clone()),
-                                error: Some(error_msg.clone()),
-                            },
-                        );
-                        anyhow::anyhow!(error_msg)
-                    })?;
-                LoadedEngine::Moonshine(engine)
-            }
-        };
+        // A fresh load counts as use, so the idle timeout starts over
+        self.mark_activity();
 
         // Update the current engine and model ID
         {
@@ -297,14 +588,14 @@ impl TranscriptionManager {
         }
 
         // Emit loading completed event
-        let _ = self.app_handle.emit(
-            "model-state-changed",
-            ModelStateEvent {
+        emit_event(
+            &self.app_handle,
+            AppEvent::ModelStateChanged(ModelStateEvent {
                 event_type: "loading_completed".to_string(),
                 model_id: Some(model_id.to_string()),
                 model_name: Some(model_info.name.clone()),
                 error: None,
-            },
+            }),
         );
 
         let load_duration = load_start.elapsed();
@@ -316,6 +607,18 @@ impl TranscriptionManager {
         Ok(())
     }
 
+    /// Loads a downloaded model into an engine of its own, leaving the model used
+    /// for dictation loaded and untouched
+    pub fn load_standalone(&self, model_id: &str) -> Result<StandaloneEngine> {
+        let model_info = self
+            .model_manager
+            .get_model_info(model_id)
+            .filter(|m| m.is_downloaded)
+            .ok_or_else(|| anyhow::anyhow!("Model not downloaded: {}", model_id))?;
+        let model_path = self.model_manager.get_model_path(model_id)?;
+        open_engine(model_id, &model_info.engine_type, &model_path, true).map(StandaloneEngine)
+    }
+
     /// Kicks off the model loading in a background thread if it's not already loaded
     pub fn initiate_model_load(&self) {
         let mut is_loading = self.is_loading.lock().unwrap();
@@ -327,29 +630,137 @@ impl TranscriptionManager {
         let self_clone = self.clone();
         thread::spawn(move || {
             let settings = get_settings(&self_clone.app_handle);
-            if let Err(e) = self_clone.load_model(&settings.selected_model) {
+            let model_id = power_profile::selected_model(&self_clone.app_handle, &settings);
+            if let Err(e) = self_clone.load_model(&model_id) {
                 error!("Failed to load model: {}", e);
             }
-            let mut is_loading = self_clone.is_loading.lock().unwrap();
-            *is_loading = false;
-            self_clone.loading_condvar.notify_all();
+            self_clone.finish_loading();
+        });
+    }
+
+    /// Model a binding transcribes with: its own once downloaded, otherwise the one
+    /// selected for the current power profile
+    pub fn model_for_binding(&self, settings: &AppSettings, binding_id: &str) -> String {
+        settings
+            .bindings
+            .get(binding_id)
+            .and_then(|binding| binding.selected_model.as_ref())
+            .filter(|id| {
+                !id.is_empty()
+                    && self
+                        .model_manager
+                        .get_model_info(id)
+                        .is_some_and(|info| info.is_downloaded)
+            })
+            .cloned()
+            .unwrap_or_else(|| power_profile::selected_model(&self.app_handle, settings))
+    }
+
+    /// Whether `model_id` is loaded, or its replacement from the VRAM guard
+    fn serves(&self, model_id: &str) -> bool {
+        self.get_current_model().as_deref() == Some(model_id)
+            || self
+                .vram_guard
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|decision| decision.requested_model == model_id)
+    }
+
+    /// Like `initiate_model_load`, but replaces a loaded model other than `model_id`.
+    /// During a load the switch is queued and follows it; the latest request wins.
+    pub fn initiate_model_switch(&self, model_id: String) {
+        let mut is_loading = self.is_loading.lock().unwrap();
+        if model_id.is_empty() {
+            return;
+        }
+        if *is_loading {
+            debug!("Queueing the switch to {} after the current load", model_id);
+            *self.queued_switch.lock().unwrap() = Some(model_id);
+            return;
+        }
+        if self.serves(&model_id) {
+            return;
+        }
+
+        *is_loading = true;
+        let self_clone = self.clone();
+        thread::spawn(move || {
+            if let Err(e) = self_clone.switch_to(&model_id) {
+                error!("Failed to load model {}: {}", model_id, e);
+            }
+            self_clone.finish_loading();
         });
     }
 
+    /// Waits for a load in progress, then loads `model_id` unless it is already
+    /// the one transcribing, so a recording isn't transcribed by a model it didn't
+    /// ask for
+    pub fn ensure_model(&self, model_id: &str) -> Result<()> {
+        let mut is_loading = self.is_loading.lock().unwrap();
+        while *is_loading {
+            is_loading = self.loading_condvar.wait(is_loading).unwrap();
+        }
+        if model_id.is_empty() || self.serves(model_id) {
+            return Ok(());
+        }
+        *is_loading = true;
+        drop(is_loading);
+        info!("Loading {} before transcribing", model_id);
+        let switched = self.switch_to(model_id);
+        self.finish_loading();
+        switched
+    }
+
+    /// Replaces a loaded model other than `model_id` with it
+    fn switch_to(&self, model_id: &str) -> Result<()> {
+        if self.serves(model_id) {
+            return Ok(());
+        }
+        // Free the current model first so only one is held in memory
+        if self.is_model_loaded() {
+            debug!("Switching to model {}", model_id);
+            self.unload_model()?;
+        }
+        self.load_model(model_id)
+    }
+
+    /// Loads the switch queued during a load, if any, then wakes the transcriptions
+    /// waiting for loading to end
+    fn finish_loading(&self) {
+        loop {
+            let mut is_loading = self.is_loading.lock().unwrap();
+            let queued = self.queued_switch.lock().unwrap().take();
+            let Some(model_id) = queued else {
+                *is_loading = false;
+                self.loading_condvar.notify_all();
+                return;
+            };
+            drop(is_loading);
+            if let Err(e) = self.switch_to(&model_id) {
+                error!("Failed to load model {}: {}", model_id, e);
+            }
+        }
+    }
+
     pub fn get_current_model(&self) -> Option<String> {
         let current_model = self.current_model_id.lock().unwrap();
         current_model.clone()
     }
 
-    pub fn transcribe(&self, audio: Vec<f32>) -> Result<String> {
+    pub fn transcribe(&self, audio: Vec<f32>) -> Result<Transcription> {
+        let options = TranscriptionOptions::from_settings(&get_settings(&self.app_handle));
+        self.transcribe_with(audio, &options)
+    }
+
+    /// Transcribes with explicit options instead of the current settings
+    pub fn transcribe_with(
+        &self,
+        audio: Vec<f32>,
+        options: &TranscriptionOptions,
+    ) -> Result<Transcription> {
         // Update last activity timestamp
-        self.last_activity.store(
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            Ordering::Relaxed,
-        );
+        self.mark_activity();
 
         let st = std::time::Instant::now();
 
@@ -357,8 +768,16 @@ impl TranscriptionManager {
 
         if audio.is_empty() {
             debug!("Empty audio vector");
-            self.maybe_unload_immediately("empty audio");
-            return Ok(String::new());
+            if !options.keep_loaded {
+                self.maybe_unload_immediately("empty audio");
+            }
+            return Ok(Transcription {
+                text: String::new(),
+                translation_check: None,
+                word_corrections: None,
+                speaker_turns: None,
+                vram_guard: None,
+            });
         }
 
         // Check if model is loaded, if not try to load it
@@ -375,76 +794,91 @@ impl TranscriptionManager {
             }
         }
 
-        // Get current settings for configuration
-        let settings = get_settings(&self.app_handle);
-
-        // Perform transcription with the appropriate engine
-        let result = {
-            let mut engine_guard = self.engine.lock().unwrap();
-            let engine = engine_guard.as_mut().ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Model failed to load after auto-load attempt. Please check your model settings."
-                )
-            })?;
-
-            match engine {
-                LoadedEngine::Whisper(whisper_engine) => {
-                    // Normalize language code for Whisper
-                    // Convert zh-Hans and zh-Hant to zh since Whisper uses ISO 639-1 codes
-                    let whisper_language = if settings.selected_language == "auto" {
-                        None
-                    } else {
-                        let normalized = if settings.selected_language == "zh-Hans"
-                            || settings.selected_language == "zh-Hant"
-                        {
-                            "zh".to_string()
-                        } else {
-                            settings.selected_language.clone()
-                        };
-                        Some(normalized)
-                    };
-
-                    let params = WhisperInferenceParams {
-                        language: whisper_language,
-                        translate: settings.translate_to_english,
-                        ..Default::default()
-                    };
-
-                    whisper_engine
-                        .transcribe_samples(audio, Some(params))
-                        .map_err(|e| anyhow::anyhow!("Whisper transcription failed: {}", e))?
-                }
-                LoadedEngine::Parakeet(parakeet_engine) => {
-                    let params = ParakeetInferenceParams {
-                        timestamp_granularity: TimestampGranularity::Segment,
-                        ..Default::default()
-                    };
-                    parakeet_engine
-                        .transcribe_samples(audio, Some(params))
-                        .map_err(|e| anyhow::anyhow!("Parakeet transcription failed: {}", e))?
-                }
-                LoadedEngine::Moonshine(moonshine_engine) => moonshine_engine
-                    .transcribe_samples(audio, None)
-                    .map_err(|e| anyhow::anyhow!("Moonshine transcription failed: {}", e))?,
-            }
+        // Speaker turns are transcribed separately to put a marker between them,
+        // see `speaker_turns`
+        let turns: Vec<Range<usize>> = if options.speaker_change_hints {
+            detect_turns(&audio)
+        } else {
+            std::iter::once(0..audio.len()).collect()
         };
 
-        // Apply word correction if custom words are configured
-        let corrected_result = if !settings.custom_words.is_empty() {
-            apply_custom_words(
-                &result.text,
-                &settings.custom_words,
-                settings.word_correction_threshold,
-            )
+        // Long recordings are transcribed in chunks, see `chunking`
+        let ranges: Vec<(usize, Range<usize>)> = turns
+            .iter()
+            .enumerate()
+            .flat_map(|(turn, range)| {
+                chunk_ranges(&audio[range.clone()], options.chunk_seconds)
+                    .into_iter()
+                    .map(move |r| (turn, range.start + r.start..range.start + r.end))
+            })
+            .collect();
+        let chunks: Vec<(usize, Vec<f32>)> = if ranges.len() == 1 {
+            vec![(0, audio)]
         } else {
-            result.text
+            info!(
+                "Transcribing {} samples in {} chunks over {} turns",
+                audio.len(),
+                ranges.len(),
+                turns.len()
+            );
+            ranges
+                .into_iter()
+                .map(|(turn, r)| (turn, audio[r].to_vec()))
+                .collect()
         };
 
-        // Filter out filler words and hallucinations
-        let filtered_result = filter_transcription_output(&corrected_result);
+        // Perform transcription with the appropriate engine
+        let mut texts: Vec<Vec<(String, usize)>> = vec![Vec::new(); turns.len()];
+        let mut translation_check: Option<TranslationCheck> = None;
+        // The engine is locked per chunk, so a retry's back-off or a long recording
+        // doesn't keep other transcriptions waiting
+        for (turn, chunk) in chunks {
+            let len = chunk.len();
+            let (text, check) = self.run_engine_with_retries(chunk, options)?;
+            texts[turn].push((text, len));
+            // A single suspect chunk makes the whole transcript suspect
+            if translation_check.as_ref().is_none_or(|c| !c.suspect) {
+                translation_check = check.or(translation_check);
+            }
+        }
+        if let Some(check) = translation_check.as_ref().filter(|c| c.suspect) {
+            emit_event(
+                &self.app_handle,
+                AppEvent::TranslationSuspect(check.clone()),
+            );
+        }
+
+        // Each turn is cleaned up on its own, the clean up would flatten the marker
+        let mut applied = Vec::new();
+        let mut kept_turns = Vec::new();
+        let mut turn_texts = Vec::new();
+        for (mut chunk_texts, range) in texts.into_iter().zip(&turns) {
+            let text = if chunk_texts.len() == 1 {
+                chunk_texts.remove(0).0
+            } else {
+                stitch(&chunk_texts)
+            };
+            let text = clean_up_transcript(&text, options, &mut applied);
+            // A turn that came out empty gets no marker
+            if !text.is_empty() {
+                kept_turns.push(SpeakerTurn::from_samples(range));
+                turn_texts.push(text);
+            }
+        }
+        let final_result = turn_texts.join(&options.speaker_change_marker);
+
+        if !applied.is_empty() {
+            debug!("Applied {} custom word corrections", applied.len());
+        }
+        let word_corrections = options.has_custom_words().then(|| WordCorrections {
+            threshold: options.word_correction_threshold,
+            algorithm: options.correction_algorithm,
+            applied,
+        });
+        let speaker_turns = options.speaker_change_hints.then_some(kept_turns);
 
         let et = std::time::Instant::now();
-        let translation_note = if settings.translate_to_english {
+        let translation_note = if options.translate_to_english {
             " (translated)"
         } else {
             ""
@@ -455,18 +889,271 @@ impl TranscriptionManager {
             translation_note
         );
 
-        let final_result = filtered_result;
-
         if final_result.is_empty() {
             info!("Transcription result is empty");
         } else {
             info!("Transcription result: {}", final_result);
         }
 
-        self.maybe_unload_immediately("transcription");
+        // Read before the model may be unloaded, which forgets the decision
+        let vram_guard = self.vram_guard.lock().unwrap().clone();
+        if !options.keep_loaded {
+            self.maybe_unload_immediately("transcription");
+        }
 
-        Ok(final_result)
+        Ok(Transcription {
+            text: final_result,
+            translation_check,
+            word_corrections,
+            speaker_turns,
+            vram_guard,
+        })
     }
+
+    /// Retries inference that failed for a reason that can pass, see `is_transient`.
+    /// The engine isn't locked while backing off.
+    fn run_engine_with_retries(
+        &self,
+        audio: Vec<f32>,
+        options: &TranscriptionOptions,
+    ) -> Result<(String, Option<TranslationCheck>)> {
+        let mut attempt = 0;
+        loop {
+            let input = if attempt < options.retry_count {
+                audio.clone()
+            } else {
+                return self.run_loaded_engine(audio, options);
+            };
+            match self.run_loaded_engine(input, options) {
+                Ok(result) => return Ok(result),
+                Err(e) if !is_transient(&e) => return Err(e),
+                Err(e) => {
+                    attempt += 1;
+                    warn!(
+                        "Transcription failed, retrying ({}/{}): {}",
+                        attempt, options.retry_count, e
+                    );
+                    emit_event(
+                        &self.app_handle,
+                        AppEvent::TranscriptionRetrying(TranscriptionRetrying {
+                            attempt,
+                            max_retries: options.retry_count,
+                            error: e.to_string(),
+                        }),
+                    );
+                    thread::sleep(RETRY_BACKOFF * attempt);
+                }
+            }
+        }
+    }
+
+    /// Runs one inference, holding the engine lock only for its duration
+    fn run_loaded_engine(
+        &self,
+        audio: Vec<f32>,
+        options: &TranscriptionOptions,
+    ) -> Result<(String, Option<TranslationCheck>)> {
+        let mut engine_guard = self.engine.lock().unwrap();
+        let engine = engine_guard.as_mut().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Model failed to load after auto-load attempt. Please check your model settings."
+            )
+        })?;
+        run_engine(engine, audio, options)
+    }
+}
+
+/// Whether a failed inference may succeed when retried, e.g. after a GPU or
+/// memory allocation failed while another app held the memory. Errors from the
+/// input or the model, or a model that is no longer loaded, fail every time.
+fn is_transient(error: &anyhow::Error) -> bool {
+    if let Some(error) = error.downcast_ref::<WhisperError>() {
+        return matches!(
+            error,
+            WhisperError::FailedToEncode
+                | WhisperError::FailedToDecode
+                | WhisperError::UnableToCalculateEvaluation
+                | WhisperError::GenericError(_)
+        );
+    }
+    // The ONNX engines only report messages
+    let message = error.to_string().to_lowercase();
+    ["memory", "alloc", "resource", "timed out", "timeout"]
+        .iter()
+        .any(|hint| message.contains(hint))
+}
+
+fn word_matcher(algorithm: CorrectionAlgorithm) -> &'static dyn WordMatcher {
+    match algorithm {
+        CorrectionAlgorithm::Levenshtein => &Levenshtein,
+        CorrectionAlgorithm::DamerauLevenshtein => &DamerauLevenshtein,
+        CorrectionAlgorithm::Jaro => &Jaro,
+        CorrectionAlgorithm::Phonetic => &Phonetic,
+    }
+}
+
+/// Custom-word correction, filler and hallucination filtering of raw engine
+/// output. Replaced words are added to `applied`.
+fn clean_up_transcript(
+    text: &str,
+    options: &TranscriptionOptions,
+    applied: &mut Vec<WordCorrection>,
+) -> String {
+    // Apply word correction if custom words are configured
+    let custom_words = options.custom_words_for(text);
+    let corrected = if !custom_words.is_empty() {
+        let (corrected, changes) = apply_custom_words_with_changes(
+            text,
+            &custom_words,
+            options.word_correction_threshold,
+            word_matcher(options.correction_algorithm),
+        );
+        applied.extend(
+            changes
+                .into_iter()
+                .map(|(from, to)| WordCorrection { from, to }),
+        );
+        corrected
+    } else {
+        text.to_string()
+    };
+
+    // Filter out filler words and hallucinations
+    let filtered = filter_transcription_output(&corrected);
+
+    // Collapse phrases Whisper looped on, typically over silence or music
+    if options.anti_hallucination {
+        collapse_repeated_phrases(&filtered, options.hallucination_max_repeats as usize)
+    } else {
+        filtered
+    }
+}
+
+/// Reads a model from disk into the engine for its type. Only Whisper uses the
+/// GPU, and only if `use_gpu`.
+fn open_engine(
+    model_id: &str,
+    engine_type: &EngineType,
+    model_path: &Path,
+    use_gpu: bool,
+) -> Result<LoadedEngine> {
+    let engine = match engine_type {
+        EngineType::Whisper => {
+            let engine = WhisperDecoder::load(model_path, use_gpu)
+                .map_err(|e| anyhow::anyhow!("Failed to load whisper model {}: {}", model_id, e))?;
+            LoadedEngine::Whisper(engine)
+        }
+        EngineType::Parakeet => {
+            let mut engine = ParakeetEngine::new();
+            engine
+                .load_model_with_params(model_path, ParakeetModelParams::int8())
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to load parakeet model {}: {}", model_id, e)
+                })?;
+            LoadedEngine::Parakeet(engine)
+        }
+        EngineType::Moonshine => {
+            let mut engine = MoonshineEngine::new();
+            engine
+                .load_model_with_params(
+                    model_path,
+                    MoonshineModelParams::variant(ModelVariant::Base),
+                )
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to load moonshine model {}: {}", model_id, e)
+                })?;
+            LoadedEngine::Moonshine(engine)
+        }
+    };
+    Ok(engine)
+}
+
+/// Runs one transcription on the loaded engine, with the translation guard's verdict
+fn run_engine(
+    engine: &mut LoadedEngine,
+    audio: Vec<f32>,
+    options: &TranscriptionOptions,
+) -> Result<(String, Option<TranslationCheck>)> {
+    let mut translation_check = None;
+    let text = match engine {
+        LoadedEngine::Whisper(whisper_engine) => {
+            // Normalize language code for Whisper
+            // Convert zh-Hans and zh-Hant to zh since Whisper uses ISO 639-1 codes
+            let whisper_language = if options.language == "auto" {
+                None
+            } else {
+                let normalized = if options.language == "zh-Hans" || options.language == "zh-Hant" {
+                    "zh".to_string()
+                } else {
+                    options.language.clone()
+                };
+                Some(normalized)
+            };
+
+            let guard = if options.translate_to_english {
+                options.translation_guard
+            } else {
+                TranslationGuard::Off
+            };
+
+            let decoding = options.decoding.clamped();
+            let mut text = whisper_engine
+                .transcribe(
+                    &audio,
+                    whisper_language.as_deref(),
+                    options.translate_to_english,
+                    &decoding,
+                )
+                .map_err(|e| {
+                    // Kept as the source so retries can tell what failed
+                    anyhow::Error::new(e).context(format!("Whisper transcription failed: {}", e))
+                })?;
+
+            if guard != TranslationGuard::Off {
+                let mut check = check_english(&text);
+
+                if check.suspect && guard == TranslationGuard::Retry {
+                    // Forcing the English language token makes whisper decode into English
+                    warn!(
+                        "Translated output looks like {:?}, retrying with English forced",
+                        check.detected_language
+                    );
+                    match whisper_engine.transcribe(&audio, Some("en"), true, &decoding) {
+                        Ok(retry_text) => {
+                            let retry_check = check_english(&retry_text);
+                            if !retry_check.suspect {
+                                text = retry_text;
+                                check = retry_check;
+                            }
+                        }
+                        Err(e) => warn!("Translation retry failed: {}", e),
+                    }
+                    check.retried = true;
+                }
+
+                translation_check = Some(check);
+            }
+
+            text
+        }
+        LoadedEngine::Parakeet(parakeet_engine) => {
+            let params = ParakeetInferenceParams {
+                timestamp_granularity: TimestampGranularity::Segment,
+                ..Default::default()
+            };
+            parakeet_engine
+                .transcribe_samples(audio, Some(params))
+                .map_err(|e| anyhow::anyhow!("Parakeet transcription failed: {}", e))?
+                .text
+        }
+        LoadedEngine::Moonshine(moonshine_engine) => {
+            moonshine_engine
+                .transcribe_samples(audio, None)
+                .map_err(|e| anyhow::anyhow!("Moonshine transcription failed: {}", e))?
+                .text
+        }
+    };
+    Ok((text, translation_check))
 }
 
 impl Drop for TranscriptionManager {
@@ -486,3 +1173,28 @@ impl Drop for TranscriptionManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let whisper =
+            |e: WhisperError| anyhow::Error::new(e).context("Whisper transcription failed");
+        assert!(is_transient(&whisper(WhisperError::FailedToEncode)));
+        assert!(is_transient(&whisper(WhisperError::GenericError(-6))));
+        assert!(!is_transient(&whisper(WhisperError::NoSamples)));
+        assert!(!is_transient(&whisper(WhisperError::InvalidText)));
+
+        assert!(is_transient(&anyhow::anyhow!(
+            "Parakeet transcription failed: Failed to allocate memory for requested buffer"
+        )));
+        assert!(!is_transient(&anyhow::anyhow!(
+            "Parakeet transcription failed: Invalid input shape"
+        )));
+        assert!(!is_transient(&anyhow::anyhow!(
+            "Model failed to load after auto-load attempt. Please check your model settings."
+        )));
+    }
+}
@@ -0,0 +1,377 @@
+//! Microphone quality check: the user reads `CHECK_SENTENCE` aloud for a few
+//! seconds, and the recording is measured and transcribed to tell whether the
+//! microphone is good enough for dictation.
+//!
+//! The clip is measured for signal-to-noise ratio, clipping, DC offset and the
+//! sample rate the device actually delivers, then transcribed with the current
+//! model and compared with the sentence. The recording only lives in memory and is
+//! dropped once analysed, it never reaches history.
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::{get_cpal_host, list_input_devices, AudioRecorder};
+use crate::error::HandyError;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::transcription::{TranscriptionManager, TranscriptionOptions};
+use crate::settings::get_settings;
+use cpal::traits::{DeviceTrait, HostTrait};
+use log::info;
+use serde::Serialize;
+use specta::Type;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Read aloud during the check, it covers most English sounds
+pub const CHECK_SENTENCE: &str =
+    "The quick brown fox jumps over the lazy dog while five wizards box on a sunny hill.";
+const RECORD_DURATION: Duration = Duration::from_secs(5);
+/// Lets the stream settle before the clip starts
+const WARM_UP: Duration = Duration::from_millis(300);
+/// Frames over which the level is measured
+const FRAME_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize / 50;
+/// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.99;
+/// Cap of the SNR, reached when the quiet frames are digital silence
+const MAX_SNR_DB: f32 = 60.0;
+
+// Thresholds of the suggestions
+const LOW_SNR_DB: f32 = 20.0;
+const POOR_SNR_DB: f32 = 10.0;
+const QUIET_SPEECH_RMS: f32 = 0.03;
+const CLIPPING_RATIO: f32 = 0.001;
+const POOR_CLIPPING_RATIO: f32 = 0.01;
+const DC_OFFSET: f32 = 0.02;
+/// Delivered sample rate off by more than this share of the nominal rate
+const RATE_TOLERANCE: f32 = 0.05;
+const OK_WER: f32 = 0.1;
+const POOR_WER: f32 = 0.3;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MicVerdict {
+    Good,
+    Ok,
+    Poor,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct MicAnalysis {
+    /// The device that was checked, None for the system default
+    pub device: Option<String>,
+    pub sentence: String,
+    pub transcript: String,
+    /// Word error rate of the transcript against the sentence, 0 when they match
+    pub word_error_rate: f32,
+    pub snr_db: f32,
+    /// Share of samples at full scale, between 0 and 1
+    pub clipping_ratio: f32,
+    pub dc_offset: f32,
+    /// RMS of the loudest frames, i.e. how loud the speech came through
+    pub speech_rms: f32,
+    /// Sample rate the device claims to capture at
+    pub device_sample_rate: u32,
+    /// Sample rate the device delivered during the check
+    pub delivered_sample_rate: u32,
+    pub verdict: MicVerdict,
+    /// What the user can do about it, empty when the verdict is good
+    pub suggestions: Vec<String>,
+}
+
+/// Level measurements of a clip
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SignalStats {
+    snr_db: f32,
+    clipping_ratio: f32,
+    dc_offset: f32,
+    speech_rms: f32,
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.unicode_words().map(str::to_lowercase).collect()
+}
+
+/// Word error rate of `hypothesis` against `reference`: the substitutions,
+/// deletions and insertions needed to turn one into the other, over the number of
+/// reference words. Case and punctuation are ignored.
+pub fn word_error_rate(reference: &str, hypothesis: &str) -> f32 {
+    let reference = words(reference);
+    let hypothesis = words(hypothesis);
+    if reference.is_empty() {
+        return if hypothesis.is_empty() { 0.0 } else { 1.0 };
+    }
+    // Edit distance over words, one row at a time
+    let mut previous: Vec<usize> = (0..=hypothesis.len()).collect();
+    for (i, expected) in reference.iter().enumerate() {
+        let mut current = vec![i + 1; hypothesis.len() + 1];
+        for (j, heard) in hypothesis.iter().enumerate() {
+            let substitution = previous[j] + usize::from(expected != heard);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[hypothesis.len()] as f32 / reference.len() as f32
+}
+
+fn measure(samples: &[f32]) -> SignalStats {
+    if samples.is_empty() {
+        return SignalStats {
+            snr_db: 0.0,
+            clipping_ratio: 0.0,
+            dc_offset: 0.0,
+            speech_rms: 0.0,
+        };
+    }
+    let dc_offset = samples.iter().sum::<f32>() / samples.len() as f32;
+    let clipped = samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+
+    let mut levels: Vec<f32> = samples
+        .chunks(FRAME_SAMPLES)
+        .map(|frame| {
+            let power = frame.iter().map(|s| (s - dc_offset).powi(2)).sum::<f32>();
+            (power / frame.len() as f32).sqrt()
+        })
+        .collect();
+    levels.sort_by(|a, b| a.total_cmp(b));
+    // The quietest frames are the pauses, the loudest the speech
+    let percentile = |p: usize| levels[(levels.len() - 1) * p / 100];
+    let noise = percentile(10);
+    let speech = percentile(90);
+    let snr_db = if noise > 0.0 {
+        (20.0 * (speech / noise).log10()).clamp(0.0, MAX_SNR_DB)
+    } else if speech > 0.0 {
+        MAX_SNR_DB
+    } else {
+        0.0
+    };
+
+    SignalStats {
+        snr_db,
+        clipping_ratio: clipped as f32 / samples.len() as f32,
+        dc_offset,
+        speech_rms: speech,
+    }
+}
+
+/// Sample rate the device delivered, from the number of 16 kHz samples it
+/// produced over `elapsed`
+fn delivered_rate(samples: usize, elapsed: Duration, device_rate: u32) -> u32 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds <= 0.0 {
+        return device_rate;
+    }
+    (samples as f64 / seconds * device_rate as f64 / WHISPER_SAMPLE_RATE as f64).round() as u32
+}
+
+fn assess(
+    stats: &SignalStats,
+    word_error_rate: f32,
+    device_rate: u32,
+    delivered_rate: u32,
+) -> (MicVerdict, Vec<String>) {
+    let mut suggestions = Vec::new();
+    let mut poor = false;
+
+    if stats.clipping_ratio > CLIPPING_RATIO {
+        poor |= stats.clipping_ratio > POOR_CLIPPING_RATIO;
+        suggestions.push(
+            "Reduce the input gain or move back from the microphone, the signal clips.".to_string(),
+        );
+    } else if stats.speech_rms < QUIET_SPEECH_RMS {
+        suggestions.push(
+            "Increase the input gain or move closer to the microphone, your voice is quiet."
+                .to_string(),
+        );
+    }
+    if stats.snr_db < LOW_SNR_DB {
+        poor |= stats.snr_db < POOR_SNR_DB;
+        suggestions
+            .push("Reduce background noise, or use a microphone closer to your mouth.".to_string());
+    }
+    if stats.dc_offset.abs() > DC_OFFSET {
+        suggestions.push(
+            "The signal has a DC offset, try another port or turn off the device's audio enhancements."
+                .to_string(),
+        );
+    }
+    let rate_error = (delivered_rate as f32 - device_rate as f32).abs() / device_rate.max(1) as f32;
+    if rate_error > RATE_TOLERANCE {
+        poor = true;
+        suggestions.push(format!(
+            "Device resampling detected: it claims {} Hz but delivered {} Hz. Set the device to {} Hz in your system's sound settings.",
+            device_rate, delivered_rate, device_rate
+        ));
+    }
+    if word_error_rate > OK_WER && suggestions.is_empty() {
+        suggestions.push(
+            "Speak clearly at a steady pace, or try a larger transcription model.".to_string(),
+        );
+    }
+    poor |= word_error_rate > POOR_WER;
+
+    let verdict = if poor {
+        MicVerdict::Poor
+    } else if !suggestions.is_empty() || word_error_rate > OK_WER {
+        MicVerdict::Ok
+    } else {
+        MicVerdict::Good
+    };
+    (verdict, suggestions)
+}
+
+/// The named device, or the selected microphone and then the system default
+fn resolve_device(
+    app: &AppHandle,
+    device: Option<String>,
+) -> Result<(Option<String>, cpal::Device), HandyError> {
+    let name = device
+        .or_else(|| get_settings(app).selected_microphone)
+        .filter(|name| !name.eq_ignore_ascii_case("default"));
+    let Some(name) = name else {
+        let device = get_cpal_host()
+            .default_input_device()
+            .ok_or_else(|| HandyError::not_found("No input device found"))?;
+        return Ok((None, device));
+    };
+    let devices = list_input_devices()
+        .map_err(|e| HandyError::permission(format!("Failed to list input devices: {}", e)))?;
+    let device = devices
+        .into_iter()
+        .find(|d| d.name == name)
+        .ok_or_else(|| HandyError::not_found(format!("Input device '{}' not found", name)))?;
+    Ok((Some(name), device.device))
+}
+
+/// Records the clip, returning its 16 kHz samples and how long it recorded
+fn record(device: cpal::Device) -> Result<(Vec<f32>, Duration), HandyError> {
+    let mut recorder = AudioRecorder::new().map_err(|e| HandyError::internal(e.to_string()))?;
+    let recorded = (|| {
+        recorder.open(Some(device))?;
+        thread::sleep(WARM_UP);
+        let started = Instant::now();
+        recorder.start()?;
+        thread::sleep(RECORD_DURATION);
+        let samples = recorder.stop()?;
+        Ok::<_, Box<dyn std::error::Error>>((samples, started.elapsed()))
+    })();
+    let _ = recorder.close();
+    recorded.map_err(|e| HandyError::io(format!("Failed to record from the microphone: {}", e)))
+}
+
+pub async fn analyze(app: &AppHandle, device: Option<String>) -> Result<MicAnalysis, HandyError> {
+    if app.state::<Arc<AudioRecordingManager>>().is_recording() {
+        return Err(HandyError::invalid_input(
+            "A recording is in progress, try again once it's done",
+        ));
+    }
+    let (device_name, device) = resolve_device(app, device)?;
+    let device_rate = AudioRecorder::input_sample_rate(&device).unwrap_or(WHISPER_SAMPLE_RATE);
+    let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+    let settings = get_settings(app);
+    // Loads while recording, the transcription waits for it
+    tm.initiate_model_switch(tm.model_for_binding(&settings, "transcribe"));
+
+    let analysis = tauri::async_runtime::spawn_blocking(move || {
+        let (samples, elapsed) = record(device)?;
+        let stats = measure(&samples);
+        let delivered = delivered_rate(samples.len(), elapsed, device_rate);
+
+        let mut options = TranscriptionOptions::from_settings(&settings);
+        // The sentence is English whatever the dictation language
+        options.language = "en".to_string();
+        options.translate_to_english = false;
+        options.speaker_change_hints = false;
+        options.custom_words.clear();
+        options.custom_words_by_language.clear();
+        options.retry_count = 0;
+        let transcript = tm.transcribe_with(samples, &options)?.text;
+        let wer = word_error_rate(CHECK_SENTENCE, &transcript);
+        let (verdict, suggestions) = assess(&stats, wer, device_rate, delivered);
+        Ok::<_, HandyError>(MicAnalysis {
+            device: device_name,
+            sentence: CHECK_SENTENCE.to_string(),
+            transcript,
+            word_error_rate: wer,
+            snr_db: stats.snr_db,
+            clipping_ratio: stats.clipping_ratio,
+            dc_offset: stats.dc_offset,
+            speech_rms: stats.speech_rms,
+            device_sample_rate: device_rate,
+            delivered_sample_rate: delivered,
+            verdict,
+            suggestions,
+        })
+    })
+    .await
+    .map_err(|e| HandyError::internal(format!("Microphone check failed: {}", e)))??;
+
+    info!(
+        "Microphone check: {:?}, SNR {:.1} dB, WER {:.2}, {} Hz delivered of {} Hz",
+        analysis.verdict,
+        analysis.snr_db,
+        analysis.word_error_rate,
+        analysis.delivered_sample_rate,
+        analysis.device_sample_rate
+    );
+    Ok(analysis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_error_rate_counts_edits_per_reference_word() {
+        let reference = "the quick brown fox";
+        assert_eq!(word_error_rate(reference, "The quick, brown fox."), 0.0);
+        // One substitution
+        assert_eq!(word_error_rate(reference, "the quick brown box"), 0.25);
+        // One deletion and one insertion
+        assert_eq!(word_error_rate(reference, "quick brown fox jumps"), 0.5);
+        assert_eq!(word_error_rate(reference, ""), 1.0);
+        assert_eq!(word_error_rate("", ""), 0.0);
+        // Insertions can take it past 1
+        assert!(word_error_rate("fox", "a quick brown fox") > 1.0);
+    }
+
+    #[test]
+    fn clean_speech_is_good_and_problems_get_suggestions() {
+        // Speech-like tone with pauses and faint noise
+        let samples: Vec<f32> = (0..WHISPER_SAMPLE_RATE as usize * 2)
+            .map(|i| {
+                let noise = if i % 2 == 0 { 0.001 } else { -0.001 };
+                let speaking = (i / 8_000) % 2 == 0;
+                let tone = (i as f32 * 0.05).sin() * 0.3;
+                if speaking {
+                    tone + noise
+                } else {
+                    noise
+                }
+            })
+            .collect();
+        let stats = measure(&samples);
+        assert!(stats.snr_db > 40.0);
+        assert_eq!(stats.clipping_ratio, 0.0);
+        assert_eq!(assess(&stats, 0.0, 48_000, 48_000).0, MicVerdict::Good);
+
+        let clipped: Vec<f32> = samples
+            .iter()
+            .map(|s| (s * 10.0).clamp(-1.0, 1.0))
+            .collect();
+        let (verdict, suggestions) = assess(&measure(&clipped), 0.0, 48_000, 48_000);
+        assert_eq!(verdict, MicVerdict::Poor);
+        assert_eq!(suggestions.len(), 1);
+
+        let (verdict, suggestions) = assess(&stats, 0.0, 48_000, 44_100);
+        assert_eq!(verdict, MicVerdict::Poor);
+        assert!(suggestions[0].starts_with("Device resampling detected"));
+    }
+
+    #[test]
+    fn delivered_rate_scales_back_to_the_device_rate() {
+        let five_seconds = Duration::from_secs(5);
+        assert_eq!(delivered_rate(80_000, five_seconds, 48_000), 48_000);
+        assert_eq!(delivered_rate(73_500, five_seconds, 48_000), 44_100);
+    }
+}
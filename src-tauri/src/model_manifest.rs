@@ -0,0 +1,385 @@
+//! Catalog of the downloadable models.
+//!
+//! The catalog ships with the app in `resources/model_manifest.json`. When
+//! `model_manifest_url` is set, a newer one is fetched from there at most once a
+//! day, checked against the SHA-256 published next to it at `<url>.sha256`, and
+//! cached in the app data directory so it also applies offline. A fetched catalog
+//! only adds and updates models, and one that can't be fetched, verified or read
+//! is ignored, so models already downloaded stay usable whatever the server does.
+//!
+//! Downloads are checked against the SHA-256 their entry lists.
+//! `scripts/update-model-hashes.ts` fills those in for the bundled catalog.
+
+use crate::managers::model::{
+    EngineType, ModelInfo, ModelManager, QualityTier, QuantizationVariant,
+};
+use crate::settings::get_settings;
+use log::{info, warn};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager};
+
+const BUNDLED: &str = include_str!("../resources/model_manifest.json");
+/// Manifest layout this build understands
+const SCHEMA: u32 = 1;
+pub const CACHE_FILE: &str = "model_manifest.json";
+/// Age at which the cached manifest is fetched again
+const MAX_CACHE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// Pause after a fetch before trying again, so an unreachable server isn't asked
+/// every time the model list is shown
+const RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+static LAST_ATTEMPT: Mutex<Option<Instant>> = Mutex::new(None);
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModelManifest {
+    pub schema: u32,
+    /// Raised with every published change, a cached or fetched manifest is only
+    /// used over the bundled one when its version is higher
+    pub version: u32,
+    pub models: Vec<ManifestModel>,
+    /// Files that support the app rather than transcribe, see
+    /// `ModelManager::ensure_auxiliary`
+    #[serde(default)]
+    pub auxiliary: Vec<AuxiliaryFile>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AuxiliaryFile {
+    /// Subdirectory of the models directory, e.g. `wake_word`
+    pub kind: String,
+    pub filename: String,
+    pub url: String,
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ManifestModel {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub engine_type: EngineType,
+    pub filename: String,
+    pub url: Option<String>,
+    pub is_directory: bool,
+    pub size_mb: u64,
+    /// Of the downloaded file, or of the archive for directory-based models
+    #[serde(default)]
+    pub sha256: Option<String>,
+    pub parameters_m: u32,
+    pub languages: Vec<String>,
+    pub quality_tier: QualityTier,
+    pub recommended_ram_mb: u32,
+    #[serde(default)]
+    pub recommended_vram_mb: Option<u32>,
+    pub accuracy_score: f32,
+    pub speed_score: f32,
+    #[serde(default)]
+    pub variants: Vec<ManifestVariant>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ManifestVariant {
+    pub id: String,
+    pub filename: String,
+    pub url: Option<String>,
+    pub size_mb: u64,
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+impl ManifestModel {
+    /// Catalog entry for the model, not downloaded yet
+    pub fn into_info(self) -> ModelInfo {
+        ModelInfo {
+            id: self.id,
+            name: self.name,
+            description: self.description,
+            filename: self.filename,
+            url: self.url,
+            size_mb: self.size_mb,
+            is_downloaded: false,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: self.is_directory,
+            engine_type: self.engine_type,
+            accuracy_score: self.accuracy_score,
+            speed_score: self.speed_score,
+            variants: self
+                .variants
+                .into_iter()
+                .map(|v| QuantizationVariant {
+                    id: v.id,
+                    filename: v.filename,
+                    url: v.url,
+                    size_mb: v.size_mb,
+                    sha256: v.sha256,
+                    is_downloaded: false,
+                })
+                .collect(),
+            quantization: None,
+            parameters_m: self.parameters_m,
+            languages: self.languages,
+            quality_tier: self.quality_tier,
+            recommended_ram_mb: self.recommended_ram_mb,
+            recommended_vram_mb: self.recommended_vram_mb,
+            sha256: self.sha256,
+        }
+    }
+}
+
+pub fn parse(json: &str) -> Result<ModelManifest, String> {
+    let manifest: ModelManifest = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if manifest.schema != SCHEMA {
+        return Err(format!("unsupported manifest schema {}", manifest.schema));
+    }
+    Ok(manifest)
+}
+
+fn bundled() -> ModelManifest {
+    parse(BUNDLED).expect("bundled model manifest is valid")
+}
+
+/// `newer`'s models and files, plus those of `base` it doesn't list
+fn merge(base: ModelManifest, newer: ModelManifest) -> ModelManifest {
+    let mut models = newer.models;
+    for model in base.models {
+        if !models.iter().any(|m| m.id == model.id) {
+            models.push(model);
+        }
+    }
+    let mut auxiliary = newer.auxiliary;
+    for file in base.auxiliary {
+        if !auxiliary
+            .iter()
+            .any(|f| f.kind == file.kind && f.filename == file.filename)
+        {
+            auxiliary.push(file);
+        }
+    }
+    ModelManifest {
+        schema: newer.schema,
+        version: newer.version,
+        models,
+        auxiliary,
+    }
+}
+
+/// With the cached manifest applied over the bundled one if it is newer
+fn with_newer(manifest: Option<ModelManifest>) -> ModelManifest {
+    let bundled = bundled();
+    match manifest {
+        Some(manifest) if manifest.version > bundled.version => merge(bundled, manifest),
+        _ => bundled,
+    }
+}
+
+/// The catalog to start with. Only reads files, never waits on the network.
+pub fn load(app: &AppHandle) -> ModelManifest {
+    with_newer(read_cache(app))
+}
+
+fn cache_path(app: &AppHandle) -> PathBuf {
+    crate::paths::get(app).cache.join(CACHE_FILE)
+}
+
+fn read_cache(app: &AppHandle) -> Option<ModelManifest> {
+    let json = fs::read_to_string(cache_path(app)).ok()?;
+    parse(&json)
+        .map_err(|e| warn!("Ignoring the cached model manifest: {}", e))
+        .ok()
+}
+
+fn cache_is_fresh(app: &AppHandle) -> bool {
+    fs::metadata(cache_path(app))
+        .ok()
+        .and_then(|meta| meta.modified().ok())
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < MAX_CACHE_AGE)
+}
+
+/// Forgets the fetched manifest, the bundled one applies from the next start
+pub fn clear_cache(app: &AppHandle) {
+    let path = cache_path(app);
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            warn!("Failed to remove the cached model manifest: {}", e);
+        }
+    }
+}
+
+fn sha256_hex(digest: impl AsRef<[u8]>) -> String {
+    digest
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Hex SHA-256 of a file, read in pieces so large models don't fill memory
+pub fn file_sha256(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(sha256_hex(hasher.finalize()))
+}
+
+pub fn checksum_matches(expected: &str, actual: &str) -> bool {
+    expected.trim().eq_ignore_ascii_case(actual)
+}
+
+/// Fetches a newer manifest in the background when one is configured and the
+/// cached copy is a day old. `force` skips the age checks, after the URL changed.
+pub fn refresh_in_background(app: &AppHandle, force: bool) {
+    let Some(url) = get_settings(app).model_manifest_url else {
+        return;
+    };
+    if !force && cache_is_fresh(app) {
+        return;
+    }
+    {
+        let mut last = LAST_ATTEMPT.lock().unwrap();
+        if !force && last.is_some_and(|at| at.elapsed() < RETRY_INTERVAL) {
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = refresh(&app, &url).await {
+            warn!("Failed to refresh the model manifest from {}: {}", url, e);
+        }
+    });
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {} from {}", response.status(), url));
+    }
+    let body = response.bytes().await.map_err(|e| e.to_string())?;
+    Ok(body.to_vec())
+}
+
+async fn refresh(app: &AppHandle, url: &str) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let body = fetch(&client, url).await?;
+    let published = fetch(&client, &format!("{}.sha256", url)).await?;
+    // Formatted like `sha256sum` output, the file name after the hash is optional
+    let published = String::from_utf8_lossy(&published);
+    let expected = published.split_whitespace().next().unwrap_or_default();
+    let actual = sha256_hex(Sha256::digest(&body));
+    if !checksum_matches(expected, &actual) {
+        return Err(format!(
+            "checksum mismatch, published {} but got {}",
+            expected, actual
+        ));
+    }
+    let json = String::from_utf8(body).map_err(|e| e.to_string())?;
+    let manifest = parse(&json)?;
+
+    let path = cache_path(app);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, &json).map_err(|e| e.to_string())?;
+    if manifest.version > bundled().version {
+        info!(
+            "Updated the model catalog to manifest version {}",
+            manifest.version
+        );
+        app.state::<Arc<ModelManager>>()
+            .apply_manifest(with_newer(Some(manifest)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_manifest_lists_every_model() {
+        let manifest = bundled();
+        assert_eq!(manifest.schema, SCHEMA);
+        let ids: Vec<&str> = manifest.models.iter().map(|m| m.id.as_str()).collect();
+        for id in ["small", "medium", "turbo", "large", "moonshine-base"] {
+            assert!(ids.contains(&id), "{} missing", id);
+        }
+        for model in &manifest.models {
+            assert!(!model.languages.is_empty(), "{} has no languages", model.id);
+            // The first variant is the file the model downloads by default
+            if let Some(first) = model.variants.first() {
+                assert_eq!(first.filename, model.filename);
+                assert_eq!(first.sha256, model.sha256);
+            }
+            let hashes =
+                std::iter::once(&model.sha256).chain(model.variants.iter().map(|v| &v.sha256));
+            for hash in hashes.flatten() {
+                assert!(
+                    hash.len() == 64
+                        && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')),
+                    "{} lists a malformed checksum {}",
+                    model.id,
+                    hash
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn newer_manifests_update_models_without_dropping_any() {
+        let mut newer = bundled();
+        newer.version += 1;
+        newer.models.retain(|m| m.id == "small");
+        newer.models[0].description = "Updated".to_string();
+
+        let merged = with_newer(Some(newer.clone()));
+        assert_eq!(merged.models.len(), bundled().models.len());
+        let small = merged.models.iter().find(|m| m.id == "small").unwrap();
+        assert_eq!(small.description, "Updated");
+
+        // A manifest that isn't newer than the bundled one is ignored
+        newer.version = bundled().version;
+        let ignored = with_newer(Some(newer));
+        let small = ignored.models.iter().find(|m| m.id == "small").unwrap();
+        assert_ne!(small.description, "Updated");
+
+        assert!(parse(r#"{"schema": 2, "version": 9, "models": []}"#).is_err());
+    }
+
+    #[test]
+    fn file_checksums_are_hex_sha256() {
+        let path = std::env::temp_dir().join(format!("handy-sha256-{}", std::process::id()));
+        fs::write(&path, b"abc").unwrap();
+        let hash = file_sha256(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            hash,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(checksum_matches(
+            "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD\n",
+            &hash
+        ));
+        assert!(!checksum_matches("00", &hash));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::settings::{self, AppSettings, RecordingRetentionPeriod};
+use crate::tray_i18n::get_language_code;
+use log::{debug, warn};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Notification texts, generated by build.rs from the "notification" section of
+/// the frontend locale files like the tray menu's
+mod strings {
+    use once_cell::sync::Lazy;
+    use std::collections::HashMap;
+
+    include!(concat!(env!("OUT_DIR"), "/notification_translations.rs"));
+}
+
+use strings::{NotificationStrings, TRANSLATIONS};
+
+/// Maximum number of characters of the transcript shown in the notification body
+const PREVIEW_MAX_CHARS: usize = 120;
+
+/// Shortens the text to a single-line preview suitable for a notification body
+fn preview_text(text: &str, max_chars: usize) -> String {
+    let single_line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if single_line.chars().count() <= max_chars {
+        return single_line;
+    }
+
+    let truncated: String = single_line.chars().take(max_chars).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// Notification texts in the app language, English when it has no translation
+fn notification_strings(settings: &AppSettings) -> NotificationStrings {
+    TRANSLATIONS
+        .get(get_language_code(&settings.app_language))
+        .or_else(|| TRANSLATIONS.get("en"))
+        .cloned()
+        .expect("English translations must exist")
+}
+
+/// Whether history keeps transcripts at all. It keeps none when limited to zero
+/// entries.
+fn history_keeps_transcripts(settings: &AppSettings) -> bool {
+    settings.recording_retention_period != RecordingRetentionPeriod::PreserveLimit
+        || settings.history_limit > 0
+}
+
+/// Returns true when the main window has focus, in which case the user is
+/// already looking at Handy and a notification would just be noise.
+fn main_window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false)
+}
+
+/// Whether the OS holds notifications back, e.g. for do-not-disturb or a full-screen
+/// app. macOS isn't asked, it holds them during a Focus by itself.
+fn notifications_suppressed() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::Shell::{
+            SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS, QUNS_APP,
+        };
+        unsafe { SHQueryUserNotificationState() }
+            .is_ok_and(|state| state != QUNS_ACCEPTS_NOTIFICATIONS && state != QUNS_APP)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // GNOME's do-not-disturb turns the banners off
+        std::process::Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .is_some_and(|output| banners_off(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        false
+    }
+}
+
+/// Parses `gsettings get org.gnome.desktop.notifications show-banners`
+#[cfg(any(target_os = "linux", test))]
+fn banners_off(output: &str) -> bool {
+    output.trim() == "false"
+}
+
+/// Shows a native notification with a preview of the pasted text if the user enabled
+/// it, unless the OS is holding notifications back
+pub fn notify_transcription_complete(app: &AppHandle, text: &str) {
+    let settings = settings::get_settings(app);
+    if !settings.notify_on_complete || text.trim().is_empty() {
+        return;
+    }
+
+    if main_window_focused(app) {
+        debug!("Skipping completion notification, main window is focused");
+        return;
+    }
+
+    if notifications_suppressed() {
+        debug!("Skipping completion notification, the OS is holding notifications back");
+        return;
+    }
+
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(notification_strings(&settings).transcription_complete)
+        .body(preview_text(text, PREVIEW_MAX_CHARS))
+        .show()
+    {
+        warn!("Failed to show completion notification: {}", e);
+    }
+}
+
+/// Tells the user a transcript was not inserted because a password field has focus,
+/// and whether history still has it
+pub fn notify_secure_field_refused(app: &AppHandle) {
+    let settings = settings::get_settings(app);
+    let strings = notification_strings(&settings);
+    let body = if history_keeps_transcripts(&settings) {
+        strings.secure_field_kept_in_history
+    } else {
+        strings.secure_field_not_kept
+    };
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(strings.transcript_not_inserted)
+        .body(body)
+        .show()
+    {
+        warn!("Failed to show secure field notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gnome_do_not_disturb_turns_banners_off() {
+        assert!(banners_off("false\n"));
+        assert!(!banners_off("true\n"));
+    }
+
+    #[test]
+    fn short_text_is_unchanged() {
+        assert_eq!(preview_text("hello world", 20), "hello world");
+    }
+
+    #[test]
+    fn long_text_is_truncated_with_ellipsis() {
+        assert_eq!(preview_text("hello wonderful world", 10), "hello wond…");
+    }
+
+    #[test]
+    fn newlines_are_collapsed() {
+        assert_eq!(
+            preview_text("first line\n\nsecond  line", 50),
+            "first line second line"
+        );
+    }
+
+    #[test]
+    fn history_limited_to_zero_keeps_nothing() {
+        let mut settings = settings::get_default_settings();
+        settings.recording_retention_period = RecordingRetentionPeriod::PreserveLimit;
+        settings.history_limit = 0;
+        assert!(!history_keeps_transcripts(&settings));
+
+        settings.history_limit = 5;
+        assert!(history_keeps_transcripts(&settings));
+
+        settings.history_limit = 0;
+        settings.recording_retention_period = RecordingRetentionPeriod::Weeks2;
+        assert!(history_keeps_transcripts(&settings));
+    }
+
+    #[test]
+    fn truncation_respects_multibyte_characters() {
+        assert_eq!(preview_text("日本語のテキスト", 3), "日本語…");
+    }
+}
@@ -1,7 +1,9 @@
+use crate::deferred_paste::DeferredPaste;
+use crate::events::{emit_event, AppEvent, OverlayState};
 use crate::input;
 use crate::settings;
 use crate::settings::OverlayPosition;
-use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize};
 
 #[cfg(not(target_os = "macos"))]
 use log::debug;
@@ -158,11 +160,14 @@ pub fn create_recording_overlay(app_handle: &AppHandle) {
     .skip_taskbar(true)
     .transparent(true)
     .focused(false)
+    // Clicks in interactive mode must never take keyboard focus from the target app
+    .focusable(false)
     .visible(false)
     .build()
     {
-        Ok(_window) => {
+        Ok(window) => {
             debug!("Recording overlay window created successfully (hidden)");
+            apply_overlay_click_through(app_handle, &window);
         }
         Err(e) => {
             debug!("Failed to create recording overlay window: {}", e);
@@ -199,6 +204,9 @@ pub fn create_recording_overlay(app_handle: &AppHandle) {
         {
             Ok(panel) => {
                 let _ = panel.hide();
+                if let Some(window) = app_handle.get_webview_window("recording_overlay") {
+                    apply_overlay_click_through(app_handle, &window);
+                }
             }
             Err(e) => {
                 log::error!("Failed to create recording overlay panel: {}", e);
@@ -207,6 +215,23 @@ pub fn create_recording_overlay(app_handle: &AppHandle) {
     }
 }
 
+/// Makes the overlay click-through unless the interactive overlay is enabled.
+/// Tauri maps this to ignoresMouseEvents on macOS, WS_EX_TRANSPARENT on Windows
+/// and an empty input region on Linux. The window stays non-focusable either way.
+fn apply_overlay_click_through(app_handle: &AppHandle, window: &tauri::WebviewWindow) {
+    let interactive = settings::get_settings(app_handle).overlay_interactive;
+    if let Err(e) = window.set_ignore_cursor_events(!interactive) {
+        log::warn!("Failed to update overlay click-through: {}", e);
+    }
+}
+
+/// Re-applies the click-through state after the interactive setting changes
+pub fn update_overlay_interactivity(app_handle: &AppHandle) {
+    if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
+        apply_overlay_click_through(app_handle, &overlay_window);
+    }
+}
+
 /// Shows the recording overlay window with fade-in animation
 pub fn show_recording_overlay(app_handle: &AppHandle) {
     // Check if overlay should be shown based on position setting
@@ -228,8 +253,17 @@ pub fn show_recording_overlay(app_handle: &AppHandle) {
         #[cfg(target_os = "windows")]
         force_overlay_topmost(&overlay_window);
 
+        // Tell the overlay whether to offer stop/cancel controls
+        emit_event(
+            &overlay_window,
+            AppEvent::setting_changed("overlay_interactive", settings.overlay_interactive),
+        );
+
         // Emit event to trigger fade-in animation with recording state
-        let _ = overlay_window.emit("show-overlay", "recording");
+        emit_event(
+            &overlay_window,
+            AppEvent::ShowOverlay(OverlayState::Recording),
+        );
     }
 }
 
@@ -251,7 +285,36 @@ pub fn show_transcribing_overlay(app_handle: &AppHandle) {
         force_overlay_topmost(&overlay_window);
 
         // Emit event to switch to transcribing state
-        let _ = overlay_window.emit("show-overlay", "transcribing");
+        emit_event(
+            &overlay_window,
+            AppEvent::ShowOverlay(OverlayState::Transcribing),
+        );
+    }
+}
+
+/// Shows the pending chip while a deferred transcript waits to be pasted
+pub fn show_deferred_overlay(app_handle: &AppHandle) {
+    let settings = settings::get_settings(app_handle);
+    if settings.overlay_position == OverlayPosition::None {
+        return;
+    }
+
+    update_overlay_position(app_handle);
+
+    if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
+        let _ = overlay_window.show();
+
+        #[cfg(target_os = "windows")]
+        force_overlay_topmost(&overlay_window);
+
+        emit_event(
+            &overlay_window,
+            AppEvent::setting_changed("overlay_interactive", settings.overlay_interactive),
+        );
+        emit_event(
+            &overlay_window,
+            AppEvent::ShowOverlay(OverlayState::Deferred),
+        );
     }
 }
 
@@ -267,11 +330,26 @@ pub fn update_overlay_position(app_handle: &AppHandle) {
 
 /// Hides the recording overlay window with fade-out animation
 pub fn hide_recording_overlay(app_handle: &AppHandle) {
+    // The deferred paste chip stays up until its transcript is pasted
+    if app_handle
+        .try_state::<DeferredPaste>()
+        .is_some_and(|deferred| deferred.is_pending())
+        && settings::get_settings(app_handle).overlay_position != OverlayPosition::None
+    {
+        show_deferred_overlay(app_handle);
+        return;
+    }
+
+    // A dictation session keeps recording between its segments
+    if crate::dictation_session::is_active(app_handle) {
+        return;
+    }
+
     // Always hide the overlay regardless of settings - if setting was changed while recording,
     // we still want to hide it properly
     if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
         // Emit event to trigger fade-out animation
-        let _ = overlay_window.emit("hide-overlay", ());
+        emit_event(&overlay_window, AppEvent::HideOverlay);
         // Hide the window after a short delay to allow animation to complete
         let window_clone = overlay_window.clone();
         std::thread::spawn(move || {
@@ -283,10 +361,10 @@ pub fn hide_recording_overlay(app_handle: &AppHandle) {
 
 pub fn emit_levels(app_handle: &AppHandle, levels: &Vec<f32>) {
     // emit levels to main app
-    let _ = app_handle.emit("mic-level", levels);
+    emit_event(app_handle, AppEvent::MicLevel(levels.clone()));
 
     // also emit to the recording overlay if it's open
     if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
-        let _ = overlay_window.emit("mic-level", levels);
+        emit_event(&overlay_window, AppEvent::MicLevel(levels.clone()));
     }
 }
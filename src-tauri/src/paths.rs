@@ -0,0 +1,332 @@
+//! Where the app keeps its files.
+//!
+//! - Linux follows the XDG base directories: settings under `$XDG_CONFIG_HOME`,
+//!   history, recordings and models under `$XDG_DATA_HOME`, logs under
+//!   `$XDG_STATE_HOME` and caches under `$XDG_CACHE_HOME`.
+//! - macOS and Windows keep everything in the app data directory, except logs and
+//!   caches, which go to the platform's log and cache directories.
+//! - Launched with `--portable`, everything goes in a `data` folder next to the
+//!   executable, and nothing outside it is read or written.
+//!
+//! Older versions kept everything except logs in the app data directory. The first
+//! launch after that moves those files to their new place, then leaves a marker in
+//! the config directory so the move never runs twice. A portable install starts
+//! out empty instead of taking over the installed app's files.
+
+use crate::settings::SETTINGS_STORE_PATH;
+use log::{info, warn};
+use serde::Serialize;
+use specta::Type;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+pub const PORTABLE_FLAG: &str = "--portable";
+/// Folder next to the executable holding a portable install's files
+const PORTABLE_DIR: &str = "data";
+/// Left in the config directory once files were moved from the old layout
+const MIGRATION_MARKER: &str = ".paths_migrated";
+const MODELS_DIR: &str = "models";
+const RECORDINGS_DIR: &str = "recordings";
+const LOGS_DIR: &str = "logs";
+
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct AppPaths {
+    /// Settings
+    pub config: PathBuf,
+    /// History, custom sounds and everything else worth keeping
+    pub data: PathBuf,
+    pub models: PathBuf,
+    pub recordings: PathBuf,
+    pub logs: PathBuf,
+    /// Files that are fetched again when missing, like the model catalog
+    pub cache: PathBuf,
+    pub portable: bool,
+}
+
+impl AppPaths {
+    /// Everything under `dir`, as in portable mode
+    fn in_dir(dir: &Path, portable: bool) -> Self {
+        Self {
+            config: dir.to_path_buf(),
+            data: dir.to_path_buf(),
+            models: dir.join(MODELS_DIR),
+            recordings: dir.join(RECORDINGS_DIR),
+            logs: dir.join(LOGS_DIR),
+            cache: dir.join("cache"),
+            portable,
+        }
+    }
+
+    pub fn settings_store(&self) -> PathBuf {
+        self.config.join(SETTINGS_STORE_PATH)
+    }
+}
+
+static PATHS: OnceLock<AppPaths> = OnceLock::new();
+
+/// Whether the app was launched with `--portable`
+pub fn is_portable() -> bool {
+    std::env::args().skip(1).any(|arg| arg == PORTABLE_FLAG)
+}
+
+/// The app's directories, resolved once per run
+pub fn get(app: &AppHandle) -> &'static AppPaths {
+    PATHS.get_or_init(|| resolve(app))
+}
+
+fn resolve(app: &AppHandle) -> AppPaths {
+    if is_portable() {
+        match std::env::current_exe() {
+            Ok(exe) => {
+                let dir = exe.parent().unwrap_or(Path::new(".")).join(PORTABLE_DIR);
+                return AppPaths::in_dir(&dir, true);
+            }
+            Err(e) => warn!(
+                "Portable mode unavailable, can't locate the executable: {}",
+                e
+            ),
+        }
+    }
+    platform_paths(app)
+}
+
+#[cfg(target_os = "linux")]
+fn platform_paths(app: &AppHandle) -> AppPaths {
+    let home = app.path().home_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let identifier = &app.config().identifier;
+    let base = |var: &str, fallback: &str| {
+        xdg_dir(std::env::var_os(var).map(PathBuf::from), &home, fallback).join(identifier)
+    };
+    let data = base("XDG_DATA_HOME", ".local/share");
+    AppPaths {
+        config: base("XDG_CONFIG_HOME", ".config"),
+        models: data.join(MODELS_DIR),
+        recordings: data.join(RECORDINGS_DIR),
+        data,
+        logs: base("XDG_STATE_HOME", ".local/state").join(LOGS_DIR),
+        cache: base("XDG_CACHE_HOME", ".cache"),
+        portable: false,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn platform_paths(app: &AppHandle) -> AppPaths {
+    let legacy = legacy_paths(app);
+    AppPaths {
+        cache: app.path().app_cache_dir().unwrap_or(legacy.cache.clone()),
+        ..legacy
+    }
+}
+
+/// A base directory from an XDG variable, which only counts when it's absolute
+#[cfg(any(target_os = "linux", test))]
+fn xdg_dir(value: Option<PathBuf>, home: &Path, fallback: &str) -> PathBuf {
+    value
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| home.join(fallback))
+}
+
+/// Where older versions kept everything
+fn legacy_paths(app: &AppHandle) -> AppPaths {
+    let data = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    AppPaths {
+        logs: app.path().app_log_dir().unwrap_or(data.join(LOGS_DIR)),
+        cache: data.clone(),
+        ..AppPaths::in_dir(&data, false)
+    }
+}
+
+/// Files to move from the old layout besides settings, as (from, to). Everything
+/// else in the data directory stays put, it's the same directory in both.
+fn moves(legacy: &AppPaths, paths: &AppPaths) -> Vec<(PathBuf, PathBuf)> {
+    let manifest = crate::model_manifest::CACHE_FILE;
+    [
+        (legacy.models.clone(), paths.models.clone()),
+        (legacy.recordings.clone(), paths.recordings.clone()),
+        (legacy.logs.clone(), paths.logs.clone()),
+        (legacy.cache.join(manifest), paths.cache.join(manifest)),
+    ]
+    .into_iter()
+    .filter(|(from, to)| from != to)
+    .collect()
+}
+
+fn is_migrated(paths: &AppPaths) -> bool {
+    paths.portable || paths.config.join(MIGRATION_MARKER).exists()
+}
+
+/// Moves the settings store from the old layout. Runs before the rest of
+/// `migrate`, as settings are read before logging starts; the outcome is for
+/// `migrate` to log.
+pub fn migrate_settings(app: &AppHandle) -> io::Result<bool> {
+    let paths = get(app);
+    let from = legacy_paths(app).settings_store();
+    if is_migrated(paths) || from == paths.settings_store() {
+        return Ok(false);
+    }
+    move_path(&from, &paths.settings_store())
+}
+
+/// Moves files from the old layout to where they belong now, once
+pub fn migrate(app: &AppHandle, settings: io::Result<bool>) {
+    let paths = get(app);
+    if is_migrated(paths) {
+        return;
+    }
+    match settings {
+        Ok(true) => info!("Moved the settings to {}", paths.config.display()),
+        Ok(false) => {}
+        Err(e) => warn!("Failed to move the settings, they stay in place: {}", e),
+    }
+
+    let moves: Vec<_> = moves(&legacy_paths(app), paths)
+        .into_iter()
+        .filter(|(from, _)| from.exists())
+        .collect();
+    for (step, (from, to)) in moves.iter().enumerate() {
+        info!(
+            "Moving files to the new layout ({}/{}): {} to {}",
+            step + 1,
+            moves.len(),
+            from.display(),
+            to.display()
+        );
+        if let Err(e) = move_path(from, to) {
+            warn!(
+                "Failed to move {}, it stays in place: {}",
+                from.display(),
+                e
+            );
+        }
+    }
+
+    // Written even if some moves failed, whatever is left behind is still on disk
+    let marker = paths.config.join(MIGRATION_MARKER);
+    match fs::create_dir_all(&paths.config).and_then(|_| fs::write(&marker, "")) {
+        Ok(_) => info!("Files are in the new layout"),
+        Err(e) => warn!("Failed to mark the file move as done: {}", e),
+    }
+}
+
+/// Moves `from` to `to`, copying when they're on different filesystems. A
+/// directory is merged into an existing one, files already at `to` are kept.
+/// Returns whether anything was moved.
+fn move_path(from: &Path, to: &Path) -> io::Result<bool> {
+    let Ok(metadata) = fs::symlink_metadata(from) else {
+        return Ok(false);
+    };
+    if metadata.is_dir() && to.is_dir() {
+        let mut moved = false;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            moved |= move_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        // Only goes once empty, skipped files stay
+        let _ = fs::remove_dir(from);
+        return Ok(moved);
+    }
+    if to.exists() {
+        warn!(
+            "Not moving {}, {} already exists",
+            from.display(),
+            to.display()
+        );
+        return Ok(false);
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_err() {
+        copy_path(from, to)?;
+        if metadata.is_dir() {
+            fs::remove_dir_all(from)?;
+        } else {
+            fs::remove_file(from)?;
+        }
+    }
+    Ok(true)
+}
+
+fn copy_path(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.is_dir() {
+        return fs::copy(from, to).map(|_| ());
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_path(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xdg_variables_only_count_when_absolute() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            xdg_dir(Some(PathBuf::from("/tmp/config")), home, ".config"),
+            PathBuf::from("/tmp/config")
+        );
+        assert_eq!(
+            xdg_dir(Some(PathBuf::from("config")), home, ".config"),
+            PathBuf::from("/home/user/.config")
+        );
+        assert_eq!(
+            xdg_dir(None, home, ".cache"),
+            PathBuf::from("/home/user/.cache")
+        );
+    }
+
+    #[test]
+    fn only_relocated_files_are_moved() {
+        let legacy = AppPaths {
+            logs: PathBuf::from("/data/logs"),
+            cache: PathBuf::from("/data"),
+            ..AppPaths::in_dir(Path::new("/data"), false)
+        };
+        let paths = AppPaths {
+            config: PathBuf::from("/config"),
+            cache: PathBuf::from("/cache"),
+            ..legacy.clone()
+        };
+        assert_eq!(
+            moves(&legacy, &paths),
+            vec![(
+                PathBuf::from("/data/model_manifest.json"),
+                PathBuf::from("/cache/model_manifest.json")
+            )]
+        );
+        assert_eq!(
+            paths.settings_store(),
+            PathBuf::from("/config/settings_store.json")
+        );
+    }
+
+    #[test]
+    fn directories_are_merged_without_overwriting() {
+        let root = std::env::temp_dir().join(format!("handy-paths-{}", std::process::id()));
+        let (from, to) = (root.join("from"), root.join("to"));
+        fs::create_dir_all(from.join("sub")).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        fs::write(from.join("sub/a.bin"), "a").unwrap();
+        fs::write(from.join("kept.txt"), "old").unwrap();
+        fs::write(to.join("kept.txt"), "new").unwrap();
+
+        assert!(move_path(&from, &to).unwrap());
+        assert_eq!(fs::read_to_string(to.join("sub/a.bin")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(to.join("kept.txt")).unwrap(), "new");
+        // The skipped file keeps the source around
+        assert!(from.join("kept.txt").exists());
+        assert!(!from.join("sub").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
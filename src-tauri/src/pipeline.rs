@@ -0,0 +1,346 @@
+//! Lifecycle of a dictation, from the start of the recording until the text is pasted.
+//!
+//! Every stage is reported through `PipelineOperation::report`, which emits one of
+//! `recording-started`, `recording-stopped`, `inference-started`, `inference-done`,
+//! `post-process-started`, `post-process-done` or `paste-done` with timings. The
+//! stages also drive the stop sound, see `feedback_timing`. Whichever way a
+//! dictation leaves the pipeline, it ends with one `dictation-ended`.
+//!
+//! Recording only needs the recorder, so a new recording starts right away while
+//! earlier dictations are still transcribed. Their transcriptions take turns on
+//! `ActiveOperations::lock_inference` unless `overlap_transcription` lets them run
+//! together. `dictations-in-flight` reports how many are recording and processing
+//! whenever that changes.
+//!
+//! Each dictation carries a `LiveToken`. Cancelling drops the current dictation
+//! only: the one recording, or else the latest one still processing, whose text is
+//! then never pasted.
+
+use crate::audio_feedback;
+use crate::events::{emit_event, AppEvent};
+use crate::llm_client::LiveToken;
+use crate::settings::FeedbackMode;
+use log::debug;
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineStage {
+    RecordingStarted,
+    RecordingStopped,
+    InferenceStarted,
+    InferenceDone,
+    PostProcessStarted,
+    PostProcessDone,
+    PasteDone,
+}
+
+/// Payload of every pipeline event
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct PipelineProgress {
+    /// Identifies the dictation, increasing with every recording
+    pub operation_id: u32,
+    pub binding_id: String,
+    /// Time since the recording started
+    pub elapsed_ms: u32,
+    /// Time since the previous event of this dictation, e.g. the inference time for
+    /// `inference-done`
+    pub stage_ms: u32,
+}
+
+/// How a dictation left the pipeline
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DictationOutcome {
+    Pasted,
+    /// Appended to the file dictation target instead of being pasted
+    WrittenToFile,
+    /// Parked until the target app is ready, see `deferred_paste`
+    Deferred,
+    /// Dropped by the discard filters or in review
+    Discarded,
+    /// No audio or no words
+    Empty,
+    Failed,
+    Cancelled,
+}
+
+/// Payload of `dictation-ended`
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct DictationEnded {
+    pub progress: PipelineProgress,
+    pub outcome: DictationOutcome,
+}
+
+impl PipelineStage {
+    fn into_event(self, progress: PipelineProgress) -> AppEvent {
+        match self {
+            PipelineStage::RecordingStarted => AppEvent::RecordingStarted(progress),
+            PipelineStage::RecordingStopped => AppEvent::RecordingStopped(progress),
+            PipelineStage::InferenceStarted => AppEvent::InferenceStarted(progress),
+            PipelineStage::InferenceDone => AppEvent::InferenceDone(progress),
+            PipelineStage::PostProcessStarted => AppEvent::PostProcessStarted(progress),
+            PipelineStage::PostProcessDone => AppEvent::PostProcessDone(progress),
+            PipelineStage::PasteDone => AppEvent::PasteDone(progress),
+        }
+    }
+}
+
+/// One dictation moving through the pipeline. Cloned into whichever task runs the
+/// next stage, so events keep their order and timings even when recordings overlap.
+#[derive(Clone, Debug)]
+pub struct PipelineOperation {
+    id: u32,
+    binding_id: String,
+    started: Instant,
+    last_event: Arc<Mutex<Instant>>,
+    /// The binding's feedback when the recording started, kept for the whole
+    /// dictation even if the settings change meanwhile
+    feedback: FeedbackMode,
+    cancel: LiveToken,
+}
+
+impl PipelineOperation {
+    fn new(id: u32, binding_id: &str, feedback: FeedbackMode) -> Self {
+        let now = Instant::now();
+        Self {
+            id,
+            binding_id: binding_id.to_string(),
+            started: now,
+            last_event: Arc::new(Mutex::new(now)),
+            feedback,
+            cancel: LiveToken::default(),
+        }
+    }
+
+    pub fn feedback(&self) -> FeedbackMode {
+        self.feedback
+    }
+
+    /// Token cancelling this dictation's post-processing, shared by its clones
+    pub fn cancel_token(&self) -> LiveToken {
+        self.cancel.clone()
+    }
+
+    fn progress(&self) -> PipelineProgress {
+        let now = Instant::now();
+        let previous = std::mem::replace(&mut *self.last_event.lock().unwrap(), now);
+        PipelineProgress {
+            operation_id: self.id,
+            binding_id: self.binding_id.clone(),
+            elapsed_ms: now.duration_since(self.started).as_millis() as u32,
+            stage_ms: now.duration_since(previous).as_millis() as u32,
+        }
+    }
+
+    pub fn report(&self, app: &AppHandle, stage: PipelineStage) {
+        let progress = self.progress();
+        debug!(
+            "Dictation {} reached {:?} after {}ms ({}ms in stage)",
+            progress.operation_id, stage, progress.elapsed_ms, progress.stage_ms
+        );
+        emit_event(app, stage.into_event(progress));
+        audio_feedback::play_stage_feedback(app, self.feedback, stage);
+    }
+
+    /// Reports that the dictation left the pipeline, the last event it gets
+    pub fn end(&self, app: &AppHandle, outcome: DictationOutcome) {
+        let progress = self.progress();
+        debug!(
+            "Dictation {} ended {:?} after {}ms",
+            progress.operation_id, outcome, progress.elapsed_ms
+        );
+        emit_event(
+            app,
+            AppEvent::DictationEnded(DictationEnded { progress, outcome }),
+        );
+    }
+}
+
+/// Payload of `dictations-in-flight`
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+pub struct DictationsInFlight {
+    pub recording: u32,
+    /// Stopped and still being transcribed, post-processed or pasted
+    pub processing: u32,
+}
+
+/// Dictations that are recording, keyed by binding id until the recording stops,
+/// and the tokens of those still processing after that, oldest first
+#[derive(Default)]
+pub struct ActiveOperations {
+    next_id: AtomicU32,
+    recording: Mutex<HashMap<String, PipelineOperation>>,
+    processing: Mutex<Vec<LiveToken>>,
+    /// Held while a dictation is transcribed, never while recording
+    inference: Mutex<()>,
+}
+
+impl ActiveOperations {
+    /// Starts tracking a new recording and reports `recording-started`
+    pub fn begin(&self, app: &AppHandle, binding_id: &str, feedback: FeedbackMode) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let operation = PipelineOperation::new(id, binding_id, feedback);
+        operation.report(app, PipelineStage::RecordingStarted);
+        self.recording
+            .lock()
+            .unwrap()
+            .insert(binding_id.to_string(), operation);
+        self.report_in_flight(app);
+    }
+
+    pub fn processing(&self) -> u32 {
+        self.processing.lock().unwrap().len() as u32
+    }
+
+    /// Feedback of a dictation that is recording, if any
+    pub fn recording_feedback(&self) -> Option<FeedbackMode> {
+        self.recording
+            .lock()
+            .unwrap()
+            .values()
+            .next()
+            .map(PipelineOperation::feedback)
+    }
+
+    /// A stopped recording entered the pipeline. Returns the token that cancels it,
+    /// the operation's own when there is one.
+    pub fn start_processing(
+        &self,
+        app: &AppHandle,
+        operation: Option<&PipelineOperation>,
+    ) -> LiveToken {
+        let token = operation.map_or_else(LiveToken::default, PipelineOperation::cancel_token);
+        self.processing.lock().unwrap().push(token.clone());
+        self.report_in_flight(app);
+        token
+    }
+
+    /// A stopped recording left the pipeline, pasted or not
+    pub fn finish_processing(&self, app: &AppHandle, token: &LiveToken) {
+        self.processing.lock().unwrap().retain(|t| t != token);
+        self.report_in_flight(app);
+    }
+
+    /// Serializes transcriptions, so overlapping dictations are transcribed one
+    /// after the other instead of taking turns on the engine chunk by chunk. None
+    /// when `overlap_transcription` lets them run together.
+    pub fn lock_inference(&self, overlap: bool) -> Option<MutexGuard<'_, ()>> {
+        (!overlap).then(|| self.inference.lock().unwrap())
+    }
+
+    fn report_in_flight(&self, app: &AppHandle) {
+        let in_flight = DictationsInFlight {
+            recording: self.recording.lock().unwrap().len() as u32,
+            processing: self.processing(),
+        };
+        emit_event(app, AppEvent::DictationsInFlight(in_flight));
+    }
+
+    /// Id of the binding's dictation while it is recording
+    pub fn id(&self, binding_id: &str) -> Option<u32> {
+        self.recording
+            .lock()
+            .unwrap()
+            .get(binding_id)
+            .map(|operation| operation.id)
+    }
+
+    /// Hands over the recording's operation once the recording stops
+    pub fn take(&self, binding_id: &str) -> Option<PipelineOperation> {
+        self.recording.lock().unwrap().remove(binding_id)
+    }
+
+    /// Cancels the current dictation: every recording, or the latest dictation
+    /// still processing when nothing is recording
+    pub fn cancel_current(&self, app: &AppHandle) {
+        let cancelled: Vec<_> = self.recording.lock().unwrap().drain().collect();
+        if cancelled.is_empty() {
+            self.cancel_last_processing();
+        }
+        for (_, operation) in cancelled {
+            operation.cancel.cancel();
+            operation.end(app, DictationOutcome::Cancelled);
+        }
+        self.report_in_flight(app);
+    }
+
+    /// Returns false when nothing was processing. The dictation itself leaves the
+    /// pipeline once its task sees the token.
+    fn cancel_last_processing(&self) -> bool {
+        match self.processing.lock().unwrap().last() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancels every dictation, so nothing is pasted after quitting
+    pub fn cancel_all(&self) {
+        for operation in self.recording.lock().unwrap().values() {
+            operation.cancel.cancel();
+        }
+        for token in self.processing.lock().unwrap().iter() {
+            token.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_time_is_measured_from_previous_event() {
+        let operation = PipelineOperation::new(7, "transcribe", FeedbackMode::Both);
+        *operation.last_event.lock().unwrap() -= std::time::Duration::from_millis(50);
+        let first = operation.progress();
+        assert_eq!(first.operation_id, 7);
+        assert_eq!(first.binding_id, "transcribe");
+        assert!(first.stage_ms >= 50);
+
+        let second = operation.clone().progress();
+        assert!(second.stage_ms < first.stage_ms);
+        assert!(second.elapsed_ms >= first.elapsed_ms);
+    }
+
+    #[test]
+    fn transcriptions_take_turns_unless_overlap_is_on() {
+        let operations = ActiveOperations::default();
+        let _first = operations.lock_inference(false);
+        assert!(operations.inference.try_lock().is_err());
+        assert!(operations.lock_inference(true).is_none());
+    }
+
+    #[test]
+    fn cancel_drops_only_the_latest_dictation() {
+        let operations = ActiveOperations::default();
+        assert!(!operations.cancel_last_processing());
+
+        let earlier = PipelineOperation::new(1, "transcribe", FeedbackMode::Both);
+        let latest = PipelineOperation::new(2, "transcribe", FeedbackMode::Both);
+        for operation in [&earlier, &latest] {
+            operations
+                .processing
+                .lock()
+                .unwrap()
+                .push(operation.cancel_token());
+        }
+        assert_eq!(operations.processing(), 2);
+
+        // The check the pipeline makes before pasting
+        assert!(operations.cancel_last_processing());
+        assert!(latest.clone().cancel_token().is_cancelled());
+        assert!(!earlier.cancel_token().is_cancelled());
+
+        operations.cancel_all();
+        assert!(earlier.cancel_token().is_cancelled());
+    }
+}
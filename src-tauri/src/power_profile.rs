@@ -0,0 +1,477 @@
+//! Battery-aware model settings.
+//!
+//! `power_profiles` overrides the model, preloading, the unload timeout and the
+//! compute backend on battery and when plugged in. On laptops the power source is
+//! followed through the platform's change notifications: power setting
+//! notifications on Windows, the power source notification on macOS and udev's
+//! `power_supply` events on Linux. Where those aren't available it's read every
+//! few seconds while either profile is set. When it changes the matching profile
+//! applies, a loaded model that no longer matches is unloaded and
+//! `power-profile-changed` is sent. Computers without a battery, as told by
+//! `is_laptop`, always use the plugged-in profile and aren't watched.
+//!
+//! `set_power_profile` picks a profile by hand, which sticks until the next plug
+//! or unplug.
+
+use crate::events::{emit_event, AppEvent};
+use crate::helpers::clamshell::is_laptop;
+use crate::managers::model::{EngineType, ModelManager};
+use crate::managers::transcription::TranscriptionManager;
+use crate::settings::{
+    get_settings, AppSettings, ComputeBackend, ModelUnloadTimeout, PowerProfile,
+};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often the power source is read where the platform doesn't notify
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Battery,
+    #[default]
+    PluggedIn,
+}
+
+/// The profile in effect, reported with `power-profile-changed` and `get_model_state`
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+pub struct ActivePowerProfile {
+    pub source: PowerSource,
+    /// Picked with `set_power_profile` rather than detected
+    pub manual: bool,
+}
+
+#[derive(Default)]
+struct Sources {
+    detected: PowerSource,
+    manual: Option<PowerSource>,
+}
+
+#[derive(Default)]
+pub struct PowerState(Mutex<Sources>);
+
+impl PowerState {
+    pub fn active(&self) -> ActivePowerProfile {
+        let sources = self.0.lock().unwrap();
+        ActivePowerProfile {
+            source: sources.manual.unwrap_or(sources.detected),
+            manual: sources.manual.is_some(),
+        }
+    }
+}
+
+/// Model settings after applying a profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Resolved {
+    model_id: String,
+    preload: bool,
+    unload_timeout: ModelUnloadTimeout,
+    compute_backend: ComputeBackend,
+}
+
+fn profile(settings: &AppSettings, source: PowerSource) -> &PowerProfile {
+    match source {
+        PowerSource::Battery => &settings.power_profiles.battery,
+        PowerSource::PluggedIn => &settings.power_profiles.plugged_in,
+    }
+}
+
+/// A profile's model only applies once it's downloaded
+fn resolve(
+    settings: &AppSettings,
+    source: PowerSource,
+    is_downloaded: impl Fn(&str) -> bool,
+) -> Resolved {
+    let profile = profile(settings, source);
+    Resolved {
+        model_id: profile
+            .model_id
+            .as_ref()
+            .filter(|id| !id.is_empty() && is_downloaded(id))
+            .cloned()
+            .unwrap_or_else(|| settings.selected_model.clone()),
+        preload: profile.preload.unwrap_or(false),
+        unload_timeout: profile
+            .unload_timeout
+            .unwrap_or(settings.model_unload_timeout),
+        compute_backend: profile.compute_backend.unwrap_or(ComputeBackend::Gpu),
+    }
+}
+
+fn resolve_for(app: &AppHandle, settings: &AppSettings) -> Resolved {
+    let source = app
+        .try_state::<PowerState>()
+        .map_or_else(PowerSource::default, |state| state.active().source);
+    let models = app.try_state::<Arc<ModelManager>>();
+    resolve(settings, source, |id| {
+        models
+            .as_ref()
+            .and_then(|models| models.get_model_info(id))
+            .is_some_and(|info| info.is_downloaded)
+    })
+}
+
+/// Model to load under the current profile
+pub fn selected_model(app: &AppHandle, settings: &AppSettings) -> String {
+    resolve_for(app, settings).model_id
+}
+
+pub fn unload_timeout(app: &AppHandle, settings: &AppSettings) -> ModelUnloadTimeout {
+    resolve_for(app, settings).unload_timeout
+}
+
+pub fn compute_backend(app: &AppHandle, settings: &AppSettings) -> ComputeBackend {
+    resolve_for(app, settings).compute_backend
+}
+
+pub fn active(app: &AppHandle) -> ActivePowerProfile {
+    app.state::<PowerState>().active()
+}
+
+/// Reads the power source and keeps following it on laptops
+pub fn start(app: &AppHandle) {
+    if !is_laptop().unwrap_or(false) {
+        info!("No battery, using the plugged-in power profile");
+        apply(app);
+        return;
+    }
+    app.state::<PowerState>().0.lock().unwrap().detected = detect();
+    info!("Power source: {:?}", active(app).source);
+    apply(app);
+
+    let watched = app.clone();
+    if watch(Box::new(move || update_source(&watched))) {
+        return;
+    }
+    debug!("No power source notifications, polling the power source");
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        if !get_settings(&app).power_profiles.is_empty() {
+            update_source(&app);
+        }
+    });
+}
+
+/// Reads the power source again after a notification or poll
+fn update_source(app: &AppHandle) {
+    let source = detect();
+    let changed = {
+        let mut sources = app.state::<PowerState>().0.lock().unwrap();
+        if sources.detected == source {
+            false
+        } else {
+            sources.detected = source;
+            // A plug or unplug ends the manual choice
+            sources.manual = None;
+            true
+        }
+    };
+    if changed {
+        info!("Power source changed to {:?}", source);
+        changed_profile(app);
+    }
+}
+
+/// Uses `source`'s profile until the next plug or unplug, or the detected one with None
+pub fn set_manual(app: &AppHandle, source: Option<PowerSource>) {
+    let previous = active(app);
+    app.state::<PowerState>().0.lock().unwrap().manual = source;
+    if active(app) != previous {
+        changed_profile(app);
+    }
+}
+
+fn changed_profile(app: &AppHandle) {
+    emit_event(app, AppEvent::PowerProfileChanged(active(app)));
+    apply(app);
+}
+
+/// Brings the loaded model in line with the profile in effect
+pub fn apply(app: &AppHandle) {
+    let resolved = resolve_for(app, &get_settings(app));
+    let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+    if let Some(current) = tm.get_current_model() {
+        // Only Whisper runs on the GPU. One on the CPU is reloaded when the GPU is
+        // allowed again, which checks the free GPU memory anew.
+        let whisper = app
+            .state::<Arc<ModelManager>>()
+            .get_model_info(&current)
+            .is_some_and(|info| matches!(info.engine_type, EngineType::Whisper));
+        let on_cpu = resolved.compute_backend == ComputeBackend::Cpu;
+        let moves = whisper && tm.loaded_on_cpu() != on_cpu;
+        if current != resolved.model_id || moves {
+            debug!(
+                "Unloading {} for the power profile's {} on the {:?}",
+                current, resolved.model_id, resolved.compute_backend
+            );
+            if let Err(e) = tm.unload_model() {
+                warn!("Failed to unload the model for the power profile: {}", e);
+            }
+        }
+    }
+    if resolved.preload && !resolved.model_id.is_empty() {
+        tm.initiate_model_load();
+    }
+}
+
+/// Only called on laptops, which run on battery unless a charger is online
+#[cfg(target_os = "linux")]
+fn detect() -> PowerSource {
+    use crate::helpers::clamshell::PowerSupply;
+
+    let charging = PowerSupply::all().iter().any(|supply| {
+        matches!(supply.read("type").as_str(), "Mains" | "USB") && supply.read("online") == "1"
+    });
+    if charging {
+        PowerSource::PluggedIn
+    } else {
+        PowerSource::Battery
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> PowerSource {
+    // `pmset -g ps` starts with "Now drawing from 'AC Power'" or "'Battery Power'"
+    std::process::Command::new("pmset")
+        .args(["-g", "ps"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map_or(PowerSource::PluggedIn, |output| {
+            if String::from_utf8_lossy(&output.stdout).contains("'Battery Power'") {
+                PowerSource::Battery
+            } else {
+                PowerSource::PluggedIn
+            }
+        })
+}
+
+#[cfg(target_os = "windows")]
+fn detect() -> PowerSource {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    if unsafe { GetSystemPowerStatus(&mut status) }.is_err() {
+        return PowerSource::PluggedIn;
+    }
+    // 0 is offline; 1 online and 255 unknown, which desktops may report
+    if status.ACLineStatus == 0 {
+        PowerSource::Battery
+    } else {
+        PowerSource::PluggedIn
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect() -> PowerSource {
+    PowerSource::PluggedIn
+}
+
+type Callback = Box<dyn Fn() + Send + Sync>;
+
+/// Calls `on_change` from a background thread when the power source may have
+/// changed. Returns false when the platform offers no notifications.
+#[cfg(target_os = "linux")]
+fn watch(on_change: Callback) -> bool {
+    udev_events::watch(on_change)
+}
+
+#[cfg(target_os = "macos")]
+fn watch(on_change: Callback) -> bool {
+    use std::ffi::{c_char, c_int};
+    use std::fs::File;
+    use std::io::Read;
+    use std::os::fd::FromRawFd;
+
+    extern "C" {
+        fn notify_register_file_descriptor(
+            name: *const c_char,
+            notify_fd: *mut c_int,
+            flags: c_int,
+            out_token: *mut c_int,
+        ) -> u32;
+    }
+
+    let mut fd: c_int = -1;
+    let mut token: c_int = 0;
+    // kIOPSNotifyPowerSource, posted when the power source changes
+    let status = unsafe {
+        notify_register_file_descriptor(
+            c"com.apple.system.powersources.source".as_ptr(),
+            &mut fd,
+            0,
+            &mut token,
+        )
+    };
+    if status != 0 {
+        warn!("Failed to listen for power source changes: {}", status);
+        return false;
+    }
+    let mut notifications = unsafe { File::from_raw_fd(fd) };
+    thread::spawn(move || {
+        // Each notification writes the registration's token
+        let mut token = [0u8; 4];
+        while notifications.read_exact(&mut token).is_ok() {
+            on_change();
+        }
+        warn!("Power source notifications stopped");
+    });
+    true
+}
+
+#[cfg(target_os = "windows")]
+fn watch(on_change: Callback) -> bool {
+    use std::ffi::c_void;
+    use windows::Win32::{
+        Foundation::{ERROR_SUCCESS, HANDLE},
+        System::{
+            Power::{PowerSettingRegisterNotification, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS},
+            SystemServices::GUID_ACDC_POWER_SOURCE,
+        },
+        UI::WindowsAndMessaging::DEVICE_NOTIFY_CALLBACK,
+    };
+
+    /// Runs on a system thread once on registering and on every AC/DC change
+    unsafe extern "system" fn on_power_setting(
+        context: *const c_void,
+        _type: u32,
+        _setting: *const c_void,
+    ) -> u32 {
+        let on_change = &*(context as *const Callback);
+        on_change();
+        0
+    }
+
+    // Never freed, the notification stays registered for the life of the app
+    let parameters = Box::leak(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+        Callback: Some(on_power_setting),
+        Context: Box::into_raw(Box::new(on_change)) as *mut c_void,
+    }));
+    let mut registration = std::ptr::null_mut();
+    let status = unsafe {
+        PowerSettingRegisterNotification(
+            &GUID_ACDC_POWER_SOURCE,
+            DEVICE_NOTIFY_CALLBACK,
+            HANDLE(parameters as *mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS as *mut c_void),
+            &mut registration,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        warn!("Failed to listen for power source changes: {:?}", status);
+        return false;
+    }
+    true
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn watch(on_change: Callback) -> bool {
+    let _ = on_change;
+    false
+}
+
+#[cfg(any(target_os = "linux", test))]
+mod udev_events {
+    /// Whether a `udevadm monitor` line reports a power supply event, such as
+    /// `UDEV  [4021.518714] change   /devices/.../power_supply/AC (power_supply)`.
+    /// Batteries also send one as they drain, which just reads an unchanged source.
+    pub fn is_power_supply_event(line: &str) -> bool {
+        let line = line.trim();
+        line.starts_with("UDEV") && line.ends_with("(power_supply)")
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn watch(on_change: super::Callback) -> bool {
+        use log::{debug, warn};
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+
+        let mut child = match Command::new("udevadm")
+            .args(["monitor", "--udev", "--subsystem-match=power_supply"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                debug!("udevadm is not available for power source events: {}", e);
+                return false;
+            }
+        };
+        let Some(stdout) = child.stdout.take() else {
+            return false;
+        };
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if is_power_supply_event(&line) {
+                    on_change();
+                }
+            }
+            let _ = child.wait();
+            warn!("udevadm monitor exited, no longer notified of power source changes");
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    #[test]
+    fn profiles_override_only_what_they_set() {
+        let mut settings = get_default_settings();
+        settings.selected_model = "large".to_string();
+        settings.model_unload_timeout = ModelUnloadTimeout::Min15;
+        settings.power_profiles.battery = PowerProfile {
+            model_id: Some("small".to_string()),
+            preload: Some(false),
+            unload_timeout: Some(ModelUnloadTimeout::Immediately),
+            compute_backend: None,
+        };
+        settings.power_profiles.plugged_in.preload = Some(true);
+        assert!(get_default_settings().power_profiles.is_empty());
+        assert!(!settings.power_profiles.is_empty());
+
+        let battery = resolve(&settings, PowerSource::Battery, |_| true);
+        assert_eq!(battery.model_id, "small");
+        assert_eq!(battery.unload_timeout, ModelUnloadTimeout::Immediately);
+
+        let plugged_in = resolve(&settings, PowerSource::PluggedIn, |_| true);
+        assert_eq!(plugged_in.model_id, "large");
+        assert!(plugged_in.preload);
+        assert_eq!(plugged_in.unload_timeout, ModelUnloadTimeout::Min15);
+
+        // A profile model that isn't downloaded can't be loaded
+        let missing = resolve(&settings, PowerSource::Battery, |id| id != "small");
+        assert_eq!(missing.model_id, "large");
+
+        // The GPU unless a profile keeps Whisper on the CPU
+        assert_eq!(plugged_in.compute_backend, ComputeBackend::Gpu);
+        settings.power_profiles.battery.compute_backend = Some(ComputeBackend::Cpu);
+        let battery = resolve(&settings, PowerSource::Battery, |_| true);
+        assert_eq!(battery.compute_backend, ComputeBackend::Cpu);
+    }
+
+    #[test]
+    fn udev_power_supply_events_count() {
+        use super::udev_events::is_power_supply_event;
+
+        assert!(is_power_supply_event(
+            "UDEV  [4021.518714] change   /devices/LNXSYSTM:00/LNXSYBUS:00/ACPI0003:00/power_supply/AC (power_supply)"
+        ));
+        assert!(!is_power_supply_event(
+            "monitor will print the received events for:"
+        ));
+        assert!(!is_power_supply_event(
+            "UDEV - the event which udev sends out after rule processing"
+        ));
+    }
+}
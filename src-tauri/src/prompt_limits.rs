@@ -0,0 +1,108 @@
+//! Size caps applied while building post-processing requests.
+//!
+//! A prompt variable longer than `max_variable_chars` is cut and ends with a
+//! marker saying how much was dropped. Past `MAX_SUBSTITUTION_CHARS` it is refused
+//! instead, since a prompt built around a fraction of it would be misleading. A
+//! request whose messages add up to more than `max_request_chars` is refused too.
+
+use crate::error::HandyError;
+use crate::llm_client::ChatMessage;
+use crate::settings::PromptLimits;
+use std::borrow::Cow;
+
+/// Longest value a prompt variable may hold at all, whatever the settings
+pub const MAX_SUBSTITUTION_CHARS: usize = 2_000_000;
+/// Bounds of both configurable limits
+pub const MIN_LIMIT_CHARS: usize = 1_000;
+pub const MAX_LIMIT_CHARS: usize = MAX_SUBSTITUTION_CHARS;
+
+/// Keeps the limits within bounds, and a variable no longer than a whole request
+pub fn clamp(limits: PromptLimits) -> PromptLimits {
+    let max_request_chars = limits
+        .max_request_chars
+        .clamp(MIN_LIMIT_CHARS, MAX_LIMIT_CHARS);
+    PromptLimits {
+        max_variable_chars: limits
+            .max_variable_chars
+            .clamp(MIN_LIMIT_CHARS, max_request_chars),
+        max_request_chars,
+    }
+}
+
+/// The value of the `${name}` variable, cut to the limit
+pub fn limit_variable<'a>(
+    name: &str,
+    value: &'a str,
+    limits: &PromptLimits,
+) -> Result<Cow<'a, str>, HandyError> {
+    // A string has at least a quarter as many chars as bytes, so most values are
+    // measured without counting
+    if value.len() / 4 > MAX_SUBSTITUTION_CHARS
+        || (value.len() > MAX_SUBSTITUTION_CHARS && value.chars().count() > MAX_SUBSTITUTION_CHARS)
+    {
+        return Err(HandyError::invalid_input(format!(
+            "${{{}}} is larger than the maximum of {} characters, the request was not sent",
+            name, MAX_SUBSTITUTION_CHARS
+        )));
+    }
+
+    let max_chars = limits.max_variable_chars;
+    let Some((cut, _)) = value.char_indices().nth(max_chars) else {
+        return Ok(Cow::Borrowed(value));
+    };
+    let dropped = value[cut..].chars().count();
+    Ok(Cow::Owned(format!(
+        "{}\n[{} more characters truncated]",
+        &value[..cut],
+        dropped
+    )))
+}
+
+/// Refuses requests whose messages are longer than the limit together
+pub fn check_request(messages: &[ChatMessage], limits: &PromptLimits) -> Result<(), HandyError> {
+    let chars: usize = messages.iter().map(|m| m.content.chars().count()).sum();
+    if chars > limits.max_request_chars {
+        return Err(HandyError::invalid_input(format!(
+            "The request is {} characters, more than the limit of {}, and was not sent",
+            chars, limits.max_request_chars
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variables_are_cut_or_refused() {
+        let limits = PromptLimits {
+            max_variable_chars: 5,
+            max_request_chars: 20,
+        };
+        assert_eq!(limit_variable("output", "héllo", &limits).unwrap(), "héllo");
+        assert_eq!(
+            limit_variable("output", "héllo wörld", &limits).unwrap(),
+            "héllo\n[6 more characters truncated]"
+        );
+        let huge = "a".repeat(MAX_SUBSTITUTION_CHARS + 1);
+        assert!(limit_variable("output", &huge, &limits).is_err());
+
+        let message = |content: &str| ChatMessage {
+            role: "user".to_string(),
+            content: content.to_string(),
+        };
+        assert!(check_request(&[message("0123456789"), message("0123456789")], &limits).is_ok());
+        assert!(check_request(&[message("0123456789"), message("0123456789!")], &limits).is_err());
+    }
+
+    #[test]
+    fn limits_are_clamped() {
+        let limits = clamp(PromptLimits {
+            max_variable_chars: 50_000,
+            max_request_chars: 10,
+        });
+        assert_eq!(limits.max_request_chars, MIN_LIMIT_CHARS);
+        assert_eq!(limits.max_variable_chars, MIN_LIMIT_CHARS);
+    }
+}
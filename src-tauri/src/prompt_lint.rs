@@ -0,0 +1,199 @@
+//! Offline checks for post-processing prompts. Nothing here makes a network call.
+
+use crate::formatting::{self, OUTPUT_TRANSFORMS};
+use crate::llm_client;
+use crate::settings::{
+    AppSettings, AuthStyle, PostProcessMisconfiguration, APPLE_INTELLIGENCE_PROVIDER_ID,
+};
+use serde::Serialize;
+use specta::Type;
+
+/// Rough characters-per-token ratio used to estimate prompt size
+const CHARS_PER_TOKEN: usize = 4;
+/// Prompts estimated above this many tokens get a warning
+const PROMPT_TOKEN_WARNING: usize = 4_000;
+/// More `${output}` tokens than this repeats the transcript wastefully
+const MAX_OUTPUT_TOKENS: usize = 2;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct PromptLintIssue {
+    pub severity: LintSeverity,
+    pub message: String,
+}
+
+impl PromptLintIssue {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: LintSeverity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: LintSeverity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks a prompt and the active provider configuration it would run with
+pub fn lint_prompt(settings: &AppSettings, prompt: &str) -> Vec<PromptLintIssue> {
+    let mut issues = Vec::new();
+
+    if prompt.trim().is_empty() {
+        issues.push(PromptLintIssue::error("The prompt is empty."));
+    }
+
+    let hardened = settings.prompt_hardening
+        && settings
+            .active_post_process_provider()
+            .is_some_and(llm_client::supports_role_separation);
+    let transforms = formatting::output_token_transforms(prompt);
+    if transforms.is_empty() {
+        if hardened {
+            issues.push(PromptLintIssue::warning(
+                "The prompt has no ${output} token. The transcript is still sent separately, but referencing it makes the instructions clearer.",
+            ));
+        } else {
+            issues.push(PromptLintIssue::error(
+                "The prompt has no ${output} token, so the transcript would never be sent.",
+            ));
+        }
+    } else if transforms.len() > MAX_OUTPUT_TOKENS {
+        issues.push(PromptLintIssue::warning(format!(
+            "The prompt includes the transcript {} times, which multiplies its token cost.",
+            transforms.len()
+        )));
+    }
+    for name in transforms.iter().flatten() {
+        if !OUTPUT_TRANSFORMS.contains(&name.as_str()) {
+            issues.push(PromptLintIssue::warning(format!(
+                "Unknown transform '{}' in ${{output:{}}}, the plain transcript will be used. Supported: {}.",
+                name,
+                name,
+                OUTPUT_TRANSFORMS.join(", ")
+            )));
+        }
+    }
+
+    let estimated_tokens = prompt.chars().count().div_ceil(CHARS_PER_TOKEN);
+    if estimated_tokens > PROMPT_TOKEN_WARNING {
+        issues.push(PromptLintIssue::warning(format!(
+            "The prompt is roughly {} tokens before the transcript is added, which adds latency and cost to every dictation.",
+            estimated_tokens
+        )));
+    }
+
+    match settings.post_process_provider_misconfiguration() {
+        Some(PostProcessMisconfiguration::NoProvider) => {
+            issues.push(PromptLintIssue::error(
+                "No post-processing provider is selected.",
+            ));
+        }
+        Some(PostProcessMisconfiguration::MissingModel { provider_id }) => {
+            issues.push(PromptLintIssue::error(format!(
+                "No model is configured for provider '{}'.",
+                provider_id
+            )));
+        }
+        Some(PostProcessMisconfiguration::RemoteProviderOffline { provider_id }) => {
+            issues.push(PromptLintIssue::error(format!(
+                "Offline mode is on, provider '{}' is not running on this machine.",
+                provider_id
+            )));
+        }
+        _ => {}
+    }
+
+    if let Some(provider) = settings.active_post_process_provider() {
+        // Local endpoints and Apple Intelligence work without a key
+        let needs_key = provider.auth_style != AuthStyle::None
+            && !provider.allow_base_url_edit
+            && provider.id != APPLE_INTELLIGENCE_PROVIDER_ID;
+        let has_key = settings
+            .post_process_api_keys
+            .get(&provider.id)
+            .is_some_and(|key| !key.trim().is_empty());
+        if needs_key && !has_key {
+            issues.push(PromptLintIssue::error(format!(
+                "No API key is set for provider '{}'.",
+                provider.label
+            )));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    fn configured_settings() -> AppSettings {
+        let mut settings = get_default_settings();
+        settings.post_process_provider_id = "openai".to_string();
+        settings
+            .post_process_models
+            .insert("openai".to_string(), "gpt-4o-mini".to_string());
+        settings
+            .post_process_api_keys
+            .insert("openai".to_string(), "sk-test".to_string());
+        settings
+    }
+
+    #[test]
+    fn well_formed_prompt_has_no_issues() {
+        let settings = configured_settings();
+        assert!(lint_prompt(&settings, "Fix grammar:\n${output}").is_empty());
+    }
+
+    #[test]
+    fn missing_output_token_depends_on_hardening() {
+        let mut settings = configured_settings();
+        let issues = lint_prompt(&settings, "Fix grammar.");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, LintSeverity::Warning);
+
+        settings.prompt_hardening = false;
+        let issues = lint_prompt(&settings, "Fix grammar.");
+        assert_eq!(issues[0].severity, LintSeverity::Error);
+    }
+
+    #[test]
+    fn flags_unknown_transform_and_missing_key() {
+        let mut settings = configured_settings();
+        settings
+            .post_process_api_keys
+            .insert("openai".to_string(), String::new());
+
+        let issues = lint_prompt(&settings, "Fix: ${output:shout}");
+        assert!(issues
+            .iter()
+            .any(|i| i.severity == LintSeverity::Warning && i.message.contains("'shout'")));
+        assert!(issues
+            .iter()
+            .any(|i| i.severity == LintSeverity::Error && i.message.contains("API key")));
+    }
+
+    #[test]
+    fn flags_missing_model_and_oversized_prompt() {
+        let mut settings = configured_settings();
+        settings
+            .post_process_models
+            .insert("openai".to_string(), String::new());
+        let long_prompt = format!("{} ${{output}}", "word ".repeat(4_000));
+
+        let issues = lint_prompt(&settings, &long_prompt);
+        assert!(issues.iter().any(|i| i.message.contains("No model")));
+        assert!(issues.iter().any(|i| i.message.contains("roughly")));
+    }
+}
@@ -0,0 +1,328 @@
+//! Locale-aware punctuation. Whisper and most LLMs emit ASCII punctuation for every
+//! language; this rewrites it following the conventions of the transcript's language.
+
+use crate::language;
+use crate::settings::AppSettings;
+
+/// Minimum detector confidence before an `auto` transcript gets localized punctuation.
+/// Stricter than for the prompt tokens, wrong punctuation is worse than none.
+const MIN_DETECTION_CONFIDENCE: f64 = 0.8;
+
+/// Narrow no-break space, used by French typography before high punctuation
+const NARROW_NBSP: char = '\u{202F}';
+
+/// Punctuation conventions of one language
+struct PunctuationRules {
+    /// ISO 639-1 code, as used by `selected_language`
+    language: &'static str,
+    /// ASCII punctuation replaced by full-width forms, which take no following space
+    full_width: &'static [(char, char)],
+    /// Opening and closing quotes replacing straight double quotes
+    quotes: Option<(&'static str, &'static str)>,
+    /// Punctuation preceded by a narrow no-break space
+    spaced: &'static [char],
+    /// Whether questions and exclamations open with ¿ and ¡
+    inverted: bool,
+}
+
+const RULES: &[PunctuationRules] = &[
+    PunctuationRules {
+        language: "ja",
+        full_width: &[(',', '、'), ('.', '。'), ('?', '？'), ('!', '！')],
+        quotes: Some(("「", "」")),
+        spaced: &[],
+        inverted: false,
+    },
+    PunctuationRules {
+        language: "zh",
+        full_width: &[
+            (',', '，'),
+            ('.', '。'),
+            ('?', '？'),
+            ('!', '！'),
+            (':', '：'),
+            (';', '；'),
+        ],
+        quotes: Some(("“", "”")),
+        spaced: &[],
+        inverted: false,
+    },
+    PunctuationRules {
+        language: "es",
+        full_width: &[],
+        quotes: Some(("«", "»")),
+        spaced: &[],
+        inverted: true,
+    },
+    PunctuationRules {
+        language: "fr",
+        full_width: &[],
+        quotes: Some(("«\u{202F}", "\u{202F}»")),
+        spaced: &['!', '?', ';', ':'],
+        inverted: false,
+    },
+    PunctuationRules {
+        language: "de",
+        full_width: &[],
+        quotes: Some(("„", "“")),
+        spaced: &[],
+        inverted: false,
+    },
+];
+
+/// Rewrites punctuation for the transcript's language. With `auto` the language is
+/// detected from the text, and the text is left alone unless detection is confident.
+/// Transcripts translated to English keep their ASCII punctuation.
+pub fn localize_punctuation(text: &str, settings: &AppSettings) -> String {
+    if settings.translate_to_english {
+        return text.to_string();
+    }
+    let code = if settings.selected_language == "auto" {
+        match language::detect_language_with_confidence(text, MIN_DETECTION_CONFIDENCE) {
+            Some(detected) => detected.code,
+            None => return text.to_string(),
+        }
+    } else {
+        settings.selected_language.clone()
+    };
+    // zh-Hans and zh-Hant share the same punctuation
+    let base = code.split('-').next().unwrap_or(&code);
+
+    match RULES.iter().find(|rules| rules.language == base) {
+        Some(rules) => apply_rules(text, rules),
+        None => text.to_string(),
+    }
+}
+
+fn apply_rules(text: &str, rules: &PunctuationRules) -> String {
+    let mut text = text.to_string();
+    if let Some((open, close)) = rules.quotes {
+        text = replace_quotes(&text, open, close);
+    }
+    if !rules.full_width.is_empty() {
+        text = widen_punctuation(&text, rules.full_width);
+    }
+    if !rules.spaced.is_empty() {
+        text = space_punctuation(&text, rules.spaced);
+    }
+    if rules.inverted {
+        text = add_inverted_marks(&text);
+    }
+    text
+}
+
+/// Whether the punctuation at `i` is part of a number, time, URL or ellipsis
+/// rather than prose, e.g. `3.14`, `1,000`, `10:30`, `https://` or `...`
+fn is_literal(chars: &[char], i: usize) -> bool {
+    let prev = i.checked_sub(1).map(|p| chars[p]);
+    let next = chars.get(i + 1).copied();
+    let between_digits =
+        matches!((prev, next), (Some(p), Some(n)) if p.is_ascii_digit() && n.is_ascii_digit());
+    let ellipsis = chars[i] == '.' && (prev == Some('.') || next == Some('.'));
+    let url = chars[i] == ':' && next == Some('/');
+    between_digits || ellipsis || url
+}
+
+/// Replaces paired straight double quotes. Unbalanced quotes are left as they are.
+fn replace_quotes(text: &str, open: &str, close: &str) -> String {
+    if text.matches('"').count() % 2 != 0 {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut opening = true;
+    for c in text.chars() {
+        if c == '"' {
+            out.push_str(if opening { open } else { close });
+            opening = !opening;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn widen_punctuation(text: &str, table: &[(char, char)]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let wide = table
+            .iter()
+            .find(|(ascii, _)| *ascii == chars[i])
+            .filter(|_| !is_literal(&chars, i));
+        match wide {
+            Some((_, wide)) => {
+                // Full-width punctuation carries its own spacing
+                while out.ends_with(' ') {
+                    out.pop();
+                }
+                out.push(*wide);
+                while chars.get(i + 1) == Some(&' ') {
+                    i += 1;
+                }
+            }
+            None => out.push(chars[i]),
+        }
+        i += 1;
+    }
+    out
+}
+
+fn space_punctuation(text: &str, spaced: &[char]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if spaced.contains(&c) && !is_literal(&chars, i) {
+            let trimmed_len = out.trim_end_matches([' ', NARROW_NBSP, '\u{00A0}']).len();
+            out.truncate(trimmed_len);
+            let follows_spaced = out.ends_with(|p: char| spaced.contains(&p));
+            // "?!" gets one space before the run, none inside it
+            if !out.is_empty() && !follows_spaced {
+                out.push(NARROW_NBSP);
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Opens Spanish questions and exclamations with ¿ and ¡
+fn add_inverted_marks(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() + 4);
+    let mut sentence = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        sentence.push(chars[i]);
+        if matches!(chars[i], '.' | '?' | '!') && !is_literal(&chars, i) {
+            // Keep a run such as "?!" in the same sentence
+            while matches!(chars.get(i + 1), Some('.' | '?' | '!')) {
+                i += 1;
+                sentence.push(chars[i]);
+            }
+            out.push_str(&invert_sentence(&sentence));
+            sentence.clear();
+        }
+        i += 1;
+    }
+    out.push_str(&sentence);
+    out
+}
+
+fn invert_sentence(sentence: &str) -> String {
+    let opening = match sentence.trim_end().chars().last() {
+        Some('?') => '¿',
+        Some('!') => '¡',
+        _ => return sentence.to_string(),
+    };
+    if sentence.contains(opening) {
+        return sentence.to_string();
+    }
+    let body_start = sentence.len() - sentence.trim_start().len();
+    format!(
+        "{}{}{}",
+        &sentence[..body_start],
+        opening,
+        &sentence[body_start..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    fn localize(text: &str, selected_language: &str) -> String {
+        let mut settings = get_default_settings();
+        settings.selected_language = selected_language.to_string();
+        localize_punctuation(text, &settings)
+    }
+
+    #[test]
+    fn japanese_uses_full_width_punctuation_and_brackets() {
+        assert_eq!(
+            localize("今日は晴れです, 散歩に行きます. 本当?", "ja"),
+            "今日は晴れです、散歩に行きます。本当？"
+        );
+        assert_eq!(
+            localize("彼は\"はい\"と言った. 3.5キロ", "ja"),
+            "彼は「はい」と言った。3.5キロ"
+        );
+    }
+
+    #[test]
+    fn chinese_variants_share_rules() {
+        assert_eq!(
+            localize("你好, 世界! 价格是1,000元.", "zh-Hans"),
+            "你好，世界！价格是1,000元。"
+        );
+        assert_eq!(localize("注意: 你好", "zh-Hant"), "注意：你好");
+    }
+
+    #[test]
+    fn spanish_opens_questions_and_exclamations() {
+        assert_eq!(
+            localize("Hola. Cómo estás? Qué bien!", "es"),
+            "Hola. ¿Cómo estás? ¡Qué bien!"
+        );
+        assert_eq!(
+            localize("¿Ya lo sabías? Cuesta 3.5 euros?", "es"),
+            "¿Ya lo sabías? ¿Cuesta 3.5 euros?"
+        );
+        assert_eq!(localize("Dijo \"sí\".", "es"), "Dijo «sí».");
+    }
+
+    #[test]
+    fn french_spaces_high_punctuation() {
+        assert_eq!(
+            localize("Vraiment ? Oui!", "fr"),
+            "Vraiment\u{202F}? Oui\u{202F}!"
+        );
+        assert_eq!(
+            localize("Quoi?! Voir https://example.com à 10:30 : merci", "fr"),
+            "Quoi\u{202F}?! Voir https://example.com à 10:30\u{202F}: merci"
+        );
+        assert_eq!(
+            localize("Il a dit \"bonjour\"", "fr"),
+            "Il a dit «\u{202F}bonjour\u{202F}»"
+        );
+    }
+
+    #[test]
+    fn german_uses_low_opening_quotes() {
+        assert_eq!(
+            localize("Er sagte \"Hallo\" und ging.", "de"),
+            "Er sagte „Hallo“ und ging."
+        );
+    }
+
+    #[test]
+    fn unknown_or_uncertain_languages_are_untouched() {
+        let text = "Is this \"fine\"? Yes!";
+        assert_eq!(localize(text, "en"), text);
+        assert_eq!(localize("Ok?", "auto"), "Ok?");
+        assert_eq!(localize("Say \"hi", "de"), "Say \"hi");
+    }
+
+    #[test]
+    fn auto_uses_confident_detection() {
+        assert_eq!(
+            localize(
+                "Hola amigo. Cómo estás hoy? Quiero saber si vienes a la fiesta de mañana?",
+                "auto"
+            ),
+            "Hola amigo. ¿Cómo estás hoy? ¿Quiero saber si vienes a la fiesta de mañana?"
+        );
+    }
+
+    #[test]
+    fn translated_transcripts_keep_ascii_punctuation() {
+        let mut settings = get_default_settings();
+        settings.selected_language = "es".to_string();
+        settings.translate_to_english = true;
+        assert_eq!(
+            localize_punctuation("How are you? Fine!", &settings),
+            "How are you? Fine!"
+        );
+    }
+}
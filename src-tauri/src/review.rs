@@ -0,0 +1,278 @@
+//! Review mode: instead of pasting right away, the transcript is shown in a small
+//! window where it can be edited, then confirmed (pasted) or discarded.
+
+use crate::actions::paste_transcript;
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent};
+use crate::pipeline::{DictationOutcome, PipelineOperation};
+use crate::settings::{get_settings, ReviewTimeoutAction};
+use crate::utils::{activate_application, FrontmostApplication};
+use log::{debug, error, info};
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+const REVIEW_WINDOW_LABEL: &str = "transcript_review";
+
+/// Payload of the `transcript-review` event
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct TranscriptReview {
+    pub id: u32,
+    pub text: String,
+    /// Seconds until the review resolves on its own, 0 when it waits indefinitely
+    pub timeout_secs: u32,
+}
+
+/// How a transcript left the review window
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewOutcome {
+    Confirmed,
+    Discarded,
+    /// Resolved by `review_timeout_action`
+    TimedOut,
+    /// Dropped because the operation was cancelled
+    Cancelled,
+}
+
+/// Payload of the `transcript-review-ended` event
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct TranscriptReviewEnded {
+    pub id: u32,
+    pub outcome: ReviewOutcome,
+}
+
+struct PendingReview {
+    review: TranscriptReview,
+    /// Application that had focus when the recording started, refocused before pasting
+    target_app: Option<FrontmostApplication>,
+    operation: Option<PipelineOperation>,
+}
+
+/// Transcripts waiting for the user to confirm or discard them
+#[derive(Default)]
+pub struct PendingReviews {
+    next_id: AtomicU32,
+    pending: Mutex<HashMap<u32, PendingReview>>,
+}
+
+impl PendingReviews {
+    fn insert(
+        &self,
+        text: String,
+        timeout_secs: u32,
+        target_app: Option<FrontmostApplication>,
+        operation: Option<PipelineOperation>,
+    ) -> TranscriptReview {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let review = TranscriptReview {
+            id,
+            text,
+            timeout_secs,
+        };
+        self.pending.lock().unwrap().insert(
+            id,
+            PendingReview {
+                review: review.clone(),
+                target_app,
+                operation,
+            },
+        );
+        review
+    }
+
+    fn take(&self, id: u32) -> Option<PendingReview> {
+        self.pending.lock().unwrap().remove(&id)
+    }
+
+    /// Drops every pending review, returning them oldest first
+    fn clear(&self) -> Vec<(u32, PendingReview)> {
+        let mut pending: Vec<_> = self.pending.lock().unwrap().drain().collect();
+        pending.sort_by_key(|(id, _)| *id);
+        pending
+    }
+
+    /// Pending reviews, oldest first
+    pub fn list(&self) -> Vec<TranscriptReview> {
+        let mut reviews: Vec<_> = self
+            .pending
+            .lock()
+            .unwrap()
+            .values()
+            .map(|p| p.review.clone())
+            .collect();
+        reviews.sort_by_key(|r| r.id);
+        reviews
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pending.lock().unwrap().is_empty()
+    }
+}
+
+/// Holds a transcript for review, shows the review window and arms the timeout.
+/// `target_app` had focus when the recording started and gets it back for pasting.
+pub fn begin_review(
+    app: &AppHandle,
+    text: String,
+    target_app: Option<FrontmostApplication>,
+    operation: Option<PipelineOperation>,
+) {
+    let settings = get_settings(app);
+    let timeout_secs = settings.review_timeout_secs;
+    let review =
+        app.state::<PendingReviews>()
+            .inner()
+            .insert(text, timeout_secs, target_app, operation);
+    let id = review.id;
+    debug!("Transcript {} is waiting for review", id);
+
+    emit_event(app, AppEvent::TranscriptReview(review));
+    show_review_window(app);
+
+    if timeout_secs > 0 {
+        let app = app.clone();
+        let action = settings.review_timeout_action;
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(timeout_secs as u64)).await;
+            // Resolving fails harmlessly if the user already acted on it
+            let result = match action {
+                ReviewTimeoutAction::Confirm => {
+                    resolve(&app, id, Some(None), ReviewOutcome::TimedOut)
+                }
+                ReviewTimeoutAction::Discard => resolve(&app, id, None, ReviewOutcome::TimedOut),
+            };
+            if result.is_ok() {
+                info!("Transcript review {} timed out ({:?})", id, action);
+            }
+        });
+    }
+}
+
+/// Pastes a reviewed transcript, optionally replaced by the user's edit
+pub fn confirm(app: &AppHandle, id: u32, edited_text: Option<String>) -> Result<(), HandyError> {
+    resolve(app, id, Some(edited_text), ReviewOutcome::Confirmed)
+}
+
+pub fn discard(app: &AppHandle, id: u32) -> Result<(), HandyError> {
+    resolve(app, id, None, ReviewOutcome::Discarded)
+}
+
+/// Ends a review, pasting the transcript when `paste` holds the optional edit
+fn resolve(
+    app: &AppHandle,
+    id: u32,
+    paste: Option<Option<String>>,
+    outcome: ReviewOutcome,
+) -> Result<(), HandyError> {
+    let pending = app
+        .state::<PendingReviews>()
+        .take(id)
+        .ok_or_else(|| HandyError::not_found(format!("No pending transcript with id {}", id)))?;
+    hide_review_window_if_idle(app);
+    emit_event(
+        app,
+        AppEvent::TranscriptReviewEnded(TranscriptReviewEnded { id, outcome }),
+    );
+
+    let Some(edited_text) = paste else {
+        debug!("Transcript review {} discarded ({:?})", id, outcome);
+        if let Some(operation) = &pending.operation {
+            operation.end(app, DictationOutcome::Discarded);
+        }
+        return Ok(());
+    };
+    let text = edited_text.unwrap_or(pending.review.text);
+    // Hand focus back to where the user was dictating before pasting
+    if let Some(target) = &pending.target_app {
+        activate_application(target);
+    }
+    let app = app.clone();
+    std::thread::spawn(move || paste_transcript(&app, text, pending.operation));
+    Ok(())
+}
+
+/// Drops every pending review, used when the current operation is cancelled
+pub fn cancel_all(app: &AppHandle) {
+    let Some(reviews) = app.try_state::<PendingReviews>() else {
+        return;
+    };
+    let cancelled = reviews.clear();
+    if cancelled.is_empty() {
+        return;
+    }
+    debug!("Discarded {} pending transcript review(s)", cancelled.len());
+    hide_review_window_if_idle(app);
+    for (id, pending) in cancelled {
+        emit_event(
+            app,
+            AppEvent::TranscriptReviewEnded(TranscriptReviewEnded {
+                id,
+                outcome: ReviewOutcome::Cancelled,
+            }),
+        );
+        if let Some(operation) = &pending.operation {
+            operation.end(app, DictationOutcome::Cancelled);
+        }
+    }
+}
+
+fn show_review_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(REVIEW_WINDOW_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+
+    let result = WebviewWindowBuilder::new(
+        app,
+        REVIEW_WINDOW_LABEL,
+        WebviewUrl::App("src/review/index.html".into()),
+    )
+    .title("Review Transcript")
+    .inner_size(440.0, 240.0)
+    .resizable(true)
+    .maximizable(false)
+    .minimizable(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .focused(true)
+    .build();
+    if let Err(e) = result {
+        error!("Failed to create transcript review window: {}", e);
+    }
+}
+
+/// Hides the review window once no transcripts are left to review
+fn hide_review_window_if_idle(app: &AppHandle) {
+    if !app.state::<PendingReviews>().is_empty() {
+        return;
+    }
+    if let Some(window) = app.get_webview_window(REVIEW_WINDOW_LABEL) {
+        let _ = window.hide();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_reviews_get_unique_ids_and_resolve_once() {
+        let reviews = PendingReviews::default();
+        let first = reviews.insert("first".to_string(), 30, None, None);
+        let second = reviews.insert("second".to_string(), 30, None, None);
+        assert_ne!(first.id, second.id);
+        assert_eq!(reviews.list(), vec![first.clone(), second.clone()]);
+
+        assert!(reviews.take(first.id).is_some());
+        assert!(reviews.take(first.id).is_none());
+        let cleared: Vec<u32> = reviews.clear().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(cleared, vec![second.id]);
+        assert!(reviews.is_empty());
+    }
+}
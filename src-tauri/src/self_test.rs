@@ -0,0 +1,379 @@
+//! On-demand diagnostics for support requests.
+//!
+//! `run` checks each subsystem a dictation depends on and reports pass or fail with
+//! a hint on how to fix it, so a user can paste a single report into an issue.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+use specta::Type;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::audio_toolkit::{get_cpal_host, list_input_devices};
+use crate::llm_client;
+use crate::managers::audio::default_input_name_for_role;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::power_profile;
+use crate::settings::{
+    get_settings, AppSettings, WindowsDeviceRole, APPLE_INTELLIGENCE_PROVIDER_ID,
+};
+use crate::shortcut::handy_keys::HandyKeysState;
+
+/// A provider that hasn't answered by then is treated as unreachable
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// The subsystem isn't in use with the current settings
+    Skipped,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct SelfTestCheck {
+    /// `model`, `microphone`, `input`, `shortcuts` or `provider`
+    pub id: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What the user can do about a failure
+    pub remediation: Option<String>,
+}
+
+impl SelfTestCheck {
+    fn pass(id: &str, detail: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            remediation: None,
+        }
+    }
+
+    fn fail(id: &str, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            remediation: Some(remediation.into()),
+        }
+    }
+
+    fn skipped(id: &str, detail: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            status: CheckStatus::Skipped,
+            detail: detail.into(),
+            remediation: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct SelfTestReport {
+    /// True when no check failed
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    fn new(checks: Vec<SelfTestCheck>) -> Self {
+        Self {
+            passed: checks.iter().all(|c| c.status != CheckStatus::Fail),
+            checks,
+        }
+    }
+}
+
+pub async fn run(app: &AppHandle) -> SelfTestReport {
+    let settings = get_settings(app);
+
+    let model = {
+        let app = app.clone();
+        let selected_model = power_profile::selected_model(&app, &settings);
+        tauri::async_runtime::spawn_blocking(move || check_model(&app, &selected_model))
+            .await
+            .unwrap_or_else(|e| {
+                SelfTestCheck::fail("model", e.to_string(), "Restart Handy and try again.")
+            })
+    };
+
+    let checks = vec![
+        model,
+        check_microphone(&settings),
+        check_input(app),
+        check_shortcuts(app, &settings),
+        check_provider(&settings).await,
+    ];
+
+    for check in &checks {
+        log::info!(
+            "Self-test {}: {:?} - {}",
+            check.id,
+            check.status,
+            check.detail
+        );
+    }
+    SelfTestReport::new(checks)
+}
+
+fn check_model(app: &AppHandle, selected_model: &str) -> SelfTestCheck {
+    if selected_model.is_empty() {
+        return SelfTestCheck::fail(
+            "model",
+            "No transcription model is selected.",
+            "Download a model in the Models settings and select it.",
+        );
+    }
+
+    let model_manager = app.state::<Arc<ModelManager>>();
+    let Some(info) = model_manager.get_model_info(selected_model) else {
+        return SelfTestCheck::fail(
+            "model",
+            format!("The selected model '{}' is unknown.", selected_model),
+            "Select one of the available models.",
+        );
+    };
+    if !info.is_downloaded {
+        return SelfTestCheck::fail(
+            "model",
+            format!("{} is not downloaded.", info.name),
+            "Download the model again from the Models settings.",
+        );
+    }
+
+    let transcription_manager = app.state::<Arc<TranscriptionManager>>();
+    if transcription_manager.is_model_loaded()
+        && transcription_manager.get_current_model().as_deref() == Some(selected_model)
+    {
+        return SelfTestCheck::pass("model", format!("{} is loaded.", info.name));
+    }
+    match transcription_manager.load_model(selected_model) {
+        Ok(()) => {
+            transcription_manager.maybe_unload_immediately("self-test");
+            SelfTestCheck::pass("model", format!("{} loaded successfully.", info.name))
+        }
+        Err(e) => SelfTestCheck::fail(
+            "model",
+            format!("{} failed to load: {}", info.name, e),
+            "Delete the model and download it again, or pick a smaller model.",
+        ),
+    }
+}
+
+fn check_microphone(settings: &AppSettings) -> SelfTestCheck {
+    let devices = match list_input_devices() {
+        Ok(devices) => devices,
+        Err(e) => {
+            return SelfTestCheck::fail(
+                "microphone",
+                format!("Input devices could not be listed: {}", e),
+                "Check that Handy has microphone permission in your system settings.",
+            )
+        }
+    };
+
+    // Without a selection, Windows opens the default input of the configured role
+    let role_default = if settings.selected_microphone.is_none() {
+        default_input_name_for_role(settings.windows_default_device_role)
+    } else {
+        None
+    };
+    let selected = settings
+        .selected_microphone
+        .as_ref()
+        .or(role_default.as_ref())
+        .and_then(|name| devices.into_iter().find(|d| &d.name == name));
+    let (device, fallback_note) = match (selected, &settings.selected_microphone) {
+        (Some(info), _) => (Some(info.device), None),
+        (None, Some(name)) => (
+            get_cpal_host().default_input_device(),
+            Some(format!(
+                "'{}' is not connected, using the default input. ",
+                name
+            )),
+        ),
+        (None, None) => (get_cpal_host().default_input_device(), None),
+    };
+    let role_note = if cfg!(target_os = "windows") && settings.selected_microphone.is_none() {
+        let role = match settings.windows_default_device_role {
+            WindowsDeviceRole::Console => "console",
+            WindowsDeviceRole::Communications => "communications",
+        };
+        format!("Windows {} default: ", role)
+    } else {
+        String::new()
+    };
+
+    let Some(device) = device else {
+        return SelfTestCheck::fail(
+            "microphone",
+            "No input device is available.",
+            "Connect a microphone, or check microphone permission in your system settings.",
+        );
+    };
+    let name = device.name().unwrap_or_else(|_| "Unknown".into());
+    match device.default_input_config() {
+        Ok(config) => SelfTestCheck::pass(
+            "microphone",
+            format!(
+                "{}{}{} is accessible ({} Hz, {} channels).",
+                fallback_note.unwrap_or_default(),
+                role_note,
+                name,
+                config.sample_rate().0,
+                config.channels()
+            ),
+        ),
+        Err(e) => SelfTestCheck::fail(
+            "microphone",
+            format!("{} can't be opened: {}", name, e),
+            "Check microphone permission in your system settings, or select another microphone.",
+        ),
+    }
+}
+
+fn check_input(app: &AppHandle) -> SelfTestCheck {
+    match crate::commands::initialize_enigo(app.clone()) {
+        Ok(()) => SelfTestCheck::pass("input", "Keyboard simulation is available for pasting."),
+        Err(e) => SelfTestCheck::fail(
+            "input",
+            e.to_string(),
+            if cfg!(target_os = "macos") {
+                "Grant Handy accessibility permission in System Settings > Privacy & Security."
+            } else {
+                "Pasting needs keyboard simulation; on Wayland install the tool for your paste method."
+            },
+        ),
+    }
+}
+
+fn check_shortcuts(app: &AppHandle, settings: &AppSettings) -> SelfTestCheck {
+    let Some(state) = app.try_state::<HandyKeysState>() else {
+        return SelfTestCheck::fail(
+            "shortcuts",
+            "The shortcut listener is not running.",
+            if cfg!(target_os = "macos") {
+                "Grant Handy accessibility permission, then restart it."
+            } else {
+                "Restart Handy; check the logs if shortcuts still don't start."
+            },
+        );
+    };
+    let registered = match state.registered_bindings() {
+        Ok(registered) => registered,
+        Err(e) => {
+            return SelfTestCheck::fail(
+                "shortcuts",
+                format!("The shortcut listener stopped: {}", e),
+                "Restart Handy; check the logs if shortcuts still don't start.",
+            )
+        }
+    };
+
+    // Cancel is only registered while recording
+    let mut missing: Vec<&str> = settings
+        .bindings
+        .values()
+        .filter(|b| b.id != "cancel" && !b.current_binding.is_empty())
+        .filter(|b| !registered.contains(&b.id))
+        .map(|b| b.current_binding.as_str())
+        .collect();
+    missing.sort_unstable();
+
+    if missing.is_empty() {
+        SelfTestCheck::pass(
+            "shortcuts",
+            format!("{} shortcuts are registered.", registered.len()),
+        )
+    } else {
+        SelfTestCheck::fail(
+            "shortcuts",
+            format!("Not registered: {}.", missing.join(", ")),
+            "Another application may own these shortcuts; assign different ones.",
+        )
+    }
+}
+
+async fn check_provider(settings: &AppSettings) -> SelfTestCheck {
+    if !settings.post_process_enabled {
+        return SelfTestCheck::skipped("provider", "Post-processing is disabled.");
+    }
+    if let Some(misconfiguration) = settings.post_process_misconfiguration() {
+        return SelfTestCheck::fail(
+            "provider",
+            format!("Post-processing is misconfigured: {:?}", misconfiguration),
+            "Finish the provider, model and prompt setup in the Post Process settings.",
+        );
+    }
+    let Some(provider) = settings.active_post_process_provider() else {
+        return SelfTestCheck::fail(
+            "provider",
+            "No post-processing provider is selected.",
+            "Select a provider in the Post Process settings.",
+        );
+    };
+
+    if provider.id == APPLE_INTELLIGENCE_PROVIDER_ID {
+        return if crate::commands::check_apple_intelligence_available() {
+            SelfTestCheck::pass("provider", "Apple Intelligence is available.")
+        } else {
+            SelfTestCheck::fail(
+                "provider",
+                "Apple Intelligence is not available on this device.",
+                "Turn on Apple Intelligence in System Settings, or pick another provider.",
+            )
+        };
+    }
+
+    let api_key = settings
+        .post_process_api_keys
+        .get(&provider.id)
+        .cloned()
+        .unwrap_or_default();
+    match tokio::time::timeout(
+        PROVIDER_TIMEOUT,
+        llm_client::fetch_models(provider, api_key),
+    )
+    .await
+    {
+        Ok(Ok(_)) => SelfTestCheck::pass("provider", format!("{} is reachable.", provider.label)),
+        Ok(Err(e)) => SelfTestCheck::fail(
+            "provider",
+            format!("{} did not respond correctly: {}", provider.label, e),
+            "Check the base URL and API key, and that the provider is running.",
+        ),
+        Err(_) => SelfTestCheck::fail(
+            "provider",
+            format!(
+                "{} did not answer within {} seconds.",
+                provider.label,
+                PROVIDER_TIMEOUT.as_secs()
+            ),
+            "Check your network connection and the provider's base URL.",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_fails_only_on_failed_checks() {
+        let report = SelfTestReport::new(vec![
+            SelfTestCheck::pass("model", "loaded"),
+            SelfTestCheck::skipped("provider", "disabled"),
+        ]);
+        assert!(report.passed);
+
+        let report = SelfTestReport::new(vec![
+            SelfTestCheck::pass("model", "loaded"),
+            SelfTestCheck::fail("microphone", "missing", "connect one"),
+        ]);
+        assert!(!report.passed);
+    }
+}
@@ -0,0 +1,274 @@
+//! Log of today's dictations, separate from history so a "today" view still works
+//! with a small `history_limit`.
+//!
+//! Entries record when, how many words, into which app, for how long and whether
+//! the dictation worked; the text is only kept with `session_log_store_text`. The
+//! log starts over at local midnight, and is written to disk until then only when
+//! `persist_session_log` is on.
+
+use crate::dictation_metrics::word_count;
+use crate::paths;
+use crate::settings::AppSettings;
+use chrono::{Local, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const LOG_FILE_NAME: &str = "session_log.json";
+/// Speaking rates of shorter recordings swing wildly on a single word
+const MIN_RATE_DURATION_MS: u32 = 2_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct SessionLogEntry {
+    pub timestamp: i64,
+    pub word_count: u32,
+    /// Application dictated into, when it could be determined
+    pub app: Option<String>,
+    /// Length of the recording
+    pub duration_ms: u32,
+    /// False when transcription failed
+    pub success: bool,
+    /// Only kept with `session_log_store_text`
+    pub text: Option<String>,
+}
+
+/// Totals of today's dictations
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct SessionSummary {
+    /// `YYYY-MM-DD`, in local time
+    pub date: String,
+    pub dictations: u32,
+    pub failed: u32,
+    pub words: u32,
+    pub duration_ms: u64,
+    /// Over the successful dictations long enough to rate, None without any
+    pub average_wpm: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct DayLog {
+    /// `YYYY-MM-DD` in local time
+    date: String,
+    entries: Vec<SessionLogEntry>,
+}
+
+pub struct SessionLog {
+    /// Where the log is persisted, None when the app data dir is unavailable
+    path: Option<PathBuf>,
+    log: Mutex<DayLog>,
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+impl SessionLog {
+    /// Creates the log, restoring today's persisted entries if persistence is enabled
+    pub fn load(app: &AppHandle, settings: &AppSettings) -> Self {
+        let path = Some(paths::get(app).data.join(LOG_FILE_NAME));
+        Self::with_path(path, settings, &today())
+    }
+
+    fn with_path(path: Option<PathBuf>, settings: &AppSettings, date: &str) -> Self {
+        let stored = if settings.persist_session_log {
+            path.as_deref().and_then(read_log)
+        } else {
+            None
+        };
+        let session_log = Self {
+            path,
+            log: Mutex::new(DayLog {
+                date: date.to_string(),
+                entries: Vec::new(),
+            }),
+        };
+        match stored {
+            Some(stored) if stored.date == date => *session_log.log.lock().unwrap() = stored,
+            // A previous day's log is of no use anymore
+            Some(_) => session_log.remove_file(),
+            None => {}
+        }
+        session_log
+    }
+
+    /// Records a dictation, `text` is None when its transcription failed
+    pub fn record(
+        &self,
+        settings: &AppSettings,
+        text: Option<&str>,
+        app: Option<String>,
+        duration: Duration,
+    ) {
+        let entry = SessionLogEntry {
+            timestamp: Utc::now().timestamp(),
+            word_count: text.map_or(0, |text| word_count(text) as u32),
+            app,
+            duration_ms: duration.as_millis() as u32,
+            success: text.is_some(),
+            text: text
+                .filter(|_| settings.session_log_store_text)
+                .map(str::to_string),
+        };
+        self.push(entry, settings, &today());
+    }
+
+    fn push(&self, entry: SessionLogEntry, settings: &AppSettings, date: &str) {
+        let mut log = self.log.lock().unwrap();
+        self.roll_over(&mut log, date);
+        log.entries.push(entry);
+        if settings.persist_session_log {
+            self.save(&log);
+        }
+    }
+
+    /// Today's entries, oldest first
+    pub fn entries(&self) -> Vec<SessionLogEntry> {
+        self.entries_on(&today())
+    }
+
+    fn entries_on(&self, date: &str) -> Vec<SessionLogEntry> {
+        let mut log = self.log.lock().unwrap();
+        self.roll_over(&mut log, date);
+        log.entries.clone()
+    }
+
+    pub fn summary(&self) -> SessionSummary {
+        summarize(&today(), &self.entries())
+    }
+
+    pub fn clear(&self) {
+        let mut log = self.log.lock().unwrap();
+        log.entries.clear();
+        self.remove_file();
+    }
+
+    /// Applies changed settings. Turning persistence off deletes the file, and
+    /// turning text off drops the text already logged.
+    pub fn apply_settings(&self, settings: &AppSettings) {
+        let mut log = self.log.lock().unwrap();
+        if !settings.session_log_store_text {
+            for entry in &mut log.entries {
+                entry.text = None;
+            }
+        }
+        if settings.persist_session_log {
+            self.save(&log);
+        } else {
+            self.remove_file();
+        }
+    }
+
+    /// Starts a new day's log once the date changed, removing the previous day's file
+    fn roll_over(&self, log: &mut DayLog, date: &str) {
+        if log.date == date {
+            return;
+        }
+        if !log.entries.is_empty() {
+            debug!("Session log rolled over to {}", date);
+        }
+        log.date = date.to_string();
+        log.entries.clear();
+        self.remove_file();
+    }
+
+    fn save(&self, log: &DayLog) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string(log)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to persist session log: {}", e);
+        }
+    }
+
+    fn remove_file(&self) {
+        if let Some(path) = self.path.as_ref().filter(|p| p.exists()) {
+            if let Err(e) = std::fs::remove_file(path) {
+                warn!("Failed to remove persisted session log: {}", e);
+            }
+        }
+    }
+}
+
+fn summarize(date: &str, entries: &[SessionLogEntry]) -> SessionSummary {
+    let rated: Vec<_> = entries
+        .iter()
+        .filter(|e| e.success && e.word_count > 0 && e.duration_ms >= MIN_RATE_DURATION_MS)
+        .map(|e| f64::from(e.word_count) * 60_000.0 / f64::from(e.duration_ms))
+        .collect();
+    SessionSummary {
+        date: date.to_string(),
+        dictations: entries.len() as u32,
+        failed: entries.iter().filter(|e| !e.success).count() as u32,
+        words: entries.iter().map(|e| e.word_count).sum(),
+        duration_ms: entries.iter().map(|e| u64::from(e.duration_ms)).sum(),
+        average_wpm: (!rated.is_empty()).then(|| rated.iter().sum::<f64>() / rated.len() as f64),
+    }
+}
+
+fn read_log(path: &Path) -> Option<DayLog> {
+    let json = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json)
+        .map_err(|e| debug!("Ignoring unreadable session log file: {}", e))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    fn entry(word_count: u32, duration_ms: u32, success: bool) -> SessionLogEntry {
+        SessionLogEntry {
+            timestamp: 0,
+            word_count,
+            app: None,
+            duration_ms,
+            success,
+            text: None,
+        }
+    }
+
+    #[test]
+    fn log_starts_over_at_midnight() {
+        let settings = get_default_settings();
+        let log = SessionLog::with_path(None, &settings, "2026-03-01");
+        log.push(entry(10, 5_000, true), &settings, "2026-03-01");
+        log.push(entry(0, 1_000, false), &settings, "2026-03-01");
+        assert_eq!(log.entries_on("2026-03-01").len(), 2);
+
+        let summary = summarize("2026-03-01", &log.entries_on("2026-03-01"));
+        assert_eq!(summary.dictations, 2);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.words, 10);
+        assert_eq!(summary.average_wpm, Some(120.0));
+
+        assert!(log.entries_on("2026-03-02").is_empty());
+    }
+
+    #[test]
+    fn persisted_log_is_only_restored_on_the_same_day() {
+        let path =
+            std::env::temp_dir().join(format!("handy-session-log-{}.json", std::process::id()));
+        let mut settings = get_default_settings();
+        settings.persist_session_log = true;
+        let log = SessionLog::with_path(Some(path.clone()), &settings, "2026-03-01");
+        log.push(entry(3, 1_000, true), &settings, "2026-03-01");
+
+        let restored = SessionLog::with_path(Some(path.clone()), &settings, "2026-03-01");
+        assert_eq!(restored.entries_on("2026-03-01").len(), 1);
+        restored.clear();
+        assert!(!path.exists());
+
+        log.push(entry(3, 1_000, true), &settings, "2026-03-01");
+        let next_day = SessionLog::with_path(Some(path.clone()), &settings, "2026-03-02");
+        assert!(next_day.entries_on("2026-03-02").is_empty());
+        assert!(!path.exists());
+    }
+}
@@ -1,13 +1,17 @@
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent};
 use log::{debug, warn};
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 use specta::Type;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tauri::AppHandle;
 use tauri_plugin_store::StoreExt;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub const APPLE_INTELLIGENCE_PROVIDER_ID: &str = "apple_intelligence";
 pub const APPLE_INTELLIGENCE_DEFAULT_MODEL_ID: &str = "Apple Intelligence";
+pub const GEMINI_PROVIDER_ID: &str = "gemini";
 
 #[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
 #[serde(rename_all = "lowercase")]
@@ -83,6 +87,61 @@ pub struct ShortcutBinding {
     pub description: String,
     pub default_binding: String,
     pub current_binding: String,
+    /// Overrides the global `review_before_paste` setting for this binding
+    #[serde(default)]
+    pub review_before_paste: Option<bool>,
+    /// Overrides the global `feedback_mode` setting for this binding
+    #[serde(default)]
+    pub feedback_mode: Option<FeedbackMode>,
+    /// Whether the key is the physical key or the character it types, see `shortcut::layout`
+    #[serde(default)]
+    pub match_mode: BindingMatchMode,
+    /// Overrides the global `collapse_to_single_line` setting for this binding
+    #[serde(default)]
+    pub collapse_to_single_line: Option<bool>,
+    /// Model this binding transcribes with instead of `selected_model`
+    #[serde(default)]
+    pub selected_model: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingMatchMode {
+    /// The character the key types on the current layout
+    Symbol,
+    /// The key at that position on a US QWERTY keyboard
+    #[default]
+    Scancode,
+}
+
+/// Matching used for custom word correction, see `audio_toolkit::text::WordMatcher`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectionAlgorithm {
+    /// Edit distance favouring words that sound alike, best for English
+    #[default]
+    Levenshtein,
+    /// Also forgives two swapped letters
+    DamerauLevenshtein,
+    /// Forgiving of differing accents and endings, for inflected languages
+    Jaro,
+    /// Only replaces words that sound alike
+    Phonetic,
+}
+
+/// Selects a prompt while a matching application has focus
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct AppPromptRule {
+    pub id: String,
+    /// Application identifier or name, compared case-insensitively
+    pub app_matcher: String,
+    pub prompt_id: String,
+    /// Forces post-processing on or off, None keeps the global setting
+    #[serde(default)]
+    pub post_process: Option<bool>,
+    /// Forces single-line pasting on or off, over the binding and global settings
+    #[serde(default)]
+    pub collapse_to_single_line: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Type)]
@@ -90,6 +149,84 @@ pub struct LLMPrompt {
     pub id: String,
     pub name: String,
     pub prompt: String,
+    /// Structured output requested from providers that support `response_format`
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
+    /// What to paste when the response is JSON
+    #[serde(default)]
+    pub json_output: JsonOutput,
+    /// Short label or emoji shown in compact UIs such as the tray menu
+    #[serde(default)]
+    pub short_label: Option<String>,
+    /// Sampling seed for reproducible output, sent to providers that accept one
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// Longest short label of a prompt, also the length names are cut to without one
+pub const PROMPT_SHORT_LABEL_MAX_CHARS: usize = 16;
+
+impl LLMPrompt {
+    /// Label for compact UIs, the start of the name when no short label is set
+    pub fn display_label(&self) -> String {
+        if let Some(label) = self.short_label.as_deref().map(str::trim) {
+            if !label.is_empty() {
+                return label.to_string();
+            }
+        }
+        let name = self.name.trim();
+        if name.chars().count() <= PROMPT_SHORT_LABEL_MAX_CHARS {
+            return name.to_string();
+        }
+        let start: String = name
+            .chars()
+            .take(PROMPT_SHORT_LABEL_MAX_CHARS - 1)
+            .collect();
+        format!("{}…", start.trim_end())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseFormat {
+    JsonObject,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonOutput {
+    /// Paste the response unchanged
+    #[default]
+    Raw,
+    Pretty,
+    /// Paste a single top-level field
+    Field(String),
+}
+
+impl PostProcessProvider {
+    /// Whether requests stay on this machine, see `AppSettings::offline_mode`
+    pub fn is_local(&self) -> bool {
+        self.id == APPLE_INTELLIGENCE_PROVIDER_ID || is_local_base_url(&self.base_url)
+    }
+}
+
+/// Whether `base_url` points at this machine. Only loopback hosts count as local,
+/// a server elsewhere on the network does not.
+pub fn is_local_base_url(base_url: &str) -> bool {
+    if base_url.starts_with("apple-intelligence://") {
+        return true;
+    }
+    let Ok(url) = reqwest::Url::parse(base_url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Type)]
@@ -101,6 +238,170 @@ pub struct PostProcessProvider {
     pub allow_base_url_edit: bool,
     #[serde(default)]
     pub models_endpoint: Option<String>,
+    /// Path of the chat endpoint relative to `base_url`, e.g. for Azure-style gateways
+    #[serde(default = "default_chat_completions_path")]
+    pub chat_completions_path: String,
+    /// Sent as the `api-version` query parameter when set
+    #[serde(default)]
+    pub api_version: Option<String>,
+    /// Maximum number of requests in flight at once for this provider, 0 means unlimited.
+    /// Requests beyond the limit wait in a queue.
+    #[serde(default = "default_llm_max_concurrency")]
+    pub max_concurrent_requests: u32,
+    /// Maximum number of requests started per minute, 0 means unlimited
+    #[serde(default = "default_llm_requests_per_minute")]
+    pub requests_per_minute: u32,
+    #[serde(default)]
+    pub auth_style: AuthStyle,
+    /// Additional headers sent with every request, e.g. an organization ID
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+    /// Additional query parameters appended to every request URL, e.g. `api-version`
+    #[serde(default)]
+    pub extra_query_params: HashMap<String, String>,
+    /// Sent as `OpenAI-Organization` by OpenAI-compatible providers.
+    /// Treated like an API key: some orgs consider it sensitive.
+    #[serde(default)]
+    pub organization_id: Option<String>,
+    /// Sent as `OpenAI-Project` by OpenAI-compatible providers, also sensitive
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+/// The piece of post-processing configuration that is missing
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum PostProcessMisconfiguration {
+    NoProvider,
+    MissingModel {
+        provider_id: String,
+    },
+    NoPromptSelected,
+    PromptNotFound {
+        prompt_id: String,
+    },
+    /// Offline mode is on and the selected provider isn't on this machine
+    RemoteProviderOffline {
+        provider_id: String,
+    },
+    EmptyPrompt {
+        prompt_id: String,
+    },
+}
+
+/// How the configured input channels of a microphone are combined
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelMixMode {
+    #[default]
+    Average,
+    /// Use only the first configured channel
+    Select,
+}
+
+/// Input channels used for one microphone
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct MicChannelConfig {
+    /// One-based channel numbers, as audio interfaces label their inputs
+    pub channels: Vec<u32>,
+    #[serde(default)]
+    pub mode: ChannelMixMode,
+}
+
+/// Which signals mark the start and end of a recording
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackMode {
+    Sound,
+    Overlay,
+    #[default]
+    Both,
+    None,
+}
+
+impl FeedbackMode {
+    pub fn plays_sound(self) -> bool {
+        matches!(self, FeedbackMode::Sound | FeedbackMode::Both)
+    }
+
+    pub fn shows_overlay(self) -> bool {
+        matches!(self, FeedbackMode::Overlay | FeedbackMode::Both)
+    }
+
+    /// The same overlay, but never a sound
+    pub fn without_sound(self) -> FeedbackMode {
+        if self.shows_overlay() {
+            FeedbackMode::Overlay
+        } else {
+            FeedbackMode::None
+        }
+    }
+}
+
+/// When the stop sound plays. The later timings play a short tick when the
+/// recording stops instead, and the stop sound only once the dictation got that far.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackTiming {
+    #[default]
+    RecordingStop,
+    TranscriptionComplete,
+    PasteComplete,
+}
+
+/// What happens to a transcript left in review past the timeout
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewTimeoutAction {
+    Confirm,
+    #[default]
+    Discard,
+}
+
+/// Where transcripts of files picked up from a watch folder go, besides history
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchFolderOutput {
+    #[default]
+    HistoryOnly,
+    /// A `.txt` file next to the audio file, with the same name
+    Sidecar,
+    /// Appended to `journal_path`, separated like file dictation entries
+    Journal,
+}
+
+/// A folder whose new audio files are transcribed automatically
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct WatchFolderConfig {
+    pub id: String,
+    pub path: String,
+    pub enabled: bool,
+    /// Post-process the transcripts, whatever the global setting
+    #[serde(default)]
+    pub post_process: bool,
+    #[serde(default)]
+    pub output: WatchFolderOutput,
+    #[serde(default)]
+    pub journal_path: Option<String>,
+}
+
+/// How the API key is sent to a post-processing provider
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthStyle {
+    /// `Authorization: Bearer <key>` (or `x-api-key` for Anthropic)
+    Bearer,
+    /// `api-key: <key>` header, as used by Azure OpenAI
+    ApiKeyHeader,
+    /// `?api-key=<key>` query parameter
+    QueryParam,
+    None,
+}
+
+impl Default for AuthStyle {
+    fn default() -> Self {
+        AuthStyle::Bearer
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
@@ -124,6 +425,64 @@ pub enum ModelUnloadTimeout {
     Sec5, // Debug mode only
 }
 
+/// Overrides applied on one power source, unset fields keep the regular setting
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Type)]
+pub struct PowerProfile {
+    /// Used instead of `selected_model` while it is downloaded
+    #[serde(default)]
+    pub model_id: Option<String>,
+    /// Load the model as soon as the profile applies instead of on the first dictation
+    #[serde(default)]
+    pub preload: Option<bool>,
+    #[serde(default)]
+    pub unload_timeout: Option<ModelUnloadTimeout>,
+    /// Where Whisper models run, the other engines always use the CPU
+    #[serde(default)]
+    pub compute_backend: Option<ComputeBackend>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ComputeBackend {
+    /// The GPU, subject to `vram_guard_behavior`
+    Gpu,
+    Cpu,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Type)]
+pub struct PowerProfiles {
+    #[serde(default)]
+    pub battery: PowerProfile,
+    /// Also used by computers without a battery
+    #[serde(default)]
+    pub plugged_in: PowerProfile,
+}
+
+impl PowerProfiles {
+    /// Neither profile overrides anything, so the power source doesn't matter
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Size caps for post-processing requests, see `prompt_limits`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+pub struct PromptLimits {
+    /// Longer prompt variables are cut, with a marker at the end
+    pub max_variable_chars: usize,
+    /// Requests whose messages are longer together are not sent
+    pub max_request_chars: usize,
+}
+
+impl Default for PromptLimits {
+    fn default() -> Self {
+        Self {
+            max_variable_chars: 100_000,
+            max_request_chars: 200_000,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
 #[serde(rename_all = "snake_case")]
 pub enum PasteMethod {
@@ -132,6 +491,8 @@ pub enum PasteMethod {
     None,
     ShiftInsert,
     CtrlShiftV,
+    /// Insert through the accessibility API, falling back to CtrlV when unsupported
+    Accessibility,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
@@ -141,6 +502,33 @@ pub enum ClipboardHandling {
     CopyToClipboard,
 }
 
+/// Text transform applied at paste time for a specific target application
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputTransform {
+    AsIs,
+    SnakeCase,
+    KebabCase,
+    Lowercase,
+}
+
+/// What to do when translated output doesn't look like English
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationGuard {
+    Off,
+    Flag,
+    Retry,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncationStrategy {
+    None,
+    Truncate,
+    Ellipsis,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
 #[serde(rename_all = "snake_case")]
 pub enum RecordingRetentionPeriod {
@@ -151,6 +539,69 @@ pub enum RecordingRetentionPeriod {
     Months3,
 }
 
+/// `User-Agent` sent with post-processing requests
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Type, Default)]
+#[serde(tag = "mode", content = "value", rename_all = "snake_case")]
+pub enum LlmUserAgent {
+    /// Identifies the app as Handy
+    #[default]
+    Default,
+    Custom(String),
+    /// No `User-Agent` header at all
+    Omit,
+}
+
+/// Format new recordings are stored in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingFormat {
+    #[default]
+    Wav,
+    /// Lossless, about half the size of WAV
+    Flac,
+    /// Lossy speech codec, around a tenth of the size of WAV
+    Opus,
+}
+
+/// What to do when a model won't fit in the free GPU memory, see `vram_guard`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VramGuardBehavior {
+    /// Load the model anyway
+    #[default]
+    Off,
+    /// Fail the load with an error saying how much memory is missing
+    Refuse,
+    /// Load the most accurate downloaded model that fits instead
+    SmallerModel,
+    /// Load the model on the CPU, slower but without paging GPU memory
+    CpuFallback,
+}
+
+/// Which of Windows' default inputs is used when no microphone is selected
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowsDeviceRole {
+    /// The default device for general use
+    #[default]
+    Console,
+    /// The default device for calls, usually a headset
+    Communications,
+}
+
+/// What cancelling does with the recording in progress
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelBehavior {
+    /// Throw the audio away
+    #[default]
+    Discard,
+    /// Keep the audio as a draft history entry, transcribed only on request
+    SaveDraft,
+    /// Transcribe into history without pasting
+    TranscribeSilently,
+}
+
 impl Default for ModelUnloadTimeout {
     fn default() -> Self {
         ModelUnloadTimeout::Never
@@ -171,6 +622,55 @@ impl Default for ClipboardHandling {
     }
 }
 
+impl Default for OutputTransform {
+    fn default() -> Self {
+        OutputTransform::AsIs
+    }
+}
+
+impl Default for TranslationGuard {
+    fn default() -> Self {
+        TranslationGuard::Off
+    }
+}
+
+impl Default for TruncationStrategy {
+    fn default() -> Self {
+        TruncationStrategy::None
+    }
+}
+
+impl TruncationStrategy {
+    /// Shortens text to at most `max_chars` characters.
+    /// Returns None when the text already fits or the strategy leaves it untouched.
+    pub fn apply(self, text: &str, max_chars: usize) -> Option<String> {
+        if text.chars().count() <= max_chars {
+            return None;
+        }
+
+        match self {
+            TruncationStrategy::None => None,
+            TruncationStrategy::Truncate => Some(take_graphemes(text, max_chars)),
+            TruncationStrategy::Ellipsis => Some(format!(
+                "{}…",
+                take_graphemes(text, max_chars.saturating_sub(1))
+            )),
+        }
+    }
+}
+
+/// The longest start of `text` with at most `max_chars` characters that doesn't
+/// split a grapheme, so accents and joined emoji are kept or dropped whole
+fn take_graphemes(text: &str, max_chars: usize) -> String {
+    let mut chars = 0;
+    text.graphemes(true)
+        .take_while(|grapheme| {
+            chars += grapheme.chars().count();
+            chars <= max_chars
+        })
+        .collect()
+}
+
 impl ModelUnloadTimeout {
     pub fn to_minutes(self) -> Option<u64> {
         match self {
@@ -221,14 +721,112 @@ impl SoundTheme {
     }
 }
 
+/// Whisper decoding parameters, see whisper.cpp's `whisper_full_params`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Type)]
+#[serde(default)]
+pub struct DecodingParams {
+    /// Sampling temperature of the first decode, 0 decodes deterministically
+    pub temperature: f32,
+    /// Added to the temperature on each fallback retry, 0 disables fallback
+    pub temperature_increment_on_fallback: f32,
+    /// Beam size, 1 decodes greedily. Larger beams are slower but recover from
+    /// more early mistakes; gains flatten out past about 5.
+    pub beam_size: u32,
+    /// Candidates sampled per fallback retry
+    pub best_of: u32,
+    /// Probability of the no-speech token above which a segment counts as silence
+    pub no_speech_threshold: f32,
+    /// Feed the previous segment's text as context for the next one
+    pub condition_on_previous_text: bool,
+}
+
+/// The decoding transcribe-rs' `WhisperEngine` used, so output only changes for
+/// users who change the parameters
+impl Default for DecodingParams {
+    fn default() -> Self {
+        Self {
+            temperature: 0.0,
+            temperature_increment_on_fallback: 0.2,
+            beam_size: 3,
+            best_of: 5,
+            no_speech_threshold: 0.2,
+            condition_on_previous_text: false,
+        }
+    }
+}
+
+impl DecodingParams {
+    /// Clamps every parameter into its supported range, warning about the ones that
+    /// were out of range, so bad values never reach the decoder
+    pub fn clamped(self) -> Self {
+        fn clamp_f32(name: &str, value: f32, min: f32, max: f32, default: f32) -> f32 {
+            let clamped = if value.is_nan() {
+                default
+            } else {
+                value.clamp(min, max)
+            };
+            if clamped != value {
+                warn!("{} {} is out of range, using {}", name, value, clamped);
+            }
+            clamped
+        }
+        fn clamp_u32(name: &str, value: u32, min: u32, max: u32) -> u32 {
+            let clamped = value.clamp(min, max);
+            if clamped != value {
+                warn!("{} {} is out of range, using {}", name, value, clamped);
+            }
+            clamped
+        }
+
+        let defaults = Self::default();
+        Self {
+            temperature: clamp_f32(
+                "Temperature",
+                self.temperature,
+                0.0,
+                1.0,
+                defaults.temperature,
+            ),
+            temperature_increment_on_fallback: clamp_f32(
+                "Temperature increment",
+                self.temperature_increment_on_fallback,
+                0.0,
+                1.0,
+                defaults.temperature_increment_on_fallback,
+            ),
+            beam_size: clamp_u32("Beam size", self.beam_size, 1, MAX_BEAM_SIZE),
+            best_of: clamp_u32("Best-of", self.best_of, 1, MAX_BEST_OF),
+            no_speech_threshold: clamp_f32(
+                "No-speech threshold",
+                self.no_speech_threshold,
+                0.0,
+                1.0,
+                defaults.no_speech_threshold,
+            ),
+            condition_on_previous_text: self.condition_on_previous_text,
+        }
+    }
+}
+
 /* still handy for composing the initial JSON in the store ------------- */
 #[derive(Serialize, Deserialize, Debug, Clone, Type)]
 pub struct AppSettings {
     pub bindings: HashMap<String, ShortcutBinding>,
     pub push_to_talk: bool,
+    /// Drop other key presses while a push-to-talk binding is held so they don't
+    /// reach the focused app as shortcuts (macOS and Windows only)
+    #[serde(default)]
+    pub suppress_keys_during_ptt: bool,
     pub audio_feedback: bool,
     #[serde(default = "default_audio_feedback_volume")]
     pub audio_feedback_volume: f32,
+    #[serde(default)]
+    pub feedback_mode: FeedbackMode,
+    #[serde(default)]
+    pub feedback_timing: FeedbackTiming,
+    /// Trackpad haptic feedback when recording starts and stops (macOS only)
+    #[serde(default)]
+    pub haptic_feedback: bool,
     #[serde(default = "default_sound_theme")]
     pub sound_theme: SoundTheme,
     #[serde(default = "default_start_hidden")]
@@ -237,40 +835,184 @@ pub struct AppSettings {
     pub autostart_enabled: bool,
     #[serde(default = "default_update_checks_enabled")]
     pub update_checks_enabled: bool,
+    /// Reload the settings when `settings_store.json` is edited outside the app
+    #[serde(default = "default_watch_settings_file")]
+    pub watch_settings_file: bool,
     #[serde(default = "default_model")]
     pub selected_model: String,
     #[serde(default = "default_always_on_microphone")]
     pub always_on_microphone: bool,
+    /// Audio kept from before the shortcut press and prepended to the recording.
+    /// Only works with the always-on microphone, capped at `MAX_PRE_ROLL_MS`.
+    #[serde(default)]
+    pub pre_roll_ms: u32,
+    /// Level the microphone signal with automatic gain control before the VAD
+    #[serde(default)]
+    pub agc_enabled: bool,
+    /// Speech level the AGC aims for, as an RMS amplitude between 0 and 1
+    #[serde(default = "default_agc_target_rms")]
+    pub agc_target_rms: f32,
+    /// Start recording on a spoken wake phrase, stopping once speech goes quiet
+    #[serde(default)]
+    pub wake_word_enabled: bool,
+    /// Wake phrase model, one of `wake_word::WAKE_WORD_MODELS`
+    #[serde(default = "default_wake_word_model")]
+    pub wake_word_model: String,
+    /// Between 0 and 1, higher detects the phrase more readily but misfires more
+    #[serde(default = "default_wake_word_sensitivity")]
+    pub wake_word_sensitivity: f32,
+    /// Time after a detection during which the phrase is ignored
+    #[serde(default = "default_wake_word_cooldown_ms")]
+    pub wake_word_cooldown_ms: u32,
+    /// Silence that ends a segment of a dictation session, see `dictation_session`
+    #[serde(default = "default_dictation_session_pause_ms")]
+    pub dictation_session_pause_ms: u32,
+    /// Transcribe a dictation while earlier ones are still transcribed, instead of
+    /// after them. Off by default, the transcriptions compete for CPU and memory.
+    /// Recording never waits either way.
+    #[serde(default)]
+    pub overlap_transcription: bool,
+    /// End recordings by saying `voice_stop_phrase`, see `voice_stop`
+    #[serde(default)]
+    pub voice_stop_enabled: bool,
+    #[serde(default = "default_voice_stop_phrase")]
+    pub voice_stop_phrase: String,
     #[serde(default)]
     pub selected_microphone: Option<String>,
+    /// Default input used while `selected_microphone` is unset, Windows only
+    #[serde(default)]
+    pub windows_default_device_role: WindowsDeviceRole,
+    /// Microphone used while a laptop's lid is closed, on macOS, Windows and Linux.
+    /// Ignored elsewhere, see `helpers::clamshell`.
     #[serde(default)]
     pub clamshell_microphone: Option<String>,
     #[serde(default)]
+    pub remember_mic_per_environment: bool,
+    /// Preferred microphone keyed by device environment fingerprint
+    #[serde(default)]
+    pub environment_microphones: HashMap<String, String>,
+    /// Input channels to capture, keyed by device name. Devices without an entry are
+    /// captured as the average of all their channels.
+    #[serde(default)]
+    pub mic_channel_map: HashMap<String, MicChannelConfig>,
+    #[serde(default)]
     pub selected_output_device: Option<String>,
     #[serde(default = "default_translate_to_english")]
     pub translate_to_english: bool,
+    #[serde(default)]
+    pub translation_guard: TranslationGuard,
     #[serde(default = "default_selected_language")]
     pub selected_language: String,
+    /// Rewrite punctuation following the conventions of the transcript's language
+    #[serde(default)]
+    pub localize_punctuation: bool,
     #[serde(default = "default_overlay_position")]
     pub overlay_position: OverlayPosition,
+    /// Let the overlay take clicks (stop and cancel) instead of passing them through
+    #[serde(default)]
+    pub overlay_interactive: bool,
     #[serde(default = "default_debug_mode")]
     pub debug_mode: bool,
     #[serde(default = "default_log_level")]
     pub log_level: LogLevel,
+    /// Size in kilobytes at which the log file is rotated
+    #[serde(default = "default_log_max_file_size_kb")]
+    pub log_max_file_size_kb: u32,
+    /// Log files kept, including the current one. 0 keeps every rotated file.
+    #[serde(default = "default_log_max_files")]
+    pub log_max_files: u32,
     #[serde(default)]
     pub custom_words: Vec<String>,
+    /// Custom words for transcripts in one language, by code as in
+    /// `selected_language`, see `custom_words`
+    #[serde(default)]
+    pub custom_words_by_language: HashMap<String, Vec<String>>,
     #[serde(default)]
     pub model_unload_timeout: ModelUnloadTimeout,
+    /// Model overrides on battery and plugged in, see `power_profile`
+    #[serde(default)]
+    pub power_profiles: PowerProfiles,
+    #[serde(default)]
+    pub vram_guard_behavior: VramGuardBehavior,
+    /// Quantization to download and load by model id, models without an entry
+    /// use their default
+    #[serde(default)]
+    pub model_quantization: HashMap<String, String>,
+    /// Where to fetch newer versions of the model catalog, see `model_manifest`
+    #[serde(default)]
+    pub model_manifest_url: Option<String>,
     #[serde(default = "default_word_correction_threshold")]
     pub word_correction_threshold: f64,
+    /// How transcribed words are compared against the custom words
+    #[serde(default)]
+    pub correction_algorithm: CorrectionAlgorithm,
+    /// Advanced Whisper decoding parameters
+    #[serde(default)]
+    pub advanced_decoding: DecodingParams,
+    /// Collapse phrases repeated back to back, a Whisper artifact on silence or music
+    #[serde(default)]
+    pub anti_hallucination: bool,
+    /// Back-to-back copies of a phrase kept before it counts as a hallucinated loop
+    #[serde(default = "default_hallucination_max_repeats")]
+    pub hallucination_max_repeats: u32,
+    /// Recordings longer than this are transcribed in chunks, 0 transcribes them whole
+    #[serde(default = "default_chunk_seconds")]
+    pub chunk_seconds: u32,
+    /// Times a failed inference is retried before the error is reported
+    #[serde(default = "default_transcription_retry_count")]
+    pub transcription_retry_count: u32,
+    /// Transcribe each guessed speaker turn separately and put a marker between them
+    #[serde(default)]
+    pub speaker_change_hints: bool,
+    /// Inserted between speaker turns
+    #[serde(default = "default_speaker_change_marker")]
+    pub speaker_change_marker: String,
+    /// Drop blank transcripts and ones matching `discarded_phrases` instead of pasting
+    #[serde(default)]
+    pub discard_blank_transcriptions: bool,
+    /// Known hallucinations of accidental activations, matched against the whole transcript
+    #[serde(default = "default_discarded_phrases")]
+    pub discarded_phrases: Vec<String>,
+    /// Save discarded recordings to history anyway, for debugging
+    #[serde(default)]
+    pub keep_discarded_in_history: bool,
+    #[serde(default)]
+    pub cancel_behavior: CancelBehavior,
     #[serde(default = "default_history_limit")]
     pub history_limit: usize,
     #[serde(default = "default_recording_retention_period")]
     pub recording_retention_period: RecordingRetentionPeriod,
     #[serde(default)]
+    pub recording_format: RecordingFormat,
+    #[serde(default)]
     pub paste_method: PasteMethod,
     #[serde(default)]
     pub clipboard_handling: ClipboardHandling,
+    /// Recent transcripts kept for the copy shortcuts, 0 disables the ring
+    #[serde(default = "default_transcript_ring_size")]
+    pub transcript_ring_size: u32,
+    /// Keep the transcript ring across restarts instead of only in memory
+    #[serde(default)]
+    pub persist_transcript_ring: bool,
+    /// Keep the text of dictations in the session log, not only their counts
+    #[serde(default)]
+    pub session_log_store_text: bool,
+    /// Keep today's session log across restarts instead of only in memory
+    #[serde(default)]
+    pub persist_session_log: bool,
+    #[serde(default)]
+    pub max_transcript_chars: Option<usize>,
+    #[serde(default)]
+    pub truncation_strategy: TruncationStrategy,
+    /// Output transforms keyed by application identifier or name
+    #[serde(default)]
+    pub app_output_transforms: BTreeMap<String, OutputTransform>,
+    /// Prompt overrides by focused application, the first matching rule wins
+    #[serde(default)]
+    pub app_prompt_rules: Vec<AppPromptRule>,
+    /// Folders watched for new recordings to transcribe, see `watch_folders`
+    #[serde(default)]
+    pub watch_folders: Vec<WatchFolderConfig>,
     #[serde(default = "default_post_process_enabled")]
     pub post_process_enabled: bool,
     #[serde(default = "default_post_process_provider_id")]
@@ -285,10 +1027,54 @@ pub struct AppSettings {
     pub post_process_prompts: Vec<LLMPrompt>,
     #[serde(default)]
     pub post_process_selected_prompt_id: Option<String>,
+    /// Only allow post-processing providers running on this machine. Requests to any
+    /// other host are refused, whatever provider is selected.
+    #[serde(default)]
+    pub offline_mode: bool,
+    #[serde(default)]
+    pub llm_user_agent: LlmUserAgent,
+    /// Prompt used while none is selected, e.g. right after post-processing is enabled
+    #[serde(default = "default_post_process_default_prompt_id")]
+    pub post_process_default_prompt_id: String,
+    /// Send the transcript as delimited, untrusted user content instead of inlining it into the prompt
+    #[serde(default = "default_prompt_hardening")]
+    pub prompt_hardening: bool,
+    #[serde(default)]
+    pub prompt_limits: PromptLimits,
     #[serde(default)]
     pub mute_while_recording: bool,
+    /// Lower other audio while recording instead of muting it
+    #[serde(default)]
+    pub duck_audio_while_recording: bool,
+    /// Output volume while ducked, as a percentage of the volume before recording
+    #[serde(default = "default_duck_level")]
+    pub duck_level: u32,
     #[serde(default)]
     pub append_trailing_space: bool,
+    /// Join the transcript's lines with spaces before pasting, history keeps them
+    #[serde(default)]
+    pub collapse_to_single_line: bool,
+    /// Wrap English words, code and the like inside right-to-left transcripts in
+    /// directional isolates, so target apps keep them in order
+    #[serde(default)]
+    pub bidi_isolation: bool,
+    #[serde(default)]
+    pub notify_on_complete: bool,
+    /// Show the transcript in a review window before pasting it
+    #[serde(default)]
+    pub review_before_paste: bool,
+    /// Seconds a pending review waits before resolving on its own, 0 waits indefinitely
+    #[serde(default = "default_review_timeout_secs")]
+    pub review_timeout_secs: u32,
+    #[serde(default)]
+    pub review_timeout_action: ReviewTimeoutAction,
+    /// Park the transcript instead of pasting it when focus moved to another
+    /// application while transcribing
+    #[serde(default)]
+    pub defer_paste_on_focus_change: bool,
+    /// Written between entries in file dictation mode, `${timestamp}` expands to the local time
+    #[serde(default = "default_file_dictation_separator")]
+    pub file_dictation_separator: String,
     #[serde(default = "default_app_language")]
     pub app_language: String,
     #[serde(default)]
@@ -321,6 +1107,10 @@ fn default_update_checks_enabled() -> bool {
     true
 }
 
+fn default_watch_settings_file() -> bool {
+    true
+}
+
 fn default_selected_language() -> String {
     "auto".to_string()
 }
@@ -344,6 +1134,72 @@ fn default_word_correction_threshold() -> f64 {
     0.18
 }
 
+fn default_log_max_file_size_kb() -> u32 {
+    500
+}
+
+fn default_log_max_files() -> u32 {
+    1
+}
+
+fn default_hallucination_max_repeats() -> u32 {
+    2
+}
+
+fn default_chunk_seconds() -> u32 {
+    120
+}
+
+fn default_transcription_retry_count() -> u32 {
+    2
+}
+
+fn default_speaker_change_marker() -> String {
+    "\n\n—\n\n".to_string()
+}
+
+fn default_agc_target_rms() -> f32 {
+    0.1
+}
+
+fn default_wake_word_model() -> String {
+    "hey_jarvis".to_string()
+}
+
+fn default_wake_word_sensitivity() -> f32 {
+    0.5
+}
+
+fn default_wake_word_cooldown_ms() -> u32 {
+    3_000
+}
+
+fn default_dictation_session_pause_ms() -> u32 {
+    1_200
+}
+
+fn default_voice_stop_phrase() -> String {
+    "stop recording".to_string()
+}
+
+fn default_duck_level() -> u32 {
+    20
+}
+
+fn default_discarded_phrases() -> Vec<String> {
+    [
+        "you",
+        "thank you",
+        "thanks",
+        "thanks for watching",
+        "thank you for watching",
+        "bye",
+    ]
+    .iter()
+    .map(|phrase| phrase.to_string())
+    .collect()
+}
+
 fn default_history_limit() -> usize {
     5
 }
@@ -364,12 +1220,41 @@ fn default_post_process_enabled() -> bool {
     false
 }
 
+fn default_transcript_ring_size() -> u32 {
+    10
+}
+
+fn default_review_timeout_secs() -> u32 {
+    60
+}
+
+fn default_file_dictation_separator() -> String {
+    "\n\n".to_string()
+}
+
+fn default_prompt_hardening() -> bool {
+    true
+}
+
 fn default_app_language() -> String {
     tauri_plugin_os::locale()
         .and_then(|l| l.split(['-', '_']).next().map(String::from))
         .unwrap_or_else(|| "en".to_string())
 }
 
+fn default_chat_completions_path() -> String {
+    "/chat/completions".to_string()
+}
+
+fn default_llm_max_concurrency() -> u32 {
+    2
+}
+
+/// Applies to hosted providers, local servers default to unlimited
+fn default_llm_requests_per_minute() -> u32 {
+    30
+}
+
 fn default_post_process_provider_id() -> String {
     "openai".to_string()
 }
@@ -377,11 +1262,20 @@ fn default_post_process_provider_id() -> String {
 fn default_post_process_providers() -> Vec<PostProcessProvider> {
     let mut providers = vec![
         PostProcessProvider {
-            id: "gemini".to_string(),
+            id: GEMINI_PROVIDER_ID.to_string(),
             label: "Gemini".to_string(),
             base_url: "https://generativelanguage.googleapis.com/v1beta/openai/".to_string(),
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
+            chat_completions_path: default_chat_completions_path(),
+            api_version: None,
+            max_concurrent_requests: default_llm_max_concurrency(),
+            requests_per_minute: default_llm_requests_per_minute(),
+            auth_style: AuthStyle::ApiKeyHeader,
+            extra_headers: HashMap::new(),
+            extra_query_params: HashMap::new(),
+            organization_id: None,
+            project_id: None,
         },
         PostProcessProvider {
             id: "openai".to_string(),
@@ -389,6 +1283,15 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             base_url: "https://api.openai.com/v1".to_string(),
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
+            chat_completions_path: default_chat_completions_path(),
+            api_version: None,
+            max_concurrent_requests: default_llm_max_concurrency(),
+            requests_per_minute: default_llm_requests_per_minute(),
+            auth_style: AuthStyle::default(),
+            extra_headers: HashMap::new(),
+            extra_query_params: HashMap::new(),
+            organization_id: None,
+            project_id: None,
         },
         PostProcessProvider {
             id: "openrouter".to_string(),
@@ -396,6 +1299,15 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             base_url: "https://openrouter.ai/api/v1".to_string(),
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
+            chat_completions_path: default_chat_completions_path(),
+            api_version: None,
+            max_concurrent_requests: default_llm_max_concurrency(),
+            requests_per_minute: default_llm_requests_per_minute(),
+            auth_style: AuthStyle::default(),
+            extra_headers: HashMap::new(),
+            extra_query_params: HashMap::new(),
+            organization_id: None,
+            project_id: None,
         },
         PostProcessProvider {
             id: "anthropic".to_string(),
@@ -403,6 +1315,15 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             base_url: "https://api.anthropic.com/v1".to_string(),
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
+            chat_completions_path: default_chat_completions_path(),
+            api_version: None,
+            max_concurrent_requests: default_llm_max_concurrency(),
+            requests_per_minute: default_llm_requests_per_minute(),
+            auth_style: AuthStyle::default(),
+            extra_headers: HashMap::new(),
+            extra_query_params: HashMap::new(),
+            organization_id: None,
+            project_id: None,
         },
         PostProcessProvider {
             id: "groq".to_string(),
@@ -410,6 +1331,15 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             base_url: "https://api.groq.com/openai/v1".to_string(),
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
+            chat_completions_path: default_chat_completions_path(),
+            api_version: None,
+            max_concurrent_requests: default_llm_max_concurrency(),
+            requests_per_minute: default_llm_requests_per_minute(),
+            auth_style: AuthStyle::default(),
+            extra_headers: HashMap::new(),
+            extra_query_params: HashMap::new(),
+            organization_id: None,
+            project_id: None,
         },
         PostProcessProvider {
             id: "cerebras".to_string(),
@@ -417,6 +1347,15 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             base_url: "https://api.cerebras.ai/v1".to_string(),
             allow_base_url_edit: false,
             models_endpoint: Some("/models".to_string()),
+            chat_completions_path: default_chat_completions_path(),
+            api_version: None,
+            max_concurrent_requests: default_llm_max_concurrency(),
+            requests_per_minute: default_llm_requests_per_minute(),
+            auth_style: AuthStyle::default(),
+            extra_headers: HashMap::new(),
+            extra_query_params: HashMap::new(),
+            organization_id: None,
+            project_id: None,
         },
     ];
 
@@ -432,6 +1371,15 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
             base_url: "apple-intelligence://local".to_string(),
             allow_base_url_edit: false,
             models_endpoint: None,
+            chat_completions_path: default_chat_completions_path(),
+            api_version: None,
+            max_concurrent_requests: default_llm_max_concurrency(),
+            requests_per_minute: 0,
+            auth_style: AuthStyle::default(),
+            extra_headers: HashMap::new(),
+            extra_query_params: HashMap::new(),
+            organization_id: None,
+            project_id: None,
         });
     }
 
@@ -442,6 +1390,15 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
         base_url: "http://localhost:1234/v1".to_string(),
         allow_base_url_edit: true,
         models_endpoint: Some("/models".to_string()),
+        chat_completions_path: default_chat_completions_path(),
+        api_version: None,
+        max_concurrent_requests: default_llm_max_concurrency(),
+        requests_per_minute: 0,
+        auth_style: AuthStyle::default(),
+        extra_headers: HashMap::new(),
+        extra_query_params: HashMap::new(),
+        organization_id: None,
+        project_id: None,
     });
 
     // Custom provider always comes last
@@ -451,6 +1408,15 @@ fn default_post_process_providers() -> Vec<PostProcessProvider> {
         base_url: "http://localhost:11434/v1".to_string(),
         allow_base_url_edit: true,
         models_endpoint: Some("/models".to_string()),
+        chat_completions_path: default_chat_completions_path(),
+        api_version: None,
+        max_concurrent_requests: default_llm_max_concurrency(),
+        requests_per_minute: 0,
+        auth_style: AuthStyle::default(),
+        extra_headers: HashMap::new(),
+        extra_query_params: HashMap::new(),
+        organization_id: None,
+        project_id: None,
     });
 
     providers
@@ -482,6 +1448,10 @@ fn default_post_process_models() -> HashMap<String, String> {
     map
 }
 
+fn default_post_process_default_prompt_id() -> String {
+    "beautiful_prompts".to_string()
+}
+
 fn default_post_process_prompts() -> Vec<LLMPrompt> {
     vec![
         LLMPrompt {
@@ -490,6 +1460,10 @@ fn default_post_process_prompts() -> Vec<LLMPrompt> {
             prompt: "Refine this transcribed text into a clear, professional, and well-structured prompt for an LLM. Remove filler words and stutters while preserving the core functional intent. Only send back the refined text, no extra content.
 
 ${output}".to_string(),
+            response_format: None,
+            json_output: JsonOutput::default(),
+            short_label: None,
+            seed: None,
         },
         LLMPrompt {
             id: "everyday_messaging".to_string(),
@@ -497,6 +1471,10 @@ ${output}".to_string(),
             prompt: "Rewrite this transcribed text into a clean, casual message. Fix grammar, punctuation, and capitalization. Remove stutters and filler words while keeping the tone natural and conversational. Only send back the refined text, no extra content.
 
 ${output}".to_string(),
+            response_format: None,
+            json_output: JsonOutput::default(),
+            short_label: None,
+            seed: None,
         },
         LLMPrompt {
             id: "professional_email".to_string(),
@@ -504,10 +1482,66 @@ ${output}".to_string(),
             prompt: "Rewrite this transcribed text into a polished, professional email. Use a polite and respectful tone with clear, concise language. Only send back the email body, no extra content.
 
 ${output}".to_string(),
+            response_format: None,
+            json_output: JsonOutput::default(),
+            short_label: None,
+            seed: None,
         },
     ]
 }
 
+/// Rewrites stored settings whose meaning changed since they were saved, before
+/// they are parsed
+fn migrate_stored_settings(settings: &mut serde_json::Value) {
+    // The beam size was a top-level setting before the other decoding parameters
+    if let Some(settings) = settings.as_object_mut() {
+        if let Some(beam_size) = settings.remove("beam_size") {
+            let decoding = settings
+                .entry("advanced_decoding")
+                .or_insert_with(|| serde_json::json!({}));
+            if let Some(decoding) = decoding.as_object_mut() {
+                decoding.entry("beam_size").or_insert(beam_size);
+            }
+        }
+    }
+
+    if let Some(providers) = settings
+        .get_mut("post_process_providers")
+        .and_then(|providers| providers.as_array_mut())
+    {
+        for provider in providers.iter_mut().filter_map(|p| p.as_object_mut()) {
+            // Gemini was saved with bearer auth before providers had an auth style
+            let is_gemini =
+                provider.get("id").and_then(|id| id.as_str()) == Some(GEMINI_PROVIDER_ID);
+            if is_gemini && !provider.contains_key("auth_style") {
+                provider.insert("auth_style".to_string(), "api_key_header".into());
+            }
+
+            // Providers saved before they had a rate limit get the default for
+            // their kind, local servers aren't limited
+            let is_local = provider
+                .get("base_url")
+                .and_then(|url| url.as_str())
+                .is_some_and(is_local_base_url);
+            if is_local && !provider.contains_key("requests_per_minute") {
+                provider.insert("requests_per_minute".to_string(), 0.into());
+            }
+        }
+    }
+}
+
+/// Adds bindings introduced after the settings were first saved
+fn ensure_default_bindings(settings: &mut AppSettings) -> bool {
+    let mut changed = false;
+    for (id, binding) in get_default_settings().bindings {
+        if !settings.bindings.contains_key(&id) {
+            settings.bindings.insert(id, binding);
+            changed = true;
+        }
+    }
+    changed
+}
+
 fn ensure_post_process_defaults(settings: &mut AppSettings) -> bool {
     let mut changed = false;
     for provider in default_post_process_providers() {
@@ -578,6 +1612,19 @@ fn ensure_post_process_defaults(settings: &mut AppSettings) -> bool {
 
 pub const SETTINGS_STORE_PATH: &str = "settings_store.json";
 
+/// Largest Whisper beam size accepted; wider beams cost latency without helping accuracy
+pub const MAX_BEAM_SIZE: u32 = 8;
+/// Largest number of candidates sampled per temperature fallback
+pub const MAX_BEST_OF: u32 = 10;
+
+pub const COPY_PREVIOUS_TRANSCRIPT_BINDING: &str = "copy_previous_transcript";
+pub const COPY_TRANSCRIPT_TWO_BACK_BINDING: &str = "copy_transcript_two_back";
+pub const FLUSH_DEFERRED_PASTE_BINDING: &str = "flush_deferred_paste";
+pub const UNDO_LAST_PASTE_BINDING: &str = "undo_last_paste";
+pub const COPY_ONLY_SESSION_BINDING: &str = "toggle_copy_only_session";
+/// Starts or ends a dictation session; its segments are also recorded under this id
+pub const DICTATION_SESSION_BINDING: &str = "toggle_dictation_session";
+
 pub fn get_default_settings() -> AppSettings {
     #[cfg(target_os = "windows")]
     let default_shortcut = "ctrl+space";
@@ -597,6 +1644,11 @@ pub fn get_default_settings() -> AppSettings {
             description: "Converts your speech into text.".to_string(),
             default_binding: default_shortcut.to_string(),
             current_binding: default_shortcut.to_string(),
+            review_before_paste: None,
+            feedback_mode: None,
+            collapse_to_single_line: None,
+            selected_model: None,
+            match_mode: BindingMatchMode::default(),
         },
     );
     bindings.insert(
@@ -607,35 +1659,141 @@ pub fn get_default_settings() -> AppSettings {
             description: "Cancels the current recording.".to_string(),
             default_binding: "escape".to_string(),
             current_binding: "escape".to_string(),
+            review_before_paste: None,
+            feedback_mode: None,
+            collapse_to_single_line: None,
+            selected_model: None,
+            match_mode: BindingMatchMode::default(),
         },
     );
+    // Unbound until the user assigns a shortcut
+    for (id, name, description) in [
+        (
+            COPY_PREVIOUS_TRANSCRIPT_BINDING,
+            "Copy Previous Transcript",
+            "Copies the most recent transcript to the clipboard.",
+        ),
+        (
+            COPY_TRANSCRIPT_TWO_BACK_BINDING,
+            "Copy Transcript Two Back",
+            "Copies the transcript before the most recent one to the clipboard.",
+        ),
+        (
+            FLUSH_DEFERRED_PASTE_BINDING,
+            "Paste Deferred Transcript",
+            "Pastes the deferred transcript into the focused field.",
+        ),
+        (
+            UNDO_LAST_PASTE_BINDING,
+            "Undo Last Paste",
+            "Deletes the transcript that was just pasted.",
+        ),
+        (
+            COPY_ONLY_SESSION_BINDING,
+            "Toggle Copy-Only Session",
+            "Copies transcripts to the clipboard instead of pasting them until toggled off.",
+        ),
+        (
+            DICTATION_SESSION_BINDING,
+            "Toggle Dictation Session",
+            "Pastes what you said after every pause until toggled off.",
+        ),
+    ] {
+        bindings.insert(
+            id.to_string(),
+            ShortcutBinding {
+                id: id.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                default_binding: String::new(),
+                current_binding: String::new(),
+                review_before_paste: None,
+                feedback_mode: None,
+                collapse_to_single_line: None,
+                selected_model: None,
+                match_mode: BindingMatchMode::default(),
+            },
+        );
+    }
 
     AppSettings {
         bindings,
         push_to_talk: true,
+        suppress_keys_during_ptt: false,
         audio_feedback: false,
+        feedback_mode: FeedbackMode::default(),
+        feedback_timing: FeedbackTiming::default(),
+        haptic_feedback: false,
         audio_feedback_volume: default_audio_feedback_volume(),
         sound_theme: default_sound_theme(),
         start_hidden: default_start_hidden(),
         autostart_enabled: default_autostart_enabled(),
         update_checks_enabled: default_update_checks_enabled(),
+        watch_settings_file: default_watch_settings_file(),
         selected_model: "".to_string(),
         always_on_microphone: false,
+        pre_roll_ms: 0,
+        agc_enabled: false,
+        agc_target_rms: default_agc_target_rms(),
+        wake_word_enabled: false,
+        wake_word_model: default_wake_word_model(),
+        wake_word_sensitivity: default_wake_word_sensitivity(),
+        wake_word_cooldown_ms: default_wake_word_cooldown_ms(),
+        dictation_session_pause_ms: default_dictation_session_pause_ms(),
+        overlap_transcription: false,
+        voice_stop_enabled: false,
+        voice_stop_phrase: default_voice_stop_phrase(),
         selected_microphone: None,
+        windows_default_device_role: WindowsDeviceRole::default(),
         clamshell_microphone: None,
+        remember_mic_per_environment: false,
+        environment_microphones: HashMap::new(),
+        mic_channel_map: HashMap::new(),
         selected_output_device: None,
         translate_to_english: false,
+        translation_guard: TranslationGuard::default(),
         selected_language: "auto".to_string(),
+        localize_punctuation: false,
         overlay_position: default_overlay_position(),
+        overlay_interactive: false,
         debug_mode: false,
         log_level: default_log_level(),
+        log_max_file_size_kb: default_log_max_file_size_kb(),
+        log_max_files: default_log_max_files(),
         custom_words: Vec::new(),
+        custom_words_by_language: HashMap::new(),
         model_unload_timeout: ModelUnloadTimeout::Never,
+        power_profiles: PowerProfiles::default(),
+        vram_guard_behavior: VramGuardBehavior::default(),
+        model_quantization: HashMap::new(),
+        model_manifest_url: None,
         word_correction_threshold: default_word_correction_threshold(),
+        correction_algorithm: CorrectionAlgorithm::default(),
+        advanced_decoding: DecodingParams::default(),
+        anti_hallucination: false,
+        hallucination_max_repeats: default_hallucination_max_repeats(),
+        chunk_seconds: default_chunk_seconds(),
+        transcription_retry_count: default_transcription_retry_count(),
+        speaker_change_hints: false,
+        speaker_change_marker: default_speaker_change_marker(),
+        discard_blank_transcriptions: false,
+        discarded_phrases: default_discarded_phrases(),
+        keep_discarded_in_history: false,
+        cancel_behavior: CancelBehavior::default(),
         history_limit: default_history_limit(),
         recording_retention_period: default_recording_retention_period(),
+        recording_format: RecordingFormat::default(),
         paste_method: PasteMethod::default(),
         clipboard_handling: ClipboardHandling::default(),
+        transcript_ring_size: default_transcript_ring_size(),
+        persist_transcript_ring: false,
+        session_log_store_text: false,
+        persist_session_log: false,
+        max_transcript_chars: None,
+        truncation_strategy: TruncationStrategy::default(),
+        app_output_transforms: BTreeMap::new(),
+        app_prompt_rules: Vec::new(),
+        watch_folders: Vec::new(),
         post_process_enabled: default_post_process_enabled(),
         post_process_provider_id: default_post_process_provider_id(),
         post_process_providers: default_post_process_providers(),
@@ -643,8 +1801,23 @@ pub fn get_default_settings() -> AppSettings {
         post_process_models: default_post_process_models(),
         post_process_prompts: default_post_process_prompts(),
         post_process_selected_prompt_id: None,
+        offline_mode: false,
+        llm_user_agent: LlmUserAgent::default(),
+        post_process_default_prompt_id: default_post_process_default_prompt_id(),
+        prompt_hardening: default_prompt_hardening(),
+        prompt_limits: PromptLimits::default(),
         mute_while_recording: false,
+        duck_audio_while_recording: false,
+        duck_level: default_duck_level(),
         append_trailing_space: false,
+        collapse_to_single_line: false,
+        bidi_isolation: false,
+        notify_on_complete: false,
+        review_before_paste: false,
+        review_timeout_secs: default_review_timeout_secs(),
+        review_timeout_action: ReviewTimeoutAction::default(),
+        defer_paste_on_focus_change: false,
+        file_dictation_separator: default_file_dictation_separator(),
         app_language: default_app_language(),
         experimental_enabled: false,
         post_process_custom_models: HashMap::new(),
@@ -652,6 +1825,169 @@ pub fn get_default_settings() -> AppSettings {
 }
 
 impl AppSettings {
+    /// Returns what is missing when post-processing is enabled but can't run.
+    /// Returns None when post-processing is disabled or fully configured.
+    pub fn post_process_misconfiguration(&self) -> Option<PostProcessMisconfiguration> {
+        if !self.post_process_enabled {
+            return None;
+        }
+
+        if let Some(misconfiguration) = self.post_process_provider_misconfiguration() {
+            return Some(misconfiguration);
+        }
+
+        self.resolve_post_process_prompt().err()
+    }
+
+    /// The prompt post-processing runs with. Without a selection this is the default
+    /// prompt, or the first prompt if the default was deleted.
+    pub fn resolve_post_process_prompt(&self) -> Result<&LLMPrompt, PostProcessMisconfiguration> {
+        let find = |id: &str| self.post_process_prompts.iter().find(|p| p.id == id);
+        let prompt = match &self.post_process_selected_prompt_id {
+            Some(prompt_id) => {
+                find(prompt_id).ok_or_else(|| PostProcessMisconfiguration::PromptNotFound {
+                    prompt_id: prompt_id.clone(),
+                })?
+            }
+            None => find(&self.post_process_default_prompt_id)
+                .or_else(|| self.post_process_prompts.first())
+                .ok_or(PostProcessMisconfiguration::NoPromptSelected)?,
+        };
+
+        if prompt.prompt.trim().is_empty() {
+            return Err(PostProcessMisconfiguration::EmptyPrompt {
+                prompt_id: prompt.id.clone(),
+            });
+        }
+        Ok(prompt)
+    }
+
+    /// Checks that a provider is selected and has a model, regardless of whether
+    /// post-processing is enabled
+    pub fn post_process_provider_misconfiguration(&self) -> Option<PostProcessMisconfiguration> {
+        let Some(provider) = self.active_post_process_provider() else {
+            return Some(PostProcessMisconfiguration::NoProvider);
+        };
+        if self.offline_mode && !provider.is_local() {
+            return Some(PostProcessMisconfiguration::RemoteProviderOffline {
+                provider_id: provider.id.clone(),
+            });
+        }
+
+        let has_model = self
+            .post_process_models
+            .get(&provider.id)
+            .is_some_and(|model| !model.trim().is_empty());
+        if !has_model {
+            return Some(PostProcessMisconfiguration::MissingModel {
+                provider_id: provider.id.clone(),
+            });
+        }
+        None
+    }
+
+    /// Returns `base` if no prompt uses it yet, otherwise `base_2`, `base_3`, ...
+    fn unique_prompt_id(&self, base: &str) -> String {
+        let taken = |id: &str| self.post_process_prompts.iter().any(|p| p.id == id);
+        if !taken(base) {
+            return base.to_string();
+        }
+        (2..)
+            .map(|n| format!("{}_{}", base, n))
+            .find(|candidate| !taken(candidate))
+            .expect("unbounded range always yields a free id")
+    }
+
+    /// Points the prompt selection at the first prompt if the current one no longer exists
+    fn ensure_selected_prompt_valid(&mut self) {
+        let valid = self
+            .post_process_selected_prompt_id
+            .as_ref()
+            .is_some_and(|id| self.post_process_prompts.iter().any(|p| &p.id == id));
+        if !valid {
+            self.post_process_selected_prompt_id =
+                self.post_process_prompts.first().map(|p| p.id.clone());
+        }
+    }
+
+    /// Copies a prompt under a fresh id. The copy is named "<name> (copy)" unless a name is given.
+    pub fn duplicate_prompt(
+        &mut self,
+        id: &str,
+        new_name: Option<String>,
+    ) -> Result<LLMPrompt, HandyError> {
+        let original = self
+            .post_process_prompts
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| HandyError::not_found(format!("Prompt with id '{}' not found", id)))?;
+
+        let mut copy = original.clone();
+        copy.id = self.unique_prompt_id(&format!("{}_copy", original.id));
+        copy.name = new_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("{} (copy)", original.name));
+
+        self.post_process_prompts.push(copy.clone());
+        self.ensure_selected_prompt_valid();
+        Ok(copy)
+    }
+
+    /// Restores a built-in prompt's name and text, re-adding it if it was deleted
+    pub fn reset_prompt_to_default(&mut self, id: &str) -> Result<LLMPrompt, HandyError> {
+        let default = default_post_process_prompts()
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| {
+                HandyError::not_found(format!("Prompt '{}' is not a built-in prompt", id))
+            })?;
+
+        match self.post_process_prompts.iter_mut().find(|p| p.id == id) {
+            Some(existing) => *existing = default.clone(),
+            None => self.post_process_prompts.push(default.clone()),
+        }
+        self.ensure_selected_prompt_valid();
+        Ok(default)
+    }
+
+    /// Feedback mode for a binding. A binding's own mode decides on its own; the
+    /// global mode only plays sounds while `audio_feedback` is on.
+    pub fn feedback_mode_for(&self, binding_id: &str) -> FeedbackMode {
+        if let Some(mode) = self
+            .bindings
+            .get(binding_id)
+            .and_then(|binding| binding.feedback_mode)
+        {
+            return mode;
+        }
+        if self.audio_feedback {
+            self.feedback_mode
+        } else {
+            self.feedback_mode.without_sound()
+        }
+    }
+
+    /// Whether a binding's transcripts are pasted as a single line. An app prompt
+    /// rule's choice comes first, then the binding's, then the global setting.
+    pub fn collapse_to_single_line_for(&self, binding_id: &str, app_rule: Option<bool>) -> bool {
+        app_rule
+            .or_else(|| {
+                self.bindings
+                    .get(binding_id)
+                    .and_then(|binding| binding.collapse_to_single_line)
+            })
+            .unwrap_or(self.collapse_to_single_line)
+    }
+
+    /// Providers that can be used with the current offline mode
+    pub fn usable_post_process_providers(&self) -> impl Iterator<Item = &PostProcessProvider> {
+        self.post_process_providers
+            .iter()
+            .filter(|provider| !self.offline_mode || provider.is_local())
+    }
+
     pub fn active_post_process_provider(&self) -> Option<&PostProcessProvider> {
         self.post_process_providers
             .iter()
@@ -677,10 +2013,11 @@ impl AppSettings {
 pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
     // Initialize store
     let store = app
-        .store(SETTINGS_STORE_PATH)
+        .store(crate::paths::get(app).settings_store())
         .expect("Failed to initialize store");
 
-    let mut settings = if let Some(settings_value) = store.get("settings") {
+    let mut settings = if let Some(mut settings_value) = store.get("settings") {
+        migrate_stored_settings(&mut settings_value);
         // Parse the entire settings object
         match serde_json::from_value::<AppSettings>(settings_value) {
             Ok(mut settings) => {
@@ -718,9 +2055,11 @@ pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
         default_settings
     };
 
-    if ensure_post_process_defaults(&mut settings) {
+    let bindings_added = ensure_default_bindings(&mut settings);
+    if ensure_post_process_defaults(&mut settings) || bindings_added {
         store.set("settings", serde_json::to_value(&settings).unwrap());
         let _ = store.save();
+        crate::settings_watcher::record_own_write(app);
     }
 
     settings
@@ -728,10 +2067,11 @@ pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
 
 pub fn get_settings(app: &AppHandle) -> AppSettings {
     let store = app
-        .store(SETTINGS_STORE_PATH)
+        .store(crate::paths::get(app).settings_store())
         .expect("Failed to initialize store");
 
-    let mut settings = if let Some(settings_value) = store.get("settings") {
+    let mut settings = if let Some(mut settings_value) = store.get("settings") {
+        migrate_stored_settings(&mut settings_value);
         serde_json::from_value::<AppSettings>(settings_value).unwrap_or_else(|_| {
             let default_settings = get_default_settings();
             store.set("settings", serde_json::to_value(&default_settings).unwrap());
@@ -743,20 +2083,79 @@ pub fn get_settings(app: &AppHandle) -> AppSettings {
         default_settings
     };
 
-    if ensure_post_process_defaults(&mut settings) {
+    let bindings_added = ensure_default_bindings(&mut settings);
+    if ensure_post_process_defaults(&mut settings) || bindings_added {
         store.set("settings", serde_json::to_value(&settings).unwrap());
+        // Persist the merge so the file doesn't drift from what the app uses
+        let _ = store.save();
+        crate::settings_watcher::record_own_write(app);
     }
 
     settings
 }
 
+/// Settings as the app uses them, with every default merged in
+#[derive(Serialize, Debug, Clone, Type)]
+pub struct EffectiveSettings {
+    pub settings: AppSettings,
+    /// Whether the stored settings lacked defaults or held outdated values, in
+    /// which case they were rewritten to match
+    pub settings_were_migrated: bool,
+}
+
+/// Merged settings, rewriting the stored copy when it differs from them. Fields
+/// added since the settings were last saved only get their defaults through serde,
+/// which `get_settings` alone never writes back.
+pub fn get_effective_settings(app: &AppHandle) -> EffectiveSettings {
+    let store = app
+        .store(crate::paths::get(app).settings_store())
+        .expect("Failed to initialize store");
+    let stored = store.get("settings");
+
+    let settings = get_settings(app);
+    let merged = serde_json::to_value(&settings).unwrap();
+    let settings_were_migrated = stored.as_ref() != Some(&merged);
+    if settings_were_migrated {
+        debug!("Stored settings differ from the effective ones, rewriting them");
+        store.set("settings", merged);
+        let _ = store.save();
+        crate::settings_watcher::record_own_write(app);
+    }
+
+    EffectiveSettings {
+        settings,
+        settings_were_migrated,
+    }
+}
+
 pub fn write_settings(app: &AppHandle, settings: AppSettings) {
     let store = app
-        .store(SETTINGS_STORE_PATH)
+        .store(crate::paths::get(app).settings_store())
         .expect("Failed to initialize store");
 
+    // Only report when this write changes what is wrong, not on every save
+    let previous = store
+        .get("settings")
+        .and_then(|value| serde_json::from_value::<AppSettings>(value).ok())
+        .and_then(|previous| previous.post_process_misconfiguration());
+    let misconfiguration = settings.post_process_misconfiguration();
+
     store.set("settings", serde_json::to_value(&settings).unwrap());
     let _ = store.save();
+    crate::settings_watcher::record_own_write(app);
+
+    if let Some(misconfiguration) = misconfiguration.filter(|m| previous.as_ref() != Some(m)) {
+        report_post_process_misconfiguration(app, misconfiguration);
+    }
+}
+
+/// Warn the frontend that post-processing is enabled but will be skipped
+pub fn report_post_process_misconfiguration(
+    app: &AppHandle,
+    misconfiguration: PostProcessMisconfiguration,
+) {
+    warn!("Post-processing is misconfigured: {:?}", misconfiguration);
+    emit_event(app, AppEvent::PostProcessMisconfigured(misconfiguration));
 }
 
 pub fn get_bindings(app: &AppHandle) -> HashMap<String, ShortcutBinding> {
@@ -782,3 +2181,406 @@ pub fn get_recording_retention_period(app: &AppHandle) -> RecordingRetentionPeri
     let settings = get_settings(app);
     settings.recording_retention_period
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_providers_saved_without_a_rate_limit_are_unlimited() {
+        let mut stored = serde_json::json!({
+            "post_process_providers": [
+                { "id": "custom", "base_url": "http://localhost:11434/v1" },
+                { "id": "openai", "base_url": "https://api.openai.com/v1" },
+                {
+                    "id": "lm_studio",
+                    "base_url": "http://127.0.0.1:1234/v1",
+                    "requests_per_minute": 10
+                }
+            ]
+        });
+        migrate_stored_settings(&mut stored);
+        let providers = stored["post_process_providers"].as_array().unwrap();
+        assert_eq!(providers[0]["requests_per_minute"], 0);
+        assert!(providers[1].get("requests_per_minute").is_none());
+        assert_eq!(providers[2]["requests_per_minute"], 10);
+
+        for provider in default_post_process_providers() {
+            assert_eq!(provider.is_local(), provider.requests_per_minute == 0);
+        }
+    }
+
+    #[test]
+    fn gemini_is_listed_once_and_stored_copies_get_header_auth() {
+        let providers = default_post_process_providers();
+        let gemini: Vec<_> = providers
+            .iter()
+            .filter(|p| p.id == GEMINI_PROVIDER_ID)
+            .collect();
+        assert_eq!(gemini.len(), 1);
+        assert_eq!(gemini[0].auth_style, AuthStyle::ApiKeyHeader);
+
+        let mut stored = serde_json::json!({
+            "post_process_providers": [
+                { "id": "gemini", "base_url": "https://generativelanguage.googleapis.com/v1beta/openai/" },
+                { "id": "openai", "base_url": "https://api.openai.com/v1" }
+            ]
+        });
+        migrate_stored_settings(&mut stored);
+        let providers = stored["post_process_providers"].as_array().unwrap();
+        assert_eq!(providers[0]["auth_style"], "api_key_header");
+        assert!(providers[1].get("auth_style").is_none());
+    }
+
+    #[test]
+    fn top_level_beam_size_moves_into_advanced_decoding() {
+        let mut stored = serde_json::json!({ "beam_size": 3 });
+        migrate_stored_settings(&mut stored);
+        assert!(stored.get("beam_size").is_none());
+        assert_eq!(stored["advanced_decoding"]["beam_size"], 3);
+
+        // A beam size already set with the other parameters wins
+        let mut stored = serde_json::json!({
+            "beam_size": 3,
+            "advanced_decoding": { "beam_size": 8, "best_of": 2 }
+        });
+        migrate_stored_settings(&mut stored);
+        assert_eq!(stored["advanced_decoding"]["beam_size"], 8);
+        assert_eq!(stored["advanced_decoding"]["best_of"], 2);
+    }
+
+    #[test]
+    fn display_label_falls_back_to_start_of_name() {
+        let mut prompt = get_default_settings().post_process_prompts[0].clone();
+        prompt.name = "Improve Transcriptions".to_string();
+        assert_eq!(prompt.display_label(), "Improve Transcr…");
+
+        prompt.short_label = Some("  ".to_string());
+        assert_eq!(prompt.display_label(), "Improve Transcr…");
+
+        prompt.short_label = Some("✨ Fix".to_string());
+        assert_eq!(prompt.display_label(), "✨ Fix");
+    }
+
+    #[test]
+    fn default_decoding_matches_the_previous_engine() {
+        let params = DecodingParams::default();
+        assert_eq!(params.temperature, 0.0);
+        assert_eq!(params.temperature_increment_on_fallback, 0.2);
+        assert_eq!(params.beam_size, 3);
+        assert_eq!(params.no_speech_threshold, 0.2);
+        // whisper.cpp's default, which the engine kept
+        assert!(!params.condition_on_previous_text);
+    }
+
+    #[test]
+    fn decoding_params_are_clamped_into_range() {
+        let params = DecodingParams {
+            temperature: 3.0,
+            temperature_increment_on_fallback: f32::NAN,
+            beam_size: 0,
+            best_of: 50,
+            no_speech_threshold: -1.0,
+            condition_on_previous_text: false,
+        }
+        .clamped();
+        assert_eq!(params.temperature, 1.0);
+        assert_eq!(params.temperature_increment_on_fallback, 0.2);
+        assert_eq!(params.beam_size, 1);
+        assert_eq!(params.best_of, MAX_BEST_OF);
+        assert_eq!(params.no_speech_threshold, 0.0);
+        assert!(!params.condition_on_previous_text);
+        assert_eq!(
+            DecodingParams::default().clamped(),
+            DecodingParams::default()
+        );
+    }
+
+    #[test]
+    fn truncation_leaves_short_text_alone() {
+        assert_eq!(TruncationStrategy::Truncate.apply("hello", 5), None);
+        assert_eq!(TruncationStrategy::Ellipsis.apply("hello", 10), None);
+    }
+
+    #[test]
+    fn truncation_none_never_shortens() {
+        assert_eq!(TruncationStrategy::None.apply("hello world", 5), None);
+    }
+
+    #[test]
+    fn truncation_cuts_at_char_boundary() {
+        assert_eq!(
+            TruncationStrategy::Truncate.apply("héllo wörld", 7),
+            Some("héllo w".to_string())
+        );
+    }
+
+    #[test]
+    fn truncation_keeps_graphemes_whole() {
+        // Pointed "shalom": the points are separate chars following their letter, so
+        // cutting after the fifth char would leave the vav without its holam
+        assert_eq!(
+            TruncationStrategy::Truncate.apply(
+                "\u{5e9}\u{5b8}\u{5c1}\u{5dc}\u{5d5}\u{5b9}\u{5dd} world 42",
+                5
+            ),
+            Some("\u{5e9}\u{5b8}\u{5c1}\u{5dc}".to_string())
+        );
+        assert_eq!(
+            TruncationStrategy::Truncate.apply("שלום world 42", 8),
+            Some("שלום wor".to_string())
+        );
+    }
+
+    #[test]
+    fn ellipsis_counts_towards_limit() {
+        let result = TruncationStrategy::Ellipsis
+            .apply("hello world", 6)
+            .expect("text should be truncated");
+        assert_eq!(result, "hello…");
+        assert_eq!(result.chars().count(), 6);
+    }
+
+    fn configured_post_process_settings() -> AppSettings {
+        let mut settings = get_default_settings();
+        settings.post_process_enabled = true;
+        settings.post_process_provider_id = "openai".to_string();
+        settings
+            .post_process_models
+            .insert("openai".to_string(), "gpt-4o-mini".to_string());
+        settings.post_process_selected_prompt_id = settings
+            .post_process_prompts
+            .first()
+            .map(|prompt| prompt.id.clone());
+        settings
+    }
+
+    #[test]
+    fn post_process_validation_passes_when_configured_or_disabled() {
+        let settings = configured_post_process_settings();
+        assert_eq!(settings.post_process_misconfiguration(), None);
+
+        let mut disabled = get_default_settings();
+        disabled.post_process_enabled = false;
+        assert_eq!(disabled.post_process_misconfiguration(), None);
+    }
+
+    #[test]
+    fn post_process_validation_reports_missing_piece() {
+        let mut settings = configured_post_process_settings();
+        settings
+            .post_process_models
+            .insert("openai".to_string(), "  ".to_string());
+        assert_eq!(
+            settings.post_process_misconfiguration(),
+            Some(PostProcessMisconfiguration::MissingModel {
+                provider_id: "openai".to_string()
+            })
+        );
+
+        let mut settings = configured_post_process_settings();
+        settings.post_process_selected_prompt_id = None;
+        settings.post_process_prompts.clear();
+        assert_eq!(
+            settings.post_process_misconfiguration(),
+            Some(PostProcessMisconfiguration::NoPromptSelected)
+        );
+
+        let mut settings = configured_post_process_settings();
+        settings.post_process_selected_prompt_id = Some("missing".to_string());
+        assert_eq!(
+            settings.post_process_misconfiguration(),
+            Some(PostProcessMisconfiguration::PromptNotFound {
+                prompt_id: "missing".to_string()
+            })
+        );
+
+        let mut settings = configured_post_process_settings();
+        settings.post_process_provider_id = "nonexistent".to_string();
+        assert_eq!(
+            settings.post_process_misconfiguration(),
+            Some(PostProcessMisconfiguration::NoProvider)
+        );
+    }
+
+    #[test]
+    fn offline_mode_rejects_remote_providers() {
+        assert!(is_local_base_url("http://localhost:1234/v1"));
+        assert!(is_local_base_url("http://127.0.0.1:11434/v1"));
+        assert!(is_local_base_url("http://[::1]:8080"));
+        assert!(!is_local_base_url("http://192.168.1.20:11434/v1"));
+        assert!(!is_local_base_url("https://localhost.example.com/v1"));
+
+        let mut settings = configured_post_process_settings();
+        settings.offline_mode = true;
+        assert_eq!(
+            settings.post_process_misconfiguration(),
+            Some(PostProcessMisconfiguration::RemoteProviderOffline {
+                provider_id: "openai".to_string()
+            })
+        );
+        assert!(settings
+            .usable_post_process_providers()
+            .all(|provider| provider.is_local()));
+    }
+
+    #[test]
+    fn unselected_prompt_resolves_to_default_then_first() {
+        let mut settings = configured_post_process_settings();
+        settings.post_process_selected_prompt_id = None;
+        settings.post_process_default_prompt_id = "professional_email".to_string();
+        assert_eq!(
+            settings
+                .resolve_post_process_prompt()
+                .map(|p| p.id.as_str()),
+            Ok("professional_email")
+        );
+
+        settings.post_process_default_prompt_id = "deleted".to_string();
+        assert_eq!(
+            settings
+                .resolve_post_process_prompt()
+                .map(|p| p.id.as_str()),
+            Ok(settings.post_process_prompts[0].id.as_str())
+        );
+        assert_eq!(settings.post_process_misconfiguration(), None);
+    }
+
+    #[test]
+    fn duplicate_prompt_gets_unique_id_and_copy_suffix() {
+        let mut settings = get_default_settings();
+        let original = settings.post_process_prompts[0].clone();
+
+        let first = settings
+            .duplicate_prompt(&original.id, None)
+            .expect("duplicate prompt");
+        assert_eq!(first.id, format!("{}_copy", original.id));
+        assert_eq!(first.name, format!("{} (copy)", original.name));
+        assert_eq!(first.prompt, original.prompt);
+
+        // Duplicating again must not collide with the first copy
+        let second = settings
+            .duplicate_prompt(&original.id, Some("Variant".to_string()))
+            .expect("duplicate prompt again");
+        assert_eq!(second.id, format!("{}_copy_2", original.id));
+        assert_eq!(second.name, "Variant");
+
+        let ids: std::collections::HashSet<_> = settings
+            .post_process_prompts
+            .iter()
+            .map(|p| &p.id)
+            .collect();
+        assert_eq!(ids.len(), settings.post_process_prompts.len());
+    }
+
+    #[test]
+    fn duplicate_prompt_skips_ids_taken_by_user_prompts() {
+        let mut settings = get_default_settings();
+        let original_id = settings.post_process_prompts[0].id.clone();
+        settings.post_process_prompts.push(LLMPrompt {
+            id: format!("{}_copy", original_id),
+            name: "User prompt".to_string(),
+            prompt: "custom".to_string(),
+            response_format: None,
+            json_output: JsonOutput::default(),
+            short_label: None,
+            seed: None,
+        });
+
+        let copy = settings
+            .duplicate_prompt(&original_id, None)
+            .expect("duplicate prompt");
+        assert_eq!(copy.id, format!("{}_copy_2", original_id));
+        assert!(settings.duplicate_prompt("missing", None).is_err());
+    }
+
+    #[test]
+    fn reset_prompt_restores_builtin_text_and_selection() {
+        let mut settings = get_default_settings();
+        let builtin = default_post_process_prompts()[0].clone();
+
+        settings.post_process_prompts[0].prompt = "mangled".to_string();
+        settings.post_process_selected_prompt_id = Some("deleted".to_string());
+
+        let restored = settings
+            .reset_prompt_to_default(&builtin.id)
+            .expect("reset prompt");
+        assert_eq!(restored.prompt, builtin.prompt);
+        assert_eq!(settings.post_process_prompts[0].prompt, builtin.prompt);
+        assert_eq!(
+            settings.post_process_selected_prompt_id.as_deref(),
+            Some(builtin.id.as_str())
+        );
+    }
+
+    #[test]
+    fn reset_prompt_readds_deleted_builtin_and_rejects_custom() {
+        let mut settings = get_default_settings();
+        let builtin = default_post_process_prompts()[0].clone();
+        settings.post_process_prompts.retain(|p| p.id != builtin.id);
+
+        settings
+            .reset_prompt_to_default(&builtin.id)
+            .expect("reset prompt");
+        assert!(settings
+            .post_process_prompts
+            .iter()
+            .any(|p| p.id == builtin.id));
+        assert!(settings.reset_prompt_to_default("prompt_123").is_err());
+    }
+
+    #[test]
+    fn binding_feedback_mode_overrides_global() {
+        let mut settings = get_default_settings();
+        settings.audio_feedback = true;
+        settings.feedback_mode = FeedbackMode::Sound;
+        assert_eq!(
+            settings.feedback_mode_for("transcribe"),
+            FeedbackMode::Sound
+        );
+
+        // The global mode stays silent without audio feedback
+        settings.audio_feedback = false;
+        assert_eq!(settings.feedback_mode_for("transcribe"), FeedbackMode::None);
+        settings.feedback_mode = FeedbackMode::Both;
+        assert_eq!(
+            settings.feedback_mode_for("transcribe"),
+            FeedbackMode::Overlay
+        );
+
+        // A binding's own mode plays sounds regardless
+        settings
+            .bindings
+            .get_mut("transcribe")
+            .unwrap()
+            .feedback_mode = Some(FeedbackMode::Sound);
+        assert_eq!(
+            settings.feedback_mode_for("transcribe"),
+            FeedbackMode::Sound
+        );
+
+        settings
+            .bindings
+            .get_mut("transcribe")
+            .unwrap()
+            .feedback_mode = Some(FeedbackMode::None);
+        assert_eq!(settings.feedback_mode_for("transcribe"), FeedbackMode::None);
+        assert!(!FeedbackMode::None.plays_sound());
+        assert!(FeedbackMode::Both.shows_overlay());
+    }
+
+    #[test]
+    fn single_line_prefers_app_rule_then_binding() {
+        let mut settings = get_default_settings();
+        assert!(!settings.collapse_to_single_line_for("transcribe", None));
+
+        settings
+            .bindings
+            .get_mut("transcribe")
+            .unwrap()
+            .collapse_to_single_line = Some(true);
+        assert!(settings.collapse_to_single_line_for("transcribe", None));
+        assert!(!settings.collapse_to_single_line_for("transcribe", Some(false)));
+        assert!(!settings.collapse_to_single_line_for("cancel", None));
+    }
+}
@@ -0,0 +1,334 @@
+//! Picks up edits made to `settings_store.json` outside the app, by users or scripts,
+//! without a restart.
+//!
+//! While `watch_settings_file` is on, the store file is polled for modifications.
+//! Once it stops changing, a file whose content hash is the one last seen is skipped.
+//! `write_settings` records the hash of every save, so the app's own saves are
+//! skipped without parsing them; anything else is reloaded and applied, then
+//! `settings-reloaded` lists the keys that changed.
+
+use crate::events::{emit_event, AppEvent};
+use crate::managers::model::ModelManager;
+use crate::overlay;
+use crate::paths;
+use crate::settings::{self, AppSettings, ShortcutBinding};
+use crate::shortcut;
+use crate::tray;
+use log::{debug, info, warn};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_store::StoreExt;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Rapid successive writes are applied once, after the file is quiet for this long
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Stops the running watcher when set
+static STOP: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+/// Hash of the store file as last read or written by the app
+static LAST_HASH: Mutex<Option<u64>> = Mutex::new(None);
+
+/// (Re)starts watching the settings store for external edits, if
+/// `watch_settings_file` is on
+pub fn restart(app: &AppHandle) {
+    let mut running = STOP.lock().unwrap();
+    if let Some(stop) = running.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    if !settings::get_settings(app).watch_settings_file {
+        return;
+    }
+
+    let path = paths::get(app).settings_store();
+    record_hash(&path);
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = Arc::clone(&stop);
+    let app = app.clone();
+    std::thread::spawn(move || watch(&app, &path, &thread_stop));
+    *running = Some(stop);
+}
+
+/// Called by `write_settings` after every save, so the watcher skips it
+pub fn record_own_write(app: &AppHandle) {
+    record_hash(&paths::get(app).settings_store());
+}
+
+fn record_hash(path: &Path) {
+    *LAST_HASH.lock().unwrap() = std::fs::read_to_string(path)
+        .ok()
+        .map(|contents| content_hash(&contents));
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn content_hash(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn watch(app: &AppHandle, path: &Path, stop: &AtomicBool) {
+    debug!("Watching {} for external edits", path.display());
+    let mut last_modified = modified(path);
+    let mut changed_at: Option<Instant> = None;
+    while !stop.load(Ordering::Relaxed) {
+        std::thread::sleep(POLL_INTERVAL);
+        let current = modified(path);
+        if current != last_modified {
+            last_modified = current;
+            changed_at = Some(Instant::now());
+            continue;
+        }
+        if changed_at.is_some_and(|at| at.elapsed() >= DEBOUNCE) {
+            changed_at = None;
+            reload_if_edited(app, path);
+        }
+    }
+    debug!("Stopped watching {}", path.display());
+}
+
+fn reload_if_edited(app: &AppHandle, path: &Path) {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return;
+    };
+    // Saved by the app, or touched or rewritten as it was, nothing to parse
+    let hash = content_hash(&contents);
+    if LAST_HASH.lock().unwrap().replace(hash) == Some(hash) {
+        return;
+    }
+    let on_disk = match serde_json::from_str::<serde_json::Value>(&contents) {
+        Ok(value) => value.get("settings").cloned(),
+        Err(e) => {
+            warn!(
+                "Ignoring settings store edit, the file is not valid JSON: {}",
+                e
+            );
+            return;
+        }
+    };
+    let Some(on_disk) = on_disk else {
+        return;
+    };
+
+    let store = match app.store(paths::get(app).settings_store()) {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("Failed to open the settings store: {}", e);
+            return;
+        }
+    };
+    // A save the hash missed, e.g. the store rewritten by a plugin
+    if store.get("settings").as_ref() == Some(&on_disk) {
+        return;
+    }
+    if let Err(e) = serde_json::from_value::<AppSettings>(on_disk) {
+        warn!(
+            "Ignoring settings store edit, the settings are invalid: {}",
+            e
+        );
+        return;
+    }
+
+    let previous = settings::get_settings(app);
+    if let Err(e) = store.reload() {
+        warn!("Failed to reload the settings store: {}", e);
+        return;
+    }
+    let current = settings::get_settings(app);
+    let changed = changed_keys(&previous, &current);
+    info!(
+        "Settings store was edited externally, reloaded settings: {}",
+        changed.join(", ")
+    );
+
+    apply_changes(app, &previous, &current);
+    emit_event(app, AppEvent::SettingsReloaded(changed));
+}
+
+/// Top-level settings keys whose values differ, sorted
+fn changed_keys(previous: &AppSettings, current: &AppSettings) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(previous)), Ok(serde_json::Value::Object(current))) = (
+        serde_json::to_value(previous),
+        serde_json::to_value(current),
+    ) else {
+        return Vec::new();
+    };
+    // Both have every field, so the keys are the same
+    let mut keys: Vec<String> = current
+        .iter()
+        .filter(|(key, value)| previous.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Shortcuts to unregister and to register for the bindings that were removed,
+/// added or rebound
+fn binding_changes(
+    previous: &AppSettings,
+    current: &AppSettings,
+) -> (Vec<ShortcutBinding>, Vec<ShortcutBinding>) {
+    let mut unregister = Vec::new();
+    let mut register = Vec::new();
+    for (id, old) in &previous.bindings {
+        // The cancel shortcut is only registered while recording
+        if id == "cancel" {
+            continue;
+        }
+        match current.bindings.get(id) {
+            Some(binding) if binding.current_binding == old.current_binding => {}
+            _ => unregister.push(old.clone()),
+        }
+    }
+    for (id, binding) in &current.bindings {
+        if id == "cancel" {
+            continue;
+        }
+        match previous.bindings.get(id) {
+            Some(old) if old.current_binding == binding.current_binding => {}
+            _ => register.push(binding.clone()),
+        }
+    }
+    (unregister, register)
+}
+
+/// Applies the settings that are otherwise only read at startup or by their commands
+fn apply_changes(app: &AppHandle, previous: &AppSettings, current: &AppSettings) {
+    let (unregister, register) = binding_changes(previous, current);
+    for binding in unregister {
+        let id = binding.id.clone();
+        if let Err(e) = shortcut::unregister_shortcut(app, binding) {
+            warn!("Failed to unregister shortcut {}: {}", id, e);
+        }
+    }
+    for binding in register {
+        let id = binding.id.clone();
+        if let Err(e) = shortcut::register_shortcut(app, binding) {
+            warn!("Failed to register shortcut {}: {}", id, e);
+        }
+    }
+
+    // The menu shows prompts, models and more besides the language
+    tray::refresh_tray_menu(app);
+
+    if previous.autostart_enabled != current.autostart_enabled {
+        let autostart = app.autolaunch();
+        let result = if current.autostart_enabled {
+            autostart.enable()
+        } else {
+            autostart.disable()
+        };
+        if let Err(e) = result {
+            warn!("Failed to apply the autostart setting: {}", e);
+        }
+    }
+
+    if previous.overlay_position != current.overlay_position {
+        overlay::update_overlay_position(app);
+    }
+
+    if previous.offline_mode != current.offline_mode {
+        crate::llm_client::set_offline_mode(current.offline_mode);
+    }
+
+    if previous.llm_user_agent != current.llm_user_agent {
+        crate::llm_client::set_user_agent(current.llm_user_agent.clone());
+    }
+
+    if previous.watch_folders != current.watch_folders {
+        crate::watch_folders::restart(app);
+    }
+
+    if previous.power_profiles != current.power_profiles {
+        crate::power_profile::apply(app);
+    }
+
+    if previous.wake_word_enabled != current.wake_word_enabled
+        || previous.wake_word_model != current.wake_word_model
+        || previous.wake_word_sensitivity != current.wake_word_sensitivity
+        || previous.wake_word_cooldown_ms != current.wake_word_cooldown_ms
+    {
+        crate::wake_word::restart(app);
+    }
+
+    if previous.model_manifest_url != current.model_manifest_url {
+        match current.model_manifest_url {
+            Some(_) => crate::model_manifest::refresh_in_background(app, true),
+            None => crate::model_manifest::clear_cache(app),
+        }
+    }
+
+    if previous.model_quantization != current.model_quantization {
+        app.state::<Arc<ModelManager>>()
+            .apply_quantization_preferences();
+    }
+
+    if previous.watch_settings_file != current.watch_settings_file {
+        restart(app);
+    }
+
+    if previous.log_level != current.log_level {
+        let level: tauri_plugin_log::LogLevel = current.log_level.into();
+        let level: log::Level = level.into();
+        crate::FILE_LOG_LEVEL.store(
+            level.to_level_filter() as u8,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{get_default_settings, OverlayPosition};
+
+    #[test]
+    fn changed_keys_lists_top_level_differences() {
+        let previous = get_default_settings();
+        assert!(changed_keys(&previous, &previous).is_empty());
+
+        let mut current = previous.clone();
+        current.overlay_position = OverlayPosition::Top;
+        current.autostart_enabled = !previous.autostart_enabled;
+        current
+            .bindings
+            .get_mut("transcribe")
+            .unwrap()
+            .current_binding = "ctrl+alt+d".to_string();
+        assert_eq!(
+            changed_keys(&previous, &current),
+            vec!["autostart_enabled", "bindings", "overlay_position"]
+        );
+    }
+
+    #[test]
+    fn removed_and_rebound_bindings_are_unregistered() {
+        let ids = |bindings: Vec<ShortcutBinding>| -> Vec<String> {
+            bindings.into_iter().map(|binding| binding.id).collect()
+        };
+        let previous = get_default_settings();
+        let mut current = previous.clone();
+        let mut rebound = current.bindings.remove("transcribe").unwrap();
+        let (unregister, register) = binding_changes(&previous, &current);
+        assert_eq!(ids(unregister), vec!["transcribe"]);
+        assert!(register.is_empty());
+
+        rebound.current_binding = "ctrl+alt+d".to_string();
+        current.bindings.insert(rebound.id.clone(), rebound);
+        let (unregister, register) = binding_changes(&previous, &current);
+        assert_eq!(ids(unregister), vec!["transcribe"]);
+        assert_eq!(ids(register), vec!["transcribe"]);
+
+        let (unregister, register) = binding_changes(&previous, &previous);
+        assert!(unregister.is_empty() && register.is_empty());
+    }
+}
@@ -9,9 +9,15 @@ use tauri::{AppHandle, Manager};
 
 use crate::actions::ACTION_MAP;
 use crate::managers::audio::AudioRecordingManager;
-use crate::settings::get_settings;
+use crate::settings::{
+    get_settings, COPY_ONLY_SESSION_BINDING, COPY_PREVIOUS_TRANSCRIPT_BINDING,
+    COPY_TRANSCRIPT_TWO_BACK_BINDING, DICTATION_SESSION_BINDING, FLUSH_DEFERRED_PASTE_BINDING,
+    UNDO_LAST_PASTE_BINDING,
+};
 use crate::ManagedToggleState;
 
+use super::{key_suppression, test_mode};
+
 /// Handle a shortcut event from either implementation.
 ///
 /// This function contains the shared logic for:
@@ -39,6 +45,11 @@ pub fn handle_shortcut_event(
         is_pressed
     );
 
+    if test_mode::is_enabled() {
+        test_mode::report(app, binding_id, hotkey_string, is_pressed);
+        return;
+    }
+
     let settings = get_settings(app);
 
     let Some(action) = ACTION_MAP.get(binding_id) else {
@@ -58,11 +69,36 @@ pub fn handle_shortcut_event(
         return;
     }
 
+    // Copy, deferred paste and toggle shortcuts act once per press, regardless of push-to-talk
+    if binding_id == COPY_PREVIOUS_TRANSCRIPT_BINDING
+        || binding_id == COPY_TRANSCRIPT_TWO_BACK_BINDING
+        || binding_id == FLUSH_DEFERRED_PASTE_BINDING
+        || binding_id == COPY_ONLY_SESSION_BINDING
+        || binding_id == DICTATION_SESSION_BINDING
+    {
+        if is_pressed {
+            action.start(app, binding_id, hotkey_string);
+        }
+        return;
+    }
+
+    // Undo acts once per release, when the shortcut's modifiers are let go
+    if binding_id == UNDO_LAST_PASTE_BINDING {
+        if !is_pressed {
+            action.stop(app, binding_id, hotkey_string);
+        }
+        return;
+    }
+
     // Push-to-talk mode: start on press, stop on release
     if settings.push_to_talk {
         if is_pressed {
+            if settings.suppress_keys_during_ptt {
+                key_suppression::begin();
+            }
             action.start(app, binding_id, hotkey_string);
         } else {
+            key_suppression::end();
             action.stop(app, binding_id, hotkey_string);
         }
         return;
@@ -36,11 +36,13 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
 
+use crate::events::{emit_event, AppEvent};
 use crate::settings::{self, get_settings, ShortcutBinding};
 
 use super::handler::handle_shortcut_event;
+use super::layout;
 
 /// Commands that can be sent to the hotkey manager thread
 enum ManagerCommand {
@@ -53,6 +55,9 @@ enum ManagerCommand {
         binding_id: String,
         response: Sender<Result<(), String>>,
     },
+    Registered {
+        response: Sender<Vec<String>>,
+    },
     Shutdown,
 }
 
@@ -165,6 +170,9 @@ impl HandyKeysState {
                         );
                         let _ = response.send(result);
                     }
+                    ManagerCommand::Registered { response } => {
+                        let _ = response.send(binding_to_hotkey.keys().cloned().collect());
+                    }
                     ManagerCommand::Shutdown => {
                         info!("handy-keys manager thread shutting down");
                         break;
@@ -228,13 +236,18 @@ impl HandyKeysState {
 
     /// Register a shortcut binding
     pub fn register(&self, binding: &ShortcutBinding) -> Result<(), String> {
+        // Optional bindings stay unbound until the user assigns a shortcut
+        if binding.current_binding.is_empty() {
+            return Ok(());
+        }
+
         let (tx, rx) = mpsc::channel();
         self.command_sender
             .lock()
             .map_err(|_| "Failed to lock command_sender")?
             .send(ManagerCommand::Register {
                 binding_id: binding.id.clone(),
-                hotkey_string: binding.current_binding.clone(),
+                hotkey_string: layout::resolve_for_registration(binding),
                 response: tx,
             })
             .map_err(|_| "Failed to send register command")?;
@@ -259,6 +272,19 @@ impl HandyKeysState {
             .map_err(|_| "Failed to receive unregister response")?
     }
 
+    /// IDs of the bindings the manager thread currently listens for
+    pub fn registered_bindings(&self) -> Result<Vec<String>, String> {
+        let (tx, rx) = mpsc::channel();
+        self.command_sender
+            .lock()
+            .map_err(|_| "Failed to lock command_sender")?
+            .send(ManagerCommand::Registered { response: tx })
+            .map_err(|_| "Failed to send registered command")?;
+
+        rx.recv()
+            .map_err(|_| "Failed to receive registered response".to_string())
+    }
+
     /// Start recording mode for a specific binding
     pub fn start_recording(&self, app: &AppHandle, binding_id: String) -> Result<(), String> {
         if self.is_recording.load(Ordering::SeqCst) {
@@ -323,9 +349,7 @@ impl HandyKeysState {
                 };
 
                 // Emit to frontend
-                if let Err(e) = app.emit("handy-keys-event", &frontend_event) {
-                    error!("Failed to emit key event: {}", e);
-                }
+                emit_event(&app, AppEvent::HandyKeysEvent(frontend_event));
             } else {
                 thread::sleep(std::time::Duration::from_millis(10));
             }
@@ -449,6 +473,7 @@ pub fn init_shortcuts(app: &AppHandle) -> Result<(), String> {
     }
 
     app.manage(state);
+    layout::start_watcher(app);
     info!("handy-keys shortcuts initialized");
     Ok(())
 }
@@ -517,19 +542,19 @@ pub fn unregister_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<
 /// Start key recording mode
 #[tauri::command]
 #[specta::specta]
-pub fn start_handy_keys_recording(app: AppHandle, binding_id: String) -> Result<(), String> {
+pub fn start_handy_keys_recording(app: AppHandle, binding_id: String) -> Result<(), HandyError> {
     let state = app
         .try_state::<HandyKeysState>()
         .ok_or("HandyKeysState not initialized")?;
-    state.start_recording(&app, binding_id)
+    Ok(state.start_recording(&app, binding_id)?)
 }
 
 /// Stop key recording mode
 #[tauri::command]
 #[specta::specta]
-pub fn stop_handy_keys_recording(app: AppHandle) -> Result<(), String> {
+pub fn stop_handy_keys_recording(app: AppHandle) -> Result<(), HandyError> {
     let state = app
         .try_state::<HandyKeysState>()
         .ok_or("HandyKeysState not initialized")?;
-    state.stop_recording()
+    Ok(state.stop_recording()?)
 }
@@ -0,0 +1,94 @@
+//! Swallows key presses while a push-to-talk binding is held.
+//!
+//! A PTT shortcut built on a real modifier (Option on macOS, Alt on Windows) turns
+//! every other key pressed during the recording into an app shortcut in the focused
+//! window. With `suppress_keys_during_ptt` on, a system-wide hook drops those presses
+//! until the binding is released.
+//!
+//! Platform limitations:
+//! - macOS and Windows only. Linux would need exclusive access to the input device,
+//!   so the setting is rejected there.
+//! - Modifier keys and all key releases always pass through, so the PTT binding
+//!   itself is never stuck. Escape passes through for the default cancel shortcut;
+//!   a cancel shortcut on another plain key may be swallowed.
+//! - The hook is installed the first time suppression is needed and stays for the
+//!   rest of the session; it only acts while a binding is held.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use rdev::{Event, EventType, Key};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::sync::Once;
+
+static SUPPRESSING: AtomicBool = AtomicBool::new(false);
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+static HOOK: Once = Once::new();
+
+pub const fn is_supported() -> bool {
+    cfg!(any(target_os = "macos", target_os = "windows"))
+}
+
+/// Starts dropping key presses until `end` is called
+pub fn begin() {
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    {
+        HOOK.call_once(|| {
+            std::thread::spawn(|| {
+                if let Err(e) = rdev::grab(filter) {
+                    log::error!("Failed to install key suppression hook: {:?}", e);
+                }
+            });
+        });
+        SUPPRESSING.store(true, Ordering::SeqCst);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    log::debug!("Key suppression is not supported on this platform");
+}
+
+pub fn end() {
+    SUPPRESSING.store(false, Ordering::SeqCst);
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn filter(event: Event) -> Option<Event> {
+    if !SUPPRESSING.load(Ordering::SeqCst) {
+        return Some(event);
+    }
+    match event.event_type {
+        EventType::KeyPress(key) if !passes_through(key) => None,
+        _ => Some(event),
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn passes_through(key: Key) -> bool {
+    matches!(
+        key,
+        Key::ShiftLeft
+            | Key::ShiftRight
+            | Key::ControlLeft
+            | Key::ControlRight
+            | Key::Alt
+            | Key::AltGr
+            | Key::MetaLeft
+            | Key::MetaRight
+            | Key::CapsLock
+            | Key::Function
+            | Key::Escape
+    )
+}
+
+#[cfg(all(test, any(target_os = "macos", target_os = "windows")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_and_escape_pass_through() {
+        assert!(passes_through(Key::Alt));
+        assert!(passes_through(Key::Escape));
+        assert!(!passes_through(Key::KeyC));
+    }
+}
@@ -0,0 +1,530 @@
+//! Keyboard layout awareness for bindings.
+//!
+//! Hotkeys react to physical keys, named after the key at that position on a US
+//! QWERTY keyboard. On other layouts the name doesn't match what the key types:
+//! "ctrl+z" fires on the key labeled Y on a German keyboard. Each binding picks how
+//! its key is meant:
+//! - `Scancode`, the default and the behavior before bindings had a mode: the
+//!   physical key. Its display label shows what that key types on the current layout.
+//! - `Symbol`: the key that types the character. It is resolved to a physical key
+//!   when registering, and registered again when the layout changes.
+//!
+//! Only letter and digit keys are translated; other keys have the same name on
+//! every layout. The layout is read again when the OS reports a change: macOS
+//! posts a notification when the input source is switched. Windows keeps a layout
+//! per thread and has no system-wide notification, so it is read whenever another
+//! window comes to the foreground; a switch within the same window is picked up at
+//! the next one. On Linux the layout can't be read, so both modes behave as
+//! `Scancode` there.
+
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use super::handy_keys::HandyKeysState;
+use crate::events::{emit_event, AppEvent};
+use crate::settings::{get_settings, BindingMatchMode, ShortcutBinding};
+
+/// How long reading the layout may wait for the main thread
+const MAIN_THREAD_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// US QWERTY name, macOS virtual keycode and Windows set 1 scancode of the keys
+/// whose character depends on the layout
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+const PHYSICAL_KEYS: [(&str, u16, u16); 36] = [
+    ("a", 0, 0x1E),
+    ("s", 1, 0x1F),
+    ("d", 2, 0x20),
+    ("f", 3, 0x21),
+    ("h", 4, 0x23),
+    ("g", 5, 0x22),
+    ("z", 6, 0x2C),
+    ("x", 7, 0x2D),
+    ("c", 8, 0x2E),
+    ("v", 9, 0x2F),
+    ("b", 11, 0x30),
+    ("q", 12, 0x10),
+    ("w", 13, 0x11),
+    ("e", 14, 0x12),
+    ("r", 15, 0x13),
+    ("y", 16, 0x15),
+    ("t", 17, 0x14),
+    ("1", 18, 0x02),
+    ("2", 19, 0x03),
+    ("3", 20, 0x04),
+    ("4", 21, 0x05),
+    ("6", 22, 0x07),
+    ("5", 23, 0x06),
+    ("9", 25, 0x0A),
+    ("7", 26, 0x08),
+    ("8", 28, 0x09),
+    ("0", 29, 0x0B),
+    ("o", 31, 0x18),
+    ("u", 32, 0x16),
+    ("i", 34, 0x17),
+    ("p", 35, 0x19),
+    ("l", 37, 0x26),
+    ("j", 38, 0x24),
+    ("k", 40, 0x25),
+    ("n", 45, 0x31),
+    ("m", 46, 0x32),
+];
+
+/// Characters the layout-dependent keys type on one keyboard layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardLayout {
+    /// Platform identifier, e.g. `com.apple.keylayout.German`
+    pub id: String,
+    /// Lowercase character typed by each physical key, keyed by its US QWERTY name
+    symbols: HashMap<&'static str, char>,
+}
+
+impl KeyboardLayout {
+    fn symbol_of(&self, physical: &str) -> Option<char> {
+        self.symbols.get(physical).copied()
+    }
+
+    fn physical_of(&self, symbol: char) -> Option<&'static str> {
+        self.symbols
+            .iter()
+            .find(|(_, typed)| **typed == symbol)
+            .map(|(physical, _)| *physical)
+    }
+}
+
+static CURRENT_LAYOUT: Mutex<Option<KeyboardLayout>> = Mutex::new(None);
+
+fn current_layout() -> Option<KeyboardLayout> {
+    CURRENT_LAYOUT.lock().unwrap().clone()
+}
+
+/// Replaces the keys of a hotkey string that `map` translates, keeping modifiers
+fn map_keys(hotkey: &str, map: impl Fn(&str) -> Option<String>) -> String {
+    hotkey
+        .split('+')
+        .map(|part| map(part).unwrap_or_else(|| part.to_string()))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn resolve_with(binding: &ShortcutBinding, layout: Option<&KeyboardLayout>) -> String {
+    match (binding.match_mode, layout) {
+        (BindingMatchMode::Symbol, Some(layout)) => map_keys(&binding.current_binding, |key| {
+            let mut chars = key.chars();
+            let symbol = chars.next().filter(|_| chars.next().is_none())?;
+            layout.physical_of(symbol).map(str::to_string)
+        }),
+        _ => binding.current_binding.clone(),
+    }
+}
+
+fn label_with(binding: &ShortcutBinding, layout: Option<&KeyboardLayout>) -> String {
+    match (binding.match_mode, layout) {
+        (BindingMatchMode::Scancode, Some(layout)) => map_keys(&binding.current_binding, |key| {
+            layout.symbol_of(key).map(|symbol| symbol.to_string())
+        }),
+        _ => binding.current_binding.clone(),
+    }
+}
+
+/// Hotkey string to register for a binding on the current layout
+pub fn resolve_for_registration(binding: &ShortcutBinding) -> String {
+    resolve_with(binding, current_layout().as_ref())
+}
+
+/// The binding as it reads on the current layout's key caps
+pub fn display_label(binding: &ShortcutBinding) -> String {
+    label_with(binding, current_layout().as_ref())
+}
+
+/// Reads the keyboard layout, then again whenever the OS reports a change, and
+/// re-registers `Symbol` bindings when it differs
+pub fn start_watcher(app: &AppHandle) {
+    if !cfg!(any(target_os = "macos", target_os = "windows")) {
+        return;
+    }
+    // Notifications arrive on the main thread on macOS, which reading the layout
+    // needs to be free for, so they are handled here
+    let (tx, rx) = mpsc::channel();
+    let app = app.clone();
+    std::thread::spawn(move || {
+        update_layout(&app);
+        if !platform::watch(
+            &app,
+            Box::new(move || {
+                let _ = tx.send(());
+            }),
+        ) {
+            warn!("Keyboard layout changes can't be observed, bindings follow the startup layout");
+            return;
+        }
+        while rx.recv().is_ok() {
+            // One read covers a burst of notifications
+            while rx.try_recv().is_ok() {}
+            update_layout(&app);
+        }
+    });
+}
+
+type Callback = Box<dyn Fn() + Send + Sync>;
+
+fn update_layout(app: &AppHandle) {
+    let Some(layout) = read_layout_on_main_thread(app) else {
+        return;
+    };
+    let changed = {
+        let mut current = CURRENT_LAYOUT.lock().unwrap();
+        let changed = current.as_ref() != Some(&layout);
+        *current = Some(layout.clone());
+        changed
+    };
+    if changed {
+        info!("Keyboard layout is {}", layout.id);
+        reregister_symbol_bindings(app);
+        emit_event(app, AppEvent::KeyboardLayoutChanged(layout.id));
+    }
+}
+
+fn reregister_symbol_bindings(app: &AppHandle) {
+    let Some(state) = app.try_state::<HandyKeysState>() else {
+        return;
+    };
+    // Cancel is only registered while recording, so only touch what is registered
+    let registered = state.registered_bindings().unwrap_or_default();
+    for binding in get_settings(app).bindings.values() {
+        if binding.match_mode != BindingMatchMode::Symbol || !registered.contains(&binding.id) {
+            continue;
+        }
+        let _ = state.unregister(binding);
+        match state.register(binding) {
+            Ok(()) => debug!("Re-registered {} for the new layout", binding.id),
+            Err(e) => warn!("Failed to re-register {}: {}", binding.id, e),
+        }
+    }
+}
+
+/// The layout APIs must be called from the main thread on macOS
+fn read_layout_on_main_thread(app: &AppHandle) -> Option<KeyboardLayout> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    app.run_on_main_thread(move || {
+        let _ = tx.send(platform::read_layout());
+    })
+    .ok()?;
+    rx.recv_timeout(MAIN_THREAD_TIMEOUT).ok().flatten()
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{Callback, KeyboardLayout, PHYSICAL_KEYS};
+    use log::warn;
+    use std::ffi::{c_char, c_void, CStr};
+    use tauri::AppHandle;
+
+    type CFTypeRef = *const c_void;
+
+    const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+    const UC_KEY_ACTION_DISPLAY: u16 = 3;
+    const UC_KEY_TRANSLATE_NO_DEAD_KEYS_MASK: u32 = 1;
+    const NOTIFICATION_DELIVER_IMMEDIATELY: isize = 4;
+
+    type NotificationCallback = extern "C" fn(
+        center: CFTypeRef,
+        observer: *mut c_void,
+        name: CFTypeRef,
+        object: *const c_void,
+        user_info: CFTypeRef,
+    );
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        static kTISNotifySelectedKeyboardInputSourceChanged: CFTypeRef;
+        static kTISPropertyInputSourceID: CFTypeRef;
+        static kTISPropertyUnicodeKeyLayoutData: CFTypeRef;
+        fn TISCopyCurrentKeyboardLayoutInputSource() -> CFTypeRef;
+        fn TISGetInputSourceProperty(source: CFTypeRef, key: CFTypeRef) -> CFTypeRef;
+        fn LMGetKbdType() -> u8;
+        fn UCKeyTranslate(
+            layout: *const c_void,
+            key_code: u16,
+            key_action: u16,
+            modifier_key_state: u32,
+            keyboard_type: u32,
+            key_translate_options: u32,
+            dead_key_state: *mut u32,
+            max_string_length: usize,
+            actual_string_length: *mut usize,
+            unicode_string: *mut u16,
+        ) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFDataGetBytePtr(data: CFTypeRef) -> *const u8;
+        fn CFStringGetCString(
+            string: CFTypeRef,
+            buffer: *mut c_char,
+            buffer_size: isize,
+            encoding: u32,
+        ) -> u8;
+        fn CFRelease(cf: CFTypeRef);
+        fn CFNotificationCenterGetDistributedCenter() -> CFTypeRef;
+        fn CFNotificationCenterAddObserver(
+            center: CFTypeRef,
+            observer: *const c_void,
+            callback: NotificationCallback,
+            name: CFTypeRef,
+            object: *const c_void,
+            suspension_behavior: isize,
+        );
+    }
+
+    /// Runs on the main thread
+    extern "C" fn on_input_source_changed(
+        _center: CFTypeRef,
+        observer: *mut c_void,
+        _name: CFTypeRef,
+        _object: *const c_void,
+        _user_info: CFTypeRef,
+    ) {
+        let on_change = unsafe { &*(observer as *const Callback) };
+        on_change();
+    }
+
+    /// Observes input source switches, registered from the main thread whose run
+    /// loop delivers them
+    pub fn watch(app: &AppHandle, on_change: Callback) -> bool {
+        let registered = app.run_on_main_thread(move || unsafe {
+            // Never freed, the observer stays registered for the life of the app
+            let observer = Box::into_raw(Box::new(on_change)) as *const c_void;
+            CFNotificationCenterAddObserver(
+                CFNotificationCenterGetDistributedCenter(),
+                observer,
+                on_input_source_changed,
+                kTISNotifySelectedKeyboardInputSourceChanged,
+                std::ptr::null(),
+                NOTIFICATION_DELIVER_IMMEDIATELY,
+            );
+        });
+        if let Err(e) = registered {
+            warn!("Failed to observe keyboard layout changes: {}", e);
+            return false;
+        }
+        true
+    }
+
+    pub fn read_layout() -> Option<KeyboardLayout> {
+        unsafe {
+            let source = TISCopyCurrentKeyboardLayoutInputSource();
+            if source.is_null() {
+                return None;
+            }
+            let layout = read_source(source);
+            CFRelease(source);
+            layout
+        }
+    }
+
+    unsafe fn read_source(source: CFTypeRef) -> Option<KeyboardLayout> {
+        let id = TISGetInputSourceProperty(source, kTISPropertyInputSourceID);
+        let mut buffer = [0 as c_char; 256];
+        if id.is_null()
+            || CFStringGetCString(id, buffer.as_mut_ptr(), 256, CF_STRING_ENCODING_UTF8) == 0
+        {
+            return None;
+        }
+        let id = CStr::from_ptr(buffer.as_ptr())
+            .to_string_lossy()
+            .into_owned();
+
+        let data = TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData);
+        if data.is_null() {
+            return None;
+        }
+        let layout_data = CFDataGetBytePtr(data) as *const c_void;
+        let keyboard_type = LMGetKbdType() as u32;
+
+        let mut symbols = std::collections::HashMap::new();
+        for (name, key_code, _) in PHYSICAL_KEYS {
+            let mut dead_key_state = 0u32;
+            let mut chars = [0u16; 4];
+            let mut length = 0usize;
+            let status = UCKeyTranslate(
+                layout_data,
+                key_code,
+                UC_KEY_ACTION_DISPLAY,
+                0,
+                keyboard_type,
+                UC_KEY_TRANSLATE_NO_DEAD_KEYS_MASK,
+                &mut dead_key_state,
+                chars.len(),
+                &mut length,
+                chars.as_mut_ptr(),
+            );
+            if status != 0 || length == 0 {
+                continue;
+            }
+            let typed = String::from_utf16_lossy(&chars[..length]);
+            if let Some(symbol) = typed.to_lowercase().chars().next() {
+                symbols.insert(name, symbol);
+            }
+        }
+        Some(KeyboardLayout { id, symbols })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{Callback, KeyboardLayout, PHYSICAL_KEYS};
+    use log::warn;
+    use std::sync::OnceLock;
+    use tauri::AppHandle;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Accessibility::{SetWinEventHook, HWINEVENTHOOK};
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetKeyboardLayout, MapVirtualKeyExW, MAPVK_VK_TO_CHAR, MAPVK_VSC_TO_VK,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetMessageW, GetWindowThreadProcessId, EVENT_SYSTEM_FOREGROUND, MSG,
+        WINEVENT_OUTOFCONTEXT,
+    };
+
+    /// Set on characters of dead keys, which type nothing on their own
+    const DEAD_KEY_FLAG: u32 = 0x8000_0000;
+
+    static ON_CHANGE: OnceLock<Callback> = OnceLock::new();
+
+    unsafe extern "system" fn on_foreground_changed(
+        _hook: HWINEVENTHOOK,
+        _event: u32,
+        _window: HWND,
+        _object: i32,
+        _child: i32,
+        _thread: u32,
+        _time: u32,
+    ) {
+        if let Some(on_change) = ON_CHANGE.get() {
+            on_change();
+        }
+    }
+
+    /// Observes foreground window changes, which switch to that window's layout
+    pub fn watch(_app: &AppHandle, on_change: Callback) -> bool {
+        if ON_CHANGE.set(on_change).is_err() {
+            return false;
+        }
+        std::thread::spawn(|| unsafe {
+            let hook = SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                None,
+                Some(on_foreground_changed),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT,
+            );
+            if hook.is_invalid() {
+                warn!("Failed to observe foreground window changes for the keyboard layout");
+                return;
+            }
+            // Out-of-context events are delivered through this thread's message loop
+            let mut message = MSG::default();
+            while GetMessageW(&mut message, None, 0, 0).as_bool() {}
+        });
+        true
+    }
+
+    /// Layouts are per thread, so the foreground window's is the one being typed with
+    pub fn read_layout() -> Option<KeyboardLayout> {
+        let layout = unsafe {
+            let thread = GetWindowThreadProcessId(GetForegroundWindow(), None);
+            GetKeyboardLayout(thread)
+        };
+
+        let mut symbols = std::collections::HashMap::new();
+        for (name, _, scancode) in PHYSICAL_KEYS {
+            let typed = unsafe {
+                let virtual_key = MapVirtualKeyExW(scancode as u32, MAPVK_VSC_TO_VK, Some(layout));
+                MapVirtualKeyExW(virtual_key, MAPVK_VK_TO_CHAR, Some(layout))
+            };
+            if typed == 0 || typed & DEAD_KEY_FLAG != 0 {
+                continue;
+            }
+            if let Some(symbol) = char::from_u32(typed).and_then(|c| c.to_lowercase().next()) {
+                symbols.insert(name, symbol);
+            }
+        }
+        Some(KeyboardLayout {
+            id: format!("{:08x}", layout.0 as usize),
+            symbols,
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::{Callback, KeyboardLayout};
+    use tauri::AppHandle;
+
+    pub fn watch(_app: &AppHandle, _on_change: Callback) -> bool {
+        false
+    }
+
+    pub fn read_layout() -> Option<KeyboardLayout> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn german() -> KeyboardLayout {
+        let mut symbols: HashMap<&'static str, char> = PHYSICAL_KEYS
+            .iter()
+            .map(|(name, _, _)| (*name, name.chars().next().unwrap()))
+            .collect();
+        symbols.insert("y", 'z');
+        symbols.insert("z", 'y');
+        KeyboardLayout {
+            id: "com.apple.keylayout.German".to_string(),
+            symbols,
+        }
+    }
+
+    fn binding(current: &str, match_mode: BindingMatchMode) -> ShortcutBinding {
+        ShortcutBinding {
+            id: "transcribe".to_string(),
+            name: "Transcribe".to_string(),
+            description: String::new(),
+            default_binding: current.to_string(),
+            current_binding: current.to_string(),
+            review_before_paste: None,
+            feedback_mode: None,
+            collapse_to_single_line: None,
+            selected_model: None,
+            match_mode,
+        }
+    }
+
+    #[test]
+    fn scancode_bindings_show_what_the_key_types() {
+        let layout = german();
+        let binding = binding("ctrl+z", BindingMatchMode::Scancode);
+        assert_eq!(resolve_with(&binding, Some(&layout)), "ctrl+z");
+        assert_eq!(label_with(&binding, Some(&layout)), "ctrl+y");
+        assert_eq!(label_with(&binding, None), "ctrl+z");
+    }
+
+    #[test]
+    fn symbol_bindings_register_the_key_that_types_them() {
+        let layout = german();
+        let binding = binding("ctrl+z", BindingMatchMode::Symbol);
+        assert_eq!(resolve_with(&binding, Some(&layout)), "ctrl+y");
+        assert_eq!(label_with(&binding, Some(&layout)), "ctrl+z");
+
+        let space = self::binding("option+space", BindingMatchMode::Symbol);
+        assert_eq!(resolve_with(&space, Some(&layout)), "option+space");
+    }
+}
@@ -5,18 +5,37 @@
 
 mod handler;
 pub mod handy_keys;
+pub mod key_suppression;
+pub mod layout;
+pub mod test_mode;
 
 use log::{error, info, warn};
+use reqwest::header::{HeaderName, HeaderValue};
 use serde::Serialize;
 use specta::Type;
-use tauri::{AppHandle, Emitter, Manager};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
 use tauri_plugin_autostart::ManagerExt;
 
+use crate::chunking::MIN_CHUNK_SECONDS;
+use crate::custom_words;
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent};
+use crate::managers::audio::{current_device_environment, AudioRecordingManager};
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::MAX_TRANSCRIPTION_RETRIES;
+use crate::session_log::SessionLog;
 use crate::settings::{
-    self, get_settings, ClipboardHandling, LLMPrompt, OverlayPosition, PasteMethod,
-    ShortcutBinding, SoundTheme, APPLE_INTELLIGENCE_PROVIDER_ID,
+    self, get_settings, AppPromptRule, AuthStyle, BindingMatchMode, ClipboardHandling,
+    DecodingParams, FeedbackMode, JsonOutput, LLMPrompt, OutputTransform, OverlayPosition,
+    PasteMethod, PostProcessProvider, ResponseFormat, ReviewTimeoutAction, ShortcutBinding,
+    SoundTheme, TranslationGuard, TruncationStrategy, WatchFolderConfig, WatchFolderOutput,
+    APPLE_INTELLIGENCE_PROVIDER_ID,
 };
+use crate::transcript_ring::TranscriptRing;
 use crate::tray;
+use crate::watch_folders;
 
 // Note: Commands are accessed via shortcut::handy_keys:: in lib.rs
 
@@ -64,7 +83,7 @@ pub fn change_binding(
     app: AppHandle,
     id: String,
     binding: String,
-) -> Result<BindingResponse, String> {
+) -> Result<BindingResponse, HandyError> {
     let mut settings = settings::get_settings(&app);
 
     // Get the binding to modify
@@ -102,10 +121,22 @@ pub fn change_binding(
         error!("change_binding error: {}", error_msg);
     }
 
-    // Validate the new shortcut
+    // Validate the new shortcut. Optional bindings (no default) may be cleared.
+    let unbinding = binding.is_empty() && binding_to_modify.default_binding.is_empty();
+    if unbinding {
+        let mut updated_binding = binding_to_modify;
+        updated_binding.current_binding = binding;
+        settings.bindings.insert(id, updated_binding.clone());
+        settings::write_settings(&app, settings);
+        return Ok(BindingResponse {
+            success: true,
+            binding: Some(updated_binding),
+            error: None,
+        });
+    }
     if let Err(e) = handy_keys::validate_shortcut(&binding) {
         warn!("change_binding validation error: {}", e);
-        return Err(e);
+        return Err(HandyError::invalid_input(e));
     }
 
     // Create an updated binding
@@ -139,7 +170,7 @@ pub fn change_binding(
 
 #[tauri::command]
 #[specta::specta]
-pub fn reset_binding(app: AppHandle, id: String) -> Result<BindingResponse, String> {
+pub fn reset_binding(app: AppHandle, id: String) -> Result<BindingResponse, HandyError> {
     let binding = settings::get_stored_binding(&app, &id);
     change_binding(app, id, binding.default_binding)
 }
@@ -148,11 +179,11 @@ pub fn reset_binding(app: AppHandle, id: String) -> Result<BindingResponse, Stri
 /// This avoids firing the action while keys are being recorded.
 #[tauri::command]
 #[specta::specta]
-pub fn suspend_binding(app: AppHandle, id: String) -> Result<(), String> {
+pub fn suspend_binding(app: AppHandle, id: String) -> Result<(), HandyError> {
     if let Some(b) = settings::get_bindings(&app).get(&id).cloned() {
         if let Err(e) = unregister_shortcut(&app, b) {
             error!("suspend_binding error for id '{}': {}", id, e);
-            return Err(e);
+            return Err(e.into());
         }
     }
     Ok(())
@@ -161,11 +192,11 @@ pub fn suspend_binding(app: AppHandle, id: String) -> Result<(), String> {
 /// Re-register the binding after the user has finished editing.
 #[tauri::command]
 #[specta::specta]
-pub fn resume_binding(app: AppHandle, id: String) -> Result<(), String> {
+pub fn resume_binding(app: AppHandle, id: String) -> Result<(), HandyError> {
     if let Some(b) = settings::get_bindings(&app).get(&id).cloned() {
         if let Err(e) = register_shortcut(&app, b) {
             error!("resume_binding error for id '{}': {}", id, e);
-            return Err(e);
+            return Err(e.into());
         }
     }
     Ok(())
@@ -190,7 +221,7 @@ pub struct ImplementationChangeResult {
 pub fn change_keyboard_implementation_setting(
     app: AppHandle,
     implementation: String,
-) -> Result<ImplementationChangeResult, String> {
+) -> Result<ImplementationChangeResult, HandyError> {
     if implementation == "handy_keys" {
         // Ensure settings reflect this, in case it was somehow different
         // But since we removed the field from settings struct, this is essentially a no-op
@@ -205,7 +236,9 @@ pub fn change_keyboard_implementation_setting(
             "Attempted to switch to unsupported keyboard implementation: {}",
             implementation
         );
-        Err("Only handy_keys implementation is supported".to_string())
+        Err(HandyError::invalid_input(
+            "Only handy_keys implementation is supported".to_string(),
+        ))
     }
 }
 
@@ -222,16 +255,34 @@ pub fn get_keyboard_implementation(_app: AppHandle) -> String {
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_ptt_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_ptt_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.push_to_talk = enabled;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
+/// Swallow other key presses while a push-to-talk binding is held (macOS and Windows)
+#[tauri::command]
+#[specta::specta]
+pub fn change_suppress_keys_during_ptt_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    if enabled && !key_suppression::is_supported() {
+        return Err(HandyError::invalid_input(
+            "Suppressing keys during push-to-talk is not supported on this platform".to_string(),
+        ));
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.suppress_keys_during_ptt = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
-pub fn change_audio_feedback_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_audio_feedback_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.audio_feedback = enabled;
     settings::write_settings(&app, settings);
@@ -240,16 +291,77 @@ pub fn change_audio_feedback_setting(app: AppHandle, enabled: bool) -> Result<()
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_audio_feedback_volume_setting(app: AppHandle, volume: f32) -> Result<(), String> {
+pub fn change_audio_feedback_volume_setting(app: AppHandle, volume: f32) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.audio_feedback_volume = volume;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
+/// Sets the silence that ends a dictation session segment, clamped to 300ms ..= 10s
+#[tauri::command]
+#[specta::specta]
+pub fn change_dictation_session_pause_setting(
+    app: AppHandle,
+    pause_ms: u32,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.dictation_session_pause_ms = pause_ms.clamp(300, 10_000);
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_overlap_transcription_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.overlap_transcription = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_voice_stop_setting(
+    app: AppHandle,
+    enabled: bool,
+    phrase: Option<String>,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    if let Some(phrase) = phrase {
+        let phrase = phrase.trim();
+        // Single words come up in dictation too often to stop on
+        if !crate::voice_stop::is_valid_phrase(phrase) {
+            return Err(HandyError::invalid_input(format!(
+                "The stop phrase needs at least {} words",
+                crate::voice_stop::MIN_PHRASE_WORDS
+            )));
+        }
+        settings.voice_stop_phrase = phrase.to_string();
+    }
+    settings.voice_stop_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_feedback_timing_setting(
+    app: AppHandle,
+    timing: settings::FeedbackTiming,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.feedback_timing = timing;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
-pub fn change_sound_theme_setting(app: AppHandle, theme: String) -> Result<(), String> {
+pub fn change_sound_theme_setting(app: AppHandle, theme: String) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     let parsed = match theme.as_str() {
         "marimba" => SoundTheme::Marimba,
@@ -267,7 +379,10 @@ pub fn change_sound_theme_setting(app: AppHandle, theme: String) -> Result<(), S
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_translate_to_english_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_translate_to_english_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.translate_to_english = enabled;
     settings::write_settings(&app, settings);
@@ -276,7 +391,28 @@ pub fn change_translate_to_english_setting(app: AppHandle, enabled: bool) -> Res
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_selected_language_setting(app: AppHandle, language: String) -> Result<(), String> {
+pub fn change_translation_guard_setting(app: AppHandle, guard: String) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let parsed = match guard.as_str() {
+        "off" => TranslationGuard::Off,
+        "flag" => TranslationGuard::Flag,
+        "retry" => TranslationGuard::Retry,
+        other => {
+            warn!("Invalid translation guard '{}', defaulting to off", other);
+            TranslationGuard::Off
+        }
+    };
+    settings.translation_guard = parsed;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_selected_language_setting(
+    app: AppHandle,
+    language: String,
+) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.selected_language = language;
     settings::write_settings(&app, settings);
@@ -285,7 +421,19 @@ pub fn change_selected_language_setting(app: AppHandle, language: String) -> Res
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_overlay_position_setting(app: AppHandle, position: String) -> Result<(), String> {
+pub fn change_localize_punctuation_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.localize_punctuation = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_overlay_position_setting(app: AppHandle, position: String) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     let parsed = match position.as_str() {
         "none" => OverlayPosition::None,
@@ -307,45 +455,45 @@ pub fn change_overlay_position_setting(app: AppHandle, position: String) -> Resu
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_debug_mode_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_overlay_interactive_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.overlay_interactive = enabled;
+    settings::write_settings(&app, settings);
+
+    crate::utils::update_overlay_interactivity(&app);
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_debug_mode_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.debug_mode = enabled;
     settings::write_settings(&app, settings);
 
     // Emit event to notify frontend of debug mode change
-    let _ = app.emit(
-        "settings-changed",
-        serde_json::json!({
-            "setting": "debug_mode",
-            "value": enabled
-        }),
-    );
+    emit_event(&app, AppEvent::setting_changed("debug_mode", enabled));
 
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_start_hidden_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_start_hidden_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.start_hidden = enabled;
     settings::write_settings(&app, settings);
 
     // Notify frontend
-    let _ = app.emit(
-        "settings-changed",
-        serde_json::json!({
-            "setting": "start_hidden",
-            "value": enabled
-        }),
-    );
+    emit_event(&app, AppEvent::setting_changed("start_hidden", enabled));
 
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_autostart_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_autostart_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.autostart_enabled = enabled;
     settings::write_settings(&app, settings);
@@ -359,12 +507,9 @@ pub fn change_autostart_setting(app: AppHandle, enabled: bool) -> Result<(), Str
     }
 
     // Notify frontend
-    let _ = app.emit(
-        "settings-changed",
-        serde_json::json!({
-            "setting": "autostart_enabled",
-            "value": enabled
-        }),
+    emit_event(
+        &app,
+        AppEvent::setting_changed("autostart_enabled", enabled),
     );
 
     Ok(())
@@ -372,17 +517,14 @@ pub fn change_autostart_setting(app: AppHandle, enabled: bool) -> Result<(), Str
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_update_checks_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_update_checks_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.update_checks_enabled = enabled;
     settings::write_settings(&app, settings);
 
-    let _ = app.emit(
-        "settings-changed",
-        serde_json::json!({
-            "setting": "update_checks_enabled",
-            "value": enabled
-        }),
+    emit_event(
+        &app,
+        AppEvent::setting_changed("update_checks_enabled", enabled),
     );
 
     Ok(())
@@ -390,19 +532,52 @@ pub fn change_update_checks_setting(app: AppHandle, enabled: bool) -> Result<(),
 
 #[tauri::command]
 #[specta::specta]
-pub fn update_custom_words(app: AppHandle, words: Vec<String>) -> Result<(), String> {
+pub fn change_watch_settings_file_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.watch_settings_file = enabled;
+    settings::write_settings(&app, settings);
+    crate::settings_watcher::restart(&app);
+
+    emit_event(
+        &app,
+        AppEvent::setting_changed("watch_settings_file", enabled),
+    );
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_custom_words(app: AppHandle, words: Vec<String>) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.custom_words = words;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
+/// Replaces the custom words used only for `language`, an empty list removes them
+#[tauri::command]
+#[specta::specta]
+pub fn update_language_custom_words(
+    app: AppHandle,
+    language: String,
+    words: Vec<String>,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    *custom_words::list_mut(&mut settings, Some(&language))? = words;
+    settings
+        .custom_words_by_language
+        .retain(|_, words| !words.is_empty());
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_word_correction_threshold_setting(
     app: AppHandle,
     threshold: f64,
-) -> Result<(), String> {
+) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.word_correction_threshold = threshold;
     settings::write_settings(&app, settings);
@@ -411,217 +586,1035 @@ pub fn change_word_correction_threshold_setting(
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_paste_method_setting(app: AppHandle, method: String) -> Result<(), String> {
+pub fn change_correction_algorithm_setting(
+    app: AppHandle,
+    algorithm: settings::CorrectionAlgorithm,
+) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
-    let parsed = match method.as_str() {
-        "ctrl_v" => PasteMethod::CtrlV,
-        "direct" => PasteMethod::Direct,
-        "none" => PasteMethod::None,
-        "shift_insert" => PasteMethod::ShiftInsert,
-        "ctrl_shift_v" => PasteMethod::CtrlShiftV,
-        other => {
-            warn!("Invalid paste method '{}', defaulting to ctrl_v", other);
-            PasteMethod::CtrlV
-        }
-    };
-    settings.paste_method = parsed;
+    settings.correction_algorithm = algorithm;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
+/// Sets the Whisper beam size, clamped to 1 (greedy) ..= `MAX_BEAM_SIZE`
 #[tauri::command]
 #[specta::specta]
-pub fn change_clipboard_handling_setting(app: AppHandle, handling: String) -> Result<(), String> {
+pub fn change_beam_size_setting(app: AppHandle, beam_size: u32) -> Result<(), HandyError> {
+    let clamped = beam_size.clamp(1, settings::MAX_BEAM_SIZE);
+    if clamped != beam_size {
+        warn!("Beam size {} is out of range, using {}", beam_size, clamped);
+    }
     let mut settings = settings::get_settings(&app);
-    let parsed = match handling.as_str() {
-        "dont_modify" => ClipboardHandling::DontModify,
-        "copy_to_clipboard" => ClipboardHandling::CopyToClipboard,
-        other => {
-            warn!(
-                "Invalid clipboard handling '{}', defaulting to dont_modify",
-                other
-            );
-            ClipboardHandling::DontModify
-        }
-    };
-    settings.clipboard_handling = parsed;
+    settings.advanced_decoding.beam_size = clamped;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
+/// Sets the advanced Whisper decoding parameters, clamping out-of-range values
 #[tauri::command]
 #[specta::specta]
-pub fn change_post_process_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_decoding_params(app: AppHandle, params: DecodingParams) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
-    settings.post_process_enabled = enabled;
+    settings.advanced_decoding = params.clamped();
     settings::write_settings(&app, settings);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_experimental_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn reset_decoding_params(app: AppHandle) -> Result<DecodingParams, HandyError> {
     let mut settings = settings::get_settings(&app);
-    settings.experimental_enabled = enabled;
+    settings.advanced_decoding = DecodingParams::default();
     settings::write_settings(&app, settings);
-    Ok(())
+    Ok(DecodingParams::default())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_post_process_base_url_setting(
-    app: AppHandle,
-    provider_id: String,
-    base_url: String,
-) -> Result<(), String> {
+pub fn change_anti_hallucination_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
-    let label = settings
-        .post_process_provider(&provider_id)
-        .map(|provider| provider.label.clone())
-        .ok_or_else(|| format!("Provider '{}' not found", provider_id))?;
-
-    let provider = settings
-        .post_process_provider_mut(&provider_id)
-        .expect("Provider looked up above must exist");
+    settings.anti_hallucination = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
 
-    if !provider.allow_base_url_edit {
-        return Err(format!(
-            "Provider '{}' does not allow editing the base URL",
-            label
+#[tauri::command]
+#[specta::specta]
+pub fn change_hallucination_max_repeats_setting(
+    app: AppHandle,
+    max_repeats: u32,
+) -> Result<(), HandyError> {
+    if max_repeats == 0 {
+        return Err(HandyError::invalid_input(
+            "At least one copy of a phrase must be kept".to_string(),
         ));
     }
-
-    provider.base_url = base_url;
+    let mut settings = settings::get_settings(&app);
+    settings.hallucination_max_repeats = max_repeats;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
-/// Generic helper to validate provider exists
-fn validate_provider_exists(
-    settings: &settings::AppSettings,
-    provider_id: &str,
-) -> Result<(), String> {
-    if !settings
-        .post_process_providers
-        .iter()
-        .any(|provider| provider.id == provider_id)
-    {
-        return Err(format!("Provider '{}' not found", provider_id));
+#[tauri::command]
+#[specta::specta]
+pub fn change_chunk_seconds_setting(app: AppHandle, chunk_seconds: u32) -> Result<(), HandyError> {
+    if chunk_seconds != 0 && chunk_seconds < MIN_CHUNK_SECONDS {
+        return Err(HandyError::invalid_input(format!(
+            "Chunks must be at least {} seconds long",
+            MIN_CHUNK_SECONDS
+        )));
     }
+    let mut settings = settings::get_settings(&app);
+    settings.chunk_seconds = chunk_seconds;
+    settings::write_settings(&app, settings);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_post_process_api_key_setting(
+pub fn change_transcription_retry_count_setting(
     app: AppHandle,
-    provider_id: String,
-    api_key: String,
-) -> Result<(), String> {
+    retry_count: u32,
+) -> Result<(), HandyError> {
+    if retry_count > MAX_TRANSCRIPTION_RETRIES {
+        return Err(HandyError::invalid_input(format!(
+            "At most {} retries are allowed",
+            MAX_TRANSCRIPTION_RETRIES
+        )));
+    }
     let mut settings = settings::get_settings(&app);
-    validate_provider_exists(&settings, &provider_id)?;
-    settings.post_process_api_keys.insert(provider_id, api_key);
+    settings.transcription_retry_count = retry_count;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_post_process_model_setting(
+pub fn change_speaker_change_hints_setting(
     app: AppHandle,
-    provider_id: String,
-    model: String,
-) -> Result<(), String> {
+    enabled: bool,
+    marker: String,
+) -> Result<(), HandyError> {
+    if marker.is_empty() {
+        return Err(HandyError::invalid_input(
+            "The speaker change marker can't be empty".to_string(),
+        ));
+    }
     let mut settings = settings::get_settings(&app);
-    validate_provider_exists(&settings, &provider_id)?;
+    settings.speaker_change_hints = enabled;
+    settings.speaker_change_marker = marker;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
 
-    settings
-        .post_process_models
-        .insert(provider_id.clone(), model.clone());
+#[tauri::command]
+#[specta::specta]
+pub fn change_discard_blank_transcriptions_setting(
+    app: AppHandle,
+    enabled: bool,
+    keep_in_history: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.discard_blank_transcriptions = enabled;
+    settings.keep_discarded_in_history = keep_in_history;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
 
-    // Save as a custom model option if not empty
-    let trimmed_model = model.trim();
-    if !trimmed_model.is_empty() {
-        let entry = settings
-            .post_process_custom_models
-            .entry(provider_id.clone())
-            .or_default();
+#[tauri::command]
+#[specta::specta]
+pub fn update_discarded_phrases(app: AppHandle, phrases: Vec<String>) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.discarded_phrases = phrases;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
 
-        if !entry.contains(&trimmed_model.to_string()) {
-            entry.push(trimmed_model.to_string());
+#[tauri::command]
+#[specta::specta]
+pub fn change_paste_method_setting(app: AppHandle, method: String) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let parsed = match method.as_str() {
+        "ctrl_v" => PasteMethod::CtrlV,
+        "direct" => PasteMethod::Direct,
+        "none" => PasteMethod::None,
+        "shift_insert" => PasteMethod::ShiftInsert,
+        "ctrl_shift_v" => PasteMethod::CtrlShiftV,
+        "accessibility" => PasteMethod::Accessibility,
+        other => {
+            warn!("Invalid paste method '{}', defaulting to ctrl_v", other);
+            PasteMethod::CtrlV
         }
-    }
-
+    };
+    settings.paste_method = parsed;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_post_process_provider(app: AppHandle, provider_id: String) -> Result<(), String> {
+pub fn change_max_transcript_chars_setting(
+    app: AppHandle,
+    max_chars: Option<usize>,
+) -> Result<(), HandyError> {
+    if max_chars == Some(0) {
+        return Err(HandyError::invalid_input(
+            "Maximum transcript length must be greater than zero".to_string(),
+        ));
+    }
     let mut settings = settings::get_settings(&app);
-    validate_provider_exists(&settings, &provider_id)?;
-    settings.post_process_provider_id = provider_id;
+    settings.max_transcript_chars = max_chars;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn add_post_process_prompt(
+pub fn change_truncation_strategy_setting(
     app: AppHandle,
-    name: String,
-    prompt: String,
-) -> Result<LLMPrompt, String> {
+    strategy: String,
+) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
+    let parsed = match strategy.as_str() {
+        "none" => TruncationStrategy::None,
+        "truncate" => TruncationStrategy::Truncate,
+        "ellipsis" => TruncationStrategy::Ellipsis,
+        other => {
+            warn!(
+                "Invalid truncation strategy '{}', defaulting to none",
+                other
+            );
+            TruncationStrategy::None
+        }
+    };
+    settings.truncation_strategy = parsed;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
 
-    // Generate unique ID using timestamp and random component
-    let id = format!("prompt_{}", chrono::Utc::now().timestamp_millis());
+#[tauri::command]
+#[specta::specta]
+pub fn set_app_output_transform(
+    app: AppHandle,
+    app_id: String,
+    transform: String,
+) -> Result<(), HandyError> {
+    let app_id = app_id.trim().to_string();
+    if app_id.is_empty() {
+        return Err(HandyError::invalid_input(
+            "Application identifier cannot be empty".to_string(),
+        ));
+    }
 
-    let new_prompt = LLMPrompt {
-        id: id.clone(),
-        name,
-        prompt,
+    let parsed = match transform.as_str() {
+        "as_is" => OutputTransform::AsIs,
+        "snake_case" => OutputTransform::SnakeCase,
+        "kebab_case" => OutputTransform::KebabCase,
+        "lowercase" => OutputTransform::Lowercase,
+        other => {
+            return Err(HandyError::invalid_input(format!(
+                "Invalid output transform '{}'",
+                other
+            )))
+        }
     };
 
-    settings.post_process_prompts.push(new_prompt.clone());
+    let mut settings = settings::get_settings(&app);
+    // Entries match apps ignoring case, so replace any spelling of this one
+    settings
+        .app_output_transforms
+        .retain(|key, _| !key.eq_ignore_ascii_case(&app_id));
+    if parsed != OutputTransform::AsIs {
+        settings.app_output_transforms.insert(app_id, parsed);
+    }
     settings::write_settings(&app, settings);
-
-    Ok(new_prompt)
+    Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn update_post_process_prompt(
+pub fn change_clipboard_handling_setting(
+    app: AppHandle,
+    handling: String,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let parsed = match handling.as_str() {
+        "dont_modify" => ClipboardHandling::DontModify,
+        "copy_to_clipboard" => ClipboardHandling::CopyToClipboard,
+        other => {
+            warn!(
+                "Invalid clipboard handling '{}', defaulting to dont_modify",
+                other
+            );
+            ClipboardHandling::DontModify
+        }
+    };
+    settings.clipboard_handling = parsed;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_enabled_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.post_process_enabled = enabled;
+    settings::write_settings(&app, settings);
+    tray::refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// Sets the `User-Agent` sent with post-processing requests
+#[tauri::command]
+#[specta::specta]
+pub fn change_llm_user_agent_setting(
+    app: AppHandle,
+    user_agent: settings::LlmUserAgent,
+) -> Result<(), HandyError> {
+    let user_agent = match user_agent {
+        settings::LlmUserAgent::Custom(value) => {
+            let value = value.trim().to_string();
+            if value.is_empty() {
+                return Err(HandyError::invalid_input(
+                    "The user agent can't be empty, omit it instead".to_string(),
+                ));
+            }
+            if HeaderValue::from_str(&value).is_err() {
+                return Err(HandyError::invalid_input(
+                    "The user agent contains characters not allowed in a header".to_string(),
+                ));
+            }
+            settings::LlmUserAgent::Custom(value)
+        }
+        other => other,
+    };
+
+    let mut settings = settings::get_settings(&app);
+    settings.llm_user_agent = user_agent.clone();
+    crate::llm_client::set_user_agent(user_agent);
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Restricts post-processing to providers running on this machine
+#[tauri::command]
+#[specta::specta]
+pub fn change_offline_mode_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.offline_mode = enabled;
+    crate::llm_client::set_offline_mode(enabled);
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_experimental_enabled_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.experimental_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Sets where newer model catalogs are fetched from, None to only use the one
+/// that ships with the app
+#[tauri::command]
+#[specta::specta]
+pub fn change_model_manifest_url_setting(
+    app: AppHandle,
+    url: Option<String>,
+) -> Result<(), HandyError> {
+    let url = url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty());
+    if let Some(url) = &url {
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| HandyError::invalid_input(format!("Invalid URL '{}': {}", url, e)))?;
+        if parsed.scheme() != "https" && parsed.scheme() != "http" {
+            return Err(HandyError::invalid_input(format!(
+                "The model manifest URL must use http or https, not {}",
+                parsed.scheme()
+            )));
+        }
+    }
+
+    let mut settings = settings::get_settings(&app);
+    settings.model_manifest_url = url;
+    let enabled = settings.model_manifest_url.is_some();
+    settings::write_settings(&app, settings);
+    if enabled {
+        crate::model_manifest::refresh_in_background(&app, true);
+    } else {
+        crate::model_manifest::clear_cache(&app);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_base_url_setting(
+    app: AppHandle,
+    provider_id: String,
+    base_url: String,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let provider = settings
+        .post_process_provider(&provider_id)
+        .ok_or_else(|| HandyError::not_found(format!("Provider '{}' not found", provider_id)))?;
+
+    if !provider.allow_base_url_edit {
+        return Err(HandyError::invalid_input(format!(
+            "Provider '{}' does not allow editing the base URL",
+            provider.label
+        )));
+    }
+
+    if settings.offline_mode && !settings::is_local_base_url(&base_url) {
+        return Err(HandyError::invalid_input(
+            "Offline mode is on, the base URL must point at this machine".to_string(),
+        ));
+    }
+
+    let provider = settings
+        .post_process_provider_mut(&provider_id)
+        .expect("Provider looked up above must exist");
+    provider.base_url = base_url;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Look up a provider whose request settings the user may customize
+fn editable_provider_mut<'a>(
+    settings: &'a mut settings::AppSettings,
+    provider_id: &str,
+) -> Result<&'a mut PostProcessProvider, HandyError> {
+    let provider = settings
+        .post_process_provider_mut(provider_id)
+        .ok_or_else(|| HandyError::not_found(format!("Provider '{}' not found", provider_id)))?;
+
+    if !provider.allow_base_url_edit {
+        return Err(HandyError::invalid_input(format!(
+            "Provider '{}' does not allow custom request settings",
+            provider.label
+        )));
+    }
+    Ok(provider)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_auth_style_setting(
+    app: AppHandle,
+    provider_id: String,
+    auth_style: String,
+) -> Result<(), HandyError> {
+    let parsed = match auth_style.as_str() {
+        "bearer" => AuthStyle::Bearer,
+        "api_key_header" => AuthStyle::ApiKeyHeader,
+        "query_param" => AuthStyle::QueryParam,
+        "none" => AuthStyle::None,
+        other => {
+            return Err(HandyError::invalid_input(format!(
+                "Invalid auth style '{}'",
+                other
+            )))
+        }
+    };
+
+    let mut settings = settings::get_settings(&app);
+    editable_provider_mut(&mut settings, &provider_id)?.auth_style = parsed;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_extra_headers_setting(
+    app: AppHandle,
+    provider_id: String,
+    headers: HashMap<String, String>,
+) -> Result<(), HandyError> {
+    // Reject headers reqwest would refuse so the error shows up when saving, not when sending
+    for (name, value) in &headers {
+        HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| HandyError::invalid_input(format!("Invalid header name '{}'", name)))?;
+        HeaderValue::from_str(value).map_err(|_| {
+            HandyError::invalid_input(format!("Invalid value for header '{}'", name))
+        })?;
+    }
+
+    let mut settings = settings::get_settings(&app);
+    editable_provider_mut(&mut settings, &provider_id)?.extra_headers = headers;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_extra_query_params_setting(
+    app: AppHandle,
+    provider_id: String,
+    params: HashMap<String, String>,
+) -> Result<(), HandyError> {
+    if params.keys().any(|key| key.trim().is_empty()) {
+        return Err(HandyError::invalid_input(
+            "Query parameter names cannot be empty".to_string(),
+        ));
+    }
+
+    let mut settings = settings::get_settings(&app);
+    editable_provider_mut(&mut settings, &provider_id)?.extra_query_params = params;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_openai_scope_setting(
+    app: AppHandle,
+    provider_id: String,
+    organization_id: Option<String>,
+    project_id: Option<String>,
+) -> Result<(), HandyError> {
+    let normalize = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let mut settings = settings::get_settings(&app);
+    let provider = settings
+        .post_process_provider_mut(&provider_id)
+        .ok_or_else(|| HandyError::not_found(format!("Provider '{}' not found", provider_id)))?;
+    provider.organization_id = normalize(organization_id);
+    provider.project_id = normalize(project_id);
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_chat_path_setting(
+    app: AppHandle,
+    provider_id: String,
+    path: String,
+) -> Result<(), HandyError> {
+    let path = path.trim();
+    if path.is_empty() || path.contains('?') || path.contains("://") {
+        return Err(HandyError::invalid_input(format!(
+            "Invalid chat completions path: '{}'",
+            path
+        )));
+    }
+
+    let mut settings = settings::get_settings(&app);
+    editable_provider_mut(&mut settings, &provider_id)?.chat_completions_path =
+        format!("/{}", path.trim_start_matches('/'));
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_api_version_setting(
+    app: AppHandle,
+    provider_id: String,
+    api_version: Option<String>,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    editable_provider_mut(&mut settings, &provider_id)?.api_version = api_version
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Rejects providers that offline mode rules out
+fn ensure_usable_provider(
+    settings: &settings::AppSettings,
+    provider_id: &str,
+) -> Result<(), HandyError> {
+    if !settings
+        .usable_post_process_providers()
+        .any(|provider| provider.id == provider_id)
+    {
+        return Err(HandyError::config(format!(
+            "Offline mode is on, provider '{}' is not running on this machine",
+            provider_id
+        )));
+    }
+    Ok(())
+}
+
+/// Generic helper to validate provider exists
+fn validate_provider_exists(
+    settings: &settings::AppSettings,
+    provider_id: &str,
+) -> Result<(), HandyError> {
+    if !settings
+        .post_process_providers
+        .iter()
+        .any(|provider| provider.id == provider_id)
+    {
+        return Err(HandyError::not_found(format!(
+            "Provider '{}' not found",
+            provider_id
+        )));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_api_key_setting(
+    app: AppHandle,
+    provider_id: String,
+    api_key: String,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    validate_provider_exists(&settings, &provider_id)?;
+    settings.post_process_api_keys.insert(provider_id, api_key);
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_rate_limit_setting(
+    app: AppHandle,
+    provider_id: String,
+    max_concurrent_requests: u32,
+    requests_per_minute: u32,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let provider = settings
+        .post_process_provider_mut(&provider_id)
+        .ok_or_else(|| HandyError::not_found(format!("Provider '{}' not found", provider_id)))?;
+    provider.max_concurrent_requests = max_concurrent_requests;
+    provider.requests_per_minute = requests_per_minute;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_model_setting(
+    app: AppHandle,
+    provider_id: String,
+    model: String,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    validate_provider_exists(&settings, &provider_id)?;
+
+    settings
+        .post_process_models
+        .insert(provider_id.clone(), model.clone());
+
+    // Save as a custom model option if not empty
+    let trimmed_model = model.trim();
+    if !trimmed_model.is_empty() {
+        let entry = settings
+            .post_process_custom_models
+            .entry(provider_id.clone())
+            .or_default();
+
+        if !entry.contains(&trimmed_model.to_string()) {
+            entry.push(trimmed_model.to_string());
+        }
+    }
+
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_post_process_provider(app: AppHandle, provider_id: String) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    validate_provider_exists(&settings, &provider_id)?;
+    ensure_usable_provider(&settings, &provider_id)?;
+    settings.post_process_provider_id = provider_id;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn add_post_process_prompt(
+    app: AppHandle,
+    name: String,
+    prompt: String,
+) -> Result<LLMPrompt, HandyError> {
+    let mut settings = settings::get_settings(&app);
+
+    // Generate unique ID using timestamp and random component
+    let id = format!("prompt_{}", chrono::Utc::now().timestamp_millis());
+
+    let new_prompt = LLMPrompt {
+        id: id.clone(),
+        name,
+        prompt,
+        response_format: None,
+        json_output: JsonOutput::default(),
+        short_label: None,
+        seed: None,
+    };
+
+    settings.post_process_prompts.push(new_prompt.clone());
+    settings::write_settings(&app, settings);
+    tray::refresh_tray_menu(&app);
+
+    Ok(new_prompt)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_post_process_prompt(
+    app: AppHandle,
+    id: String,
+    name: String,
+    prompt: String,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+
+    if let Some(existing_prompt) = settings
+        .post_process_prompts
+        .iter_mut()
+        .find(|p| p.id == id)
+    {
+        existing_prompt.name = name;
+        existing_prompt.prompt = prompt;
+        settings::write_settings(&app, settings);
+        tray::refresh_tray_menu(&app);
+        Ok(())
+    } else {
+        Err(HandyError::not_found(format!(
+            "Prompt with id '{}' not found",
+            id
+        )))
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_prompt_output_setting(
+    app: AppHandle,
+    id: String,
+    response_format: Option<String>,
+    json_output: String,
+    json_field: Option<String>,
+) -> Result<(), HandyError> {
+    let response_format = match response_format.as_deref() {
+        None | Some("text") => None,
+        Some("json_object") => Some(ResponseFormat::JsonObject),
+        Some(other) => {
+            return Err(HandyError::invalid_input(format!(
+                "Invalid response format: {}",
+                other
+            )))
+        }
+    };
+    let json_output = match json_output.as_str() {
+        "raw" => JsonOutput::Raw,
+        "pretty" => JsonOutput::Pretty,
+        "field" => {
+            let field = json_field
+                .map(|f| f.trim().to_string())
+                .filter(|f| !f.is_empty())
+                .ok_or_else(|| {
+                    HandyError::invalid_input(
+                        "A field name is required to extract a field".to_string(),
+                    )
+                })?;
+            JsonOutput::Field(field)
+        }
+        other => {
+            return Err(HandyError::invalid_input(format!(
+                "Invalid JSON output mode: {}",
+                other
+            )))
+        }
+    };
+
+    let mut settings = settings::get_settings(&app);
+    let prompt = settings
+        .post_process_prompts
+        .iter_mut()
+        .find(|p| p.id == id)
+        .ok_or_else(|| HandyError::not_found(format!("Prompt with id '{}' not found", id)))?;
+    prompt.response_format = response_format;
+    prompt.json_output = json_output;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Checks an app prompt rule's matcher and prompt before it is stored
+fn validate_app_prompt_rule(
+    settings: &settings::AppSettings,
+    app_matcher: &str,
+    prompt_id: &str,
+) -> Result<(), HandyError> {
+    if app_matcher.trim().is_empty() {
+        return Err(HandyError::invalid_input(
+            "An application is required".to_string(),
+        ));
+    }
+    if !settings
+        .post_process_prompts
+        .iter()
+        .any(|p| p.id == prompt_id)
+    {
+        return Err(HandyError::not_found(format!(
+            "Prompt with id '{}' not found",
+            prompt_id
+        )));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn add_app_prompt_rule(
+    app: AppHandle,
+    app_matcher: String,
+    prompt_id: String,
+    post_process: Option<bool>,
+    collapse_to_single_line: Option<bool>,
+) -> Result<AppPromptRule, HandyError> {
+    let mut settings = settings::get_settings(&app);
+    validate_app_prompt_rule(&settings, &app_matcher, &prompt_id)?;
+
+    let rule = AppPromptRule {
+        id: format!("rule_{}", chrono::Utc::now().timestamp_millis()),
+        app_matcher: app_matcher.trim().to_string(),
+        prompt_id,
+        post_process,
+        collapse_to_single_line,
+    };
+    settings.app_prompt_rules.push(rule.clone());
+    settings::write_settings(&app, settings);
+    Ok(rule)
+}
+
+/// Adds a rule for the application that currently has focus
+#[tauri::command]
+#[specta::specta]
+pub fn add_app_prompt_rule_for_current_app(
+    app: AppHandle,
+    prompt_id: String,
+    post_process: Option<bool>,
+    collapse_to_single_line: Option<bool>,
+) -> Result<AppPromptRule, HandyError> {
+    let current = crate::utils::frontmost_application().ok_or_else(|| {
+        HandyError::not_found("The focused application is not available".to_string())
+    })?;
+    // Identifiers are stable across app renames and localizations
+    let app_matcher = current.identifier.unwrap_or(current.name);
+    add_app_prompt_rule(
+        app,
+        app_matcher,
+        prompt_id,
+        post_process,
+        collapse_to_single_line,
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_app_prompt_rule(
+    app: AppHandle,
+    id: String,
+    app_matcher: String,
+    prompt_id: String,
+    post_process: Option<bool>,
+    collapse_to_single_line: Option<bool>,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    validate_app_prompt_rule(&settings, &app_matcher, &prompt_id)?;
+
+    let rule = settings
+        .app_prompt_rules
+        .iter_mut()
+        .find(|r| r.id == id)
+        .ok_or_else(|| {
+            HandyError::not_found(format!("App prompt rule with id '{}' not found", id))
+        })?;
+    rule.app_matcher = app_matcher.trim().to_string();
+    rule.prompt_id = prompt_id;
+    rule.post_process = post_process;
+    rule.collapse_to_single_line = collapse_to_single_line;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_app_prompt_rule(app: AppHandle, id: String) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let before = settings.app_prompt_rules.len();
+    settings.app_prompt_rules.retain(|r| r.id != id);
+    if settings.app_prompt_rules.len() == before {
+        return Err(HandyError::not_found(format!(
+            "App prompt rule with id '{}' not found",
+            id
+        )));
+    }
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Reorders the rules, which are evaluated first to last. `ids` must list every rule once.
+#[tauri::command]
+#[specta::specta]
+pub fn reorder_app_prompt_rules(app: AppHandle, ids: Vec<String>) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let mut remaining = std::mem::take(&mut settings.app_prompt_rules);
+    if ids.len() != remaining.len() {
+        return Err(HandyError::invalid_input(
+            "The new order must include every app prompt rule".to_string(),
+        ));
+    }
+
+    let mut reordered = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let index = remaining.iter().position(|r| &r.id == id).ok_or_else(|| {
+            HandyError::not_found(format!("App prompt rule with id '{}' not found", id))
+        })?;
+        reordered.push(remaining.swap_remove(index));
+    }
+    settings.app_prompt_rules = reordered;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Checks a watch folder's directory and output before it is stored
+fn validate_watch_folder(
+    path: &str,
+    output: WatchFolderOutput,
+    journal_path: Option<&str>,
+) -> Result<(), HandyError> {
+    if !std::path::Path::new(path).is_dir() {
+        return Err(HandyError::not_found(format!(
+            "Folder '{}' doesn't exist",
+            path
+        )));
+    }
+    if output == WatchFolderOutput::Journal && journal_path.is_none_or(|p| p.trim().is_empty()) {
+        return Err(HandyError::invalid_input(
+            "A journal file is required to append transcripts to".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn add_watch_folder(
+    app: AppHandle,
+    path: String,
+    post_process: bool,
+    output: WatchFolderOutput,
+    journal_path: Option<String>,
+) -> Result<WatchFolderConfig, HandyError> {
+    validate_watch_folder(&path, output, journal_path.as_deref())?;
+    let mut settings = settings::get_settings(&app);
+    let folder = WatchFolderConfig {
+        id: format!("folder_{}", chrono::Utc::now().timestamp_millis()),
+        path,
+        enabled: true,
+        post_process,
+        output,
+        journal_path,
+    };
+    settings.watch_folders.push(folder.clone());
+    settings::write_settings(&app, settings);
+    watch_folders::restart(&app);
+    Ok(folder)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_watch_folder(
+    app: AppHandle,
+    id: String,
+    path: String,
+    enabled: bool,
+    post_process: bool,
+    output: WatchFolderOutput,
+    journal_path: Option<String>,
+) -> Result<(), HandyError> {
+    validate_watch_folder(&path, output, journal_path.as_deref())?;
+    let mut settings = settings::get_settings(&app);
+    let folder = settings
+        .watch_folders
+        .iter_mut()
+        .find(|f| f.id == id)
+        .ok_or_else(|| HandyError::not_found(format!("Watch folder with id '{}' not found", id)))?;
+    folder.path = path;
+    folder.enabled = enabled;
+    folder.post_process = post_process;
+    folder.output = output;
+    folder.journal_path = journal_path;
+    settings::write_settings(&app, settings);
+    watch_folders::restart(&app);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_watch_folder(app: AppHandle, id: String) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let before = settings.watch_folders.len();
+    settings.watch_folders.retain(|f| f.id != id);
+    if settings.watch_folders.len() == before {
+        return Err(HandyError::not_found(format!(
+            "Watch folder with id '{}' not found",
+            id
+        )));
+    }
+    settings::write_settings(&app, settings);
+    watch_folders::restart(&app);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn duplicate_post_process_prompt(
     app: AppHandle,
     id: String,
-    name: String,
-    prompt: String,
-) -> Result<(), String> {
+    new_name: Option<String>,
+) -> Result<LLMPrompt, HandyError> {
     let mut settings = settings::get_settings(&app);
+    let copy = settings.duplicate_prompt(&id, new_name)?;
+    settings::write_settings(&app, settings);
+    Ok(copy)
+}
 
-    if let Some(existing_prompt) = settings
-        .post_process_prompts
-        .iter_mut()
-        .find(|p| p.id == id)
-    {
-        existing_prompt.name = name;
-        existing_prompt.prompt = prompt;
-        settings::write_settings(&app, settings);
-        Ok(())
-    } else {
-        Err(format!("Prompt with id '{}' not found", id))
-    }
+#[tauri::command]
+#[specta::specta]
+pub fn reset_prompt_to_default(app: AppHandle, id: String) -> Result<LLMPrompt, HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let restored = settings.reset_prompt_to_default(&id)?;
+    settings::write_settings(&app, settings);
+    Ok(restored)
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn delete_post_process_prompt(app: AppHandle, id: String) -> Result<(), String> {
+pub fn delete_post_process_prompt(app: AppHandle, id: String) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
 
     // Don't allow deleting the last prompt
     if settings.post_process_prompts.len() <= 1 {
-        return Err("Cannot delete the last prompt".to_string());
+        return Err(HandyError::invalid_input(
+            "Cannot delete the last prompt".to_string(),
+        ));
     }
 
     // Find and remove the prompt
@@ -629,7 +1622,10 @@ pub fn delete_post_process_prompt(app: AppHandle, id: String) -> Result<(), Stri
     settings.post_process_prompts.retain(|p| p.id != id);
 
     if settings.post_process_prompts.len() == original_len {
-        return Err(format!("Prompt with id '{}' not found", id));
+        return Err(HandyError::not_found(format!(
+            "Prompt with id '{}' not found",
+            id
+        )));
     }
 
     // If the deleted prompt was selected, select the first one or None
@@ -638,7 +1634,21 @@ pub fn delete_post_process_prompt(app: AppHandle, id: String) -> Result<(), Stri
             settings.post_process_prompts.first().map(|p| p.id.clone());
     }
 
+    // Rules pointing at the prompt would select one that no longer exists
+    let rule_count = settings.app_prompt_rules.len();
+    settings
+        .app_prompt_rules
+        .retain(|rule| rule.prompt_id != id);
+    let removed_rules = rule_count - settings.app_prompt_rules.len();
+    if removed_rules > 0 {
+        info!(
+            "Removed {} app prompt rule(s) that used prompt '{}'",
+            removed_rules, id
+        );
+    }
+
     settings::write_settings(&app, settings);
+    tray::refresh_tray_menu(&app);
     Ok(())
 }
 
@@ -647,7 +1657,7 @@ pub fn delete_post_process_prompt(app: AppHandle, id: String) -> Result<(), Stri
 pub async fn fetch_post_process_models(
     app: AppHandle,
     provider_id: String,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, HandyError> {
     let settings = settings::get_settings(&app);
 
     // Find the provider
@@ -655,7 +1665,8 @@ pub async fn fetch_post_process_models(
         .post_process_providers
         .iter()
         .find(|p| p.id == provider_id)
-        .ok_or_else(|| format!("Provider '{}' not found", provider_id))?;
+        .ok_or_else(|| HandyError::not_found(format!("Provider '{}' not found", provider_id)))?;
+    ensure_usable_provider(&settings, &provider_id)?;
 
     if provider.id == APPLE_INTELLIGENCE_PROVIDER_ID {
         #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
@@ -665,7 +1676,7 @@ pub async fn fetch_post_process_models(
 
         #[cfg(not(all(target_os = "macos", target_arch = "aarch64")))]
         {
-            return Err("Apple Intelligence is only available on Apple silicon Macs running macOS 15 or later.".to_string());
+            return Err(HandyError::config("Apple Intelligence is only available on Apple silicon Macs running macOS 15 or later.".to_string()));
         }
     }
 
@@ -717,31 +1728,173 @@ pub async fn fetch_post_process_models(
 
 #[tauri::command]
 #[specta::specta]
-pub fn set_post_process_selected_prompt(app: AppHandle, id: String) -> Result<(), String> {
+pub fn set_post_process_selected_prompt(app: AppHandle, id: String) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
 
     // Verify the prompt exists
     if !settings.post_process_prompts.iter().any(|p| p.id == id) {
-        return Err(format!("Prompt with id '{}' not found", id));
+        return Err(HandyError::not_found(format!(
+            "Prompt with id '{}' not found",
+            id
+        )));
     }
 
     settings.post_process_selected_prompt_id = Some(id);
     settings::write_settings(&app, settings);
+    tray::refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// Sets the prompt post-processing uses while no prompt is selected
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_default_prompt_setting(
+    app: AppHandle,
+    id: String,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    if !settings.post_process_prompts.iter().any(|p| p.id == id) {
+        return Err(HandyError::not_found(format!(
+            "Prompt with id '{}' not found",
+            id
+        )));
+    }
+    settings.post_process_default_prompt_id = id;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Sets the sampling seed sent with a prompt, None lets the provider pick
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_prompt_seed(
+    app: AppHandle,
+    id: String,
+    seed: Option<u64>,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let prompt = settings
+        .post_process_prompts
+        .iter_mut()
+        .find(|p| p.id == id)
+        .ok_or_else(|| HandyError::not_found(format!("Prompt with id '{}' not found", id)))?;
+    prompt.seed = seed;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Sets the label shown for a prompt in the tray, None falls back to its name
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_prompt_short_label(
+    app: AppHandle,
+    id: String,
+    short_label: Option<String>,
+) -> Result<(), HandyError> {
+    let short_label = short_label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty());
+    if let Some(label) = &short_label {
+        if label.chars().count() > settings::PROMPT_SHORT_LABEL_MAX_CHARS {
+            return Err(HandyError::invalid_input(format!(
+                "Short labels can be at most {} characters",
+                settings::PROMPT_SHORT_LABEL_MAX_CHARS
+            )));
+        }
+    }
+
+    let mut settings = settings::get_settings(&app);
+    let prompt = settings
+        .post_process_prompts
+        .iter_mut()
+        .find(|p| p.id == id)
+        .ok_or_else(|| HandyError::not_found(format!("Prompt with id '{}' not found", id)))?;
+    prompt.short_label = short_label;
+    settings::write_settings(&app, settings);
+    tray::refresh_tray_menu(&app);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_mute_while_recording_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_mute_while_recording_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.mute_while_recording = enabled;
+    // Muting and ducking both own the output volume while recording
+    if enabled {
+        settings.duck_audio_while_recording = false;
+    }
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_duck_audio_while_recording_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.duck_audio_while_recording = enabled;
+    if enabled {
+        settings.mute_while_recording = false;
+    }
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_duck_level_setting(app: AppHandle, level: u32) -> Result<(), HandyError> {
+    if level > 100 {
+        return Err(HandyError::invalid_input(
+            "Duck level must be a percentage between 0 and 100".to_string(),
+        ));
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.duck_level = level;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_append_trailing_space_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+pub fn change_remember_mic_per_environment_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.remember_mic_per_environment = enabled;
+
+    // Seed the current environment with the mic the user already picked
+    if enabled {
+        if let Some(environment) = current_device_environment() {
+            let selected = settings
+                .selected_microphone
+                .clone()
+                .unwrap_or_else(|| "default".to_string());
+            settings
+                .environment_microphones
+                .entry(environment)
+                .or_insert(selected);
+        }
+    }
+    settings::write_settings(&app, settings);
+
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    rm.update_selected_device()
+        .map_err(|e| HandyError::io(format!("Failed to update selected device: {}", e)))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_append_trailing_space_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.append_trailing_space = enabled;
     settings::write_settings(&app, settings);
@@ -750,7 +1903,286 @@ pub fn change_append_trailing_space_setting(app: AppHandle, enabled: bool) -> Re
 
 #[tauri::command]
 #[specta::specta]
-pub fn change_app_language_setting(app: AppHandle, language: String) -> Result<(), String> {
+pub fn change_bidi_isolation_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.bidi_isolation = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_transcript_ring_setting(
+    app: AppHandle,
+    size: u32,
+    persist: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.transcript_ring_size = size;
+    settings.persist_transcript_ring = persist;
+    app.state::<TranscriptRing>().apply_settings(&settings);
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_session_log_setting(
+    app: AppHandle,
+    store_text: bool,
+    persist: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.session_log_store_text = store_text;
+    settings.persist_session_log = persist;
+    app.state::<SessionLog>().apply_settings(&settings);
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_review_before_paste_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.review_before_paste = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_defer_paste_on_focus_change_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.defer_paste_on_focus_change = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_binding_review_setting(
+    app: AppHandle,
+    id: String,
+    review_before_paste: Option<bool>,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let binding = settings
+        .bindings
+        .get_mut(&id)
+        .ok_or_else(|| HandyError::not_found(format!("Binding with id '{}' not found", id)))?;
+    binding.review_before_paste = review_before_paste;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_collapse_to_single_line_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.collapse_to_single_line = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Pins the model a binding transcribes with, None falls back to `selected_model`
+#[tauri::command]
+#[specta::specta]
+pub fn change_binding_model_setting(
+    app: AppHandle,
+    id: String,
+    model_id: Option<String>,
+) -> Result<(), HandyError> {
+    let model_id = model_id.filter(|model_id| !model_id.trim().is_empty());
+    if let Some(model_id) = &model_id {
+        if app
+            .state::<Arc<ModelManager>>()
+            .get_model_info(model_id)
+            .is_none()
+        {
+            return Err(HandyError::not_found(format!(
+                "Model '{}' not found",
+                model_id
+            )));
+        }
+    }
+    let mut settings = settings::get_settings(&app);
+    let binding = settings
+        .bindings
+        .get_mut(&id)
+        .ok_or_else(|| HandyError::not_found(format!("Binding with id '{}' not found", id)))?;
+    binding.selected_model = model_id;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Sets whether a binding pastes a single line, None falls back to the global setting
+#[tauri::command]
+#[specta::specta]
+pub fn change_binding_single_line_setting(
+    app: AppHandle,
+    id: String,
+    collapse_to_single_line: Option<bool>,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let binding = settings
+        .bindings
+        .get_mut(&id)
+        .ok_or_else(|| HandyError::not_found(format!("Binding with id '{}' not found", id)))?;
+    binding.collapse_to_single_line = collapse_to_single_line;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// The binding as it reads on the current keyboard layout, see `layout`
+#[tauri::command]
+#[specta::specta]
+pub fn get_binding_display_label(app: AppHandle, id: String) -> Result<String, HandyError> {
+    let settings = settings::get_settings(&app);
+    let binding = settings
+        .bindings
+        .get(&id)
+        .ok_or_else(|| HandyError::not_found(format!("Binding with id '{}' not found", id)))?;
+    Ok(layout::display_label(binding))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_binding_match_mode(
+    app: AppHandle,
+    id: String,
+    match_mode: BindingMatchMode,
+) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    let binding = settings
+        .bindings
+        .get_mut(&id)
+        .ok_or_else(|| HandyError::not_found(format!("Binding with id '{}' not found", id)))?;
+    if binding.match_mode == match_mode {
+        return Ok(());
+    }
+    binding.match_mode = match_mode;
+    let binding = binding.clone();
+    settings::write_settings(&app, settings);
+
+    // Cancel is registered while recording only and picks up the mode next time
+    if id != "cancel" {
+        let _ = unregister_shortcut(&app, binding.clone());
+        register_shortcut(&app, binding)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_feedback_mode_setting(app: AppHandle, mode: String) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.feedback_mode = parse_feedback_mode(&mode)?;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Sets a binding's feedback mode, None falls back to the global setting
+#[tauri::command]
+#[specta::specta]
+pub fn change_binding_feedback_mode_setting(
+    app: AppHandle,
+    id: String,
+    mode: Option<String>,
+) -> Result<(), HandyError> {
+    let mode = mode.as_deref().map(parse_feedback_mode).transpose()?;
+    let mut settings = settings::get_settings(&app);
+    let binding = settings
+        .bindings
+        .get_mut(&id)
+        .ok_or_else(|| HandyError::not_found(format!("Binding with id '{}' not found", id)))?;
+    binding.feedback_mode = mode;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+fn parse_feedback_mode(mode: &str) -> Result<FeedbackMode, HandyError> {
+    match mode {
+        "sound" => Ok(FeedbackMode::Sound),
+        "overlay" => Ok(FeedbackMode::Overlay),
+        "both" => Ok(FeedbackMode::Both),
+        "none" => Ok(FeedbackMode::None),
+        other => Err(HandyError::invalid_input(format!(
+            "Invalid feedback mode: {}",
+            other
+        ))),
+    }
+}
+
+/// While enabled, bindings report presses through `shortcut-test-fired` instead of
+/// running. Ends on its own after a minute or when the settings window closes.
+#[tauri::command]
+#[specta::specta]
+pub fn shortcut_test_mode(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    test_mode::set_enabled(&app, enabled);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_haptic_feedback_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.haptic_feedback = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_review_timeout_setting(
+    app: AppHandle,
+    timeout_secs: u32,
+    action: String,
+) -> Result<(), HandyError> {
+    let action = match action.as_str() {
+        "confirm" => ReviewTimeoutAction::Confirm,
+        "discard" => ReviewTimeoutAction::Discard,
+        other => {
+            return Err(HandyError::invalid_input(format!(
+                "Invalid review timeout action: {}",
+                other
+            )))
+        }
+    };
+
+    let mut settings = settings::get_settings(&app);
+    settings.review_timeout_secs = timeout_secs;
+    settings.review_timeout_action = action;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_prompt_hardening_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.prompt_hardening = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_notify_on_complete_setting(app: AppHandle, enabled: bool) -> Result<(), HandyError> {
+    let mut settings = settings::get_settings(&app);
+    settings.notify_on_complete = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_app_language_setting(app: AppHandle, language: String) -> Result<(), HandyError> {
     let mut settings = settings::get_settings(&app);
     settings.app_language = language.clone();
     settings::write_settings(&app, settings);
@@ -0,0 +1,77 @@
+//! Shortcut test mode, used by onboarding to confirm key presses reach Handy.
+//!
+//! While enabled, bindings don't run their actions. Each press and release emits
+//! a `shortcut-test-fired` event instead and plays a feedback sound, so push-to-talk
+//! holds can be checked as well as single presses.
+
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::audio_feedback::{play_test_sound, SoundType};
+use crate::events::{emit_event, AppEvent, ShortcutTestFired};
+
+/// Test mode turns itself off so a forgotten session can't swallow real dictation
+const TEST_MODE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Id of the running test session; the timeout only ends the session it started
+static ACTIVE_SESSION: Lazy<Mutex<Option<u32>>> = Lazy::new(|| Mutex::new(None));
+static NEXT_SESSION: AtomicU32 = AtomicU32::new(0);
+
+pub fn is_enabled() -> bool {
+    ACTIVE_SESSION.lock().unwrap().is_some()
+}
+
+pub fn set_enabled(app: &AppHandle, enabled: bool) {
+    if !enabled {
+        end_session(app, None);
+        return;
+    }
+
+    let session = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
+    let was_enabled = ACTIVE_SESSION.lock().unwrap().replace(session).is_some();
+    if !was_enabled {
+        emit_event(app, AppEvent::ShortcutTestModeChanged(true));
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(TEST_MODE_TIMEOUT);
+        end_session(&app, Some(session));
+    });
+}
+
+/// Ends the given session, or whichever one is running when `session` is `None`
+fn end_session(app: &AppHandle, session: Option<u32>) {
+    {
+        let mut active = ACTIVE_SESSION.lock().unwrap();
+        if active.is_none() || (session.is_some() && *active != session) {
+            return;
+        }
+        *active = None;
+    }
+    log::info!("Shortcut test mode ended");
+    emit_event(app, AppEvent::ShortcutTestModeChanged(false));
+}
+
+/// Reports a binding event in place of running its action
+pub fn report(app: &AppHandle, binding_id: &str, hotkey_string: &str, is_pressed: bool) {
+    emit_event(
+        app,
+        AppEvent::ShortcutTestFired(ShortcutTestFired {
+            binding_id: binding_id.to_string(),
+            shortcut: hotkey_string.to_string(),
+            pressed: is_pressed,
+        }),
+    );
+
+    let sound_type = if is_pressed {
+        SoundType::Start
+    } else {
+        SoundType::Stop
+    };
+    let app = app.clone();
+    std::thread::spawn(move || play_test_sound(&app, sound_type));
+}
@@ -0,0 +1,197 @@
+//! Rough speaker turn detection for recordings with several people talking.
+//!
+//! There is no speaker model, so a change of speaker is guessed from what usually
+//! comes with one: a pause, with a voice on either side that sounds different.
+//! Voices are compared by loudness and zero-crossing rate, which follow how close
+//! someone sits to the microphone and how high their voice is. Quick exchanges
+//! without a pause are missed and one speaker changing their tone can be split, so
+//! the turns are only offered as hints in the transcript.
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::ops::Range;
+use std::time::Duration;
+
+/// Loudness and zero crossings are measured over frames this long
+const FRAME: Duration = Duration::from_millis(20);
+/// Shortest silence a turn can change in
+const MIN_PAUSE: Duration = Duration::from_millis(600);
+/// Speech compared on either side of a pause
+const CONTEXT: Duration = Duration::from_secs(3);
+/// Shortest turn, so a cough or a short reply doesn't get a marker of its own
+const MIN_TURN: Duration = Duration::from_secs(2);
+/// Frames quieter than this fraction of the loud speech level count as silence
+const SILENCE_RATIO: f32 = 0.1;
+/// Loudness difference that suggests another speaker
+const LOUDNESS_CHANGE_DB: f32 = 6.0;
+/// Relative zero-crossing rate difference that suggests another speaker
+const ZCR_CHANGE: f32 = 0.35;
+/// Speech frames needed on a side of a pause to judge the voice
+const MIN_VOICE_FRAMES: usize = 10;
+
+/// Part of a recording attributed to one speaker
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+pub struct SpeakerTurn {
+    pub start_ms: u32,
+    pub end_ms: u32,
+}
+
+impl SpeakerTurn {
+    pub fn from_samples(range: &Range<usize>) -> Self {
+        let ms = |samples: usize| (samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as u32;
+        Self {
+            start_ms: ms(range.start),
+            end_ms: ms(range.end),
+        }
+    }
+}
+
+struct Frame {
+    rms: f32,
+    /// Sign changes per sample
+    zcr: f32,
+}
+
+struct Voice {
+    loudness_db: f32,
+    zcr: f32,
+}
+
+fn frames_in(duration: Duration) -> usize {
+    (duration.as_millis() / FRAME.as_millis()) as usize
+}
+
+fn frame_samples() -> usize {
+    FRAME.as_millis() as usize * WHISPER_SAMPLE_RATE as usize / 1000
+}
+
+fn measure(audio: &[f32]) -> Vec<Frame> {
+    audio
+        .chunks(frame_samples())
+        .map(|frame| {
+            let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+            let crossings = frame
+                .windows(2)
+                .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+                .count();
+            Frame {
+                rms,
+                zcr: crossings as f32 / frame.len() as f32,
+            }
+        })
+        .collect()
+}
+
+/// Average voice of the speech frames, None if there is too little speech
+fn voice(frames: &[Frame], silence: f32) -> Option<Voice> {
+    let speech: Vec<&Frame> = frames.iter().filter(|f| f.rms >= silence).collect();
+    if speech.len() < MIN_VOICE_FRAMES {
+        return None;
+    }
+    let count = speech.len() as f32;
+    let rms = speech.iter().map(|f| f.rms).sum::<f32>() / count;
+    Some(Voice {
+        loudness_db: 20.0 * rms.log10(),
+        zcr: speech.iter().map(|f| f.zcr).sum::<f32>() / count,
+    })
+}
+
+fn sounds_different(a: &Voice, b: &Voice) -> bool {
+    let zcr_change = (a.zcr - b.zcr).abs() / a.zcr.max(b.zcr).max(f32::EPSILON);
+    (a.loudness_db - b.loudness_db).abs() >= LOUDNESS_CHANGE_DB || zcr_change >= ZCR_CHANGE
+}
+
+/// Ranges of 16kHz `audio` that likely belong to one speaker each, in order and
+/// covering all of it. A single range when no change was found.
+pub fn detect_turns(audio: &[f32]) -> Vec<Range<usize>> {
+    let frames = measure(audio);
+    let mut levels: Vec<f32> = frames.iter().map(|f| f.rms).collect();
+    levels.sort_by(f32::total_cmp);
+    let loud = levels.get(levels.len() * 9 / 10).copied().unwrap_or(0.0);
+    let silence = (loud * SILENCE_RATIO).max(1e-4);
+
+    let (min_pause, context, min_turn) = (
+        frames_in(MIN_PAUSE),
+        frames_in(CONTEXT),
+        frames_in(MIN_TURN),
+    );
+    let mut cuts = Vec::new();
+    let mut last_cut = 0;
+    let mut i = 0;
+    while i < frames.len() {
+        if frames[i].rms >= silence {
+            i += 1;
+            continue;
+        }
+        let pause_start = i;
+        while i < frames.len() && frames[i].rms < silence {
+            i += 1;
+        }
+        let cut = (pause_start + i) / 2;
+        if i - pause_start < min_pause || cut - last_cut < min_turn || frames.len() - cut < min_turn
+        {
+            continue;
+        }
+        let before = voice(
+            &frames[pause_start.saturating_sub(context)..pause_start],
+            silence,
+        );
+        let after = voice(&frames[i..(i + context).min(frames.len())], silence);
+        if let (Some(before), Some(after)) = (before, after) {
+            if sounds_different(&before, &after) {
+                cuts.push(cut);
+                last_cut = cut;
+            }
+        }
+    }
+
+    let mut turns = Vec::with_capacity(cuts.len() + 1);
+    let mut start = 0;
+    for cut in cuts {
+        let end = (cut * frame_samples()).min(audio.len());
+        turns.push(start..end);
+        start = end;
+    }
+    turns.push(start..audio.len());
+    turns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(hz: f32, amplitude: f32, seconds: f32) -> Vec<f32> {
+        let rate = WHISPER_SAMPLE_RATE as f32;
+        (0..(seconds * rate) as usize)
+            .map(|n| amplitude * (2.0 * std::f32::consts::PI * hz * n as f32 / rate).sin())
+            .collect()
+    }
+
+    #[test]
+    fn turn_changes_at_a_pause_between_different_voices() {
+        let second = WHISPER_SAMPLE_RATE as usize;
+        let mut audio = tone(150.0, 0.5, 4.0);
+        audio.extend(vec![0.0; second]);
+        audio.extend(tone(600.0, 0.1, 4.0));
+        // Same voice again after a pause, no new turn
+        audio.extend(vec![0.0; second]);
+        audio.extend(tone(600.0, 0.1, 3.0));
+
+        let turns = detect_turns(&audio);
+        assert_eq!(turns.len(), 2);
+        assert!((4 * second..5 * second).contains(&turns[0].end));
+        assert_eq!(turns[1].start, turns[0].end);
+        assert_eq!(turns[1].end, audio.len());
+        assert_eq!(
+            SpeakerTurn::from_samples(&(0..second / 2)),
+            SpeakerTurn {
+                start_ms: 0,
+                end_ms: 500
+            }
+        );
+
+        assert_eq!(detect_turns(&[]).len(), 1);
+        assert_eq!(detect_turns(&tone(150.0, 0.5, 5.0)).len(), 1);
+    }
+}
@@ -0,0 +1,149 @@
+//! Warns before switching to a model that may not fit in the free system memory.
+//!
+//! A model larger than the free memory still loads, but the system starts swapping
+//! or the app is killed, which reads like a crash. The footprint is the memory the
+//! manifest recommends for the model, so the warning is a hint rather than a limit.
+
+use crate::managers::model::{EngineType, ModelInfo};
+use serde::Serialize;
+use specta::Type;
+use sysinfo::System;
+
+/// Inference runtime, audio buffers and decoder state on top of the weights
+const RUNTIME_OVERHEAD_MB: u64 = 300;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+pub struct SystemMemory {
+    pub total_mb: u64,
+    /// Memory that can be used without swapping, including reclaimable caches
+    pub available_mb: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct MemoryWarning {
+    pub model_id: String,
+    /// Estimated memory the model takes once loaded
+    pub required_mb: u64,
+    pub available_mb: u64,
+    pub total_mb: u64,
+    pub message: String,
+}
+
+/// Result of `set_active_model`
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Type)]
+#[serde(tag = "status", content = "warning", rename_all = "snake_case")]
+pub enum ModelSwitch {
+    Switched,
+    /// Nothing changed, the switch waits for confirmation through
+    /// `ignore_memory_warning`
+    NeedsConfirmation(MemoryWarning),
+}
+
+pub fn system_memory() -> SystemMemory {
+    let mut system = System::new();
+    system.refresh_memory();
+    SystemMemory {
+        total_mb: system.total_memory() / (1024 * 1024),
+        available_mb: system.available_memory() / (1024 * 1024),
+    }
+}
+
+/// Memory in MB the model takes once loaded: the manifest's recommendation, or an
+/// estimate from the download size when the chosen quantization needs more
+pub fn footprint_mb(model: &ModelInfo) -> u64 {
+    let working_set = match model.engine_type {
+        // Whisper's key-value cache and compute buffers grow with the weights
+        EngineType::Whisper => model.size_mb / 5,
+        EngineType::Parakeet | EngineType::Moonshine => model.size_mb / 2,
+    };
+    let estimate = model.size_mb + working_set + RUNTIME_OVERHEAD_MB;
+    estimate.max(model.recommended_ram_mb as u64)
+}
+
+/// A warning when the model needs more memory than is free, None if it fits
+pub fn check(model: &ModelInfo, memory: SystemMemory) -> Option<MemoryWarning> {
+    let required_mb = footprint_mb(model);
+    if required_mb <= memory.available_mb {
+        return None;
+    }
+    let message = if required_mb > memory.total_mb {
+        format!(
+            "{} needs about {} MB of memory but this computer has {} MB. Choose a smaller model.",
+            model.name, required_mb, memory.total_mb
+        )
+    } else {
+        format!(
+            "{} needs about {} MB of memory but only {} MB is free. Close other apps first, or \
+             your computer may slow down or Handy may quit.",
+            model.name, required_mb, memory.available_mb
+        )
+    };
+    Some(MemoryWarning {
+        model_id: model.id.clone(),
+        required_mb,
+        available_mb: memory.available_mb,
+        total_mb: memory.total_mb,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_manifest::ManifestModel;
+
+    fn model(size_mb: u64, recommended_ram_mb: u32) -> ModelInfo {
+        let entry = format!(
+            r#"{{
+                "id": "large",
+                "name": "Whisper Large",
+                "description": "",
+                "engine_type": "Whisper",
+                "filename": "ggml-large-v3-q5_0.bin",
+                "url": null,
+                "is_directory": false,
+                "size_mb": {},
+                "parameters_m": 1550,
+                "languages": ["en"],
+                "quality_tier": "best",
+                "recommended_ram_mb": {},
+                "accuracy_score": 0.85,
+                "speed_score": 0.3
+            }}"#,
+            size_mb, recommended_ram_mb
+        );
+        serde_json::from_str::<ManifestModel>(&entry)
+            .expect("valid manifest entry")
+            .into_info()
+    }
+
+    #[test]
+    fn warns_only_when_the_model_exceeds_free_memory() {
+        let large = model(1100, 1024);
+        assert_eq!(footprint_mb(&large), 1620);
+        assert_eq!(footprint_mb(&model(1100, 4096)), 4096);
+
+        let roomy = SystemMemory {
+            total_mb: 16_384,
+            available_mb: 8_000,
+        };
+        assert_eq!(check(&large, roomy), None);
+
+        let busy = SystemMemory {
+            total_mb: 8_192,
+            available_mb: 1_200,
+        };
+        let warning = check(&large, busy).expect("warning");
+        assert_eq!(warning.required_mb, 1620);
+        assert!(warning.message.contains("only 1200 MB is free"));
+
+        let tiny = SystemMemory {
+            total_mb: 1_024,
+            available_mb: 600,
+        };
+        assert!(check(&large, tiny)
+            .expect("warning")
+            .message
+            .contains("this computer has 1024 MB"));
+    }
+}
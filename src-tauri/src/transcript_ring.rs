@@ -0,0 +1,167 @@
+//! In-memory ring of recent transcripts, independent of the OS clipboard and of history.
+//! It is only written to disk when `persist_transcript_ring` is on.
+
+use crate::paths;
+use crate::settings::AppSettings;
+use chrono::Utc;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const RING_FILE_NAME: &str = "transcript_ring.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct TranscriptRingEntry {
+    pub text: String,
+    pub timestamp: i64,
+}
+
+pub struct TranscriptRing {
+    /// Where the ring is persisted, None when the app data dir is unavailable
+    path: Option<PathBuf>,
+    /// Newest entry first
+    entries: Mutex<VecDeque<TranscriptRingEntry>>,
+}
+
+impl TranscriptRing {
+    /// Creates the ring, restoring persisted entries if persistence is enabled
+    pub fn load(app: &AppHandle, settings: &AppSettings) -> Self {
+        let path = Some(paths::get(app).data.join(RING_FILE_NAME));
+        Self::with_path(path, settings)
+    }
+
+    fn with_path(path: Option<PathBuf>, settings: &AppSettings) -> Self {
+        let mut entries = VecDeque::new();
+        if settings.persist_transcript_ring {
+            if let Some(path) = &path {
+                entries = read_entries(path);
+                entries.truncate(settings.transcript_ring_size as usize);
+            }
+        }
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub fn push(&self, text: &str, settings: &AppSettings) {
+        let capacity = settings.transcript_ring_size as usize;
+        if capacity == 0 || text.trim().is_empty() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.push_front(TranscriptRingEntry {
+            text: text.to_string(),
+            timestamp: Utc::now().timestamp(),
+        });
+        entries.truncate(capacity);
+        if settings.persist_transcript_ring {
+            self.save(&entries);
+        }
+    }
+
+    /// Entries, newest first
+    pub fn entries(&self) -> Vec<TranscriptRingEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Entry `index` back from the newest, which is 0
+    pub fn get(&self, index: usize) -> Option<TranscriptRingEntry> {
+        self.entries.lock().unwrap().get(index).cloned()
+    }
+
+    /// Applies a changed size or persistence setting. Turning persistence off deletes the file.
+    pub fn apply_settings(&self, settings: &AppSettings) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.truncate(settings.transcript_ring_size as usize);
+        if settings.persist_transcript_ring {
+            self.save(&entries);
+        } else if let Some(path) = self.path.as_ref().filter(|p| p.exists()) {
+            if let Err(e) = std::fs::remove_file(path) {
+                warn!("Failed to remove persisted transcript ring: {}", e);
+            }
+        }
+    }
+
+    fn save(&self, entries: &VecDeque<TranscriptRingEntry>) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string(entries)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to persist transcript ring: {}", e);
+        }
+    }
+}
+
+fn read_entries(path: &Path) -> VecDeque<TranscriptRingEntry> {
+    let Ok(json) = std::fs::read_to_string(path) else {
+        return VecDeque::new();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        debug!("Ignoring unreadable transcript ring file: {}", e);
+        VecDeque::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "handy-transcript-ring-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn keeps_newest_entries_up_to_size() {
+        let mut settings = get_default_settings();
+        settings.transcript_ring_size = 2;
+        let ring = TranscriptRing::with_path(None, &settings);
+
+        ring.push("one", &settings);
+        ring.push("two", &settings);
+        ring.push("three", &settings);
+        ring.push("   ", &settings);
+
+        let texts: Vec<_> = ring.entries().into_iter().map(|e| e.text).collect();
+        assert_eq!(texts, vec!["three", "two"]);
+        assert_eq!(ring.get(1).map(|e| e.text).as_deref(), Some("two"));
+        assert!(ring.get(2).is_none());
+
+        settings.transcript_ring_size = 1;
+        ring.apply_settings(&settings);
+        assert_eq!(ring.entries().len(), 1);
+    }
+
+    #[test]
+    fn persists_only_when_enabled() {
+        let path = temp_path("persist");
+        let mut settings = get_default_settings();
+        let ring = TranscriptRing::with_path(Some(path.clone()), &settings);
+        ring.push("private", &settings);
+        assert!(!path.exists());
+
+        settings.persist_transcript_ring = true;
+        ring.push("kept", &settings);
+        let restored = TranscriptRing::with_path(Some(path.clone()), &settings);
+        assert_eq!(restored.get(0).map(|e| e.text).as_deref(), Some("kept"));
+
+        settings.persist_transcript_ring = false;
+        restored.apply_settings(&settings);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use whatlang::Lang;
+
+/// Minimum detector confidence before non-English output is treated as suspect
+const SUSPECT_CONFIDENCE: f64 = 0.5;
+/// Shorter outputs don't carry enough signal for language detection
+const MIN_CHECK_CHARS: usize = 20;
+
+/// Verdict of the post-decode language check, stored with the history entry
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct TranslationCheck {
+    /// ISO 639-3 code of the detected language, if detection succeeded
+    pub detected_language: Option<String>,
+    pub confidence: f64,
+    pub suspect: bool,
+    /// Whether the decode was retried with English forced
+    pub retried: bool,
+}
+
+/// Checks whether translated output actually looks like English
+pub fn check_english(text: &str) -> TranslationCheck {
+    let trimmed = text.trim();
+    if trimmed.chars().count() < MIN_CHECK_CHARS {
+        return TranslationCheck {
+            detected_language: None,
+            confidence: 0.0,
+            suspect: false,
+            retried: false,
+        };
+    }
+
+    match whatlang::detect(trimmed) {
+        Some(info) => TranslationCheck {
+            detected_language: Some(info.lang().code().to_string()),
+            confidence: info.confidence(),
+            suspect: info.lang() != Lang::Eng && info.confidence() >= SUSPECT_CONFIDENCE,
+            retried: false,
+        },
+        None => TranslationCheck {
+            detected_language: None,
+            confidence: 0.0,
+            suspect: false,
+            retried: false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_output_passes() {
+        let check = check_english("I would like to schedule a meeting with the team tomorrow.");
+        assert_eq!(check.detected_language.as_deref(), Some("eng"));
+        assert!(!check.suspect);
+    }
+
+    #[test]
+    fn untranslated_output_is_suspect() {
+        let check =
+            check_english("Ich möchte morgen ein Treffen mit dem ganzen Team vereinbaren, bitte.");
+        assert_eq!(check.detected_language.as_deref(), Some("deu"));
+        assert!(check.suspect);
+    }
+
+    #[test]
+    fn short_output_is_not_checked() {
+        let check = check_english("Hallo");
+        assert!(check.detected_language.is_none());
+        assert!(!check.suspect);
+    }
+}
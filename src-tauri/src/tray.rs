@@ -1,14 +1,20 @@
+use crate::copy_only_session::CopyOnlySession;
 use crate::managers::history::{HistoryEntry, HistoryManager};
+use crate::managers::transcription::TranscriptionManager;
+use crate::power_profile;
 use crate::settings;
 use crate::tray_i18n::get_tray_translations;
 use log::{error, info, warn};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tauri::image::Image;
-use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::TrayIcon;
-use tauri::{AppHandle, Manager, Theme};
+use tauri::{AppHandle, Listener, Manager, Theme, Wry};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
+/// Menu ids of the prompt quick-switch items are this prefix followed by the prompt id
+pub const PROMPT_MENU_ID_PREFIX: &str = "prompt:";
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum TrayIconState {
     Idle,
@@ -16,6 +22,9 @@ pub enum TrayIconState {
     Transcribing,
 }
 
+/// State the menu was last built for, so it can be rebuilt when the model changes
+static MENU_STATE: Mutex<TrayIconState> = Mutex::new(TrayIconState::Idle);
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum AppTheme {
     Dark,
@@ -79,7 +88,21 @@ pub fn change_tray_icon(app: &AppHandle, icon: TrayIconState) {
     update_tray_menu(app, &icon, None);
 }
 
+/// Rebuilds the menu for its current state, e.g. after the model was loaded or unloaded
+pub fn refresh_tray_menu(app: &AppHandle) {
+    let state = MENU_STATE.lock().unwrap().clone();
+    update_tray_menu(app, &state, None);
+}
+
+/// Keeps the load/unload item in step with the model, which can change from the
+/// settings window, the idle timeout or the menu itself
+pub fn follow_model_state(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen("model-state-changed", move |_| refresh_tray_menu(&handle));
+}
+
 pub fn update_tray_menu(app: &AppHandle, state: &TrayIconState, locale: Option<&str>) {
+    *MENU_STATE.lock().unwrap() = state.clone();
     let settings = settings::get_settings(app);
 
     let locale = locale.unwrap_or(&settings.app_language);
@@ -123,52 +146,145 @@ pub fn update_tray_menu(app: &AppHandle, state: &TrayIconState, locale: Option<&
         None::<&str>,
     )
     .expect("failed to create copy last transcript item");
+    let copy_only_active = app
+        .try_state::<CopyOnlySession>()
+        .is_some_and(|session| session.is_active());
+    let copy_only_i = CheckMenuItem::with_id(
+        app,
+        "copy_only_session",
+        &strings.copy_only_session,
+        true,
+        copy_only_active,
+        None::<&str>,
+    )
+    .expect("failed to create copy-only session item");
     let quit_i = MenuItem::with_id(app, "quit", &strings.quit, true, quit_accelerator)
         .expect("failed to create quit item");
-    let separator = || PredefinedMenuItem::separator(app).expect("failed to create separator");
-
-    let menu = match state {
-        TrayIconState::Recording | TrayIconState::Transcribing => {
-            let cancel_i = MenuItem::with_id(app, "cancel", &strings.cancel, true, None::<&str>)
-                .expect("failed to create cancel item");
-            Menu::with_items(
-                app,
-                &[
-                    &version_i,
-                    &separator(),
-                    &cancel_i,
-                    &separator(),
-                    &copy_last_transcript_i,
-                    &separator(),
-                    &settings_i,
-                    &check_updates_i,
-                    &separator(),
-                    &quit_i,
-                ],
-            )
-            .expect("failed to create menu")
-        }
-        TrayIconState::Idle => Menu::with_items(
+    let cancel_i = MenuItem::with_id(app, "cancel", &strings.cancel, true, None::<&str>)
+        .expect("failed to create cancel item");
+    let model_loaded = app
+        .try_state::<Arc<TranscriptionManager>>()
+        .is_some_and(|tm| tm.is_model_loaded());
+    let model_i = if model_loaded {
+        MenuItem::with_id(
             app,
-            &[
-                &version_i,
-                &separator(),
-                &copy_last_transcript_i,
-                &separator(),
-                &settings_i,
-                &check_updates_i,
-                &separator(),
-                &quit_i,
-            ],
+            "unload_model",
+            &strings.unload_model,
+            true,
+            None::<&str>,
         )
-        .expect("failed to create menu"),
-    };
+    } else {
+        MenuItem::with_id(
+            app,
+            "load_model",
+            &strings.load_model,
+            !settings.selected_model.is_empty(),
+            None::<&str>,
+        )
+    }
+    .expect("failed to create model item");
+    let prompts_i = prompt_submenu(app, &settings, &strings.prompt);
+    let separator = || PredefinedMenuItem::separator(app).expect("failed to create separator");
+    let separators: Vec<_> = (0..4).map(|_| separator()).collect();
+
+    let mut items: Vec<&dyn IsMenuItem<Wry>> = vec![&version_i, &separators[0]];
+    if *state != TrayIconState::Idle {
+        items.extend([&cancel_i as &dyn IsMenuItem<Wry>, &separators[1]]);
+    }
+    items.extend([
+        &copy_last_transcript_i as &dyn IsMenuItem<Wry>,
+        &copy_only_i,
+    ]);
+    if let Some(prompts_i) = &prompts_i {
+        items.push(prompts_i);
+    }
+    items.push(&model_i);
+    items.extend([
+        &separators[2] as &dyn IsMenuItem<Wry>,
+        &settings_i,
+        &check_updates_i,
+        &separators[3],
+        &quit_i,
+    ]);
+    let menu = Menu::with_items(app, &items).expect("failed to create menu");
 
     let tray = app.state::<TrayIcon>();
     let _ = tray.set_menu(Some(menu));
     let _ = tray.set_icon_as_template(true);
 }
 
+/// Quick switch between post-processing prompts, shown while post-processing is enabled
+fn prompt_submenu(
+    app: &AppHandle,
+    settings: &settings::AppSettings,
+    title: &str,
+) -> Option<Submenu<Wry>> {
+    if !settings.post_process_enabled || settings.post_process_prompts.is_empty() {
+        return None;
+    }
+    let selected = settings
+        .resolve_post_process_prompt()
+        .ok()
+        .map(|prompt| prompt.id.as_str());
+    let submenu = Submenu::with_id(app, "prompts", title, true).ok()?;
+    for prompt in &settings.post_process_prompts {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{}{}", PROMPT_MENU_ID_PREFIX, prompt.id),
+            prompt.display_label(),
+            true,
+            selected == Some(prompt.id.as_str()),
+            None::<&str>,
+        );
+        match item {
+            Ok(item) => {
+                if let Err(e) = submenu.append(&item) {
+                    warn!("Failed to add prompt '{}' to the tray: {}", prompt.id, e);
+                }
+            }
+            Err(e) => warn!(
+                "Failed to create tray item for prompt '{}': {}",
+                prompt.id, e
+            ),
+        }
+    }
+    Some(submenu)
+}
+
+/// Loads the selected model in the background so the next dictation starts at once
+pub fn load_model_from_menu(app: &AppHandle) {
+    let model_id = power_profile::selected_model(app, &settings::get_settings(app));
+    let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+    std::thread::spawn(move || {
+        if let Err(e) = tm.load_model(&model_id) {
+            error!("Failed to load model '{}' from the tray: {}", model_id, e);
+        }
+    });
+}
+
+pub fn unload_model_from_menu(app: &AppHandle) {
+    if let Err(e) = app.state::<Arc<TranscriptionManager>>().unload_model() {
+        error!("Failed to unload model from the tray: {}", e);
+    }
+}
+
+/// Selects the prompt behind a quick-switch menu item
+pub fn select_prompt_from_menu(app: &AppHandle, menu_id: &str) {
+    let Some(id) = menu_id.strip_prefix(PROMPT_MENU_ID_PREFIX) else {
+        return;
+    };
+    let mut settings = settings::get_settings(app);
+    if !settings.post_process_prompts.iter().any(|p| p.id == id) {
+        warn!("Tray selected unknown prompt '{}'", id);
+        return;
+    }
+    settings.post_process_selected_prompt_id = Some(id.to_string());
+    settings::write_settings(app, settings);
+    info!("Selected prompt '{}' from the tray", id);
+    // Check items toggle themselves on click, rebuild so only the selected one is checked
+    update_tray_menu(app, &TrayIconState::Idle, None);
+}
+
 fn last_transcript_text(entry: &HistoryEntry) -> &str {
     entry
         .post_processed_text
@@ -213,6 +329,15 @@ mod tests {
             transcription_text: transcription.to_string(),
             post_processed_text: post_processed.map(|text| text.to_string()),
             post_process_prompt: None,
+            translation_check: None,
+            output_file: None,
+            app_prompt_rule: None,
+            words_per_minute: None,
+            word_corrections: None,
+            speaker_turns: None,
+            vram_guard: None,
+            collapsed_to_single_line: false,
+            draft: false,
         }
     }
 
@@ -19,7 +19,7 @@ use std::collections::HashMap;
 include!(concat!(env!("OUT_DIR"), "/tray_translations.rs"));
 
 /// Get the language code from a locale string (e.g., "en-US" -> "en")
-fn get_language_code(locale: &str) -> &str {
+pub(crate) fn get_language_code(locale: &str) -> &str {
     locale.split(['-', '_']).next().unwrap_or("en")
 }
 
@@ -0,0 +1,116 @@
+//! Undo of the most recent paste by deleting it again with backspaces.
+//!
+//! Only the length of the pasted text is known, not whether it is still where the
+//! paste put it. Typing or moving the caret in between would make the backspaces
+//! delete the wrong text, so the undo is only offered for a short time after the
+//! paste and only once. It runs once the shortcut's modifiers are let go, as a
+//! held modifier would turn the backspaces into word or line deletions.
+
+use crate::error::HandyError;
+use crate::input::{self, EnigoState};
+use log::info;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How long after a paste it can still be undone
+pub const UNDO_WINDOW: Duration = Duration::from_secs(10);
+/// How long the undo waits for the shortcut's modifiers to be let go
+const MODIFIER_RELEASE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Wait before deleting where held modifiers can't be detected
+const MODIFIER_RELEASE_GRACE: Duration = Duration::from_millis(150);
+
+struct Pasted {
+    /// Backspaces that remove the text again, one per grapheme cluster as editors
+    /// delete an accented letter or an emoji sequence with a single backspace
+    chars: usize,
+    at: Instant,
+}
+
+#[derive(Default)]
+pub struct LastPaste(Mutex<Option<Pasted>>);
+
+impl LastPaste {
+    /// Remembers a paste that just went into the focused field
+    pub fn record(&self, text: &str) {
+        *self.0.lock().unwrap() = Some(Pasted {
+            chars: text.graphemes(true).count(),
+            at: Instant::now(),
+        });
+    }
+
+    /// Characters to delete for an undo at `now`, consuming the recorded paste
+    fn take(&self, now: Instant) -> Result<usize, HandyError> {
+        let pasted = self
+            .0
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| HandyError::not_found("Nothing was pasted that can be undone"))?;
+        if now.duration_since(pasted.at) > UNDO_WINDOW {
+            return Err(HandyError::invalid_input(format!(
+                "The last paste is older than {} seconds and can no longer be undone",
+                UNDO_WINDOW.as_secs()
+            )));
+        }
+        Ok(pasted.chars)
+    }
+}
+
+/// Blocks until no modifier key is held, false if they were still held at the timeout
+fn wait_for_modifier_release() -> bool {
+    let deadline = Instant::now() + MODIFIER_RELEASE_TIMEOUT;
+    loop {
+        match input::modifier_keys_held() {
+            Some(false) => return true,
+            None => {
+                std::thread::sleep(MODIFIER_RELEASE_GRACE);
+                return true;
+            }
+            Some(true) if Instant::now() >= deadline => return false,
+            Some(true) => std::thread::sleep(Duration::from_millis(10)),
+        }
+    }
+}
+
+/// Deletes the text of the last paste from the focused field, once the shortcut's
+/// modifiers are released. Blocks while waiting for them.
+pub fn undo(app: &AppHandle) -> Result<(), HandyError> {
+    if !wait_for_modifier_release() {
+        return Err(HandyError::invalid_input(
+            "Modifier keys are still held, not undoing the paste",
+        ));
+    }
+    let chars = app.state::<LastPaste>().take(Instant::now())?;
+    let enigo_state = app
+        .try_state::<EnigoState>()
+        .ok_or_else(|| HandyError::permission("Keyboard simulation is not available"))?;
+    let mut enigo = enigo_state
+        .0
+        .lock()
+        .map_err(|e| HandyError::internal(format!("Failed to lock Enigo: {}", e)))?;
+    input::send_backspaces(&mut enigo, chars)
+        .map_err(|e| HandyError::internal(format!("Failed to undo the paste: {}", e)))?;
+    info!("Undid the last paste ({} characters)", chars);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_can_be_undone_once_within_the_window() {
+        let last = LastPaste::default();
+        // The combining accent and the skin tone belong to the letter and emoji
+        last.record("he\u{301}llo 👍🏽");
+        let now = Instant::now();
+        assert_eq!(last.take(now).unwrap(), 7);
+        assert_eq!(last.take(now).unwrap_err().code(), "not_found");
+
+        last.record("hello");
+        let late = Instant::now() + UNDO_WINDOW + Duration::from_secs(1);
+        assert_eq!(last.take(late).unwrap_err().code(), "invalid_input");
+    }
+}
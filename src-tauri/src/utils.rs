@@ -1,8 +1,11 @@
+use crate::actions::ACTION_MAP;
 use crate::managers::audio::AudioRecordingManager;
 use crate::managers::transcription::TranscriptionManager;
 use crate::shortcut;
 use crate::ManagedToggleState;
-use log::{info, warn};
+use log::{debug, info, warn};
+use serde::Serialize;
+use specta::Type;
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 
@@ -19,6 +22,7 @@ pub fn cancel_current_operation(app: &AppHandle) {
 
     // Unregister the cancel shortcut asynchronously
     shortcut::unregister_cancel_shortcut(app);
+    shortcut::key_suppression::end();
 
     // First, reset all shortcut toggle states.
     // This is critical for non-push-to-talk mode where shortcuts toggle on/off
@@ -29,21 +33,54 @@ pub fn cancel_current_operation(app: &AppHandle) {
         warn!("Failed to lock toggle state manager during cancellation");
     }
 
-    // Cancel any ongoing recording
+    // Cancel any ongoing recording, keeping its audio if `cancel_behavior` says so
     let audio_manager = app.state::<Arc<AudioRecordingManager>>();
-    audio_manager.cancel_recording();
+    let transcribing = audio_manager
+        .cancel_recording()
+        .is_some_and(|samples| crate::actions::keep_cancelled_recording(app, samples));
+
+    crate::dictation_session::cancel(app);
 
     // Update tray icon and hide overlay
     change_tray_icon(app, crate::tray::TrayIconState::Idle);
     hide_recording_overlay(app);
 
-    // Unload model if immediate unload is enabled
-    let tm = app.state::<Arc<TranscriptionManager>>();
-    tm.maybe_unload_immediately("cancellation");
+    // Drop transcripts that were waiting for review, and abandon the current
+    // dictation's post-processing so its result isn't pasted
+    crate::review::cancel_all(app);
+    app.state::<crate::pipeline::ActiveOperations>()
+        .cancel_current(app);
+
+    // Unload model if immediate unload is enabled. Transcribing the cancelled
+    // recording unloads it when done.
+    if !transcribing {
+        let tm = app.state::<Arc<TranscriptionManager>>();
+        tm.maybe_unload_immediately("cancellation");
+    }
 
     info!("Operation cancellation completed - returned to idle state");
 }
 
+/// Stops the current recording and transcribes it, as if its shortcut had been released.
+/// Used by the interactive overlay.
+pub fn stop_current_recording(app: &AppHandle) -> Result<(), String> {
+    let audio_manager = app.state::<Arc<AudioRecordingManager>>();
+    let binding_id = audio_manager
+        .active_binding()
+        .ok_or_else(|| "No recording in progress".to_string())?;
+    let action = ACTION_MAP
+        .get(binding_id.as_str())
+        .ok_or_else(|| format!("No action defined for binding '{}'", binding_id))?;
+
+    // Keep toggle mode in sync so the next shortcut press starts a new recording
+    if let Ok(mut states) = app.state::<ManagedToggleState>().lock() {
+        states.active_toggles.insert(binding_id.clone(), false);
+    }
+
+    action.stop(app, &binding_id, "overlay");
+    Ok(())
+}
+
 /// Check if using the Wayland display server protocol
 #[cfg(target_os = "linux")]
 pub fn is_wayland() -> bool {
@@ -52,3 +89,265 @@ pub fn is_wayland() -> bool {
             .map(|v| v.to_lowercase() == "wayland")
             .unwrap_or(false)
 }
+
+/// Information about the application that currently has keyboard focus.
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct FrontmostApplication {
+    /// Human readable application name (e.g. "Safari", "chrome")
+    pub name: String,
+    /// Stable identifier: bundle ID on macOS, executable name on Windows/Linux
+    pub identifier: Option<String>,
+    /// Path to the application bundle or executable, if known
+    pub path: Option<String>,
+    /// Native handle of the focused window (HWND on Windows, X11 window ID on
+    /// Linux), used to focus it again
+    #[serde(skip)]
+    pub window_id: Option<u64>,
+}
+
+impl FrontmostApplication {
+    /// True when `matcher` equals the identifier or the name, ignoring case
+    pub fn matches(&self, matcher: &str) -> bool {
+        self.identifier
+            .as_deref()
+            .is_some_and(|id| id.eq_ignore_ascii_case(matcher))
+            || self.name.eq_ignore_ascii_case(matcher)
+    }
+}
+
+/// Returns the application that currently has focus.
+/// Returns None if the platform API is unavailable (e.g. Wayland) or the query fails.
+pub fn frontmost_application() -> Option<FrontmostApplication> {
+    #[cfg(target_os = "macos")]
+    {
+        frontmost_application_macos()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        frontmost_application_windows()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        frontmost_application_linux()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Uses lsappinfo, which unlike System Events does not require automation permissions.
+#[cfg(target_os = "macos")]
+fn frontmost_application_macos() -> Option<FrontmostApplication> {
+    use std::process::Command;
+
+    let front = Command::new("lsappinfo").arg("front").output().ok()?;
+    if !front.status.success() {
+        return None;
+    }
+    let asn = String::from_utf8_lossy(&front.stdout).trim().to_string();
+    if asn.is_empty() {
+        return None;
+    }
+
+    let output = Command::new("lsappinfo")
+        .args([
+            "info",
+            "-only",
+            "name",
+            "-only",
+            "bundleid",
+            "-only",
+            "bundlepath",
+            &asn,
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Output lines look like: "LSDisplayName"="Safari"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut name = None;
+    let mut identifier = None;
+    let mut path = None;
+    for line in stdout.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().trim_matches('"');
+        let value = value.trim().trim_matches('"');
+        if value.is_empty() || value == "[ NULL ]" {
+            continue;
+        }
+        match key {
+            "LSDisplayName" => name = Some(value.to_string()),
+            "CFBundleIdentifier" => identifier = Some(value.to_string()),
+            "LSBundlePath" => path = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    Some(FrontmostApplication {
+        name: name.or_else(|| identifier.clone())?,
+        identifier,
+        path,
+        window_id: None,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn frontmost_application_windows() -> Option<FrontmostApplication> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.0.is_null() {
+        return None;
+    }
+    let exe_path = unsafe {
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut size = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+
+        String::from_utf16_lossy(&buffer[..size as usize])
+    };
+
+    let path = std::path::Path::new(&exe_path);
+    Some(FrontmostApplication {
+        name: path.file_stem()?.to_string_lossy().to_string(),
+        identifier: path.file_name().map(|n| n.to_string_lossy().to_lowercase()),
+        path: Some(exe_path.clone()),
+        window_id: Some(hwnd.0 as usize as u64),
+    })
+}
+
+/// Uses xdotool to find the focused window's process. Wayland compositors don't expose
+/// the focused window to clients, so this only works on X11 (and XWayland windows).
+#[cfg(target_os = "linux")]
+fn frontmost_application_linux() -> Option<FrontmostApplication> {
+    use std::process::Command;
+
+    let xdotool = |args: &[&str]| -> Option<String> {
+        let output = Command::new("xdotool").args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let window_id = xdotool(&["getactivewindow"])?;
+    let pid: u32 = xdotool(&["getwindowpid", &window_id])?.parse().ok()?;
+
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    let path = std::fs::read_link(format!("/proc/{}/exe", pid))
+        .ok()
+        .map(|p| p.to_string_lossy().to_string());
+
+    let name = comm.clone().or_else(|| {
+        path.as_ref().and_then(|p| {
+            std::path::Path::new(p)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+        })
+    })?;
+
+    Some(FrontmostApplication {
+        name,
+        identifier: comm,
+        path,
+        window_id: window_id.parse().ok(),
+    })
+}
+
+/// Brings a previously captured application back to the foreground. On Wayland the
+/// window isn't known, so focus is left to the compositor.
+pub fn activate_application(app: &FrontmostApplication) {
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+
+        let Some(bundle_id) = app.identifier.as_deref() else {
+            return;
+        };
+        match Command::new("open").args(["-b", bundle_id]).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!(
+                "Failed to activate {}: open exited with {}",
+                bundle_id, status
+            ),
+            Err(e) => warn!("Failed to activate {}: {}", bundle_id, e),
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::SetForegroundWindow;
+
+        let Some(window_id) = app.window_id else {
+            debug!("No window to reactivate for '{}'", app.name);
+            return;
+        };
+        let hwnd = HWND(window_id as usize as *mut std::ffi::c_void);
+        if !unsafe { SetForegroundWindow(hwnd) }.as_bool() {
+            warn!("Failed to activate {}", app.name);
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::process::Command;
+
+        let Some(window_id) = app.window_id else {
+            debug!("No window to reactivate for '{}'", app.name);
+            return;
+        };
+        match Command::new("xdotool")
+            .args(["windowactivate", &window_id.to_string()])
+            .status()
+        {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!(
+                "Failed to activate {}: xdotool exited with {}",
+                app.name, status
+            ),
+            Err(e) => warn!("Failed to activate {}: {}", app.name, e),
+        }
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        debug!("Not reactivating '{}' on this platform", app.name);
+    }
+}
+
+/// Get the application that currently has focus, for pickers in the settings UI.
+#[tauri::command]
+#[specta::specta]
+pub fn get_frontmost_application() -> Option<FrontmostApplication> {
+    let app = frontmost_application();
+    if app.is_none() {
+        debug!("Frontmost application is not available on this platform/session");
+    }
+    app
+}
@@ -0,0 +1,209 @@
+//! Ending a recording by voice: saying `voice_stop_phrase` and pausing stops the
+//! recording, and the phrase is removed from the transcript.
+//!
+//! While recording, every pause after new speech gets the last few seconds
+//! transcribed, and the recording stops when that ends with the phrase. In a
+//! dictation session the segments are transcribed anyway, so a segment ending
+//! with the phrase ends the session instead.
+//!
+//! To keep false positives down the phrase needs at least two words, must be the
+//! very last thing said and must match word for word, ignoring case and
+//! punctuation only.
+
+use crate::dictation_session;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::transcription::{TranscriptionManager, TranscriptionOptions};
+use crate::settings::{get_settings, DICTATION_SESSION_BINDING};
+use crate::utils;
+use log::{debug, info, warn};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use unicode_segmentation::UnicodeSegmentation;
+
+pub const MIN_PHRASE_WORDS: usize = 2;
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Silence after the phrase before the tail is checked
+const PAUSE: Duration = Duration::from_millis(600);
+/// Audio transcribed to look for the phrase
+const TAIL: Duration = Duration::from_secs(3);
+/// Recordings younger than this aren't checked, the phrase can't end them
+const MIN_RECORDING: Duration = Duration::from_secs(1);
+
+/// Bindings whose recording was stopped by the phrase, so it's removed from
+/// their transcript
+#[derive(Default)]
+pub struct VoiceStop {
+    stopped: Mutex<HashSet<String>>,
+}
+
+/// Lowercase words of `text`, without punctuation
+fn words(text: &str) -> Vec<String> {
+    text.unicode_words().map(str::to_lowercase).collect()
+}
+
+/// Whether `phrase` can be used, see `MIN_PHRASE_WORDS`
+pub fn is_valid_phrase(phrase: &str) -> bool {
+    words(phrase).len() >= MIN_PHRASE_WORDS
+}
+
+pub fn ends_with_phrase(text: &str, phrase: &str) -> bool {
+    let phrase = words(phrase);
+    phrase.len() >= MIN_PHRASE_WORDS && words(text).ends_with(&phrase)
+}
+
+/// `text` without the phrase at its end, along with the spaces and commas before it
+pub fn strip_phrase(text: &str, phrase: &str) -> Option<String> {
+    if !ends_with_phrase(text, phrase) {
+        return None;
+    }
+    let phrase_words = words(phrase).len();
+    // Start of the phrase's first word: the word boundary `phrase_words` words
+    // from the end
+    let start = text
+        .unicode_word_indices()
+        .rev()
+        .nth(phrase_words - 1)
+        .map_or(0, |(start, _)| start);
+    let kept = text[..start].trim_end_matches(|c: char| c.is_whitespace() || c == ',');
+    Some(kept.to_string())
+}
+
+/// Listens for the phrase while `binding_id` records
+pub fn watch(app: &AppHandle, binding_id: &str) {
+    let settings = get_settings(app);
+    // Session segments are checked once transcribed, see `finish_transcript`
+    if !settings.voice_stop_enabled
+        || binding_id == DICTATION_SESSION_BINDING
+        || !is_valid_phrase(&settings.voice_stop_phrase)
+    {
+        return;
+    }
+    let app = app.clone();
+    let binding_id = binding_id.to_string();
+    thread::spawn(move || listen(&app, &binding_id));
+}
+
+fn listen(app: &AppHandle, binding_id: &str) {
+    let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
+    let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
+    let started = Instant::now();
+    let mut previous_silence: Option<Duration> = None;
+    // Each pause is checked once
+    let mut checked = false;
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if rm.active_binding().as_deref() != Some(binding_id) {
+            return;
+        }
+        let Some(silence) = rm.since_speech() else {
+            continue;
+        };
+        if previous_silence.is_some_and(|previous| silence < previous) {
+            checked = false;
+        }
+        previous_silence = Some(silence);
+        if checked || silence < PAUSE || started.elapsed() < MIN_RECORDING {
+            continue;
+        }
+        checked = true;
+
+        let settings = get_settings(app);
+        // Loading a model just for the check would hold up the recording's own
+        // transcription
+        if !tm.is_model_loaded() {
+            continue;
+        }
+        let Some(tail) = rm.recording_tail(TAIL) else {
+            return;
+        };
+        let mut options = TranscriptionOptions::from_settings(&settings);
+        options.translate_to_english = false;
+        options.custom_words.clear();
+        options.custom_words_by_language.clear();
+        options.speaker_change_hints = false;
+        options.retry_count = 0;
+        options.keep_loaded = true;
+        let text = match tm.transcribe_with(tail, &options) {
+            Ok(transcription) => transcription.text,
+            Err(e) => {
+                warn!("Failed to check the recording for the stop phrase: {}", e);
+                continue;
+            }
+        };
+        if !ends_with_phrase(&text, &settings.voice_stop_phrase) {
+            continue;
+        }
+        // The recording may have ended while the tail was transcribed
+        if rm.active_binding().as_deref() != Some(binding_id) {
+            return;
+        }
+        info!("Stop phrase heard, stopping the recording");
+        app.state::<VoiceStop>()
+            .stopped
+            .lock()
+            .unwrap()
+            .insert(binding_id.to_string());
+        if let Err(e) = utils::stop_current_recording(app) {
+            warn!("Failed to stop the recording by voice: {}", e);
+        }
+        return;
+    }
+}
+
+/// Removes the phrase from a transcript of `binding_id` that it ended. A session
+/// segment ending with the phrase also ends the session.
+pub fn finish_transcript(app: &AppHandle, binding_id: &str, text: String) -> String {
+    let settings = get_settings(app);
+    let stopped = app
+        .state::<VoiceStop>()
+        .stopped
+        .lock()
+        .unwrap()
+        .remove(binding_id);
+    let session_segment = binding_id == DICTATION_SESSION_BINDING
+        && settings.voice_stop_enabled
+        && dictation_session::is_active(app);
+    if !stopped && !session_segment {
+        return text;
+    }
+    let Some(stripped) = strip_phrase(&text, &settings.voice_stop_phrase) else {
+        if stopped {
+            debug!("Stop phrase not found in the final transcript, keeping it whole");
+        }
+        return text;
+    };
+    if session_segment {
+        info!("Stop phrase heard, ending the dictation session");
+        let app = app.clone();
+        // Ending waits for the segment being recorded to stop
+        thread::spawn(move || {
+            if let Err(e) = dictation_session::end(&app) {
+                debug!("{}", e);
+            }
+        });
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phrase_is_matched_and_stripped_only_at_the_end() {
+        let phrase = "stop recording";
+        assert_eq!(
+            strip_phrase("Send the email, stop recording.", phrase).as_deref(),
+            Some("Send the email")
+        );
+        assert_eq!(strip_phrase("Stop Recording!", phrase).as_deref(), Some(""));
+        // Said mid-sentence, or only partly
+        assert_eq!(strip_phrase("Stop recording the call now.", phrase), None);
+        assert_eq!(strip_phrase("We keep recording.", phrase), None);
+        assert!(!is_valid_phrase("stop"));
+        assert!(!ends_with_phrase("stop", "stop"));
+    }
+}
@@ -0,0 +1,253 @@
+//! Check before loading a Whisper model that the GPU has room for it.
+//!
+//! A model that doesn't fit in video memory doesn't fail to load: the driver pages
+//! it in and out of system memory and the whole desktop stalls. Free GPU memory is
+//! read from the driver where one reports it, and the model's need is estimated
+//! from its download size. Parakeet and Moonshine run on the CPU and are never
+//! checked.
+//!
+//! Free memory can't be read on macOS, where the GPU shares system memory, or on
+//! Linux without `nvidia-smi` or an amdgpu driver; models load unchecked there.
+//! A model that doesn't fit is refused, swapped for a smaller one or loaded on the
+//! CPU, see `VramGuardBehavior`.
+
+use crate::managers::model::{EngineType, ModelInfo};
+use crate::settings::VramGuardBehavior;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Whisper's compute buffers and key-value cache, on top of the weights
+const WHISPER_OVERHEAD_MB: u64 = 400;
+
+/// A model swapped for a smaller one because it wouldn't fit in GPU memory
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct VramGuardDecision {
+    /// Model that was selected
+    pub requested_model: String,
+    /// Model that was loaded instead
+    pub loaded_model: String,
+    /// Estimated GPU memory the selected model needs
+    pub required_mb: u64,
+    /// GPU memory that was free when loading, counting the model it replaced
+    pub available_mb: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Load,
+    /// Load the model without the GPU
+    LoadOnCpu,
+    /// Load this model instead
+    Substitute(String),
+    /// Don't load, with the reason to show
+    Refuse(String),
+}
+
+/// Estimated GPU memory in MB the model needs, 0 for models run on the CPU
+pub fn required_mb(model: &ModelInfo) -> u64 {
+    match model.engine_type {
+        // The weights plus about a tenth for the buffers that scale with them
+        EngineType::Whisper => model.size_mb + model.size_mb / 10 + WHISPER_OVERHEAD_MB,
+        EngineType::Parakeet | EngineType::Moonshine => 0,
+    }
+}
+
+/// What to load for `requested` with `available_mb` of GPU memory free. A
+/// replacement is the most accurate of the downloaded `models` that fits.
+pub fn decide(
+    requested: &ModelInfo,
+    models: &[ModelInfo],
+    available_mb: u64,
+    behavior: VramGuardBehavior,
+) -> Outcome {
+    let required = required_mb(requested);
+    if behavior == VramGuardBehavior::Off || required <= available_mb {
+        return Outcome::Load;
+    }
+    let shortfall = format!(
+        "{} needs about {} MB of GPU memory but only {} MB is free",
+        requested.name, required, available_mb
+    );
+    match behavior {
+        VramGuardBehavior::Refuse => {
+            return Outcome::Refuse(format!(
+                "{}. Close other apps using the GPU or choose a smaller model.",
+                shortfall
+            ))
+        }
+        VramGuardBehavior::CpuFallback => return Outcome::LoadOnCpu,
+        VramGuardBehavior::Off | VramGuardBehavior::SmallerModel => {}
+    }
+
+    let replacement = models
+        .iter()
+        .filter(|m| m.is_downloaded && m.id != requested.id && required_mb(m) <= available_mb)
+        .max_by(|a, b| a.accuracy_score.total_cmp(&b.accuracy_score));
+    match replacement {
+        Some(model) => Outcome::Substitute(model.id.clone()),
+        None => Outcome::Refuse(format!(
+            "{} and no downloaded model fits. Download a smaller model.",
+            shortfall
+        )),
+    }
+}
+
+/// GPU memory in MB the driver says is free, None when it can't be read
+pub fn available_mb() -> Option<u64> {
+    platform::available_mb()
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::core::Interface;
+    use windows::Win32::Graphics::Dxgi::{
+        CreateDXGIFactory1, IDXGIAdapter3, IDXGIFactory1, DXGI_MEMORY_SEGMENT_GROUP_LOCAL,
+        DXGI_QUERY_VIDEO_MEMORY_INFO,
+    };
+
+    /// Budget left on the primary adapter. The budget is what Windows lets this
+    /// process use alongside every other app on the GPU.
+    pub fn available_mb() -> Option<u64> {
+        unsafe {
+            let factory: IDXGIFactory1 = CreateDXGIFactory1().ok()?;
+            let adapter: IDXGIAdapter3 = factory.EnumAdapters1(0).ok()?.cast().ok()?;
+            let mut info = DXGI_QUERY_VIDEO_MEMORY_INFO::default();
+            adapter
+                .QueryVideoMemoryInfo(0, DXGI_MEMORY_SEGMENT_GROUP_LOCAL, &mut info)
+                .ok()?;
+            Some(info.Budget.saturating_sub(info.CurrentUsage) / (1024 * 1024))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+    use std::process::Command;
+
+    pub fn available_mb() -> Option<u64> {
+        nvidia().or_else(amdgpu)
+    }
+
+    /// Free memory of the first NVIDIA GPU, reported in MiB
+    fn nvidia() -> Option<u64> {
+        let output = Command::new("nvidia-smi")
+            .args(["--query-gpu=memory.free", "--format=csv,noheader,nounits"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout.lines().next()?.trim().parse().ok()
+    }
+
+    /// Free memory of the first AMD GPU, reported in bytes through sysfs
+    fn amdgpu() -> Option<u64> {
+        let mut cards: Vec<_> = fs::read_dir("/sys/class/drm")
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join("device"))
+            .filter(|device| device.join("mem_info_vram_total").exists())
+            .collect();
+        cards.sort();
+        let device = cards.first()?;
+        let read = |name: &str| -> Option<u64> {
+            fs::read_to_string(device.join(name))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        };
+        let free = read("mem_info_vram_total")?.saturating_sub(read("mem_info_vram_used")?);
+        Some(free / (1024 * 1024))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    pub fn available_mb() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::model::QualityTier;
+
+    fn model(id: &str, engine_type: EngineType, size_mb: u64, accuracy: f32) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            filename: String::new(),
+            url: None,
+            size_mb,
+            is_downloaded: true,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: false,
+            engine_type,
+            accuracy_score: accuracy,
+            speed_score: 0.5,
+            variants: Vec::new(),
+            quantization: None,
+            parameters_m: 0,
+            languages: vec!["en".to_string()],
+            quality_tier: QualityTier::Good,
+            recommended_ram_mb: 0,
+            recommended_vram_mb: None,
+            sha256: None,
+        }
+    }
+
+    #[test]
+    fn models_that_dont_fit_are_refused_or_swapped() {
+        let large = model("large", EngineType::Whisper, 1100, 0.9);
+        let small = model("small", EngineType::Whisper, 487, 0.6);
+        let parakeet = model("parakeet", EngineType::Parakeet, 475, 0.8);
+        let models = [large.clone(), small, parakeet.clone()];
+        assert_eq!(required_mb(&large), 1610);
+        assert_eq!(required_mb(&parakeet), 0);
+
+        assert_eq!(
+            decide(&large, &models, 2000, VramGuardBehavior::Refuse),
+            Outcome::Load
+        );
+        assert_eq!(
+            decide(&large, &models, 1000, VramGuardBehavior::Off),
+            Outcome::Load
+        );
+        assert_eq!(
+            decide(&large, &models, 1000, VramGuardBehavior::CpuFallback),
+            Outcome::LoadOnCpu
+        );
+        assert_eq!(
+            decide(&large, &models, 2000, VramGuardBehavior::CpuFallback),
+            Outcome::Load
+        );
+        assert!(matches!(
+            decide(&large, &models, 1000, VramGuardBehavior::Refuse),
+            Outcome::Refuse(reason) if reason.contains("1610 MB") && reason.contains("1000 MB")
+        ));
+        // Parakeet runs on the CPU and is more accurate than small
+        assert_eq!(
+            decide(&large, &models, 1000, VramGuardBehavior::SmallerModel),
+            Outcome::Substitute("parakeet".to_string())
+        );
+        assert_eq!(
+            decide(
+                &large,
+                std::slice::from_ref(&large),
+                1000,
+                VramGuardBehavior::SmallerModel
+            ),
+            Outcome::Refuse(
+                "large needs about 1610 MB of GPU memory but only 1000 MB is free and no \
+                 downloaded model fits. Download a smaller model."
+                    .to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,534 @@
+//! Hands-free recording start: a spoken wake phrase starts the transcribe
+//! action, and the recording stops once the VAD hears no more speech.
+//!
+//! Detection uses openWakeWord's ONNX models: a melspectrogram and a speech
+//! embedding model shared by all phrases, and a small model per phrase scoring
+//! the last second and a half of embeddings. They are listed in the model manifest
+//! and downloaded next to the transcription models on first use.
+//!
+//! The detector only listens between recordings, through the recorder's frame
+//! tap. Frames go through a bounded in-memory queue into the detector's rolling
+//! buffers and are dropped from there; nothing it hears is ever written anywhere.
+
+use crate::actions::ACTION_MAP;
+use crate::audio_toolkit::FrameTap;
+use crate::events::{emit_event, AppEvent, WakeWordDetection};
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::model::ModelManager;
+use crate::settings::{get_settings, AppSettings};
+use crate::utils;
+use crate::ManagedToggleState;
+use log::{debug, error, info, warn};
+use ort::session::Session;
+use ort::value::Tensor;
+use serde::Serialize;
+use specta::Type;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const MELSPECTROGRAM_FILE: &str = "melspectrogram.onnx";
+const EMBEDDING_FILE: &str = "embedding_model.onnx";
+/// Kind of the files in the manifest's auxiliary list, and their directory under
+/// the models directory
+const MODELS_SUBDIR: &str = "wake_word";
+
+/// Wake phrase models: id as in `wake_word_model`, file and the phrase to say
+pub const WAKE_WORD_MODELS: &[(&str, &str, &str)] = &[
+    ("hey_jarvis", "hey_jarvis_v0.1.onnx", "Hey Jarvis"),
+    ("alexa", "alexa_v0.1.onnx", "Alexa"),
+    ("hey_mycroft", "hey_mycroft_v0.1.onnx", "Hey Mycroft"),
+    ("hey_rhasspy", "hey_rhasspy_v0.1.onnx", "Hey Rhasspy"),
+];
+
+/// Audio is scored in steps of 80 ms
+const CHUNK_SAMPLES: usize = 1280;
+/// Audio the melspectrogram needs from before each chunk
+const MEL_CONTEXT_SAMPLES: usize = 480;
+const MEL_BINS: usize = 32;
+/// Melspectrogram frames per embedding
+const EMBEDDING_WINDOW: usize = 76;
+/// Embeddings the phrase model scores at once
+const FEATURE_WINDOW: usize = 16;
+
+/// 30 ms frames queued for the detector, about two seconds; frames arriving
+/// while it's full are dropped rather than held
+const FRAME_QUEUE: usize = 64;
+/// The recording stops after this long without speech
+const SILENCE_TIMEOUT: Duration = Duration::from_millis(1_500);
+/// Longer grace when nothing was said after the wake phrase at all
+const NO_SPEECH_TIMEOUT: Duration = Duration::from_secs(5);
+const SILENCE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long `test_wake_word` listens
+const TEST_DURATION: Duration = Duration::from_secs(10);
+/// Binding a detection starts, as if its shortcut was pressed
+const BINDING: &str = "transcribe";
+
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct WakeWordModel {
+    pub id: String,
+    pub phrase: String,
+}
+
+/// Outcome of `test_wake_word`
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct WakeWordTest {
+    pub model: String,
+    /// Score a detection has to reach at the current sensitivity
+    pub threshold: f32,
+    /// Highest score heard, to tune the sensitivity against
+    pub peak_score: f32,
+    pub detections: Vec<WakeWordDetection>,
+}
+
+pub fn models() -> Vec<WakeWordModel> {
+    WAKE_WORD_MODELS
+        .iter()
+        .map(|(id, _, phrase)| WakeWordModel {
+            id: id.to_string(),
+            phrase: phrase.to_string(),
+        })
+        .collect()
+}
+
+pub fn is_known_model(id: &str) -> bool {
+    WAKE_WORD_MODELS.iter().any(|(known, _, _)| *known == id)
+}
+
+/// Score a detection has to reach; higher sensitivity lowers it
+fn threshold(sensitivity: f32) -> f32 {
+    (1.0 - sensitivity.clamp(0.0, 1.0)).clamp(0.05, 0.95)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Config {
+    model: String,
+    threshold: f32,
+    cooldown: Duration,
+}
+
+impl Config {
+    fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            model: settings.wake_word_model.clone(),
+            threshold: threshold(settings.wake_word_sensitivity),
+            cooldown: Duration::from_millis(settings.wake_word_cooldown_ms as u64),
+        }
+    }
+}
+
+/// Decides which scores count as a detection
+struct Trigger {
+    threshold: f32,
+    cooldown: Duration,
+    last: Option<Instant>,
+}
+
+impl Trigger {
+    fn new(config: &Config) -> Self {
+        Self {
+            threshold: config.threshold,
+            cooldown: config.cooldown,
+            last: None,
+        }
+    }
+
+    fn fire(&mut self, score: f32, now: Instant) -> bool {
+        if score < self.threshold {
+            return false;
+        }
+        if self
+            .last
+            .is_some_and(|last| now.duration_since(last) < self.cooldown)
+        {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+struct ModelPaths {
+    melspectrogram: PathBuf,
+    embedding: PathBuf,
+    phrase: PathBuf,
+}
+
+/// Streaming openWakeWord pipeline over 16 kHz audio
+struct Detector {
+    melspectrogram: Session,
+    embedding: Session,
+    phrase: Session,
+    /// Audio not scored yet, starting with the context the next chunk needs
+    audio: Vec<f32>,
+    mel: VecDeque<Vec<f32>>,
+    features: VecDeque<Vec<f32>>,
+}
+
+fn load_session(path: &Path) -> Result<Session, String> {
+    Session::builder()
+        .and_then(|builder| builder.with_intra_threads(1))
+        .and_then(|builder| builder.commit_from_file(path))
+        .map_err(|e| format!("failed to load {}: {}", path.display(), e))
+}
+
+/// Runs a single-input, single-output model
+fn infer<const N: usize>(
+    session: &mut Session,
+    shape: [usize; N],
+    data: Vec<f32>,
+) -> Result<Vec<f32>, String> {
+    let input = Tensor::from_array((shape, data)).map_err(|e| e.to_string())?;
+    let outputs = session
+        .run(ort::inputs![input])
+        .map_err(|e| e.to_string())?;
+    let (_, values) = outputs[0]
+        .try_extract_tensor::<f32>()
+        .map_err(|e| e.to_string())?;
+    Ok(values.to_vec())
+}
+
+impl Detector {
+    fn load(paths: &ModelPaths) -> Result<Self, String> {
+        Ok(Self {
+            melspectrogram: load_session(&paths.melspectrogram)?,
+            embedding: load_session(&paths.embedding)?,
+            phrase: load_session(&paths.phrase)?,
+            audio: Vec::new(),
+            mel: VecDeque::new(),
+            features: VecDeque::new(),
+        })
+    }
+
+    /// Forgets everything heard so far
+    fn reset(&mut self) {
+        self.audio.clear();
+        self.mel.clear();
+        self.features.clear();
+    }
+
+    /// Adds a frame, returning the phrase score when it completed a chunk and a
+    /// full window of features has been heard
+    fn push(&mut self, frame: &[f32]) -> Result<Option<f32>, String> {
+        // The models were trained on 16-bit sample values
+        self.audio
+            .extend(frame.iter().map(|sample| sample * i16::MAX as f32));
+        if self.audio.len() < MEL_CONTEXT_SAMPLES + CHUNK_SAMPLES {
+            return Ok(None);
+        }
+        let window = self.audio[..MEL_CONTEXT_SAMPLES + CHUNK_SAMPLES].to_vec();
+        self.audio.drain(..CHUNK_SAMPLES);
+
+        let spectrogram = infer(&mut self.melspectrogram, [1, window.len()], window)?;
+        for bins in spectrogram.chunks_exact(MEL_BINS) {
+            // Same scaling openWakeWord applies before the embedding model
+            self.mel
+                .push_back(bins.iter().map(|value| value / 10.0 + 2.0).collect());
+        }
+        while self.mel.len() > EMBEDDING_WINDOW {
+            self.mel.pop_front();
+        }
+        if self.mel.len() < EMBEDDING_WINDOW {
+            return Ok(None);
+        }
+
+        let mel = self.mel.iter().flatten().copied().collect();
+        let embedding = infer(&mut self.embedding, [1, EMBEDDING_WINDOW, MEL_BINS, 1], mel)?;
+        self.features.push_back(embedding);
+        while self.features.len() > FEATURE_WINDOW {
+            self.features.pop_front();
+        }
+        if self.features.len() < FEATURE_WINDOW {
+            return Ok(None);
+        }
+
+        let features: Vec<f32> = self.features.iter().flatten().copied().collect();
+        let size = features.len() / FEATURE_WINDOW;
+        let score = infer(&mut self.phrase, [1, FEATURE_WINDOW, size], features)?;
+        Ok(score.first().copied())
+    }
+}
+
+/// A `test_wake_word` in progress
+struct TestRun {
+    peak_score: f32,
+    detections: Vec<WakeWordDetection>,
+}
+
+#[derive(Default)]
+pub struct WakeWord {
+    /// Configuration the detector runs with, None when it isn't listening
+    listening: Mutex<Option<Config>>,
+    /// Raised by every restart, so a slow model download doesn't start a stale detector
+    generation: AtomicU64,
+    test: Mutex<Option<TestRun>>,
+}
+
+impl WakeWord {
+    fn is_testing(&self) -> bool {
+        self.test.lock().unwrap().is_some()
+    }
+}
+
+/// Starts, reconfigures or stops the detector to match the settings
+pub fn restart(app: &AppHandle) {
+    let settings = get_settings(app);
+    let state = app.state::<WakeWord>();
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if !settings.wake_word_enabled && !state.is_testing() {
+        stop(app);
+        return;
+    }
+    let config = Config::from_settings(&settings);
+    if state.listening.lock().unwrap().as_ref() == Some(&config) {
+        return;
+    }
+    stop(app);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let paths = match ensure_models(&app, &config.model).await {
+            Ok(paths) => paths,
+            Err(e) => {
+                error!("Wake word models unavailable: {}", e);
+                return;
+            }
+        };
+        if app.state::<WakeWord>().generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Err(e) = start(&app, config, &paths) {
+            error!("Failed to start wake word detection: {}", e);
+        }
+    });
+}
+
+fn start(app: &AppHandle, config: Config, paths: &ModelPaths) -> Result<(), String> {
+    let detector = Detector::load(paths)?;
+    let (frame_tx, frame_rx) = mpsc::sync_channel::<Vec<f32>>(FRAME_QUEUE);
+    let tap: FrameTap = Arc::new(move |frame: &[f32]| {
+        let _ = frame_tx.try_send(frame.to_vec());
+    });
+
+    info!("Listening for wake word {}", config.model);
+    *app.state::<WakeWord>().listening.lock().unwrap() = Some(config.clone());
+    let listener_app = app.clone();
+    thread::spawn(move || listen(&listener_app, detector, &config, frame_rx));
+
+    let tapped = app
+        .state::<Arc<AudioRecordingManager>>()
+        .set_frame_tap(Some(tap));
+    if let Err(e) = tapped {
+        stop(app);
+        return Err(e.to_string());
+    }
+    Ok(())
+}
+
+/// Removes the tap, which ends the detector thread once it drained its queue
+fn stop(app: &AppHandle) {
+    if app
+        .state::<WakeWord>()
+        .listening
+        .lock()
+        .unwrap()
+        .take()
+        .is_none()
+    {
+        return;
+    }
+    info!("Stopped listening for the wake word");
+    if let Err(e) = app
+        .state::<Arc<AudioRecordingManager>>()
+        .set_frame_tap(None)
+    {
+        warn!("Failed to remove the wake word listener: {}", e);
+    }
+}
+
+fn listen(app: &AppHandle, mut detector: Detector, config: &Config, frames: Receiver<Vec<f32>>) {
+    let state = app.state::<WakeWord>();
+    let mut trigger = Trigger::new(config);
+    while let Ok(frame) = frames.recv() {
+        let score = match detector.push(&frame) {
+            Ok(Some(score)) => score,
+            Ok(None) => continue,
+            Err(e) => {
+                error!("Wake word detection failed: {}", e);
+                stop(app);
+                return;
+            }
+        };
+        if let Some(test) = state.test.lock().unwrap().as_mut() {
+            test.peak_score = test.peak_score.max(score);
+        }
+        if !trigger.fire(score, Instant::now()) {
+            continue;
+        }
+        // Audio leading up to the detection mustn't trigger again afterwards
+        detector.reset();
+        on_detection(app, config, score);
+    }
+    debug!("Wake word detector stopped");
+}
+
+fn on_detection(app: &AppHandle, config: &Config, score: f32) {
+    let mut test = app.state::<WakeWord>().test.lock().unwrap();
+    let detection = WakeWordDetection {
+        model: config.model.clone(),
+        score,
+        test: test.is_some(),
+    };
+    info!("Wake word {} detected (score {:.2})", config.model, score);
+    emit_event(app, AppEvent::WakeWordDetected(detection.clone()));
+    if let Some(test) = test.as_mut() {
+        test.detections.push(detection);
+        return;
+    }
+    drop(test);
+    start_recording(app);
+}
+
+fn start_recording(app: &AppHandle) {
+    let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
+    if rm.is_recording() {
+        return;
+    }
+    let Some(action) = ACTION_MAP.get(BINDING) else {
+        return;
+    };
+    // A toggle press on the shortcut then stops the recording
+    if let Ok(mut states) = app.state::<ManagedToggleState>().lock() {
+        states.active_toggles.insert(BINDING.to_string(), true);
+    }
+    action.start(app, BINDING, "wake_word");
+    if rm.is_recording() {
+        let app = app.clone();
+        thread::spawn(move || stop_on_silence(&app, &rm));
+    }
+}
+
+/// Stops the recording once speech went quiet, there's no key release to wait for
+fn stop_on_silence(app: &AppHandle, rm: &AudioRecordingManager) {
+    let started = Instant::now();
+    loop {
+        thread::sleep(SILENCE_POLL_INTERVAL);
+        if rm.active_binding().as_deref() != Some(BINDING) {
+            // Stopped or cancelled some other way
+            return;
+        }
+        let quiet = match rm.since_speech() {
+            Some(silence) => silence >= SILENCE_TIMEOUT,
+            None => started.elapsed() >= NO_SPEECH_TIMEOUT,
+        };
+        if quiet {
+            debug!("Stopping the wake word recording after silence");
+            if let Err(e) = utils::stop_current_recording(app) {
+                warn!("Failed to stop the wake word recording: {}", e);
+            }
+            return;
+        }
+    }
+}
+
+/// Listens for the wake phrase for a few seconds, reporting detections instead of
+/// recording. Works whether or not the wake word is enabled.
+pub async fn test(app: &AppHandle) -> Result<WakeWordTest, String> {
+    let settings = get_settings(app);
+    ensure_models(app, &settings.wake_word_model).await?;
+    {
+        let mut test = app.state::<WakeWord>().test.lock().unwrap();
+        if test.is_some() {
+            return Err("A wake word test is already running".to_string());
+        }
+        *test = Some(TestRun {
+            peak_score: 0.0,
+            detections: Vec::new(),
+        });
+    }
+    restart(app);
+    tokio::time::sleep(TEST_DURATION).await;
+
+    let run = app.state::<WakeWord>().test.lock().unwrap().take();
+    // Stops the detector again unless the wake word is enabled
+    restart(app);
+    let run = run.ok_or_else(|| "The wake word test was interrupted".to_string())?;
+    Ok(WakeWordTest {
+        model: settings.wake_word_model.clone(),
+        threshold: threshold(settings.wake_word_sensitivity),
+        peak_score: run.peak_score,
+        detections: run.detections,
+    })
+}
+
+/// Paths of the models for `model`, downloading the missing ones
+async fn ensure_models(app: &AppHandle, model: &str) -> Result<ModelPaths, String> {
+    let phrase_file = WAKE_WORD_MODELS
+        .iter()
+        .find(|(id, _, _)| *id == model)
+        .map(|(_, file, _)| *file)
+        .ok_or_else(|| format!("Unknown wake word model '{}'", model))?;
+    let mm = Arc::clone(&app.state::<Arc<ModelManager>>());
+    let ensure = |file: &'static str| {
+        let mm = Arc::clone(&mm);
+        async move {
+            mm.ensure_auxiliary(MODELS_SUBDIR, file)
+                .await
+                .map_err(|e| e.to_string())
+        }
+    };
+    Ok(ModelPaths {
+        melspectrogram: ensure(MELSPECTROGRAM_FILE).await?,
+        embedding: ensure(EMBEDDING_FILE).await?,
+        phrase: ensure(phrase_file).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detections_respect_threshold_and_cooldown() {
+        let config = Config {
+            model: "hey_jarvis".to_string(),
+            threshold: threshold(0.5),
+            cooldown: Duration::from_secs(3),
+        };
+        let mut trigger = Trigger::new(&config);
+        let start = Instant::now();
+        assert!(!trigger.fire(0.3, start));
+        assert!(trigger.fire(0.8, start));
+        assert!(!trigger.fire(0.9, start + Duration::from_secs(1)));
+        assert!(trigger.fire(0.9, start + Duration::from_secs(4)));
+
+        assert_eq!(threshold(0.0), 0.95);
+        assert_eq!(threshold(2.0), 0.05);
+        assert!(is_known_model("alexa"));
+        assert!(!is_known_model("computer"));
+    }
+
+    #[test]
+    fn every_model_file_can_be_downloaded() {
+        let manifest =
+            crate::model_manifest::parse(include_str!("../resources/model_manifest.json"))
+                .expect("bundled manifest");
+        let shared = [MELSPECTROGRAM_FILE, EMBEDDING_FILE];
+        let phrases = WAKE_WORD_MODELS.iter().map(|(_, file, _)| *file);
+        for file in shared.into_iter().chain(phrases) {
+            assert!(
+                manifest
+                    .auxiliary
+                    .iter()
+                    .any(|aux| aux.kind == MODELS_SUBDIR && aux.filename == file),
+                "{} is missing from the manifest",
+                file
+            );
+        }
+    }
+}
@@ -0,0 +1,533 @@
+//! Transcribes audio files that appear in watched folders, such as voice memos
+//! synced from a phone.
+//!
+//! Each enabled folder gets a file system watcher. A file is picked up once it has
+//! been quiet for a moment and its size didn't change between two checks, so files
+//! still being copied aren't read half-written. Files are then transcribed one at a
+//! time on a worker thread that steps aside while a dictation is recording, in
+//! short chunks so a dictation never waits long for the model. A transcribed file
+//! is remembered by a hash of its path and modification time, so a restart only
+//! picks up what is new and an edited file is transcribed again. Files deleted or
+//! edited since are forgotten whenever watching (re)starts.
+
+use crate::actions::maybe_post_process_transcription;
+use crate::audio_toolkit::{read_audio_file, AudioFormat};
+use crate::error::HandyError;
+use crate::events::{emit_event, AppEvent, WatchFolderError};
+use crate::file_dictation;
+use crate::llm_client::RequestPriority;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::history::{HistoryManager, NewHistoryEntry};
+use crate::managers::transcription::{TranscriptionManager, TranscriptionOptions};
+use crate::paths;
+use crate::settings::{get_settings, WatchFolderConfig, WatchFolderOutput};
+use log::{debug, info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// How long a file must go without changes before its size is checked
+const SETTLE: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Longest chunk a file is transcribed in, the engine is free between chunks
+const MAX_CHUNK_SECONDS: u32 = 30;
+/// Hashes of transcribed files, in the app data directory
+const PROCESSED_FILE: &str = "watch_folders_processed.json";
+
+type FolderEvent = (String, notify::Result<notify::Event>);
+
+struct Job {
+    folder: WatchFolderConfig,
+    path: PathBuf,
+    key: u64,
+}
+
+/// A file that changed recently and is waiting to settle
+struct Pending {
+    folder_id: String,
+    changed_at: Instant,
+    /// Size at the last check, None until it was checked once
+    size: Option<u64>,
+}
+
+impl Pending {
+    fn new(folder_id: &str) -> Self {
+        Self {
+            folder_id: folder_id.to_string(),
+            changed_at: Instant::now(),
+            size: None,
+        }
+    }
+}
+
+/// Watchers of the enabled folders, dropped to stop watching
+struct Session {
+    _watchers: Vec<RecommendedWatcher>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A transcribed file in the store. Files transcribed before paths were stored
+/// only have their key.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredFile {
+    Key(u64),
+    File { key: u64, path: PathBuf },
+}
+
+struct ProcessedFiles {
+    store: Option<PathBuf>,
+    /// File versions by `file_key`, with the file they were read from
+    keys: HashMap<u64, Option<PathBuf>>,
+}
+
+impl ProcessedFiles {
+    fn load(store: Option<PathBuf>) -> Self {
+        let files = store
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str::<Vec<StoredFile>>(&json).ok())
+            .unwrap_or_default();
+        let keys = files
+            .into_iter()
+            .map(|file| match file {
+                StoredFile::Key(key) => (key, None),
+                StoredFile::File { key, path } => (key, Some(path)),
+            })
+            .collect();
+        Self { store, keys }
+    }
+
+    fn contains(&self, key: u64) -> bool {
+        self.keys.contains_key(&key)
+    }
+
+    fn insert(&mut self, key: u64, path: &Path) {
+        if self.keys.insert(key, Some(path.to_path_buf())).is_none() {
+            self.save();
+        }
+    }
+
+    /// Forgets files that were deleted or changed since, `current_key` gives the
+    /// key of a file as it is now
+    fn prune(&mut self, current_key: impl Fn(&Path) -> Option<u64>) {
+        let before = self.keys.len();
+        self.keys.retain(|key, path| {
+            path.as_deref()
+                .is_none_or(|path| current_key(path) == Some(*key))
+        });
+        if self.keys.len() < before {
+            debug!(
+                "Forgot {} deleted or changed watch folder files",
+                before - self.keys.len()
+            );
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let files: Vec<StoredFile> = self
+            .keys
+            .iter()
+            .map(|(key, path)| match path {
+                Some(path) => StoredFile::File {
+                    key: *key,
+                    path: path.clone(),
+                },
+                None => StoredFile::Key(*key),
+            })
+            .collect();
+        let saved = serde_json::to_string(&files)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(store, json).map_err(|e| e.to_string()));
+        if let Err(e) = saved {
+            warn!("Failed to save transcribed watch folder files: {}", e);
+        }
+    }
+}
+
+pub struct WatchFolders {
+    session: Mutex<Option<Session>>,
+    jobs: Sender<Job>,
+    processed: Arc<Mutex<ProcessedFiles>>,
+}
+
+impl WatchFolders {
+    /// Starts the worker that transcribes picked up files; watching starts with `restart`
+    pub fn new(app: &AppHandle) -> Self {
+        let store = Some(paths::get(app).data.join(PROCESSED_FILE));
+        let processed = Arc::new(Mutex::new(ProcessedFiles::load(store)));
+        let (jobs, queue) = mpsc::channel();
+        let app = app.clone();
+        let worker_processed = Arc::clone(&processed);
+        thread::spawn(move || run_jobs(&app, queue, &worker_processed));
+        Self {
+            session: Mutex::new(None),
+            jobs,
+            processed,
+        }
+    }
+}
+
+/// Stable across runs and Rust versions, unlike the standard library hasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Identifies a file version by its path and modification time
+fn file_key(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    let mut bytes = path.to_string_lossy().into_owned().into_bytes();
+    bytes.push(0);
+    bytes.extend_from_slice(&nanos.to_le_bytes());
+    Some(fnv1a(&bytes))
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    AudioFormat::from_path(path).is_some() && path.is_file()
+}
+
+fn report(app: &AppHandle, folder_id: &str, path: Option<&Path>, error: String) {
+    warn!("Watch folder {} error: {}", folder_id, error);
+    emit_event(
+        app,
+        AppEvent::WatchFolderError(WatchFolderError {
+            folder_id: folder_id.to_string(),
+            path: path.map(|p| p.to_string_lossy().to_string()),
+            error,
+        }),
+    );
+}
+
+/// (Re)starts watching the enabled folders in the settings
+pub fn restart(app: &AppHandle) {
+    let state = app.state::<WatchFolders>();
+    let mut session = state.session.lock().unwrap();
+    *session = None;
+    state.processed.lock().unwrap().prune(file_key);
+
+    let folders: Vec<WatchFolderConfig> = get_settings(app)
+        .watch_folders
+        .into_iter()
+        .filter(|f| f.enabled)
+        .collect();
+    if folders.is_empty() {
+        return;
+    }
+
+    let (events, received) = mpsc::channel();
+    let mut watchers = Vec::new();
+    for folder in &folders {
+        match watch_folder(folder, events.clone()) {
+            Ok(watcher) => watchers.push(watcher),
+            Err(e) => report(app, &folder.id, None, e.to_string()),
+        }
+    }
+    info!("Watching {} folder(s) for new recordings", watchers.len());
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = Arc::clone(&stop);
+    let app = app.clone();
+    thread::spawn(move || watch(&app, &folders, received, &thread_stop));
+    *session = Some(Session {
+        _watchers: watchers,
+        stop,
+    });
+}
+
+fn watch_folder(
+    folder: &WatchFolderConfig,
+    events: Sender<FolderEvent>,
+) -> notify::Result<RecommendedWatcher> {
+    let folder_id = folder.id.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let _ = events.send((folder_id.clone(), event));
+    })?;
+    watcher.watch(Path::new(&folder.path), RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+fn watch(
+    app: &AppHandle,
+    folders: &[WatchFolderConfig],
+    events: Receiver<FolderEvent>,
+    stop: &AtomicBool,
+) {
+    let mut pending: HashMap<PathBuf, Pending> = HashMap::new();
+    // Files that arrived while the app wasn't running
+    for folder in folders {
+        let Ok(entries) = fs::read_dir(&folder.path) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if is_audio_file(&path) {
+                pending.insert(path, Pending::new(&folder.id));
+            }
+        }
+    }
+
+    while !stop.load(Ordering::Relaxed) {
+        match events.recv_timeout(POLL_INTERVAL) {
+            Ok((folder_id, Ok(event))) => {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    continue;
+                }
+                for path in event.paths {
+                    if is_audio_file(&path) {
+                        pending.insert(path, Pending::new(&folder_id));
+                    }
+                }
+            }
+            Ok((folder_id, Err(e))) => report(app, &folder_id, None, e.to_string()),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let size_of = |path: &Path| fs::metadata(path).ok().map(|m| m.len());
+        for (path, folder_id) in settle(&mut pending, Instant::now(), size_of) {
+            if let Some(folder) = folders.iter().find(|f| f.id == folder_id) {
+                enqueue(app, folder, path);
+            }
+        }
+    }
+    debug!("Stopped watching folders");
+}
+
+/// Takes the files whose size held still since the last check, as `(path, folder id)`
+fn settle(
+    pending: &mut HashMap<PathBuf, Pending>,
+    now: Instant,
+    size_of: impl Fn(&Path) -> Option<u64>,
+) -> Vec<(PathBuf, String)> {
+    let mut ready = Vec::new();
+    pending.retain(|path, file| {
+        if now.duration_since(file.changed_at) < SETTLE {
+            return true;
+        }
+        match size_of(path) {
+            // Deleted or moved away
+            None => false,
+            Some(size) if file.size == Some(size) => {
+                ready.push((path.clone(), file.folder_id.clone()));
+                false
+            }
+            Some(size) => {
+                file.size = Some(size);
+                file.changed_at = now;
+                true
+            }
+        }
+    });
+    ready
+}
+
+fn enqueue(app: &AppHandle, folder: &WatchFolderConfig, path: PathBuf) {
+    let state = app.state::<WatchFolders>();
+    let Some(key) = file_key(&path) else {
+        return;
+    };
+    if state.processed.lock().unwrap().contains(key) {
+        return;
+    }
+    debug!("Queued {} for transcription", path.display());
+    let _ = state.jobs.send(Job {
+        folder: folder.clone(),
+        path,
+        key,
+    });
+}
+
+fn run_jobs(app: &AppHandle, queue: Receiver<Job>, processed: &Mutex<ProcessedFiles>) {
+    for job in queue {
+        // A file can be queued twice when it changes while waiting
+        if processed.lock().unwrap().contains(job.key) {
+            continue;
+        }
+        // Dictation goes first, it would otherwise wait for the model
+        let recorder = app.state::<Arc<AudioRecordingManager>>();
+        while recorder.is_recording() {
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        match transcribe_file(app, &job) {
+            Ok(()) => processed.lock().unwrap().insert(job.key, &job.path),
+            Err(e) => report(app, &job.folder.id, Some(&job.path), e.to_string()),
+        }
+    }
+}
+
+fn transcribe_file(app: &AppHandle, job: &Job) -> Result<(), HandyError> {
+    info!("Transcribing {} from a watch folder", job.path.display());
+    let audio = read_audio_file(&job.path)
+        .map_err(|e| HandyError::io(format!("Failed to read {}: {}", job.path.display(), e)))?;
+
+    let tm = app.state::<Arc<TranscriptionManager>>();
+    tm.initiate_model_load();
+    // Long memos always go in chunks, so a dictation waits at most for one of them
+    let mut options = TranscriptionOptions::from_settings(&get_settings(app));
+    options.chunk_seconds = match options.chunk_seconds {
+        0 => MAX_CHUNK_SECONDS,
+        seconds => seconds.min(MAX_CHUNK_SECONDS),
+    };
+    let transcription = tm.transcribe_with(audio.clone(), &options)?;
+    if transcription.text.is_empty() {
+        info!("{} contains no speech", job.path.display());
+        return Ok(());
+    }
+
+    let mut settings = get_settings(app);
+    settings.post_process_enabled = job.folder.post_process;
+    let post_processed_text = tauri::async_runtime::block_on(maybe_post_process_transcription(
+        &settings,
+        &transcription.text,
+        RequestPriority::Batch,
+    ));
+    let post_process_prompt = post_processed_text.as_ref().and_then(|_| {
+        settings
+            .resolve_post_process_prompt()
+            .ok()
+            .map(|prompt| prompt.prompt.clone())
+    });
+
+    let text = post_processed_text.as_ref().unwrap_or(&transcription.text);
+    let output_file = write_output(
+        &job.folder,
+        &job.path,
+        text,
+        &settings.file_dictation_separator,
+    )?;
+
+    let hm = app.state::<Arc<HistoryManager>>();
+    tauri::async_runtime::block_on(hm.save_transcription(
+        audio,
+        NewHistoryEntry {
+            transcription_text: transcription.text,
+            post_processed_text,
+            post_process_prompt,
+            translation_check: transcription.translation_check,
+            output_file,
+            word_corrections: transcription.word_corrections,
+            speaker_turns: transcription.speaker_turns,
+            vram_guard: transcription.vram_guard,
+            ..Default::default()
+        },
+    ))?;
+    Ok(())
+}
+
+/// Writes the transcript where the folder wants it, returning the file written to
+fn write_output(
+    folder: &WatchFolderConfig,
+    audio_path: &Path,
+    text: &str,
+    separator: &str,
+) -> Result<Option<String>, HandyError> {
+    let path = match folder.output {
+        WatchFolderOutput::HistoryOnly => return Ok(None),
+        WatchFolderOutput::Sidecar => {
+            let path = audio_path.with_extension("txt");
+            fs::write(&path, text)?;
+            path
+        }
+        WatchFolderOutput::Journal => {
+            let path = folder
+                .journal_path
+                .as_deref()
+                .map(PathBuf::from)
+                .ok_or_else(|| HandyError::config("No journal file is set for this folder"))?;
+            file_dictation::append_to(&path, text, separator)?;
+            path
+        }
+    };
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_ready_once_their_size_holds_still() {
+        let path = PathBuf::from("/memos/note.wav");
+        let mut pending = HashMap::new();
+        let start = Instant::now();
+        pending.insert(
+            path.clone(),
+            Pending {
+                folder_id: "folder_1".to_string(),
+                changed_at: start,
+                size: None,
+            },
+        );
+
+        // Too recent to check
+        assert!(settle(&mut pending, start + SETTLE / 2, |_| Some(10)).is_empty());
+        // First check records the size
+        let first_check = start + SETTLE;
+        assert!(settle(&mut pending, first_check, |_| Some(10)).is_empty());
+        // Still growing
+        let second_check = first_check + SETTLE;
+        assert!(settle(&mut pending, second_check, |_| Some(20)).is_empty());
+        let ready = settle(&mut pending, second_check + SETTLE, |_| Some(20));
+        assert_eq!(ready, vec![(path, "folder_1".to_string())]);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn processed_files_survive_a_reload() {
+        let store =
+            std::env::temp_dir().join(format!("handy-watch-processed-{}.json", std::process::id()));
+        let _ = fs::remove_file(&store);
+        let key = fnv1a(b"/memos/note.wav");
+        assert_eq!(key, fnv1a(b"/memos/note.wav"));
+        assert_ne!(key, fnv1a(b"/memos/note2.wav"));
+
+        // Written before paths were stored
+        fs::write(&store, format!("[{}]", key)).unwrap();
+        let mut processed = ProcessedFiles::load(Some(store.clone()));
+        processed.insert(fnv1a(b"v1"), Path::new("/memos/note2.wav"));
+        let reloaded = ProcessedFiles::load(Some(store.clone()));
+        assert!(reloaded.contains(key));
+        assert!(reloaded.contains(fnv1a(b"v1")));
+        let _ = fs::remove_file(&store);
+    }
+
+    #[test]
+    fn deleted_and_changed_files_are_forgotten() {
+        let mut processed = ProcessedFiles::load(None);
+        processed.insert(1, Path::new("/memos/kept.wav"));
+        processed.insert(2, Path::new("/memos/deleted.wav"));
+        processed.insert(3, Path::new("/memos/edited.wav"));
+        processed.keys.insert(4, None);
+
+        processed.prune(|path| match path.to_str() {
+            Some("/memos/kept.wav") => Some(1),
+            Some("/memos/edited.wav") => Some(5),
+            _ => None,
+        });
+        let mut kept: Vec<u64> = processed.keys.keys().copied().collect();
+        kept.sort();
+        assert_eq!(kept, vec![1, 4]);
+    }
+}
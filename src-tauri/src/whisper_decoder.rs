@@ -0,0 +1,102 @@
+//! Whisper inference with the decoding settings applied. transcribe-rs'
+//! `WhisperEngine` fixes the sampling strategy, so Whisper models are run through
+//! whisper-rs directly.
+
+use crate::settings::DecodingParams;
+use std::path::Path;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperError,
+    WhisperState,
+};
+
+/// A loaded Whisper model. The state keeps the model's context alive.
+pub struct WhisperDecoder {
+    state: WhisperState,
+}
+
+impl WhisperDecoder {
+    /// Loads the model, on the GPU if `use_gpu` and whisper.cpp was built with one
+    pub fn load(model_path: &Path, use_gpu: bool) -> Result<Self, WhisperError> {
+        let params = WhisperContextParameters {
+            use_gpu,
+            ..Default::default()
+        };
+        let context = WhisperContext::new_with_params(&model_path.to_string_lossy(), params)?;
+        let state = context.create_state()?;
+        Ok(Self { state })
+    }
+
+    /// Transcribes 16 kHz mono `samples`. `language` is an ISO 639-1 code, None
+    /// detects it.
+    pub fn transcribe(
+        &mut self,
+        samples: &[f32],
+        language: Option<&str>,
+        translate: bool,
+        decoding: &DecodingParams,
+    ) -> Result<String, WhisperError> {
+        let mut params = FullParams::new(sampling_strategy(decoding));
+        params.set_language(language);
+        params.set_translate(translate);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_suppress_blank(true);
+        params.set_suppress_non_speech_tokens(true);
+        params.set_temperature(decoding.temperature);
+        params.set_temperature_inc(decoding.temperature_increment_on_fallback);
+        params.set_no_speech_thold(decoding.no_speech_threshold);
+        params.set_no_context(!decoding.condition_on_previous_text);
+
+        self.state.full(params, samples)?;
+
+        let mut text = String::new();
+        for segment in 0..self.state.full_n_segments()? {
+            text.push_str(&self.state.full_get_segment_text(segment)?);
+        }
+        Ok(text.trim().to_string())
+    }
+}
+
+/// Beam search for beams wider than one, otherwise greedy decoding
+fn sampling_strategy(decoding: &DecodingParams) -> SamplingStrategy {
+    if decoding.beam_size > 1 {
+        SamplingStrategy::BeamSearch {
+            beam_size: decoding.beam_size as i32,
+            // Not implemented in whisper.cpp, -1 is its default
+            patience: -1.0,
+        }
+    } else {
+        SamplingStrategy::Greedy {
+            best_of: decoding.best_of as i32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beam_size_picks_the_sampling_strategy() {
+        let decoding = DecodingParams {
+            beam_size: 4,
+            ..Default::default()
+        };
+        assert!(matches!(
+            sampling_strategy(&decoding),
+            SamplingStrategy::BeamSearch { beam_size: 4, .. }
+        ));
+
+        let decoding = DecodingParams {
+            beam_size: 1,
+            best_of: 3,
+            ..Default::default()
+        };
+        assert!(matches!(
+            sampling_strategy(&decoding),
+            SamplingStrategy::Greedy { best_of: 3 }
+        ));
+    }
+}
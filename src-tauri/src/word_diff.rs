@@ -0,0 +1,197 @@
+//! Word-level diff between the raw and the post-processed transcript.
+//!
+//! Transcripts are split at Unicode word boundaries, each token keeping the
+//! whitespace after it, and compared token by token like `git diff --word-diff`,
+//! ignoring whitespace. Leaving out the deletions, the spans concatenate back to
+//! the post-processed text.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Above this many tokens on either side the diff is skipped; the comparison is
+/// quadratic in the token counts
+const MAX_DIFF_WORDS: usize = 2_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct DiffSpan {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Type)]
+pub struct WordDiff {
+    pub spans: Vec<DiffSpan>,
+    /// The texts were too long to compare and `spans` is empty
+    pub skipped: bool,
+}
+
+/// Words and punctuation marks with the whitespace that follows them, leading
+/// whitespace stays with the first token. Scripts written without spaces, such as
+/// Chinese and Japanese, come out a character at a time (UAX #29).
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut has_content = false;
+    for (i, segment) in text.split_word_bound_indices() {
+        let is_space = segment.chars().all(char::is_whitespace);
+        if !is_space && has_content {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        has_content |= !is_space;
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+pub fn diff_words(old: &str, new: &str) -> WordDiff {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    if old_tokens.len() > MAX_DIFF_WORDS || new_tokens.len() > MAX_DIFF_WORDS {
+        return WordDiff {
+            spans: Vec::new(),
+            skipped: true,
+        };
+    }
+
+    let old_words: Vec<&str> = old_tokens.iter().map(|t| t.trim()).collect();
+    let new_words: Vec<&str> = new_tokens.iter().map(|t| t.trim()).collect();
+
+    // Longest common subsequence lengths of every pair of suffixes
+    let (n, m) = (old_words.len(), new_words.len());
+    let mut lcs = vec![0u16; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if old_words[i] == new_words[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut spans: Vec<DiffSpan> = Vec::new();
+    let mut push = |op: DiffOp, token: &str| match spans.last_mut() {
+        Some(last) if last.op == op => last.text.push_str(token),
+        _ => spans.push(DiffSpan {
+            op,
+            text: token.to_string(),
+        }),
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_words[i] == new_words[j] {
+            push(DiffOp::Equal, new_tokens[j]);
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[at(i + 1, j)] >= lcs[at(i, j + 1)]) {
+            // Deletions go first, so a replaced word reads as old then new
+            push(DiffOp::Delete, old_tokens[i]);
+            i += 1;
+        } else {
+            push(DiffOp::Insert, new_tokens[j]);
+            j += 1;
+        }
+    }
+
+    WordDiff {
+        spans,
+        skipped: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(op: DiffOp, text: &str) -> DiffSpan {
+        DiffSpan {
+            op,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_marks_changed_words() {
+        let diff = diff_words("um so the café is open", "So the café is closed.");
+        assert!(!diff.skipped);
+        assert_eq!(
+            diff.spans,
+            vec![
+                span(DiffOp::Delete, "um so "),
+                span(DiffOp::Insert, "So "),
+                span(DiffOp::Equal, "the café is "),
+                span(DiffOp::Delete, "open"),
+                span(DiffOp::Insert, "closed."),
+            ]
+        );
+    }
+
+    #[test]
+    fn unicode_words_compare_whole() {
+        let new = "東京 へ 行きます naïve résumé ✅";
+        let diff = diff_words("東京 に 行きます  naïve résumé", new);
+        assert_eq!(
+            diff.spans,
+            vec![
+                span(DiffOp::Equal, "東京 "),
+                span(DiffOp::Delete, "に "),
+                span(DiffOp::Insert, "へ "),
+                span(DiffOp::Equal, "行きます naïve résumé "),
+                span(DiffOp::Insert, "✅"),
+            ]
+        );
+        let rebuilt: String = diff
+            .spans
+            .iter()
+            .filter(|s| s.op != DiffOp::Delete)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(rebuilt, new);
+    }
+
+    #[test]
+    fn text_without_spaces_is_compared_per_character() {
+        let diff = diff_words("我今天去学校。", "我明天去学校。");
+        assert_eq!(
+            diff.spans,
+            vec![
+                span(DiffOp::Equal, "我"),
+                span(DiffOp::Delete, "今"),
+                span(DiffOp::Insert, "明"),
+                span(DiffOp::Equal, "天去学校。"),
+            ]
+        );
+
+        // Punctuation is its own token, a changed mark leaves the word equal
+        let diff = diff_words("see you, then", "see you. Then");
+        assert_eq!(
+            diff.spans,
+            vec![
+                span(DiffOp::Equal, "see you"),
+                span(DiffOp::Delete, ", then"),
+                span(DiffOp::Insert, ". Then"),
+            ]
+        );
+    }
+
+    #[test]
+    fn long_texts_are_skipped() {
+        let long = "word ".repeat(MAX_DIFF_WORDS + 1);
+        let diff = diff_words(&long, "word");
+        assert!(diff.skipped);
+        assert!(diff.spans.is_empty());
+    }
+}
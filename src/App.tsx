@@ -3,6 +3,7 @@ import { Toaster } from "sonner";
 import "./App.css";
 import AccessibilityPermissions from "./components/AccessibilityPermissions";
 import Footer from "./components/footer";
+import { MicrophoneMutedNotice } from "./components/MicrophoneMutedNotice";
 import Onboarding, { AccessibilityOnboarding } from "./components/onboarding";
 import { Sidebar, SidebarSection, SECTIONS_CONFIG } from "./components/Sidebar";
 import { useSettings } from "./hooks/useSettings";
@@ -159,6 +160,7 @@ function App() {
           },
         }}
       />
+      <MicrophoneMutedNotice />
       {/* Main content area: Grid layout for Sidebar + Scrollable Content */}
       <div className="flex-1 grid grid-cols-[160px_1fr] grid-rows-[1fr] min-h-0 overflow-hidden">
         <Sidebar
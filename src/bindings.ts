@@ -5,7 +5,7 @@
 
 
 export const commands = {
-async changeBinding(id: string, binding: string) : Promise<Result<BindingResponse, string>> {
+async changeBinding(id: string, binding: string) : Promise<Result<BindingResponse, HandyError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("change_binding", { id, binding }) };
 } catch (e) {
@@ -13,7 +13,7 @@ async changeBinding(id: string, binding: string) : Promise<Result<BindingRespons
     else return { status: "error", error: e  as any };
 }
 },
-async resetBinding(id: string) : Promise<Result<BindingResponse, string>> {
+async resetBinding(id: string) : Promise<Result<BindingResponse, HandyError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("reset_binding", { id }) };
 } catch (e) {
@@ -21,7 +21,7 @@ async resetBinding(id: string) : Promise<Result<BindingResponse, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async changePttSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changePttSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("change_ptt_setting", { enabled }) };
 } catch (e) {
@@ -29,7 +29,18 @@ async changePttSetting(enabled: boolean) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async changeAudioFeedbackSetting(enabled: boolean) : Promise<Result<null, string>> {
+/**
+ * Swallow other key presses while a push-to-talk binding is held (macOS and Windows)
+ */
+async changeSuppressKeysDuringPttSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_suppress_keys_during_ptt_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAudioFeedbackSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("change_audio_feedback_setting", { enabled }) };
 } catch (e) {
@@ -37,7 +48,7 @@ async changeAudioFeedbackSetting(enabled: boolean) : Promise<Result<null, string
     else return { status: "error", error: e  as any };
 }
 },
-async changeAudioFeedbackVolumeSetting(volume: number) : Promise<Result<null, string>> {
+async changeAudioFeedbackVolumeSetting(volume: number) : Promise<Result<null, HandyError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("change_audio_feedback_volume_setting", { volume }) };
 } catch (e) {
@@ -45,648 +56,1860 @@ async changeAudioFeedbackVolumeSetting(volume: number) : Promise<Result<null, st
     else return { status: "error", error: e  as any };
 }
 },
-async changeSoundThemeSetting(theme: string) : Promise<Result<null, string>> {
+async changeFeedbackTimingSetting(timing: FeedbackTiming) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_sound_theme_setting", { theme }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_feedback_timing_setting", { timing }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeStartHiddenSetting(enabled: boolean) : Promise<Result<null, string>> {
+/**
+ * Sets the silence that ends a dictation session segment, clamped to 300ms ..= 10s
+ */
+async changeDictationSessionPauseSetting(pauseMs: number) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_start_hidden_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_dictation_session_pause_setting", { pauseMs }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeAutostartSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changeOverlapTranscriptionSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_autostart_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_overlap_transcription_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeTranslateToEnglishSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changeVoiceStopSetting(enabled: boolean, phrase: string | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_translate_to_english_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_voice_stop_setting", { enabled, phrase }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeSelectedLanguageSetting(language: string) : Promise<Result<null, string>> {
+async changeFeedbackModeSetting(mode: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_selected_language_setting", { language }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_feedback_mode_setting", { mode }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeOverlayPositionSetting(position: string) : Promise<Result<null, string>> {
+/**
+ * Sets a binding's feedback mode, None falls back to the global setting
+ */
+async changeBindingFeedbackModeSetting(id: string, mode: string | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_overlay_position_setting", { position }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_binding_feedback_mode_setting", { id, mode }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeDebugModeSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changeBindingMatchMode(id: string, matchMode: BindingMatchMode) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_debug_mode_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_binding_match_mode", { id, matchMode }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeWordCorrectionThresholdSetting(threshold: number) : Promise<Result<null, string>> {
+/**
+ * The binding as it reads on the current keyboard layout, see `layout`
+ */
+async getBindingDisplayLabel(id: string) : Promise<Result<string, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_word_correction_threshold_setting", { threshold }) };
+    return { status: "ok", data: await TAURI_INVOKE("get_binding_display_label", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changePasteMethodSetting(method: string) : Promise<Result<null, string>> {
+async changeHapticFeedbackSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_paste_method_setting", { method }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_haptic_feedback_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeClipboardHandlingSetting(handling: string) : Promise<Result<null, string>> {
+/**
+ * While enabled, bindings report presses through `shortcut-test-fired` instead of
+ * running. Ends on its own after a minute or when the settings window closes.
+ */
+async shortcutTestMode(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_clipboard_handling_setting", { handling }) };
+    return { status: "ok", data: await TAURI_INVOKE("shortcut_test_mode", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changePostProcessEnabledSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changeSoundThemeSetting(theme: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_post_process_enabled_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_sound_theme_setting", { theme }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeExperimentalEnabledSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changeStartHiddenSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_experimental_enabled_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_start_hidden_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changePostProcessBaseUrlSetting(providerId: string, baseUrl: string) : Promise<Result<null, string>> {
+async changeAutostartSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_post_process_base_url_setting", { providerId, baseUrl }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_autostart_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changePostProcessApiKeySetting(providerId: string, apiKey: string) : Promise<Result<null, string>> {
+async changeTranslateToEnglishSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_post_process_api_key_setting", { providerId, apiKey }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_translate_to_english_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changePostProcessModelSetting(providerId: string, model: string) : Promise<Result<null, string>> {
+async changeTranslationGuardSetting(guard: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_post_process_model_setting", { providerId, model }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_translation_guard_setting", { guard }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setPostProcessProvider(providerId: string) : Promise<Result<null, string>> {
+async changeSelectedLanguageSetting(language: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_post_process_provider", { providerId }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_selected_language_setting", { language }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async fetchPostProcessModels(providerId: string) : Promise<Result<string[], string>> {
+async changeLocalizePunctuationSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("fetch_post_process_models", { providerId }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_localize_punctuation_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async addPostProcessPrompt(name: string, prompt: string) : Promise<Result<LLMPrompt, string>> {
+async changeOverlayPositionSetting(position: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("add_post_process_prompt", { name, prompt }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_overlay_position_setting", { position }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updatePostProcessPrompt(id: string, name: string, prompt: string) : Promise<Result<null, string>> {
+async changeOverlayInteractiveSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_post_process_prompt", { id, name, prompt }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_overlay_interactive_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deletePostProcessPrompt(id: string) : Promise<Result<null, string>> {
+async changeDebugModeSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_post_process_prompt", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_debug_mode_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setPostProcessSelectedPrompt(id: string) : Promise<Result<null, string>> {
+async changeWordCorrectionThresholdSetting(threshold: number) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_post_process_selected_prompt", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_word_correction_threshold_setting", { threshold }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updateCustomWords(words: string[]) : Promise<Result<null, string>> {
+async changeCorrectionAlgorithmSetting(algorithm: CorrectionAlgorithm) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_custom_words", { words }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_correction_algorithm_setting", { algorithm }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Temporarily unregister a binding while the user is editing it in the UI.
- * This avoids firing the action while keys are being recorded.
+ * Sets the Whisper beam size, clamped to 1 (greedy) ..= `MAX_BEAM_SIZE`
  */
-async suspendBinding(id: string) : Promise<Result<null, string>> {
+async changeBeamSizeSetting(beamSize: number) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("suspend_binding", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_beam_size_setting", { beamSize }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Re-register the binding after the user has finished editing.
+ * Sets the advanced Whisper decoding parameters, clamping out-of-range values
  */
-async resumeBinding(id: string) : Promise<Result<null, string>> {
+async changeDecodingParams(params: DecodingParams) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("resume_binding", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_decoding_params", { params }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeMuteWhileRecordingSetting(enabled: boolean) : Promise<Result<null, string>> {
+async resetDecodingParams() : Promise<Result<DecodingParams, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_mute_while_recording_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("reset_decoding_params") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeAppendTrailingSpaceSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changeAntiHallucinationSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_append_trailing_space_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_anti_hallucination_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeAppLanguageSetting(language: string) : Promise<Result<null, string>> {
+async changeHallucinationMaxRepeatsSetting(maxRepeats: number) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_app_language_setting", { language }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_hallucination_max_repeats_setting", { maxRepeats }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeUpdateChecksSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changeChunkSecondsSetting(chunkSeconds: number) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_update_checks_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_chunk_seconds_setting", { chunkSeconds }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-/**
- * Change the keyboard implementation with runtime switching.
- * This will unregister all shortcuts from the old implementation,
- * validate shortcuts for the new implementation (resetting invalid ones to defaults),
- * and register them with the new implementation.
- */
-async changeKeyboardImplementationSetting(implementation: string) : Promise<Result<ImplementationChangeResult, string>> {
+async changeTranscriptionRetryCountSetting(retryCount: number) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_keyboard_implementation_setting", { implementation }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_transcription_retry_count_setting", { retryCount }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-/**
- * Get the current keyboard implementation
- */
-async getKeyboardImplementation() : Promise<string> {
-    return await TAURI_INVOKE("get_keyboard_implementation");
+async changeSpeakerChangeHintsSetting(enabled: boolean, marker: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_speaker_change_hints_setting", { enabled, marker }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeDiscardBlankTranscriptionsSetting(enabled: boolean, keepInHistory: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_discard_blank_transcriptions_setting", { enabled, keepInHistory }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateDiscardedPhrases(phrases: string[]) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_discarded_phrases", { phrases }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changePasteMethodSetting(method: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_paste_method_setting", { method }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeClipboardHandlingSetting(handling: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_clipboard_handling_setting", { handling }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setAppOutputTransform(appId: string, transform: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_app_output_transform", { appId, transform }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeMaxTranscriptCharsSetting(maxChars: number | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_max_transcript_chars_setting", { maxChars }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeTruncationStrategySetting(strategy: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_truncation_strategy_setting", { strategy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changePostProcessEnabledSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_enabled_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 },
 /**
- * Start key recording mode
+ * Restricts post-processing to providers running on this machine
  */
-async startHandyKeysRecording(bindingId: string) : Promise<Result<null, string>> {
+async changeOfflineModeSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("start_handy_keys_recording", { bindingId }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_offline_mode_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Stop key recording mode
+ * Sets the `User-Agent` sent with post-processing requests
  */
-async stopHandyKeysRecording() : Promise<Result<null, string>> {
+async changeLlmUserAgentSetting(userAgent: LlmUserAgent) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("stop_handy_keys_recording") };
+    return { status: "ok", data: await TAURI_INVOKE("change_llm_user_agent_setting", { userAgent }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async triggerUpdateCheck() : Promise<Result<null, string>> {
+async changeExperimentalEnabledSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("trigger_update_check") };
+    return { status: "ok", data: await TAURI_INVOKE("change_experimental_enabled_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async cancelOperation() : Promise<void> {
-    await TAURI_INVOKE("cancel_operation");
+/**
+ * Sets where newer model catalogs are fetched from, None to only use the one
+ * that ships with the app
+ */
+async changeModelManifestUrlSetting(url: string | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_model_manifest_url_setting", { url }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 },
-async getAppDirPath() : Promise<Result<string, string>> {
+async changePostProcessBaseUrlSetting(providerId: string, baseUrl: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_app_dir_path") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_base_url_setting", { providerId, baseUrl }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAppSettings() : Promise<Result<AppSettings, string>> {
+async changePostProcessAuthStyleSetting(providerId: string, authStyle: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_app_settings") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_auth_style_setting", { providerId, authStyle }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getDefaultSettings() : Promise<Result<AppSettings, string>> {
+async changePostProcessExtraHeadersSetting(providerId: string, headers: Partial<{ [key in string]: string }>) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_default_settings") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_extra_headers_setting", { providerId, headers }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getLogDirPath() : Promise<Result<string, string>> {
+async changePostProcessExtraQueryParamsSetting(providerId: string, params: Partial<{ [key in string]: string }>) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_log_dir_path") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_extra_query_params_setting", { providerId, params }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setLogLevel(level: LogLevel) : Promise<Result<null, string>> {
+async changePostProcessOpenaiScopeSetting(providerId: string, organizationId: string | null, projectId: string | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_log_level", { level }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_openai_scope_setting", { providerId, organizationId, projectId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async openRecordingsFolder() : Promise<Result<null, string>> {
+async changePostProcessChatPathSetting(providerId: string, path: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("open_recordings_folder") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_chat_path_setting", { providerId, path }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async openLogDir() : Promise<Result<null, string>> {
+async changePostProcessApiVersionSetting(providerId: string, apiVersion: string | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("open_log_dir") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_api_version_setting", { providerId, apiVersion }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async openAppDataDir() : Promise<Result<null, string>> {
+async changePostProcessApiKeySetting(providerId: string, apiKey: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("open_app_data_dir") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_api_key_setting", { providerId, apiKey }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-/**
- * Check if Apple Intelligence is available on this device.
- * Called by the frontend when the user selects Apple Intelligence provider.
- */
-async checkAppleIntelligenceAvailable() : Promise<boolean> {
-    return await TAURI_INVOKE("check_apple_intelligence_available");
+async changePostProcessRateLimitSetting(providerId: string, maxConcurrentRequests: number, requestsPerMinute: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_rate_limit_setting", { providerId, maxConcurrentRequests, requestsPerMinute }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 },
-/**
- * Try to initialize Enigo (keyboard/mouse simulation).
- * On macOS, this will return an error if accessibility permissions are not granted.
- */
-async initializeEnigo() : Promise<Result<null, string>> {
+async changePostProcessModelSetting(providerId: string, model: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("initialize_enigo") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_model_setting", { providerId, model }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-/**
- * Test post-processing on arbitrary input text.
- * Returns the processed text if successful.
- */
-async testPostProcess(inputText: string) : Promise<Result<string, string>> {
+async setPostProcessProvider(providerId: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("test_post_process", { inputText }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_post_process_provider", { providerId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAvailableModels() : Promise<Result<ModelInfo[], string>> {
+async fetchPostProcessModels(providerId: string) : Promise<Result<string[], HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_available_models") };
+    return { status: "ok", data: await TAURI_INVOKE("fetch_post_process_models", { providerId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getModelInfo(modelId: string) : Promise<Result<ModelInfo | null, string>> {
+async addPostProcessPrompt(name: string, prompt: string) : Promise<Result<LLMPrompt, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_model_info", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("add_post_process_prompt", { name, prompt }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async downloadModel(modelId: string) : Promise<Result<null, string>> {
+async updatePostProcessPrompt(id: string, name: string, prompt: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("download_model", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("update_post_process_prompt", { id, name, prompt }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deleteModel(modelId: string) : Promise<Result<null, string>> {
+async changePostProcessPromptOutputSetting(id: string, responseFormat: string | null, jsonOutput: string, jsonField: string | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_model", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_prompt_output_setting", { id, responseFormat, jsonOutput, jsonField }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async cancelDownload(modelId: string) : Promise<Result<null, string>> {
+async addAppPromptRule(appMatcher: string, promptId: string, postProcess: boolean | null, collapseToSingleLine: boolean | null) : Promise<Result<AppPromptRule, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("cancel_download", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("add_app_prompt_rule", { appMatcher, promptId, postProcess, collapseToSingleLine }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setActiveModel(modelId: string) : Promise<Result<null, string>> {
+/**
+ * Adds a rule for the application that currently has focus
+ */
+async addAppPromptRuleForCurrentApp(promptId: string, postProcess: boolean | null, collapseToSingleLine: boolean | null) : Promise<Result<AppPromptRule, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_active_model", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("add_app_prompt_rule_for_current_app", { promptId, postProcess, collapseToSingleLine }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getCurrentModel() : Promise<Result<string, string>> {
+async updateAppPromptRule(id: string, appMatcher: string, promptId: string, postProcess: boolean | null, collapseToSingleLine: boolean | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_current_model") };
+    return { status: "ok", data: await TAURI_INVOKE("update_app_prompt_rule", { id, appMatcher, promptId, postProcess, collapseToSingleLine }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getTranscriptionModelStatus() : Promise<Result<string | null, string>> {
+async deleteAppPromptRule(id: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_transcription_model_status") };
+    return { status: "ok", data: await TAURI_INVOKE("delete_app_prompt_rule", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async isModelLoading() : Promise<Result<boolean, string>> {
+/**
+ * Reorders the rules, which are evaluated first to last. `ids` must list every rule once.
+ */
+async reorderAppPromptRules(ids: string[]) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("is_model_loading") };
+    return { status: "ok", data: await TAURI_INVOKE("reorder_app_prompt_rules", { ids }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async hasAnyModelsAvailable() : Promise<Result<boolean, string>> {
+async addWatchFolder(path: string, postProcess: boolean, output: WatchFolderOutput, journalPath: string | null) : Promise<Result<WatchFolderConfig, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("has_any_models_available") };
+    return { status: "ok", data: await TAURI_INVOKE("add_watch_folder", { path, postProcess, output, journalPath }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async hasAnyModelsOrDownloads() : Promise<Result<boolean, string>> {
+async updateWatchFolder(id: string, path: string, enabled: boolean, postProcess: boolean, output: WatchFolderOutput, journalPath: string | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("has_any_models_or_downloads") };
+    return { status: "ok", data: await TAURI_INVOKE("update_watch_folder", { id, path, enabled, postProcess, output, journalPath }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getRecommendedFirstModel() : Promise<Result<string, string>> {
+async deleteWatchFolder(id: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_recommended_first_model") };
+    return { status: "ok", data: await TAURI_INVOKE("delete_watch_folder", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updateMicrophoneMode(alwaysOn: boolean) : Promise<Result<null, string>> {
+async duplicatePostProcessPrompt(id: string, newName: string | null) : Promise<Result<LLMPrompt, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_microphone_mode", { alwaysOn }) };
+    return { status: "ok", data: await TAURI_INVOKE("duplicate_post_process_prompt", { id, newName }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getMicrophoneMode() : Promise<Result<boolean, string>> {
+async resetPromptToDefault(id: string) : Promise<Result<LLMPrompt, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_microphone_mode") };
+    return { status: "ok", data: await TAURI_INVOKE("reset_prompt_to_default", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAvailableMicrophones() : Promise<Result<AudioDevice[], string>> {
+async deletePostProcessPrompt(id: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_available_microphones") };
+    return { status: "ok", data: await TAURI_INVOKE("delete_post_process_prompt", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setSelectedMicrophone(deviceName: string) : Promise<Result<null, string>> {
+async setPostProcessSelectedPrompt(id: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_selected_microphone", { deviceName }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_post_process_selected_prompt", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getSelectedMicrophone() : Promise<Result<string, string>> {
+/**
+ * Sets the label shown for a prompt in the tray, None falls back to its name
+ */
+async changePostProcessPromptShortLabel(id: string, shortLabel: string | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_selected_microphone") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_prompt_short_label", { id, shortLabel }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAvailableOutputDevices() : Promise<Result<AudioDevice[], string>> {
+/**
+ * Sets the sampling seed sent with a prompt, None lets the provider pick
+ */
+async changePostProcessPromptSeed(id: string, seed: number | null) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_available_output_devices") };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_prompt_seed", { id, seed }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setSelectedOutputDevice(deviceName: string) : Promise<Result<null, string>> {
+/**
+ * Sets the prompt post-processing uses while no prompt is selected
+ */
+async changePostProcessDefaultPromptSetting(id: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_selected_output_device", { deviceName }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_default_prompt_setting", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getSelectedOutputDevice() : Promise<Result<string, string>> {
+async updateCustomWords(words: string[]) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_selected_output_device") };
+    return { status: "ok", data: await TAURI_INVOKE("update_custom_words", { words }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async playTestSound(soundType: string) : Promise<void> {
-    await TAURI_INVOKE("play_test_sound", { soundType });
+/**
+ * Replaces the custom words used only for `language`, an empty list removes them
+ */
+async updateLanguageCustomWords(language: string, words: string[]) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_language_custom_words", { language, words }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 },
-async checkCustomSounds() : Promise<CustomSounds> {
-    return await TAURI_INVOKE("check_custom_sounds");
+/**
+ * Temporarily unregister a binding while the user is editing it in the UI.
+ * This avoids firing the action while keys are being recorded.
+ */
+async suspendBinding(id: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("suspend_binding", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 },
-async setClamshellMicrophone(deviceName: string) : Promise<Result<null, string>> {
+/**
+ * Re-register the binding after the user has finished editing.
+ */
+async resumeBinding(id: string) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_clamshell_microphone", { deviceName }) };
+    return { status: "ok", data: await TAURI_INVOKE("resume_binding", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getClamshellMicrophone() : Promise<Result<string, string>> {
+async changeMuteWhileRecordingSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_clamshell_microphone") };
+    return { status: "ok", data: await TAURI_INVOKE("change_mute_while_recording_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async isRecording() : Promise<boolean> {
-    return await TAURI_INVOKE("is_recording");
+async changeDuckAudioWhileRecordingSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_duck_audio_while_recording_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 },
-async setModelUnloadTimeout(timeout: ModelUnloadTimeout) : Promise<void> {
-    await TAURI_INVOKE("set_model_unload_timeout", { timeout });
+async changeDuckLevelSetting(level: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_duck_level_setting", { level }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 },
-async getModelLoadStatus() : Promise<Result<ModelLoadStatus, string>> {
+async changeRememberMicPerEnvironmentSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_model_load_status") };
+    return { status: "ok", data: await TAURI_INVOKE("change_remember_mic_per_environment_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async unloadModelManually() : Promise<Result<null, string>> {
+async changeAppendTrailingSpaceSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("unload_model_manually") };
+    return { status: "ok", data: await TAURI_INVOKE("change_append_trailing_space_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getHistoryEntries() : Promise<Result<HistoryEntry[], string>> {
+async changeBidiIsolationSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_history_entries") };
+    return { status: "ok", data: await TAURI_INVOKE("change_bidi_isolation_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async toggleHistoryEntrySaved(id: number) : Promise<Result<null, string>> {
+async changeNotifyOnCompleteSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("toggle_history_entry_saved", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_notify_on_complete_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAudioFilePath(fileName: string) : Promise<Result<string, string>> {
+async changePromptHardeningSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_audio_file_path", { fileName }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_prompt_hardening_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deleteHistoryEntry(id: number) : Promise<Result<null, string>> {
+async changeTranscriptRingSetting(size: number, persist: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_history_entry", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_transcript_ring_setting", { size, persist }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updateHistoryLimit(limit: number) : Promise<Result<null, string>> {
+async changeSessionLogSetting(storeText: boolean, persist: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_history_limit", { limit }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_session_log_setting", { storeText, persist }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updateRecordingRetentionPeriod(period: string) : Promise<Result<null, string>> {
+async changeReviewBeforePasteSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_recording_retention_period", { period }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_review_before_paste_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-/**
- * Stub implementation for non-macOS platforms
- * Always returns false since laptop detection is macOS-specific
- */
-async isLaptop() : Promise<Result<boolean, string>> {
+async changeDeferPasteOnFocusChangeSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("is_laptop") };
+    return { status: "ok", data: await TAURI_INVOKE("change_defer_paste_on_focus_change_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+async changeBindingReviewSetting(id: string, reviewBeforePaste: boolean | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_binding_review_setting", { id, reviewBeforePaste }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeCollapseToSingleLineSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_collapse_to_single_line_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets whether a binding pastes a single line, None falls back to the global setting
+ */
+async changeBindingSingleLineSetting(id: string, collapseToSingleLine: boolean | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_binding_single_line_setting", { id, collapseToSingleLine }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Pins the model a binding transcribes with, None falls back to `selected_model`
+ */
+async changeBindingModelSetting(id: string, modelId: string | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_binding_model_setting", { id, modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeReviewTimeoutSetting(timeoutSecs: number, action: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_review_timeout_setting", { timeoutSecs, action }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAppLanguageSetting(language: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_app_language_setting", { language }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeUpdateChecksSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_update_checks_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeWatchSettingsFileSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_watch_settings_file_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Change the keyboard implementation setting.
+ * NOTE: Only "handy_keys" is supported now. Other values will return an error.
+ */
+async changeKeyboardImplementationSetting(implementation: string) : Promise<Result<ImplementationChangeResult, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_keyboard_implementation_setting", { implementation }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Get the current keyboard implementation
+ */
+async getKeyboardImplementation() : Promise<string> {
+    return await TAURI_INVOKE("get_keyboard_implementation");
+},
+/**
+ * Start key recording mode
+ */
+async startHandyKeysRecording(bindingId: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_handy_keys_recording", { bindingId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stop key recording mode
+ */
+async stopHandyKeysRecording() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_handy_keys_recording") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async triggerUpdateCheck() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("trigger_update_check") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelOperation() : Promise<void> {
+    await TAURI_INVOKE("cancel_operation");
+},
+/**
+ * Stops the current recording and transcribes it, used by the interactive overlay
+ */
+async stopRecording() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_recording") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The data directory, superseded by `get_app_paths`
+ */
+async getAppDirPath() : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_app_dir_path") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Every directory the app keeps files in
+ */
+async getAppPaths() : Promise<AppPaths> {
+    return await TAURI_INVOKE("get_app_paths");
+},
+async getAppSettings() : Promise<Result<AppSettings, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_app_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Settings with all defaults merged in, exactly as the app uses them. Unlike
+ * `get_app_settings` this also writes the merge back to the settings file.
+ */
+async getEffectiveSettings() : Promise<Result<EffectiveSettings, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_effective_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDefaultSettings() : Promise<Result<AppSettings, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_default_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLogDirPath() : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_log_dir_path") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLogLevel(level: LogLevel) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_log_level", { level }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Configures log rotation. Takes effect the next time the app starts.
+ */
+async changeLogRotationSetting(maxFileSizeKb: number, maxFiles: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_log_rotation_setting", { maxFileSizeKb, maxFiles }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Deletes rotated log files and empties the active one
+ */
+async clearLogs() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_logs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns the most recent entries of the active log file, oldest first.
+ * `since` is an RFC 3339 timestamp; `min_level` keeps that level and more severe ones.
+ */
+async getRecentLogs(lines: number | null, since: string | null, minLevel: LogLevel | null) : Promise<Result<string[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recent_logs", { lines, since, minLevel }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openRecordingsFolder() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_recordings_folder") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openLogDir() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_log_dir") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openAppDataDir() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_app_data_dir") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Check if Apple Intelligence is available on this device.
+ * Called by the frontend when the user selects Apple Intelligence provider.
+ */
+async checkAppleIntelligenceAvailable() : Promise<boolean> {
+    return await TAURI_INVOKE("check_apple_intelligence_available");
+},
+/**
+ * Try to initialize Enigo (keyboard/mouse simulation).
+ * On macOS, this will return an error if accessibility permissions are not granted.
+ */
+async initializeEnigo() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("initialize_enigo") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Test post-processing on arbitrary input text.
+ * Returns the processed text if successful.
+ */
+async testPostProcess(inputText: string) : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_post_process", { inputText }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Queue depth and rate-limit state for every configured post-processing provider.
+ */
+async getLlmQueueStatus() : Promise<Result<LlmQueueStatus[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_llm_queue_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns what is missing when post-processing is enabled but can't run.
+ */
+async getPostProcessMisconfiguration() : Promise<PostProcessMisconfiguration | null> {
+    return await TAURI_INVOKE("get_post_process_misconfiguration");
+},
+/**
+ * Checks the model, microphone, input simulation, shortcuts and post-processing
+ * provider, returning a pass/fail report with a remediation hint per failure.
+ */
+async runSelfTest() : Promise<Result<SelfTestReport, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_self_test") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Offline checks of a prompt against the active provider configuration.
+ */
+async lintPrompt(prompt: string) : Promise<PromptLintIssue[]> {
+    return await TAURI_INVOKE("lint_prompt", { prompt });
+},
+/**
+ * The messages post-processing `input` with a saved prompt would send to the
+ * active provider, with every token expanded. Nothing is sent.
+ */
+async renderPrompt(promptId: string, input: string) : Promise<Result<ChatMessage[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("render_prompt", { promptId, input }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets the size caps for post-processing requests, kept within bounds
+ */
+async changePromptLimitsSetting(limits: PromptLimits) : Promise<Result<PromptLimits, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_prompt_limits_setting", { limits }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The model catalog. A stale fetched catalog is refreshed in the background and
+ * shows up on a later call.
+ */
+async getAvailableModels() : Promise<Result<ModelInfo[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_available_models") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Every model in the catalog with what sets it apart, smallest first. Sizes of
+ * downloaded models are measured on disk.
+ */
+async listAvailableModels() : Promise<ModelSummary[]> {
+    return await TAURI_INVOKE("list_available_models");
+},
+async getModelInfo(modelId: string) : Promise<Result<ModelInfo | null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_info", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Reads a model into memory ahead of use. Without an id the selected model is
+ * loaded; with one, that model is loaded and becomes the selected model.
+ */
+async loadModel(modelId: string | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("load_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Frees the loaded model; the next dictation loads it again
+ */
+async unloadModel() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unload_model") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getModelState() : Promise<Result<ModelStatus, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_state") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changePowerProfilesSetting(profiles: PowerProfiles) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_power_profiles_setting", { profiles }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Uses the battery or plugged-in profile regardless of the power source until
+ * the next plug or unplug. None goes back to the detected source.
+ */
+async setPowerProfile(source: PowerSource | null) : Promise<ActivePowerProfile> {
+    return await TAURI_INVOKE("set_power_profile", { source });
+},
+async downloadModel(modelId: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("download_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteModel(modelId: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Quantizations a model can be downloaded in, empty when it comes in only one
+ */
+async listModelVariants(modelId: string) : Promise<Result<QuantizationVariant[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_model_variants", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Chooses the quantization to download and load for a model, None for its
+ * default. Until it's downloaded, another downloaded quantization is loaded.
+ */
+async setModelQuantization(modelId: string, quantization: string | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_model_quantization", { modelId, quantization }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelDownload(modelId: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_download", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Switches to a model. If it may not fit in the free memory, nothing changes and
+ * the warning is returned for confirmation; call again with
+ * `ignore_memory_warning` to switch anyway.
+ */
+async setActiveModel(modelId: string, ignoreMemoryWarning: boolean | null) : Promise<Result<ModelSwitch, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_active_model", { modelId, ignoreMemoryWarning }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSystemMemory() : Promise<SystemMemory> {
+    return await TAURI_INVOKE("get_system_memory");
+},
+async getCurrentModel() : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_current_model") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTranscriptionModelStatus() : Promise<Result<string | null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_transcription_model_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async isModelLoading() : Promise<Result<boolean, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("is_model_loading") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async hasAnyModelsAvailable() : Promise<Result<boolean, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("has_any_models_available") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async hasAnyModelsOrDownloads() : Promise<Result<boolean, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("has_any_models_or_downloads") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecommendedFirstModel() : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recommended_first_model") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Loads a model and transcribes a recording with it, by default the latest one
+ * in history, reporting speed and memory use on this machine
+ */
+async benchmarkModel(modelId: string, recordingSource: RecordingSource | null) : Promise<Result<ModelBenchmark, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("benchmark_model", { modelId, recordingSource }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateMicrophoneMode(alwaysOn: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_microphone_mode", { alwaysOn }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets how much audio from before the shortcut press is kept (always-on microphone only)
+ */
+async changePreRollSetting(preRollMs: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_pre_roll_setting", { preRollMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAgcEnabledSetting(enabled: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_agc_enabled_setting", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async changeAgcTargetRmsSetting(targetRms: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_agc_target_rms_setting", { targetRms }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMicrophoneMode() : Promise<Result<boolean, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_microphone_mode") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAvailableMicrophones() : Promise<Result<AudioDevice[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_available_microphones") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAvailableMicrophonesDetailed() : Promise<Result<DetailedAudioDevice[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_available_microphones_detailed") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Chooses which input channels of a device are captured. Channels are numbered from
+ * one; an empty list goes back to averaging all channels.
+ */
+async changeMicChannels(device: string, channels: number[], mode: ChannelMixMode | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_mic_channels", { device, channels, mode }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSelectedMicrophone(deviceName: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_selected_microphone", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSelectedMicrophone() : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_selected_microphone") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Chooses which Windows default input is used while no microphone is selected.
+ * Other platforms have a single default and ignore it.
+ */
+async setWindowsDefaultDeviceRole(role: WindowsDeviceRole) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_windows_default_device_role", { role }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAvailableOutputDevices() : Promise<Result<AudioDevice[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_available_output_devices") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSelectedOutputDevice(deviceName: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_selected_output_device", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSelectedOutputDevice() : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_selected_output_device") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async playTestSound(soundType: string) : Promise<void> {
+    await TAURI_INVOKE("play_test_sound", { soundType });
+},
+async checkCustomSounds() : Promise<CustomSounds> {
+    return await TAURI_INVOKE("check_custom_sounds");
+},
+async setClamshellMicrophone(deviceName: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_clamshell_microphone", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getClamshellMicrophone() : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_clamshell_microphone") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async isRecording() : Promise<boolean> {
+    return await TAURI_INVOKE("is_recording");
+},
+/**
+ * Whether the microphone stream is warm and how fast the last recording started
+ */
+async getCaptureState() : Promise<CaptureState> {
+    return await TAURI_INVOKE("get_capture_state");
+},
+async getWakeWordModels() : Promise<WakeWordModel[]> {
+    return await TAURI_INVOKE("get_wake_word_models");
+},
+/**
+ * Configures the wake word, starting or stopping the detector to match
+ */
+async changeWakeWordSetting(enabled: boolean, model: string, sensitivity: number, cooldownMs: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_wake_word_setting", { enabled, model, sensitivity, cooldownMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Listens for the wake phrase for a few seconds and reports what was heard,
+ * without recording anything
+ */
+async testWakeWord() : Promise<Result<WakeWordTest, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_wake_word") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sentence the user reads aloud during `analyze_microphone`
+ */
+async getMicCheckSentence() : Promise<string> {
+    return await TAURI_INVOKE("get_mic_check_sentence");
+},
+/**
+ * Records the user reading the check sentence for a few seconds and rates the
+ * microphone, see `mic_check`. Uses the selected microphone when `device` is None.
+ */
+async analyzeMicrophone(device: string | null) : Promise<Result<MicAnalysis, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("analyze_microphone", { device }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Unmutes the microphone recordings use, where the platform lets apps do that
+ */
+async unmuteMicrophone() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unmute_microphone") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setModelUnloadTimeout(timeout: ModelUnloadTimeout) : Promise<void> {
+    await TAURI_INVOKE("set_model_unload_timeout", { timeout });
+},
+async setVramGuardBehavior(behavior: VramGuardBehavior) : Promise<void> {
+    await TAURI_INVOKE("set_vram_guard_behavior", { behavior });
+},
+async setCancelBehavior(behavior: CancelBehavior) : Promise<void> {
+    await TAURI_INVOKE("set_cancel_behavior", { behavior });
+},
+async getModelLoadStatus() : Promise<Result<ModelLoadStatus, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_load_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async unloadModelManually() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unload_model_manually") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async startFileDictation(path: string, append: boolean) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_file_dictation", { path, append }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopFileDictation() : Promise<Result<string | null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_file_dictation") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Pastes a transcript held for review, replacing it with `edited_text` when given
+ */
+async confirmTranscript(id: number, editedText: string | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("confirm_transcript", { id, editedText }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async discardTranscript(id: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("discard_transcript", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getPendingTranscriptReviews() : Promise<Result<TranscriptReview[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_pending_transcript_reviews") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Transcribes one recording with each configuration in turn, reporting every run
+ * through `compare-progress`
+ */
+async compareTranscriptions(recordingSource: RecordingSource, configs: CompareConfig[]) : Promise<Result<CompareResult[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("compare_transcriptions", { recordingSource, configs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelTranscriptionComparison() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_transcription_comparison") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Merges a word list into `custom_words`, or the list of `language`, see
+ * `custom_words`. Without a path, text replacements are read from the system's
+ * preferences (macOS only). With `dry_run` nothing is saved and the report lists
+ * what would be added.
+ */
+async importCustomWords(path: string | null, format: WordListFormat, dryRun: boolean, language: string | null) : Promise<Result<ImportReport, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_custom_words", { path, format, dryRun, language }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Writes `custom_words`, or the list of `language`, to `path`
+ */
+async exportCustomWords(path: string, format: WordListFormat, language: string | null) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_custom_words", { path, format, language }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Parks the next transcript instead of pasting it, see `flush_deferred_paste`
+ */
+async deferPaste() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("defer_paste") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Pastes the deferred transcript into the currently focused field
+ */
+async flushDeferredPaste() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("flush_deferred_paste") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Deletes the text of the most recent paste, see `undo_paste`
+ */
+async undoLastTranscription() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("undo_last_transcription") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Whether transcripts are currently copied instead of pasted, see `copy_only_session`
+ */
+async getCopyOnlySession() : Promise<boolean> {
+    return await TAURI_INVOKE("get_copy_only_session");
+},
+async setCopyOnlySession(active: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_copy_only_session", { active });
+},
+/**
+ * Starts a continuous dictation session, see `dictation_session`
+ */
+async startDictationSession() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_dictation_session") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Ends the dictation session once its last segment is transcribed
+ */
+async endDictationSession() : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("end_dictation_session") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDictationSession() : Promise<boolean> {
+    return await TAURI_INVOKE("get_dictation_session");
+},
+async getHistoryEntries() : Promise<Result<HistoryEntry[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history_entries") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async searchHistory(query: string) : Promise<Result<HistoryEntry[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_history", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async rebuildHistoryIndex() : Promise<Result<number, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rebuild_history_index") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Recent transcripts kept in memory, newest first. Works even when history is disabled.
+ */
+async getTranscriptClipboard() : Promise<Result<TranscriptRingEntry[], HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_transcript_clipboard") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Copies a transcript from the ring to the OS clipboard, 0 being the newest
+ */
+async copyTranscriptClipboardEntry(index: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("copy_transcript_clipboard_entry", { index }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async toggleHistoryEntrySaved(id: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("toggle_history_entry_saved", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Transcribes an entry's recording again with the current model and settings,
+ * replacing its transcript. Draft entries get their text this way.
+ */
+async retranscribeHistoryEntry(id: number) : Promise<Result<HistoryEntry, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("retranscribe_history_entry", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAudioFilePath(fileName: string) : Promise<Result<string, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_audio_file_path", { fileName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteHistoryEntry(id: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_history_entry", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateHistoryLimit(limit: number) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_history_limit", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateRecordingRetentionPeriod(period: string) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_recording_retention_period", { period }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets the format new recordings are stored in. Existing recordings keep theirs,
+ * see `convert_existing_recordings`.
+ */
+async changeRecordingFormatSetting(format: RecordingFormat) : Promise<Result<null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_recording_format_setting", { format }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Re-encodes stored recordings in `format`, emitting `recording-conversion-progress`
+ */
+async convertExistingRecordings(format: RecordingFormat) : Promise<Result<RecordingConversionProgress, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("convert_existing_recordings", { format }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Average words per minute of past dictations, over the last `days` days or all
+ * time. Today's totals come from the session log, so they are complete even when
+ * history keeps only a few entries.
+ */
+async getSpeakingRateStats(days: number | null) : Promise<Result<SpeakingRateStats, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_speaking_rate_stats", { days }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Today's dictations, oldest first. Kept apart from history and cleared at midnight.
+ */
+async getSessionLog() : Promise<SessionLogEntry[]> {
+    return await TAURI_INVOKE("get_session_log");
+},
+async clearSessionLog() : Promise<void> {
+    await TAURI_INVOKE("clear_session_log");
+},
+/**
+ * Word diff between the raw and post-processed transcript of a history entry,
+ * None when it wasn't post-processed
+ */
+async getHistoryDiff(id: number) : Promise<Result<WordDiff | null, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history_diff", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Checks for a system battery, which laptops have and desktops typically don't
+ */
+async isLaptop() : Promise<Result<boolean, HandyError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("is_laptop") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Get the application that currently has focus, for pickers in the settings UI.
+ */
+async getFrontmostApplication() : Promise<FrontmostApplication | null> {
+    return await TAURI_INVOKE("get_frontmost_application");
 }
 }
 
@@ -700,33 +1923,1379 @@ async isLaptop() : Promise<Result<boolean, string>> {
 
 /** user-defined types **/
 
-export type AppSettings = { bindings: Partial<{ [key in string]: ShortcutBinding }>; push_to_talk: boolean; audio_feedback: boolean; audio_feedback_volume?: number; sound_theme?: SoundTheme; start_hidden?: boolean; autostart_enabled?: boolean; update_checks_enabled?: boolean; selected_model?: string; always_on_microphone?: boolean; selected_microphone?: string | null; clamshell_microphone?: string | null; selected_output_device?: string | null; translate_to_english?: boolean; selected_language?: string; overlay_position?: OverlayPosition; debug_mode?: boolean; log_level?: LogLevel; custom_words?: string[]; model_unload_timeout?: ModelUnloadTimeout; word_correction_threshold?: number; history_limit?: number; recording_retention_period?: RecordingRetentionPeriod; paste_method?: PasteMethod; clipboard_handling?: ClipboardHandling; post_process_enabled?: boolean; post_process_provider_id?: string; post_process_providers?: PostProcessProvider[]; post_process_api_keys?: Partial<{ [key in string]: string }>; post_process_models?: Partial<{ [key in string]: string }>; post_process_prompts?: LLMPrompt[]; post_process_selected_prompt_id?: string | null; mute_while_recording?: boolean; append_trailing_space?: boolean; app_language?: string; experimental_enabled?: boolean; keyboard_implementation?: KeyboardImplementation; post_process_custom_models?: Partial<{ [key in string]: string[] }> }
+/**
+ * The profile in effect, reported with `power-profile-changed` and `get_model_state`
+ */
+export type ActivePowerProfile = { source: PowerSource; 
+/**
+ * Picked with `set_power_profile` rather than detected
+ */
+manual: boolean }
+export type AppEvent = { type: "settings-changed"; payload: SettingChanged } | { type: "check-for-updates" } | { type: "mic-level"; payload: number[] } | { type: "show-overlay"; payload: OverlayState } | { type: "hide-overlay" } | { type: "history-updated" } | { type: "history-index-progress"; payload: HistoryIndexProgress } | { type: "recording-conversion-progress"; payload: RecordingConversionProgress } | { type: "dictation-metrics"; payload: DictationMetrics } | { type: "shortcut-test-fired"; payload: ShortcutTestFired } | { type: "shortcut-test-mode-changed"; payload: boolean } | 
+/**
+ * Identifier of the keyboard layout now in use
+ */
+{ type: "keyboard-layout-changed"; payload: string } | { type: "model-state-changed"; payload: ModelStateEvent } | { type: "model-download-progress"; payload: DownloadProgress } | { type: "model-download-complete"; payload: string } | { type: "model-extraction-started"; payload: string } | { type: "model-extraction-completed"; payload: string } | { type: "model-extraction-failed"; payload: ModelExtractionFailed } | { type: "handy-keys-event"; payload: FrontendKeyEvent } | { type: "transcript-truncated"; payload: TranscriptTruncated } | { type: "translation-suspect"; payload: TranslationCheck } | { type: "post-process-misconfigured"; payload: PostProcessMisconfiguration } | 
+/**
+ * Target file of file dictation, None when the session ended
+ */
+{ type: "file-dictation-changed"; payload: string | null } | { type: "file-dictation-write-failed"; payload: FileDictationWriteFailed } | 
+/**
+ * A transcript is waiting in the review window
+ */
+{ type: "transcript-review"; payload: TranscriptReview } | { type: "transcript-review-ended"; payload: TranscriptReviewEnded } | { type: "output-device-changed"; payload: OutputDeviceChanged } | { type: "recording-device-recovered"; payload: RecordingDeviceRecovered } | 
+/**
+ * No input could be reopened; the recording is stopped with what was captured
+ */
+{ type: "recording-device-lost" } | { type: "microphone-muted"; payload: MicrophoneMuted } | { type: "transcription-discarded"; payload: TranscriptionDiscarded } | { type: "transcription-retrying"; payload: TranscriptionRetrying } | { type: "watch-folder-error"; payload: WatchFolderError } | { type: "transcript-pasted"; payload: PasteReport } | { type: "recording-started"; payload: PipelineProgress } | { type: "recording-stopped"; payload: PipelineProgress } | { type: "inference-started"; payload: PipelineProgress } | { type: "inference-done"; payload: PipelineProgress } | { type: "post-process-started"; payload: PipelineProgress } | { type: "post-process-done"; payload: PipelineProgress } | { type: "paste-done"; payload: PipelineProgress } | { type: "dictation-ended"; payload: DictationEnded } | { type: "dictations-in-flight"; payload: DictationsInFlight } | { type: "compare-progress"; payload: CompareProgress } | 
+/**
+ * The deferred transcript, None once it was pasted
+ */
+{ type: "deferred-paste-changed"; payload: string | null } | 
+/**
+ * A deferred transcript was dropped for a newer one
+ */
+{ type: "deferred-paste-replaced"; payload: string } | 
+/**
+ * Whether a copy-only session is on, see `copy_only_session`
+ */
+{ type: "copy-only-session-changed"; payload: boolean } | { type: "wake-word-detected"; payload: WakeWordDetection } | { type: "power-profile-changed"; payload: ActivePowerProfile } | 
+/**
+ * Whether a dictation session is running, see `dictation_session`
+ */
+{ type: "dictation-session-changed"; payload: boolean } | { type: "dictation-segment"; payload: DictationSegment } | 
+/**
+ * Top-level settings keys that changed when the store file was edited outside
+ * the app, see `settings_watcher`
+ */
+{ type: "settings-reloaded"; payload: string[] }
+export type AppPaths = { 
+/**
+ * Settings
+ */
+config: string; 
+/**
+ * History, custom sounds and everything else worth keeping
+ */
+data: string; models: string; recordings: string; logs: string; 
+/**
+ * Files that are fetched again when missing, like the model catalog
+ */
+cache: string; portable: boolean }
+/**
+ * Selects a prompt while a matching application has focus
+ */
+export type AppPromptRule = { id: string; 
+/**
+ * Application identifier or name, compared case-insensitively
+ */
+app_matcher: string; prompt_id: string; 
+/**
+ * Forces post-processing on or off, None keeps the global setting
+ */
+post_process?: boolean | null; 
+/**
+ * Forces single-line pasting on or off, over the binding and global settings
+ */
+collapse_to_single_line?: boolean | null }
+export type AppSettings = { bindings: Partial<{ [key in string]: ShortcutBinding }>; push_to_talk: boolean; 
+/**
+ * Drop other key presses while a push-to-talk binding is held so they don't
+ * reach the focused app as shortcuts (macOS and Windows only)
+ */
+suppress_keys_during_ptt?: boolean; audio_feedback: boolean; audio_feedback_volume?: number; feedback_mode?: FeedbackMode; feedback_timing?: FeedbackTiming; 
+/**
+ * Trackpad haptic feedback when recording starts and stops (macOS only)
+ */
+haptic_feedback?: boolean; sound_theme?: SoundTheme; start_hidden?: boolean; autostart_enabled?: boolean; update_checks_enabled?: boolean; 
+/**
+ * Reload the settings when `settings_store.json` is edited outside the app
+ */
+watch_settings_file?: boolean; selected_model?: string; always_on_microphone?: boolean; 
+/**
+ * Audio kept from before the shortcut press and prepended to the recording.
+ * Only works with the always-on microphone, capped at `MAX_PRE_ROLL_MS`.
+ */
+pre_roll_ms?: number; 
+/**
+ * Level the microphone signal with automatic gain control before the VAD
+ */
+agc_enabled?: boolean; 
+/**
+ * Speech level the AGC aims for, as an RMS amplitude between 0 and 1
+ */
+agc_target_rms?: number; 
+/**
+ * Start recording on a spoken wake phrase, stopping once speech goes quiet
+ */
+wake_word_enabled?: boolean; 
+/**
+ * Wake phrase model, one of `wake_word::WAKE_WORD_MODELS`
+ */
+wake_word_model?: string; 
+/**
+ * Between 0 and 1, higher detects the phrase more readily but misfires more
+ */
+wake_word_sensitivity?: number; 
+/**
+ * Time after a detection during which the phrase is ignored
+ */
+wake_word_cooldown_ms?: number; 
+/**
+ * Silence that ends a segment of a dictation session, see `dictation_session`
+ */
+dictation_session_pause_ms?: number; 
+/**
+ * Transcribe a dictation while earlier ones are still transcribed, instead of
+ * after them. Off by default, the transcriptions compete for CPU and memory.
+ * Recording never waits either way.
+ */
+overlap_transcription?: boolean; 
+/**
+ * End recordings by saying `voice_stop_phrase`, see `voice_stop`
+ */
+voice_stop_enabled?: boolean; voice_stop_phrase?: string; selected_microphone?: string | null; 
+/**
+ * Default input used while `selected_microphone` is unset, Windows only
+ */
+windows_default_device_role?: WindowsDeviceRole; 
+/**
+ * Microphone used while a laptop's lid is closed, on macOS, Windows and Linux.
+ * Ignored elsewhere, see `helpers::clamshell`.
+ */
+clamshell_microphone?: string | null; remember_mic_per_environment?: boolean; 
+/**
+ * Preferred microphone keyed by device environment fingerprint
+ */
+environment_microphones?: Partial<{ [key in string]: string }>; 
+/**
+ * Input channels to capture, keyed by device name. Devices without an entry are
+ * captured as the average of all their channels.
+ */
+mic_channel_map?: Partial<{ [key in string]: MicChannelConfig }>; selected_output_device?: string | null; translate_to_english?: boolean; translation_guard?: TranslationGuard; selected_language?: string; 
+/**
+ * Rewrite punctuation following the conventions of the transcript's language
+ */
+localize_punctuation?: boolean; overlay_position?: OverlayPosition; 
+/**
+ * Let the overlay take clicks (stop and cancel) instead of passing them through
+ */
+overlay_interactive?: boolean; debug_mode?: boolean; log_level?: LogLevel; 
+/**
+ * Size in kilobytes at which the log file is rotated
+ */
+log_max_file_size_kb?: number; 
+/**
+ * Log files kept, including the current one. 0 keeps every rotated file.
+ */
+log_max_files?: number; custom_words?: string[]; 
+/**
+ * Custom words for transcripts in one language, by code as in
+ * `selected_language`, see `custom_words`
+ */
+custom_words_by_language?: Partial<{ [key in string]: string[] }>; model_unload_timeout?: ModelUnloadTimeout; 
+/**
+ * Model overrides on battery and plugged in, see `power_profile`
+ */
+power_profiles?: PowerProfiles; vram_guard_behavior?: VramGuardBehavior; 
+/**
+ * Quantization to download and load by model id, models without an entry
+ * use their default
+ */
+model_quantization?: Partial<{ [key in string]: string }>; 
+/**
+ * Where to fetch newer versions of the model catalog, see `model_manifest`
+ */
+model_manifest_url?: string | null; word_correction_threshold?: number; 
+/**
+ * How transcribed words are compared against the custom words
+ */
+correction_algorithm?: CorrectionAlgorithm; 
+/**
+ * Advanced Whisper decoding parameters
+ */
+advanced_decoding?: DecodingParams; 
+/**
+ * Collapse phrases repeated back to back, a Whisper artifact on silence or music
+ */
+anti_hallucination?: boolean; 
+/**
+ * Back-to-back copies of a phrase kept before it counts as a hallucinated loop
+ */
+hallucination_max_repeats?: number; 
+/**
+ * Recordings longer than this are transcribed in chunks, 0 transcribes them whole
+ */
+chunk_seconds?: number; 
+/**
+ * Times a failed inference is retried before the error is reported
+ */
+transcription_retry_count?: number; 
+/**
+ * Transcribe each guessed speaker turn separately and put a marker between them
+ */
+speaker_change_hints?: boolean; 
+/**
+ * Inserted between speaker turns
+ */
+speaker_change_marker?: string; 
+/**
+ * Drop blank transcripts and ones matching `discarded_phrases` instead of pasting
+ */
+discard_blank_transcriptions?: boolean; 
+/**
+ * Known hallucinations of accidental activations, matched against the whole transcript
+ */
+discarded_phrases?: string[]; 
+/**
+ * Save discarded recordings to history anyway, for debugging
+ */
+keep_discarded_in_history?: boolean; cancel_behavior?: CancelBehavior; history_limit?: number; recording_retention_period?: RecordingRetentionPeriod; recording_format?: RecordingFormat; paste_method?: PasteMethod; clipboard_handling?: ClipboardHandling; 
+/**
+ * Recent transcripts kept for the copy shortcuts, 0 disables the ring
+ */
+transcript_ring_size?: number; 
+/**
+ * Keep the transcript ring across restarts instead of only in memory
+ */
+persist_transcript_ring?: boolean; 
+/**
+ * Keep the text of dictations in the session log, not only their counts
+ */
+session_log_store_text?: boolean; 
+/**
+ * Keep today's session log across restarts instead of only in memory
+ */
+persist_session_log?: boolean; max_transcript_chars?: number | null; truncation_strategy?: TruncationStrategy; 
+/**
+ * Output transforms keyed by application identifier or name
+ */
+app_output_transforms?: Partial<{ [key in string]: OutputTransform }>; 
+/**
+ * Prompt overrides by focused application, the first matching rule wins
+ */
+app_prompt_rules?: AppPromptRule[]; 
+/**
+ * Folders watched for new recordings to transcribe, see `watch_folders`
+ */
+watch_folders?: WatchFolderConfig[]; post_process_enabled?: boolean; post_process_provider_id?: string; post_process_providers?: PostProcessProvider[]; post_process_api_keys?: Partial<{ [key in string]: string }>; post_process_models?: Partial<{ [key in string]: string }>; post_process_prompts?: LLMPrompt[]; post_process_selected_prompt_id?: string | null; 
+/**
+ * Only allow post-processing providers running on this machine. Requests to any
+ * other host are refused, whatever provider is selected.
+ */
+offline_mode?: boolean; llm_user_agent?: LlmUserAgent; 
+/**
+ * Prompt used while none is selected, e.g. right after post-processing is enabled
+ */
+post_process_default_prompt_id?: string; 
+/**
+ * Send the transcript as delimited, untrusted user content instead of inlining it into the prompt
+ */
+prompt_hardening?: boolean; prompt_limits?: PromptLimits; mute_while_recording?: boolean; 
+/**
+ * Lower other audio while recording instead of muting it
+ */
+duck_audio_while_recording?: boolean; 
+/**
+ * Output volume while ducked, as a percentage of the volume before recording
+ */
+duck_level?: number; append_trailing_space?: boolean; 
+/**
+ * Join the transcript's lines with spaces before pasting, history keeps them
+ */
+collapse_to_single_line?: boolean; 
+/**
+ * Wrap English words, code and the like inside right-to-left transcripts in
+ * directional isolates, so target apps keep them in order
+ */
+bidi_isolation?: boolean; notify_on_complete?: boolean; 
+/**
+ * Show the transcript in a review window before pasting it
+ */
+review_before_paste?: boolean; 
+/**
+ * Seconds a pending review waits before resolving on its own, 0 waits indefinitely
+ */
+review_timeout_secs?: number; review_timeout_action?: ReviewTimeoutAction; 
+/**
+ * Park the transcript instead of pasting it when focus moved to another
+ * application while transcribing
+ */
+defer_paste_on_focus_change?: boolean; 
+/**
+ * Written between entries in file dictation mode, `${timestamp}` expands to the local time
+ */
+file_dictation_separator?: string; app_language?: string; experimental_enabled?: boolean; post_process_custom_models?: Partial<{ [key in string]: string[] }> }
+/**
+ * A rule that was applied to a dictation, recorded in history
+ */
+export type AppliedPromptRule = { rule_id: string; app_matcher: string; 
+/**
+ * Name of the application that had focus
+ */
+app_name: string; prompt_id: string; post_process: boolean | null; collapse_to_single_line?: boolean | null }
 export type AudioDevice = { index: string; name: string; is_default: boolean }
+/**
+ * How the API key is sent to a post-processing provider
+ */
+export type AuthStyle = 
+/**
+ * `Authorization: Bearer <key>` (or `x-api-key` for Anthropic)
+ */
+"bearer" | 
+/**
+ * `api-key: <key>` header, as used by Azure OpenAI
+ */
+"api_key_header" | 
+/**
+ * `?api-key=<key>` query parameter
+ */
+"query_param" | "none"
+export type BindingMatchMode = 
+/**
+ * The character the key types on the current layout
+ */
+"symbol" | 
+/**
+ * The key at that position on a US QWERTY keyboard
+ */
+"scancode"
 export type BindingResponse = { success: boolean; binding: ShortcutBinding | null; error: string | null }
+/**
+ * What cancelling does with the recording in progress
+ */
+export type CancelBehavior = 
+/**
+ * Throw the audio away
+ */
+"discard" | 
+/**
+ * Keep the audio as a draft history entry, transcribed only on request
+ */
+"save_draft" | 
+/**
+ * Transcribe into history without pasting
+ */
+"transcribe_silently"
+/**
+ * Diagnostics for the capture pipeline, see `get_capture_state`
+ */
+export type CaptureState = { always_on: boolean; 
+/**
+ * Whether the input stream is currently running
+ */
+stream_open: boolean; recording: boolean; pre_roll_ms: number; 
+/**
+ * Whether the last recording started on an already running stream
+ */
+last_start_warm: boolean | null; 
+/**
+ * Time from the start request until samples were being kept, for the last recording
+ */
+last_start_latency_ms: number | null }
+/**
+ * How the configured input channels of a microphone are combined
+ */
+export type ChannelMixMode = "average" | 
+/**
+ * Use only the first configured channel
+ */
+"select"
+export type ChatMessage = { 
+/**
+ * `system` or `user`
+ */
+role: string; content: string }
+export type CheckStatus = "pass" | "fail" | 
+/**
+ * The subsystem isn't in use with the current settings
+ */
+"skipped"
 export type ClipboardHandling = "dont_modify" | "copy_to_clipboard"
+/**
+ * One run of a comparison. Fields left out use the current settings.
+ */
+export type CompareConfig = { model_id: string; language: string | null; translate_to_english: boolean | null; custom_words: string[] | null; word_correction_threshold: number | null; correction_algorithm: CorrectionAlgorithm | null; 
+/**
+ * Whisper decoding parameters to try without saving them
+ */
+decoding: DecodingParams | null }
+/**
+ * Payload of the `compare-progress` event, sent after every run
+ */
+export type CompareProgress = { completed: number; total: number; result: CompareResult }
+export type CompareResult = { model_id: string; text: string; 
+/**
+ * Why the run failed, `text` is empty in that case
+ */
+error: string | null; 
+/**
+ * Time spent loading the model, 0 when it was already loaded
+ */
+load_ms: number; inference_ms: number }
+export type ComputeBackend = 
+/**
+ * The GPU, subject to `vram_guard_behavior`
+ */
+"gpu" | "cpu"
+/**
+ * Matching used for custom word correction, see `audio_toolkit::text::WordMatcher`
+ */
+export type CorrectionAlgorithm = 
+/**
+ * Edit distance favouring words that sound alike, best for English
+ */
+"levenshtein" | 
+/**
+ * Also forgives two swapped letters
+ */
+"damerau_levenshtein" | 
+/**
+ * Forgiving of differing accents and endings, for inflected languages
+ */
+"jaro" | 
+/**
+ * Only replaces words that sound alike
+ */
+"phonetic"
 export type CustomSounds = { start: boolean; stop: boolean }
+export type DailySpeakingRate = { 
+/**
+ * `YYYY-MM-DD`
+ */
+date: string; average_wpm: number; dictations: number }
+/**
+ * Whisper decoding parameters, see whisper.cpp's `whisper_full_params`
+ */
+export type DecodingParams = { 
+/**
+ * Sampling temperature of the first decode, 0 decodes deterministically
+ */
+temperature: number; 
+/**
+ * Added to the temperature on each fallback retry, 0 disables fallback
+ */
+temperature_increment_on_fallback: number; 
+/**
+ * Beam size, 1 decodes greedily. Larger beams are slower but recover from
+ * more early mistakes; gains flatten out past about 5.
+ */
+beam_size: number; 
+/**
+ * Candidates sampled per fallback retry
+ */
+best_of: number; 
+/**
+ * Probability of the no-speech token above which a segment counts as silence
+ */
+no_speech_threshold: number; 
+/**
+ * Feed the previous segment's text as context for the next one
+ */
+condition_on_previous_text: boolean }
+/**
+ * An input device with its channel layout, for configuring multi-channel interfaces
+ */
+export type DetailedAudioDevice = { index: string; name: string; 
+/**
+ * Whether this is the system default input, on Windows the console default
+ */
+is_default: boolean; 
+/**
+ * Whether this is Windows' default input for calls, always false elsewhere
+ */
+is_communications_default: boolean; 
+/**
+ * Channels the device is captured with, None if its configuration can't be read
+ */
+channels: number | null; 
+/**
+ * Configured channels, None when all channels are averaged
+ */
+channel_config: MicChannelConfig | null }
+/**
+ * Payload of `dictation-ended`
+ */
+export type DictationEnded = { progress: PipelineProgress; outcome: DictationOutcome }
+/**
+ * Pace of the dictation being recorded, sent once a second. Word count and rate
+ * stay empty until interim transcripts are available.
+ */
+export type DictationMetrics = { elapsed_ms: number; word_count: number | null; words_per_minute: number | null }
+/**
+ * How a dictation left the pipeline
+ */
+export type DictationOutcome = "pasted" | 
+/**
+ * Appended to the file dictation target instead of being pasted
+ */
+"written_to_file" | 
+/**
+ * Parked until the target app is ready, see `deferred_paste`
+ */
+"deferred" | 
+/**
+ * Dropped by the discard filters or in review
+ */
+"discarded" | 
+/**
+ * No audio or no words
+ */
+"empty" | "failed" | "cancelled"
+/**
+ * Payload of `dictation-segment`, sent when a segment is cut
+ */
+export type DictationSegment = { 
+/**
+ * Counts the session's segments from 1
+ */
+segment: number; 
+/**
+ * The segment's pipeline operation, to match it with `paste-done`
+ */
+operation_id: number | null }
+/**
+ * Payload of `dictations-in-flight`
+ */
+export type DictationsInFlight = { recording: number; 
+/**
+ * Stopped and still being transcribed, post-processed or pasted
+ */
+processing: number }
+export type DiffOp = "equal" | "insert" | "delete"
+export type DiffSpan = { op: DiffOp; text: string }
+export type DownloadProgress = { model_id: string; downloaded: number; total: number; percentage: number }
+/**
+ * Settings as the app uses them, with every default merged in
+ */
+export type EffectiveSettings = { settings: AppSettings; 
+/**
+ * Whether the stored settings lacked defaults or held outdated values, in
+ * which case they were rewritten to match
+ */
+settings_were_migrated: boolean }
 export type EngineType = "Whisper" | "Parakeet" | "Moonshine"
-export type HistoryEntry = { id: number; file_name: string; timestamp: number; saved: boolean; title: string; transcription_text: string; post_processed_text: string | null; post_process_prompt: string | null }
 /**
- * Result of changing keyboard implementation
+ * What the UI can offer the user to resolve an error
+ */
+export type ErrorActionHint = "open_settings" | "grant_permission" | "check_network"
+/**
+ * Which signals mark the start and end of a recording
+ */
+export type FeedbackMode = "sound" | "overlay" | "both" | "none"
+/**
+ * When the stop sound plays. The later timings play a short tick when the
+ * recording stops instead, and the stop sound only once the dictation got that far.
+ */
+export type FeedbackTiming = "recording_stop" | "transcription_complete" | "paste_complete"
+/**
+ * Sent when a transcription couldn't be written in file dictation mode.
+ * The text is copied to the clipboard instead.
+ */
+export type FileDictationWriteFailed = { path: string; error: string }
+/**
+ * Key event sent to frontend during recording mode
+ */
+export type FrontendKeyEvent = { 
+/**
+ * Currently pressed modifier keys
+ */
+modifiers: string[]; 
+/**
+ * The key that was pressed (if any)
+ */
+key: string | null; 
+/**
+ * Whether this is a key down event
+ */
+is_key_down: boolean; 
+/**
+ * The full hotkey string (e.g., "option+space")
+ */
+hotkey_string: string }
+/**
+ * Information about the application that currently has keyboard focus.
+ */
+export type FrontmostApplication = { 
+/**
+ * Human readable application name (e.g. "Safari", "chrome")
+ */
+name: string; 
+/**
+ * Stable identifier: bundle ID on macOS, executable name on Windows/Linux
+ */
+identifier: string | null; 
+/**
+ * Path to the application bundle or executable, if known
+ */
+path: string | null }
+export type HandyError = (HandyErrorKind) & { 
+/**
+ * Whether retrying can succeed without the user changing anything
+ */
+recoverable: boolean; action_hint: ErrorActionHint | null }
+export type HandyErrorKind = 
+/**
+ * An argument or setting value was rejected
+ */
+{ code: "invalid_input"; message: string } | 
+/**
+ * A model, binding, prompt, history entry or similar doesn't exist
+ */
+{ code: "not_found"; message: string } | 
+/**
+ * Missing or inconsistent configuration, such as no provider or model selected
+ */
+{ code: "config"; message: string } | 
+/**
+ * A remote service rejected the credentials
+ */
+{ code: "auth"; message: string } | 
+/**
+ * A request to a remote service failed
+ */
+{ code: "network"; message: string } | 
+/**
+ * An OS permission such as accessibility or microphone access is missing
+ */
+{ code: "permission"; message: string } | 
+/**
+ * Reading or writing files, the database or audio devices failed
+ */
+{ code: "io"; message: string } | 
+/**
+ * Anything without a more specific category
+ */
+{ code: "internal"; message: string }
+export type HistoryEntry = { id: number; file_name: string; timestamp: number; saved: boolean; title: string; transcription_text: string; post_processed_text: string | null; post_process_prompt: string | null; translation_check: TranslationCheck | null; 
+/**
+ * File the text was written to in file dictation mode instead of being pasted
+ */
+output_file: string | null; 
+/**
+ * App prompt rule that selected the prompt for this dictation
+ */
+app_prompt_rule: AppliedPromptRule | null; 
+/**
+ * Speaking rate of the raw transcript, None for very short recordings
+ */
+words_per_minute: number | null; 
+/**
+ * Custom-word corrections applied to the raw transcript, None when no custom
+ * words were configured or the entry predates recording them
+ */
+word_corrections: WordCorrections | null; 
+/**
+ * Parts of the recording separated by the speaker change marker in the
+ * transcript, None when speaker change hints were off
+ */
+speaker_turns: SpeakerTurn[] | null; 
+/**
+ * Set when the selected model didn't fit in GPU memory and a smaller one
+ * transcribed this entry
+ */
+vram_guard: VramGuardDecision | null; 
+/**
+ * The text was pasted as a single line, `transcription_text` keeps its line breaks
+ */
+collapsed_to_single_line: boolean; 
+/**
+ * A cancelled recording kept without transcribing, see `CancelBehavior`.
+ * `transcription_text` stays empty until the entry is retranscribed.
+ */
+draft: boolean }
+/**
+ * Progress of a history search index rebuild
+ */
+export type HistoryIndexProgress = { indexed: number; total: number }
+/**
+ * Result of changing keyboard implementation
+ */
+export type ImplementationChangeResult = { success: boolean; 
+/**
+ * List of binding IDs that were reset to defaults due to incompatibility
+ */
+reset_bindings: string[] }
+export type ImportReport = { 
+/**
+ * Words new to the list, in file order
+ */
+added: string[]; 
+/**
+ * Words already in the list or repeated in the file
+ */
+duplicates: number; 
+/**
+ * Nothing was saved, `added` is what the import would add
+ */
+dry_run: boolean; 
+/**
+ * Size of the list after the import
+ */
+total: number; 
+/**
+ * Set when the list grows past `RECOMMENDED_MAX_CUSTOM_WORDS`. No words are
+ * dropped, every one is still used for correction.
+ */
+warning: string | null }
+export type JsonOutput = 
+/**
+ * Paste the response unchanged
+ */
+"raw" | "pretty" | 
+/**
+ * Paste a single top-level field
+ */
+{ field: string }
+export type LLMPrompt = { id: string; name: string; prompt: string; 
+/**
+ * Structured output requested from providers that support `response_format`
+ */
+response_format?: ResponseFormat | null; 
+/**
+ * What to paste when the response is JSON
+ */
+json_output?: JsonOutput; 
+/**
+ * Short label or emoji shown in compact UIs such as the tray menu
+ */
+short_label?: string | null; 
+/**
+ * Sampling seed for reproducible output, sent to providers that accept one
+ */
+seed?: number | null }
+export type LintSeverity = "error" | "warning"
+/**
+ * Snapshot of a provider's request queue
+ */
+export type LlmQueueStatus = { provider_id: string; in_flight: number; queued_live: number; queued_batch: number; requests_last_minute: number; backoff_remaining_ms: number | null }
+/**
+ * `User-Agent` sent with post-processing requests
+ */
+export type LlmUserAgent = 
+/**
+ * Identifies the app as Handy
+ */
+{ mode: "default" } | { mode: "custom"; value: string } | 
+/**
+ * No `User-Agent` header at all
+ */
+{ mode: "omit" }
+export type LogLevel = "trace" | "debug" | "info" | "warn" | "error"
+export type MemoryWarning = { model_id: string; 
+/**
+ * Estimated memory the model takes once loaded
+ */
+required_mb: number; available_mb: number; total_mb: number; message: string }
+export type MicAnalysis = { 
+/**
+ * The device that was checked, None for the system default
+ */
+device: string | null; sentence: string; transcript: string; 
+/**
+ * Word error rate of the transcript against the sentence, 0 when they match
+ */
+word_error_rate: number; snr_db: number; 
+/**
+ * Share of samples at full scale, between 0 and 1
+ */
+clipping_ratio: number; dc_offset: number; 
+/**
+ * RMS of the loudest frames, i.e. how loud the speech came through
+ */
+speech_rms: number; 
+/**
+ * Sample rate the device claims to capture at
+ */
+device_sample_rate: number; 
+/**
+ * Sample rate the device delivered during the check
+ */
+delivered_sample_rate: number; verdict: MicVerdict; 
+/**
+ * What the user can do about it, empty when the verdict is good
+ */
+suggestions: string[] }
+/**
+ * Input channels used for one microphone
+ */
+export type MicChannelConfig = { 
+/**
+ * One-based channel numbers, as audio interfaces label their inputs
+ */
+channels: number[]; mode?: ChannelMixMode }
+export type MicVerdict = "good" | "ok" | "poor"
+/**
+ * The OS muted the microphone, so a recording was stopped as it started or cut short
+ */
+export type MicrophoneMuted = { 
+/**
+ * The muted input, None for the system default
+ */
+device: string | null; 
+/**
+ * True when the mute came mid-recording and the recording was stopped there
+ */
+during_recording: boolean }
+export type ModelBenchmark = { model_id: string; 
+/**
+ * Length of the benchmark recording
+ */
+audio_ms: number; load_ms: number; 
+/**
+ * Time from handing the audio to the model to getting the transcript
+ */
+latency_ms: number; 
+/**
+ * Latency over recording length, below 1 is faster than real time
+ */
+real_time_factor: number; 
+/**
+ * Growth of resident memory while loading and running the model, None when
+ * the platform doesn't report it
+ */
+memory_mb: number | null }
+export type ModelExtractionFailed = { model_id: string; error: string }
+export type ModelInfo = { id: string; name: string; description: string; filename: string; url: string | null; size_mb: number; is_downloaded: boolean; is_downloading: boolean; partial_size: number; is_directory: boolean; engine_type: EngineType; accuracy_score: number; speed_score: number; 
+/**
+ * Quantizations the weights come in, empty when there is only one file
+ */
+variants: QuantizationVariant[]; 
+/**
+ * Variant that `filename`, `url` and `size_mb` refer to, see `model_quantization`
  */
-export type ImplementationChangeResult = { success: boolean; 
+quantization: string | null; 
 /**
- * List of binding IDs that were reset to defaults due to incompatibility
+ * Parameters in millions
  */
-reset_bindings: string[] }
-export type KeyboardImplementation = "tauri" | "handy_keys"
-export type LLMPrompt = { id: string; name: string; prompt: string }
-export type LogLevel = "trace" | "debug" | "info" | "warn" | "error"
-export type ModelInfo = { id: string; name: string; description: string; filename: string; url: string | null; size_mb: number; is_downloaded: boolean; is_downloading: boolean; partial_size: number; is_directory: boolean; engine_type: EngineType; accuracy_score: number; speed_score: number }
+parameters_m: number; 
+/**
+ * ISO 639-1 codes of the languages the model transcribes
+ */
+languages: string[]; quality_tier: QualityTier; recommended_ram_mb: number; 
+/**
+ * None for models that run on the CPU
+ */
+recommended_vram_mb: number | null; 
+/**
+ * Checksum of `filename`'s download, None when the manifest doesn't list one
+ */
+sha256: string | null }
 export type ModelLoadStatus = { is_loaded: boolean; current_model: string | null }
+/**
+ * Whether a transcription model is in memory, as reported to the frontend
+ */
+export type ModelState = { state: "unloaded" } | { state: "loading"; model_id: string } | { state: "loaded"; model_id: string; 
+/**
+ * Rough memory use, models take about their download size once loaded
+ */
+memory_estimate_mb: number; 
+/**
+ * Seconds since the last transcription or load
+ */
+idle_secs: number }
+export type ModelStateEvent = { event_type: string; model_id: string | null; model_name: string | null; error: string | null }
+/**
+ * `ModelState` along with the power profile that picks the model
+ */
+export type ModelStatus = (ModelState) & { power_profile: ActivePowerProfile }
+/**
+ * What sets models apart, for comparing them, see `list_available_models`
+ */
+export type ModelSummary = { id: string; display_name: string; 
+/**
+ * Taken on disk once downloaded, otherwise the size of the download
+ */
+size_bytes: number; param_count: number; multilingual: boolean; 
+/**
+ * ISO 639-1 codes of the languages the model transcribes
+ */
+languages: string[]; downloaded: boolean }
+/**
+ * Result of `set_active_model`
+ */
+export type ModelSwitch = { status: "switched" } | 
+/**
+ * Nothing changed, the switch waits for confirmation through
+ * `ignore_memory_warning`
+ */
+{ status: "needs_confirmation"; warning: MemoryWarning }
 export type ModelUnloadTimeout = "never" | "immediately" | "min_2" | "min_5" | "min_10" | "min_15" | "hour_1" | "sec_5"
+/**
+ * Output device that feedback sounds now play on
+ */
+export type OutputDeviceChanged = { device: string; 
+/**
+ * True when the selected device is unavailable and the default is used
+ */
+fallback: boolean }
+/**
+ * Text transform applied at paste time for a specific target application
+ */
+export type OutputTransform = "as_is" | "snake_case" | "kebab_case" | "lowercase"
 export type OverlayPosition = "none" | "top" | "bottom"
-export type PasteMethod = "ctrl_v" | "direct" | "none" | "shift_insert" | "ctrl_shift_v"
-export type PostProcessProvider = { id: string; label: string; base_url: string; allow_base_url_edit?: boolean; models_endpoint?: string | null }
+/**
+ * Which state the recording overlay should display
+ */
+export type OverlayState = "recording" | "transcribing" | 
+/**
+ * A deferred transcript is waiting to be pasted
+ */
+"deferred"
+export type PasteMethod = "ctrl_v" | "direct" | "none" | "shift_insert" | "ctrl_shift_v" | 
+/**
+ * Insert through the accessibility API, falling back to CtrlV when unsupported
+ */
+"accessibility"
+/**
+ * How a transcript was delivered to the focused application
+ */
+export type PasteReport = { requested: PasteMethod; used: PasteMethod; 
+/**
+ * Why the requested method wasn't used, when it fell back
+ */
+fallback_reason: string | null; elapsed_ms: number }
+/**
+ * Payload of every pipeline event
+ */
+export type PipelineProgress = { 
+/**
+ * Identifies the dictation, increasing with every recording
+ */
+operation_id: number; binding_id: string; 
+/**
+ * Time since the recording started
+ */
+elapsed_ms: number; 
+/**
+ * Time since the previous event of this dictation, e.g. the inference time for
+ * `inference-done`
+ */
+stage_ms: number }
+/**
+ * The piece of post-processing configuration that is missing
+ */
+export type PostProcessMisconfiguration = { reason: "no_provider" } | { reason: "missing_model"; provider_id: string } | { reason: "no_prompt_selected" } | { reason: "prompt_not_found"; prompt_id: string } | 
+/**
+ * Offline mode is on and the selected provider isn't on this machine
+ */
+{ reason: "remote_provider_offline"; provider_id: string } | { reason: "empty_prompt"; prompt_id: string }
+export type PostProcessProvider = { id: string; label: string; base_url: string; allow_base_url_edit?: boolean; models_endpoint?: string | null; 
+/**
+ * Path of the chat endpoint relative to `base_url`, e.g. for Azure-style gateways
+ */
+chat_completions_path?: string; 
+/**
+ * Sent as the `api-version` query parameter when set
+ */
+api_version?: string | null; 
+/**
+ * Maximum number of requests in flight at once for this provider, 0 means unlimited.
+ * Requests beyond the limit wait in a queue.
+ */
+max_concurrent_requests?: number; 
+/**
+ * Maximum number of requests started per minute, 0 means unlimited
+ */
+requests_per_minute?: number; auth_style?: AuthStyle; 
+/**
+ * Additional headers sent with every request, e.g. an organization ID
+ */
+extra_headers?: Partial<{ [key in string]: string }>; 
+/**
+ * Additional query parameters appended to every request URL, e.g. `api-version`
+ */
+extra_query_params?: Partial<{ [key in string]: string }>; 
+/**
+ * Sent as `OpenAI-Organization` by OpenAI-compatible providers.
+ * Treated like an API key: some orgs consider it sensitive.
+ */
+organization_id?: string | null; 
+/**
+ * Sent as `OpenAI-Project` by OpenAI-compatible providers, also sensitive
+ */
+project_id?: string | null }
+/**
+ * Overrides applied on one power source, unset fields keep the regular setting
+ */
+export type PowerProfile = { 
+/**
+ * Used instead of `selected_model` while it is downloaded
+ */
+model_id?: string | null; 
+/**
+ * Load the model as soon as the profile applies instead of on the first dictation
+ */
+preload?: boolean | null; unload_timeout?: ModelUnloadTimeout | null; 
+/**
+ * Where Whisper models run, the other engines always use the CPU
+ */
+compute_backend?: ComputeBackend | null }
+export type PowerProfiles = { battery?: PowerProfile; 
+/**
+ * Also used by computers without a battery
+ */
+plugged_in?: PowerProfile }
+export type PowerSource = "battery" | "plugged_in"
+/**
+ * Size caps for post-processing requests, see `prompt_limits`
+ */
+export type PromptLimits = { 
+/**
+ * Longer prompt variables are cut, with a marker at the end
+ */
+max_variable_chars: number; 
+/**
+ * Requests whose messages are longer together are not sent
+ */
+max_request_chars: number }
+export type PromptLintIssue = { severity: LintSeverity; message: string }
+/**
+ * Rough transcription quality, for comparing models at a glance
+ */
+export type QualityTier = "basic" | "good" | "great" | "best"
+/**
+ * One quantization of a model's weights, stored in a file of its own. Smaller
+ * quantizations load faster and need less memory, at some cost in accuracy.
+ */
+export type QuantizationVariant = { 
+/**
+ * Quantization name, like "q5_0" or "f16"
+ */
+id: string; filename: string; url: string | null; size_mb: number; sha256: string | null; is_downloaded: boolean }
+/**
+ * Progress of re-encoding stored recordings, see `convert_existing_recordings`
+ */
+export type RecordingConversionProgress = { converted: number; 
+/**
+ * Recordings left as they were because they couldn't be read or re-encoded
+ */
+failed: number; total: number }
+/**
+ * The input stream failed during a recording and was reopened
+ */
+export type RecordingDeviceRecovered = { 
+/**
+ * True when the chosen microphone didn't come back and the default is used
+ */
+fallback: boolean }
+/**
+ * Format new recordings are stored in
+ */
+export type RecordingFormat = "wav" | 
+/**
+ * Lossless, about half the size of WAV
+ */
+"flac" | 
+/**
+ * Lossy speech codec, around a tenth of the size of WAV
+ */
+"opus"
 export type RecordingRetentionPeriod = "never" | "preserve_limit" | "days_3" | "weeks_2" | "months_3"
-export type ShortcutBinding = { id: string; name: string; description: string; default_binding: string; current_binding: string }
+/**
+ * Audio to transcribe in a comparison
+ */
+export type RecordingSource = { type: "history_entry"; value: number } | 
+/**
+ * Path to a WAV file
+ */
+{ type: "file"; value: string }
+export type ResponseFormat = "json_object"
+/**
+ * How a transcript left the review window
+ */
+export type ReviewOutcome = "confirmed" | "discarded" | 
+/**
+ * Resolved by `review_timeout_action`
+ */
+"timed_out" | 
+/**
+ * Dropped because the operation was cancelled
+ */
+"cancelled"
+/**
+ * What happens to a transcript left in review past the timeout
+ */
+export type ReviewTimeoutAction = "confirm" | "discard"
+export type SelfTestCheck = { 
+/**
+ * `model`, `microphone`, `input`, `shortcuts` or `provider`
+ */
+id: string; status: CheckStatus; detail: string; 
+/**
+ * What the user can do about a failure
+ */
+remediation: string | null }
+export type SelfTestReport = { 
+/**
+ * True when no check failed
+ */
+passed: boolean; checks: SelfTestCheck[] }
+export type SessionLogEntry = { timestamp: number; word_count: number; 
+/**
+ * Application dictated into, when it could be determined
+ */
+app: string | null; 
+/**
+ * Length of the recording
+ */
+duration_ms: number; 
+/**
+ * False when transcription failed
+ */
+success: boolean; 
+/**
+ * Only kept with `session_log_store_text`
+ */
+text: string | null }
+/**
+ * Totals of today's dictations
+ */
+export type SessionSummary = { 
+/**
+ * `YYYY-MM-DD`, in local time
+ */
+date: string; dictations: number; failed: number; words: number; duration_ms: number; 
+/**
+ * Over the successful dictations long enough to rate, None without any
+ */
+average_wpm: number | null }
+/**
+ * Payload for the generic `settings-changed` event
+ */
+export type SettingChanged = { setting: string; value: boolean }
+export type ShortcutBinding = { id: string; name: string; description: string; default_binding: string; current_binding: string; 
+/**
+ * Overrides the global `review_before_paste` setting for this binding
+ */
+review_before_paste?: boolean | null; 
+/**
+ * Overrides the global `feedback_mode` setting for this binding
+ */
+feedback_mode?: FeedbackMode | null; 
+/**
+ * Whether the key is the physical key or the character it types, see `shortcut::layout`
+ */
+match_mode?: BindingMatchMode; 
+/**
+ * Overrides the global `collapse_to_single_line` setting for this binding
+ */
+collapse_to_single_line?: boolean | null; 
+/**
+ * Model this binding transcribes with instead of `selected_model`
+ */
+selected_model?: string | null }
+/**
+ * A binding pressed or released while shortcut test mode is on
+ */
+export type ShortcutTestFired = { binding_id: string; shortcut: string; pressed: boolean }
 export type SoundTheme = "marimba" | "pop" | "custom"
+/**
+ * Part of a recording attributed to one speaker
+ */
+export type SpeakerTurn = { start_ms: number; end_ms: number }
+/**
+ * Average speaking rate over past dictations, see `get_speaking_rate_stats`
+ */
+export type SpeakingRateStats = { average_wpm: number | null; dictations: number; 
+/**
+ * Oldest day first, in local time
+ */
+daily: DailySpeakingRate[]; 
+/**
+ * Today's dictations from the session log, None when not read
+ */
+today: SessionSummary | null }
+export type SystemMemory = { total_mb: number; 
+/**
+ * Memory that can be used without swapping, including reclaimable caches
+ */
+available_mb: number }
+/**
+ * Payload of the `transcript-review` event
+ */
+export type TranscriptReview = { id: number; text: string; 
+/**
+ * Seconds until the review resolves on its own, 0 when it waits indefinitely
+ */
+timeout_secs: number }
+/**
+ * Payload of the `transcript-review-ended` event
+ */
+export type TranscriptReviewEnded = { id: number; outcome: ReviewOutcome }
+export type TranscriptRingEntry = { text: string; timestamp: number }
+/**
+ * Sent when a transcript was shortened before pasting; history keeps the full text
+ */
+export type TranscriptTruncated = { original_chars: number; max_chars: number }
+/**
+ * Sent instead of pasting when a transcript was blank or a known hallucination
+ */
+export type TranscriptionDiscarded = { text: string; 
+/**
+ * Whether the recording was saved to history anyway
+ */
+kept_in_history: boolean }
+/**
+ * A failed inference is being retried
+ */
+export type TranscriptionRetrying = { 
+/**
+ * Starts at 1 for the first retry
+ */
+attempt: number; max_retries: number; error: string }
+/**
+ * Verdict of the post-decode language check, stored with the history entry
+ */
+export type TranslationCheck = { 
+/**
+ * ISO 639-3 code of the detected language, if detection succeeded
+ */
+detected_language: string | null; confidence: number; suspect: boolean; 
+/**
+ * Whether the decode was retried with English forced
+ */
+retried: boolean }
+/**
+ * What to do when translated output doesn't look like English
+ */
+export type TranslationGuard = "off" | "flag" | "retry"
+export type TruncationStrategy = "none" | "truncate" | "ellipsis"
+/**
+ * What to do when a model won't fit in the free GPU memory, see `vram_guard`
+ */
+export type VramGuardBehavior = 
+/**
+ * Load the model anyway
+ */
+"off" | 
+/**
+ * Fail the load with an error saying how much memory is missing
+ */
+"refuse" | 
+/**
+ * Load the most accurate downloaded model that fits instead
+ */
+"smaller_model" | 
+/**
+ * Load the model on the CPU, slower but without paging GPU memory
+ */
+"cpu_fallback"
+/**
+ * A model swapped for a smaller one because it wouldn't fit in GPU memory
+ */
+export type VramGuardDecision = { 
+/**
+ * Model that was selected
+ */
+requested_model: string; 
+/**
+ * Model that was loaded instead
+ */
+loaded_model: string; 
+/**
+ * Estimated GPU memory the selected model needs
+ */
+required_mb: number; 
+/**
+ * GPU memory that was free when loading, counting the model it replaced
+ */
+available_mb: number }
+/**
+ * The wake phrase was heard, see `wake_word`
+ */
+export type WakeWordDetection = { model: string; score: number; 
+/**
+ * Heard during `test_wake_word`, so no recording was started
+ */
+test: boolean }
+export type WakeWordModel = { id: string; phrase: string }
+/**
+ * Outcome of `test_wake_word`
+ */
+export type WakeWordTest = { model: string; 
+/**
+ * Score a detection has to reach at the current sensitivity
+ */
+threshold: number; 
+/**
+ * Highest score heard, to tune the sensitivity against
+ */
+peak_score: number; detections: WakeWordDetection[] }
+/**
+ * A folder whose new audio files are transcribed automatically
+ */
+export type WatchFolderConfig = { id: string; path: string; enabled: boolean; 
+/**
+ * Post-process the transcripts, whatever the global setting
+ */
+post_process?: boolean; output?: WatchFolderOutput; journal_path?: string | null }
+/**
+ * A watch folder couldn't be watched or one of its files failed to transcribe
+ */
+export type WatchFolderError = { folder_id: string; 
+/**
+ * File that failed, None when watching the folder itself failed
+ */
+path: string | null; error: string }
+/**
+ * Where transcripts of files picked up from a watch folder go, besides history
+ */
+export type WatchFolderOutput = "history_only" | 
+/**
+ * A `.txt` file next to the audio file, with the same name
+ */
+"sidecar" | 
+/**
+ * Appended to `journal_path`, separated like file dictation entries
+ */
+"journal"
+/**
+ * Which of Windows' default inputs is used when no microphone is selected
+ */
+export type WindowsDeviceRole = 
+/**
+ * The default device for general use
+ */
+"console" | 
+/**
+ * The default device for calls, usually a headset
+ */
+"communications"
+/**
+ * A word that custom-word correction replaced, with surrounding punctuation
+ */
+export type WordCorrection = { from: string; to: string }
+/**
+ * Custom-word corrections applied to a transcript
+ */
+export type WordCorrections = { 
+/**
+ * `word_correction_threshold` the words were matched with
+ */
+threshold: number; algorithm?: CorrectionAlgorithm; 
+/**
+ * In text order, empty when no word was close enough to a custom word
+ */
+applied: WordCorrection[] }
+export type WordDiff = { spans: DiffSpan[]; 
+/**
+ * The texts were too long to compare and `spans` is empty
+ */
+skipped: boolean }
+export type WordListFormat = 
+/**
+ * One word per line, lines starting with `#` are skipped
+ */
+"text" | 
+/**
+ * The first column of each row, a `word` or `term` header is skipped
+ */
+"csv" | 
+/**
+ * A plist of macOS text replacements, import only
+ */
+"macos_text_replacements"
 
 /** tauri-specta globals **/
 
@@ -0,0 +1,34 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { useTranslation } from "react-i18next";
+import { toast } from "sonner";
+import { commands, type MicrophoneMuted } from "@/bindings";
+import { formatError } from "@/lib/utils/errors";
+
+// Offers to unmute when a recording was refused or stopped on a muted microphone
+export const MicrophoneMutedNotice = () => {
+  const { t } = useTranslation();
+
+  useEffect(() => {
+    const unmute = async () => {
+      const result = await commands.unmuteMicrophone();
+      if (result.status === "error") {
+        toast.error(formatError(result.error));
+      }
+    };
+
+    const unlisten = listen<MicrophoneMuted>("microphone-muted", (event) => {
+      toast.error(t("microphoneMuted.title"), {
+        description: event.payload.during_recording
+          ? t("microphoneMuted.duringRecording")
+          : t("microphoneMuted.refused"),
+        action: { label: t("microphoneMuted.unmute"), onClick: unmute },
+      });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [t]);
+
+  return null;
+};
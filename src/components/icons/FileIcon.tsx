@@ -0,0 +1,33 @@
+import React from "react";
+
+interface FileIconProps {
+  width?: number;
+  height?: number;
+  color?: string;
+  className?: string;
+}
+
+const FileIcon: React.FC<FileIconProps> = ({
+  width = 24,
+  height = 24,
+  color = "#FAA2CA",
+  className = "",
+}) => {
+  return (
+    <svg
+      width={width}
+      height={height}
+      viewBox="0 0 24 24"
+      fill="none"
+      xmlns="http://www.w3.org/2000/svg"
+      className={className}
+    >
+      <g fill={color}>
+        <path d="m7 2h6.586c.2652 0 .5196.10536.7071.29289l5.4142 5.41422c.1875.18753.2929.44188.2929.70711v10.58578c0 1.6569-1.3431 3-3 3h-10c-1.65685 0-3-1.3431-3-3v-14c0-1.65685 1.34315-3 3-3zm0 2c-.55228 0-1 .44772-1 1v14c0 .5523.44772 1 1 1h10c.5523 0 1-.4477 1-1v-10h-4c-.5523 0-1-.44772-1-1v-4z" />
+        <path d="m8 13h8v2h-8zm0 3h5v2h-5z" opacity=".6" />
+      </g>
+    </svg>
+  );
+};
+
+export default FileIcon;
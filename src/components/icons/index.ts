@@ -1,3 +1,4 @@
 export { default as MicrophoneIcon } from "./MicrophoneIcon";
 export { default as TranscriptionIcon } from "./TranscriptionIcon";
 export { default as CancelIcon } from "./CancelIcon";
+export { default as FileIcon } from "./FileIcon";
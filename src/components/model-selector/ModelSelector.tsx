@@ -3,6 +3,8 @@ import { useTranslation } from "react-i18next";
 import { listen } from "@tauri-apps/api/event";
 import { produce } from "immer";
 import { commands, type ModelInfo } from "@/bindings";
+import { formatError } from "@/lib/utils/errors";
+import { switchModel } from "@/lib/utils/modelSwitch";
 import { getTranslatedModelName } from "../../lib/utils/modelTranslation";
 import ModelStatusButton from "./ModelStatusButton";
 import ModelDropdown from "./ModelDropdown";
@@ -291,16 +293,20 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
   };
 
   const handleModelSelect = async (modelId: string) => {
+    const previousModelId = currentModelId;
     try {
       setCurrentModelId(modelId); // Set optimistically so loading text shows correct model
       setModelError(null);
       setShowModelDropdown(false);
-      const result = await commands.setActiveModel(modelId);
+      const result = await switchModel(modelId);
       if (result.status === "error") {
-        const errorMsg = result.error;
+        const errorMsg = formatError(result.error);
         setModelError(errorMsg);
         setModelStatus("error");
         onError?.(errorMsg);
+      } else if (!result.data) {
+        // Declined after the memory warning, the previous model stays
+        setCurrentModelId(previousModelId);
       }
     } catch (err) {
       const errorMsg = `${err}`;
@@ -315,7 +321,7 @@ const ModelSelector: React.FC<ModelSelectorProps> = ({ onError }) => {
       setModelError(null);
       const result = await commands.downloadModel(modelId);
       if (result.status === "error") {
-        const errorMsg = result.error;
+        const errorMsg = formatError(result.error);
         setModelError(errorMsg);
         setModelStatus("error");
         onError?.(errorMsg);
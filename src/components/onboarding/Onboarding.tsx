@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from "react";
 import { useTranslation } from "react-i18next";
 import { commands, type ModelInfo } from "@/bindings";
+import { formatError } from "@/lib/utils/errors";
 import ModelCard from "./ModelCard";
 import HandyTextLogo from "../icons/HandyTextLogo";
 
@@ -44,7 +45,11 @@ const Onboarding: React.FC<OnboardingProps> = ({ onModelSelected }) => {
       const result = await commands.downloadModel(modelId);
       if (result.status === "error") {
         console.error("Download failed:", result.error);
-        setError(t("onboarding.errors.downloadModel", { error: result.error }));
+        setError(
+          t("onboarding.errors.downloadModel", {
+            error: formatError(result.error),
+          }),
+        );
         setDownloading(false);
       }
     } catch (err) {
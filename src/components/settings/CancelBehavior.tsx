@@ -0,0 +1,54 @@
+import React from "react";
+import { useTranslation } from "react-i18next";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+import type { CancelBehavior } from "@/bindings";
+
+interface CancelBehaviorProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const CancelBehaviorSetting: React.FC<CancelBehaviorProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { t } = useTranslation();
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const cancelBehaviorOptions = [
+      {
+        value: "discard",
+        label: t("settings.advanced.cancelBehavior.options.discard"),
+      },
+      {
+        value: "save_draft",
+        label: t("settings.advanced.cancelBehavior.options.saveDraft"),
+      },
+      {
+        value: "transcribe_silently",
+        label: t("settings.advanced.cancelBehavior.options.transcribeSilently"),
+      },
+    ];
+
+    const selectedBehavior = (getSetting("cancel_behavior") ||
+      "discard") as CancelBehavior;
+
+    return (
+      <SettingContainer
+        title={t("settings.advanced.cancelBehavior.title")}
+        description={t("settings.advanced.cancelBehavior.description")}
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      >
+        <Dropdown
+          options={cancelBehaviorOptions}
+          selectedValue={selectedBehavior}
+          onSelect={(value) =>
+            updateSetting("cancel_behavior", value as CancelBehavior)
+          }
+          disabled={isUpdating("cancel_behavior")}
+        />
+      </SettingContainer>
+    );
+  },
+);
@@ -1,10 +1,17 @@
 import React, { useState } from "react";
 import { useTranslation } from "react-i18next";
+import { commands } from "@/bindings";
+import { LANGUAGES } from "@/lib/constants/languages";
+import { formatError } from "@/lib/utils/errors";
 import { useSettings } from "../../hooks/useSettings";
 import { Input } from "../ui/Input";
 import { Button } from "../ui/Button";
+import { Dropdown } from "../ui/Dropdown";
 import { SettingContainer } from "../ui/SettingContainer";
 
+// Scope of the words used for every language
+const ALL_LANGUAGES = "all";
+
 interface CustomWordsProps {
   descriptionMode?: "inline" | "tooltip";
   grouped?: boolean;
@@ -13,11 +20,45 @@ interface CustomWordsProps {
 export const CustomWords: React.FC<CustomWordsProps> = React.memo(
   ({ descriptionMode = "tooltip", grouped = false }) => {
     const { t } = useTranslation();
-    const { getSetting, updateSetting, isUpdating } = useSettings();
+    const { getSetting, updateSetting, refreshSettings, isUpdating } =
+      useSettings();
     const [newWord, setNewWord] = useState("");
+    const [scope, setScope] = useState(ALL_LANGUAGES);
     const [error, setError] = useState<string | null>(null);
     const [isLoading, setIsLoading] = useState(false);
-    const customWords = getSetting("custom_words") || [];
+    const wordsByLanguage = getSetting("custom_words_by_language") || {};
+    const customWords =
+      scope === ALL_LANGUAGES
+        ? getSetting("custom_words") || []
+        : wordsByLanguage[scope] || [];
+
+    const scopeOptions = [
+      {
+        value: ALL_LANGUAGES,
+        label: t("settings.advanced.customWords.allLanguages"),
+      },
+      ...LANGUAGES.filter((language) => language.value !== "auto").map(
+        (language) => {
+          const count = wordsByLanguage[language.value]?.length ?? 0;
+          return {
+            value: language.value,
+            label: count > 0 ? `${language.label} (${count})` : language.label,
+          };
+        },
+      ),
+    ];
+
+    const saveWords = async (words: string[]) => {
+      if (scope === ALL_LANGUAGES) {
+        await updateSetting("custom_words", words);
+        return;
+      }
+      const result = await commands.updateLanguageCustomWords(scope, words);
+      if (result.status === "error") {
+        throw new Error(formatError(result.error));
+      }
+      await refreshSettings();
+    };
 
     const handleAddWord = async () => {
       setError(null);
@@ -32,7 +73,7 @@ export const CustomWords: React.FC<CustomWordsProps> = React.memo(
       ) {
         try {
           setIsLoading(true);
-          await updateSetting("custom_words", [...customWords, sanitizedWord]);
+          await saveWords([...customWords, sanitizedWord]);
           setNewWord("");
         } catch (err: any) {
           console.error("Failed to add custom word:", err);
@@ -50,10 +91,7 @@ export const CustomWords: React.FC<CustomWordsProps> = React.memo(
     const handleRemoveWord = async (wordToRemove: string) => {
       setError(null);
       try {
-        await updateSetting(
-          "custom_words",
-          customWords.filter((word) => word !== wordToRemove),
-        );
+        await saveWords(customWords.filter((word) => word !== wordToRemove));
       } catch (err: any) {
         console.error("Failed to remove custom word:", err);
         setError(
@@ -81,6 +119,13 @@ export const CustomWords: React.FC<CustomWordsProps> = React.memo(
         >
           <div className="flex flex-col gap-2">
             <div className="flex items-center gap-2">
+              <Dropdown
+                className="max-w-44"
+                options={scopeOptions}
+                selectedValue={scope}
+                onSelect={setScope}
+                disabled={isLoading}
+              />
               <Input
                 type="text"
                 className="max-w-40"
@@ -0,0 +1,132 @@
+import React, { useState } from "react";
+import { useTranslation } from "react-i18next";
+import { commands, type MicAnalysis } from "@/bindings";
+import { formatError } from "@/lib/utils/errors";
+import { SettingContainer } from "../ui/SettingContainer";
+import { Button } from "../ui/Button";
+
+interface MicrophoneCheckProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+type Step =
+  | { kind: "idle" }
+  | { kind: "ready"; sentence: string }
+  | { kind: "recording"; sentence: string }
+  | { kind: "done"; analysis: MicAnalysis }
+  | { kind: "failed"; error: string };
+
+const verdictClasses: Record<MicAnalysis["verdict"], string> = {
+  good: "text-green-500",
+  ok: "text-yellow-500",
+  poor: "text-red-400",
+};
+
+export const MicrophoneCheck: React.FC<MicrophoneCheckProps> = ({
+  descriptionMode = "tooltip",
+  grouped = false,
+}) => {
+  const { t } = useTranslation();
+  const [step, setStep] = useState<Step>({ kind: "idle" });
+
+  const prepare = async () => {
+    const sentence = await commands.getMicCheckSentence();
+    setStep({ kind: "ready", sentence });
+  };
+
+  const run = async (sentence: string) => {
+    setStep({ kind: "recording", sentence });
+    try {
+      const result = await commands.analyzeMicrophone(null);
+      if (result.status === "ok") {
+        setStep({ kind: "done", analysis: result.data });
+      } else {
+        setStep({ kind: "failed", error: formatError(result.error) });
+      }
+    } catch (err) {
+      setStep({ kind: "failed", error: String(err) });
+    }
+  };
+
+  return (
+    <SettingContainer
+      title={t("settings.sound.microphoneCheck.title")}
+      description={t("settings.sound.microphoneCheck.description")}
+      descriptionMode={descriptionMode}
+      grouped={grouped}
+      layout={step.kind === "idle" ? "horizontal" : "stacked"}
+    >
+      {step.kind === "idle" && (
+        <Button variant="secondary" size="sm" onClick={prepare}>
+          {t("settings.sound.microphoneCheck.start")}
+        </Button>
+      )}
+
+      {(step.kind === "ready" || step.kind === "recording") && (
+        <div className="flex flex-col gap-2">
+          <p className="text-sm text-text/70">
+            {step.kind === "ready"
+              ? t("settings.sound.microphoneCheck.instructions")
+              : t("settings.sound.microphoneCheck.recording")}
+          </p>
+          <p className="text-sm font-medium border border-mid-gray/20 rounded p-2">
+            {step.sentence}
+          </p>
+          <div>
+            <Button
+              variant="primary"
+              size="sm"
+              disabled={step.kind === "recording"}
+              onClick={() => run(step.sentence)}
+            >
+              {t("settings.sound.microphoneCheck.record")}
+            </Button>
+          </div>
+        </div>
+      )}
+
+      {step.kind === "done" && (
+        <div className="flex flex-col gap-2 text-sm">
+          <p
+            className={`font-semibold ${verdictClasses[step.analysis.verdict]}`}
+          >
+            {t(
+              `settings.sound.microphoneCheck.verdict.${step.analysis.verdict}`,
+            )}
+          </p>
+          <p className="text-text/70">
+            {t("settings.sound.microphoneCheck.metrics", {
+              snr: step.analysis.snr_db.toFixed(1),
+              wer: Math.round(step.analysis.word_error_rate * 100),
+              rate: step.analysis.delivered_sample_rate,
+            })}
+          </p>
+          {step.analysis.suggestions.length > 0 && (
+            <ul className="list-disc pl-5 space-y-1">
+              {step.analysis.suggestions.map((suggestion) => (
+                <li key={suggestion}>{suggestion}</li>
+              ))}
+            </ul>
+          )}
+          <div>
+            <Button variant="secondary" size="sm" onClick={prepare}>
+              {t("settings.sound.microphoneCheck.again")}
+            </Button>
+          </div>
+        </div>
+      )}
+
+      {step.kind === "failed" && (
+        <div className="flex flex-col gap-2 text-sm">
+          <p className="text-red-400">{step.error}</p>
+          <div>
+            <Button variant="secondary" size="sm" onClick={prepare}>
+              {t("settings.sound.microphoneCheck.again")}
+            </Button>
+          </div>
+        </div>
+      )}
+    </SettingContainer>
+  );
+};
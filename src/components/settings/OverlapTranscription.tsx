@@ -0,0 +1,29 @@
+import React from "react";
+import { useTranslation } from "react-i18next";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+
+interface OverlapTranscriptionProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const OverlapTranscription: React.FC<OverlapTranscriptionProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { t } = useTranslation();
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const enabled = getSetting("overlap_transcription") ?? false;
+
+    return (
+      <ToggleSwitch
+        checked={enabled}
+        onChange={(enabled) => updateSetting("overlap_transcription", enabled)}
+        isUpdating={isUpdating("overlap_transcription")}
+        label={t("settings.advanced.overlapTranscription.label")}
+        description={t("settings.advanced.overlapTranscription.description")}
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      />
+    );
+  });
@@ -37,6 +37,14 @@ export const PasteMethodSetting: React.FC<PasteMethodProps> = React.memo(
         },
       ];
 
+      // Accessibility insertion is implemented for macOS and Windows only
+      if (osType === "macos" || osType === "windows") {
+        options.push({
+          value: "accessibility",
+          label: t("settings.advanced.pasteMethod.options.accessibility"),
+        });
+      }
+
       // Add Shift+Insert and Ctrl+Shift+V options for Windows and Linux only
       if (osType === "windows" || osType === "linux") {
         options.push(
@@ -0,0 +1,30 @@
+import React from "react";
+import { useTranslation } from "react-i18next";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+
+interface WatchSettingsFileToggleProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const WatchSettingsFileToggle: React.FC<WatchSettingsFileToggleProps> = ({
+  descriptionMode = "tooltip",
+  grouped = false,
+}) => {
+  const { t } = useTranslation();
+  const { getSetting, updateSetting, isUpdating } = useSettings();
+  const watchSettingsFile = getSetting("watch_settings_file") ?? true;
+
+  return (
+    <ToggleSwitch
+      checked={watchSettingsFile}
+      onChange={(enabled) => updateSetting("watch_settings_file", enabled)}
+      isUpdating={isUpdating("watch_settings_file")}
+      label={t("settings.debug.watchSettingsFile.label")}
+      description={t("settings.debug.watchSettingsFile.description")}
+      descriptionMode={descriptionMode}
+      grouped={grouped}
+    />
+  );
+};
@@ -1,6 +1,7 @@
 import React, { useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { commands } from "@/bindings";
+import { formatError } from "@/lib/utils/errors";
 import { SettingContainer } from "../../ui/SettingContainer";
 import { PathDisplay } from "../../ui/PathDisplay";
 
@@ -25,7 +26,7 @@ export const LogDirectory: React.FC<LogDirectoryProps> = ({
         if (result.status === "ok") {
           setLogDir(result.data);
         } else {
-          setError(result.error);
+          setError(formatError(result.error));
         }
       } catch (err) {
         const errorMessage =
@@ -2,7 +2,14 @@ import React, { useState, useEffect, useCallback } from "react";
 import { useTranslation } from "react-i18next";
 import { AudioPlayer } from "../../ui/AudioPlayer";
 import { Button } from "../../ui/Button";
-import { Copy, Star, Check, Trash2, FolderOpen } from "lucide-react";
+import {
+  Copy,
+  Star,
+  Check,
+  Trash2,
+  FolderOpen,
+  RotateCw,
+} from "lucide-react";
 import { convertFileSrc } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { readFile } from "@tauri-apps/plugin-fs";
@@ -230,6 +237,7 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
   const { t, i18n } = useTranslation();
   const [audioUrl, setAudioUrl] = useState<string | null>(null);
   const [showCopied, setShowCopied] = useState(false);
+  const [transcribing, setTranscribing] = useState(false);
 
   useEffect(() => {
     let cancelled = false;
@@ -272,6 +280,18 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
     }
   };
 
+  const handleTranscribeDraft = async () => {
+    setTranscribing(true);
+    try {
+      const result = await commands.retranscribeHistoryEntry(entry.id);
+      if (result.status === "error") {
+        console.error("Failed to transcribe draft:", result.error);
+      }
+    } finally {
+      setTranscribing(false);
+    }
+  };
+
   const formattedDate = formatDateTime(String(entry.timestamp), i18n.language);
 
   return (
@@ -318,9 +338,30 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
           </button>
         </div>
       </div>
-      <p className="italic text-text/90 text-sm pb-2 select-text cursor-text">
-        {entry.transcription_text}
-      </p>
+      {entry.draft ? (
+        <div className="flex items-center justify-between gap-2 border border-dashed border-mid-gray/40 rounded px-3 py-2">
+          <p className="text-text/50 text-sm">{t("settings.history.draft")}</p>
+          <Button
+            onClick={handleTranscribeDraft}
+            disabled={transcribing}
+            variant="secondary"
+            size="sm"
+            className="flex items-center gap-2"
+          >
+            <RotateCw
+              className={`w-4 h-4 ${transcribing ? "animate-spin" : ""}`}
+            />
+            <span>{t("settings.history.transcribeDraft")}</span>
+          </Button>
+        </div>
+      ) : (
+        <p
+          dir="auto"
+          className="italic text-text/90 text-sm pb-2 select-text cursor-text"
+        >
+          {entry.transcription_text}
+        </p>
+      )}
       {audioUrl && <AudioPlayer src={audioUrl} className="w-full" />}
     </div>
   );
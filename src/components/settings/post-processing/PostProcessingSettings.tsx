@@ -3,6 +3,7 @@ import { useTranslation } from "react-i18next";
 import { RefreshCcw } from "lucide-react";
 import { toast } from "sonner";
 import { commands } from "@/bindings";
+import { formatError } from "@/lib/utils/errors";
 
 import { Alert } from "../../ui/Alert";
 import {
@@ -499,7 +500,7 @@ const PostProcessingTestComponent: React.FC = () => {
       if (result.status === "ok") {
         setOutputText(result.data);
       } else {
-        setError(result.error);
+        setError(formatError(result.error));
       }
     } catch (err) {
       if (abortControllerRef.current?.signal.aborted) {
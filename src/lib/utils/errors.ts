@@ -0,0 +1,21 @@
+import type { HandyError } from "@/bindings";
+
+const isHandyError = (error: unknown): error is HandyError =>
+  typeof error === "object" &&
+  error !== null &&
+  "code" in error &&
+  "message" in error;
+
+/**
+ * Human-readable text for an error returned by a command. Command errors carry a
+ * `code` for branching and a `message` for display; anything else is stringified.
+ */
+export const formatError = (error: unknown): string => {
+  if (isHandyError(error)) {
+    return error.message;
+  }
+  if (error instanceof Error) {
+    return error.message;
+  }
+  return String(error);
+};
@@ -0,0 +1,29 @@
+import { commands, type HandyError, type Result } from "@/bindings";
+import i18n from "@/i18n";
+
+/**
+ * Switches to a model. When the backend warns that it may not fit in the free
+ * memory, the user is asked first; `false` means they declined and nothing changed.
+ */
+export const switchModel = async (
+  modelId: string,
+): Promise<Result<boolean, HandyError>> => {
+  const result = await commands.setActiveModel(modelId, null);
+  if (result.status === "error") {
+    return result;
+  }
+  if (result.data.status === "switched") {
+    return { status: "ok", data: true };
+  }
+
+  const confirmed = window.confirm(
+    i18n.t("modelSelector.memoryWarning", {
+      message: result.data.warning.message,
+    }),
+  );
+  if (!confirmed) {
+    return { status: "ok", data: false };
+  }
+  const retried = await commands.setActiveModel(modelId, true);
+  return retried.status === "error" ? retried : { status: "ok", data: true };
+};
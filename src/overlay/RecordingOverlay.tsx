@@ -1,3 +1,4 @@
+import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import React, { useEffect, useRef, useState } from "react";
 import { useTranslation } from "react-i18next";
@@ -5,18 +6,21 @@ import {
   MicrophoneIcon,
   TranscriptionIcon,
   CancelIcon,
+  FileIcon,
 } from "../components/icons";
 import "./RecordingOverlay.css";
 import { commands } from "@/bindings";
 import { syncLanguageFromSettings } from "@/i18n";
 
-type OverlayState = "recording" | "transcribing";
+type OverlayState = "recording" | "transcribing" | "deferred";
 
 const RecordingOverlay: React.FC = () => {
   const { t } = useTranslation();
   const [isVisible, setIsVisible] = useState(false);
   const [state, setState] = useState<OverlayState>("recording");
   const [levels, setLevels] = useState<number[]>(Array(16).fill(0));
+  const [dictationFile, setDictationFile] = useState<string | null>(null);
+  const [interactive, setInteractive] = useState(false);
   const smoothedLevelsRef = useRef<number[]>(Array(16).fill(0));
 
   useEffect(() => {
@@ -49,11 +53,31 @@ const RecordingOverlay: React.FC = () => {
         setLevels(smoothed.slice(0, 9));
       });
 
+      // Show a file indicator while transcriptions go to a file instead of being pasted
+      const unlistenFile = await listen<string | null>(
+        "file-dictation-changed",
+        (event) => {
+          setDictationFile(event.payload);
+        },
+      );
+
+      // The backend sends the interactive setting before each show
+      const unlistenSettings = await listen<{
+        setting: string;
+        value: boolean;
+      }>("settings-changed", (event) => {
+        if (event.payload.setting === "overlay_interactive") {
+          setInteractive(event.payload.value);
+        }
+      });
+
       // Cleanup function
       return () => {
         unlistenShow();
         unlistenHide();
         unlistenLevel();
+        unlistenFile();
+        unlistenSettings();
       };
     };
 
@@ -61,6 +85,9 @@ const RecordingOverlay: React.FC = () => {
   }, []);
 
   const getIcon = () => {
+    if (dictationFile) {
+      return <FileIcon />;
+    }
     if (state === "recording") {
       return <MicrophoneIcon />;
     } else {
@@ -68,8 +95,24 @@ const RecordingOverlay: React.FC = () => {
     }
   };
 
+  const canStop = interactive && state === "recording";
+  const canFlush = interactive && state === "deferred";
+
   return (
-    <div className={`recording-overlay ${isVisible ? "fade-in" : ""}`}>
+    <div
+      className={`recording-overlay ${isVisible ? "fade-in" : ""} ${canStop || canFlush ? "interactive" : ""}`}
+      onClick={() => {
+        if (canStop) {
+          invoke("stop_recording").catch((error) =>
+            console.warn("Failed to stop recording:", error),
+          );
+        } else if (canFlush) {
+          invoke("flush_deferred_paste").catch((error) =>
+            console.warn("Failed to paste deferred transcript:", error),
+          );
+        }
+      }}
+    >
       <div className="overlay-left">{getIcon()}</div>
 
       <div className="overlay-middle">
@@ -91,13 +134,17 @@ const RecordingOverlay: React.FC = () => {
         {state === "transcribing" && (
           <div className="transcribing-text">{t("overlay.transcribing")}</div>
         )}
+        {state === "deferred" && (
+          <div className="transcribing-text">{t("overlay.deferred")}</div>
+        )}
       </div>
 
       <div className="overlay-right">
-        {state === "recording" && (
+        {canStop && (
           <div
             className="cancel-button"
-            onClick={() => {
+            onClick={(event) => {
+              event.stopPropagation();
               commands.cancelOperation();
             }}
           >
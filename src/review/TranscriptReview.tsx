@@ -0,0 +1,117 @@
+import { listen } from "@tauri-apps/api/event";
+import React, { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import "./TranscriptReview.css";
+import {
+  commands,
+  type TranscriptReview as PendingTranscript,
+  type TranscriptReviewEnded,
+} from "@/bindings";
+import { syncLanguageFromSettings } from "@/i18n";
+
+const TranscriptReview: React.FC = () => {
+  const { t } = useTranslation();
+  const [queue, setQueue] = useState<PendingTranscript[]>([]);
+  const [draft, setDraft] = useState("");
+  const current = queue[0];
+
+  useEffect(() => {
+    const setup = async () => {
+      await syncLanguageFromSettings();
+      // Pick up transcripts that arrived before this window finished loading
+      const pending = await commands.getPendingTranscriptReviews();
+      if (pending.status === "ok") {
+        setQueue(pending.data);
+      }
+
+      const unlistenReview = await listen<PendingTranscript>(
+        "transcript-review",
+        (event) => {
+          setQueue((prev) =>
+            prev.some((p) => p.id === event.payload.id)
+              ? prev
+              : [...prev, event.payload],
+          );
+        },
+      );
+      // Timeouts and cancellation resolve reviews without this window
+      const unlistenEnded = await listen<TranscriptReviewEnded>(
+        "transcript-review-ended",
+        (event) => {
+          setQueue((prev) => prev.filter((p) => p.id !== event.payload.id));
+        },
+      );
+      return () => {
+        unlistenReview();
+        unlistenEnded();
+      };
+    };
+
+    const cleanup = setup();
+    return () => {
+      cleanup.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  useEffect(() => {
+    setDraft(current?.text ?? "");
+  }, [current?.id]);
+
+  const resolve = async (confirm: boolean) => {
+    if (!current) return;
+    const result = confirm
+      ? await commands.confirmTranscript(
+          current.id,
+          draft === current.text ? null : draft,
+        )
+      : await commands.discardTranscript(current.id);
+    if (result.status === "error") {
+      // Already resolved, e.g. by the timeout
+      console.warn("Failed to resolve transcript review:", result.error);
+    }
+    setQueue((prev) => prev.filter((p) => p.id !== current.id));
+  };
+
+  const onKeyDown = (event: React.KeyboardEvent<HTMLTextAreaElement>) => {
+    if (event.key === "Enter" && (event.metaKey || event.ctrlKey)) {
+      event.preventDefault();
+      resolve(true);
+    } else if (event.key === "Escape") {
+      event.preventDefault();
+      resolve(false);
+    }
+  };
+
+  if (!current) {
+    return null;
+  }
+
+  return (
+    <div className="transcript-review">
+      <div className="review-header">{t("review.title")}</div>
+      <textarea
+        className="review-text"
+        value={draft}
+        autoFocus
+        onChange={(e) => setDraft(e.target.value)}
+        onKeyDown={onKeyDown}
+      />
+      <div className="review-footer">
+        <span className="review-hint">
+          {current.timeout_secs > 0 &&
+            t("review.timeout", { seconds: current.timeout_secs })}
+          {queue.length > 1 &&
+            ` · ${t("review.more", { count: queue.length - 1 })}`}
+        </span>
+        <button className="review-discard" onClick={() => resolve(false)}>
+          {t("review.discard")}
+        </button>
+        <button className="review-confirm" onClick={() => resolve(true)}>
+          {t("review.confirm")}
+        </button>
+      </div>
+    </div>
+  );
+};
+
+export default TranscriptReview;
@@ -0,0 +1,10 @@
+import React from "react";
+import ReactDOM from "react-dom/client";
+import TranscriptReview from "./TranscriptReview";
+import "@/i18n";
+
+ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
+  <React.StrictMode>
+    <TranscriptReview />
+  </React.StrictMode>,
+);
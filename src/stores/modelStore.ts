@@ -2,6 +2,8 @@ import { create } from "zustand";
 import { subscribeWithSelector } from "zustand/middleware";
 import { listen } from "@tauri-apps/api/event";
 import { commands, type ModelInfo } from "@/bindings";
+import { formatError } from "@/lib/utils/errors";
+import { switchModel } from "@/lib/utils/modelSwitch";
 
 interface DownloadProgress {
   model_id: string;
@@ -118,7 +120,9 @@ export const useModelStore = create<ModelStore>()(
         if (result.status === "ok") {
           set({ models: result.data, error: null });
         } else {
-          set({ error: `Failed to load models: ${result.error}` });
+          set({
+            error: `Failed to load models: ${formatError(result.error)}`,
+          });
         }
       } catch (err) {
         set({ error: `Failed to load models: ${err}` });
@@ -156,12 +160,17 @@ export const useModelStore = create<ModelStore>()(
     selectModel: async (modelId) => {
       try {
         set({ error: null });
-        const result = await commands.setActiveModel(modelId);
+        const result = await switchModel(modelId);
         if (result.status === "ok") {
+          if (!result.data) {
+            return false;
+          }
           set({ currentModel: modelId, isFirstRun: false, hasAnyModels: true });
           return true;
         } else {
-          set({ error: `Failed to switch to model: ${result.error}` });
+          set({
+            error: `Failed to switch to model: ${formatError(result.error)}`,
+          });
           return false;
         }
       } catch (err) {
@@ -179,7 +188,9 @@ export const useModelStore = create<ModelStore>()(
         if (result.status === "ok") {
           return true;
         } else {
-          set({ error: `Failed to download model: ${result.error}` });
+          set({
+            error: `Failed to download model: ${formatError(result.error)}`,
+          });
           removeDownloadingModel(modelId);
           return false;
         }
@@ -199,7 +210,9 @@ export const useModelStore = create<ModelStore>()(
           await loadModels();
           return true;
         } else {
-          set({ error: `Failed to delete model: ${result.error}` });
+          set({
+            error: `Failed to delete model: ${formatError(result.error)}`,
+          });
           return false;
         }
       } catch (err) {
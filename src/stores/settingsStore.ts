@@ -1,7 +1,13 @@
+import { listen } from "@tauri-apps/api/event";
 import { create } from "zustand";
 import { subscribeWithSelector } from "zustand/middleware";
-import type { AppSettings as Settings, AudioDevice } from "@/bindings";
+import type {
+  AppSettings as Settings,
+  AudioDevice,
+  CancelBehavior,
+} from "@/bindings";
 import { commands } from "@/bindings";
+import { formatError } from "@/lib/utils/errors";
 
 interface SettingsStore {
   settings: Settings | null;
@@ -82,6 +88,8 @@ const settingUpdaters: {
     commands.changeAutostartSetting(value as boolean),
   update_checks_enabled: (value) =>
     commands.changeUpdateChecksSetting(value as boolean),
+  watch_settings_file: (value) =>
+    commands.changeWatchSettingsFileSetting(value as boolean),
   push_to_talk: (value) => commands.changePttSetting(value as boolean),
   selected_microphone: (value) =>
     commands.setSelectedMicrophone(
@@ -115,6 +123,8 @@ const settingUpdaters: {
   clipboard_handling: (value) =>
     commands.changeClipboardHandlingSetting(value as string),
   history_limit: (value) => commands.updateHistoryLimit(value as number),
+  cancel_behavior: (value) =>
+    commands.setCancelBehavior(value as CancelBehavior),
   post_process_enabled: (value) =>
     commands.changePostProcessEnabledSetting(value as boolean),
   post_process_selected_prompt_id: (value) =>
@@ -123,6 +133,8 @@ const settingUpdaters: {
     commands.changeMuteWhileRecordingSetting(value as boolean),
   append_trailing_space: (value) =>
     commands.changeAppendTrailingSpaceSetting(value as boolean),
+  overlap_transcription: (value) =>
+    commands.changeOverlapTranscriptionSetting(value as boolean),
   log_level: (value) => commands.setLogLevel(value as any),
   app_language: (value) => commands.changeAppLanguageSetting(value as string),
   experimental_enabled: (value) =>
@@ -172,7 +184,10 @@ export const useSettingsStore = create<SettingsStore>()(
           };
           set({ settings: normalizedSettings, isLoading: false });
         } else {
-          console.error("Failed to load settings:", result.error);
+          console.error(
+            "Failed to load settings:",
+            formatError(result.error),
+          );
           set({ isLoading: false });
         }
       } catch (error) {
@@ -314,7 +329,7 @@ export const useSettingsStore = create<SettingsStore>()(
 
         // Check if the command executed successfully
         if (result.status === "error") {
-          throw new Error(result.error);
+          throw new Error(formatError(result.error));
         }
 
         // Check if the binding change was successful
@@ -460,7 +475,10 @@ export const useSettingsStore = create<SettingsStore>()(
           setPostProcessModelOptions(providerId, result.data);
           return result.data;
         } else {
-          console.error("Failed to fetch models:", result.error);
+          console.error(
+            "Failed to fetch models:",
+            formatError(result.error),
+          );
           return [];
         }
       } catch (error) {
@@ -487,7 +505,10 @@ export const useSettingsStore = create<SettingsStore>()(
         if (result.status === "ok") {
           set({ defaultSettings: result.data });
         } else {
-          console.error("Failed to load default settings:", result.error);
+          console.error(
+            "Failed to load default settings:",
+            formatError(result.error),
+          );
         }
       } catch (error) {
         console.error("Failed to load default settings:", error);
@@ -510,3 +531,9 @@ export const useSettingsStore = create<SettingsStore>()(
     },
   })),
 );
+
+// The backend reloads settings when the store file is edited outside the app,
+// the payload lists the keys that changed
+listen<string[]>("settings-reloaded", () => {
+  useSettingsStore.getState().refreshSettings();
+});
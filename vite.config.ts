@@ -23,6 +23,7 @@ export default defineConfig(async () => ({
       input: {
         main: resolve(__dirname, "index.html"),
         overlay: resolve(__dirname, "src/overlay/index.html"),
+        review: resolve(__dirname, "src/review/index.html"),
       },
     },
   },